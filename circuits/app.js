@@ -82,7 +82,10 @@ async function generateWithdrawalProof(
   nullifierSecret, 
   recipient, 
   externalNullifier = '1', 
-  index
+  index,
+  proofSlot = '0',
+  relayerFee = '0',
+  relayer = '0'
 ) {
   // Generate the commitment
   const commitment = await generateCommitment(amount, secret, nullifierSecret);
@@ -116,7 +119,10 @@ async function generateWithdrawalProof(
     nullifierHash: nullifierHash,
     recipient: recipient,
     amount: amount,
-    externalNullifier: externalNullifier
+    externalNullifier: externalNullifier,
+    proofSlot: proofSlot,
+    relayerFee: relayerFee,
+    relayer: relayer
   };
   
   // Save inputs to file
@@ -204,7 +210,7 @@ async function runDemo() {
   // For demonstration, we'll explain the process rather than executing it
   console.log('\nAfter generating a valid proof, you would submit:');
   console.log('1. The proof');
-  console.log('2. The public inputs (merkleRoot, nullifierHash, recipient, amount, externalNullifier)');
+  console.log('2. The public inputs (merkleRoot, nullifierHash, recipient, amount, externalNullifier, proofSlot, relayerFee, relayer)');
   console.log('3. The smart contract would verify the proof and transfer the funds');
 }
 
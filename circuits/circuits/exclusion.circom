@@ -0,0 +1,98 @@
+pragma circom 2.1.5;
+
+include "circomlib/circuits/bitify.circom";
+include "circomlib/circuits/comparators.circom";
+include "circomlib/circuits/poseidon.circom";
+
+// Exclusion set: proves a commitment is NOT among the commitments the pool's exclusion operator
+// has flagged as illicit, for pools that require it (the program's set_exclusion_required).
+//
+// The operator keeps the flagged commitments in a sorted Merkle tree: each leaf is
+// H(value, nextValue), where nextValue is the next larger flagged commitment, or 0 for the
+// largest. The first leaf is (0, smallest flagged commitment), so every commitment has a leaf
+// below it. A commitment is outside the set when some leaf has value < commitment and
+// commitment < nextValue (or nextValue is 0); the leaf's path to exclusionRoot shows the leaf is
+// real. The root is published on-chain with publish_exclusion_root, and the program appends it
+// as the last public input of every spend proof, so a proof against an older or made-up set
+// fails.
+//
+// Not a main component: spend circuits include it once per note they spend.
+
+// a < b for any two field elements. circomlib's LessThan only takes up to 252 bits, so the
+// strict 254-bit decompositions are compared in 127-bit halves.
+template FieldLessThan() {
+    signal input a;
+    signal input b;
+    signal output out;
+
+    component aBits = Num2Bits_strict();
+    component bBits = Num2Bits_strict();
+    aBits.in <== a;
+    bBits.in <== b;
+
+    component aLow = Bits2Num(127);
+    component aHigh = Bits2Num(127);
+    component bLow = Bits2Num(127);
+    component bHigh = Bits2Num(127);
+    for (var i = 0; i < 127; i++) {
+        aLow.in[i] <== aBits.out[i];
+        bLow.in[i] <== bBits.out[i];
+        aHigh.in[i] <== aBits.out[127 + i];
+        bHigh.in[i] <== bBits.out[127 + i];
+    }
+
+    component highLess = LessThan(127);
+    highLess.in[0] <== aHigh.out;
+    highLess.in[1] <== bHigh.out;
+    component highEqual = IsEqual();
+    highEqual.in[0] <== aHigh.out;
+    highEqual.in[1] <== bHigh.out;
+    component lowLess = LessThan(127);
+    lowLess.in[0] <== aLow.out;
+    lowLess.in[1] <== bLow.out;
+
+    out <== highLess.out + highEqual.out * lowLess.out;
+}
+
+template ExclusionNonMembership(levels) {
+    // The commitment being spent, computed by the including circuit
+    signal input commitment;
+    // The leaf just below the commitment, and its path in the exclusion tree
+    signal input lowValue;
+    signal input lowNextValue;
+    signal input pathElements[levels];
+    signal input pathIndices[levels];
+    // The published exclusion root
+    signal input exclusionRoot;
+
+    // --- 1. The low leaf brackets the commitment ---
+    component aboveLow = FieldLessThan();
+    aboveLow.a <== lowValue;
+    aboveLow.b <== commitment;
+    aboveLow.out === 1;
+
+    // nextValue 0 marks the largest flagged commitment: nothing above it is flagged
+    component isLast = IsZero();
+    isLast.in <== lowNextValue;
+    component belowNext = FieldLessThan();
+    belowNext.a <== commitment;
+    belowNext.b <== lowNextValue;
+    (1 - isLast.out) * (1 - belowNext.out) === 0;
+
+    // --- 2. The low leaf is in the published tree ---
+    component leafHasher = Poseidon(2);
+    leafHasher.inputs[0] <== lowValue;
+    leafHasher.inputs[1] <== lowNextValue;
+
+    signal currentHashes[levels + 1];
+    currentHashes[0] <== leafHasher.out;
+    component levelHashers[levels];
+    for (var i = 0; i < levels; i++) {
+        pathIndices[i] * (pathIndices[i] - 1) === 0;
+        levelHashers[i] = Poseidon(2);
+        levelHashers[i].inputs[0] <== (1 - pathIndices[i]) * currentHashes[i] + pathIndices[i] * pathElements[i];
+        levelHashers[i].inputs[1] <== pathIndices[i] * currentHashes[i] + (1 - pathIndices[i]) * pathElements[i];
+        currentHashes[i + 1] <== levelHashers[i].out;
+    }
+    exclusionRoot === currentHashes[levels];
+}
@@ -0,0 +1,65 @@
+pragma circom 2.1.5;
+
+include "circomlib/circuits/poseidon.circom";
+
+// NFT withdrawal, for pools in NFT mode: spends a note that holds one specific NFT and sends
+// that NFT to the recipient through the program's withdraw_nft instruction. An NFT note
+// commits to the mint instead of an amount, H(mint, secret, nullifierSecret, 1); the fourth
+// input keeps its commitments apart from fungible notes' H(amount, secret, nullifierSecret),
+// so neither kind of note can be opened as the other.
+//
+// Public inputs, in the order the program expects them (NFT_PUBLIC_INPUTS):
+//   merkleRoot, nullifierHash, recipient, mint, externalNullifier, proofSlot
+template NftWithdraw(levels) {
+
+    // --- Private Inputs ---
+
+    // The note's secrets and its Merkle path, as in Withdraw
+    signal input secret;
+    signal input nullifierSecret;
+    signal input pathElements[levels];
+    signal input pathIndices[levels];
+
+    // --- Public Inputs ---
+
+    signal input merkleRoot;
+    signal input nullifierHash;
+    // The wallet receiving the NFT: the first 8 bytes (LE) of its key
+    signal input recipient;
+    // The NFT the note holds: the first 8 bytes (LE) of its mint
+    signal input mint;
+    // nft_external_nullifier: keeps NFT proofs apart from every fungible circuit's
+    signal input externalNullifier;
+    // A recent slot at proving time; the program rejects stale proofs
+    signal input proofSlot;
+
+    // --- 1. The note: commitment, nullifier and membership ---
+    component commitmentHasher = Poseidon(4);
+    commitmentHasher.inputs[0] <== mint;
+    commitmentHasher.inputs[1] <== secret;
+    commitmentHasher.inputs[2] <== nullifierSecret;
+    commitmentHasher.inputs[3] <== 1; // NFT note
+
+    component nullifierHasher = Poseidon(2);
+    nullifierHasher.inputs[0] <== nullifierSecret;
+    nullifierHasher.inputs[1] <== 1; // Same domain separator as Withdraw
+    nullifierHash === nullifierHasher.out;
+
+    signal currentHashes[levels + 1];
+    currentHashes[0] <== commitmentHasher.out;
+    component merkleLevelHashers[levels];
+    for (var i = 0; i < levels; i++) {
+        pathIndices[i] * (pathIndices[i] - 1) === 0;
+        merkleLevelHashers[i] = Poseidon(2);
+        merkleLevelHashers[i].inputs[0] <== (1 - pathIndices[i]) * currentHashes[i] + pathIndices[i] * pathElements[i];
+        merkleLevelHashers[i].inputs[1] <== pathIndices[i] * currentHashes[i] + (1 - pathIndices[i]) * pathElements[i];
+        currentHashes[i + 1] <== merkleLevelHashers[i].out;
+    }
+    merkleRoot === currentHashes[levels];
+
+    // recipient, externalNullifier and proofSlot are bound by being public, as in Split. The
+    // mint is bound twice: by the commitment, and by the program checking it against the NFT
+    // it sends.
+}
+
+component main {public [merkleRoot, nullifierHash, recipient, mint, externalNullifier, proofSlot]} = NftWithdraw(20);
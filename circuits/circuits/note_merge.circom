@@ -0,0 +1,83 @@
+pragma circom 2.1.5;
+
+include "circomlib/circuits/comparators.circom";
+include "circomlib/circuits/poseidon.circom";
+
+// Note merge: spends up to `inputs` notes of the same pool and commits their total to one new
+// note, for the program's merge_notes instruction. The reverse of NoteSplit; no tokens move.
+//
+// Public inputs, in the order the program expects them (NOTE_TRANSFER_PUBLIC_INPUTS):
+//   merkleRoot, nullifierHashes[inputs], outputCommitment, externalNullifier, proofSlot
+// Unused inputs have nullifierHash 0 and amount 0; their Merkle paths are not checked.
+template NoteMerge(levels, inputs) {
+
+    // --- Private Inputs ---
+
+    // Each input note's secrets, amount and Merkle path, as in Withdraw
+    signal input amounts[inputs];
+    signal input secrets[inputs];
+    signal input nullifierSecrets[inputs];
+    signal input pathElements[inputs][levels];
+    signal input pathIndices[inputs][levels];
+    // The output note's secrets, chosen by the user like a deposit's
+    signal input outSecret;
+    signal input outNullifierSecret;
+
+    // --- Public Inputs ---
+
+    signal input merkleRoot;
+    signal input nullifierHashes[inputs];
+    signal input outputCommitment;
+    // note_merge_external_nullifier: keeps these proofs apart from every other circuit's
+    signal input externalNullifier;
+    // A recent slot at proving time; the program rejects stale proofs
+    signal input proofSlot;
+
+    // --- 1. Input notes: commitment, nullifier and membership, as in Withdraw ---
+    component commitmentHashers[inputs];
+    component nullifierHashers[inputs];
+    component unused[inputs];
+    component merkleLevelHashers[inputs][levels];
+    signal currentHashes[inputs][levels + 1];
+    var total = 0;
+    for (var n = 0; n < inputs; n++) {
+        commitmentHashers[n] = Poseidon(3);
+        commitmentHashers[n].inputs[0] <== amounts[n];
+        commitmentHashers[n].inputs[1] <== secrets[n];
+        commitmentHashers[n].inputs[2] <== nullifierSecrets[n];
+
+        // An unused slot (nullifierHash 0) must carry no amount
+        unused[n] = IsZero();
+        unused[n].in <== nullifierHashes[n];
+        unused[n].out * amounts[n] === 0;
+
+        nullifierHashers[n] = Poseidon(2);
+        nullifierHashers[n].inputs[0] <== nullifierSecrets[n];
+        nullifierHashers[n].inputs[1] <== 1; // Same domain separator as Withdraw, so a note has one nullifier
+        (1 - unused[n].out) * (nullifierHashes[n] - nullifierHashers[n].out) === 0;
+
+        currentHashes[n][0] <== commitmentHashers[n].out;
+        for (var i = 0; i < levels; i++) {
+            pathIndices[n][i] * (pathIndices[n][i] - 1) === 0;
+            merkleLevelHashers[n][i] = Poseidon(2);
+            merkleLevelHashers[n][i].inputs[0] <== (1 - pathIndices[n][i]) * currentHashes[n][i] + pathIndices[n][i] * pathElements[n][i];
+            merkleLevelHashers[n][i].inputs[1] <== pathIndices[n][i] * currentHashes[n][i] + (1 - pathIndices[n][i]) * pathElements[n][i];
+            currentHashes[n][i + 1] <== merkleLevelHashers[n][i].out;
+        }
+        (1 - unused[n].out) * (merkleRoot - currentHashes[n][levels]) === 0;
+        total += amounts[n];
+    }
+
+    // --- 2. Output note: H(total, outSecret, outNullifierSecret), like any deposit ---
+    component outputHasher = Poseidon(3);
+    outputHasher.inputs[0] <== total;
+    outputHasher.inputs[1] <== outSecret;
+    outputHasher.inputs[2] <== outNullifierSecret;
+    outputCommitment === outputHasher.out;
+
+    // externalNullifier and proofSlot are bound by being public: the proof only verifies for the
+    // values the program checks them against.
+}
+
+// MAX_INPUT_NOTES in the program
+component main {public [merkleRoot, nullifierHashes, outputCommitment, externalNullifier, proofSlot]} = NoteMerge(20, 4);
@@ -0,0 +1,92 @@
+pragma circom 2.1.5;
+
+include "circomlib/circuits/bitify.circom";
+include "circomlib/circuits/comparators.circom";
+include "circomlib/circuits/poseidon.circom";
+
+// Note split: spends one note and commits its amount to up to `outputs` new notes in the same
+// pool, for the program's split_note instruction. No tokens move, so the only thing the proof
+// has to show beyond the spend is that the output notes hold exactly the input's amount.
+//
+// Public inputs, in the order the program expects them (NOTE_TRANSFER_PUBLIC_INPUTS):
+//   merkleRoot, nullifierHash, outputCommitments[outputs], externalNullifier, proofSlot
+// Unused outputs have amount 0 and commitment 0.
+template NoteSplit(levels, outputs) {
+
+    // --- Private Inputs ---
+
+    // The input note's secrets, amount and Merkle path, as in Withdraw
+    signal input amount;
+    signal input secret;
+    signal input nullifierSecret;
+    signal input pathElements[levels];
+    signal input pathIndices[levels];
+    // Each output note's amount and secrets, chosen by the user like a deposit's
+    signal input outAmounts[outputs];
+    signal input outSecrets[outputs];
+    signal input outNullifierSecrets[outputs];
+
+    // --- Public Inputs ---
+
+    signal input merkleRoot;
+    signal input nullifierHash;
+    signal input outputCommitments[outputs];
+    // note_split_external_nullifier: keeps these proofs apart from every other circuit's
+    signal input externalNullifier;
+    // A recent slot at proving time; the program rejects stale proofs
+    signal input proofSlot;
+
+    // --- 1. The input note: commitment, nullifier and membership, as in Withdraw ---
+    component commitmentHasher = Poseidon(3);
+    commitmentHasher.inputs[0] <== amount;
+    commitmentHasher.inputs[1] <== secret;
+    commitmentHasher.inputs[2] <== nullifierSecret;
+
+    component nullifierHasher = Poseidon(2);
+    nullifierHasher.inputs[0] <== nullifierSecret;
+    nullifierHasher.inputs[1] <== 1; // Same domain separator as Withdraw, so a note has one nullifier
+    nullifierHash === nullifierHasher.out;
+
+    signal currentHashes[levels + 1];
+    currentHashes[0] <== commitmentHasher.out;
+    component merkleLevelHashers[levels];
+    for (var i = 0; i < levels; i++) {
+        pathIndices[i] * (pathIndices[i] - 1) === 0;
+        merkleLevelHashers[i] = Poseidon(2);
+        merkleLevelHashers[i].inputs[0] <== (1 - pathIndices[i]) * currentHashes[i] + pathIndices[i] * pathElements[i];
+        merkleLevelHashers[i].inputs[1] <== pathIndices[i] * currentHashes[i] + (1 - pathIndices[i]) * pathElements[i];
+        currentHashes[i + 1] <== merkleLevelHashers[i].out;
+    }
+    merkleRoot === currentHashes[levels];
+
+    // --- 2. Output notes: H(amount, secret, nullifierSecret) each, or 0 when unused ---
+    // Each output amount fits in 64 bits, as in Withdraw, so the outputs can't wrap around the
+    // field and hold more than the input.
+    component outputHashers[outputs];
+    component outAmountBits[outputs];
+    component unused[outputs];
+    var total = 0;
+    for (var i = 0; i < outputs; i++) {
+        outAmountBits[i] = Num2Bits(64);
+        outAmountBits[i].in <== outAmounts[i];
+        outputHashers[i] = Poseidon(3);
+        outputHashers[i].inputs[0] <== outAmounts[i];
+        outputHashers[i].inputs[1] <== outSecrets[i];
+        outputHashers[i].inputs[2] <== outNullifierSecrets[i];
+        // An unused slot (commitment 0) must carry no amount
+        unused[i] = IsZero();
+        unused[i].in <== outputCommitments[i];
+        unused[i].out * outAmounts[i] === 0;
+        (1 - unused[i].out) * (outputCommitments[i] - outputHashers[i].out) === 0;
+        total += outAmounts[i];
+    }
+
+    // --- 3. The outputs hold exactly the input ---
+    amount === total;
+
+    // externalNullifier and proofSlot are bound by being public: the proof only verifies for the
+    // values the program checks them against.
+}
+
+// MAX_NOTE_SPLIT_OUTPUTS in the program
+component main {public [merkleRoot, nullifierHash, outputCommitments, externalNullifier, proofSlot]} = NoteSplit(20, 4);
@@ -0,0 +1,85 @@
+pragma circom 2.1.5;
+
+include "circomlib/circuits/poseidon.circom";
+include "circomlib/circuits/bitify.circom";
+
+// Split withdrawal: spends one note and pays it out to up to `recipients` recipients, each a
+// fixed amount, for the program's withdraw_split instruction. Every recipient and amount is a
+// public input, so the proof is only valid for exactly that set of payouts.
+//
+// Public inputs, in the order the program expects them (SPLIT_PUBLIC_INPUTS):
+//   merkleRoot, nullifierHash, amount, externalNullifier, proofSlot, relayerFee, relayer,
+//   then payouts[i] = (recipient, amount) for each i; unused payouts are (0, 0)
+template Split(levels, recipients) {
+
+    // --- Private Inputs ---
+
+    // The note's secrets and its Merkle path, as in Withdraw
+    signal input secret;
+    signal input nullifierSecret;
+    signal input pathElements[levels];
+    signal input pathIndices[levels];
+
+    // --- Public Inputs ---
+
+    signal input merkleRoot;
+    signal input nullifierHash;
+    // The note's amount; the payouts add up to it
+    signal input amount;
+    // split_external_nullifier: keeps split proofs apart from withdraw and swap proofs
+    signal input externalNullifier;
+    // A recent slot at proving time; the program rejects stale proofs
+    signal input proofSlot;
+    // What the payouts pay the relayer, and the relayer (first 8 bytes, LE, of its key; 0 for
+    // none). The program only lets that relayer submit the proof.
+    signal input relayerFee;
+    signal input relayer;
+    // (recipient, amount) per payout; the recipient as the first 8 bytes (LE) of its key
+    signal input payouts[recipients][2];
+
+    // --- 1. The note: commitment, nullifier and membership, as in Withdraw ---
+    component commitmentHasher = Poseidon(3);
+    commitmentHasher.inputs[0] <== amount;
+    commitmentHasher.inputs[1] <== secret;
+    commitmentHasher.inputs[2] <== nullifierSecret;
+
+    component nullifierHasher = Poseidon(2);
+    nullifierHasher.inputs[0] <== nullifierSecret;
+    nullifierHasher.inputs[1] <== 1; // Same domain separator as Withdraw, so a note has one nullifier
+    nullifierHash === nullifierHasher.out;
+
+    signal currentHashes[levels + 1];
+    currentHashes[0] <== commitmentHasher.out;
+    component merkleLevelHashers[levels];
+    for (var i = 0; i < levels; i++) {
+        pathIndices[i] * (pathIndices[i] - 1) === 0;
+        merkleLevelHashers[i] = Poseidon(2);
+        merkleLevelHashers[i].inputs[0] <== (1 - pathIndices[i]) * currentHashes[i] + pathIndices[i] * pathElements[i];
+        merkleLevelHashers[i].inputs[1] <== pathIndices[i] * currentHashes[i] + (1 - pathIndices[i]) * pathElements[i];
+        currentHashes[i + 1] <== merkleLevelHashers[i].out;
+    }
+    merkleRoot === currentHashes[levels];
+
+    // --- 2. The payouts spend exactly the note ---
+    // Every amount fits in 64 bits, as in Withdraw, so the sum can't wrap around the field and
+    // pay out more than the note holds.
+    component amountBits = Num2Bits(64);
+    amountBits.in <== amount;
+    component relayerFeeBits = Num2Bits(64);
+    relayerFeeBits.in <== relayerFee;
+    component payoutBits[recipients];
+    var total = 0;
+    for (var i = 0; i < recipients; i++) {
+        payoutBits[i] = Num2Bits(64);
+        payoutBits[i].in <== payouts[i][1];
+        total += payouts[i][1];
+    }
+    amount === total;
+
+    // The recipients, externalNullifier, proofSlot and the relayer are bound by being public:
+    // the proof only verifies for the values the program checks them against.
+}
+
+// MAX_SPLIT_RECIPIENTS in the program
+component main {public [merkleRoot, nullifierHash, amount, externalNullifier, proofSlot, relayerFee, relayer, payouts]} =
+    Split(20, 4);
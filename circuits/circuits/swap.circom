@@ -0,0 +1,82 @@
+pragma circom 2.1.5;
+
+include "circomlib/circuits/poseidon.circom";
+include "circomlib/circuits/bitify.circom";
+
+// Shielded swap: spends a note in one pool and commits to a new note for another pool, which
+// the program's shielded_swap instruction deposits there after swapping the tokens. The output
+// note is an ordinary note of the target pool and is later withdrawn with its Withdraw circuit.
+//
+// Public inputs, in the order the program expects them (SWAP_PUBLIC_INPUTS):
+//   merkleRoot, nullifierHash, targetPool, amountIn, externalNullifier, proofSlot, amountOut,
+//   outputCommitment
+template Swap(levels) {
+
+    // --- Private Inputs ---
+
+    // The input note's secrets and its Merkle path, as in Withdraw
+    signal input secret;
+    signal input nullifierSecret;
+    signal input pathElements[levels];
+    signal input pathIndices[levels];
+    // The output note's secrets, chosen by the user like a deposit's
+    signal input outSecret;
+    signal input outNullifierSecret;
+
+    // --- Public Inputs ---
+
+    signal input merkleRoot;
+    signal input nullifierHash;
+    // The pool the output note goes into: the first 8 bytes (LE) of its program ID
+    signal input targetPool;
+    // The input note's full amount, in this pool's mint
+    signal input amountIn;
+    // swap_external_nullifier: keeps swap proofs apart from withdraw proofs
+    signal input externalNullifier;
+    // A recent slot at proving time; the program rejects stale proofs
+    signal input proofSlot;
+    // The output note's amount, in the target pool's mint; the least the swap may return
+    signal input amountOut;
+    signal input outputCommitment;
+
+    // --- 0. Both amounts fit in 64 bits, as in Withdraw ---
+    component amountInBits = Num2Bits(64);
+    amountInBits.in <== amountIn;
+    component amountOutBits = Num2Bits(64);
+    amountOutBits.in <== amountOut;
+
+    // --- 1. Input note: commitment, nullifier and membership, as in Withdraw ---
+    component commitmentHasher = Poseidon(3);
+    commitmentHasher.inputs[0] <== amountIn;
+    commitmentHasher.inputs[1] <== secret;
+    commitmentHasher.inputs[2] <== nullifierSecret;
+
+    component nullifierHasher = Poseidon(2);
+    nullifierHasher.inputs[0] <== nullifierSecret;
+    nullifierHasher.inputs[1] <== 1; // Same domain separator as Withdraw, so a note has one nullifier
+    nullifierHash === nullifierHasher.out;
+
+    signal currentHashes[levels + 1];
+    currentHashes[0] <== commitmentHasher.out;
+    component merkleLevelHashers[levels];
+    for (var i = 0; i < levels; i++) {
+        pathIndices[i] * (pathIndices[i] - 1) === 0;
+        merkleLevelHashers[i] = Poseidon(2);
+        merkleLevelHashers[i].inputs[0] <== (1 - pathIndices[i]) * currentHashes[i] + pathIndices[i] * pathElements[i];
+        merkleLevelHashers[i].inputs[1] <== pathIndices[i] * currentHashes[i] + (1 - pathIndices[i]) * pathElements[i];
+        currentHashes[i + 1] <== merkleLevelHashers[i].out;
+    }
+    merkleRoot === currentHashes[levels];
+
+    // --- 2. Output note: H(amountOut, outSecret, outNullifierSecret), like any deposit ---
+    component outputHasher = Poseidon(3);
+    outputHasher.inputs[0] <== amountOut;
+    outputHasher.inputs[1] <== outSecret;
+    outputHasher.inputs[2] <== outNullifierSecret;
+    outputCommitment === outputHasher.out;
+
+    // targetPool, externalNullifier and proofSlot are bound by being public: the proof only
+    // verifies for the values the program checks them against.
+}
+
+component main {public [merkleRoot, nullifierHash, targetPool, amountIn, externalNullifier, proofSlot, amountOut, outputCommitment]} = Swap(20);
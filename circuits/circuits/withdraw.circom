@@ -3,6 +3,7 @@ pragma circom 2.1.5;
 // Import Poseidon hash function from circomlib
 // Ensure circomlib is installed and accessible in your include path (e.g., node_modules)
 include "circomlib/circuits/poseidon.circom";
+include "circomlib/circuits/bitify.circom";
 
 // Template for the withdrawal circuit
 // 'levels' is the depth of the Merkle tree
@@ -37,6 +38,21 @@ template Withdraw(levels) {
     // An external nullifier, e.g., address of the contract or a domain-specific value, 
     // to prevent replay attacks across different contexts.
     signal input externalNullifier;
+    // A recent slot at proving time; the program rejects stale proofs
+    signal input proofSlot;
+    // The fee the relayer takes from the amount, and the relayer (first 8 bytes, LE, of its key;
+    // 0 for none). The program only lets that relayer submit the proof, for exactly that fee.
+    signal input relayerFee;
+    signal input relayer;
+
+    // --- 0. Range-check the amounts ---
+    // Both are u64s on-chain. In the field, an amount of 2^64 or more (or a "negative" one, near
+    // the modulus) would hash into a note worth more than was ever deposited, so each must fit
+    // in 64 bits.
+    component amountBits = Num2Bits(64);
+    amountBits.in <== amount;
+    component relayerFeeBits = Num2Bits(64);
+    relayerFeeBits.in <== relayerFee;
 
     // --- 1. Reconstruct the Commitment --- 
     // The commitment is calculated as H(amount, secret, nullifierSecret)
@@ -92,7 +108,8 @@ template Withdraw(levels) {
     merkleRoot === currentHashes[levels];
 
     // --- 4. Public Input Usage (Implicit Constraints) --- 
-    // The public inputs `recipient`, `amount`, and `externalNullifier` are part of the statement being proven.
+    // The public inputs `recipient`, `amount`, `externalNullifier`, `proofSlot`, `relayerFee` and `relayer` are part of
+    // the statement being proven.
     // `amount` is used in commitment calculation.
     // `recipient` defines the withdrawal destination.
     // `externalNullifier` scopes the proof (e.g., to a specific contract or action type).
@@ -103,4 +120,5 @@ template Withdraw(levels) {
 
 // Main component declaration with a Merkle tree of depth 20
 // Public inputs are declared here - these are the values that will be known to both prover and verifier
-component main {public [merkleRoot, nullifierHash, recipient, amount, externalNullifier]} = Withdraw(20); 
\ No newline at end of file
+component main {public [merkleRoot, nullifierHash, recipient, amount, externalNullifier, proofSlot, relayerFee, relayer]} =
+    Withdraw(20); 
\ No newline at end of file
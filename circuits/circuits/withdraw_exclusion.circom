@@ -0,0 +1,91 @@
+pragma circom 2.1.5;
+
+include "circomlib/circuits/poseidon.circom";
+include "circomlib/circuits/bitify.circom";
+include "./exclusion.circom";
+
+// Withdraw for pools that require exclusion proofs: the same statement as Withdraw, plus that
+// the note's commitment isn't in the exclusion set (see exclusion.circom). Its verifying key is
+// registered under a circuit version of its own before the pool turns exclusion proofs on.
+//
+// Public inputs, in the order the program expects them (withdraw's layout for one note, then
+// the exclusion root the program appends):
+//   merkleRoot, nullifierHash, recipient, amount, externalNullifier, proofSlot, relayerFee,
+//   relayer, exclusionRoot
+template WithdrawExclusion(levels, exclusionLevels) {
+
+    // --- Private Inputs ---
+
+    // The note's secrets and its Merkle path, as in Withdraw
+    signal input secret;
+    signal input nullifierSecret;
+    signal input pathElements[levels];
+    signal input pathIndices[levels];
+    // The exclusion tree leaf below the note's commitment, and its path
+    signal input exclusionLowValue;
+    signal input exclusionLowNextValue;
+    signal input exclusionPathElements[exclusionLevels];
+    signal input exclusionPathIndices[exclusionLevels];
+
+    // --- Public Inputs ---
+
+    signal input merkleRoot;
+    signal input nullifierHash;
+    signal input recipient;
+    signal input amount;
+    signal input externalNullifier;
+    // A recent slot at proving time; the program rejects stale proofs and picks the exclusion
+    // root by it
+    signal input proofSlot;
+    // The relayer fee and the relayer allowed to submit the proof (0 for none), as in Withdraw
+    signal input relayerFee;
+    signal input relayer;
+    signal input exclusionRoot;
+
+    // --- 0. Both amounts fit in 64 bits, as in Withdraw ---
+    component amountBits = Num2Bits(64);
+    amountBits.in <== amount;
+    component relayerFeeBits = Num2Bits(64);
+    relayerFeeBits.in <== relayerFee;
+
+    // --- 1. The note: commitment, nullifier and membership, as in Withdraw ---
+    component commitmentHasher = Poseidon(3);
+    commitmentHasher.inputs[0] <== amount;
+    commitmentHasher.inputs[1] <== secret;
+    commitmentHasher.inputs[2] <== nullifierSecret;
+
+    component nullifierHasher = Poseidon(2);
+    nullifierHasher.inputs[0] <== nullifierSecret;
+    nullifierHasher.inputs[1] <== 1; // Same domain separator as Withdraw
+    nullifierHash === nullifierHasher.out;
+
+    signal currentHashes[levels + 1];
+    currentHashes[0] <== commitmentHasher.out;
+    component merkleLevelHashers[levels];
+    for (var i = 0; i < levels; i++) {
+        pathIndices[i] * (pathIndices[i] - 1) === 0;
+        merkleLevelHashers[i] = Poseidon(2);
+        merkleLevelHashers[i].inputs[0] <== (1 - pathIndices[i]) * currentHashes[i] + pathIndices[i] * pathElements[i];
+        merkleLevelHashers[i].inputs[1] <== pathIndices[i] * currentHashes[i] + (1 - pathIndices[i]) * pathElements[i];
+        currentHashes[i + 1] <== merkleLevelHashers[i].out;
+    }
+    merkleRoot === currentHashes[levels];
+
+    // --- 2. The note isn't flagged ---
+    component exclusion = ExclusionNonMembership(exclusionLevels);
+    exclusion.commitment <== commitmentHasher.out;
+    exclusion.lowValue <== exclusionLowValue;
+    exclusion.lowNextValue <== exclusionLowNextValue;
+    for (var i = 0; i < exclusionLevels; i++) {
+        exclusion.pathElements[i] <== exclusionPathElements[i];
+        exclusion.pathIndices[i] <== exclusionPathIndices[i];
+    }
+    exclusion.exclusionRoot <== exclusionRoot;
+
+    // recipient, externalNullifier, proofSlot and the relayer are bound by being public, as in
+    // Split.
+}
+
+component main {
+    public [merkleRoot, nullifierHash, recipient, amount, externalNullifier, proofSlot, relayerFee, relayer, exclusionRoot]
+} = WithdrawExclusion(20, 20);
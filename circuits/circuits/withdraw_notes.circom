@@ -0,0 +1,110 @@
+pragma circom 2.1.5;
+
+include "circomlib/circuits/bitify.circom";
+include "circomlib/circuits/poseidon.circom";
+
+// Multi-note withdrawal: spends `inputs` notes in one proof, pays `amount` out of them and
+// commits the rest to a change note, for withdraw with several input notes and a
+// change_commitment. The program takes exactly one nullifier per spent note, so each note
+// count is an instantiation of its own, registered under its own circuit version.
+//
+// Public inputs, in the order the program expects them (withdraw's layout, then the other
+// notes' nullifiers and the change commitment):
+//   merkleRoot, nullifierHash, recipient, amount, externalNullifier, proofSlot, relayerFee,
+//   relayer, nullifierHashes[inputs - 1], changeCommitment
+template WithdrawNotes(levels, inputs) {
+
+    // --- Private Inputs ---
+
+    // Each input note's amount, secrets and Merkle path, as in Withdraw
+    signal input amounts[inputs];
+    signal input secrets[inputs];
+    signal input nullifierSecrets[inputs];
+    signal input pathElements[inputs][levels];
+    signal input pathIndices[inputs][levels];
+    // The change note's amount and secrets, chosen by the user like a deposit's
+    signal input changeAmount;
+    signal input changeSecret;
+    signal input changeNullifierSecret;
+
+    // --- Public Inputs ---
+
+    signal input merkleRoot;
+    // The first note's nullifier hash; the others follow the fixed inputs
+    signal input nullifierHash;
+    signal input recipient;
+    signal input amount;
+    signal input externalNullifier;
+    // A recent slot at proving time; the program rejects stale proofs
+    signal input proofSlot;
+    // The relayer fee and the relayer allowed to submit the proof (0 for none), as in Withdraw
+    signal input relayerFee;
+    signal input relayer;
+    signal input nullifierHashes[inputs - 1];
+    signal input changeCommitment;
+
+    // --- 0. Every amount fits in 64 bits, as in Withdraw ---
+    // With at most a few notes, the sums below stay far under the field modulus, so they can't
+    // wrap around and pay out or keep more than the notes hold.
+    component amountBits = Num2Bits(64);
+    amountBits.in <== amount;
+    component relayerFeeBits = Num2Bits(64);
+    relayerFeeBits.in <== relayerFee;
+    component changeAmountBits = Num2Bits(64);
+    changeAmountBits.in <== changeAmount;
+
+    // --- 1. Input notes: commitment, nullifier and membership, as in Withdraw ---
+    component inputAmountBits[inputs];
+    component commitmentHashers[inputs];
+    component nullifierHashers[inputs];
+    component merkleLevelHashers[inputs][levels];
+    signal currentHashes[inputs][levels + 1];
+    var total = 0;
+    for (var n = 0; n < inputs; n++) {
+        inputAmountBits[n] = Num2Bits(64);
+        inputAmountBits[n].in <== amounts[n];
+
+        commitmentHashers[n] = Poseidon(3);
+        commitmentHashers[n].inputs[0] <== amounts[n];
+        commitmentHashers[n].inputs[1] <== secrets[n];
+        commitmentHashers[n].inputs[2] <== nullifierSecrets[n];
+
+        nullifierHashers[n] = Poseidon(2);
+        nullifierHashers[n].inputs[0] <== nullifierSecrets[n];
+        nullifierHashers[n].inputs[1] <== 1; // Same domain separator as Withdraw, so a note has one nullifier
+        if (n == 0) {
+            nullifierHash === nullifierHashers[n].out;
+        } else {
+            nullifierHashes[n - 1] === nullifierHashers[n].out;
+        }
+
+        currentHashes[n][0] <== commitmentHashers[n].out;
+        for (var i = 0; i < levels; i++) {
+            pathIndices[n][i] * (pathIndices[n][i] - 1) === 0;
+            merkleLevelHashers[n][i] = Poseidon(2);
+            merkleLevelHashers[n][i].inputs[0] <== (1 - pathIndices[n][i]) * currentHashes[n][i] + pathIndices[n][i] * pathElements[n][i];
+            merkleLevelHashers[n][i].inputs[1] <== pathIndices[n][i] * currentHashes[n][i] + (1 - pathIndices[n][i]) * pathElements[n][i];
+            currentHashes[n][i + 1] <== merkleLevelHashers[n][i].out;
+        }
+        merkleRoot === currentHashes[n][levels];
+        total += amounts[n];
+    }
+
+    // --- 2. Change note: H(changeAmount, changeSecret, changeNullifierSecret), like any deposit ---
+    component changeHasher = Poseidon(3);
+    changeHasher.inputs[0] <== changeAmount;
+    changeHasher.inputs[1] <== changeSecret;
+    changeHasher.inputs[2] <== changeNullifierSecret;
+    changeCommitment === changeHasher.out;
+
+    // --- 3. The payout and the change hold exactly the notes ---
+    total === amount + changeAmount;
+
+    // recipient, externalNullifier, proofSlot and the relayer are bound by being public, as in
+    // Split.
+}
+
+// MAX_INPUT_NOTES in the program
+component main {
+    public [merkleRoot, nullifierHash, recipient, amount, externalNullifier, proofSlot, relayerFee, relayer, nullifierHashes, changeCommitment]
+} = WithdrawNotes(20, 4);
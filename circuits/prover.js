@@ -0,0 +1,108 @@
+// HTTP proving service over the compiled withdraw circuit, for the localnet stack and for
+// clients that can't run snarkjs themselves.
+//
+//   POST /prove  { "input": { ...circuit inputs, as in inputs/main_input.json } }
+//     -> { proof, publicSignals, a, b, c }
+//   GET /healthz  liveness; GET /readyz  503 until the circuit's wasm and proving key are readable
+//
+// a, b and c are hex in the program's on-chain layout (big-endian coordinates, G2 as
+// x.c1 || x.c0 || y.c1 || y.c0), ready for withdraw's a_proof, b_proof and c_proof.
+// Expects `yarn setup` to have run.
+const http = require('http');
+const fs = require('fs');
+const snarkjs = require('snarkjs');
+
+const PORT = Number(process.env.PROVER_PORT || 8080);
+const CIRCUIT_NAME = process.env.CIRCUIT_NAME || 'main';
+const WASM_PATH = process.env.CIRCUIT_WASM || `./build/binaries/${CIRCUIT_NAME}_js/${CIRCUIT_NAME}.wasm`;
+const ZKEY_PATH = process.env.CIRCUIT_ZKEY || `./build/keys/${CIRCUIT_NAME}_0001.zkey`;
+const VKEY_PATH = process.env.CIRCUIT_VKEY || `./build/keys/${CIRCUIT_NAME}_verification_key.json`;
+const MAX_BODY_BYTES = 1 << 20;
+
+const field = (value) => BigInt(value).toString(16).padStart(64, '0');
+const g1 = (point) => field(point[0]) + field(point[1]);
+const g2 = (point) => field(point[0][1]) + field(point[0][0]) + field(point[1][1]) + field(point[1][0]);
+
+function send(res, status, body) {
+  res.writeHead(status, { 'Content-Type': 'application/json' });
+  res.end(JSON.stringify(body));
+}
+
+function readBody(req) {
+  return new Promise((resolve, reject) => {
+    let body = '';
+    req.on('data', (chunk) => {
+      body += chunk;
+      if (body.length > MAX_BODY_BYTES) {
+        reject(new Error('Request body too large'));
+        req.destroy();
+      }
+    });
+    req.on('end', () => resolve(body));
+    req.on('error', reject);
+  });
+}
+
+async function prove(input) {
+  const { proof, publicSignals } = await snarkjs.groth16.fullProve(input, WASM_PATH, ZKEY_PATH);
+  return { proof, publicSignals, a: g1(proof.pi_a), b: g2(proof.pi_b), c: g1(proof.pi_c) };
+}
+
+// Artifacts the prover can't prove without, by name, with the reason each is unusable
+function missingArtifacts() {
+  const missing = {};
+  for (const [name, path] of [['wasm', WASM_PATH], ['zkey', ZKEY_PATH]]) {
+    try {
+      fs.accessSync(path, fs.constants.R_OK);
+    } catch (error) {
+      missing[name] = `${path}: ${error.code}`;
+    }
+  }
+  return missing;
+}
+
+async function handle(req, res) {
+  if (req.method === 'GET' && (req.url === '/' || req.url === '/healthz')) {
+    return send(res, 200, { status: 'ok', circuit: CIRCUIT_NAME });
+  }
+  if (req.method === 'GET' && req.url === '/readyz') {
+    const missing = missingArtifacts();
+    const ready = Object.keys(missing).length === 0;
+    return send(res, ready ? 200 : 503, { status: ready ? 'ready' : 'not ready', circuit: CIRCUIT_NAME, missing });
+  }
+  if (req.method === 'GET' && req.url === '/verification_key') {
+    return send(res, 200, JSON.parse(fs.readFileSync(VKEY_PATH, 'utf8')));
+  }
+  if (req.method !== 'POST' || req.url !== '/prove') {
+    return send(res, 404, { error: 'Not found' });
+  }
+
+  let input;
+  try {
+    ({ input } = JSON.parse(await readBody(req)));
+  } catch (error) {
+    return send(res, 400, { error: `Invalid request: ${error.message}` });
+  }
+  if (!input || typeof input !== 'object') {
+    return send(res, 400, { error: 'Expected circuit inputs in "input"' });
+  }
+  try {
+    send(res, 200, await prove(input));
+  } catch (error) {
+    // Unsatisfied constraints and malformed inputs both surface from witness generation
+    send(res, 422, { error: error.message });
+  }
+}
+
+for (const file of [WASM_PATH, ZKEY_PATH]) {
+  if (!fs.existsSync(file)) {
+    console.error(`Missing ${file}; build the circuit first (yarn setup)`);
+    process.exit(1);
+  }
+}
+
+http.createServer((req, res) => {
+  handle(req, res).catch((error) => send(res, 500, { error: error.message }));
+}).listen(PORT, () => {
+  console.log(`Prover listening on port ${PORT} (${CIRCUIT_NAME})`);
+});
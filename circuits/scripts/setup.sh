@@ -0,0 +1,117 @@
+#!/bin/bash
+# Reproducible Groth16 setup for one circuit: compiles it, fetches the Hermez powers of tau its
+# size needs, runs phase 2 ending in a public random beacon, and writes the verifying key and
+# the register_verifying_key payload to artifacts/<circuit>/, with a SHA256SUMS manifest of
+# every input and output. With the same circom, snarkjs and beacon, every run produces the
+# same bytes, so anyone can rebuild the keys and compare them against the committed manifest:
+#
+#   bash ./scripts/setup.sh withdraw           # build, and write artifacts/withdraw/
+#   bash ./scripts/setup.sh withdraw --check   # rebuild, and compare with artifacts/withdraw/SHA256SUMS
+#
+# BEACON (64 hex digits) is the beacon's value; a production setup uses one nobody could know
+# in advance, such as the hash of a future block, and announces it before running. Without it
+# the development beacon below is used. After a public ceremony (privax-ceremony, in
+# ../sol-contracts/ceremony), CEREMONY_ZKEY and CEREMONY_TRANSCRIPT name its final key and
+# transcript: they are checked against the phase 2 key built here and used instead of the beacon.
+set -euo pipefail
+. ./scripts/build_constants
+
+CIRCOM_VERSION="2.1.5"
+SNARKJS_VERSION="0.7.1"
+BEACON_ITERATIONS_EXP=10
+DEV_BEACON=$(printf 'privax development beacon' | sha256sum | cut -d' ' -f1)
+
+CIRCUIT_NAME=""
+if [ "${1:-}" ]; then
+    CIRCUIT_NAME=$1
+else
+  echo "Please provide name of the circuit"
+  exit 1
+fi
+CHECK=""
+if [ "${2:-}" = "--check" ]; then
+    CHECK=1
+fi
+BEACON=${BEACON:-$DEV_BEACON}
+if ! [[ "$BEACON" =~ ^[0-9a-fA-F]{64}$ ]]; then
+    echo "BEACON must be 64 hex digits"
+    exit 1
+fi
+
+# Other versions may compile or serialize differently, and then the hashes don't match
+if ! circom --version | grep -q " ${CIRCOM_VERSION}$"; then
+    echo "Expected circom ${CIRCOM_VERSION}, found: $(circom --version)"
+    exit 1
+fi
+FOUND_SNARKJS=$(node -p "require('./node_modules/snarkjs/package.json').version")
+if [ "$FOUND_SNARKJS" != "$SNARKJS_VERSION" ]; then
+    echo "Expected snarkjs ${SNARKJS_VERSION}, found ${FOUND_SNARKJS}"
+    exit 1
+fi
+
+ARTIFACTS=${ARTIFACTS_FOLDER_PATH}${CIRCUIT_NAME}/
+if [ "$CHECK" ] && [ ! -f "${ARTIFACTS}SHA256SUMS" ]; then
+    echo "No ${ARTIFACTS}SHA256SUMS to check against"
+    exit 1
+fi
+
+echo "Compiling circuit $CIRCUIT_NAME..."
+bash ./scripts/compile.sh ${CIRCUIT_NAME}
+R1CS=${BINARIES_FOLDER_PATH}${CIRCUIT_NAME}.r1cs
+
+# The smallest powers of tau whose domain fits the constraints plus the public signals
+SIGNALS=$(snarkjs r1cs info ${R1CS} | awk -F': ' '/# of (Constraints|Public Inputs|Outputs)/ { total += $NF } END { print total + 1 }')
+PTAU_SIZE=8
+while [ $((1 << PTAU_SIZE)) -lt "$SIGNALS" ]; do
+    PTAU_SIZE=$((PTAU_SIZE + 1))
+done
+bash ./scripts/ptau_fetch.sh ${PTAU_SIZE}
+PTAU=${PTAU_FOLDER_PATH}powers_of_tau_${PTAU_SIZE}.ptau
+
+mkdir -p ${KEYS_FOLDER_PATH} ${ARTIFACTS}
+echo "Initializing phase 2 with powers_of_tau_${PTAU_SIZE}.ptau..."
+snarkjs zkey new ${R1CS} ${PTAU} ${KEYS_FOLDER_PATH}${CIRCUIT_NAME}_0000.zkey
+if [ "${CEREMONY_ZKEY:-}" ]; then
+    # The ceremony's proofs are in its transcript, which snarkjs zkey verify doesn't read
+    echo "Verifying the ceremony's final key..."
+    ${PRIVAX_CEREMONY:-privax-ceremony} verify ${KEYS_FOLDER_PATH}${CIRCUIT_NAME}_0000.zkey ${CEREMONY_ZKEY} \
+        --transcript ${CEREMONY_TRANSCRIPT:?CEREMONY_TRANSCRIPT is required with CEREMONY_ZKEY}
+    cp ${CEREMONY_ZKEY} ${KEYS_FOLDER_PATH}${CIRCUIT_NAME}_0001.zkey
+    cp ${CEREMONY_TRANSCRIPT} ${ARTIFACTS}ceremony.json
+    PHASE2="ceremony.json"
+else
+    echo "Applying beacon ${BEACON}..."
+    snarkjs zkey beacon ${KEYS_FOLDER_PATH}${CIRCUIT_NAME}_0000.zkey ${KEYS_FOLDER_PATH}${CIRCUIT_NAME}_0001.zkey \
+        ${BEACON} ${BEACON_ITERATIONS_EXP} -n="Final beacon"
+    snarkjs zkey verify ${R1CS} ${PTAU} ${KEYS_FOLDER_PATH}${CIRCUIT_NAME}_0001.zkey
+    PHASE2="beacon ${BEACON}, 2^${BEACON_ITERATIONS_EXP} iterations"
+fi
+snarkjs zkey export verificationkey ${KEYS_FOLDER_PATH}${CIRCUIT_NAME}_0001.zkey ${KEYS_FOLDER_PATH}${CIRCUIT_NAME}_verification_key.json
+
+cp ${KEYS_FOLDER_PATH}${CIRCUIT_NAME}_verification_key.json ${ARTIFACTS}verification_key.json
+node ./scripts/vk_payload.js ${ARTIFACTS}verification_key.json ${ARTIFACTS}vk_payload.json
+cat > ${ARTIFACTS}setup.json <<JSON
+{
+  "circuit": "${CIRCUIT_NAME}",
+  "circom": "${CIRCOM_VERSION}",
+  "snarkjs": "${SNARKJS_VERSION}",
+  "ptau": "powersOfTau28_hez_final_${PTAU_SIZE}.ptau",
+  "phase2": "${PHASE2}"
+}
+JSON
+
+HASHED="${PTAU} ${R1CS} ${KEYS_FOLDER_PATH}${CIRCUIT_NAME}_0001.zkey ${ARTIFACTS}verification_key.json ${ARTIFACTS}vk_payload.json ${ARTIFACTS}setup.json"
+if [ "${CEREMONY_ZKEY:-}" ]; then
+    HASHED="${HASHED} ${ARTIFACTS}ceremony.json"
+fi
+if [ "$CHECK" ]; then
+    echo "Comparing with ${ARTIFACTS}SHA256SUMS..."
+    if ! sha256sum -c ${ARTIFACTS}SHA256SUMS; then
+        echo "The rebuilt artifacts differ from the committed ones"
+        exit 1
+    fi
+    echo "The rebuilt artifacts match the committed ones"
+else
+    sha256sum ${HASHED} > ${ARTIFACTS}SHA256SUMS
+    echo "Setup complete; commit ${ARTIFACTS} to publish the keys"
+fi
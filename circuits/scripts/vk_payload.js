@@ -0,0 +1,53 @@
+// Converts a snarkjs verification key into the arguments of the program's
+// register_verifying_key, so the key that is uploaded can be checked against the setup:
+//
+//   node scripts/vk_payload.js build/keys/withdraw_verification_key.json [vk_payload.json]
+//
+// vkData is alpha || beta || gamma || delta || IC[], in the program's on-chain layout (see
+// ../sol-contracts/app/src/proofFormat.ts, vkFromSnarkjs). Keys over a transaction's size are
+// registered with a first chunk and completed with write_verifying_key_chunk.
+const fs = require('fs');
+
+// Limits the program enforces on Groth16 keys
+const MAX_VK_LEN = 2048;
+const MIN_PUBLIC_INPUTS = 8;
+const MAX_PUBLIC_INPUTS = 13;
+const AMOUNT_RANGE_BITS = 64;
+
+const field = (value) => BigInt(value).toString(16).padStart(64, '0');
+const g1 = (point) => field(point[0]) + field(point[1]);
+const g2 = (point) => field(point[0][1]) + field(point[0][0]) + field(point[1][1]) + field(point[1][0]);
+
+function payload(vk) {
+  if (vk.protocol !== 'groth16' || vk.curve !== 'bn128') {
+    throw new Error(`expected a groth16 key over bn128, got ${vk.protocol} over ${vk.curve}`);
+  }
+  if (vk.IC.length !== vk.nPublic + 1) {
+    throw new Error(`${vk.IC.length} IC points for ${vk.nPublic} public inputs`);
+  }
+  const vkData = g1(vk.vk_alpha_1) + g2(vk.vk_beta_2) + g2(vk.vk_gamma_2) + g2(vk.vk_delta_2) + vk.IC.map(g1).join('');
+  const vkLen = vkData.length / 2;
+  if (vkLen > MAX_VK_LEN) {
+    throw new Error(`the key is ${vkLen} bytes; the program holds at most ${MAX_VK_LEN}`);
+  }
+  if (vk.nPublic < MIN_PUBLIC_INPUTS || vk.nPublic > MAX_PUBLIC_INPUTS) {
+    console.warn(`warning: ${vk.nPublic} public inputs; the program accepts ${MIN_PUBLIC_INPUTS} to ${MAX_PUBLIC_INPUTS}`);
+  }
+  return { proofSystem: 'groth16', amountRangeBits: AMOUNT_RANGE_BITS, nPublic: vk.nPublic, vkLen, vkData };
+}
+
+function main() {
+  const [input, output] = process.argv.slice(2);
+  if (!input) {
+    console.error('Usage: node scripts/vk_payload.js <verification_key.json> [output.json]');
+    process.exit(1);
+  }
+  const json = JSON.stringify(payload(JSON.parse(fs.readFileSync(input, 'utf8'))), null, 2) + '\n';
+  if (output) {
+    fs.writeFileSync(output, json);
+  } else {
+    process.stdout.write(json);
+  }
+}
+
+main();
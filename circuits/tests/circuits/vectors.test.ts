@@ -0,0 +1,64 @@
+import { expect } from "chai";
+import fs from "fs";
+import path from "path";
+import { wasm as wasm_tester } from "circom_tester";
+import { MerkleTree, generateCommitment, generateNullifierHash, poseidonHash } from "../../utils/merkleTree";
+
+// Shared with the program and the Rust SDKs; see sol-contracts/test-vectors.
+const vectors = JSON.parse(
+  fs.readFileSync(path.join(__dirname, "../../../sol-contracts/test-vectors/vectors.json"), "utf8")
+);
+
+// 32 big-endian bytes in hex, as a decimal field element
+const decimal = (hex: string) => BigInt("0x" + hex).toString();
+
+describe("Test vectors", function() {
+  this.timeout(100000); // Circuit compilation
+
+  it("matches circomlib Poseidon", async () => {
+    for (const vector of vectors.poseidon) {
+      expect(await poseidonHash(vector.inputs)).to.equal(vector.output);
+    }
+  });
+
+  it("matches note commitments and nullifier hashes", async () => {
+    for (const note of vectors.notes) {
+      const secret = "0x" + note.secret;
+      const nullifierSecret = "0x" + note.nullifier_secret;
+      expect(await generateCommitment(note.amount, secret, nullifierSecret)).to.equal(decimal(note.commitment));
+      expect(await generateNullifierHash(nullifierSecret)).to.equal(decimal(note.nullifier_hash));
+    }
+  });
+
+  it("matches the tree's empty subtrees and paths", async () => {
+    const tree = vectors.poseidon_tree;
+    const zeros = await new MerkleTree(tree.depth).zeros;
+    expect(zeros.map(zero => zero.toString())).to.deep.equal(tree.zeros.map(decimal));
+
+    for (const vector of tree.paths) {
+      let node = decimal(tree.leaves[vector.leaf_index]);
+      for (let level = 0; level < tree.depth; level++) {
+        const sibling = decimal(vector.elements[level]);
+        node = await poseidonHash(vector.indices[level] ? [sibling, node] : [node, sibling]);
+      }
+      expect(node).to.equal(decimal(tree.root));
+    }
+  });
+
+  it("accepts the SDK's witness input", async () => {
+    const circuit = await wasm_tester(path.join(__dirname, "../../circuits/main.circom"), {
+      include: path.join(__dirname, "../../node_modules"),
+    });
+    for (const withdrawal of vectors.withdrawals.filter((w: any) => w.witness_input)) {
+      const input = withdrawal.witness_input;
+      expect(input.merkleRoot).to.equal(decimal(vectors.poseidon_tree.root));
+      expect(input.recipient).to.equal(withdrawal.public_inputs[2]);
+      expect(input.externalNullifier).to.equal(withdrawal.public_inputs[4]);
+      expect(input.proofSlot).to.equal(withdrawal.public_inputs[5]);
+      expect([input.relayerFee, input.relayer]).to.deep.equal(withdrawal.public_inputs.slice(6, 8));
+
+      const witness = await circuit.calculateWitness(input);
+      await circuit.checkConstraints(witness);
+    }
+  });
+});
@@ -15,6 +15,10 @@ describe("Withdraw Circuit", function() {
   const nullifierSecret = randomField();
   const recipient = "123456789";
   const externalNullifier = "1";
+  const proofSlot = "245000000";
+  // Withdrawn without a relayer
+  const relayerFee = "0";
+  const relayer = "0";
   
   // To store calculated values
   let commitment: string;
@@ -58,7 +62,10 @@ describe("Withdraw Circuit", function() {
       nullifierHash: nullifierHash,
       recipient: recipient,
       amount: amount,
-      externalNullifier: externalNullifier
+      externalNullifier: externalNullifier,
+      proofSlot: proofSlot,
+      relayerFee: relayerFee,
+      relayer: relayer
     };
     
     const witness = await circuit.calculateWitness(input);
@@ -81,7 +88,10 @@ describe("Withdraw Circuit", function() {
       nullifierHash: nullifierHash,
       recipient: recipient,
       amount: amount,
-      externalNullifier: externalNullifier
+      externalNullifier: externalNullifier,
+      proofSlot: proofSlot,
+      relayerFee: relayerFee,
+      relayer: relayer
     };
     
     try {
@@ -93,4 +103,46 @@ describe("Withdraw Circuit", function() {
       expect(err.toString()).to.include("Error: Assert Failed");
     }
   });
+
+  // A note for `noteAmount` in a tree of its own, so only the range check can reject it
+  async function rangeInput(noteAmount: string, fee: string) {
+    const tree = new MerkleTree(20);
+    await tree._calculateZeros();
+    const leaf = await tree.insert(await generateCommitment(noteAmount, secret, nullifierSecret));
+    const path = await tree.generateProof(leaf);
+    return {
+      secret: secret,
+      nullifierSecret: nullifierSecret,
+      pathElements: path.pathElements,
+      pathIndices: path.pathIndices,
+      merkleRoot: await tree.getRoot(),
+      nullifierHash: nullifierHash,
+      recipient: recipient,
+      amount: noteAmount,
+      externalNullifier: externalNullifier,
+      proofSlot: proofSlot,
+      relayerFee: fee,
+      relayer: relayer
+    };
+  }
+
+  it("should accept amounts up to 2^64 - 1", async () => {
+    const max = (2n ** 64n - 1n).toString();
+    const witness = await circuit.calculateWitness(await rangeInput(max, max));
+    await circuit.checkConstraints(witness);
+  });
+
+  it("should reject an amount or relayer fee of 2^64", async () => {
+    const max = (2n ** 64n - 1n).toString();
+    const over = (2n ** 64n).toString();
+    for (const [noteAmount, fee] of [[over, "0"], [max, over]]) {
+      try {
+        const witness = await circuit.calculateWitness(await rangeInput(noteAmount, fee));
+        await circuit.checkConstraints(witness);
+        expect.fail("Circuit should have rejected an amount past 64 bits");
+      } catch (err: any) {
+        expect(err.toString()).to.include("Error: Assert Failed");
+      }
+    }
+  });
 }); 
\ No newline at end of file
@@ -0,0 +1,27 @@
+#!/bin/bash
+# Starts a test validator with privax_protocol and the mock verifier deployed at their program
+# ids and the Geyser plugin streaming to the relayer. The ledger starts fresh on every run; the seed service
+# recreates the pool.
+set -e
+
+PROGRAM_ID="${PROGRAM_ID:-Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS}"
+MOCK_VERIFIER_ID="${MOCK_VERIFIER_ID:-EkRE7RWwAXyZWZFGfrEPWmuSyaERNAzP25B3ea3UAQdU}"
+KEYS_DIR="${KEYS_DIR:-/state/keys}"
+
+# The admin and relayer keys are shared with the seed and relayer services
+mkdir -p "$KEYS_DIR"
+for key in admin relayer; do
+  if [ ! -f "$KEYS_DIR/$key.json" ]; then
+    solana-keygen new --silent --no-bip39-passphrase --outfile "$KEYS_DIR/$key.json"
+  fi
+done
+
+exec solana-test-validator \
+  --reset \
+  --ledger /ledger \
+  --bind-address 0.0.0.0 \
+  --rpc-port 8899 \
+  --bpf-program "$PROGRAM_ID" /opt/privax/privax_protocol.so \
+  --bpf-program "$MOCK_VERIFIER_ID" /opt/privax/privax_mock_verifier.so \
+  --geyser-plugin-config /opt/privax/geyser.json \
+  --log
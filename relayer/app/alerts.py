@@ -0,0 +1,165 @@
+"""
+Alert rules: notify operators of protocol activity worth a look as the cross-chain indexer
+records it, e.g. a large deposit or withdrawal, a relayer added or removed, the pool paused or
+unpaused, or the admin changed.
+
+ALERT_RULES_FILE points at a JSON list of rules:
+
+    [
+        {
+            "name": "large-usdc-withdrawal",
+            "events": ["WithdrawalOccurred"],
+            "token": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "min_amount": 100000000000,
+            "channels": [
+                {"type": "slack", "url": "https://hooks.slack.com/services/..."},
+                {"type": "pagerduty", "routing_key": "...", "severity": "critical"}
+            ]
+        },
+        {
+            "name": "governance",
+            "events": ["AdminChanged", "RelayerAdded", "RelayerRemoved", "PauseToggled"],
+            "channels": [{"type": "webhook", "url": "https://ops.example/privax"}]
+        }
+    ]
+
+`token`, `chain` and `min_amount` are optional filters. Amounts are in base units, so an
+amount threshold usually goes with a token. A `webhook` channel is POSTed the rule's name and
+the event as JSON, signed like job callbacks when WEBHOOK_SECRET is set. A `slack` channel is a
+Slack incoming webhook URL, and a `pagerduty` channel a PagerDuty Events API v2 routing key,
+whose incidents are deduplicated by event id.
+"""
+import json
+import logging
+import os
+
+from . import metrics
+
+logger = logging.getLogger(__name__)
+
+CHANNEL_TYPES = ("webhook", "slack", "pagerduty")
+PAGERDUTY_URL = "https://events.pagerduty.com/v2/enqueue"
+PAGERDUTY_SEVERITIES = ("critical", "error", "warning", "info")
+
+def check_rules(rules, events):
+    """
+    Reject rules that could never fire or couldn't be delivered
+
+    Args:
+        rules: The parsed rules
+        events: The event names the index records
+
+    Raises:
+        ValueError: Naming the first invalid rule and what is wrong with it
+    """
+    if not isinstance(rules, list):
+        raise ValueError("Alert rules must be a JSON list")
+    for position, rule in enumerate(rules):
+        if not rule.get("name"):
+            raise ValueError(f"Alert rule #{position} has no name")
+        name = rule["name"]
+        unknown = [event for event in rule.get("events") or [] if event not in events]
+        if not rule.get("events") or unknown:
+            raise ValueError(f"Alert rule {name} needs events from {', '.join(events)}")
+        if rule.get("min_amount") is not None and not isinstance(rule["min_amount"], int):
+            raise ValueError(f"Alert rule {name}: min_amount must be an integer in base units")
+        if not rule.get("channels"):
+            raise ValueError(f"Alert rule {name} has no channels")
+        for channel in rule["channels"]:
+            if channel.get("type") not in CHANNEL_TYPES:
+                raise ValueError(f"Alert rule {name}: channel type must be one of {', '.join(CHANNEL_TYPES)}")
+            if channel["type"] == "pagerduty":
+                if not channel.get("routing_key"):
+                    raise ValueError(f"Alert rule {name}: a pagerduty channel needs a routing_key")
+                if channel.get("severity", "critical") not in PAGERDUTY_SEVERITIES:
+                    raise ValueError(f"Alert rule {name}: severity must be one of {', '.join(PAGERDUTY_SEVERITIES)}")
+            elif not channel.get("url"):
+                raise ValueError(f"Alert rule {name}: a {channel['type']} channel needs a url")
+
+def summary(rule, event):
+    """A one-line description of what fired, for Slack and PagerDuty"""
+    details = {
+        "AdminChanged": lambda: f"admin changed from {event.get('old_admin')} to {event.get('new_admin')}",
+        "RelayerAdded": lambda: f"relayer {event.get('relayer_address')} added",
+        "RelayerRemoved": lambda: f"relayer {event.get('relayer_address')} removed",
+        "PauseToggled": lambda: f"{'paused' if event.get('paused') else 'unpaused'} by {event.get('by')}",
+        "DepositOccurred": lambda: f"deposit of {event.get('amount')} {event.get('token')}",
+        "WithdrawalOccurred": lambda: f"withdrawal of {event.get('amount')} {event.get('token')}",
+    }[event["event"]]()
+    return f"[{rule['name']}] {event['chain']} {event['network']}: {details} (tx {event['tx']}, height {event['height']})"
+
+class AlertRules:
+    """Matches indexed events against the rules and sends each match to the rule's channels"""
+
+    def __init__(self, rules, notifier, secret=None):
+        """
+        Args:
+            rules: Rules, checked with check_rules
+            notifier: CallbackNotifier the alerts are delivered with
+            secret: Key of the X-Privax-Signature HMAC on webhook channels; the notifier's by default
+        """
+        self.rules = rules
+        self.notifier = notifier
+        self.secret = secret if secret is not None else notifier.secret
+
+    @classmethod
+    def from_env(cls, notifier, events):
+        """
+        Load the rules in ALERT_RULES_FILE
+
+        Args:
+            notifier: CallbackNotifier the alerts are delivered with
+            events: The event names the index records
+
+        Returns:
+            AlertRules, or None when ALERT_RULES_FILE isn't set
+        """
+        path = os.getenv("ALERT_RULES_FILE")
+        if not path:
+            return None
+        with open(path, "r") as f:
+            rules = json.load(f)
+        check_rules(rules, events)
+        logger.info(f"Loaded {len(rules)} alert rules from {path}")
+        return cls(rules, notifier)
+
+    def matches(self, rule, event):
+        """Whether an indexed event fires a rule"""
+        if event["event"] not in rule["events"]:
+            return False
+        if rule.get("chain") and event["chain"] != rule["chain"]:
+            return False
+        if rule.get("token") and event.get("token") != rule["token"]:
+            return False
+        if rule.get("min_amount") is not None and int(event.get("amount") or 0) < rule["min_amount"]:
+            return False
+        return True
+
+    def check(self, event):
+        """Fire every rule a newly indexed event matches"""
+        for rule in self.rules:
+            if not self.matches(rule, event):
+                continue
+            logger.warning(f"Alert {summary(rule, event)}")
+            metrics.ALERTS.inc(rule=rule["name"])
+            for channel in rule["channels"]:
+                self.send(channel, rule, event)
+
+    def send(self, channel, rule, event):
+        label = f"alert {rule['name']} ({event['id']}) to {channel['type']}"
+        if channel["type"] == "webhook":
+            self.notifier.post(channel["url"], {"alert": rule["name"], "event": event}, label, self.secret)
+        elif channel["type"] == "slack":
+            self.notifier.post(channel["url"], {"text": summary(rule, event)}, label, None)
+        else:
+            self.notifier.post(PAGERDUTY_URL, {
+                "routing_key": channel["routing_key"],
+                "event_action": "trigger",
+                "dedup_key": event["id"],
+                "payload": {
+                    "summary": summary(rule, event),
+                    "source": "privax-indexer",
+                    "severity": channel.get("severity", "critical"),
+                    "custom_details": event
+                }
+            }, label, None)
@@ -0,0 +1,101 @@
+"""
+Pool analytics: daily volume and anonymity-set estimates per pool, from the indexed deposits and
+withdrawals, for frontends showing pool health.
+
+A withdrawal proof hides which leaf it spends, so in principle every deposit ever made is in its
+anonymity set. In practice each note is withdrawn once, and a note that has already been
+withdrawn is a weaker cover, so the estimate here is the notes still outstanding: deposits minus
+withdrawals. It is an upper bound on the real set, which amount and timing correlations shrink.
+
+Days are UTC, bucketed by when an entry was indexed. Entries indexed before timestamps were
+recorded fall before every day of the series: they count toward the anonymity set but not
+toward any day's volume.
+
+THIN_POOL_SIZE sets the anonymity set below which a pool is flagged as thin (20 by default).
+"""
+import os
+import time
+from datetime import datetime, timezone
+
+SECONDS_PER_DAY = 86400
+
+def thin_pool_size():
+    """
+    The anonymity set below which THIN_POOL_SIZE flags a pool as thin
+
+    Returns:
+        int: The threshold
+    """
+    return int(os.getenv("THIN_POOL_SIZE", "20"))
+
+def recipients(withdrawals):
+    """The addresses withdrawals paid, each of a split withdrawal's recipients separately"""
+    return {
+        recipient
+        for info in withdrawals
+        for recipient in (info.get("recipient") or "").split(",")
+        if recipient
+    }
+
+def pool_analytics(deposits, withdrawals, mint, days=30, now=None, thin_below=None):
+    """
+    Daily series and current health of one pool
+
+    Args:
+        deposits: Indexed deposit infos, as the relayer stores them
+        withdrawals: Indexed withdrawal infos, as the relayer stores them
+        mint: The token (mint) address of the pool
+        days: Number of days in the series, ending with today
+        now: Unix time to end the series at; the current time by default
+        thin_below: Anonymity set below which the pool is thin; THIN_POOL_SIZE by default
+
+    Returns:
+        dict: The series, oldest day first, and the pool's current anonymity set and whether
+              it is thin. A day's unique depositors and recipients count addresses, so one
+              user with several addresses counts several times.
+    """
+    now = int(time.time()) if now is None else now
+    thin_below = thin_pool_size() if thin_below is None else thin_below
+    today = now // SECONDS_PER_DAY
+    first_day = today - days + 1
+
+    buckets = {
+        day: {"deposits": [], "withdrawals": []}
+        for day in range(first_day, today + 1)
+    }
+    # Outstanding notes at the start of the series
+    outstanding = 0
+    for kind, entries, sign in (("deposits", deposits, 1), ("withdrawals", withdrawals, -1)):
+        for info in entries:
+            if info.get("token") != mint:
+                continue
+            timestamp = info.get("timestamp")
+            day = None if timestamp is None else timestamp // SECONDS_PER_DAY
+            if day is None or day < first_day:
+                outstanding += sign
+            elif day <= today:
+                buckets[day][kind].append(info)
+
+    series = []
+    for day in range(first_day, today + 1):
+        bucket = buckets[day]
+        outstanding += len(bucket["deposits"]) - len(bucket["withdrawals"])
+        series.append({
+            "date": datetime.fromtimestamp(day * SECONDS_PER_DAY, tz=timezone.utc).strftime("%Y-%m-%d"),
+            "deposit_count": len(bucket["deposits"]),
+            "deposit_volume": sum(int(info["amount"]) for info in bucket["deposits"]),
+            "withdrawal_count": len(bucket["withdrawals"]),
+            "withdrawal_volume": sum(int(info.get("amount") or 0) for info in bucket["withdrawals"]),
+            "unique_depositors": len({info.get("user") for info in bucket["deposits"] if info.get("user")}),
+            "unique_recipients": len(recipients(bucket["withdrawals"])),
+            "anonymity_set": max(outstanding, 0)
+        })
+
+    anonymity_set = series[-1]["anonymity_set"]
+    return {
+        "mint": mint,
+        "days": series,
+        "anonymity_set": anonymity_set,
+        "thin": anonymity_set < thin_below,
+        "thin_below": thin_below
+    }
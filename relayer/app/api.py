@@ -1,23 +1,34 @@
-from fastapi import FastAPI, HTTPException, Depends, BackgroundTasks
+from fastapi import FastAPI, HTTPException, Depends, Query, Request, Response, Header
+from fastapi.responses import JSONResponse, PlainTextResponse
 from pydantic import BaseModel, Field, constr
 from typing import Optional, Dict, Any, List
 import logging
 import asyncio
+import math
+import os
+import httpx
 
 from .relayer import Relayer
+from .ratelimit import RateLimiter
+from . import logs, metrics
+from .config import profile
+from .health import HealthMonitor
+from .jobs import CORRELATION_ID_HEADER
+from .indexer import ChainEventIndex, indexer_chains, CHAINS, EVENTS
+from .alerts import AlertRules
 from .blockchain.ethereum import EthereumListener
 from .blockchain.solana import SolanaListener
 
-# Set up logging
-logging.basicConfig(
-    level=logging.INFO,
-    format="%(asctime)s - %(name)s - %(levelname)s - %(message)s"
-)
+# Set up logging, for when the app is served by uvicorn directly instead of app.main
+logs.configure()
 logger = logging.getLogger(__name__)
 
 # Initialize relayer
 relayer = Relayer()
 
+# Solana and EVM events in one schema, fed by the listeners INDEXER_CHAINS turns on
+chain_events = ChainEventIndex(relayer.persistence, AlertRules.from_env(relayer.jobs.notifier, EVENTS))
+
 # Create FastAPI app
 app = FastAPI(
     title="Privax Relayer",
@@ -25,24 +36,73 @@ app = FastAPI(
     version="0.1.0"
 )
 
+# Rate limits on requests that cost the relayer SOL, per client IP or, with X-API-Key, per key
+ip_rate_limiter = RateLimiter(int(os.getenv("RATE_LIMIT_PER_MINUTE", "10")))
+api_key_rate_limiter = RateLimiter(int(os.getenv("API_KEY_RATE_LIMIT_PER_MINUTE", "120")))
+api_keys = {key.strip() for key in os.getenv("RELAYER_API_KEYS", "").split(",") if key.strip()}
+
 # Initialize background listeners
 ethereum_listener = None
 solana_listener = None
 background_tasks = set()
+chain_tip_slot = None
+health = HealthMonitor()
 
 # --- Pydantic models for API requests/responses ---
 
 class CommitmentQuery(BaseModel):
     commitment: str = Field(..., description="Hex-encoded commitment value")
 
+class WithdrawalProof(BaseModel):
+    a: str = Field(..., description="Hex-encoded Groth16 A (64 bytes, on-chain layout; zero for PLONK)")
+    b: str = Field(..., description="Hex-encoded Groth16 B (128 bytes, on-chain layout; zero for PLONK)")
+    c: str = Field(..., description="Hex-encoded Groth16 C (64 bytes, on-chain layout; zero for PLONK)")
+    public_inputs: List[int] = Field(..., description="The 12 u64 public inputs, unused slots zero")
+    merkle_root: str = Field(..., description="Hex-encoded Merkle root the proof is against")
+    extra_nullifier_hashes: List[str] = Field([], description="Hex-encoded nullifier hashes of the further notes a multi-note spend spends")
+    circuit_version: int = Field(..., ge=0, le=65535, description="Circuit the proof was generated for")
+    plonk_proof: str = Field("", description="Hex-encoded PLONK proof (768 bytes) for PLONK circuits, with a, b and c zero; empty for Groth16")
+    change_commitment: Optional[str] = Field(None, description="Hex-encoded change note commitment, for multi-note spends")
+
+class FeeQuote(BaseModel):
+    relayer: str = Field(..., description="Relayer public key the quote is signed with")
+    mint: str = Field(..., description="Token (mint) address of the pool")
+    amount: int = Field(..., description="Amount to withdraw, in base units")
+    fee: int = Field(..., description="Relayer fee taken from the amount, in base units")
+    expires_at: int = Field(..., description="Unix time after which the relayer no longer honors the quote")
+    signature: str = Field(..., description="Hex-encoded ed25519 signature over the quote")
+
+class SplitPayout(BaseModel):
+    recipient: str = Field(..., description="Recipient address")
+    amount: int = Field(..., gt=0, description="Amount paid to the recipient, in base units")
+
+class SplitWithdrawalProof(BaseModel):
+    a: str = Field(..., description="Hex-encoded Groth16 A (64 bytes, on-chain layout; zero for PLONK)")
+    b: str = Field(..., description="Hex-encoded Groth16 B (128 bytes, on-chain layout; zero for PLONK)")
+    c: str = Field(..., description="Hex-encoded Groth16 C (64 bytes, on-chain layout; zero for PLONK)")
+    public_inputs: List[int] = Field(..., description="The 15 u64 public inputs, unused payout pairs zero")
+    merkle_root: str = Field(..., description="Hex-encoded Merkle root the proof is against")
+    circuit_version: int = Field(..., ge=0, le=65535, description="Split circuit the proof was generated for")
+    plonk_proof: str = Field("", description="Hex-encoded PLONK proof (768 bytes) for PLONK circuits, with a, b and c zero; empty for Groth16")
+
+class SplitWithdrawalRequest(BaseModel):
+    nullifier_hash: str = Field(..., description="Hex-encoded nullifier hash")
+    commitment: str = Field(..., description="Hex-encoded commitment being spent")
+    token: str = Field(..., description="Token address")
+    payouts: List[SplitPayout] = Field(..., description="Payouts in proof order; one must pay the relayer its quoted fee")
+    proof: SplitWithdrawalProof = Field(..., description="Split proof, checked before the relayer submits it")
+    quote: FeeQuote = Field(..., description="Signed fee quote from /quote for the note's total")
+    callback_url: Optional[str] = Field(None, description="URL to POST the job to when it is confirmed, finalized or permanently fails")
+
 class WithdrawalRequest(BaseModel):
     nullifier_hash: str = Field(..., description="Hex-encoded nullifier hash")
     commitment: str = Field(..., description="Hex-encoded commitment being spent")
     recipient: str = Field(..., description="Recipient address")
     token: str = Field(..., description="Token address")
     amount: int = Field(..., description="Amount to withdraw")
-    # In a real implementation, you'd also include a ZK proof
-    # proof: dict = Field(..., description="Zero-knowledge proof")
+    proof: WithdrawalProof = Field(..., description="Withdraw proof, checked before the relayer submits it")
+    quote: FeeQuote = Field(..., description="Signed fee quote from /quote the withdrawal is priced with")
+    callback_url: Optional[str] = Field(None, description="URL to POST the job to when it is confirmed, finalized or permanently fails")
 
 class MerkleRoot(BaseModel):
     root: Optional[str] = Field(None, description="Current Merkle root")
@@ -57,16 +117,230 @@ class ZeroCommitmentInfo(BaseModel):
     is_in_tree: bool = Field(..., description="Whether the zero commitment is in the Merkle tree")
     leaf_index: Optional[int] = Field(None, description="The leaf index of the zero commitment if in the tree")
 
+class OnchainTree(BaseModel):
+    slot: int = Field(..., description="Slot of the update")
+    next_index: int = Field(..., description="Leaf index the next on-chain commitment takes")
+    root: str = Field(..., description="Hex-encoded on-chain root")
+
+class LatestRoot(BaseModel):
+    root: Optional[str] = Field(None, description="Current Merkle root")
+    leaf_count: int = Field(..., description="Number of leaves the root covers")
+    onchain: Optional[OnchainTree] = Field(None, description="Latest on-chain tree state, when streamed by the Geyser plugin")
+
+class CommitmentEntry(BaseModel):
+    leaf_index: int = Field(..., description="Index of the leaf in the tree")
+    commitment: str = Field(..., description="Hex-encoded commitment value")
+
+class CommitmentsPage(BaseModel):
+    commitments: List[CommitmentEntry] = Field(..., description="Commitments in leaf order")
+    next_from_leaf: int = Field(..., description="Cursor for the next page (from_leaf of the next request)")
+    has_more: bool = Field(..., description="Whether more leaves follow this page")
+
+class DepositEntry(BaseModel):
+    leaf_index: int = Field(..., description="Index of the deposit's leaf in the tree")
+    commitment: str = Field(..., description="Hex-encoded commitment value")
+    user: Optional[str] = Field(None, description="Depositor address")
+    token: Optional[str] = Field(None, description="Token (mint) address")
+    amount: int = Field(..., description="Amount in base units")
+    slot: Optional[int] = Field(None, description="Slot of the deposit, when known")
+    timestamp: Optional[int] = Field(None, description="Unix time the deposit was indexed")
+
+class DepositsPage(BaseModel):
+    deposits: List[DepositEntry] = Field(..., description="Deposits in leaf order")
+    next_cursor: int = Field(..., description="Cursor for the next page (cursor of the next request)")
+    has_more: bool = Field(..., description="Whether more leaves in the range follow this page")
+
+class WithdrawalEntry(BaseModel):
+    nullifier_hash: str = Field(..., description="Hex-encoded nullifier hash")
+    recipient: Optional[str] = Field(None, description="Recipient address; comma-separated for split withdrawals")
+    token: Optional[str] = Field(None, description="Token (mint) address")
+    amount: Optional[int] = Field(None, description="Amount in base units")
+    slot: Optional[int] = Field(None, description="Slot of the withdrawal, once it has landed and when known")
+    timestamp: Optional[int] = Field(None, description="Unix time the withdrawal was indexed")
+
+class WithdrawalsPage(BaseModel):
+    withdrawals: List[WithdrawalEntry] = Field(..., description="Withdrawals in the order they were indexed")
+    next_cursor: int = Field(..., description="Cursor for the next page (cursor of the next request)")
+    has_more: bool = Field(..., description="Whether more withdrawals follow this page")
+
+class NullifierStatus(BaseModel):
+    nullifier: str = Field(..., description="Hex-encoded nullifier hash")
+    is_used: bool = Field(..., description="Whether the nullifier has been used")
+    withdrawal: Optional[Dict[str, Any]] = Field(None, description="The withdrawal that used it, if indexed")
+
+class NullifierBatch(BaseModel):
+    nullifier_hashes: List[str] = Field(..., min_length=1, max_length=1000, description="Hex-encoded nullifier hashes, at most 1000")
+
+class NullifierStatuses(BaseModel):
+    nullifiers: List[NullifierStatus] = Field(..., description="The status of each nullifier, in request order")
+
+class PoolStats(BaseModel):
+    mint: str = Field(..., description="Token (mint) address of the pool")
+    deposit_count: int = Field(..., description="Number of indexed deposits")
+    total_deposited: int = Field(..., description="Sum of indexed deposit amounts, in base units")
+    withdrawal_count: int = Field(..., description="Number of indexed withdrawals")
+    total_withdrawn: int = Field(..., description="Sum of indexed withdrawal amounts, in base units")
+
+class PoolDay(BaseModel):
+    date: str = Field(..., description="UTC day, YYYY-MM-DD")
+    deposit_count: int = Field(..., description="Deposits indexed that day")
+    deposit_volume: int = Field(..., description="Sum of their amounts, in base units")
+    withdrawal_count: int = Field(..., description="Withdrawals indexed that day")
+    withdrawal_volume: int = Field(..., description="Sum of their amounts, in base units")
+    unique_depositors: int = Field(..., description="Distinct depositor addresses that day")
+    unique_recipients: int = Field(..., description="Distinct recipient addresses that day")
+    anonymity_set: int = Field(..., description="Estimated notes outstanding at the end of the day")
+
+class PoolAnalytics(BaseModel):
+    mint: str = Field(..., description="Token (mint) address of the pool")
+    days: List[PoolDay] = Field(..., description="Daily series, oldest day first, ending today")
+    anonymity_set: int = Field(..., description="Estimated notes outstanding now: deposits minus withdrawals")
+    thin: bool = Field(..., description="Whether the anonymity set is below thin_below")
+    thin_below: int = Field(..., description="Anonymity set below which the pool is considered thin")
+
+class WithdrawalJob(BaseModel):
+    job_id: str = Field(..., description="Id returned by POST /withdraw")
+    status: str = Field(..., description="pending, submitted, confirmed, finalized or failed")
+    nullifier_hash: str = Field(..., description="Hex-encoded nullifier hash being spent")
+    recipient: str = Field(..., description="Recipient address; comma-separated for split withdrawals")
+    token: str = Field(..., description="Token address")
+    amount: int = Field(..., description="Amount withdrawn")
+    relayer_fee: int = Field(..., description="Quoted relayer fee, in base units")
+    signature: Optional[str] = Field(None, description="Signature of the withdrawal transaction, once known")
+    slot: Optional[int] = Field(None, description="Slot the transaction landed in, once known")
+    fee_charged: Optional[int] = Field(None, description="Fee actually charged: the relayer fee once confirmed, 0 if failed")
+    error: Optional[str] = Field(None, description="Why the job failed")
+    correlation_id: Optional[str] = Field(None, description="The X-Correlation-Id the withdrawal was submitted with")
+    created_at: float = Field(..., description="Unix time the request was accepted")
+    updated_at: float = Field(..., description="Unix time of the last status change")
+    submitted_at: Optional[float] = Field(None, description="Unix time the transaction was sent")
+
+class ChainEvent(BaseModel):
+    id: str = Field(..., description="<chain>:<tx>:<log_index>, unique across chains")
+    chain: str = Field(..., description="solana or evm")
+    network: str = Field(..., description="Solana cluster name, or EVM chain id")
+    event: str = Field(..., description="Event name, shared by the program and the Solidity contract")
+    height: int = Field(..., description="Slot or block number")
+    tx: str = Field(..., description="Transaction signature or hash")
+    log_index: int = Field(..., description="Position of the event within the transaction")
+    user: Optional[str] = Field(None, description="Depositor, for DepositOccurred")
+    token: Optional[str] = Field(None, description="Mint or ERC-20 token address, for deposits and withdrawals")
+    amount: Optional[int] = Field(None, description="Amount in base units, for deposits and withdrawals")
+    commitment: Optional[str] = Field(None, description="Hex-encoded commitment, for DepositOccurred")
+    nullifier_hash: Optional[str] = Field(None, description="Hex-encoded nullifier hash, for WithdrawalOccurred")
+    recipient: Optional[str] = Field(None, description="Withdrawal recipient, for WithdrawalOccurred")
+    old_admin: Optional[str] = Field(None, description="Previous admin, for AdminChanged")
+    new_admin: Optional[str] = Field(None, description="New admin, for AdminChanged")
+    relayer_address: Optional[str] = Field(None, description="Relayer, for RelayerAdded and RelayerRemoved")
+    paused: Optional[bool] = Field(None, description="Whether the pool is now paused, for PauseToggled (Solana only)")
+    by: Optional[str] = Field(None, description="Signer who paused or unpaused the pool, for PauseToggled")
+
+class ChainEventsPage(BaseModel):
+    events: List[ChainEvent] = Field(..., description="Events in the order they were indexed")
+    next_offset: int = Field(..., description="Cursor for the next page (offset of the next request)")
+    has_more: bool = Field(..., description="Whether more events follow this page")
+
+class ChainPoolStats(BaseModel):
+    chain: str = Field(..., description="solana or evm")
+    network: str = Field(..., description="Solana cluster name, or EVM chain id")
+    token: str = Field(..., description="Mint or ERC-20 token address")
+    deposit_count: int = Field(..., description="Number of indexed deposits")
+    total_deposited: int = Field(..., description="Sum of indexed deposit amounts, in base units")
+    withdrawal_count: int = Field(..., description="Number of indexed withdrawals")
+    total_withdrawn: int = Field(..., description="Sum of indexed withdrawal amounts, in base units")
+
+class ChainStats(BaseModel):
+    pools: List[ChainPoolStats] = Field(..., description="Totals per chain, network and token")
+    heights: Dict[str, int] = Field(..., description="Latest indexed slot or block, per chain")
+
+class GeyserEvent(BaseModel):
+    kind: str = Field(..., description="commitment, nullifier, note, attestation, tree or archived_roots")
+    slot: int = Field(..., description="Slot of the event")
+    signature: Optional[str] = Field(None, description="Transaction signature, for commitment, nullifier, note and attestation events")
+    leaf_index: Optional[int] = Field(None, description="On-chain leaf index of a commitment")
+    commitment: Optional[str] = Field(None, description="Hex-encoded commitment")
+    ciphertext: Optional[str] = Field(None, description="Hex-encoded note envelope, for note events")
+    envelope_hash: Optional[str] = Field(None, description="Hex-encoded SHA-256 of a compliance envelope, for attestation events")
+    user: Optional[str] = Field(None, description="Depositor; absent for change notes")
+    nullifier_hash: Optional[str] = Field(None, description="Hex-encoded nullifier hash")
+    recipient: Optional[str] = Field(None, description="Withdrawal recipient")
+    mint: Optional[str] = Field(None, description="Token (mint) address")
+    amount: Optional[int] = Field(None, description="Amount in base units; absent for change notes")
+    next_index: Optional[int] = Field(None, description="On-chain next leaf index, for tree events")
+    root: Optional[str] = Field(None, description="Hex-encoded on-chain root, for tree events")
+    roots: Optional[List[str]] = Field(
+        None, description="Hex-encoded recent on-chain roots, for tree events; the page's roots, for archived_roots events"
+    )
+    accept_archived_roots: Optional[bool] = Field(None, description="Whether the tree accepts archived roots, for tree events")
+    page: Optional[int] = Field(None, description="Root archive page, for archived_roots events")
+
+class MerchantRegistration(BaseModel):
+    viewing_key: str = Field(..., description="Hex-encoded X25519 private key payers encrypt notes to")
+    webhook_url: str = Field(..., description="URL announced payments are POSTed to")
+
+class Merchant(BaseModel):
+    merchant_id: str = Field(..., description="Id of the registration")
+    public_key: str = Field(..., description="Hex-encoded X25519 public key to give payers")
+    webhook_url: str = Field(..., description="URL announced payments are POSTed to")
+    created_at: int = Field(..., description="Unix time of the registration")
+    webhook_secret: Optional[str] = Field(
+        None, description="Key of the webhooks' X-Privax-Signature HMAC; only returned at registration"
+    )
+
+class ComplianceSubmission(BaseModel):
+    commitment: str = Field(..., description="Hex-encoded commitment of the attested deposit")
+    envelope: str = Field(..., description="Hex-encoded envelope, encrypted to the pool's compliance key")
+
+class ComplianceEnvelope(BaseModel):
+    commitment: str = Field(..., description="Hex-encoded commitment of the attested deposit")
+    envelope: str = Field(..., description="Hex-encoded envelope, encrypted to the pool's compliance key")
+    envelope_hash: str = Field(..., description="Hex-encoded SHA-256 of the envelope, as attested on-chain")
+    depositor: Optional[str] = Field(None, description="The attesting depositor")
+    slot: Optional[int] = Field(None, description="Slot of the attestation")
+    signature: Optional[str] = Field(None, description="Transaction signature of the attestation")
+
+class GeyserBatch(BaseModel):
+    slot: int = Field(..., description="Slot the events belong to")
+    events: List[GeyserEvent] = Field(..., description="The slot's events in block order")
+
 # --- Helper functions ---
 
-async def start_blockchain_listeners():
-    """Start blockchain event listeners in the background"""
+def rate_limit(request: Request, x_api_key: Optional[str] = Header(None)):
+    """Reject clients over their rate limit with 429, and unknown API keys with 401"""
+    if x_api_key is not None:
+        if x_api_key not in api_keys:
+            raise HTTPException(status_code=401, detail="Unknown API key")
+        limiter, client = api_key_rate_limiter, f"key:{x_api_key}"
+    else:
+        limiter, client = ip_rate_limiter, f"ip:{request.client.host if request.client else 'unknown'}"
+    retry_after = limiter.acquire(client)
+    if retry_after:
+        metrics.WITHDRAWAL_REQUESTS.inc(outcome="rate_limited")
+        raise HTTPException(
+            status_code=429,
+            detail="Rate limit exceeded",
+            headers={"Retry-After": str(math.ceil(retry_after))}
+        )
+
+def require_api_key(x_api_key: Optional[str] = Header(None)):
+    """Reject requests without a known API key with 401, and return the key"""
+    if x_api_key is None or x_api_key not in api_keys:
+        raise HTTPException(status_code=401, detail="A known API key is required")
+    return x_api_key
+
+async def start_blockchain_listeners(chains):
+    """
+    Start blockchain event listeners in the background
+    
+    Args:
+        chains: The chains to index, from INDEXER_CHAINS
+    """
     global ethereum_listener, solana_listener
     
     # Initialize Ethereum listener if not already running
-    if not ethereum_listener or not ethereum_listener.running:
+    if "evm" in chains and (not ethereum_listener or not ethereum_listener.running):
         try:
-            ethereum_listener = EthereumListener(relayer)
+            ethereum_listener = EthereumListener(relayer, index=chain_events)
             task = asyncio.create_task(ethereum_listener.start())
             background_tasks.add(task)
             task.add_done_callback(background_tasks.discard)
@@ -75,9 +349,9 @@ async def start_blockchain_listeners():
             logger.error(f"Failed to start Ethereum listener: {str(e)}")
     
     # Initialize Solana listener if not already running
-    if not solana_listener or not solana_listener.running:
+    if "solana" in chains and (not solana_listener or not solana_listener.running):
         try:
-            solana_listener = SolanaListener(relayer)
+            solana_listener = SolanaListener(chain_events)
             task = asyncio.create_task(solana_listener.start())
             background_tasks.add(task)
             task.add_done_callback(background_tasks.discard)
@@ -85,13 +359,62 @@ async def start_blockchain_listeners():
         except Exception as e:
             logger.error(f"Failed to start Solana listener: {str(e)}")
 
+async def track_chain(interval=10):
+    """Poll the cluster for its tip and the finality of relayed withdrawal transactions"""
+    global chain_tip_slot
+    rpc_url = profile().rpc_url
+    async with httpx.AsyncClient(timeout=10) as client:
+        while True:
+            try:
+                await relayer.jobs.check_finality(rpc_url, client)
+            except Exception as e:
+                metrics.RPC_ERRORS.inc(method="getSignatureStatuses")
+                logger.error(f"Error checking withdrawal job finality: {str(e)}")
+            try:
+                response = await client.post(rpc_url, json={
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "method": "getSlot",
+                    "params": [{"commitment": "confirmed"}]
+                })
+                response.raise_for_status()
+                chain_tip_slot = response.json()["result"]
+                health.record_rpc()
+            except Exception as e:
+                metrics.RPC_ERRORS.inc(method="getSlot")
+                health.record_rpc(str(e))
+                logger.error(f"Error fetching the chain tip: {str(e)}")
+            await asyncio.sleep(interval)
+
+async def track_prover(interval=10):
+    """Poll the prover's readiness, when PROVER_URL is set"""
+    if not health.prover_url:
+        return
+    async with httpx.AsyncClient(timeout=5) as client:
+        while True:
+            try:
+                response = await client.get(f"{health.prover_url.rstrip('/')}/readyz")
+                response.raise_for_status()
+                health.record_prover()
+            except Exception as e:
+                health.record_prover(str(e))
+                logger.warning(f"Prover is not ready: {str(e)}")
+            await asyncio.sleep(interval)
+
+def start_job_tracker():
+    """Start tracking withdrawal jobs and the prover in the background"""
+    for tracker in (track_chain, track_prover):
+        task = asyncio.create_task(tracker())
+        background_tasks.add(task)
+        task.add_done_callback(background_tasks.discard)
+
 # --- API Endpoints ---
 
 @app.on_event("startup")
 async def startup_event():
     """Start background tasks on application startup"""
-    background_tasks = BackgroundTasks()
-    background_tasks.add_task(start_blockchain_listeners)
+    await start_blockchain_listeners(indexer_chains())
+    start_job_tracker()
 
 @app.on_event("shutdown")
 async def shutdown_event():
@@ -107,6 +430,42 @@ async def root():
     """Health check endpoint"""
     return {"status": "Relayer is running"}
 
+@app.get("/healthz", tags=["Health"])
+async def healthz():
+    """Liveness: the process is up and serving requests"""
+    return {"status": "ok"}
+
+@app.get("/readyz", tags=["Health"])
+async def readyz():
+    """
+    Readiness: the RPC node answers, the index is within READY_MAX_LAG_SLOTS of the chain tip,
+    the data directory is writable and, with PROVER_URL set, the prover is ready.
+    Returns 503 with the failing checks otherwise.
+    """
+    ready, checks = health.readiness(relayer, chain_tip_slot)
+    return JSONResponse(
+        status_code=200 if ready else 503,
+        content={"status": "ready" if ready else "not ready", "checks": checks}
+    )
+
+@app.get("/metrics", response_class=PlainTextResponse, tags=["Health"])
+async def get_metrics(accept: Optional[str] = Header(None)):
+    """
+    Operational metrics in the Prometheus text format, or in OpenMetrics (with exemplars
+    carrying withdrawals' correlation ids) when the scraper accepts it
+    """
+    counts = relayer.jobs.count_by_status()
+    for status, count in counts.items():
+        metrics.JOBS.set(count, status=status)
+    metrics.QUEUE_DEPTH.set(counts["pending"] + counts["submitted"] + counts["confirmed"])
+    if chain_tip_slot is not None:
+        metrics.CHAIN_TIP_SLOT.set(chain_tip_slot)
+        if relayer.latest_slot is not None:
+            metrics.LAG_SLOTS.set(max(chain_tip_slot - relayer.latest_slot, 0))
+    if accept and "application/openmetrics-text" in accept:
+        return PlainTextResponse(metrics.render(openmetrics=True), media_type=metrics.OPENMETRICS_CONTENT_TYPE)
+    return PlainTextResponse(metrics.render(), media_type=metrics.TEXT_CONTENT_TYPE)
+
 @app.get("/merkle_root", response_model=MerkleRoot, tags=["Merkle Tree"])
 async def get_merkle_root():
     """Get the current Merkle root"""
@@ -126,12 +485,20 @@ async def get_merkle_path(commitment: str):
     except ValueError as e:
         raise HTTPException(status_code=404, detail=str(e))
 
-@app.post("/withdraw", tags=["Withdrawals"])
-async def submit_withdrawal(request: WithdrawalRequest):
+@app.post("/withdraw", tags=["Withdrawals"], dependencies=[Depends(rate_limit)])
+async def submit_withdrawal(
+    request: WithdrawalRequest,
+    response: Response,
+    x_correlation_id: Optional[str] = Header(None)
+):
     """
     Submit a withdrawal request
     
-    In a real implementation, this would verify a ZK proof
+    The proof is checked against the on-chain root history, the indexed nullifiers and
+    the program's own checks before anything is submitted. The response carries a
+    `job_id` to poll at `/jobs/{job_id}`; with a `callback_url`, the job is also POSTed
+    there when it is confirmed, finalized or permanently fails. An `X-Correlation-Id`
+    header is echoed back and kept with the job, its log lines and its callbacks.
     """
     try:
         result = relayer.submit_withdrawal(
@@ -139,21 +506,286 @@ async def submit_withdrawal(request: WithdrawalRequest):
             request.commitment,
             request.recipient,
             request.token,
-            request.amount
+            request.amount,
+            request.proof.model_dump(),
+            request.quote.model_dump(),
+            request.callback_url,
+            x_correlation_id
+        )
+        metrics.WITHDRAWAL_REQUESTS.inc(outcome="accepted")
+        if x_correlation_id:
+            response.headers[CORRELATION_ID_HEADER] = x_correlation_id
+        return result
+    except ValueError as e:
+        metrics.WITHDRAWAL_REQUESTS.inc(outcome="rejected")
+        raise HTTPException(status_code=400, detail=str(e))
+
+@app.post("/withdraw_split", tags=["Withdrawals"], dependencies=[Depends(rate_limit)])
+async def submit_split_withdrawal(
+    request: SplitWithdrawalRequest,
+    response: Response,
+    x_correlation_id: Optional[str] = Header(None)
+):
+    """
+    Submit a withdraw_split request, paying one note out to up to four recipients
+    
+    Quote the note's total at `/quote` and make the relayer one of the payouts, for at
+    least the quoted fee. The job is polled like a withdrawal's, at `/jobs/{job_id}`,
+    and takes an `X-Correlation-Id` header the same way.
+    """
+    try:
+        result = relayer.submit_split_withdrawal(
+            request.nullifier_hash,
+            request.commitment,
+            request.token,
+            [(payout.recipient, payout.amount) for payout in request.payouts],
+            request.proof.model_dump(),
+            request.quote.model_dump(),
+            request.callback_url,
+            x_correlation_id
         )
+        metrics.WITHDRAWAL_REQUESTS.inc(outcome="accepted")
+        if x_correlation_id:
+            response.headers[CORRELATION_ID_HEADER] = x_correlation_id
         return result
     except ValueError as e:
+        metrics.WITHDRAWAL_REQUESTS.inc(outcome="rejected")
         raise HTTPException(status_code=400, detail=str(e))
 
-@app.get("/nullifier/{nullifier_hash}", tags=["Nullifiers"])
+@app.get("/quote", response_model=FeeQuote, tags=["Withdrawals"])
+async def get_quote(
+    mint: str = Query(..., description="Token (mint) address of the pool"),
+    amount: int = Query(..., gt=0, description="Amount to withdraw, in base units")
+):
+    """
+    Get a fee quote for relaying a withdrawal
+    
+    The quote is signed with the relayer's key and must be sent back with `/withdraw`
+    before `expires_at`; the relayer then charges exactly the quoted fee.
+    """
+    try:
+        return relayer.get_quote(mint, amount)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+@app.get("/jobs/{job_id}", response_model=WithdrawalJob, tags=["Withdrawals"])
+async def get_job(job_id: str):
+    """
+    Get the status of a relayed withdrawal
+    
+    - **job_id**: Id returned by `/withdraw`
+    """
+    job = relayer.get_job(job_id)
+    if job is None:
+        raise HTTPException(status_code=404, detail="Unknown job")
+    return job
+
+@app.get("/nullifier/{nullifier_hash}", response_model=NullifierStatus, tags=["Nullifiers"])
 async def check_nullifier(nullifier_hash: str):
     """
-    Check if a nullifier has been used
+    Check if a nullifier has been used, and by which withdrawal
     
     - **nullifier_hash**: Hex-encoded nullifier hash
     """
-    is_used = relayer.is_nullifier_used(nullifier_hash)
-    return {"nullifier": nullifier_hash, "is_used": is_used}
+    return relayer.get_nullifier(nullifier_hash)
+
+@app.post("/nullifiers/status", response_model=NullifierStatuses, tags=["Nullifiers"])
+async def check_nullifiers(batch: NullifierBatch):
+    """
+    Check many nullifiers at once, e.g. for a wallet reconciling its notes on startup
+    
+    - **nullifier_hashes**: Hex-encoded nullifier hashes, at most 1000 per request
+    """
+    return relayer.get_nullifiers(batch.nullifier_hashes)
+
+@app.get("/commitments", response_model=CommitmentsPage, tags=["Indexer"])
+async def get_commitments(
+    from_leaf: int = Query(0, ge=0, description="Leaf index to start from"),
+    limit: int = Query(100, ge=1, le=1000, description="Maximum number of commitments to return")
+):
+    """
+    Page through indexed commitments in leaf order
+    
+    - **from_leaf**: Leaf index to start from; pass the previous page's `next_from_leaf`
+    - **limit**: Page size, at most 1000
+    """
+    return relayer.get_commitments(from_leaf, limit)
+
+@app.get("/deposits", response_model=DepositsPage, tags=["Indexer"])
+async def get_deposits(
+    cursor: int = Query(0, ge=0, description="Leaf index to resume from"),
+    limit: int = Query(100, ge=1, le=1000, description="Maximum number of deposits to return"),
+    mint: Optional[str] = Query(None, description="Only deposits of this token"),
+    from_time: Optional[int] = Query(None, ge=0, description="Only deposits indexed at or after this unix time"),
+    to_time: Optional[int] = Query(None, ge=0, description="Only deposits indexed before this unix time"),
+    from_leaf: Optional[int] = Query(None, ge=0, description="Only deposits at or after this leaf index"),
+    to_leaf: Optional[int] = Query(None, ge=0, description="Only deposits before this leaf index")
+):
+    """
+    Page through indexed deposits in leaf order, e.g. for a wallet rescan
+    
+    - **cursor**: Leaf index to resume from; pass the previous page's `next_cursor`
+    - **limit**: Page size, at most 1000. With filters a page may hold fewer deposits while
+      `has_more` is still true.
+    """
+    return relayer.get_deposits(cursor, limit, mint, from_time, to_time, from_leaf, to_leaf)
+
+@app.get("/withdrawals", response_model=WithdrawalsPage, tags=["Indexer"])
+async def get_withdrawals(
+    cursor: int = Query(0, ge=0, description="Position to resume from"),
+    limit: int = Query(100, ge=1, le=1000, description="Maximum number of withdrawals to return"),
+    mint: Optional[str] = Query(None, description="Only withdrawals of this token"),
+    from_time: Optional[int] = Query(None, ge=0, description="Only withdrawals indexed at or after this unix time"),
+    to_time: Optional[int] = Query(None, ge=0, description="Only withdrawals indexed before this unix time")
+):
+    """
+    Page through indexed withdrawals in the order they were indexed
+    
+    - **cursor**: Position to resume from; pass the previous page's `next_cursor`
+    - **limit**: Page size, at most 1000
+    """
+    return relayer.get_withdrawals(cursor, limit, mint, from_time, to_time)
+
+@app.get("/roots/latest", response_model=LatestRoot, tags=["Indexer"])
+async def get_latest_root():
+    """Get the current Merkle root and the number of leaves it covers"""
+    return relayer.get_latest_root()
+
+@app.post("/geyser/events", tags=["Indexer"])
+async def ingest_geyser_events(batch: GeyserBatch):
+    """
+    Index a slot's events pushed by the Privax Geyser plugin
+    
+    Events already indexed are skipped, so the plugin can safely retry a slot.
+    """
+    events = [event.model_dump(exclude_none=True) for event in batch.events]
+    return relayer.ingest_geyser_events(batch.slot, events)
+
+@app.get("/snapshot", tags=["Indexer"])
+async def get_snapshot():
+    """
+    Get a snapshot of the index, for a new relayer or indexer node to bootstrap from
+    
+    Point the new node's `BOOTSTRAP_SNAPSHOT` at this URL. The snapshot carries a SHA-256
+    of its content and its Merkle root, both checked on import.
+    """
+    return relayer.get_snapshot()
+
+@app.get("/pools/{mint}/stats", response_model=PoolStats, tags=["Indexer"])
+async def get_pool_stats(mint: str):
+    """
+    Get deposit and withdrawal totals for a pool
+    
+    - **mint**: Token (mint) address of the pool
+    """
+    return relayer.get_pool_stats(mint)
+
+@app.get("/pools/{mint}/analytics", response_model=PoolAnalytics, tags=["Indexer"])
+async def get_pool_analytics(
+    mint: str,
+    days: int = Query(30, ge=1, le=365, description="Number of days in the series, ending today")
+):
+    """
+    Get a pool's daily deposit and withdrawal volume and anonymity-set estimates
+    
+    - **mint**: Token (mint) address of the pool
+    - **days**: Length of the daily series, at most 365
+    
+    `thin` is set when fewer notes than THIN_POOL_SIZE are outstanding, for frontends to warn
+    that a withdrawal from the pool is easier to link to its deposit.
+    """
+    return relayer.get_pool_analytics(mint, days)
+
+@app.get("/events", response_model=ChainEventsPage, response_model_exclude_none=True, tags=["Indexer"])
+async def get_chain_events(
+    chain: Optional[str] = Query(None, description="Only this chain's events: solana or evm"),
+    event: Optional[str] = Query(None, description="Only events of this name, e.g. DepositOccurred"),
+    token: Optional[str] = Query(None, description="Only events for this mint or token address"),
+    offset: int = Query(0, ge=0, description="Position to start from"),
+    limit: int = Query(100, ge=1, le=1000, description="Maximum number of events to return")
+):
+    """
+    Page through the Solana program's and the EVM contract's events, in one schema
+    
+    - **offset**: Position to start from; pass the previous page's `next_offset`
+    - **limit**: Page size, at most 1000
+    """
+    if chain and chain not in CHAINS:
+        raise HTTPException(status_code=400, detail=f"chain must be one of {', '.join(CHAINS)}")
+    if event and event not in EVENTS:
+        raise HTTPException(status_code=400, detail=f"event must be one of {', '.join(EVENTS)}")
+    return chain_events.get_events(chain, event, token, offset, limit)
+
+@app.get("/events/stats", response_model=ChainStats, tags=["Indexer"])
+async def get_chain_stats():
+    """Get deposit and withdrawal totals per chain and token, from the cross-chain index"""
+    return chain_events.get_stats()
+
+@app.post("/merchants", response_model=Merchant, response_model_exclude_none=True, tags=["Merchants"])
+async def register_merchant(registration: MerchantRegistration, api_key: str = Depends(require_api_key)):
+    """
+    Register a viewing key to be notified of shielded payments to it
+
+    - **viewing_key**: Hex-encoded X25519 private key; payers encrypt notes to its `public_key`
+    - **webhook_url**: URL each payment whose announced note decrypts under the key is POSTed to
+
+    Requires an `X-API-Key`. The response's `webhook_secret` signs the webhooks' bodies
+    (HMAC-SHA256 in `X-Privax-Signature`) and isn't returned again.
+    """
+    try:
+        return relayer.register_merchant(registration.viewing_key, registration.webhook_url, api_key)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+@app.get("/merchants/{merchant_id}", response_model=Merchant, response_model_exclude_none=True, tags=["Merchants"])
+async def get_merchant(merchant_id: str, api_key: str = Depends(require_api_key)):
+    """
+    Get a merchant registered with the same API key
+
+    - **merchant_id**: Id returned by `/merchants`
+    """
+    merchant = relayer.get_merchant(merchant_id, api_key)
+    if merchant is None:
+        raise HTTPException(status_code=404, detail="Unknown merchant")
+    return merchant
+
+@app.delete("/merchants/{merchant_id}", status_code=204, tags=["Merchants"])
+async def remove_merchant(merchant_id: str, api_key: str = Depends(require_api_key)):
+    """
+    Stop notifying a merchant registered with the same API key
+
+    - **merchant_id**: Id returned by `/merchants`
+    """
+    if not relayer.remove_merchant(merchant_id, api_key):
+        raise HTTPException(status_code=404, detail="Unknown merchant")
+
+@app.post("/compliance/envelopes", response_model=ComplianceEnvelope, tags=["Compliance"])
+async def submit_compliance_envelope(submission: ComplianceSubmission):
+    """
+    Store the compliance envelope of a deposit into an institutional pool
+
+    - **commitment**: Commitment the depositor attested to with `attest_deposit`
+    - **envelope**: The envelope whose SHA-256 was attested
+
+    Open to anyone: the envelope is only accepted if it matches the on-chain attestation, and only
+    the compliance key can read it.
+    """
+    try:
+        return relayer.submit_compliance_envelope(submission.commitment, submission.envelope)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+@app.get("/compliance/envelopes/{commitment}", response_model=ComplianceEnvelope, tags=["Compliance"])
+async def get_compliance_envelope(commitment: str, api_key: str = Depends(require_api_key)):
+    """
+    Get a deposit's compliance envelope, for the compliance operator to decrypt
+
+    - **commitment**: Hex-encoded commitment of the attested deposit
+    """
+    envelope = relayer.get_compliance_envelope(commitment)
+    if envelope is None:
+        raise HTTPException(status_code=404, detail="No envelope for this commitment")
+    return envelope
 
 @app.get("/zero_commitment", response_model=ZeroCommitmentInfo, tags=["Merkle Tree"])
 async def get_zero_commitment():
@@ -8,11 +8,32 @@ logger = logging.getLogger(__name__)
 
 # ABI for the privacy contract events
 CONTRACT_ABI = [
+    {
+        "anonymous": False,
+        "inputs": [
+            {"indexed": True, "name": "oldAdmin", "type": "address"},
+            {"indexed": True, "name": "newAdmin", "type": "address"}
+        ],
+        "name": "AdminChanged",
+        "type": "event"
+    },
+    {
+        "anonymous": False,
+        "inputs": [{"indexed": True, "name": "relayerAddress", "type": "address"}],
+        "name": "RelayerAdded",
+        "type": "event"
+    },
+    {
+        "anonymous": False,
+        "inputs": [{"indexed": True, "name": "relayerAddress", "type": "address"}],
+        "name": "RelayerRemoved",
+        "type": "event"
+    },
     {
         "anonymous": False,
         "inputs": [
             {"indexed": True, "name": "user", "type": "address"},
-            {"indexed": True, "name": "token", "type": "address"},
+            {"indexed": True, "name": "tokenAddress", "type": "address"},
             {"indexed": False, "name": "amount", "type": "uint256"},
             {"indexed": False, "name": "commitment", "type": "bytes32"}
         ],
@@ -24,7 +45,7 @@ CONTRACT_ABI = [
         "inputs": [
             {"indexed": True, "name": "nullifierHash", "type": "bytes32"},
             {"indexed": True, "name": "recipient", "type": "address"},
-            {"indexed": True, "name": "token", "type": "address"},
+            {"indexed": True, "name": "tokenAddress", "type": "address"},
             {"indexed": False, "name": "amount", "type": "uint256"}
         ],
         "name": "WithdrawalOccurred",
@@ -33,7 +54,7 @@ CONTRACT_ABI = [
 ]
 
 class EthereumListener:
-    def __init__(self, relayer, rpc_url=None, contract_address=None):
+    def __init__(self, relayer, rpc_url=None, contract_address=None, index=None):
         """
         Initialize Ethereum event listener
         
@@ -41,8 +62,11 @@ class EthereumListener:
             relayer: The relayer instance
             rpc_url: The Ethereum RPC URL
             contract_address: The privacy contract address
+            index: The cross-chain event index; when given, every contract event is recorded
+                   there instead of being forwarded to the relayer, whose tree is the Solana pool's
         """
         self.relayer = relayer
+        self.index = index
         self.rpc_url = rpc_url or os.getenv("ETH_RPC_URL")
         self.contract_address = contract_address or os.getenv("ETH_CONTRACT_ADDRESS")
         
@@ -69,8 +93,12 @@ class EthereumListener:
         self.running = True
         logger.info(f"Starting Ethereum event listener for contract {self.contract_address}")
         
-        # Get the latest block number as a starting point
-        self.last_processed_block = self.w3.eth.block_number
+        # Resume the index where it stopped, or start from the latest block
+        if self.index and self.index.cursor("evm") is not None:
+            self.last_processed_block = self.index.cursor("evm")
+        else:
+            self.last_processed_block = self.w3.eth.block_number
+        self.chain_id = self.w3.eth.chain_id
         logger.info(f"Starting from block {self.last_processed_block}")
         
         while self.running:
@@ -96,6 +124,12 @@ class EthereumListener:
         
         logger.info(f"Processing blocks {self.last_processed_block + 1} to {current_block}")
         
+        if self.index:
+            self.index_events(self.last_processed_block + 1, current_block)
+            self.last_processed_block = current_block
+            self.index.advance("evm", current_block)
+            return
+        
         # Get deposit events
         deposit_filter = self.contract.events.DepositOccurred.create_filter(
             fromBlock=self.last_processed_block + 1,
@@ -125,7 +159,7 @@ class EthereumListener:
         try:
             args = event['args']
             user_address = args['user']
-            token_address = args['token']
+            token_address = args['tokenAddress']
             amount = args['amount']
             commitment = args['commitment'].hex()
             
@@ -143,7 +177,7 @@ class EthereumListener:
             args = event['args']
             nullifier_hash = args['nullifierHash'].hex()
             recipient = args['recipient']
-            token = args['token']
+            token = args['tokenAddress']
             amount = args['amount']
             
             logger.info(f"Processing withdrawal: nullifier={nullifier_hash[:10]}..., recipient={recipient}, token={token}, amount={amount}")
@@ -152,4 +186,41 @@ class EthereumListener:
             self.relayer.process_withdrawal(nullifier_hash, recipient, token, amount)
             
         except Exception as e:
-            logger.error(f"Error processing withdrawal event: {str(e)}") 
\ No newline at end of file
+            logger.error(f"Error processing withdrawal event: {str(e)}")
+    
+    def index_events(self, from_block, to_block):
+        """Record the contract's events in a block range in the cross-chain index, in chain order"""
+        events = []
+        for name in ("AdminChanged", "RelayerAdded", "RelayerRemoved", "DepositOccurred", "WithdrawalOccurred"):
+            event_filter = self.contract.events[name].create_filter(fromBlock=from_block, toBlock=to_block)
+            events.extend(event_filter.get_all_entries())
+        events.sort(key=lambda event: (event['blockNumber'], event['logIndex']))
+        
+        for event in events:
+            args = event['args']
+            fields = {
+                "AdminChanged": lambda: {"old_admin": args['oldAdmin'], "new_admin": args['newAdmin']},
+                "RelayerAdded": lambda: {"relayer_address": args['relayerAddress']},
+                "RelayerRemoved": lambda: {"relayer_address": args['relayerAddress']},
+                "DepositOccurred": lambda: {
+                    "user": args['user'],
+                    "token": args['tokenAddress'],
+                    "amount": args['amount'],
+                    "commitment": args['commitment'].hex().removeprefix("0x")
+                },
+                "WithdrawalOccurred": lambda: {
+                    "nullifier_hash": args['nullifierHash'].hex().removeprefix("0x"),
+                    "recipient": args['recipient'],
+                    "token": args['tokenAddress'],
+                    "amount": args['amount']
+                },
+            }[event['event']]()
+            self.index.record({
+                "chain": "evm",
+                "network": str(self.chain_id),
+                "event": event['event'],
+                "height": event['blockNumber'],
+                "tx": event['transactionHash'].hex(),
+                "log_index": event['logIndex'],
+                **fields
+            })
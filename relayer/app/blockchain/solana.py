@@ -1,133 +1,167 @@
 import logging
-import os
 import asyncio
-import json
+import base64
+import hashlib
+
+import httpx
+
+from ..config import profile
+from ..verifier import base58_encode
 
 logger = logging.getLogger(__name__)
 
+# The program's events that mirror the EVM contract's, and PauseToggled, as Anchor encodes them in
+# "Program data:" logs: an 8-byte discriminator, sha256("event:<Name>")[:8], then the Borsh fields.
+# Every event starts with its version byte; only the leading fields the shared schema needs are decoded.
+EVENT_NAMES = ("AdminChanged", "RelayerAdded", "RelayerRemoved", "PauseToggled", "DepositOccurred", "WithdrawalOccurred")
+DISCRIMINATORS = {hashlib.sha256(f"event:{name}".encode()).digest()[:8]: name for name in EVENT_NAMES}
+SIGNATURES_PER_PAGE = 1000
+
+def decode_event(data):
+    """
+    Decode a "Program data:" payload into the cross-chain schema's fields
+
+    Args:
+        data: The base64-decoded payload
+
+    Returns:
+        tuple: The event name and its fields, or None for events the schema doesn't cover
+    """
+    name = DISCRIMINATORS.get(data[:8])
+    if not name:
+        return None
+    body = data[9:]  # After the discriminator and the version byte
+
+    def key(offset):
+        return base58_encode(body[offset:offset + 32])
+
+    def amount(offset):
+        return int.from_bytes(body[offset:offset + 8], "little")
+
+    if name == "AdminChanged":
+        return name, {"old_admin": key(0), "new_admin": key(32)}
+    if name in ("RelayerAdded", "RelayerRemoved"):
+        return name, {"relayer_address": key(0)}
+    if name == "PauseToggled":
+        return name, {"paused": body[0] == 1, "by": key(1)}
+    if name == "DepositOccurred":
+        return name, {"user": key(0), "token": key(32), "amount": amount(64), "commitment": body[72:104].hex()}
+    return name, {"nullifier_hash": body[0:32].hex(), "recipient": key(32), "token": key(64), "amount": amount(96)}
+
 class SolanaListener:
-    def __init__(self, relayer, rpc_url=None, program_id=None):
+    def __init__(self, index, rpc_url=None, program_id=None):
         """
         Initialize Solana event listener
-        
+
         Args:
-            relayer: The relayer instance
-            rpc_url: The Solana RPC URL
-            program_id: The Solana program ID (contract address)
+            index: The cross-chain event index the program's events are recorded in
+            rpc_url: The Solana RPC URL; the profile's by default
+            program_id: The Solana program ID (contract address); the profile's by default
         """
-        self.relayer = relayer
-        self.rpc_url = rpc_url or os.getenv("SOLANA_RPC_URL")
-        self.program_id = program_id or os.getenv("SOLANA_CONTRACT_ADDRESS")
-        
+        self.index = index
+        self.rpc_url = rpc_url or profile().rpc_url
+        self.program_id = program_id or profile().program_id
+        self.cluster = profile().cluster
+
         if not self.rpc_url:
             raise ValueError("Solana RPC URL is required")
         if not self.program_id:
             raise ValueError("Solana program ID is required")
-            
-        self.last_signature = None
+
+        # The newest transaction processed; the relayer's own tree is fed by the Geyser plugin
+        self.last_signature = index.cursor("solana")
         self.running = False
-        
-        # In a real implementation, you would use a Solana client library
-        # For example, using solana-py:
-        # from solana.rpc.async_api import AsyncClient
-        # self.client = AsyncClient(self.rpc_url)
-        
+
     async def start(self):
         """Start listening for events"""
         if self.running:
             logger.warning("Solana listener is already running")
             return
-            
+
         self.running = True
         logger.info(f"Starting Solana event listener for program {self.program_id}")
-        
-        # In a real implementation, you would fetch the latest signature
-        # as a starting point for polling
-        
-        while self.running:
-            try:
-                await self.poll_events()
-                await asyncio.sleep(5)  # Poll every 5 seconds
-            except Exception as e:
-                logger.error(f"Error polling Solana events: {str(e)}")
-                await asyncio.sleep(30)  # Longer delay on error
-    
+
+        async with httpx.AsyncClient(timeout=30) as client:
+            # Without a cursor, index from the latest transaction on
+            if self.last_signature is None:
+                latest = await self.rpc(client, "getSignaturesForAddress", [self.program_id, {"limit": 1}])
+                self.last_signature = latest[0]["signature"] if latest else None
+                logger.info(f"Starting after signature {self.last_signature}")
+
+            while self.running:
+                try:
+                    await self.poll_events(client)
+                    await asyncio.sleep(5)  # Poll every 5 seconds
+                except Exception as e:
+                    logger.error(f"Error polling Solana events: {str(e)}")
+                    await asyncio.sleep(30)  # Longer delay on error
+
     async def stop(self):
         """Stop listening for events"""
         logger.info("Stopping Solana event listener")
         self.running = False
-    
-    async def poll_events(self):
-        """
-        Poll for new events
-        
-        In a real implementation, you would:
-        1. Use getProgramAccounts or getSignaturesForAddress to fetch transactions
-        2. Parse transaction logs to identify deposit and withdrawal events
-        3. Extract relevant data and forward to the relayer
-        """
-        # Placeholder implementation
-        # In a real implementation, you would fetch new transactions and parse them
-        
-        # Example of how you might parse events in a real implementation:
-        # signatures = await self.client.get_signatures_for_address(self.program_id, since=self.last_signature)
-        # for sig_info in signatures:
-        #     signature = sig_info.signature
-        #     tx = await self.client.get_transaction(signature)
-        #     logs = tx.result.transaction.meta.log_messages
-        #     
-        #     for log in logs:
-        #         if "Program log: DepositOccurred" in log:
-        #             # Parse log and extract event data
-        #             await self.process_deposit_event(parsed_data)
-        #         elif "Program log: WithdrawalOccurred" in log:
-        #             # Parse log and extract event data
-        #             await self.process_withdrawal_event(parsed_data)
-        #
-        #     self.last_signature = signature
-        
-        pass
-    
-    async def process_deposit_event(self, event_data):
-        """
-        Process a deposit event
-        
-        Args:
-            event_data: The parsed event data containing user, token, amount, and commitment
-        """
-        try:
-            # In a real implementation, extract data from Solana program logs
-            user_address = event_data.get("user")
-            token_address = event_data.get("token")
-            amount = event_data.get("amount")
-            commitment = event_data.get("commitment")
-            
-            logger.info(f"Processing Solana deposit: user={user_address}, token={token_address}, amount={amount}, commitment={commitment[:10]}...")
-            
-            # Forward to relayer
-            self.relayer.process_deposit(user_address, token_address, amount, commitment)
-            
-        except Exception as e:
-            logger.error(f"Error processing Solana deposit event: {str(e)}")
-    
-    async def process_withdrawal_event(self, event_data):
-        """
-        Process a withdrawal event
-        
-        Args:
-            event_data: The parsed event data containing nullifier, recipient, token, and amount
-        """
-        try:
-            # In a real implementation, extract data from Solana program logs
-            nullifier_hash = event_data.get("nullifierHash")
-            recipient = event_data.get("recipient")
-            token = event_data.get("token")
-            amount = event_data.get("amount")
-            
-            logger.info(f"Processing Solana withdrawal: nullifier={nullifier_hash[:10]}..., recipient={recipient}, token={token}, amount={amount}")
-            
-            # Forward to relayer
-            self.relayer.process_withdrawal(nullifier_hash, recipient, token, amount)
-            
-        except Exception as e:
-            logger.error(f"Error processing Solana withdrawal event: {str(e)}") 
\ No newline at end of file
+
+    async def rpc(self, client, method, params):
+        """Call a JSON-RPC method on the cluster"""
+        response = await client.post(self.rpc_url, json={"jsonrpc": "2.0", "id": 1, "method": method, "params": params})
+        response.raise_for_status()
+        body = response.json()
+        if "error" in body:
+            raise RuntimeError(f"{method} failed: {body['error']}")
+        return body["result"]
+
+    async def poll_events(self, client):
+        """Index the program's transactions since the last one processed, oldest first"""
+        # Newest first, one page at a time back to the cursor
+        signatures = []
+        before = None
+        while True:
+            options = {"limit": SIGNATURES_PER_PAGE, "commitment": "confirmed"}
+            if self.last_signature:
+                options["until"] = self.last_signature
+            if before:
+                options["before"] = before
+            page = await self.rpc(client, "getSignaturesForAddress", [self.program_id, options])
+            signatures.extend(page)
+            if len(page) < SIGNATURES_PER_PAGE or not self.last_signature:
+                break
+            before = page[-1]["signature"]
+
+        for info in reversed(signatures):
+            if info.get("err") is None:
+                await self.index_transaction(client, info["signature"], info["slot"])
+            self.last_signature = info["signature"]
+        if signatures:
+            self.index.advance("solana", self.last_signature)
+
+    async def index_transaction(self, client, signature, slot):
+        """Record the program's events in one transaction"""
+        transaction = await self.rpc(client, "getTransaction", [
+            signature, {"encoding": "json", "commitment": "confirmed", "maxSupportedTransactionVersion": 0}
+        ])
+        logs = (transaction or {}).get("meta", {}).get("logMessages") or []
+
+        # Only data logged by the program itself, not by programs it invokes
+        stack = []
+        log_index = 0
+        for log in logs:
+            words = log.split()
+            if len(words) >= 3 and words[0] == "Program" and words[2] == "invoke":
+                stack.append(words[1])
+            elif len(words) >= 3 and words[0] == "Program" and words[2] in ("success", "failed:"):
+                if stack:
+                    stack.pop()
+            elif log.startswith("Program data: ") and stack and stack[-1] == self.program_id:
+                decoded = decode_event(base64.b64decode(log[len("Program data: "):]))
+                if decoded:
+                    name, fields = decoded
+                    self.index.record({
+                        "chain": "solana",
+                        "network": self.cluster,
+                        "event": name,
+                        "height": slot,
+                        "tx": signature,
+                        "log_index": log_index,
+                        **fields
+                    })
+                log_index += 1
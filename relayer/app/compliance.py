@@ -0,0 +1,155 @@
+"""
+Compliance envelopes for institutional pools.
+
+A pool with a compliance key (the program's `set_compliance_key`) only takes deposits whose
+depositor first attests to an envelope of origin information, e.g. the originator, the source
+of funds and a reference the institution can look up. The envelope is encrypted to the
+compliance key the same way notes are encrypted to merchants (see merchants.py), with info
+`privax-compliance-v1`:
+
+    ephemeral public key (32 bytes) || ChaCha20-Poly1305 ciphertext and tag
+
+The commitment is the associated data, so an envelope can't be passed off for another deposit.
+Only its SHA-256 goes on-chain (`attest_deposit`); the envelope itself is submitted here once
+the attestation is indexed, and checked against the hash. Envelopes are stored as submitted:
+the relayer can't read them, and only the holder of the compliance key can.
+"""
+import hashlib
+import json
+import logging
+
+from cryptography.exceptions import InvalidTag
+from cryptography.hazmat.primitives import hashes
+from cryptography.hazmat.primitives.asymmetric.x25519 import X25519PrivateKey, X25519PublicKey
+from cryptography.hazmat.primitives.ciphers.aead import ChaCha20Poly1305
+from cryptography.hazmat.primitives.kdf.hkdf import HKDF
+from cryptography.hazmat.primitives.serialization import Encoding, PublicFormat
+
+logger = logging.getLogger(__name__)
+
+ENVELOPE_INFO = b"privax-compliance-v1"
+NONCE = bytes(12)
+# Origin information is a few fields, not documents; those stay with the institution
+MAX_ENVELOPE_LEN = 4096
+
+def envelope_key(shared_secret):
+    """The ChaCha20-Poly1305 key for an X25519 shared secret"""
+    return HKDF(algorithm=hashes.SHA256(), length=32, salt=None, info=ENVELOPE_INFO).derive(shared_secret)
+
+def envelope_hash(envelope):
+    """The hex-encoded SHA-256 of an envelope, as `attest_deposit` takes it"""
+    return hashlib.sha256(envelope).hexdigest()
+
+def encrypt_envelope(compliance_key_hex, commitment_hex, attestation):
+    """
+    Encrypt a depositor's attestation to the pool's compliance key
+
+    Args:
+        compliance_key_hex: The pool's hex-encoded X25519 compliance key
+        commitment_hex: Hex-encoded commitment of the deposit the attestation is for
+        attestation: JSON-serializable origin information
+
+    Returns:
+        bytes: The envelope, whose hash goes to `attest_deposit`
+    """
+    ephemeral = X25519PrivateKey.generate()
+    shared_secret = ephemeral.exchange(X25519PublicKey.from_public_bytes(bytes.fromhex(compliance_key_hex)))
+    plaintext = json.dumps(attestation, separators=(",", ":")).encode("utf-8")
+    sealed = ChaCha20Poly1305(envelope_key(shared_secret)).encrypt(NONCE, plaintext, bytes.fromhex(commitment_hex))
+    return ephemeral.public_key().public_bytes(Encoding.Raw, PublicFormat.Raw) + sealed
+
+def decrypt_envelope(private_key_hex, commitment_hex, envelope):
+    """
+    Open an envelope with the compliance key's private half, for the compliance operator
+
+    Returns:
+        The attestation, or None if the envelope isn't for this key and commitment
+    """
+    if len(envelope) <= 32:
+        return None
+    private_key = X25519PrivateKey.from_private_bytes(bytes.fromhex(private_key_hex))
+    try:
+        shared_secret = private_key.exchange(X25519PublicKey.from_public_bytes(envelope[:32]))
+        plaintext = ChaCha20Poly1305(envelope_key(shared_secret)).decrypt(NONCE, envelope[32:], bytes.fromhex(commitment_hex))
+        return json.loads(plaintext)
+    except (InvalidTag, ValueError):
+        return None
+
+class ComplianceEnvelopes:
+    """Attestations indexed from the chain, and the envelopes submitted for them"""
+
+    def __init__(self, persistence):
+        """
+        Args:
+            persistence: RelayerPersistence the attestations and envelopes are saved through
+        """
+        self.persistence = persistence
+        state = persistence.load_compliance()
+        # Commitment -> {"envelope_hash", "depositor", "slot", "signature"}
+        self.attestations = state["attestations"]
+        # Commitment -> hex-encoded envelope
+        self.envelopes = state["envelopes"]
+
+    def _save(self):
+        self.persistence.save_compliance({"attestations": self.attestations, "envelopes": self.envelopes})
+
+    def attested(self, commitment_hex, envelope_hash_hex, depositor, event):
+        """
+        Record an attestation indexed from the chain
+
+        Args:
+            commitment_hex: The commitment attested to
+            envelope_hash_hex: Hex-encoded SHA-256 of the envelope
+            depositor: The attesting depositor
+            event: The attestation's slot and signature
+
+        Returns:
+            bool: Whether the attestation is new
+        """
+        if commitment_hex in self.attestations:
+            return False
+        self.attestations[commitment_hex] = {
+            "envelope_hash": envelope_hash_hex,
+            "depositor": depositor,
+            "slot": event.get("slot"),
+            "signature": event.get("signature")
+        }
+        self._save()
+        return True
+
+    def submit(self, commitment_hex, envelope_hex):
+        """
+        Store the envelope for an indexed attestation
+
+        Args:
+            commitment_hex: The commitment the envelope is for
+            envelope_hex: The hex-encoded envelope
+
+        Returns:
+            dict: The stored envelope and its attestation
+
+        Raises:
+            ValueError: If the attestation isn't indexed yet or the envelope doesn't match its hash
+        """
+        try:
+            envelope = bytes.fromhex(envelope_hex)
+        except ValueError:
+            raise ValueError("Envelope must be hex-encoded")
+        if not envelope or len(envelope) > MAX_ENVELOPE_LEN:
+            raise ValueError(f"Envelope must be 1 to {MAX_ENVELOPE_LEN} bytes")
+        attestation = self.attestations.get(commitment_hex)
+        if attestation is None:
+            raise ValueError("No attestation for this commitment is indexed yet")
+        if envelope_hash(envelope) != attestation["envelope_hash"]:
+            raise ValueError("Envelope doesn't match the attested hash")
+        if commitment_hex not in self.envelopes:
+            self.envelopes[commitment_hex] = envelope.hex()
+            self._save()
+            logger.info(f"Stored compliance envelope for {commitment_hex[:10]}...")
+        return self.get(commitment_hex)
+
+    def get(self, commitment_hex):
+        """An attestation with its envelope, or None if no envelope was submitted for it"""
+        if commitment_hex not in self.envelopes:
+            return None
+        return {"commitment": commitment_hex, "envelope": self.envelopes[commitment_hex], **self.attestations[commitment_hex]}
@@ -0,0 +1,193 @@
+"""
+Cluster profiles: the program ID, pool addresses, RPC endpoints and fee defaults per cluster,
+shared with the Rust CLI and SDK (sol-contracts/config, the privax-config crate).
+
+Settings come in layers, each field falling through to the one below it:
+    1. PRIVAX_* environment variables, e.g. PRIVAX_RPC_URL, and the relayer's older names for
+       the same settings (SOLANA_RPC_URL, SOLANA_CONTRACT_ADDRESS, RELAYER_FEE_BPS,
+       RELAYER_MIN_FEE, QUOTE_TTL_SECONDS)
+    2. the TOML file named by PRIVAX_CONFIG
+    3. the built-in profiles, sol-contracts/config/clusters.toml, read from the repository
+       checkout or from /etc/privax/clusters.toml, where the Docker Compose files mount it
+PRIVAX_CLUSTER picks the cluster.
+"""
+import functools
+import logging
+import os
+from dataclasses import dataclass
+from pathlib import Path
+from typing import List, Optional
+
+try:
+    import tomllib
+except ImportError:  # Python < 3.11
+    import tomli as tomllib
+
+logger = logging.getLogger(__name__)
+
+CLUSTERS = ("localnet", "devnet", "mainnet")
+# The Solana CLI's names and monikers for the same clusters
+CLUSTER_ALIASES = {"localhost": "localnet", "l": "localnet", "d": "devnet", "mainnet-beta": "mainnet", "m": "mainnet"}
+BUILTIN_PATHS = (
+    Path(__file__).resolve().parents[2] / "sol-contracts" / "config" / "clusters.toml",
+    Path("/etc/privax/clusters.toml"),
+)
+FIELDS = ("program_id", "rpc_urls", "relayer_url", "mint", "lookup_table")
+FEE_FIELDS = ("relayer_fee_bps", "relayer_min_fee", "quote_ttl_seconds")
+# Environment variables for each field, highest priority first
+ENV_VARIABLES = {
+    "program_id": ("PRIVAX_PROGRAM_ID", "SOLANA_CONTRACT_ADDRESS"),
+    "rpc_urls": ("PRIVAX_RPC_URL", "SOLANA_RPC_URL"),
+    "relayer_url": ("PRIVAX_RELAYER_URL",),
+    "mint": ("PRIVAX_MINT",),
+    "lookup_table": ("PRIVAX_LOOKUP_TABLE",),
+    "relayer_fee_bps": ("PRIVAX_RELAYER_FEE_BPS", "RELAYER_FEE_BPS"),
+    "relayer_min_fee": ("PRIVAX_RELAYER_MIN_FEE", "RELAYER_MIN_FEE"),
+    "quote_ttl_seconds": ("PRIVAX_QUOTE_TTL_SECONDS", "QUOTE_TTL_SECONDS"),
+}
+MAX_FEE_BPS = 10_000
+
+class ConfigError(ValueError):
+    """A profile that is missing a setting or has an invalid one"""
+
+@dataclass(frozen=True)
+class Fees:
+    relayer_fee_bps: int
+    relayer_min_fee: int
+    quote_ttl_seconds: int
+
+@dataclass(frozen=True)
+class Profile:
+    cluster: str
+    program_id: str
+    rpc_urls: List[str]
+    relayer_url: Optional[str]
+    mint: Optional[str]
+    lookup_table: Optional[str]
+    fees: Fees
+
+    @property
+    def rpc_url(self):
+        """The preferred RPC endpoint"""
+        return self.rpc_urls[0]
+
+def cluster_name(name):
+    """The canonical name of a cluster, e.g. "mainnet" for "mainnet-beta" """
+    name = CLUSTER_ALIASES.get(name, name)
+    if name not in CLUSTERS:
+        raise ConfigError(f"Unknown cluster {name!r} (expected localnet, devnet or mainnet)")
+    return name
+
+def _read_toml(path):
+    with open(path, "rb") as f:
+        return tomllib.load(f)
+
+def _flatten(section):
+    """A cluster's table from a config file, with its fees table merged in"""
+    layer = {key: value for key, value in section.items() if key != "fees"}
+    layer.update(section.get("fees", {}))
+    unknown = set(layer) - set(FIELDS) - set(FEE_FIELDS)
+    if unknown:
+        raise ConfigError(f"Unknown settings {', '.join(sorted(unknown))}")
+    return layer
+
+def load_profile(cluster=None, env=None):
+    """
+    Resolve a cluster's profile from the built-ins, PRIVAX_CONFIG and the environment
+
+    Args:
+        cluster: The cluster; PRIVAX_CLUSTER, or the config's default, if None
+        env: The environment to read, os.environ if None
+
+    Returns:
+        Profile: The resolved profile
+
+    Raises:
+        ConfigError: If a setting is missing or invalid
+    """
+    env = os.environ if env is None else env
+    files = [path for path in BUILTIN_PATHS if path.is_file()][:1]
+    if not files:
+        logger.warning("Built-in cluster profiles (clusters.toml) not found; using PRIVAX_CONFIG and the environment only")
+    if env.get("PRIVAX_CONFIG"):
+        files.append(Path(env["PRIVAX_CONFIG"]))
+
+    default_cluster = "localnet"
+    layers = {name: {} for name in CLUSTERS}
+    for path in files:
+        try:
+            data = _read_toml(path)
+        except (OSError, tomllib.TOMLDecodeError) as e:
+            raise ConfigError(f"{path}: {str(e)}")
+        unknown = set(data) - set(CLUSTERS) - {"cluster"}
+        if unknown:
+            raise ConfigError(f"{path}: unknown tables {', '.join(sorted(unknown))}")
+        if "cluster" in data:
+            default_cluster = cluster_name(data["cluster"])
+        for name in CLUSTERS:
+            try:
+                layers[name].update(_flatten(data.get(name, {})))
+            except ConfigError as e:
+                raise ConfigError(f"{path} [{name}]: {str(e)}")
+
+    cluster = cluster_name(cluster or env.get("PRIVAX_CLUSTER") or default_cluster)
+    layer = layers[cluster]
+    for field, variables in ENV_VARIABLES.items():
+        variable = next((variable for variable in variables if env.get(variable)), None)
+        if variable is None:
+            continue
+        value = env[variable]
+        if field == "rpc_urls":
+            value = [url.strip() for url in value.split(",")]
+        elif field in FEE_FIELDS:
+            try:
+                value = int(value)
+            except ValueError:
+                raise ConfigError(f"Invalid {variable}={value!r}")
+        layer[field] = value
+    return _resolve(cluster, layer)
+
+def _resolve(cluster, layer):
+    """Check a cluster's merged settings and build its profile"""
+    from .verifier import base58_decode
+
+    def require(field):
+        if layer.get(field) in (None, "", []):
+            raise ConfigError(f"{cluster} profile: no {field}")
+        return layer[field]
+
+    def address(field):
+        value = layer.get(field)
+        if value is None:
+            return None
+        try:
+            valid = len(base58_decode(value)) == 32
+        except ValueError:
+            valid = False
+        if not valid:
+            raise ConfigError(f"{cluster} profile: invalid {field} {value!r}")
+        return value
+
+    require("program_id")
+    rpc_urls = require("rpc_urls")
+    if any(not url for url in rpc_urls):
+        raise ConfigError(f"{cluster} profile: invalid rpc_urls {rpc_urls!r}")
+    fees = Fees(**{field: int(require(field)) for field in FEE_FIELDS})
+    if not 0 <= fees.relayer_fee_bps <= MAX_FEE_BPS:
+        raise ConfigError(f"{cluster} profile: invalid relayer_fee_bps {fees.relayer_fee_bps}")
+    return Profile(
+        cluster=cluster,
+        program_id=address("program_id"),
+        rpc_urls=list(rpc_urls),
+        relayer_url=layer.get("relayer_url"),
+        mint=address("mint"),
+        lookup_table=address("lookup_table"),
+        fees=fees
+    )
+
+@functools.lru_cache(maxsize=None)
+def profile():
+    """The relayer's profile, resolved once from the process environment"""
+    resolved = load_profile()
+    logger.info(f"Using the {resolved.cluster} profile: program {resolved.program_id}, RPC {', '.join(resolved.rpc_urls)}")
+    return resolved
@@ -0,0 +1,92 @@
+"""
+Liveness and readiness, for load balancers and Kubernetes probes.
+
+`/healthz` only says the process is serving requests; when it fails, restart the relayer.
+`/readyz` says whether the relayer should receive traffic right now: its RPC node answers,
+its index is close to the chain tip, its data directory is writable and, if it uses one, its
+prover answers. A relayer that isn't ready would reject withdrawals or serve a stale tree, so
+traffic should go to another one until it recovers.
+
+The RPC and prover checks use the results of the background polling in api.py rather than
+calling out on every probe, so probes stay cheap and can't pile up on a slow endpoint.
+"""
+import logging
+import os
+import time
+
+logger = logging.getLogger(__name__)
+
+class HealthMonitor:
+    def __init__(self):
+        # Most slots the index may trail the RPC node's confirmed tip by (~1 minute)
+        self.max_lag_slots = int(os.getenv("READY_MAX_LAG_SLOTS", "150"))
+        # How long a successful RPC or prover poll counts for
+        self.stale_after = float(os.getenv("READY_STALE_SECONDS", "60"))
+        self.prover_url = os.getenv("PROVER_URL")
+        self.last_rpc_ok = None
+        self.rpc_error = None
+        self.last_prover_ok = None
+        self.prover_error = None
+
+    def record_rpc(self, error=None):
+        """Record the outcome of a call to the RPC node; error is None when it succeeded"""
+        if error is None:
+            self.last_rpc_ok = time.time()
+        self.rpc_error = error
+
+    def record_prover(self, error=None):
+        """Record the outcome of a prover poll; error is None when it succeeded"""
+        if error is None:
+            self.last_prover_ok = time.time()
+        self.prover_error = error
+
+    def _recent(self, last_ok, error):
+        """Check result for a polled dependency"""
+        if last_ok is None:
+            return {"ok": False, "detail": error or "not reached yet"}
+        age = time.time() - last_ok
+        if age > self.stale_after:
+            return {"ok": False, "detail": f"last reached {int(age)}s ago: {error}"}
+        return {"ok": True, "detail": f"reached {int(age)}s ago"}
+
+    def readiness(self, relayer, chain_tip_slot):
+        """
+        Run the readiness checks
+
+        Args:
+            relayer: The relayer, for its indexed slot and data directory
+            chain_tip_slot: The RPC node's confirmed slot, as last polled
+
+        Returns:
+            tuple: Whether every check passed, and each check's result and detail
+        """
+        checks = {"rpc": self._recent(self.last_rpc_ok, self.rpc_error)}
+
+        if relayer.latest_slot is None:
+            checks["index_lag"] = {"ok": False, "detail": "no slot indexed yet"}
+        elif chain_tip_slot is None:
+            checks["index_lag"] = {"ok": False, "detail": "chain tip unknown"}
+        else:
+            lag = max(chain_tip_slot - relayer.latest_slot, 0)
+            checks["index_lag"] = {
+                "ok": lag <= self.max_lag_slots,
+                "detail": f"{lag} slots behind the tip (at most {self.max_lag_slots})"
+            }
+
+        checks["storage"] = self._check_storage(relayer.persistence.data_dir)
+
+        if self.prover_url:
+            checks["prover"] = self._recent(self.last_prover_ok, self.prover_error)
+
+        return all(check["ok"] for check in checks.values()), checks
+
+    def _check_storage(self, data_dir):
+        """Check that the data directory takes writes, with a small probe file"""
+        probe = data_dir / ".readyz"
+        try:
+            probe.write_text(str(time.time()))
+            probe.unlink()
+            return {"ok": True, "detail": str(data_dir)}
+        except OSError as e:
+            logger.error(f"Data directory {data_dir} is not writable: {str(e)}")
+            return {"ok": False, "detail": f"{data_dir}: {e.strerror or str(e)}"}
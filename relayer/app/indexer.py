@@ -0,0 +1,158 @@
+"""
+Cross-chain indexer: the Solana program's events and the EVM Privax deployment's, in one schema.
+
+The program's events mirror the Solidity contract's (AdminChanged, RelayerAdded, RelayerRemoved,
+DepositOccurred, WithdrawalOccurred), so both chains map onto the same records. PauseToggled
+only exists on Solana, where the pool can be paused.:
+
+    {
+        "id": "<chain>:<tx>:<log_index>",
+        "chain": "solana" or "evm",
+        "network": the cluster name, or the EVM chain id,
+        "event": the shared event name, e.g. "DepositOccurred",
+        "height": slot or block number,
+        "tx": transaction signature or hash,
+        "log_index": position of the event within the transaction,
+        ...the event's fields in snake_case: user, token, amount, commitment, nullifier_hash,
+           recipient, old_admin, new_admin, relayer_address, paused, by
+    }
+
+Addresses are each chain's native form (base58 or 0x checksummed), hashes are hex without 0x,
+and amounts are integers in base units.
+
+The index is separate from the relayer's Merkle tree, which only ever holds the Solana pool's
+commitments: an EVM deposit is recorded here and nowhere else.
+
+INDEXER_CHAINS turns the listeners on, e.g. INDEXER_CHAINS=solana,evm. Newly indexed events are
+checked against the alert rules in ALERT_RULES_FILE (see alerts.py).
+"""
+import logging
+import os
+from collections import defaultdict
+
+logger = logging.getLogger(__name__)
+
+CHAINS = ("solana", "evm")
+EVENTS = ("AdminChanged", "RelayerAdded", "RelayerRemoved", "PauseToggled", "DepositOccurred", "WithdrawalOccurred")
+
+def indexer_chains():
+    """
+    The chains INDEXER_CHAINS asks to index
+
+    Returns:
+        List of chain names; empty when the indexer mode is off
+    """
+    chains = [chain.strip().lower() for chain in os.getenv("INDEXER_CHAINS", "").split(",") if chain.strip()]
+    unknown = [chain for chain in chains if chain not in CHAINS]
+    if unknown:
+        raise ValueError(f"Unknown chains in INDEXER_CHAINS: {', '.join(unknown)}; expected {', '.join(CHAINS)}")
+    return chains
+
+class ChainEventIndex:
+    def __init__(self, persistence, alerts=None):
+        """
+        Initialize the index from the data directory
+
+        Args:
+            persistence: The relayer's persistence, holding events.json
+            alerts: AlertRules newly indexed events are checked against, if any
+        """
+        self.persistence = persistence
+        self.alerts = alerts
+        state = persistence.load_events()
+        self.events = state["events"]
+        self.cursors = state["cursors"]
+        self.ids = {event["id"] for event in self.events}
+
+    def record(self, event):
+        """
+        Add an event in the shared schema
+
+        Listeners may see an event again after a restart, so one already indexed is skipped.
+
+        Args:
+            event: The event, without its id
+
+        Returns:
+            bool: Whether the event was new
+        """
+        if event["chain"] not in CHAINS or event["event"] not in EVENTS:
+            raise ValueError(f"Not a cross-chain event: {event['chain']} {event['event']}")
+        event = {"id": f"{event['chain']}:{event['tx']}:{event['log_index']}", **event}
+        if event["id"] in self.ids:
+            return False
+        self.ids.add(event["id"])
+        self.events.append(event)
+        logger.info(f"Indexed {event['chain']} {event['event']} at height {event['height']}")
+        if self.alerts:
+            self.alerts.check(event)
+        return True
+
+    def cursor(self, chain):
+        """The last block or signature the chain's listener processed, if any"""
+        return self.cursors.get(chain)
+
+    def advance(self, chain, cursor):
+        """
+        Record how far a chain's listener got and save the index
+
+        Args:
+            chain: "solana" or "evm"
+            cursor: The last block number or transaction signature processed
+        """
+        self.cursors[chain] = cursor
+        self.persistence.save_events({"events": self.events, "cursors": self.cursors})
+
+    def get_events(self, chain=None, event=None, token=None, offset=0, limit=100):
+        """
+        Get a page of events in the order they were indexed
+
+        Args:
+            chain: Only this chain's events, if given
+            event: Only events of this name, if given
+            token: Only events for this token, if given
+            offset: Position to start from (the cursor from the previous page)
+            limit: Maximum number of events to return
+
+        Returns:
+            dict: The events, the cursor for the next page, and whether more events follow it.
+                  The cursor counts all indexed events, so it stays valid as new ones arrive.
+        """
+        page = []
+        position = offset
+        while position < len(self.events) and len(page) < limit:
+            entry = self.events[position]
+            position += 1
+            if (chain and entry["chain"] != chain) or (event and entry["event"] != event):
+                continue
+            if token and entry.get("token") != token:
+                continue
+            page.append(entry)
+        return {"events": page, "next_offset": position, "has_more": position < len(self.events)}
+
+    def get_stats(self):
+        """
+        Get deposit and withdrawal totals per chain and token
+
+        Returns:
+            dict: The totals, and each chain's latest indexed height
+        """
+        pools = defaultdict(lambda: {"deposit_count": 0, "total_deposited": 0, "withdrawal_count": 0, "total_withdrawn": 0})
+        heights = {}
+        for entry in self.events:
+            heights[entry["chain"]] = max(entry["height"], heights.get(entry["chain"], 0))
+            if entry["event"] == "DepositOccurred":
+                pool = pools[(entry["chain"], entry["network"], entry["token"])]
+                pool["deposit_count"] += 1
+                pool["total_deposited"] += int(entry["amount"])
+            elif entry["event"] == "WithdrawalOccurred":
+                pool = pools[(entry["chain"], entry["network"], entry["token"])]
+                pool["withdrawal_count"] += 1
+                pool["total_withdrawn"] += int(entry["amount"])
+        return {
+            "pools": [
+                {"chain": chain, "network": network, "token": token, **totals}
+                for (chain, network, token), totals in pools.items()
+            ],
+            "heights": heights
+        }
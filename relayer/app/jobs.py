@@ -0,0 +1,262 @@
+import hashlib
+import hmac
+import json
+import logging
+import os
+import time
+import uuid
+from concurrent.futures import ThreadPoolExecutor
+
+import httpx
+
+from . import metrics
+
+logger = logging.getLogger(__name__)
+
+# Job lifecycle of a relayed withdrawal:
+#   pending -> submitted -> confirmed -> finalized
+# and failed from any non-final state. finalized and failed are final.
+PENDING = "pending"
+SUBMITTED = "submitted"
+CONFIRMED = "confirmed"
+FINALIZED = "finalized"
+FAILED = "failed"
+FINAL_STATUSES = {FINALIZED, FAILED}
+
+# A transaction the cluster hasn't seen this long after submission can no longer land:
+# its blockhash has expired (~150 slots).
+DROPPED_AFTER_SECONDS = 120
+
+# Clients may tag a request with an opaque correlation id, which follows its job through the
+# logs, metric exemplars and callbacks. It never goes into a transaction.
+CORRELATION_ID_HEADER = "X-Correlation-Id"
+MAX_CORRELATION_ID_LENGTH = 64
+
+
+def check_correlation_id(correlation_id):
+    """
+    Reject correlation ids that couldn't be echoed safely in headers, log lines and metrics
+
+    Raises:
+        ValueError: If the id is empty, too long or not visible ASCII
+    """
+    if correlation_id is None:
+        return
+    if not 0 < len(correlation_id) <= MAX_CORRELATION_ID_LENGTH:
+        raise ValueError(f"Correlation id must be 1 to {MAX_CORRELATION_ID_LENGTH} characters")
+    if not all("!" <= char <= "~" for char in correlation_id):
+        raise ValueError("Correlation id must be visible ASCII without spaces")
+
+
+def describe(job):
+    """The job's id for log lines, with the client's correlation id when it gave one"""
+    correlation_id = job.get("correlation_id")
+    return job["job_id"] + (f" (correlation id {correlation_id})" if correlation_id else "")
+
+
+class CallbackNotifier:
+    """Delivers job updates to client callback URLs from a small thread pool"""
+
+    def __init__(self, secret=None, attempts=5, timeout=10):
+        """
+        Args:
+            secret: If set, each callback carries an HMAC-SHA256 of its body in X-Privax-Signature
+            attempts: Delivery attempts per update before giving up
+            timeout: Per-attempt timeout in seconds
+        """
+        self.secret = secret if secret is not None else os.getenv("WEBHOOK_SECRET")
+        self.attempts = attempts
+        self.timeout = timeout
+        self.executor = ThreadPoolExecutor(max_workers=4, thread_name_prefix="callbacks")
+
+    def notify(self, url, job):
+        """Queue delivery of the job's current state to url"""
+        body = json.dumps(job, sort_keys=True)
+        self.executor.submit(
+            self._deliver, url, body, f"job {describe(job)} ({job['status']})", job.get("correlation_id"), self.secret
+        )
+
+    def post(self, url, payload, label, secret):
+        """
+        Queue delivery of any other payload to url, signed with its own secret
+
+        Args:
+            url: URL to POST to
+            payload: JSON-serializable body
+            label: What the payload is, for logs
+            secret: Key for the X-Privax-Signature HMAC
+        """
+        self.executor.submit(self._deliver, url, json.dumps(payload, sort_keys=True), label, None, secret)
+
+    def _deliver(self, url, body, label, correlation_id, secret):
+        headers = {"Content-Type": "application/json"}
+        if correlation_id:
+            headers[CORRELATION_ID_HEADER] = correlation_id
+        if secret:
+            digest = hmac.new(secret.encode("utf-8"), body.encode("utf-8"), hashlib.sha256).hexdigest()
+            headers["X-Privax-Signature"] = f"sha256={digest}"
+        delay = 1
+        for attempt in range(1, self.attempts + 1):
+            try:
+                response = httpx.post(url, content=body, headers=headers, timeout=self.timeout)
+                if response.status_code < 300:
+                    return
+                error = f"HTTP {response.status_code}"
+            except httpx.HTTPError as e:
+                error = str(e)
+            logger.warning(f"Callback for {label} failed, attempt {attempt}/{self.attempts}: {error}")
+            if attempt < self.attempts:
+                time.sleep(delay)
+                delay *= 2
+        metrics.CALLBACK_FAILURES.inc()
+        logger.error(f"Giving up on callback for {label} to {url}")
+
+
+class JobManager:
+    """Tracks relayed withdrawals so clients can poll them or be called back"""
+
+    def __init__(self, persistence, notifier=None):
+        """
+        Args:
+            persistence: RelayerPersistence the jobs are saved through
+            notifier: CallbackNotifier for jobs registered with a callback URL
+        """
+        self.persistence = persistence
+        self.notifier = notifier or CallbackNotifier()
+        self.jobs = persistence.load_jobs()
+
+    def create(self, nullifier_hash_hex, recipient_address, token_address, amount, relayer_fee, callback_url=None,
+               correlation_id=None):
+        """
+        Create a pending job for an accepted withdrawal request
+
+        Returns:
+            dict: The new job
+        """
+        now = time.time()
+        job = {
+            "job_id": uuid.uuid4().hex,
+            "status": PENDING,
+            "nullifier_hash": nullifier_hash_hex,
+            "recipient": recipient_address,
+            "token": token_address,
+            "amount": amount,
+            "relayer_fee": relayer_fee,
+            "callback_url": callback_url,
+            "correlation_id": correlation_id,
+            "signature": None,
+            "slot": None,
+            "fee_charged": None,
+            "error": None,
+            "created_at": now,
+            "updated_at": now,
+            "submitted_at": None
+        }
+        self.jobs[job["job_id"]] = job
+        self.persistence.save_jobs(self.jobs)
+        return job
+
+    def get(self, job_id):
+        """Get a job by id, or None"""
+        return self.jobs.get(job_id)
+
+    def find_by_nullifier(self, nullifier_hash_hex):
+        """Get the open job spending a nullifier, or None"""
+        for job in self.jobs.values():
+            if job["nullifier_hash"] == nullifier_hash_hex and job["status"] not in FINAL_STATUSES:
+                return job
+        return None
+
+    def mark_submitted(self, job_id, signature):
+        """Record the signature of the transaction sent for a job"""
+        return self._transition(self.jobs[job_id], SUBMITTED, signature=signature, submitted_at=time.time())
+
+    def mark_confirmed(self, nullifier_hash_hex, signature=None, slot=None):
+        """
+        Confirm the job spending a nullifier, once its withdrawal is seen on-chain
+
+        Returns:
+            dict: The confirmed job, or None if no open job spends the nullifier
+        """
+        job = self.find_by_nullifier(nullifier_hash_hex)
+        if job is None or job["status"] == CONFIRMED:
+            return None
+        return self._transition(job, CONFIRMED, signature=signature or job["signature"], slot=slot, fee_charged=job["relayer_fee"])
+
+    def mark_finalized(self, job_id):
+        job = self.jobs[job_id]
+        return self._transition(job, FINALIZED, fee_charged=job["relayer_fee"])
+
+    def mark_failed(self, job_id, error):
+        """Fail a job for good; the relayer charged nothing for it"""
+        return self._transition(self.jobs[job_id], FAILED, error=error, fee_charged=0)
+
+    def count_by_status(self):
+        """Number of jobs in each status"""
+        counts = {status: 0 for status in (PENDING, SUBMITTED, CONFIRMED, FINALIZED, FAILED)}
+        for job in self.jobs.values():
+            counts[job["status"]] += 1
+        return counts
+
+    def awaiting_finality(self):
+        """Jobs with a transaction that hasn't finalized or failed yet"""
+        return [job for job in self.jobs.values() if job["status"] in (SUBMITTED, CONFIRMED) and job["signature"]]
+
+    async def check_finality(self, rpc_url, client):
+        """
+        Advance submitted and confirmed jobs from the cluster's signature statuses
+
+        Args:
+            rpc_url: Solana JSON-RPC endpoint
+            client: httpx.AsyncClient to query it with
+        """
+        jobs = self.awaiting_finality()
+        if not jobs:
+            return
+        # getSignatureStatuses takes at most 256 signatures per call
+        for start in range(0, len(jobs), 256):
+            batch = jobs[start:start + 256]
+            response = await client.post(rpc_url, json={
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "getSignatureStatuses",
+                "params": [[job["signature"] for job in batch], {"searchTransactionHistory": True}]
+            })
+            response.raise_for_status()
+            statuses = response.json()["result"]["value"]
+            for job, status in zip(batch, statuses):
+                self._apply_status(job, status)
+
+    def _apply_status(self, job, status):
+        if status is None:
+            if job["status"] == SUBMITTED and time.time() - (job["submitted_at"] or job["created_at"]) > DROPPED_AFTER_SECONDS:
+                self.mark_failed(job["job_id"], "transaction was dropped before landing (blockhash expired)")
+            return
+        if status.get("err") is not None:
+            self.mark_failed(job["job_id"], f"transaction failed: {status['err']}")
+        elif status.get("confirmationStatus") == "finalized":
+            self.mark_finalized(job["job_id"])
+        elif job["status"] == SUBMITTED and status.get("confirmationStatus") == "confirmed":
+            self._transition(job, CONFIRMED, slot=status.get("slot"), fee_charged=job["relayer_fee"])
+
+    def _transition(self, job, status, **fields):
+        if job["status"] in FINAL_STATUSES:
+            raise ValueError(f"Job {job['job_id']} is already {job['status']}")
+        job.update(fields, status=status, updated_at=time.time())
+        self.persistence.save_jobs(self.jobs)
+        if status in (CONFIRMED, FINALIZED):
+            # Jobs persisted before correlation ids existed have none
+            correlation_id = job.get("correlation_id")
+            exemplar = {"correlation_id": correlation_id} if correlation_id else None
+            metrics.CONFIRMATION_LATENCY.observe(job["updated_at"] - job["created_at"], exemplar=exemplar, commitment=status)
+        if status == FINALIZED:
+            metrics.FEES_EARNED.inc(job["fee_charged"], mint=job["token"])
+        logger.info(f"Job {describe(job)} is now {status}")
+        if job["callback_url"] and status != PENDING:
+            self.notifier.notify(job["callback_url"], public_view(job))
+        return job
+
+
+def public_view(job):
+    """A job as shown to clients, without the callback URL"""
+    return {key: value for key, value in job.items() if key != "callback_url"}
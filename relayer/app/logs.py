@@ -0,0 +1,86 @@
+"""
+Logging setup shared by the API server and the snapshot tool.
+
+    LOG_LEVEL    the root level (default INFO)
+    LOG_LEVELS   per-module levels, e.g. app.indexer=debug,uvicorn.access=warning
+    LOG_FORMAT   text (default) or json, one object per line for log shippers
+
+Note strings (privax-note-v1-...) are redacted from every record, whatever logged them.
+"""
+import json
+import logging
+import os
+import re
+import sys
+
+TEXT_FORMAT = "%(asctime)s - %(name)s - %(levelname)s - %(message)s"
+# The amount and both secrets of a backed-up note are digits, hex and dashes
+NOTE_PATTERN = re.compile(r"(privax-note-v1-)[0-9a-fA-F-]+")
+REDACTED = r"\1<redacted>"
+# LogRecord's own attributes; anything else on a record came from `extra=` and goes in the JSON
+RECORD_ATTRIBUTES = set(vars(logging.LogRecord("", 0, "", 0, "", (), None))) | {"message", "asctime"}
+
+
+def redact(text):
+    return NOTE_PATTERN.sub(REDACTED, text)
+
+
+class RedactingFilter(logging.Filter):
+    """Formats the message up front so arguments can't bring a note back in"""
+
+    def filter(self, record):
+        record.msg = redact(record.getMessage())
+        record.args = None
+        return True
+
+
+class RedactingFormatter(logging.Formatter):
+    """Also covers tracebacks, which are formatted after the filters ran"""
+
+    def format(self, record):
+        return redact(super().format(record))
+
+
+class JsonFormatter(RedactingFormatter):
+    def format(self, record):
+        entry = {
+            "timestamp": self.formatTime(record),
+            "level": record.levelname,
+            "logger": record.name,
+            "message": record.getMessage(),
+        }
+        for key, value in vars(record).items():
+            if key not in RECORD_ATTRIBUTES:
+                entry[key] = value
+        if record.exc_info:
+            entry["exception"] = self.formatException(record.exc_info)
+        return redact(json.dumps(entry, default=str))
+
+
+def parse_levels(spec):
+    """`module=level,...` to {module: level}; raises ValueError on a malformed entry"""
+    levels = {}
+    for entry in filter(None, (part.strip() for part in spec.split(","))):
+        module, _, level = entry.partition("=")
+        level = level.strip().upper()
+        if not module.strip() or not isinstance(logging.getLevelName(level), int):
+            raise ValueError(f"invalid LOG_LEVELS entry {entry!r} (expected module=level)")
+        levels[module.strip()] = level
+    return levels
+
+
+def configure():
+    """Sets up the root logger from LOG_LEVEL, LOG_LEVELS and LOG_FORMAT"""
+    log_format = os.getenv("LOG_FORMAT", "text").lower()
+    if log_format not in ("text", "json"):
+        raise ValueError(f"invalid LOG_FORMAT {log_format!r} (expected text or json)")
+    handler = logging.StreamHandler(sys.stderr)
+    handler.addFilter(RedactingFilter())
+    handler.setFormatter(JsonFormatter() if log_format == "json" else RedactingFormatter(TEXT_FORMAT))
+    root = logging.getLogger()
+    for existing in list(root.handlers):
+        root.removeHandler(existing)
+    root.addHandler(handler)
+    root.setLevel(os.getenv("LOG_LEVEL", "INFO").upper())
+    for module, level in parse_levels(os.getenv("LOG_LEVELS", "")).items():
+        logging.getLogger(module).setLevel(level)
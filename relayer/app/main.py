@@ -3,14 +3,13 @@ import os
 import logging
 from dotenv import load_dotenv
 
+from . import logs
+
 # Load environment variables from .env file if it exists
 load_dotenv()
 
-# Configure logging
-logging.basicConfig(
-    level=logging.INFO,
-    format="%(asctime)s - %(name)s - %(levelname)s - %(message)s"
-)
+# Configure logging (LOG_LEVEL, LOG_LEVELS, LOG_FORMAT)
+logs.configure()
 logger = logging.getLogger(__name__)
 
 def main():
@@ -20,13 +19,13 @@ def main():
     
     logger.info(f"Starting relayer API on {host}:{port}")
     
-    # Start uvicorn server
+    # Start uvicorn server; log_config=None leaves its loggers to the root handler set up above
     uvicorn.run(
         "app.api:app",
         host=host,
         port=port,
         reload=False,
-        log_level="info"
+        log_config=None
     )
 
 if __name__ == "__main__":
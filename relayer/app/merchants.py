@@ -0,0 +1,226 @@
+"""
+Merchant webhooks for incoming shielded payments.
+
+A payer paying a merchant announces the note alongside the deposit (the program's
+`announce_note`), encrypted to the merchant's X25519 viewing key:
+
+    ephemeral public key (32 bytes) || ChaCha20-Poly1305 ciphertext and tag
+
+The key is HKDF-SHA256 over the X25519 shared secret with info `privax-note-v1`, the nonce is
+zero (each announcement uses a fresh ephemeral key) and the commitment is the associated data,
+so an envelope can't be replayed against another deposit. The plaintext is a JSON object chosen
+by the payer and merchant, e.g. the note and an order id; `encrypt_note` is the reference
+encoder for wallets.
+
+Merchants register their viewing key and a webhook URL here. Each announced note is tried
+against every registered key; when one decrypts it, the merchant's backend is POSTed the
+deposit and the note's content, signed with a secret handed out at registration. A viewing key
+lets the relayer read the notes paid to it but not spend them.
+"""
+import json
+import logging
+import secrets
+import uuid
+
+from cryptography.exceptions import InvalidTag
+from cryptography.hazmat.primitives import hashes
+from cryptography.hazmat.primitives.asymmetric.x25519 import X25519PrivateKey, X25519PublicKey
+from cryptography.hazmat.primitives.ciphers.aead import ChaCha20Poly1305
+from cryptography.hazmat.primitives.kdf.hkdf import HKDF
+from cryptography.hazmat.primitives.serialization import Encoding, PublicFormat
+
+logger = logging.getLogger(__name__)
+
+NOTE_INFO = b"privax-note-v1"
+NONCE = bytes(12)
+# Mirrors MAX_NOTE_CIPHERTEXT_LEN in the program
+MAX_CIPHERTEXT_LEN = 320
+# Announcing is permissionless, so notes waiting for a deposit that never comes are bounded
+MAX_PENDING_NOTES = 1000
+
+def note_key(shared_secret):
+    """The ChaCha20-Poly1305 key for an X25519 shared secret"""
+    return HKDF(algorithm=hashes.SHA256(), length=32, salt=None, info=NOTE_INFO).derive(shared_secret)
+
+def public_key_hex(viewing_key_hex):
+    """The hex-encoded X25519 public key payers encrypt to, for a hex-encoded viewing key"""
+    private_key = X25519PrivateKey.from_private_bytes(bytes.fromhex(viewing_key_hex))
+    return private_key.public_key().public_bytes(Encoding.Raw, PublicFormat.Raw).hex()
+
+def encrypt_note(public_key_hex, commitment_hex, payload):
+    """
+    Encrypt a note's content to a merchant's viewing key
+
+    Args:
+        public_key_hex: The merchant's hex-encoded X25519 public key
+        commitment_hex: Hex-encoded commitment of the deposit the note is for
+        payload: JSON-serializable note content
+
+    Returns:
+        bytes: The envelope to pass to `announce_note`
+
+    Raises:
+        ValueError: If the envelope would be longer than the program accepts
+    """
+    ephemeral = X25519PrivateKey.generate()
+    shared_secret = ephemeral.exchange(X25519PublicKey.from_public_bytes(bytes.fromhex(public_key_hex)))
+    plaintext = json.dumps(payload, separators=(",", ":")).encode("utf-8")
+    sealed = ChaCha20Poly1305(note_key(shared_secret)).encrypt(NONCE, plaintext, bytes.fromhex(commitment_hex))
+    envelope = ephemeral.public_key().public_bytes(Encoding.Raw, PublicFormat.Raw) + sealed
+    if len(envelope) > MAX_CIPHERTEXT_LEN:
+        raise ValueError(f"Note envelope is {len(envelope)} bytes, more than {MAX_CIPHERTEXT_LEN}")
+    return envelope
+
+def decrypt_note(viewing_key_hex, commitment_hex, envelope):
+    """
+    Open a note envelope with a viewing key
+
+    Returns:
+        The note's content, or None if the envelope isn't for this key or is malformed
+    """
+    if len(envelope) <= 32:
+        return None
+    private_key = X25519PrivateKey.from_private_bytes(bytes.fromhex(viewing_key_hex))
+    try:
+        shared_secret = private_key.exchange(X25519PublicKey.from_public_bytes(envelope[:32]))
+        plaintext = ChaCha20Poly1305(note_key(shared_secret)).decrypt(NONCE, envelope[32:], bytes.fromhex(commitment_hex))
+        return json.loads(plaintext)
+    except (InvalidTag, ValueError):
+        # A low-order ephemeral key fails the exchange, and other keys' notes fail the tag
+        return None
+
+def public_view(merchant):
+    """A merchant's registration without its viewing key, webhook secret or owning API key"""
+    return {key: merchant[key] for key in ("merchant_id", "public_key", "webhook_url", "created_at")}
+
+class MerchantRegistry:
+    """Registered viewing keys, and the webhooks for notes that decrypt under them"""
+
+    def __init__(self, persistence, notifier):
+        """
+        Args:
+            persistence: RelayerPersistence the registrations are saved through
+            notifier: CallbackNotifier the webhooks are delivered with
+        """
+        self.persistence = persistence
+        self.notifier = notifier
+        state = persistence.load_merchants()
+        self.merchants = state["merchants"]
+        # Commitments already delivered, so a slot the Geyser plugin retries isn't delivered twice
+        self.delivered = set(state["delivered"])
+        # Notes announced before their deposit was indexed, by commitment
+        self.pending = {}
+
+    def _save(self):
+        self.persistence.save_merchants({"merchants": self.merchants, "delivered": sorted(self.delivered)})
+
+    def register(self, viewing_key_hex, webhook_url, owner, now):
+        """
+        Register a viewing key and the URL notes that decrypt under it are POSTed to
+
+        Args:
+            viewing_key_hex: Hex-encoded 32-byte X25519 private key
+            webhook_url: The merchant's endpoint
+            owner: API key of the registering client; only it can see or remove the registration
+            now: Unix time of the registration
+
+        Returns:
+            dict: The registration, with the webhook secret. The secret isn't shown again.
+
+        Raises:
+            ValueError: If the viewing key isn't 32 hex-encoded bytes
+        """
+        try:
+            if len(bytes.fromhex(viewing_key_hex)) != 32:
+                raise ValueError
+        except ValueError:
+            raise ValueError("Viewing key must be 32 hex-encoded bytes")
+        merchant = {
+            "merchant_id": uuid.uuid4().hex,
+            "public_key": public_key_hex(viewing_key_hex),
+            "viewing_key": viewing_key_hex.lower(),
+            "webhook_url": webhook_url,
+            "webhook_secret": secrets.token_hex(32),
+            "owner": owner,
+            "created_at": now
+        }
+        self.merchants[merchant["merchant_id"]] = merchant
+        self._save()
+        logger.info(f"Registered merchant {merchant['merchant_id']} for viewing key {merchant['public_key'][:10]}...")
+        return {**public_view(merchant), "webhook_secret": merchant["webhook_secret"]}
+
+    def get(self, merchant_id, owner):
+        """A registration, or None if it doesn't exist or belongs to another API key"""
+        merchant = self.merchants.get(merchant_id)
+        if merchant is None or merchant["owner"] != owner:
+            return None
+        return public_view(merchant)
+
+    def remove(self, merchant_id, owner):
+        """
+        Remove a registration
+
+        Returns:
+            bool: Whether the API key had such a registration
+        """
+        if self.get(merchant_id, owner) is None:
+            return False
+        del self.merchants[merchant_id]
+        self._save()
+        logger.info(f"Removed merchant {merchant_id}")
+        return True
+
+    def announce(self, commitment_hex, ciphertext_hex, deposit, event):
+        """
+        Handle a note announced for a commitment
+
+        Args:
+            commitment_hex: The commitment the note is for
+            ciphertext_hex: The hex-encoded envelope
+            deposit: The indexed deposit of the commitment, or None if it isn't indexed yet
+            event: The announcement's slot and signature
+
+        Returns:
+            bool: Whether a merchant's key decrypted the note
+        """
+        if commitment_hex in self.delivered:
+            return False
+        if deposit is None:
+            # The deposit may come later in the slot; it is matched when indexed
+            self.pending[commitment_hex] = (ciphertext_hex, event)
+            if len(self.pending) > MAX_PENDING_NOTES:
+                del self.pending[next(iter(self.pending))]
+            return False
+        envelope = bytes.fromhex(ciphertext_hex)
+        for merchant in self.merchants.values():
+            note = decrypt_note(merchant["viewing_key"], commitment_hex, envelope)
+            if note is None:
+                continue
+            self.delivered.add(commitment_hex)
+            self._save()
+            self.notifier.post(
+                merchant["webhook_url"],
+                {
+                    "event": "payment",
+                    "merchant_id": merchant["merchant_id"],
+                    "commitment": commitment_hex,
+                    "leaf_index": deposit.get("leaf_index"),
+                    "mint": deposit.get("token"),
+                    "amount": deposit.get("amount"),
+                    "slot": event.get("slot"),
+                    "signature": event.get("signature"),
+                    "note": note
+                },
+                f"payment {commitment_hex[:10]}... to merchant {merchant['merchant_id']}",
+                merchant["webhook_secret"]
+            )
+            logger.info(f"Note for {commitment_hex[:10]}... decrypted for merchant {merchant['merchant_id']}")
+            return True
+        return False
+
+    def deposit_indexed(self, commitment_hex, deposit):
+        """Match a newly indexed deposit with a note announced before it"""
+        pending = self.pending.pop(commitment_hex, None)
+        if pending is not None:
+            ciphertext_hex, event = pending
+            self.announce(commitment_hex, ciphertext_hex, deposit, event)
@@ -58,8 +58,18 @@ class MerkleTree:
             raise ValueError("Leaf value must be a hex string.")
         
         self.leaves.append(leaf_value_hex)
-        
-        # Rebuild the tree
+        self._rebuild()
+
+    @classmethod
+    def from_leaves(cls, leaves):
+        """Builds a tree over existing leaves at once, rather than rebuilding it per leaf."""
+        tree = cls(initialize_with_zero=False)
+        tree.leaves = list(leaves)
+        tree._rebuild()
+        return tree
+
+    def _rebuild(self):
+        """Recomputes every level and the root from the leaves."""
         if not self.leaves:
             self.tree_levels = []
             self.merkle_root = None
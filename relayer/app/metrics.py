@@ -0,0 +1,139 @@
+import threading
+import time
+
+# A minimal Prometheus registry rendering the text exposition format, enough for the
+# relayer's handful of counters, gauges and one histogram. Scrapers asking for OpenMetrics
+# get that instead, which adds the histogram's exemplars: the labels of one recent
+# observation per bucket, such as the correlation id of a withdrawal.
+TEXT_CONTENT_TYPE = "text/plain; version=0.0.4"
+OPENMETRICS_CONTENT_TYPE = "application/openmetrics-text; version=1.0.0; charset=utf-8"
+
+
+class Metric:
+    def __init__(self, name, help_text, kind, labelnames=()):
+        self.name = name
+        self.help_text = help_text
+        self.kind = kind
+        self.labelnames = labelnames
+        self.values = {}  # label values -> value
+        self.lock = threading.Lock()
+
+    def _key(self, labels):
+        if set(labels) != set(self.labelnames):
+            raise ValueError(f"{self.name} takes labels {self.labelnames}")
+        return tuple(str(labels[name]) for name in self.labelnames)
+
+    def _labels(self, key, extra=()):
+        return label_set(list(zip(self.labelnames, key)) + list(extra))
+
+    def render(self, openmetrics=False):
+        # OpenMetrics names a counter's family without the _total its samples carry
+        family = self.name.removesuffix("_total") if openmetrics and self.kind == "counter" else self.name
+        lines = [f"# HELP {family} {self.help_text}", f"# TYPE {family} {self.kind}"]
+        with self.lock:
+            for key, value in sorted(self.values.items()):
+                lines.extend(self._samples(key, value, openmetrics))
+        return lines
+
+    def _samples(self, key, value, openmetrics):
+        return [f"{self.name}{self._labels(key)} {value}"]
+
+
+class Counter(Metric):
+    def __init__(self, name, help_text, labelnames=()):
+        super().__init__(name, help_text, "counter", labelnames)
+
+    def inc(self, amount=1, **labels):
+        key = self._key(labels)
+        with self.lock:
+            self.values[key] = self.values.get(key, 0) + amount
+
+
+class Gauge(Metric):
+    def __init__(self, name, help_text, labelnames=()):
+        super().__init__(name, help_text, "gauge", labelnames)
+
+    def set(self, value, **labels):
+        key = self._key(labels)
+        with self.lock:
+            self.values[key] = value
+
+
+class Histogram(Metric):
+    def __init__(self, name, help_text, buckets, labelnames=()):
+        super().__init__(name, help_text, "histogram", labelnames)
+        self.buckets = sorted(buckets)
+        self.exemplars = {}  # label values -> the latest exemplar in each bucket, +Inf last
+
+    def observe(self, value, exemplar=None, **labels):
+        """Count an observation; `exemplar` labels it in the smallest bucket it falls in"""
+        key = self._key(labels)
+        with self.lock:
+            counts, total, count = self.values.get(key, ([0] * len(self.buckets), 0, 0))
+            counts = [bucket_count + (value <= bound) for bucket_count, bound in zip(counts, self.buckets)]
+            self.values[key] = (counts, total + value, count + 1)
+            if exemplar:
+                bucket = next((i for i, bound in enumerate(self.buckets) if value <= bound), len(self.buckets))
+                self.exemplars.setdefault(key, [None] * (len(self.buckets) + 1))[bucket] = (exemplar, value, time.time())
+
+    def _samples(self, key, value, openmetrics):
+        counts, total, count = value
+        exemplars = self.exemplars.get(key, []) if openmetrics else []
+
+        def suffix(bucket):
+            if bucket >= len(exemplars) or exemplars[bucket] is None:
+                return ""
+            labels, observed, timestamp = exemplars[bucket]
+            return f" # {label_set(list(labels.items()))} {observed} {timestamp:.3f}"
+
+        samples = [
+            f"{self.name}_bucket{self._labels(key, [('le', str(bound))])} {bucket_count}{suffix(i)}"
+            for i, (bound, bucket_count) in enumerate(zip(self.buckets, counts))
+        ]
+        samples.append(f"{self.name}_bucket{self._labels(key, [('le', '+Inf')])} {count}{suffix(len(self.buckets))}")
+        samples.append(f"{self.name}_sum{self._labels(key)} {total}")
+        samples.append(f"{self.name}_count{self._labels(key)} {count}")
+        return samples
+
+
+WITHDRAWAL_REQUESTS = Counter(
+    "privax_relayer_withdrawal_requests_total", "Withdrawal requests, by outcome", ("outcome",)
+)
+JOBS = Gauge("privax_relayer_jobs", "Withdrawal jobs, by status", ("status",))
+QUEUE_DEPTH = Gauge("privax_relayer_queue_depth", "Withdrawal jobs not yet finalized or failed")
+CONFIRMATION_LATENCY = Histogram(
+    "privax_relayer_confirmation_latency_seconds",
+    "Seconds from accepting a withdrawal to it reaching a commitment level",
+    (1, 2, 5, 10, 20, 30, 60, 120, 300, 600),
+    ("commitment",)
+)
+RPC_ERRORS = Counter("privax_relayer_rpc_errors_total", "Failed Solana RPC calls, by method", ("method",))
+CALLBACK_FAILURES = Counter("privax_relayer_callback_failures_total", "Job and merchant callbacks given up on after retries")
+FEES_EARNED = Counter("privax_relayer_fees_earned_total", "Relayer fees from finalized withdrawals, in base units", ("mint",))
+ALERTS = Counter("privax_relayer_alerts_total", "Alert rules fired by indexed events, by rule", ("rule",))
+INDEXED_EVENTS = Counter("privax_relayer_indexed_events_total", "Events streamed by the Geyser plugin, by kind and result", ("kind", "result"))
+INDEXED_SLOT = Gauge("privax_relayer_indexed_slot", "Latest slot indexed from the Geyser plugin")
+CHAIN_TIP_SLOT = Gauge("privax_relayer_chain_tip_slot", "Latest slot reported by the RPC node")
+LAG_SLOTS = Gauge("privax_relayer_lag_slots", "Slots the index is behind the chain tip")
+
+REGISTRY = [
+    WITHDRAWAL_REQUESTS, JOBS, QUEUE_DEPTH, CONFIRMATION_LATENCY, RPC_ERRORS, CALLBACK_FAILURES,
+    FEES_EARNED, ALERTS, INDEXED_EVENTS, INDEXED_SLOT, CHAIN_TIP_SLOT, LAG_SLOTS
+]
+
+
+def label_set(pairs):
+    if not pairs:
+        return ""
+    escaped = (value.replace("\\", "\\\\").replace('"', '\\"').replace("\n", "\\n") for _, value in pairs)
+    return "{" + ",".join(f'{name}="{value}"' for (name, _), value in zip(pairs, escaped)) + "}"
+
+
+def render(openmetrics=False):
+    """All metrics in the Prometheus text exposition format, or in OpenMetrics with exemplars"""
+    lines = []
+    for metric in REGISTRY:
+        lines.extend(metric.render(openmetrics))
+    if openmetrics:
+        lines.append("# EOF")
+    return "\n".join(lines) + "\n"
@@ -22,9 +22,23 @@ class RelayerPersistence:
         self.leaves_file = self.data_dir / "leaves.json"
         self.deposits_file = self.data_dir / "deposits.json"
         self.withdrawals_file = self.data_dir / "withdrawals.json"
+        self.jobs_file = self.data_dir / "jobs.json"
+        self.events_file = self.data_dir / "events.json"
+        self.archived_roots_file = self.data_dir / "archived_roots.json"
+        self.merchants_file = self.data_dir / "merchants.json"
+        self.compliance_file = self.data_dir / "compliance.json"
         
         logger.info(f"Persistence initialized with data directory: {self.data_dir}")
 
+    def has_state(self):
+        """
+        Whether the data directory already holds an index
+        
+        Returns:
+            bool: True once the relayer has saved its Merkle tree leaves
+        """
+        return self.leaves_file.exists()
+
     def save_nullifiers(self, nullifiers):
         """
         Save the used nullifiers set to disk
@@ -159,4 +173,174 @@ class RelayerPersistence:
             return withdrawals
         except Exception as e:
             logger.error(f"Error loading withdrawals: {str(e)}")
-            return {} 
\ No newline at end of file
+            return {} 
+
+    def save_jobs(self, jobs):
+        """
+        Save withdrawal jobs to disk
+        
+        Args:
+            jobs: Dict mapping job id to job state
+        """
+        try:
+            with open(self.jobs_file, "w") as f:
+                json.dump(jobs, f)
+            logger.debug(f"Saved {len(jobs)} jobs to {self.jobs_file}")
+        except Exception as e:
+            logger.error(f"Error saving jobs: {str(e)}")
+
+    def load_jobs(self):
+        """
+        Load withdrawal jobs from disk
+        
+        Returns:
+            Dict mapping job id to job state
+        """
+        if not self.jobs_file.exists():
+            logger.debug(f"Jobs file {self.jobs_file} does not exist, returning empty dict")
+            return {}
+        
+        try:
+            with open(self.jobs_file, "r") as f:
+                jobs = json.load(f)
+            logger.debug(f"Loaded {len(jobs)} jobs from {self.jobs_file}")
+            return jobs
+        except Exception as e:
+            logger.error(f"Error loading jobs: {str(e)}")
+            return {}
+
+    def save_events(self, index):
+        """
+        Save the cross-chain event index to disk
+        
+        Args:
+            index: Dict with the indexed events and each chain's listener cursor
+        """
+        try:
+            with open(self.events_file, "w") as f:
+                json.dump(index, f)
+            logger.debug(f"Saved {len(index['events'])} cross-chain events to {self.events_file}")
+        except Exception as e:
+            logger.error(f"Error saving cross-chain events: {str(e)}")
+
+    def load_events(self):
+        """
+        Load the cross-chain event index from disk
+        
+        Returns:
+            Dict with the indexed events and each chain's listener cursor
+        """
+        if not self.events_file.exists():
+            logger.debug(f"Events file {self.events_file} does not exist, returning an empty index")
+            return {"events": [], "cursors": {}}
+        
+        try:
+            with open(self.events_file, "r") as f:
+                index = json.load(f)
+            logger.debug(f"Loaded {len(index['events'])} cross-chain events from {self.events_file}")
+            return index
+        except Exception as e:
+            logger.error(f"Error loading cross-chain events: {str(e)}")
+            return {"events": [], "cursors": {}}
+
+    def save_archived_roots(self, pages):
+        """
+        Save the on-chain root archive to disk
+
+        Args:
+            pages: Dict mapping archive page number (as a string) to its hex-encoded roots
+        """
+        try:
+            with open(self.archived_roots_file, "w") as f:
+                json.dump(pages, f)
+            logger.debug(f"Saved {len(pages)} root archive pages to {self.archived_roots_file}")
+        except Exception as e:
+            logger.error(f"Error saving archived roots: {str(e)}")
+
+    def load_archived_roots(self):
+        """
+        Load the on-chain root archive from disk
+
+        Returns:
+            Dict mapping archive page number (as a string) to its hex-encoded roots
+        """
+        if not self.archived_roots_file.exists():
+            logger.debug(f"Archived roots file {self.archived_roots_file} does not exist, returning empty dict")
+            return {}
+
+        try:
+            with open(self.archived_roots_file, "r") as f:
+                pages = json.load(f)
+            logger.debug(f"Loaded {len(pages)} root archive pages from {self.archived_roots_file}")
+            return pages
+        except Exception as e:
+            logger.error(f"Error loading archived roots: {str(e)}")
+            return {}
+
+    def save_merchants(self, state):
+        """
+        Save merchant registrations to disk
+
+        Args:
+            state: Dict with the registrations by merchant id and the commitments already delivered
+        """
+        try:
+            with open(self.merchants_file, "w") as f:
+                json.dump(state, f)
+            logger.debug(f"Saved {len(state['merchants'])} merchants to {self.merchants_file}")
+        except Exception as e:
+            logger.error(f"Error saving merchants: {str(e)}")
+
+    def load_merchants(self):
+        """
+        Load merchant registrations from disk
+
+        Returns:
+            Dict with the registrations by merchant id and the commitments already delivered
+        """
+        if not self.merchants_file.exists():
+            logger.debug(f"Merchants file {self.merchants_file} does not exist, returning no merchants")
+            return {"merchants": {}, "delivered": []}
+
+        try:
+            with open(self.merchants_file, "r") as f:
+                state = json.load(f)
+            logger.debug(f"Loaded {len(state['merchants'])} merchants from {self.merchants_file}")
+            return state
+        except Exception as e:
+            logger.error(f"Error loading merchants: {str(e)}")
+            return {"merchants": {}, "delivered": []}
+
+    def save_compliance(self, state):
+        """
+        Save compliance attestations and envelopes to disk
+
+        Args:
+            state: Dict with the on-chain attestations and the submitted envelopes, by commitment
+        """
+        try:
+            with open(self.compliance_file, "w") as f:
+                json.dump(state, f)
+            logger.debug(f"Saved {len(state['envelopes'])} compliance envelopes to {self.compliance_file}")
+        except Exception as e:
+            logger.error(f"Error saving compliance envelopes: {str(e)}")
+
+    def load_compliance(self):
+        """
+        Load compliance attestations and envelopes from disk
+
+        Returns:
+            Dict with the on-chain attestations and the submitted envelopes, by commitment
+        """
+        if not self.compliance_file.exists():
+            logger.debug(f"Compliance file {self.compliance_file} does not exist, returning no envelopes")
+            return {"attestations": {}, "envelopes": {}}
+
+        try:
+            with open(self.compliance_file, "r") as f:
+                state = json.load(f)
+            logger.debug(f"Loaded {len(state['envelopes'])} compliance envelopes from {self.compliance_file}")
+            return state
+        except Exception as e:
+            logger.error(f"Error loading compliance envelopes: {str(e)}")
+            return {"attestations": {}, "envelopes": {}}
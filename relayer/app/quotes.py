@@ -0,0 +1,112 @@
+import json
+import logging
+import os
+import time
+
+from cryptography.exceptions import InvalidSignature
+from cryptography.hazmat.primitives.asymmetric.ed25519 import Ed25519PrivateKey, Ed25519PublicKey
+from cryptography.hazmat.primitives.serialization import Encoding, PublicFormat
+
+from .config import profile
+from .verifier import base58_decode, base58_encode
+
+logger = logging.getLogger(__name__)
+
+QUOTE_DOMAIN = "privax-relayer-quote-v1"
+
+
+class QuoteRejected(ValueError):
+    """A fee quote the relayer won't honor"""
+
+
+def quote_message(quote):
+    """
+    The bytes a quote's signature covers
+
+    Clients verify a quote with the relayer's public key over this same message:
+    "privax-relayer-quote-v1:<relayer>:<mint>:<amount>:<fee>:<expires_at>"
+    """
+    fields = [QUOTE_DOMAIN, quote["relayer"], quote["mint"], quote["amount"], quote["fee"], quote["expires_at"]]
+    return ":".join(str(field) for field in fields).encode("utf-8")
+
+
+class QuoteSigner:
+    """Prices withdrawals and signs fee quotes with the relayer's key"""
+
+    def __init__(self, keypair_path=None, fee_bps=None, min_fee=None, ttl=None):
+        """
+        Args:
+            keypair_path: Solana keypair file (JSON array of 64 bytes) of the relayer
+            fee_bps: Fee in basis points of the withdrawn amount; the profile's by default
+            min_fee: Minimum fee, in the pool token's base units; the profile's by default
+            ttl: Seconds a quote stays valid; the profile's by default
+        """
+        keypair_path = keypair_path or os.getenv("RELAYER_KEYPAIR")
+        if keypair_path:
+            with open(keypair_path, "r") as f:
+                secret = bytes(json.load(f))
+            self.key = Ed25519PrivateKey.from_private_bytes(secret[:32])
+        else:
+            logger.warning("RELAYER_KEYPAIR is not set; signing quotes with a temporary key")
+            self.key = Ed25519PrivateKey.generate()
+        self.relayer = base58_encode(self.key.public_key().public_bytes(Encoding.Raw, PublicFormat.Raw))
+        fees = profile().fees
+        self.fee_bps = fee_bps if fee_bps is not None else fees.relayer_fee_bps
+        self.min_fee = min_fee if min_fee is not None else fees.relayer_min_fee
+        self.ttl = ttl if ttl is not None else fees.quote_ttl_seconds
+
+    def quote(self, mint, amount):
+        """
+        Quote the fee for relaying a withdrawal
+
+        Args:
+            mint: Token (mint) address of the pool
+            amount: Amount to withdraw, in base units
+
+        Returns:
+            dict: The quote, signed by the relayer and valid until expires_at
+
+        Raises:
+            QuoteRejected: If the fee would take the whole amount
+        """
+        fee = max(amount * self.fee_bps // 10000, self.min_fee)
+        if fee >= amount:
+            raise QuoteRejected(f"Amount is too small to cover the relayer fee of {fee}")
+        quote = {
+            "relayer": self.relayer,
+            "mint": mint,
+            "amount": amount,
+            "fee": fee,
+            "expires_at": int(time.time()) + self.ttl
+        }
+        quote["signature"] = self.key.sign(quote_message(quote)).hex()
+        return quote
+
+    def check(self, quote, mint, amount):
+        """
+        Check a quote bound into a withdrawal request was issued by this relayer for it
+
+        Args:
+            quote: The quote as returned by `quote`
+            mint: Token (mint) address being withdrawn
+            amount: Amount being withdrawn
+
+        Returns:
+            int: The quoted fee
+
+        Raises:
+            QuoteRejected: If the quote is not this relayer's, was altered, is for another
+                           withdrawal, or has expired
+        """
+        if quote["relayer"] != self.relayer:
+            raise QuoteRejected("Quote was issued by another relayer")
+        try:
+            public_key = Ed25519PublicKey.from_public_bytes(base58_decode(quote["relayer"]))
+            public_key.verify(bytes.fromhex(quote["signature"]), quote_message(quote))
+        except (InvalidSignature, ValueError):
+            raise QuoteRejected("Invalid quote signature")
+        if quote["mint"] != mint or quote["amount"] != amount:
+            raise QuoteRejected("Quote is for another token or amount")
+        if quote["expires_at"] < time.time():
+            raise QuoteRejected("Quote has expired")
+        return quote["fee"]
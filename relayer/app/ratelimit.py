@@ -0,0 +1,42 @@
+import time
+
+
+class RateLimiter:
+    """Token-bucket rate limits, one bucket per client (an IP address or an API key)"""
+
+    # Above this many tracked clients, buckets that have refilled are dropped
+    MAX_TRACKED = 10000
+
+    def __init__(self, per_minute, burst=None):
+        """
+        Args:
+            per_minute: Sustained requests allowed per client per minute
+            burst: Requests a client may make at once; defaults to per_minute
+        """
+        self.rate = per_minute / 60
+        self.capacity = burst or per_minute
+        self.buckets = {}  # client -> (tokens, last refill time)
+
+    def acquire(self, client):
+        """
+        Take a request from the client's bucket
+
+        Returns:
+            float: 0 if the request is allowed, otherwise seconds until it would be
+        """
+        now = time.monotonic()
+        tokens, updated = self.buckets.get(client, (self.capacity, now))
+        tokens = min(self.capacity, tokens + (now - updated) * self.rate)
+        if tokens < 1:
+            self.buckets[client] = (tokens, now)
+            return (1 - tokens) / self.rate
+        self.buckets[client] = (tokens - 1, now)
+        if len(self.buckets) > self.MAX_TRACKED:
+            self._prune(now)
+        return 0
+
+    def _prune(self, now):
+        full = [client for client, (tokens, updated) in self.buckets.items()
+                if tokens + (now - updated) * self.rate >= self.capacity]
+        for client in full:
+            del self.buckets[client]
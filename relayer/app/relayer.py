@@ -1,15 +1,41 @@
 from .merkle import MerkleTree, ZERO_COMMITMENT
 from .persistence import RelayerPersistence
+from .snapshot import build_snapshot, load_snapshot, import_snapshot
+from .jobs import JobManager, check_correlation_id, describe, public_view
+from .verifier import verify_split_withdrawal, verify_withdrawal
+from .quotes import QuoteSigner
+from .analytics import pool_analytics
+from .merchants import MerchantRegistry
+from .compliance import ComplianceEnvelopes
+from . import metrics
 import logging
 import os
+import time
 
 logger = logging.getLogger(__name__)
 
+def matches(info, mint, from_time, to_time):
+    """Whether an indexed deposit or withdrawal is of `mint` and indexed in [from_time, to_time)"""
+    if mint is not None and info.get("token") != mint:
+        return False
+    if from_time is None and to_time is None:
+        return True
+    timestamp = info.get("timestamp")
+    if timestamp is None:
+        return False
+    return (from_time is None or timestamp >= from_time) and (to_time is None or timestamp < to_time)
+
 class Relayer:
     def __init__(self, data_dir=None):
         # Initialize persistence
         self.persistence = RelayerPersistence(data_dir or os.getenv("DATA_DIR", "./data"))
         
+        # A new node starts from a snapshot (a file, or a peer's /snapshot) instead of an empty index
+        bootstrap = os.getenv("BOOTSTRAP_SNAPSHOT")
+        if bootstrap and not self.persistence.has_state():
+            logger.info(f"Bootstrapping from snapshot {bootstrap}")
+            import_snapshot(load_snapshot(bootstrap), self.persistence)
+        
         # Load state from disk
         self.used_nullifiers = self.persistence.load_nullifiers()
         self.deposits = self.persistence.load_deposits()
@@ -22,9 +48,7 @@ class Relayer:
         # If we have persisted leaves, don't initialize with zero commitment
         if persisted_leaves:
             logger.info(f"Initializing Merkle tree with {len(persisted_leaves)} persisted leaves")
-            self.merkle_tree = MerkleTree(initialize_with_zero=False)
-            for leaf in persisted_leaves:
-                self.merkle_tree.add_leaf(leaf)
+            self.merkle_tree = MerkleTree.from_leaves(persisted_leaves)
         else:
             # Initialize with default zero commitment
             logger.info("Initializing Merkle tree with default zero commitment")
@@ -32,10 +56,31 @@ class Relayer:
             # Persist the zero commitment
             self.persistence.save_leaves(self.merkle_tree.leaves)
         
+        # Latest on-chain tree state, recent roots and slot, as streamed by the Geyser plugin
+        self.onchain_tree = None
+        self.onchain_roots = []
+        self.latest_slot = None
+
+        # The program's root archive, by page, and whether the tree accepts proofs against it
+        self.archived_roots = self.persistence.load_archived_roots()
+        self.accept_archived_roots = False
+        
+        # Relayed withdrawals, for status polling and callbacks
+        self.jobs = JobManager(self.persistence)
+        
+        # Merchant viewing keys, whose announced notes are delivered to the merchant's webhook
+        self.merchants = MerchantRegistry(self.persistence, self.jobs.notifier)
+
+        # Compliance attestations of institutional pools, and the envelopes submitted for them
+        self.compliance = ComplianceEnvelopes(self.persistence)
+        
+        # Fee quotes, signed with the relayer's key
+        self.quotes = QuoteSigner()
+        
         logger.info(f"Relayer initialized with {len(self.merkle_tree.leaves)} leaves and {len(self.used_nullifiers)} used nullifiers")
         logger.info(f"Current Merkle root: {self.merkle_tree.get_merkle_root()}")
 
-    def process_deposit(self, user_address, token_address, amount, commitment_hex):
+    def process_deposit(self, user_address, token_address, amount, commitment_hex, slot=None):
         """
         Process a deposit event from the blockchain
         
@@ -44,15 +89,19 @@ class Relayer:
             token_address: The address of the token being deposited
             amount: The amount being deposited
             commitment_hex: The commitment hash for this deposit
+            slot: The slot of the transaction that emitted the event, if known
         """
         logger.info(f"Processing deposit: {commitment_hex[:10]}...")
         
-        # Store deposit info
+        # Store deposit info. The timestamp is when it was indexed, which trails the block by the
+        # listener's delay; history queries filter on it.
         self.deposits[commitment_hex] = {
             "user": user_address,
             "token": token_address,
             "amount": amount,
-            "timestamp": None  # Would be set to block timestamp in a real implementation
+            "leaf_index": len(self.merkle_tree.leaves),
+            "slot": slot,
+            "timestamp": int(time.time())
         }
         
         # Add commitment to Merkle tree
@@ -61,11 +110,12 @@ class Relayer:
         # Persist state
         self.persistence.save_deposits(self.deposits)
         self.persistence.save_leaves(self.merkle_tree.leaves)
+        self.merchants.deposit_indexed(commitment_hex, self.deposits[commitment_hex])
         
         logger.info(f"Deposit processed. New Merkle Root: {self.merkle_tree.get_merkle_root()[:10]}...")
         return self.merkle_tree.get_merkle_root()
 
-    def process_withdrawal(self, nullifier_hash_hex, recipient_address, token_address, amount):
+    def process_withdrawal(self, nullifier_hash_hex, recipient_address, token_address, amount, signature=None, slot=None):
         """
         Process a withdrawal event from the blockchain
         
@@ -74,18 +124,25 @@ class Relayer:
             recipient_address: The address receiving the withdrawal
             token_address: The token address being withdrawn
             amount: The amount being withdrawn
+            signature: The signature of the transaction that emitted the event, if known
+            slot: The slot of that transaction, if known
         """
         logger.info(f"Processing withdrawal event for nullifier: {nullifier_hash_hex[:10]}...")
         
+        # Confirm the job if this relayer accepted the withdrawal
+        self.jobs.mark_confirmed(nullifier_hash_hex, signature, slot)
+        
         # Mark the nullifier as used (even though it's already used on-chain)
         self.used_nullifiers.add(nullifier_hash_hex)
         
-        # Store withdrawal info
+        # Store withdrawal info, keeping when a withdrawal this relayer accepted was first indexed
+        previous = self.withdrawals.get(nullifier_hash_hex) or {}
         self.withdrawals[nullifier_hash_hex] = {
             "recipient": recipient_address,
             "token": token_address,
             "amount": amount,
-            "timestamp": None  # Would be set to block timestamp in a real implementation
+            "slot": slot,
+            "timestamp": previous.get("timestamp") or int(time.time())
         }
         
         # Persist state
@@ -95,6 +152,99 @@ class Relayer:
         logger.info(f"Withdrawal event processed for nullifier: {nullifier_hash_hex[:10]}...")
         return True
 
+    def ingest_geyser_events(self, slot, events):
+        """
+        Index a slot's events as streamed by the Geyser plugin (sol-contracts/geyser)
+        
+        The plugin retries a slot until it is accepted and RPC listeners may report the
+        same events, so commitments and nullifiers that are already indexed are skipped.
+        
+        Args:
+            slot: The slot the events happened in
+            events: The slot's events in block order
+            
+        Returns:
+            dict: How many events were new and how many were already indexed
+        """
+        self.latest_slot = max(slot, self.latest_slot or 0)
+        metrics.INDEXED_SLOT.set(self.latest_slot)
+        accepted = duplicates = 0
+        for event in events:
+            kind = event.get("kind")
+            if kind == "commitment":
+                commitment = event["commitment"]
+                if commitment in self.deposits or commitment in self.merkle_tree.leaves:
+                    metrics.INDEXED_EVENTS.inc(kind=kind, result="duplicate")
+                    duplicates += 1
+                    continue
+                if event.get("amount") is not None:
+                    self.process_deposit(event.get("user"), event.get("mint"), event["amount"], commitment, slot)
+                else:
+                    # Change notes from multi-note withdrawals enter the tree without a deposit
+                    self.merkle_tree.add_leaf(commitment)
+                    self.persistence.save_leaves(self.merkle_tree.leaves)
+                accepted += 1
+            elif kind == "nullifier":
+                nullifier_hash = event["nullifier_hash"]
+                if nullifier_hash in self.withdrawals:
+                    # Withdrawals this relayer accepted are indexed before they land
+                    self.jobs.mark_confirmed(nullifier_hash, event.get("signature"), slot)
+                    if self.withdrawals[nullifier_hash].get("slot") is None:
+                        self.withdrawals[nullifier_hash]["slot"] = slot
+                        self.persistence.save_withdrawals(self.withdrawals)
+                    metrics.INDEXED_EVENTS.inc(kind=kind, result="duplicate")
+                    duplicates += 1
+                    continue
+                self.process_withdrawal(
+                    nullifier_hash, event.get("recipient"), event.get("mint"), event.get("amount"),
+                    event.get("signature"), slot
+                )
+                accepted += 1
+            elif kind == "note":
+                commitment = event["commitment"]
+                self.merchants.announce(
+                    commitment, event["ciphertext"], self.deposits.get(commitment),
+                    {"slot": slot, "signature": event.get("signature")}
+                )
+                accepted += 1
+            elif kind == "attestation":
+                event_info = {"slot": slot, "signature": event.get("signature")}
+                if not self.compliance.attested(event["commitment"], event["envelope_hash"], event.get("user"), event_info):
+                    metrics.INDEXED_EVENTS.inc(kind=kind, result="duplicate")
+                    duplicates += 1
+                    continue
+                accepted += 1
+            elif kind == "tree":
+                self.onchain_tree = {"slot": slot, "next_index": event["next_index"], "root": event["root"]}
+                self.onchain_roots = event.get("roots") or [event["root"]]
+                self.accept_archived_roots = bool(event.get("accept_archived_roots"))
+                accepted += 1
+            elif kind == "archived_roots":
+                # Pages only grow, so the latest state of a page replaces what was known of it
+                self.archived_roots[str(event["page"])] = event.get("roots") or []
+                self.persistence.save_archived_roots(self.archived_roots)
+                accepted += 1
+            else:
+                logger.warning(f"Ignoring unknown Geyser event kind {kind!r} in slot {slot}")
+                continue
+            metrics.INDEXED_EVENTS.inc(kind=kind, result="accepted")
+        
+        logger.info(f"Indexed slot {slot} from Geyser: {accepted} new events, {duplicates} already indexed")
+        return {"slot": slot, "accepted": accepted, "duplicates": duplicates}
+
+    def known_roots(self):
+        """
+        Roots a withdrawal proof may use: the tree's recent history, plus the archive once the
+        tree accepts archived roots
+
+        Returns:
+            list: Hex-encoded roots
+        """
+        if not self.accept_archived_roots:
+            return self.onchain_roots
+        archived = [root for roots in self.archived_roots.values() for root in roots]
+        return self.onchain_roots + archived
+
     def is_nullifier_used(self, nullifier_hash_hex):
         """
         Check if a nullifier has been used
@@ -116,6 +266,203 @@ class Relayer:
         """
         return self.merkle_tree.get_merkle_root()
 
+    def get_latest_root(self):
+        """
+        Get the current Merkle root together with the number of leaves it covers
+        
+        Returns:
+            dict: The root, the leaf count, and the on-chain tree state if streamed
+        """
+        return {
+            "root": self.merkle_tree.get_merkle_root(),
+            "leaf_count": len(self.merkle_tree.leaves),
+            "onchain": self.onchain_tree
+        }
+
+    def get_commitments(self, from_leaf=0, limit=100):
+        """
+        Get a page of commitments in leaf order
+        
+        Args:
+            from_leaf: Leaf index to start from (the cursor from the previous page)
+            limit: Maximum number of commitments to return
+            
+        Returns:
+            dict: The commitments with their leaf indices, the cursor for the next page,
+                  and whether more leaves follow it. Once caught up, polling with the
+                  same cursor returns new commitments as they are indexed.
+        """
+        page = self.merkle_tree.leaves[from_leaf:from_leaf + limit]
+        next_from_leaf = from_leaf + len(page)
+        return {
+            "commitments": [
+                {"leaf_index": from_leaf + offset, "commitment": commitment}
+                for offset, commitment in enumerate(page)
+            ],
+            "next_from_leaf": next_from_leaf,
+            "has_more": next_from_leaf < len(self.merkle_tree.leaves)
+        }
+
+    def get_deposits(self, cursor=0, limit=100, mint=None, from_time=None, to_time=None, from_leaf=None, to_leaf=None):
+        """
+        Get a page of deposits in leaf order
+        
+        The cursor is a leaf index, so pages stay stable as new deposits are appended. Leaves
+        without a deposit (the zero commitment and change notes) are skipped, and deposits
+        indexed before timestamps were recorded only match queries without a time range.
+        
+        Args:
+            cursor: Leaf index to resume from (the cursor from the previous page)
+            limit: Maximum number of deposits to return
+            mint: Only deposits of this token
+            from_time: Only deposits indexed at or after this unix time
+            to_time: Only deposits indexed before this unix time
+            from_leaf: Only deposits at or after this leaf index
+            to_leaf: Only deposits before this leaf index
+            
+        Returns:
+            dict: The deposits with their commitments and leaf indices, the cursor for the
+                  next page, and whether more leaves in the range follow it
+        """
+        leaves = self.merkle_tree.leaves
+        end = len(leaves) if to_leaf is None else min(to_leaf, len(leaves))
+        index = max(cursor, from_leaf or 0)
+        deposits = []
+        while index < end and len(deposits) < limit:
+            commitment = leaves[index]
+            info = self.deposits.get(commitment)
+            index += 1
+            if info is None or not matches(info, mint, from_time, to_time):
+                continue
+            deposits.append({**info, "commitment": commitment, "leaf_index": index - 1})
+        return {"deposits": deposits, "next_cursor": index, "has_more": index < end}
+
+    def get_withdrawals(self, cursor=0, limit=100, mint=None, from_time=None, to_time=None):
+        """
+        Get a page of withdrawals in the order they were indexed
+        
+        Withdrawals this relayer accepted are indexed when accepted, before they land.
+        
+        Args:
+            cursor: Position to resume from (the cursor from the previous page)
+            limit: Maximum number of withdrawals to return
+            mint: Only withdrawals of this token
+            from_time: Only withdrawals indexed at or after this unix time
+            to_time: Only withdrawals indexed before this unix time
+            
+        Returns:
+            dict: The withdrawals with their nullifier hashes, the cursor for the next page,
+                  and whether more withdrawals follow it
+        """
+        entries = list(self.withdrawals.items())
+        index = cursor
+        withdrawals = []
+        while index < len(entries) and len(withdrawals) < limit:
+            nullifier_hash, info = entries[index]
+            index += 1
+            if not matches(info, mint, from_time, to_time):
+                continue
+            # The spent commitment stays private to this relayer, as in snapshots
+            withdrawal = {key: value for key, value in info.items() if key != "commitment"}
+            withdrawals.append({**withdrawal, "nullifier_hash": nullifier_hash})
+        return {"withdrawals": withdrawals, "next_cursor": index, "has_more": index < len(entries)}
+
+    def get_nullifier(self, nullifier_hash_hex):
+        """
+        Get whether a nullifier has been used, and by which withdrawal
+        
+        Args:
+            nullifier_hash_hex: The nullifier hash to look up
+            
+        Returns:
+            dict: The nullifier, whether it is used, and the withdrawal info if known
+        """
+        return {
+            "nullifier": nullifier_hash_hex,
+            "is_used": self.is_nullifier_used(nullifier_hash_hex),
+            "withdrawal": self.withdrawals.get(nullifier_hash_hex)
+        }
+
+    def get_nullifiers(self, nullifier_hashes):
+        """
+        Get the status of several nullifiers, as get_nullifier does for one
+        
+        Args:
+            nullifier_hashes: The nullifier hashes to look up
+            
+        Returns:
+            dict: Each nullifier's status, in the order given
+        """
+        return {"nullifiers": [self.get_nullifier(nullifier_hash) for nullifier_hash in nullifier_hashes]}
+
+    def get_pool_stats(self, mint):
+        """
+        Get deposit and withdrawal totals for one token
+        
+        Args:
+            mint: The token (mint) address of the pool
+            
+        Returns:
+            dict: Counts and summed amounts of the pool's indexed deposits and withdrawals
+        """
+        deposits = [info for info in self.deposits.values() if info.get("token") == mint]
+        withdrawals = [info for info in self.withdrawals.values() if info.get("token") == mint]
+        return {
+            "mint": mint,
+            "deposit_count": len(deposits),
+            "total_deposited": sum(int(info["amount"]) for info in deposits),
+            "withdrawal_count": len(withdrawals),
+            "total_withdrawn": sum(int(info["amount"]) for info in withdrawals)
+        }
+
+    def get_pool_analytics(self, mint, days=30):
+        """
+        Get a pool's daily volume and anonymity-set estimates (see analytics.py)
+        
+        Args:
+            mint: The token (mint) address of the pool
+            days: Number of days in the series, ending with today
+            
+        Returns:
+            dict: The daily series, the current anonymity set and whether the pool is thin
+        """
+        return pool_analytics(self.deposits.values(), self.withdrawals.values(), mint, days)
+
+    def register_merchant(self, viewing_key_hex, webhook_url, owner):
+        """
+        Register a merchant's viewing key, whose announced notes are POSTed to webhook_url
+
+        Args:
+            viewing_key_hex: Hex-encoded X25519 private key the merchant's payers encrypt to
+            webhook_url: The merchant's endpoint
+            owner: API key of the registering client
+
+        Returns:
+            dict: The registration, with the secret its webhooks are signed with
+        """
+        return self.merchants.register(viewing_key_hex, webhook_url, owner, int(time.time()))
+
+    def get_merchant(self, merchant_id, owner):
+        """Get a merchant registered with the owner's API key, or None"""
+        return self.merchants.get(merchant_id, owner)
+
+    def remove_merchant(self, merchant_id, owner):
+        """Remove a merchant registered with the owner's API key; False if there is none"""
+        return self.merchants.remove(merchant_id, owner)
+
+    def submit_compliance_envelope(self, commitment_hex, envelope_hex):
+        """
+        Store a deposit's compliance envelope, checked against its on-chain attestation
+
+        Raises:
+            ValueError: If the attestation isn't indexed or the envelope doesn't match it
+        """
+        return self.compliance.submit(commitment_hex.lower(), envelope_hex)
+
+    def get_compliance_envelope(self, commitment_hex):
+        """Get a deposit's compliance envelope and attestation, or None"""
+        return self.compliance.get(commitment_hex.lower())
+
     def get_merkle_path(self, commitment_hex):
         """
         Get the Merkle path for a commitment
@@ -135,7 +482,8 @@ class Relayer:
             logger.error(f"Error getting Merkle path for {commitment_hex[:10]}...: {str(e)}")
             raise
 
-    def submit_withdrawal(self, nullifier_hash_hex, commitment_hex, recipient_address, token_address, amount):
+    def submit_withdrawal(self, nullifier_hash_hex, commitment_hex, recipient_address, token_address, amount,
+                          proof, quote, callback_url=None, correlation_id=None):
         """
         Submit a withdrawal request
         
@@ -145,17 +493,25 @@ class Relayer:
             recipient_address: The address to receive the withdrawal
             token_address: The token address being withdrawn
             amount: The amount being withdrawn
+            proof: The withdraw proof and its public inputs
+            quote: The fee quote from get_quote the withdrawal was priced with
+            callback_url: URL to POST the job to when it is confirmed, finalized or failed
+            correlation_id: The client's opaque id for tracing the withdrawal, kept with the job
             
         Returns:
-            dict: The result of the withdrawal request
+            dict: The result of the withdrawal request, with the job id to poll
             
         Raises:
-            ValueError: If the nullifier has already been used or other validation fails
+            ValueError: If the nullifier has already been used, the proof would be
+                        rejected on-chain, the quote is invalid or expired, or other
+                        validation fails
         """
-        # Check if nullifier has been used
-        if self.is_nullifier_used(nullifier_hash_hex):
-            logger.warning(f"Nullifier already used: {nullifier_hash_hex[:10]}...")
-            raise ValueError("Nullifier already used")
+        if callback_url is not None and not callback_url.startswith(("http://", "https://")):
+            raise ValueError("Callback URL must be http(s)")
+        check_correlation_id(correlation_id)
+        
+        # The relayer charges exactly what it quoted
+        relayer_fee = self.quotes.check(quote, token_address, amount)
         
         # Verify the commitment exists in the tree
         try:
@@ -164,11 +520,16 @@ class Relayer:
             logger.warning(f"Commitment not found in Merkle tree: {commitment_hex[:10]}...")
             raise ValueError("Commitment not found in Merkle tree")
         
-        # In a real implementation, additional verification would happen here
-        # such as verifying a ZK proof that proves the user owns this note
+        # Reject anything the program would, before paying to submit it
+        nullifiers = verify_withdrawal(
+            proof, nullifier_hash_hex, recipient_address, token_address, amount, relayer_fee,
+            self.quotes.relayer, self.known_roots(), self.used_nullifiers, self.latest_slot
+        )
+        # Track the withdrawal by the nullifier hashes the program records, so its events match
+        nullifier_hash_hex = nullifiers[0]
         
-        # Mark the nullifier as used
-        self.used_nullifiers.add(nullifier_hash_hex)
+        # Mark the nullifiers as used
+        self.used_nullifiers.update(nullifiers)
         
         # Store withdrawal info
         self.withdrawals[nullifier_hash_hex] = {
@@ -176,17 +537,24 @@ class Relayer:
             "token": token_address,
             "amount": amount,
             "commitment": commitment_hex,
-            "timestamp": None  # Would be set to current time in a real implementation
+            "slot": None,
+            "timestamp": int(time.time())
         }
         
         # Persist state
         self.persistence.save_nullifiers(self.used_nullifiers)
         self.persistence.save_withdrawals(self.withdrawals)
         
-        logger.info(f"Withdrawal request accepted for nullifier: {nullifier_hash_hex[:10]}...")
+        job = self.jobs.create(
+            nullifier_hash_hex, recipient_address, token_address, amount, relayer_fee, callback_url, correlation_id
+        )
+        
+        logger.info(f"Withdrawal request accepted for nullifier: {nullifier_hash_hex[:10]}... as job {describe(job)}")
         
         return {
             "status": "success",
+            "job_id": job["job_id"],
+            "job_status": job["status"],
             "merkle_path": merkle_path,
             "nullifier_hash": nullifier_hash_hex,
             "recipient": recipient_address,
@@ -194,6 +562,110 @@ class Relayer:
             "amount": amount
         }
 
+    def submit_split_withdrawal(self, nullifier_hash_hex, commitment_hex, token_address, payouts, proof, quote,
+                                callback_url=None, correlation_id=None):
+        """
+        Submit a withdraw_split request, which pays one note out to several recipients
+        
+        withdraw_split has no relayer fee of its own: the relayer is paid by being one of
+        the payouts, at least the fee quoted for the note's total.
+        
+        Args:
+            nullifier_hash_hex: The nullifier hash of the note being spent
+            commitment_hex: The commitment being spent
+            token_address: The token address being withdrawn
+            payouts: (recipient address, amount) pairs, in the order the proof binds them
+            proof: The split proof and its public inputs
+            quote: The fee quote from get_quote for the note's total
+            callback_url: URL to POST the job to when it is confirmed, finalized or failed
+            correlation_id: The client's opaque id for tracing the withdrawal, kept with the job
+            
+        Returns:
+            dict: The result of the request, with the job id to poll
+            
+        Raises:
+            ValueError: If the relayer isn't paid its quoted fee, the nullifier has already
+                        been used, the proof would be rejected on-chain or the quote is invalid
+        """
+        if callback_url is not None and not callback_url.startswith(("http://", "https://")):
+            raise ValueError("Callback URL must be http(s)")
+        check_correlation_id(correlation_id)
+        
+        amount = sum(payout_amount for _, payout_amount in payouts)
+        relayer_fee = self.quotes.check(quote, token_address, amount)
+        paid = sum(payout_amount for recipient, payout_amount in payouts if recipient == self.quotes.relayer)
+        if paid < relayer_fee:
+            raise ValueError(f"The payouts must pay the relayer {self.quotes.relayer} at least {relayer_fee}")
+        if commitment_hex not in self.merkle_tree.leaves:
+            logger.warning(f"Commitment not found in Merkle tree: {commitment_hex[:10]}...")
+            raise ValueError("Commitment not found in Merkle tree")
+        
+        nullifier_hash_hex = verify_split_withdrawal(
+            proof, nullifier_hash_hex, token_address, payouts, self.quotes.relayer,
+            self.known_roots(), self.used_nullifiers, self.latest_slot
+        )
+        self.used_nullifiers.add(nullifier_hash_hex)
+        recipients = ",".join(recipient for recipient, _ in payouts)
+        self.withdrawals[nullifier_hash_hex] = {
+            "recipient": recipients,
+            "token": token_address,
+            "amount": amount,
+            "commitment": commitment_hex,
+            "slot": None,
+            "timestamp": int(time.time())
+        }
+        self.persistence.save_nullifiers(self.used_nullifiers)
+        self.persistence.save_withdrawals(self.withdrawals)
+        
+        job = self.jobs.create(nullifier_hash_hex, recipients, token_address, amount, relayer_fee, callback_url, correlation_id)
+        logger.info(f"Split withdrawal accepted for nullifier: {nullifier_hash_hex[:10]}... as job {describe(job)}")
+        return {
+            "status": "success",
+            "job_id": job["job_id"],
+            "job_status": job["status"],
+            "nullifier_hash": nullifier_hash_hex,
+            "token": token_address,
+            "amount": amount
+        }
+
+    def get_quote(self, mint, amount):
+        """
+        Get a signed fee quote for relaying a withdrawal
+        
+        Args:
+            mint: The token (mint) address of the pool
+            amount: The amount to withdraw
+            
+        Returns:
+            dict: The quote, to be sent back with the withdrawal request before it expires
+            
+        Raises:
+            ValueError: If the amount can't cover the fee
+        """
+        return self.quotes.quote(mint, amount)
+
+    def get_job(self, job_id):
+        """
+        Get the state of a withdrawal job
+        
+        Args:
+            job_id: The id returned when the withdrawal was submitted
+            
+        Returns:
+            dict: The job's status, transaction signature and fee charged, or None if unknown
+        """
+        job = self.jobs.get(job_id)
+        return public_view(job) if job else None
+
+    def get_snapshot(self):
+        """
+        Get a snapshot of the index for another node to bootstrap from
+        
+        Returns:
+            dict: The snapshot, with its content hash; see app/snapshot.py
+        """
+        return build_snapshot(self)
+
     def get_zero_commitment(self):
         """
         Get information about the zero commitment
@@ -0,0 +1,177 @@
+"""
+Snapshots of the relayer's index, so a new relayer or indexer node can start from a recent
+copy instead of indexing the chain from its first deposit.
+
+A snapshot is a single JSON file: a header with the slot it was taken at, the leaf count and
+the Merkle root, and the indexed state, with a SHA-256 of the state's canonical JSON in the
+header. Importing recomputes both the hash and the root and refuses the file if either differs.
+
+Withdrawal jobs stay out of snapshots since they only concern the relayer that accepted them,
+and so does the commitment an accepted withdrawal spends, which would link it to its deposit.
+
+Usage:
+    python -m app.snapshot export <file>
+    python -m app.snapshot import <file-or-url> [--force]
+"""
+import hashlib
+import json
+import logging
+import os
+import sys
+import time
+
+import httpx
+
+from . import logs
+from .merkle import MerkleTree
+from .persistence import RelayerPersistence
+
+logger = logging.getLogger(__name__)
+
+SNAPSHOT_FORMAT = "privax-relayer-snapshot"
+SNAPSHOT_VERSION = 1
+
+def content_hash(state):
+    """SHA-256 over the state's canonical JSON (sorted keys, no whitespace), as hex"""
+    canonical = json.dumps(state, sort_keys=True, separators=(",", ":"))
+    return hashlib.sha256(canonical.encode("utf-8")).hexdigest()
+
+def build_snapshot(relayer):
+    """
+    Snapshot a running relayer's index
+
+    Args:
+        relayer: The relayer to snapshot
+
+    Returns:
+        dict: The snapshot, ready to be written as JSON
+    """
+    state = {
+        "leaves": list(relayer.merkle_tree.leaves),
+        "nullifiers": sorted(relayer.used_nullifiers),
+        "deposits": relayer.deposits,
+        "withdrawals": {
+            nullifier: {key: value for key, value in info.items() if key != "commitment"}
+            for nullifier, info in relayer.withdrawals.items()
+        }
+    }
+    return {
+        "format": SNAPSHOT_FORMAT,
+        "version": SNAPSHOT_VERSION,
+        "created_at": int(time.time()),
+        "slot": relayer.latest_slot,
+        "leaf_count": len(state["leaves"]),
+        "merkle_root": relayer.merkle_tree.get_merkle_root(),
+        "content_hash": content_hash(state),
+        "state": state
+    }
+
+def export_snapshot(relayer, path):
+    """
+    Write a snapshot of the relayer to a file
+
+    The file is written next to its destination and renamed into place, so a reader never
+    sees a partial snapshot.
+
+    Returns:
+        dict: The snapshot written
+    """
+    snapshot = build_snapshot(relayer)
+    partial = f"{path}.partial"
+    with open(partial, "w") as f:
+        json.dump(snapshot, f)
+    os.replace(partial, path)
+    logger.info(
+        f"Exported snapshot of {snapshot['leaf_count']} leaves and {len(snapshot['state']['nullifiers'])} "
+        f"nullifiers at slot {snapshot['slot']} to {path}"
+    )
+    return snapshot
+
+def load_snapshot(source):
+    """
+    Read and verify a snapshot from a file or an http(s) URL, e.g. another relayer's /snapshot
+
+    Raises:
+        ValueError: If it isn't a snapshot this relayer understands, or its content hash or
+                    Merkle root doesn't match its state
+    """
+    if source.startswith(("http://", "https://")):
+        response = httpx.get(source, timeout=300)
+        response.raise_for_status()
+        snapshot = response.json()
+    else:
+        with open(source, "r") as f:
+            snapshot = json.load(f)
+    verify_snapshot(snapshot)
+    return snapshot
+
+def verify_snapshot(snapshot):
+    """
+    Check a snapshot's format, content hash and Merkle root
+
+    Raises:
+        ValueError: If any of them is wrong
+    """
+    if snapshot.get("format") != SNAPSHOT_FORMAT:
+        raise ValueError("Not a relayer snapshot")
+    if snapshot.get("version") != SNAPSHOT_VERSION:
+        raise ValueError(f"Unsupported snapshot version {snapshot.get('version')!r}")
+    state = snapshot.get("state")
+    if not isinstance(state, dict):
+        raise ValueError("Snapshot has no state")
+    if content_hash(state) != snapshot.get("content_hash"):
+        raise ValueError("Snapshot content hash mismatch; the file is corrupt or was modified")
+    leaves = state.get("leaves") or []
+    if len(leaves) != snapshot.get("leaf_count"):
+        raise ValueError(f"Snapshot lists {len(leaves)} leaves, its header says {snapshot.get('leaf_count')}")
+    root = MerkleTree.from_leaves(leaves).get_merkle_root()
+    if root != snapshot.get("merkle_root"):
+        raise ValueError(f"Snapshot leaves give root {root}, its header says {snapshot.get('merkle_root')}")
+
+def import_snapshot(snapshot, persistence, force=False):
+    """
+    Write a verified snapshot's state to the data directory, for the relayer to load on start
+
+    Args:
+        snapshot: The snapshot, as returned by load_snapshot
+        persistence: The persistence of the data directory to restore into
+        force: Overwrite state that is already there
+
+    Raises:
+        ValueError: If the data directory already has state and force isn't set
+    """
+    if persistence.has_state() and not force:
+        raise ValueError(f"{persistence.data_dir} already has relayer state")
+    state = snapshot["state"]
+    persistence.save_leaves(state["leaves"])
+    persistence.save_nullifiers(set(state["nullifiers"]))
+    persistence.save_deposits(state["deposits"])
+    persistence.save_withdrawals(state["withdrawals"])
+    logger.info(
+        f"Imported snapshot of {snapshot['leaf_count']} leaves and {len(state['nullifiers'])} nullifiers "
+        f"at slot {snapshot['slot']} into {persistence.data_dir}"
+    )
+
+def main(argv):
+    """Command-line entry point; see the module docstring"""
+    logs.configure()
+    args = [arg for arg in argv if arg != "--force"]
+    if len(args) != 2 or args[0] not in ("export", "import"):
+        print(__doc__.split("Usage:")[1].rstrip(), file=sys.stderr)
+        return 2
+    command, target = args
+    try:
+        if command == "export":
+            # Loads the data directory like a starting relayer; a running one serves GET /snapshot
+            from .relayer import Relayer
+            export_snapshot(Relayer(), target)
+        else:
+            persistence = RelayerPersistence(os.getenv("DATA_DIR", "./data"))
+            import_snapshot(load_snapshot(target), persistence, force="--force" in argv)
+    except (OSError, ValueError, httpx.HTTPError) as e:
+        logger.error(f"Snapshot {command} failed: {str(e)}")
+        return 1
+    return 0
+
+if __name__ == "__main__":
+    sys.exit(main(sys.argv[1:]))
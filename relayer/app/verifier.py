@@ -0,0 +1,277 @@
+import hashlib
+
+from .config import profile
+
+# Mirrors the checks privax_protocol's `withdraw` makes on a proof, so the relayer rejects a
+# withdrawal that would fail on-chain before paying to submit it. Public inputs are u64s; each
+# 32-byte value (root, nullifier hash, recipient, commitment) is represented by its first
+# 8 bytes read little-endian. The root and nullifier hashes also come in full, and the program
+# verifies the proof against those.
+
+MAX_PUBLIC_INPUTS = 12
+SPLIT_PUBLIC_INPUTS = 15
+MAX_SPLIT_RECIPIENTS = 4
+REQUIRED_PUBLIC_INPUTS_COUNT = 8
+MAX_INPUT_NOTES = 4
+MAX_PROOF_AGE_SLOTS = 1500
+VARIABLE_DENOMINATION = 0
+# A proof this close to MAX_PROOF_AGE_SLOTS could expire before the transaction lands
+SUBMISSION_MARGIN_SLOTS = 150
+
+U64_MAX = 2**64 - 1
+# Nine G1 commitments and six scalar evaluations, as privax_protocol's plonk module reads them
+PLONK_PROOF_SIZE = 9 * 64 + 6 * 32
+# BN254 base field modulus; proof point coordinates must be below it
+BN254_BASE_FIELD_MODULUS = 0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47
+
+BASE58_ALPHABET = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz"
+
+
+class ProofRejected(ValueError):
+    """A withdrawal proof that the program would reject"""
+
+
+def base58_decode(value):
+    """Decode a base58 string, as used for Solana addresses"""
+    number = 0
+    for char in value:
+        digit = BASE58_ALPHABET.find(char)
+        if digit < 0:
+            raise ValueError(f"Invalid base58 character {char!r}")
+        number = number * 58 + digit
+    leading_zeros = len(value) - len(value.lstrip("1"))
+    body = number.to_bytes((number.bit_length() + 7) // 8, "big") if number else b""
+    return b"\0" * leading_zeros + body
+
+
+def base58_encode(data):
+    """Encode bytes as base58"""
+    number = int.from_bytes(data, "big")
+    encoded = ""
+    while number:
+        number, digit = divmod(number, 58)
+        encoded = BASE58_ALPHABET[digit] + encoded
+    leading_zeros = len(data) - len(data.lstrip(b"\0"))
+    return "1" * leading_zeros + encoded
+
+
+def pubkey_bytes(address):
+    """The 32 bytes of a base58 Solana address"""
+    try:
+        key = base58_decode(address)
+    except ValueError:
+        key = b""
+    if len(key) != 32:
+        raise ProofRejected(f"Invalid address {address}")
+    return key
+
+
+def public_input(value):
+    """The u64 public input representing a 32-byte value"""
+    return int.from_bytes(value[:8], "little")
+
+
+def nullifier_hex(nullifier_hash_hex, name="nullifier_hash"):
+    """A nullifier hash as the program records it and its events report it: all 32 bytes, lowercase hex"""
+    return decode_hex(nullifier_hash_hex, 32, name).hex()
+
+
+def external_nullifier(mint, circuit_version):
+    """The external nullifier public input for a pool and circuit version"""
+    digest = hashlib.sha256(
+        b"privax-external-nullifier"
+        + pubkey_bytes(profile().program_id)
+        + pubkey_bytes(mint)
+        + VARIABLE_DENOMINATION.to_bytes(8, "little")
+        + circuit_version.to_bytes(2, "little")
+    ).digest()
+    return public_input(digest)
+
+
+def split_external_nullifier(mint, circuit_version):
+    """The external nullifier public input of withdraw_split's circuit"""
+    digest = hashlib.sha256(
+        b"privax-split-external-nullifier"
+        + pubkey_bytes(profile().program_id)
+        + pubkey_bytes(mint)
+        + circuit_version.to_bytes(2, "little")
+    ).digest()
+    return public_input(digest)
+
+
+def decode_hex(value, length, name):
+    try:
+        decoded = bytes.fromhex(value)
+    except ValueError:
+        raise ProofRejected(f"{name} is not hex")
+    if length is not None and len(decoded) != length:
+        raise ProofRejected(f"{name} must be {length} bytes")
+    return decoded
+
+
+def check_coordinates(points, name):
+    for start in range(0, len(points), 32):
+        if int.from_bytes(points[start:start + 32], "big") >= BN254_BASE_FIELD_MODULUS:
+            raise ProofRejected(f"{name} has a coordinate outside the BN254 base field")
+
+
+def verify_withdrawal(proof, nullifier_hash_hex, recipient_address, token_address, amount, relayer_fee,
+                      relayer_address, known_roots, used_nullifiers, latest_slot):
+    """
+    Check a withdrawal proof the way the program will
+
+    Args:
+        proof: The proof and its public inputs, as sent to the API
+        nullifier_hash_hex: Hex-encoded nullifier hash of the first note spent
+        recipient_address: Base58 recipient address
+        token_address: Base58 mint of the pool
+        amount: Amount withdrawn, in base units
+        relayer_fee: Relayer fee taken from the amount
+        relayer_address: Base58 key the relayer submits with, which the proof must bind
+        known_roots: Hex-encoded on-chain Merkle roots the proof may use
+        used_nullifiers: Nullifier hashes already spent
+        latest_slot: Most recent slot the relayer has seen
+
+    Returns:
+        list: Hex nullifier hashes the withdrawal spends, as the program records them
+
+    Raises:
+        ProofRejected: If the program would reject the withdrawal
+    """
+    inputs = proof["public_inputs"]
+    if len(inputs) != MAX_PUBLIC_INPUTS or any(not 0 <= value <= U64_MAX for value in inputs):
+        raise ProofRejected(f"Expected {MAX_PUBLIC_INPUTS} u64 public inputs")
+    if not 0 < amount <= U64_MAX or not 0 <= relayer_fee <= amount:
+        raise ProofRejected("Invalid amount or relayer fee")
+
+    # Layout: root, nullifier, recipient, amount, external nullifier, proof slot, relayer fee,
+    # relayer, then the further nullifiers and the change commitment; unused slots are zero.
+    extra_nullifier_hashes = proof.get("extra_nullifier_hashes") or []
+    input_notes = 1 + len(extra_nullifier_hashes)
+    if input_notes > MAX_INPUT_NOTES:
+        raise ProofRejected(f"A proof spends 1 to {MAX_INPUT_NOTES} notes")
+    change_commitment = proof.get("change_commitment")
+    used_inputs = REQUIRED_PUBLIC_INPUTS_COUNT + input_notes - 1 + (change_commitment is not None)
+    if any(inputs[used_inputs:]):
+        raise ProofRejected("Unused public inputs must be zero")
+    if change_commitment is not None:
+        if inputs[used_inputs - 1] != public_input(decode_hex(change_commitment, 32, "change_commitment")):
+            raise ProofRejected("Change commitment does not match the public inputs")
+
+    check_root_and_nullifier(inputs, proof["merkle_root"], nullifier_hash_hex, known_roots)
+    if inputs[2] != public_input(pubkey_bytes(recipient_address)):
+        raise ProofRejected("Recipient does not match the public inputs")
+    if inputs[3] != amount:
+        raise ProofRejected("Amount does not match the public inputs")
+    if inputs[4] != external_nullifier(token_address, proof["circuit_version"]):
+        raise ProofRejected("Proof is for another pool or circuit version")
+    check_proof_slot(inputs[5], latest_slot)
+    check_relayer(inputs[6], inputs[7], relayer_fee, relayer_address)
+    check_encoding(proof)
+
+    extra_nullifiers = inputs[REQUIRED_PUBLIC_INPUTS_COUNT:REQUIRED_PUBLIC_INPUTS_COUNT + input_notes - 1]
+    extra_hashes = [nullifier_hex(hash_hex, "extra_nullifier_hashes") for hash_hex in extra_nullifier_hashes]
+    for value, hash_hex in zip(extra_nullifiers, extra_hashes):
+        if value != public_input(bytes.fromhex(hash_hex)):
+            raise ProofRejected("A nullifier hash does not match the public inputs")
+    nullifiers = [nullifier_hex(nullifier_hash_hex)] + extra_hashes
+    if len(set(nullifiers)) != len(nullifiers):
+        raise ProofRejected("A proof cannot spend the same note twice")
+    spent = [nullifier for nullifier in nullifiers if nullifier in used_nullifiers]
+    if spent:
+        raise ProofRejected(f"Nullifier already used: {spent[0]}")
+    return nullifiers
+
+
+def verify_split_withdrawal(proof, nullifier_hash_hex, token_address, payouts, relayer_address, known_roots,
+                            used_nullifiers, latest_slot):
+    """
+    Check a withdraw_split proof the way the program will
+
+    Args:
+        proof: The proof and its public inputs, as sent to the API
+        nullifier_hash_hex: Hex-encoded nullifier hash of the note spent
+        token_address: Base58 mint of the pool
+        payouts: (recipient address, amount) pairs, in the order the proof binds them
+        relayer_address: Base58 key the relayer submits with; its fee is what the payouts pay it
+        known_roots: Hex-encoded on-chain Merkle roots the proof may use
+        used_nullifiers: Nullifier hashes already spent
+        latest_slot: Most recent slot the relayer has seen
+
+    Returns:
+        str: Hex nullifier hash the withdrawal spends, as the program records it
+
+    Raises:
+        ProofRejected: If the program would reject the withdrawal
+    """
+    inputs = proof["public_inputs"]
+    if len(inputs) != SPLIT_PUBLIC_INPUTS or any(not 0 <= value <= U64_MAX for value in inputs):
+        raise ProofRejected(f"Expected {SPLIT_PUBLIC_INPUTS} u64 public inputs")
+    if not 1 <= len(payouts) <= MAX_SPLIT_RECIPIENTS or any(not 0 < amount <= U64_MAX for _, amount in payouts):
+        raise ProofRejected(f"A split pays 1 to {MAX_SPLIT_RECIPIENTS} recipients a non-zero amount each")
+
+    # Layout: root, nullifier, amount, external nullifier, proof slot, relayer fee, relayer,
+    # then a (recipient, amount) pair per payout; pairs past the last payout are zero.
+    check_root_and_nullifier(inputs, proof["merkle_root"], nullifier_hash_hex, known_roots)
+    pairs = [(public_input(pubkey_bytes(recipient)), amount) for recipient, amount in payouts]
+    pairs += [(0, 0)] * (MAX_SPLIT_RECIPIENTS - len(pairs))
+    if [tuple(inputs[i:i + 2]) for i in range(7, SPLIT_PUBLIC_INPUTS, 2)] != pairs:
+        raise ProofRejected("Payouts do not match the public inputs")
+    if inputs[2] != sum(amount for _, amount in payouts):
+        raise ProofRejected("Payouts do not add up to the note's amount")
+    if inputs[3] != split_external_nullifier(token_address, proof["circuit_version"]):
+        raise ProofRejected("Proof is for another pool or circuit version")
+    check_proof_slot(inputs[4], latest_slot)
+    relayer_fee = sum(amount for recipient, amount in payouts if recipient == relayer_address)
+    check_relayer(inputs[5], inputs[6], relayer_fee, relayer_address)
+    check_encoding(proof)
+
+    nullifier = nullifier_hex(nullifier_hash_hex)
+    if nullifier in used_nullifiers:
+        raise ProofRejected(f"Nullifier already used: {nullifier}")
+    return nullifier
+
+
+def check_root_and_nullifier(inputs, merkle_root_hex, nullifier_hash_hex, known_roots):
+    if not known_roots:
+        raise ProofRejected("The on-chain root history is not known yet")
+    merkle_root = decode_hex(merkle_root_hex, 32, "merkle_root")
+    if inputs[0] != public_input(merkle_root):
+        raise ProofRejected("Merkle root does not match the public inputs")
+    # The whole root must be a known one, as on-chain
+    if merkle_root not in {bytes.fromhex(root) for root in known_roots}:
+        raise ProofRejected("Unknown or expired Merkle root")
+    if inputs[1] != public_input(decode_hex(nullifier_hash_hex, 32, "nullifier_hash")):
+        raise ProofRejected("Nullifier hash does not match the public inputs")
+
+
+def check_relayer(fee_input, relayer_input, relayer_fee, relayer_address):
+    # The program takes the fee from the proof and only lets the relayer it binds submit it
+    if fee_input != relayer_fee:
+        raise ProofRejected("Relayer fee does not match the public inputs")
+    if relayer_input != public_input(pubkey_bytes(relayer_address)):
+        raise ProofRejected("The proof is bound to another relayer")
+
+
+def check_proof_slot(proof_slot, latest_slot):
+    if latest_slot is None:
+        raise ProofRejected("The current slot is not known yet")
+    too_old = latest_slot - proof_slot > MAX_PROOF_AGE_SLOTS - SUBMISSION_MARGIN_SLOTS
+    if proof_slot > latest_slot + SUBMISSION_MARGIN_SLOTS or too_old:
+        raise ProofRejected("Proof slot is in the future or too old to land in time")
+
+
+def check_encoding(proof):
+    # The program runs the pairing check itself; this only turns away what it would reject
+    # for its encoding. A PLONK proof leaves the Groth16 points zero.
+    plonk_proof = decode_hex(proof.get("plonk_proof") or "", None, "plonk_proof")
+    points = decode_hex(proof["a"], 64, "a") + decode_hex(proof["b"], 128, "b") + decode_hex(proof["c"], 64, "c")
+    if not plonk_proof:
+        check_coordinates(points, "Groth16 proof")
+        return
+    if any(points):
+        raise ProofRejected("A PLONK proof must leave a, b and c zero")
+    if len(plonk_proof) != PLONK_PROOF_SIZE:
+        raise ProofRejected(f"plonk_proof must be {PLONK_PROOF_SIZE} bytes")
+    # Nine commitments, then six evaluations the program treats as scalars
+    check_coordinates(plonk_proof[:9 * 64], "PLONK proof")
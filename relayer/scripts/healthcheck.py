@@ -1,27 +1,28 @@
 #!/usr/bin/env python3
 """
 Simple health check script for the relayer.
-Returns exit code 0 if the relayer is healthy, non-zero otherwise.
+Returns exit code 0 if the relayer is live, non-zero otherwise; with --ready, only if it is
+also ready to serve traffic (see GET /readyz).
 """
 import sys
 import os
 import requests
 from urllib.parse import urljoin
 
-def check_health():
-    """Check if the relayer API is healthy"""
+def check_health(ready=False):
+    """Check if the relayer API is live, or with ready set, ready"""
     host = os.environ.get("HOST", "localhost")
     port = os.environ.get("PORT", "8000")
     
     base_url = f"http://{host}:{port}"
-    health_url = urljoin(base_url, "/")
+    health_url = urljoin(base_url, "/readyz" if ready else "/healthz")
     
     try:
         response = requests.get(health_url, timeout=5)
         response.raise_for_status()
         
         data = response.json()
-        if data.get("status") == "Relayer is running":
+        if data.get("status") in ("ready", "ok"):
             print("Health check successful")
             return True
         else:
@@ -32,7 +33,7 @@ def check_health():
         return False
 
 if __name__ == "__main__":
-    if check_health():
+    if check_health(ready="--ready" in sys.argv[1:]):
         sys.exit(0)
     else:
         sys.exit(1) 
\ No newline at end of file
@@ -0,0 +1,62 @@
+import { BorshCoder, Idl } from '@coral-xyz/anchor';
+import { IDL } from './privax_protocol';
+
+// Matches EVENT_VERSION in the program. Every event leads with a `version: u8` field.
+export const EVENT_VERSION = 1;
+
+export interface PrivaxEvent {
+  name: string;
+  version: number;
+  data: any;
+}
+
+// Events emitted before versioning (v0) had the same fields minus the leading version.
+const unversionedIdl = {
+  ...IDL,
+  events: IDL.events.map((event) => ({
+    ...event,
+    fields: event.fields.filter((field) => field.name !== 'version'),
+  })),
+};
+
+// Layouts for every event version, newest first. When the program bumps EVENT_VERSION,
+// snapshot the previous IDL's events here under its version number.
+const CODERS: [number, BorshCoder][] = [
+  [EVENT_VERSION, new BorshCoder(IDL as Idl)],
+  [0, new BorshCoder(unversionedIdl as Idl)],
+];
+
+// The discriminator doesn't change between versions, so a payload is attributed to the
+// first version whose layout consumes it exactly (and, from v1, whose version byte matches).
+function decodeWith(coder: BorshCoder, version: number, raw: Buffer): PrivaxEvent | null {
+  let event;
+  try {
+    event = coder.events.decode(raw.toString('base64'));
+  } catch {
+    return null;
+  }
+  if (!event) return null;
+  const layout = (coder.events as any).layouts.get(event.name);
+  if (layout.getSpan(raw, 8) !== raw.length - 8) return null;
+  if (version > 0 && event.data.version !== version) return null;
+  return { name: event.name, version, data: event.data };
+}
+
+// Decodes one base64 `Program data:` payload into an event of any known version.
+export function decodeEvent(logData: string): PrivaxEvent | null {
+  const raw = Buffer.from(logData, 'base64');
+  for (const [version, coder] of CODERS) {
+    const event = decodeWith(coder, version, raw);
+    if (event) return event;
+  }
+  return null;
+}
+
+// Decodes every Privax event in a transaction's log messages.
+export function parseEvents(logs: string[]): PrivaxEvent[] {
+  const prefix = 'Program data: ';
+  return logs
+    .filter((line) => line.startsWith(prefix))
+    .map((line) => decodeEvent(line.slice(prefix.length)))
+    .filter((event): event is PrivaxEvent => event !== null);
+}
@@ -0,0 +1,232 @@
+import { PublicKey, SystemProgram, TransactionInstruction } from '@solana/web3.js';
+import { Program } from '@coral-xyz/anchor';
+import BN from 'bn.js';
+
+// Builders for admin instructions executed through an SPL Governance (Realms) proposal.
+// The program's admin is set to the governance account (via transferOwnership), which
+// signs when the proposal executes. Rent for new accounts is paid by the governance's
+// native treasury, since the governance account itself holds data and cannot fund accounts.
+// The native treasury signs too, so it can hold the admin or fee authority role instead:
+// pass it as `authority`.
+
+export const SPL_GOVERNANCE_PROGRAM_ID = new PublicKey('GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw');
+
+export function nativeTreasuryAddress(
+  governance: PublicKey,
+  governanceProgramId: PublicKey = SPL_GOVERNANCE_PROGRAM_ID
+): PublicKey {
+  const [treasury] = PublicKey.findProgramAddressSync(
+    [Buffer.from('native-treasury'), governance.toBuffer()],
+    governanceProgramId
+  );
+  return treasury;
+}
+
+function programStateAddress(program: Program): PublicKey {
+  const [programStatePDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from('program_state')],
+    program.programId
+  );
+  return programStatePDA;
+}
+
+// Every admin instruction records itself in the audit log.
+function auditLogAddress(program: Program, programState: PublicKey): PublicKey {
+  const [auditLogPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from('audit_log'), programState.toBuffer()],
+    program.programId
+  );
+  return auditLogPDA;
+}
+
+function feeConfigAddress(program: Program, programState: PublicKey): PublicKey {
+  const [feeConfigPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from('fee_config'), programState.toBuffer()],
+    program.programId
+  );
+  return feeConfigPDA;
+}
+
+function verifyingKeyAddress(program: Program, programState: PublicKey, circuitVersion: number): PublicKey {
+  const [verifyingKeyPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from('verifying_key'), programState.toBuffer(), new BN(circuitVersion).toArrayLike(Buffer, 'le', 2)],
+    program.programId
+  );
+  return verifyingKeyPDA;
+}
+
+export async function addRelayerProposalIx(
+  program: Program,
+  governance: PublicKey,
+  relayerAddress: PublicKey,
+  endpointUrlHash: Buffer,
+  feeSchedule: { feeBps: number, minFee: number },
+  expirySlot: number | null = null,
+  governanceProgramId: PublicKey = SPL_GOVERNANCE_PROGRAM_ID,
+  authority: PublicKey = governance
+): Promise<TransactionInstruction> {
+  const [relayerInfoPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from('relayer'), relayerAddress.toBuffer()],
+    program.programId
+  );
+  const programStatePDA = programStateAddress(program);
+  return program.methods
+    .addRelayer(relayerAddress, Array.from(endpointUrlHash), {
+      feeBps: feeSchedule.feeBps,
+      minFee: new BN(feeSchedule.minFee),
+    }, expirySlot === null ? null : new BN(expirySlot))
+    .accounts({
+      programState: programStatePDA,
+      auditLog: auditLogAddress(program, programStatePDA),
+      relayerInfo: relayerInfoPDA,
+      admin: authority,
+      payer: nativeTreasuryAddress(governance, governanceProgramId),
+      systemProgram: SystemProgram.programId,
+    })
+    .instruction();
+}
+
+// Fee instructions are signed by the fee authority, which can be a governance account too.
+export async function updateFeeConfigProposalIx(
+  program: Program,
+  governance: PublicKey,
+  protocolFeeBps: number,
+  maxProtocolFee: number,
+  authority: PublicKey = governance
+): Promise<TransactionInstruction> {
+  const programStatePDA = programStateAddress(program);
+  return program.methods
+    .updateFeeConfig(protocolFeeBps, new BN(maxProtocolFee))
+    .accounts({
+      programState: programStatePDA,
+      feeConfig: feeConfigAddress(program, programStatePDA),
+      auditLog: auditLogAddress(program, programStatePDA),
+      feeAuthority: authority,
+    })
+    .instruction();
+}
+
+export async function setFeeSwapProgramProposalIx(
+  program: Program,
+  governance: PublicKey,
+  feeSwapProgram: PublicKey,
+  authority: PublicKey = governance
+): Promise<TransactionInstruction> {
+  const programStatePDA = programStateAddress(program);
+  return program.methods
+    .setFeeSwapProgram(feeSwapProgram)
+    .accounts({
+      programState: programStatePDA,
+      feeConfig: feeConfigAddress(program, programStatePDA),
+      auditLog: auditLogAddress(program, programStatePDA),
+      feeAuthority: authority,
+    })
+    .instruction();
+}
+
+// A verifying key upgrade: register the new circuit version with the key's first chunk. A key
+// over a proposal transaction's size is completed with one writeVerifyingKeyChunkProposalIx per
+// further chunk, in order, each in a transaction of its own. Keys are Groth16, the only proof
+// system the program verifies.
+export async function registerVerifyingKeyProposalIx(
+  program: Program,
+  governance: PublicKey,
+  circuitVersion: number,
+  amountRangeBits: number,
+  vkLen: number,
+  firstChunk: Buffer,
+  governanceProgramId: PublicKey = SPL_GOVERNANCE_PROGRAM_ID,
+  authority: PublicKey = governance
+): Promise<TransactionInstruction> {
+  const programStatePDA = programStateAddress(program);
+  return program.methods
+    .registerVerifyingKey(circuitVersion, { groth16: {} }, amountRangeBits, vkLen, firstChunk)
+    .accounts({
+      programState: programStatePDA,
+      auditLog: auditLogAddress(program, programStatePDA),
+      verifyingKey: verifyingKeyAddress(program, programStatePDA, circuitVersion),
+      admin: authority,
+      payer: nativeTreasuryAddress(governance, governanceProgramId),
+      systemProgram: SystemProgram.programId,
+    })
+    .instruction();
+}
+
+export async function writeVerifyingKeyChunkProposalIx(
+  program: Program,
+  governance: PublicKey,
+  circuitVersion: number,
+  chunk: Buffer,
+  authority: PublicKey = governance
+): Promise<TransactionInstruction> {
+  const programStatePDA = programStateAddress(program);
+  return program.methods
+    .writeVerifyingKeyChunk(chunk)
+    .accounts({
+      programState: programStatePDA,
+      verifyingKey: verifyingKeyAddress(program, programStatePDA, circuitVersion),
+      auditLog: auditLogAddress(program, programStatePDA),
+      admin: authority,
+    })
+    .instruction();
+}
+
+export async function setVerifierProgramProposalIx(
+  program: Program,
+  governance: PublicKey,
+  verifierProgramId: PublicKey,
+  authority: PublicKey = governance
+): Promise<TransactionInstruction> {
+  const programStatePDA = programStateAddress(program);
+  return program.methods
+    .setVerifierProgram(verifierProgramId)
+    .accounts({
+      programState: programStatePDA,
+      auditLog: auditLogAddress(program, programStatePDA),
+      admin: authority,
+    })
+    .instruction();
+}
+
+// Hands tree maintenance (checkpoint_root, archive_roots) to an operator key, so automation doesn't wait on
+// proposals; the operator can't move funds.
+export async function setTreeOperatorProposalIx(
+  program: Program,
+  governance: PublicKey,
+  treeOperator: PublicKey,
+  authority: PublicKey = governance
+): Promise<TransactionInstruction> {
+  const programStatePDA = programStateAddress(program);
+  return program.methods
+    .setTreeOperator(treeOperator)
+    .accounts({
+      programState: programStatePDA,
+      auditLog: auditLogAddress(program, programStatePDA),
+      admin: authority,
+    })
+    .instruction();
+}
+
+// Lets the relayers honor proofs against roots archived by archive_roots, not only the tree's
+// recent history.
+export async function setArchivedRootsAcceptedProposalIx(
+  program: Program,
+  governance: PublicKey,
+  accept: boolean,
+  authority: PublicKey = governance
+): Promise<TransactionInstruction> {
+  const programState = programStateAddress(program);
+  const [merkleTree] = PublicKey.findProgramAddressSync(
+    [Buffer.from('merkle_tree'), programState.toBuffer()],
+    program.programId
+  );
+  return program.methods
+    .setArchivedRootsAccepted(accept)
+    .accounts({
+      programState,
+      merkleTree,
+      auditLog: auditLogAddress(program, programState),
+      admin: authority,
+    })
+    .instruction();
+}
@@ -1,18 +1,29 @@
-import { Connection, Keypair, PublicKey, SystemProgram } from '@solana/web3.js';
+import { ComputeBudgetProgram, Connection, Keypair, PublicKey, SystemProgram } from '@solana/web3.js';
 import { Program, Wallet, AnchorProvider } from '@coral-xyz/anchor';
 import { TOKEN_PROGRAM_ID, Token } from '@solana/spl-token';
+import { createHash } from 'crypto';
 import * as fs from 'fs';
 import * as path from 'path';
 import dotenv from 'dotenv';
 import BN from 'bn.js';
 import { IDL } from './privax_protocol'; // Will be generated by Anchor
+import { createProtocolLookupTable, fetchLookupTable } from './lookupTables';
+import { TransactionBuilder } from './transactionBuilder';
 
 dotenv.config();
 
 // Constants and Configuration
 const PROGRAM_ID = new PublicKey('Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS');
 const RPC_URL = process.env.RPC_URL || 'http://localhost:8899';
+const MAX_PUBLIC_INPUTS = 12; // withdraw's fixed public input array length
 const WALLET_PATH = process.env.WALLET_PATH || path.join(process.env.HOME || '', '.config/solana/id.json');
+// Address Lookup Table with the protocol's static accounts (see createLookupTable); when set,
+// withdrawals resolve their static accounts through it
+const LOOKUP_TABLE_ADDRESS = process.env.LOOKUP_TABLE_ADDRESS;
+// Tree inserts (and initialize's zeros) hash with Poseidon at every level, past the default
+// 200k compute units per instruction
+const TREE_INSERT_UNITS = 1_400_000;
+const treeInsertBudget = () => ComputeBudgetProgram.setComputeUnitLimit({ units: TREE_INSERT_UNITS });
 
 // Helper Functions
 function loadWallet(): Keypair {
@@ -26,15 +37,47 @@ function createProvider(): AnchorProvider {
   return new AnchorProvider(connection, wallet, { commitment: 'confirmed' });
 }
 
+// Public input 4 of a withdraw proof: mirrors external_nullifier in the program, which
+// binds proofs to this deployment. Variable-amount pools use denomination 0.
+function externalNullifier(programId: PublicKey, tokenMint: PublicKey, circuitVersion: number, denomination: number = 0): BN {
+  const digest = createHash('sha256')
+    .update(
+      Buffer.concat([
+        Buffer.from('privax-external-nullifier'),
+        programId.toBuffer(),
+        tokenMint.toBuffer(),
+        new BN(denomination).toArrayLike(Buffer, 'le', 8),
+        new BN(circuitVersion).toArrayLike(Buffer, 'le', 2),
+      ])
+    )
+    .digest();
+  return new BN(digest.subarray(0, 8), 'le');
+}
+
 // Main Client Class
 class PrivaxClient {
   private program: Program;
   private provider: AnchorProvider;
+  private lookupTable: PublicKey | null;
 
   constructor() {
     this.provider = createProvider();
     // @ts-ignore - Types will be properly handled when IDL is generated
     this.program = new Program(IDL, PROGRAM_ID, this.provider);
+    this.lookupTable = LOOKUP_TABLE_ADDRESS ? new PublicKey(LOOKUP_TABLE_ADDRESS) : null;
+  }
+
+  // Creates a lookup table of the protocol's static accounts, owned by this wallet, and uses
+  // it for later withdrawals. Save the address as LOOKUP_TABLE_ADDRESS to reuse it.
+  async createLookupTable(circuitVersions: number[] = [1]) {
+    this.lookupTable = await createProtocolLookupTable(
+      this.provider.connection,
+      (this.provider.wallet as Wallet).payer,
+      this.program.programId,
+      circuitVersions
+    );
+    console.log('Lookup table created:', this.lookupTable.toBase58());
+    return this.lookupTable;
   }
 
   // Initialize the program with admin configuration
@@ -43,6 +86,18 @@ class PrivaxClient {
       [Buffer.from('program_state')],
       this.program.programId
     );
+    const [feeConfigPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('fee_config'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
+    const [nullifierFilterPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('nullifier_filter'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
+    const [merkleTreePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('merkle_tree'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
 
     console.log('Initializing Privax Protocol...');
     try {
@@ -50,9 +105,14 @@ class PrivaxClient {
         .initialize(tokenMintAddress, verifierProgramId)
         .accounts({
           programState: programStatePDA,
+          feeConfig: feeConfigPDA,
+          nullifierFilter: nullifierFilterPDA,
+          merkleTree: merkleTreePDA,
+          auditLog: this.auditLog(programStatePDA),
           admin: this.provider.wallet.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .preInstructions([treeInsertBudget()])
         .rpc();
       
       console.log('Initialization successful!');
@@ -65,19 +125,35 @@ class PrivaxClient {
   }
 
   // Add a relayer to the whitelist
-  async addRelayer(relayerAddress: PublicKey) {
+  async addRelayer(
+    relayerAddress: PublicKey,
+    endpointUrlHash: Buffer,
+    feeSchedule: { feeBps: number, minFee: number },
+    expirySlot: number | null = null
+  ) {
     const [programStatePDA] = PublicKey.findProgramAddressSync(
       [Buffer.from('program_state')],
       this.program.programId
     );
+    const [relayerInfoPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('relayer'), relayerAddress.toBuffer()],
+      this.program.programId
+    );
 
     console.log(`Adding relayer ${relayerAddress.toBase58()}...`);
     try {
       const tx = await this.program.methods
-        .addRelayer(relayerAddress)
+        .addRelayer(relayerAddress, Array.from(endpointUrlHash), {
+          feeBps: feeSchedule.feeBps,
+          minFee: new BN(feeSchedule.minFee),
+        }, expirySlot === null ? null : new BN(expirySlot))
         .accounts({
           programState: programStatePDA,
+          auditLog: this.auditLog(programStatePDA),
+          relayerInfo: relayerInfoPDA,
           admin: this.provider.wallet.publicKey,
+          payer: this.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
         })
         .rpc();
       
@@ -90,16 +166,141 @@ class PrivaxClient {
     }
   }
 
+  // The pool's USD limits and Pyth price account, which deposits and withdrawals must pass
+  // while any USD limit is set; null for both otherwise.
+  async usdLimitAccounts(programStatePDA: PublicKey) {
+    const programState = await this.program.account.programState.fetch(programStatePDA);
+    if (!programState.usdLimits) {
+      return { usdLimits: null, priceFeed: null };
+    }
+    const [usdLimits] = PublicKey.findProgramAddressSync(
+      [Buffer.from('usd_limits'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
+    const limits = await this.program.account.usdLimits.fetch(usdLimits);
+    return { usdLimits, priceFeed: limits.priceFeed as PublicKey };
+  }
+
+  // This wallet's deposit volume account, which deposits must pass while the pool caps daily
+  // deposits, and the instruction opening it before the wallet's first deposit; nulls otherwise.
+  async depositVolumeAccount(programStatePDA: PublicKey) {
+    const programState = await this.program.account.programState.fetch(programStatePDA);
+    if ((programState.maxDailyDeposit as BN).isZero()) {
+      return { depositVolume: null, openDepositVolume: null };
+    }
+    const depositor = this.provider.wallet.publicKey;
+    const [depositVolume] = PublicKey.findProgramAddressSync(
+      [Buffer.from('deposit_volume'), programStatePDA.toBuffer(), depositor.toBuffer()],
+      this.program.programId
+    );
+    if ((await this.provider.connection.getAccountInfo(depositVolume)) !== null) {
+      return { depositVolume, openDepositVolume: null };
+    }
+    const openDepositVolume = await this.program.methods
+      .initDepositVolume(depositor)
+      .accounts({
+        programState: programStatePDA,
+        depositVolume,
+        payer: depositor,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction();
+    return { depositVolume, openDepositVolume };
+  }
+
+  // Admin actions are recorded in the pool's audit log
+  auditLog(programStatePDA: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('audit_log'), programStatePDA.toBuffer()],
+      this.program.programId
+    )[0];
+  }
+
+  // Inserted commitments are recorded in shards keyed by their first two bytes
+  commitmentShard(programStatePDA: PublicKey, commitment: Buffer) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('commitment_shard'), programStatePDA.toBuffer(), commitment.subarray(0, 2)],
+      this.program.programId
+    )[0];
+  }
+
   // Deposit tokens with a commitment
-  async deposit(tokenAccount: PublicKey, amount: number, commitment: Buffer) {
+  async deposit(
+    tokenAccount: PublicKey,
+    amount: number,
+    commitment: Buffer,
+    referrer: PublicKey | null = null,
+    withReceipt: boolean = false,
+    envelopeHash: Buffer | null = null
+  ) {
     const [programStatePDA] = PublicKey.findProgramAddressSync(
       [Buffer.from('program_state')],
       this.program.programId
     );
+    const [feeConfigPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('fee_config'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
+    // Referrers must have called registerReferrer beforehand
+    const referrerAccount = referrer === null ? null : PublicKey.findProgramAddressSync(
+      [Buffer.from('referrer'), referrer.toBuffer()],
+      this.program.programId
+    )[0];
 
     // Get the token mint from the program state
     const programState = await this.program.account.programState.fetch(programStatePDA);
     const tokenMint = programState.tokenMint;
+
+    const preInstructions = [treeInsertBudget()];
+    const { depositVolume, openDepositVolume } = await this.depositVolumeAccount(programStatePDA);
+    if (openDepositVolume !== null) {
+      preInstructions.push(openDepositVolume);
+    }
+
+    // Pools with a compliance key take only deposits attested to an envelope, whose SHA-256 is envelopeHash;
+    // the attestation goes in the same transaction, and the envelope to the relayer once it lands
+    let complianceAttestation: PublicKey | null = null;
+    if ((programState.complianceKey as number[]).some((byte) => byte !== 0)) {
+      if (envelopeHash === null) {
+        throw new Error('This pool requires a compliance envelope hash with each deposit');
+      }
+      complianceAttestation = PublicKey.findProgramAddressSync(
+        [Buffer.from('compliance_attestation'), programStatePDA.toBuffer(), commitment],
+        this.program.programId
+      )[0];
+      preInstructions.push(
+        await this.program.methods
+          .attestDeposit(Array.from(commitment), Array.from(envelopeHash))
+          .accounts({
+            programState: programStatePDA,
+            complianceAttestation,
+            depositor: this.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .instruction()
+      );
+    }
+
+    // Institutional pools take deposits only from wallets their KYC attestor issued a credential to
+    const kycCredential = (programState.kycAttestor as PublicKey).equals(PublicKey.default) ? null : PublicKey.findProgramAddressSync(
+      [Buffer.from('kyc_credential'), programStatePDA.toBuffer(), this.provider.wallet.publicKey.toBuffer()],
+      this.program.programId
+    )[0];
+
+    // Receipts are keyed by the leaf index this deposit will take
+    const [merkleTreePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('merkle_tree'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
+    const merkleTree = await this.program.account.merkleTree.fetch(merkleTreePDA);
+    const depositReceipt = withReceipt ? PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('receipt'),
+        programStatePDA.toBuffer(),
+        (merkleTree.nextIndex as BN).toArrayLike(Buffer, 'le', 8),
+      ],
+      this.program.programId
+    )[0] : null;
     
     // Derive the token vault PDA
     const [vaultPDA] = PublicKey.findProgramAddressSync(
@@ -118,14 +319,25 @@ class PrivaxClient {
         .deposit(new BN(amount), Array.from(commitment))
         .accounts({
           programState: programStatePDA,
+          feeConfig: feeConfigPDA,
+          merkleTree: merkleTreePDA,
+          commitmentShard: this.commitmentShard(programStatePDA, commitment),
+          referrerAccount: referrerAccount,
+          depositReceipt: depositReceipt,
           user: this.provider.wallet.publicKey,
           userTokenAccount: tokenAccount,
+          tokenMint: tokenMint,
           programTokenVault: vaultPDA,
           programTokenVaultAuthority: vaultAuthority,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           rent: await this.provider.connection.getMinimumBalanceForRentExemption(165),
+          ...(await this.usdLimitAccounts(programStatePDA)),
+          complianceAttestation,
+          kycCredential,
+          depositVolume,
         })
+        .preInstructions(preInstructions)
         .rpc();
       
       console.log('Deposit successful!');
@@ -138,11 +350,186 @@ class PrivaxClient {
   }
 
   // Example usage (requires implementation of ZK proof generation logic)
+  // Deposits several notes as one subtree. The leaf count must be a power of two (max 16),
+  // the tree's next index must be a multiple of it, and subtreeRoot must be the root of
+  // the commitments under the program's node hash. Each commitment's shard is passed in
+  // order after the named accounts, and must exist before the deposit.
+  async depositSubtree(tokenAccount: PublicKey, amounts: number[], commitments: Buffer[], subtreeRoot: Buffer) {
+    const [programStatePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('program_state')],
+      this.program.programId
+    );
+    const [merkleTreePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('merkle_tree'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
+    const [vaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('program_token_vault'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
+    const shards = commitments.map((commitment) => this.commitmentShard(programStatePDA, commitment));
+    const preInstructions = [treeInsertBudget()];
+    const { depositVolume, openDepositVolume } = await this.depositVolumeAccount(programStatePDA);
+    if (openDepositVolume !== null) {
+      preInstructions.push(openDepositVolume);
+    }
+    for (const [i, shard] of shards.entries()) {
+      if (shards.findIndex((known) => known.equals(shard)) === i &&
+          (await this.provider.connection.getAccountInfo(shard)) === null) {
+        preInstructions.push(
+          await this.program.methods
+            .initCommitmentShard(Array.from(commitments[i].subarray(0, 2)))
+            .accounts({
+              programState: programStatePDA,
+              commitmentShard: shard,
+              payer: this.provider.wallet.publicKey,
+              systemProgram: SystemProgram.programId,
+            })
+            .instruction()
+        );
+      }
+    }
+
+    const tx = await this.program.methods
+      .depositSubtree(
+        amounts.map((amount) => new BN(amount)),
+        commitments.map((commitment) => Array.from(commitment)),
+        Array.from(subtreeRoot)
+      )
+      .accounts({
+        programState: programStatePDA,
+        merkleTree: merkleTreePDA,
+        user: this.provider.wallet.publicKey,
+        userTokenAccount: tokenAccount,
+        programTokenVault: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        ...(await this.usdLimitAccounts(programStatePDA)),
+        depositVolume,
+      })
+      .remainingAccounts(shards.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+      .preInstructions(preInstructions)
+      .rpc();
+    console.log(`Deposited ${commitments.length} notes as a subtree:`, tx);
+    return tx;
+  }
+
+  // Phase one of a two-phase deposit: registers the commitment without moving funds
+  async registerCommitment(commitment: Buffer, amount: number) {
+    const [programStatePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('program_state')],
+      this.program.programId
+    );
+    const [pendingCommitmentPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('pending_commitment'), programStatePDA.toBuffer(), commitment],
+      this.program.programId
+    );
+
+    const tx = await this.program.methods
+      .registerCommitment(Array.from(commitment), new BN(amount))
+      .accounts({
+        programState: programStatePDA,
+        pendingCommitment: pendingCommitmentPDA,
+        registrant: this.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    console.log('Commitment registered:', tx);
+    return tx;
+  }
+
+  // Phase two: funds a registered commitment from this wallet's token account
+  async fundCommitment(commitment: Buffer, tokenAccount: PublicKey) {
+    const [programStatePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('program_state')],
+      this.program.programId
+    );
+    const [pendingCommitmentPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('pending_commitment'), programStatePDA.toBuffer(), commitment],
+      this.program.programId
+    );
+    const pending = await this.program.account.pendingCommitment.fetch(pendingCommitmentPDA);
+    const [merkleTreePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('merkle_tree'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
+    const [vaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('program_token_vault'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
+
+    const { depositVolume, openDepositVolume } = await this.depositVolumeAccount(programStatePDA);
+
+    const tx = await this.program.methods
+      .fundCommitment()
+      .accounts({
+        programState: programStatePDA,
+        merkleTree: merkleTreePDA,
+        pendingCommitment: pendingCommitmentPDA,
+        commitmentShard: this.commitmentShard(programStatePDA, commitment),
+        registrant: pending.registrant,
+        funder: this.provider.wallet.publicKey,
+        funderTokenAccount: tokenAccount,
+        programTokenVault: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        ...(await this.usdLimitAccounts(programStatePDA)),
+        depositVolume,
+      })
+      .preInstructions(openDepositVolume === null ? [treeInsertBudget()] : [treeInsertBudget(), openDepositVolume])
+      .rpc();
+    console.log('Commitment funded:', tx);
+    return tx;
+  }
+
+  // Cheap pre-check against the on-chain bloom filter. False means definitely unspent;
+  // true means possibly spent, so confirm by looking up the nullifier PDA.
+  async nullifierMightBeSpent(nullifierHash: Buffer): Promise<boolean> {
+    const [programStatePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('program_state')],
+      this.program.programId
+    );
+    const [nullifierFilterPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('nullifier_filter'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
+    const filter = await this.program.account.nullifierFilter.fetch(nullifierFilterPDA);
+    const bits = filter.bits as number[];
+
+    // Mirrors NullifierFilter::bit_indices: four u16 positions from sha256(nullifier_hash)
+    const digest = createHash('sha256').update(nullifierHash).digest();
+    for (let i = 0; i < 4; i++) {
+      const index = digest.readUInt16LE(2 * i);
+      if ((bits[index >> 3] & (1 << (index & 7))) === 0) {
+        return false;
+      }
+    }
+    return true;
+  }
+
   async withdraw(
-    proofData: { aProof: Buffer, bProof: Buffer, cProof: Buffer, publicInputs: number[] },
+    // aProof and cProof are 64-byte and bProof 128-byte uncompressed Groth16 points
+    // publicInputs: [root, nullifierHash, recipient, amount, externalNullifier, proofSlot,
+    // relayerFee, relayer]. relayerFee must equal relayerFee below, and relayer is this wallet's
+    // key (its first 8 bytes, LE), or 0 for a withdrawal without a relayer fee.
+    // The circuit must range-check amount (and every note amount it sums) to 64 bits; the
+    // program only accepts circuits registered with amountRangeBits = 64.
+    // Multi-note circuits append up to three further nullifierHashes, then the change
+    // commitment's input when changeCommitment is set.
+    // merkleRoot and nullifierHashes are the full 32-byte values the root and nullifierHash
+    // inputs stand for (every note's, in order); the proof is verified against them.
+    proofData: {
+      aProof: Buffer, bProof: Buffer, cProof: Buffer, publicInputs: number[],
+      merkleRoot: Buffer, nullifierHashes: Buffer[],
+    },
     recipientAddress: PublicKey,
     recipientTokenAccount: PublicKey,
-    amountToWithdraw: number
+    amountToWithdraw: number,
+    relayerFee: number = 0,
+    relayerFeeTokenAccount: PublicKey | null = null,
+    circuitVersion: number = 1, // Circuit the note's proof was generated for
+    changeCommitment: Buffer | null = null, // 32-byte commitment for the change note, if any
+    rootArchivePage: number | null = null // Archive page holding the proof's root once it has left the recent roots
   ) {
     const [programStatePDA] = PublicKey.findProgramAddressSync(
       [Buffer.from('program_state')],
@@ -160,26 +547,106 @@ class PrivaxClient {
       this.program.programId
     );
 
+    // Spent nullifiers live in shards keyed by the first two bytes of each nullifierHash;
+    // open a shard first if this is the first nullifier with that prefix
+    const shards: PublicKey[] = [];
+    const preInstructions = [];
+    for (const nullifierHash of proofData.nullifierHashes) {
+      const shardPrefix = nullifierHash.subarray(0, 2);
+      const [shard] = PublicKey.findProgramAddressSync(
+        [Buffer.from('nullifier_shard'), programStatePDA.toBuffer(), shardPrefix],
+        this.program.programId
+      );
+      if (!shards.some((known) => known.equals(shard)) &&
+          (await this.provider.connection.getAccountInfo(shard)) === null) {
+        preInstructions.push(
+          await this.program.methods
+            .initNullifierShard(Array.from(shardPrefix))
+            .accounts({
+              programState: programStatePDA,
+              nullifierShard: shard,
+              payer: this.provider.wallet.publicKey,
+              systemProgram: SystemProgram.programId,
+            })
+            .instruction()
+        );
+      }
+      shards.push(shard);
+    }
+    const [nullifierShard, ...extraShards] = shards;
+    const [nullifierFilterPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('nullifier_filter'), programStatePDA.toBuffer()],
+      this.program.programId
+    );
+    const [verifyingKeyPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('verifying_key'),
+        programStatePDA.toBuffer(),
+        new BN(circuitVersion).toArrayLike(Buffer, 'le', 2),
+      ],
+      this.program.programId
+    );
+
     console.log(`Withdrawing ${amountToWithdraw} tokens to ${recipientAddress.toBase58()}...`);
     try {
-      const tx = await this.program.methods
+      const builder = this.program.methods
         .withdraw(
           Array.from(proofData.aProof),
           Array.from(proofData.bProof),
           Array.from(proofData.cProof),
-          proofData.publicInputs,
+          // Padded to the program's fixed MAX_PUBLIC_INPUTS slots
+          [...proofData.publicInputs, ...Array(MAX_PUBLIC_INPUTS - proofData.publicInputs.length).fill(0)],
+          Array.from(proofData.merkleRoot),
+          Array.from(proofData.nullifierHashes[0]),
+          proofData.nullifierHashes.slice(1).map((hash) => Array.from(hash)),
           recipientAddress,
-          new BN(amountToWithdraw)
+          new BN(amountToWithdraw),
+          new BN(relayerFee),
+          Buffer.alloc(0), // No fee swap: relayer is paid in the pool mint
+          new BN(0),
+          circuitVersion,
+          Buffer.alloc(0), // Groth16: no PLONK proof bytes
+          changeCommitment === null ? null : Array.from(changeCommitment)
         )
         .accounts({
           programState: programStatePDA,
+          verifyingKey: verifyingKeyPDA,
+          feeConfig: PublicKey.findProgramAddressSync(
+            [Buffer.from('fee_config'), programStatePDA.toBuffer()],
+            this.program.programId
+          )[0],
+          nullifierShard: nullifierShard,
+          nullifierFilter: nullifierFilterPDA,
+          merkleTree: PublicKey.findProgramAddressSync(
+            [Buffer.from('merkle_tree'), programStatePDA.toBuffer()],
+            this.program.programId
+          )[0],
+          rootArchive: rootArchivePage === null ? null : PublicKey.findProgramAddressSync(
+            [Buffer.from('root_archive'), programStatePDA.toBuffer(), new BN(rootArchivePage).toArrayLike(Buffer, 'le', 8)],
+            this.program.programId
+          )[0],
           user: this.provider.wallet.publicKey,
           programTokenVault: vaultPDA,
           programTokenVaultAuthority: vaultAuthority,
           recipientTokenAccount: recipientTokenAccount,
+          relayerInfo: relayerFeeTokenAccount === null ? null : PublicKey.findProgramAddressSync(
+            [Buffer.from('relayer'), this.provider.wallet.publicKey.toBuffer()],
+            this.program.programId
+          )[0],
+          relayerFeeTokenAccount: relayerFeeTokenAccount,
+          feeSwapProgram: null,
           tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          ...(await this.usdLimitAccounts(programStatePDA)),
         })
-        .rpc();
+        .remainingAccounts(extraShards.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })));
+
+      const transaction = new TransactionBuilder(this.provider.connection, this.provider.wallet)
+        .add(...preInstructions, await builder.instruction());
+      if (this.lookupTable !== null) {
+        transaction.withLookupTables(await fetchLookupTable(this.provider.connection, this.lookupTable));
+      }
+      const tx = await transaction.send();
       
       console.log('Withdrawal successful!');
       console.log('Transaction signature:', tx);
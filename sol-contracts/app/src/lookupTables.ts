@@ -0,0 +1,116 @@
+import {
+  AddressLookupTableAccount,
+  AddressLookupTableProgram,
+  Connection,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  TransactionInstruction,
+  TransactionMessage,
+  VersionedTransaction,
+} from '@solana/web3.js';
+import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
+import BN from 'bn.js';
+
+// Address Lookup Table (ALT) support for withdrawals. A withdraw touches a dozen or more
+// accounts (plus extra nullifier shards for multi-note spends), most of them the same for
+// every call; listing those in an ALT replaces their 32-byte keys with 1-byte indexes so the
+// transaction fits the packet size limit. Per-withdrawal accounts (nullifier shards,
+// recipient and relayer token accounts) stay in the message itself.
+
+// extendLookupTable instructions carry at most this many addresses to stay within a transaction.
+const MAX_ADDRESSES_PER_EXTEND = 30;
+
+// Accounts every withdraw (and deposit) references, for the given circuit versions.
+export function protocolStaticAccounts(programId: PublicKey, circuitVersions: number[] = [1]): PublicKey[] {
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, programId)[0];
+  const programState = pda(Buffer.from('program_state'));
+  return [
+    programState,
+    pda(Buffer.from('fee_config'), programState.toBuffer()),
+    pda(Buffer.from('nullifier_filter'), programState.toBuffer()),
+    pda(Buffer.from('merkle_tree'), programState.toBuffer()),
+    // The vault is its own authority
+    pda(Buffer.from('program_token_vault'), programState.toBuffer()),
+    ...circuitVersions.map((circuitVersion) =>
+      pda(Buffer.from('verifying_key'), programState.toBuffer(), new BN(circuitVersion).toArrayLike(Buffer, 'le', 2))
+    ),
+    TOKEN_PROGRAM_ID,
+    SystemProgram.programId,
+  ];
+}
+
+async function sendInstructions(connection: Connection, payer: Keypair, instructions: TransactionInstruction[]) {
+  const { blockhash, lastValidBlockHeight } = await connection.getLatestBlockhash();
+  const tx = new VersionedTransaction(
+    new TransactionMessage({ payerKey: payer.publicKey, recentBlockhash: blockhash, instructions }).compileToV0Message()
+  );
+  tx.sign([payer]);
+  const signature = await connection.sendTransaction(tx);
+  await connection.confirmTransaction({ signature, blockhash, lastValidBlockHeight });
+  return signature;
+}
+
+export async function fetchLookupTable(connection: Connection, address: PublicKey): Promise<AddressLookupTableAccount> {
+  const table = (await connection.getAddressLookupTable(address)).value;
+  if (table === null) {
+    throw new Error(`Lookup table ${address.toBase58()} not found`);
+  }
+  return table;
+}
+
+// Adds whichever of `addresses` the table doesn't hold yet. Only the table's authority can
+// extend it. New entries become usable one slot after the extension lands.
+export async function extendLookupTable(
+  connection: Connection,
+  authority: Keypair,
+  lookupTable: PublicKey,
+  addresses: PublicKey[]
+): Promise<PublicKey[]> {
+  const existing = (await fetchLookupTable(connection, lookupTable)).state.addresses;
+  const missing = addresses.filter(
+    (address, i) =>
+      !existing.some((known) => known.equals(address)) && addresses.findIndex((other) => other.equals(address)) === i
+  );
+  for (let start = 0; start < missing.length; start += MAX_ADDRESSES_PER_EXTEND) {
+    await sendInstructions(connection, authority, [
+      AddressLookupTableProgram.extendLookupTable({
+        lookupTable,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        addresses: missing.slice(start, start + MAX_ADDRESSES_PER_EXTEND),
+      }),
+    ]);
+  }
+  return missing;
+}
+
+// Creates a lookup table owned by `authority` holding the protocol's static accounts.
+export async function createProtocolLookupTable(
+  connection: Connection,
+  authority: Keypair,
+  programId: PublicKey,
+  circuitVersions: number[] = [1]
+): Promise<PublicKey> {
+  const [createIx, lookupTable] = AddressLookupTableProgram.createLookupTable({
+    authority: authority.publicKey,
+    payer: authority.publicKey,
+    recentSlot: await connection.getSlot('finalized'),
+  });
+  await sendInstructions(connection, authority, [createIx]);
+  await extendLookupTable(connection, authority, lookupTable, protocolStaticAccounts(programId, circuitVersions));
+  return lookupTable;
+}
+
+// Compiles `instructions` into an unsigned v0 transaction that resolves accounts through
+// `lookupTables`.
+export async function buildVersionedTransaction(
+  connection: Connection,
+  payer: PublicKey,
+  instructions: TransactionInstruction[],
+  lookupTables: AddressLookupTableAccount[]
+): Promise<VersionedTransaction> {
+  const { blockhash } = await connection.getLatestBlockhash();
+  const message = new TransactionMessage({ payerKey: payer, recentBlockhash: blockhash, instructions });
+  return new VersionedTransaction(message.compileToV0Message(lookupTables));
+}
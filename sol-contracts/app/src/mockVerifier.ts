@@ -0,0 +1,16 @@
+// The mock verifier program (programs/privax_mock_verifier) and its test proof, for localnet,
+// devnet and CI pools whose verifier_program_id points at it. Mirrors that program's
+// TEST_PROOF_MAGIC and test_proof; never use it for a pool holding real funds.
+import { PublicKey } from "@solana/web3.js";
+import { OnChainProof } from "./proofFormat";
+
+export const MOCK_VERIFIER_PROGRAM_ID = new PublicKey("EkRE7RWwAXyZWZFGfrEPWmuSyaERNAzP25B3ea3UAQdU");
+
+export const TEST_PROOF_MAGIC = Buffer.from("privax mock verifier test proof");
+
+// withdraw's a_proof, b_proof and c_proof: the magic after a zero byte in A's x, the rest zero
+export function testProof(): OnChainProof {
+  const aProof = Buffer.alloc(64);
+  TEST_PROOF_MAGIC.copy(aProof, 1);
+  return { aProof, bProof: Buffer.alloc(128), cProof: Buffer.alloc(64) };
+}
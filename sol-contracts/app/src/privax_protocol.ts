@@ -13,6 +13,26 @@ export const IDL = {
           "isMut": true,
           "isSigner": false
         },
+        {
+          "name": "feeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierFilter",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
         {
           "name": "admin",
           "isMut": true,
@@ -35,6 +55,32 @@ export const IDL = {
         }
       ]
     },
+    {
+      "name": "initAuditLog",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
     {
       "name": "addRelayer",
       "accounts": [
@@ -43,6 +89,75 @@ export const IDL = {
           "isMut": true,
           "isSigner": false
         },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "relayerInfo",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "relayerAddress",
+          "type": "publicKey"
+        },
+        {
+          "name": "endpointUrlHash",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "feeSchedule",
+          "type": {
+            "defined": "FeeSchedule"
+          }
+        },
+        {
+          "name": "expirySlot",
+          "type": {
+            "option": "u64"
+          }
+        }
+      ]
+    },
+    {
+      "name": "renewRelayer",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "relayerInfo",
+          "isMut": true,
+          "isSigner": false
+        },
         {
           "name": "admin",
           "isMut": false,
@@ -53,6 +168,12 @@ export const IDL = {
         {
           "name": "relayerAddress",
           "type": "publicKey"
+        },
+        {
+          "name": "expirySlot",
+          "type": {
+            "option": "u64"
+          }
         }
       ]
     },
@@ -64,10 +185,25 @@ export const IDL = {
           "isMut": true,
           "isSigner": false
         },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "relayerInfo",
+          "isMut": true,
+          "isSigner": false
+        },
         {
           "name": "admin",
           "isMut": false,
           "isSigner": true
+        },
+        {
+          "name": "rentReceiver",
+          "isMut": true,
+          "isSigner": false
         }
       ],
       "args": [
@@ -85,6 +221,11 @@ export const IDL = {
           "isMut": true,
           "isSigner": false
         },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
         {
           "name": "admin",
           "isMut": false,
@@ -99,64 +240,115 @@ export const IDL = {
       ]
     },
     {
-      "name": "deposit",
+      "name": "initiateRenounceAdmin",
       "accounts": [
         {
           "name": "programState",
-          "isMut": false,
+          "isMut": true,
           "isSigner": false
         },
         {
-          "name": "user",
+          "name": "auditLog",
           "isMut": true,
-          "isSigner": true
+          "isSigner": false
         },
         {
-          "name": "userTokenAccount",
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "confirmation",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "cancelRenounceAdmin",
+      "accounts": [
+        {
+          "name": "programState",
           "isMut": true,
           "isSigner": false
         },
         {
-          "name": "programTokenVault",
+          "name": "auditLog",
           "isMut": true,
           "isSigner": false
         },
         {
-          "name": "programTokenVaultAuthority",
+          "name": "admin",
           "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "renounceAdmin",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
           "isSigner": false
         },
         {
-          "name": "tokenProgram",
-          "isMut": false,
+          "name": "auditLog",
+          "isMut": true,
           "isSigner": false
         },
         {
-          "name": "systemProgram",
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "confirmation",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "updateFeeConfig",
+      "accounts": [
+        {
+          "name": "programState",
           "isMut": false,
           "isSigner": false
         },
         {
-          "name": "rent",
-          "isMut": false,
+          "name": "feeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
           "isSigner": false
+        },
+        {
+          "name": "feeAuthority",
+          "isMut": false,
+          "isSigner": true
         }
       ],
       "args": [
         {
-          "name": "amount",
-          "type": "u64"
+          "name": "protocolFeeBps",
+          "type": "u16"
         },
         {
-          "name": "commitment",
-          "type": {
-            "array": ["u8", 32]
-          }
+          "name": "maxProtocolFee",
+          "type": "u64"
         }
       ]
     },
     {
-      "name": "withdraw",
+      "name": "setFeeAuthority",
       "accounts": [
         {
           "name": "programState",
@@ -164,151 +356,6433 @@ export const IDL = {
           "isSigner": false
         },
         {
-          "name": "user",
+          "name": "feeConfig",
           "isMut": true,
-          "isSigner": true
+          "isSigner": false
         },
         {
-          "name": "programTokenVault",
+          "name": "auditLog",
           "isMut": true,
           "isSigner": false
         },
         {
-          "name": "programTokenVaultAuthority",
+          "name": "feeAuthority",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "newFeeAuthority",
+          "type": "publicKey"
+        }
+      ]
+    },
+    {
+      "name": "setFeeBeneficiaries",
+      "accounts": [
+        {
+          "name": "programState",
           "isMut": false,
           "isSigner": false
         },
         {
-          "name": "recipientTokenAccount",
+          "name": "feeConfig",
           "isMut": true,
           "isSigner": false
         },
         {
-          "name": "tokenProgram",
-          "isMut": false,
+          "name": "auditLog",
+          "isMut": true,
           "isSigner": false
+        },
+        {
+          "name": "feeAuthority",
+          "isMut": false,
+          "isSigner": true
         }
       ],
       "args": [
         {
-          "name": "aProof",
+          "name": "beneficiaries",
           "type": {
-            "vec": "u8"
+            "vec": {
+              "defined": "FeeBeneficiary"
+            }
           }
-        },
+        }
+      ]
+    },
+    {
+      "name": "distributeFees",
+      "accounts": [
         {
-          "name": "bProof",
-          "type": {
-            "vec": "u8"
-          }
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
         },
         {
-          "name": "cProof",
-          "type": {
-            "vec": "u8"
-          }
+          "name": "feeConfig",
+          "isMut": true,
+          "isSigner": false
         },
         {
-          "name": "publicInputs",
-          "type": {
-            "vec": "u64"
-          }
+          "name": "programTokenVault",
+          "isMut": true,
+          "isSigner": false
         },
         {
-          "name": "recipientAddress",
-          "type": "publicKey"
+          "name": "programTokenVaultAuthority",
+          "isMut": false,
+          "isSigner": false
         },
         {
-          "name": "amountToWithdraw",
-          "type": "u64"
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
         }
-      ]
-    }
-  ],
-  "accounts": [
-    {
+      ],
+      "args": []
+    },
+    {
+      "name": "setUtilizationFeeCurve",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "feeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "feeAuthority",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "utilizationKinkBps",
+          "type": "u16"
+        },
+        {
+          "name": "maxUtilizationFeeBps",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "setReferralShare",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "feeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "feeAuthority",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "referralShareBps",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "registerReferrer",
+      "accounts": [
+        {
+          "name": "referrerAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "referrer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "claimReferralFees",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "feeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "referrerAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "referrer",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "referrerTokenAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVaultAuthority",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "closeDepositReceipt",
+      "accounts": [
+        {
+          "name": "depositReceipt",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "depositor",
+          "isMut": true,
+          "isSigner": true
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "setFeeSwapProgram",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "feeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "feeAuthority",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "feeSwapProgram",
+          "type": "publicKey"
+        }
+      ]
+    },
+    {
+      "name": "setVerifierProgram",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "verifierProgramId",
+          "type": "publicKey"
+        }
+      ]
+    },
+    {
+      "name": "setProofSystem",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "proofSystem",
+          "type": {
+            "defined": "ProofSystem"
+          }
+        },
+        {
+          "name": "verifierProgramId",
+          "type": "publicKey"
+        }
+      ]
+    },
+    {
+      "name": "registerVerifyingKey",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "verifyingKey",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "circuitVersion",
+          "type": "u16"
+        },
+        {
+          "name": "proofSystem",
+          "type": {
+            "defined": "ProofSystem"
+          }
+        },
+        {
+          "name": "amountRangeBits",
+          "type": "u8"
+        },
+        {
+          "name": "vkLen",
+          "type": "u16"
+        },
+        {
+          "name": "vkData",
+          "type": "bytes"
+        }
+      ]
+    },
+    {
+      "name": "writeVerifyingKeyChunk",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "verifyingKey",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "chunk",
+          "type": "bytes"
+        }
+      ]
+    },
+    {
+      "name": "setVerifyingKeyEnabled",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "verifyingKey",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "enabled",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "freezeParams",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "groups",
+          "type": "u8"
+        }
+      ]
+    },
+    {
+      "name": "setGuardian",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "newGuardian",
+          "type": "publicKey"
+        }
+      ]
+    },
+    {
+      "name": "setAdminTimelock",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "slots",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "queueAdminAction",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "queuedAction",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "action",
+          "type": {
+            "defined": "AdminAction"
+          }
+        }
+      ]
+    },
+    {
+      "name": "executeAdminAction",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "queuedAction",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "verifyingKey",
+          "isMut": true,
+          "isSigner": false,
+          "isOptional": true
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "cancelAdminAction",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "queuedAction",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "authority",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "setTreeOperator",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "newTreeOperator",
+          "type": "publicKey"
+        }
+      ]
+    },
+    {
+      "name": "pause",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "authority",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "unpause",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "setWithdrawalLimit",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "maxWithdrawalPerEpoch",
+          "type": "u64"
+        },
+        {
+          "name": "withdrawalEpochSlots",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "setComplianceKey",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "complianceKey",
+          "type": {
+            "array": ["u8", 32]
+          }
+        }
+      ]
+    },
+    {
+      "name": "setKycAttestor",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "kycAttestor",
+          "type": "publicKey"
+        }
+      ]
+    },
+    {
+      "name": "issueKycCredential",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "kycCredential",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "attestor",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "holder",
+          "type": "publicKey"
+        },
+        {
+          "name": "expiresAt",
+          "type": "i64"
+        }
+      ]
+    },
+    {
+      "name": "revokeKycCredential",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "kycCredential",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "attestor",
+          "isMut": true,
+          "isSigner": true
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "setMaxDailyDeposit",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "maxDailyDeposit",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "initDepositVolume",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "depositVolume",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "depositor",
+          "type": "publicKey"
+        }
+      ]
+    },
+    {
+      "name": "setExclusionOperator",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "exclusionOperator",
+          "type": "publicKey"
+        }
+      ]
+    },
+    {
+      "name": "publishExclusionRoot",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "operator",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "root",
+          "type": {
+            "array": ["u8", 32]
+          }
+        }
+      ]
+    },
+    {
+      "name": "setExclusionRequired",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "exclusionRequired",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "setMaxPauseDuration",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "maxPauseSlots",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "setPauseOnShortfall",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "enabled",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "reconcile",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "feeConfig",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVault",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "deposit",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "feeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "commitmentShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "referrerAccount",
+          "isMut": true,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "depositReceipt",
+          "isMut": true,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "user",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "userTokenAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenMint",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVaultAuthority",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "rent",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "usdLimits",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "priceFeed",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "complianceAttestation",
+          "isMut": true,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "kycCredential",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "depositVolume",
+          "isMut": true,
+          "isSigner": false,
+          "isOptional": true
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        },
+        {
+          "name": "commitment",
+          "type": {
+            "array": ["u8", 32]
+          }
+        }
+      ]
+    },
+    {
+      "name": "announceNote",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "commitment",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "ciphertext",
+          "type": "bytes"
+        }
+      ]
+    },
+    {
+      "name": "attestDeposit",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "complianceAttestation",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "depositor",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "commitment",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "envelopeHash",
+          "type": {
+            "array": ["u8", 32]
+          }
+        }
+      ]
+    },
+    {
+      "name": "registerCommitment",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "pendingCommitment",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "registrant",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "commitment",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "fundCommitment",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "commitmentShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "pendingCommitment",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "registrant",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "funder",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "funderTokenAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "usdLimits",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "priceFeed",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "depositVolume",
+          "isMut": true,
+          "isSigner": false,
+          "isOptional": true
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "reclaimExpiredRegistration",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "pendingCommitment",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "registrant",
+          "isMut": true,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "depositSubtree",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "user",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "userTokenAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "usdLimits",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "priceFeed",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "depositVolume",
+          "isMut": true,
+          "isSigner": false,
+          "isOptional": true
+        }
+      ],
+      "args": [
+        {
+          "name": "amounts",
+          "type": {
+            "vec": "u64"
+          }
+        },
+        {
+          "name": "commitments",
+          "type": {
+            "vec": {
+              "array": ["u8", 32]
+            }
+          }
+        },
+        {
+          "name": "subtreeRoot",
+          "type": {
+            "array": ["u8", 32]
+          }
+        }
+      ]
+    },
+    {
+      "name": "crank",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "cranker",
+          "isMut": true,
+          "isSigner": true
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "initNullifierShard",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "prefix",
+          "type": {
+            "array": ["u8", 2]
+          }
+        }
+      ]
+    },
+    {
+      "name": "initCommitmentShard",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "commitmentShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "prefix",
+          "type": {
+            "array": ["u8", 2]
+          }
+        }
+      ]
+    },
+    {
+      "name": "checkNullifiers",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierFilter",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "nullifierHashes",
+          "type": {
+            "vec": {
+              "array": ["u8", 32]
+            }
+          }
+        }
+      ],
+      "returns": {
+        "vec": "bool"
+      }
+    },
+    {
+      "name": "withdraw",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "verifyingKey",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "feeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierFilter",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "rootArchive",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "user",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "programTokenVault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVaultAuthority",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "recipientTokenAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "relayerInfo",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "relayerFeeTokenAccount",
+          "isMut": true,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "feeSwapProgram",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "usdLimits",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "priceFeed",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        }
+      ],
+      "args": [
+        {
+          "name": "aProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "bProof",
+          "type": {
+            "array": ["u8", 128]
+          }
+        },
+        {
+          "name": "cProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "publicInputs",
+          "type": {
+            "array": ["u64", 12]
+          }
+        },
+        {
+          "name": "merkleRoot",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "nullifierHash",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "extraNullifierHashes",
+          "type": {
+            "vec": {
+              "array": ["u8", 32]
+            }
+          }
+        },
+        {
+          "name": "recipientAddress",
+          "type": "publicKey"
+        },
+        {
+          "name": "amountToWithdraw",
+          "type": "u64"
+        },
+        {
+          "name": "relayerFee",
+          "type": "u64"
+        },
+        {
+          "name": "feeSwapData",
+          "type": "bytes"
+        },
+        {
+          "name": "minFeeOut",
+          "type": "u64"
+        },
+        {
+          "name": "circuitVersion",
+          "type": "u16"
+        },
+        {
+          "name": "plonkProof",
+          "type": "bytes"
+        },
+        {
+          "name": "changeCommitment",
+          "type": {
+            "option": {
+              "array": ["u8", 32]
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "withdrawSplit",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "verifyingKey",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "feeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierFilter",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "rootArchive",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "user",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "programTokenVault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVaultAuthority",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "usdLimits",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "priceFeed",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        }
+      ],
+      "args": [
+        {
+          "name": "aProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "bProof",
+          "type": {
+            "array": ["u8", 128]
+          }
+        },
+        {
+          "name": "cProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "publicInputs",
+          "type": {
+            "array": ["u64", 15]
+          }
+        },
+        {
+          "name": "merkleRoot",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "nullifierHash",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "payouts",
+          "type": {
+            "vec": {
+              "defined": "Payout"
+            }
+          }
+        },
+        {
+          "name": "circuitVersion",
+          "type": "u16"
+        },
+        {
+          "name": "plonkProof",
+          "type": "bytes"
+        }
+      ]
+    },
+    {
+      "name": "addSwapTarget",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "targetProgram",
+          "type": "publicKey"
+        }
+      ]
+    },
+    {
+      "name": "removeSwapTarget",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "targetProgram",
+          "type": "publicKey"
+        }
+      ]
+    },
+    {
+      "name": "shieldedSwap",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "verifyingKey",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "feeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierFilter",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "rootArchive",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "submitter",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "programTokenVault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVaultAuthority",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "swapProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "swapOutputAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "submitterOutputAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "targetProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "targetProgramState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "targetFeeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "targetMerkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "targetCommitmentShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "targetVault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "targetVaultAuthority",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "targetTokenMint",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "rent",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "aProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "bProof",
+          "type": {
+            "array": ["u8", 128]
+          }
+        },
+        {
+          "name": "cProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "publicInputs",
+          "type": {
+            "array": ["u64", 8]
+          }
+        },
+        {
+          "name": "merkleRoot",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "nullifierHash",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "amountIn",
+          "type": "u64"
+        },
+        {
+          "name": "amountOut",
+          "type": "u64"
+        },
+        {
+          "name": "outputCommitment",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "swapData",
+          "type": "bytes"
+        },
+        {
+          "name": "circuitVersion",
+          "type": "u16"
+        },
+        {
+          "name": "plonkProof",
+          "type": "bytes"
+        }
+      ]
+    },
+    {
+      "name": "splitNote",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "verifyingKey",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierFilter",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "rootArchive",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "aProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "bProof",
+          "type": {
+            "array": ["u8", 128]
+          }
+        },
+        {
+          "name": "cProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "publicInputs",
+          "type": {
+            "array": ["u64", 8]
+          }
+        },
+        {
+          "name": "merkleRoot",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "nullifierHash",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "circuitVersion",
+          "type": "u16"
+        },
+        {
+          "name": "outputCommitments",
+          "type": {
+            "vec": {
+              "array": ["u8", 32]
+            }
+          }
+        },
+        {
+          "name": "plonkProof",
+          "type": "bytes"
+        }
+      ]
+    },
+    {
+      "name": "mergeNotes",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "verifyingKey",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierFilter",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "rootArchive",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "aProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "bProof",
+          "type": {
+            "array": ["u8", 128]
+          }
+        },
+        {
+          "name": "cProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "publicInputs",
+          "type": {
+            "array": ["u64", 8]
+          }
+        },
+        {
+          "name": "merkleRoot",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "nullifierHash",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "circuitVersion",
+          "type": "u16"
+        },
+        {
+          "name": "extraNullifierHashes",
+          "type": {
+            "vec": {
+              "array": ["u8", 32]
+            }
+          }
+        },
+        {
+          "name": "outputCommitment",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "plonkProof",
+          "type": "bytes"
+        }
+      ]
+    },
+    {
+      "name": "createPaymentSchedule",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "paymentSchedule",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "scheduleEscrow",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenMint",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "owner",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "associatedTokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "rent",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "scheduleId",
+          "type": "u64"
+        },
+        {
+          "name": "recipient",
+          "type": "publicKey"
+        },
+        {
+          "name": "amount",
+          "type": "u64"
+        },
+        {
+          "name": "executorFee",
+          "type": "u64"
+        },
+        {
+          "name": "intervalSlots",
+          "type": "u64"
+        },
+        {
+          "name": "firstPaymentSlot",
+          "type": "u64"
+        },
+        {
+          "name": "payments",
+          "type": "u32"
+        }
+      ]
+    },
+    {
+      "name": "executeScheduledPayment",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "paymentSchedule",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "scheduleEscrow",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "recipientTokenAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "executor",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "executorTokenAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "cancelPaymentSchedule",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "paymentSchedule",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "scheduleEscrow",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "owner",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "ownerTokenAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "enableNftMode",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "depositNft",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "commitmentShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "user",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "nftMint",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "userTokenAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "nftVault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVaultAuthority",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "programmable",
+          "accounts": [
+            {
+              "name": "metadata",
+              "isMut": true,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "edition",
+              "isMut": false,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "ownerTokenRecord",
+              "isMut": true,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "destinationTokenRecord",
+              "isMut": true,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "tokenMetadataProgram",
+              "isMut": false,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "sysvarInstructions",
+              "isMut": false,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "authorizationRulesProgram",
+              "isMut": false,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "authorizationRules",
+              "isMut": false,
+              "isSigner": false,
+              "isOptional": true
+            }
+          ]
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "associatedTokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "commitment",
+          "type": {
+            "array": ["u8", 32]
+          }
+        }
+      ]
+    },
+    {
+      "name": "withdrawNft",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "verifyingKey",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierShard",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "nullifierFilter",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "rootArchive",
+          "isMut": false,
+          "isSigner": false,
+          "isOptional": true
+        },
+        {
+          "name": "user",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "nftMint",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "nftVault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programTokenVaultAuthority",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "recipient",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "recipientTokenAccount",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programmable",
+          "accounts": [
+            {
+              "name": "metadata",
+              "isMut": true,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "edition",
+              "isMut": false,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "ownerTokenRecord",
+              "isMut": true,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "destinationTokenRecord",
+              "isMut": true,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "tokenMetadataProgram",
+              "isMut": false,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "sysvarInstructions",
+              "isMut": false,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "authorizationRulesProgram",
+              "isMut": false,
+              "isSigner": false,
+              "isOptional": true
+            },
+            {
+              "name": "authorizationRules",
+              "isMut": false,
+              "isSigner": false,
+              "isOptional": true
+            }
+          ]
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "associatedTokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "aProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "bProof",
+          "type": {
+            "array": ["u8", 128]
+          }
+        },
+        {
+          "name": "cProof",
+          "type": {
+            "array": ["u8", 64]
+          }
+        },
+        {
+          "name": "publicInputs",
+          "type": {
+            "array": ["u64", 6]
+          }
+        },
+        {
+          "name": "merkleRoot",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "nullifierHash",
+          "type": {
+            "array": ["u8", 32]
+          }
+        },
+        {
+          "name": "circuitVersion",
+          "type": "u16"
+        },
+        {
+          "name": "plonkProof",
+          "type": "bytes"
+        }
+      ]
+    },
+    {
+      "name": "setReceiptNftConfig",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "receiptNftConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "enabled",
+          "type": "bool"
+        },
+        {
+          "name": "merkleTree",
+          "type": "publicKey"
+        },
+        {
+          "name": "name",
+          "type": "string"
+        },
+        {
+          "name": "symbol",
+          "type": "string"
+        },
+        {
+          "name": "uri",
+          "type": "string"
+        }
+      ]
+    },
+    {
+      "name": "claimReceiptNft",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "receiptNftConfig",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "depositReceipt",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "receiptNftClaim",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "depositor",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "treeConfig",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "bubblegumProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "logWrapper",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "compressionProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "setUsdLimits",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "usdLimits",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenMint",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "priceFeed",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "maxPriceAge",
+          "type": "u64"
+        },
+        {
+          "name": "maxConfidenceBps",
+          "type": "u16"
+        },
+        {
+          "name": "maxDepositUsd",
+          "type": "u64"
+        },
+        {
+          "name": "largeWithdrawalUsd",
+          "type": "u64"
+        },
+        {
+          "name": "flatFeeUsd",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "checkpointRoot",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "treeCheckpoints",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "operator",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "archiveRoots",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "rootArchive",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "operator",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "page",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "setArchivedRootsAccepted",
+      "accounts": [
+        {
+          "name": "programState",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "merkleTree",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "auditLog",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        }
+      ],
+      "args": [
+        {
+          "name": "accept",
+          "type": "bool"
+        }
+      ]
+    }
+  ],
+  "accounts": [
+    {
       "name": "ProgramState",
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "admin",
+            "name": "admin",
+            "type": "publicKey"
+          },
+          {
+            "name": "tokenMint",
+            "type": "publicKey"
+          },
+          {
+            "name": "verifierProgramId",
+            "type": "publicKey"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "whitelistedRelayers",
+            "type": {
+              "vec": "publicKey"
+            }
+          },
+          {
+            "name": "guardian",
+            "type": "publicKey"
+          },
+          {
+            "name": "paused",
+            "type": "bool"
+          },
+          {
+            "name": "pausedAtSlot",
+            "type": "u64"
+          },
+          {
+            "name": "renounceInitiatedSlot",
+            "type": "u64"
+          },
+          {
+            "name": "renounced",
+            "type": "bool"
+          },
+          {
+            "name": "frozenGroups",
+            "type": "u8"
+          },
+          {
+            "name": "outstandingDeposits",
+            "type": "u64"
+          },
+          {
+            "name": "proofSystem",
+            "type": {
+              "defined": "ProofSystem"
+            }
+          },
+          {
+            "name": "poolMode",
+            "type": {
+              "defined": "PoolMode"
+            }
+          },
+          {
+            "name": "usdLimits",
+            "type": "bool"
+          },
+          {
+            "name": "treeOperator",
+            "type": "publicKey"
+          },
+          {
+            "name": "maxWithdrawalPerEpoch",
+            "type": "u64"
+          },
+          {
+            "name": "withdrawalEpochSlots",
+            "type": "u64"
+          },
+          {
+            "name": "withdrawalEpoch",
+            "type": "u64"
+          },
+          {
+            "name": "withdrawnInEpoch",
+            "type": "u64"
+          },
+          {
+            "name": "totalDeposited",
+            "type": "u64"
+          },
+          {
+            "name": "totalWithdrawn",
+            "type": "u64"
+          },
+          {
+            "name": "pauseOnShortfall",
+            "type": "bool"
+          },
+          {
+            "name": "maxPauseSlots",
+            "type": "u64"
+          },
+          {
+            "name": "complianceKey",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "kycAttestor",
+            "type": "publicKey"
+          },
+          {
+            "name": "maxDailyDeposit",
+            "type": "u64"
+          },
+          {
+            "name": "exclusionOperator",
+            "type": "publicKey"
+          },
+          {
+            "name": "exclusionRoot",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "previousExclusionRoot",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "exclusionRootSlot",
+            "type": "u64"
+          },
+          {
+            "name": "exclusionRequired",
+            "type": "bool"
+          },
+          {
+            "name": "adminTimelockSlots",
+            "type": "u64"
+          },
+          {
+            "name": "nextAdminActionId",
+            "type": "u64"
+          },
+          {
+            "name": "swapTargets",
+            "type": {
+              "vec": "publicKey"
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "FeeConfig",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "feeAuthority",
+            "type": "publicKey"
+          },
+          {
+            "name": "protocolFeeBps",
+            "type": "u16"
+          },
+          {
+            "name": "maxProtocolFee",
+            "type": "u64"
+          },
+          {
+            "name": "feeSwapProgram",
+            "type": "publicKey"
+          },
+          {
+            "name": "accruedProtocolFees",
+            "type": "u64"
+          },
+          {
+            "name": "referralShareBps",
+            "type": "u16"
+          },
+          {
+            "name": "owedReferralFees",
+            "type": "u64"
+          },
+          {
+            "name": "utilizationKinkBps",
+            "type": "u16"
+          },
+          {
+            "name": "maxUtilizationFeeBps",
+            "type": "u16"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "beneficiaries",
+            "type": {
+              "vec": {
+                "defined": "FeeBeneficiary"
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "UsdLimits",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "priceFeed",
+            "type": "publicKey"
+          },
+          {
+            "name": "maxPriceAge",
+            "type": "u64"
+          },
+          {
+            "name": "maxConfidenceBps",
+            "type": "u16"
+          },
+          {
+            "name": "mintDecimals",
+            "type": "u8"
+          },
+          {
+            "name": "maxDepositUsd",
+            "type": "u64"
+          },
+          {
+            "name": "largeWithdrawalUsd",
+            "type": "u64"
+          },
+          {
+            "name": "flatFeeUsd",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "TreeCheckpoints",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "count",
+            "type": "u64"
+          },
+          {
+            "name": "checkpoints",
+            "type": {
+              "array": [{ "defined": "TreeCheckpoint" }, 16]
+            }
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "AuditLog",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "count",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": {
+              "array": ["u8", 7]
+            }
+          },
+          {
+            "name": "entries",
+            "type": {
+              "array": [{ "defined": "AuditEntry" }, 64]
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "DepositReceipt",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "depositor",
+            "type": "publicKey"
+          },
+          {
+            "name": "leafIndex",
+            "type": "u64"
+          },
+          {
+            "name": "slot",
+            "type": "u64"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "commitment",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "MerkleTree",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "nextIndex",
+            "type": "u64"
+          },
+          {
+            "name": "currentRootIndex",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": {
+              "array": ["u8", 7]
+            }
+          },
+          {
+            "name": "filledSubtrees",
+            "type": {
+              "array": [
+                {
+                  "array": ["u8", 32]
+                },
+                20
+              ]
+            }
+          },
+          {
+            "name": "zeros",
+            "type": {
+              "array": [
+                {
+                  "array": ["u8", 32]
+                },
+                20
+              ]
+            }
+          },
+          {
+            "name": "roots",
+            "type": {
+              "array": [
+                {
+                  "array": ["u8", 32]
+                },
+                30
+              ]
+            }
+          },
+          {
+            "name": "rootCount",
+            "type": "u64"
+          },
+          {
+            "name": "archivedRoots",
+            "type": "u64"
+          },
+          {
+            "name": "acceptArchivedRoots",
+            "type": "u8"
+          },
+          {
+            "name": "reserved",
+            "type": {
+              "array": ["u8", 7]
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "RootArchivePage",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "page",
+            "type": "u64"
+          },
+          {
+            "name": "count",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": {
+              "array": ["u8", 7]
+            }
+          },
+          {
+            "name": "roots",
+            "type": {
+              "array": [
+                {
+                  "array": ["u8", 32]
+                },
+                256
+              ]
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "VerifyingKey",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "circuitVersion",
+            "type": "u16"
+          },
+          {
+            "name": "vkLen",
+            "type": "u16"
+          },
+          {
+            "name": "written",
+            "type": "u16"
+          },
+          {
+            "name": "proofSystem",
+            "type": "u8"
+          },
+          {
+            "name": "enabled",
+            "type": "u8"
+          },
+          {
+            "name": "amountRangeBits",
+            "type": "u8"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "vkData",
+            "type": {
+              "array": ["u8", 2048]
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "NullifierShard",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "prefix",
+            "type": {
+              "array": ["u8", 2]
+            }
+          },
+          {
+            "name": "count",
+            "type": "u32"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "CommitmentShard",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "prefix",
+            "type": {
+              "array": ["u8", 2]
+            }
+          },
+          {
+            "name": "count",
+            "type": "u32"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "NullifierFilter",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "inserted",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": {
+              "array": ["u8", 7]
+            }
+          },
+          {
+            "name": "bits",
+            "type": {
+              "array": ["u8", 8192]
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "PendingCommitment",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "registrant",
+            "type": "publicKey"
+          },
+          {
+            "name": "commitment",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "expirySlot",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "ComplianceAttestation",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "depositor",
+            "type": "publicKey"
+          },
+          {
+            "name": "commitment",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "envelopeHash",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "complianceKey",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "leafIndex",
+            "type": "u64"
+          },
+          {
+            "name": "depositSlot",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "KycCredential",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "holder",
+            "type": "publicKey"
+          },
+          {
+            "name": "attestor",
+            "type": "publicKey"
+          },
+          {
+            "name": "issuedAt",
+            "type": "i64"
+          },
+          {
+            "name": "expiresAt",
+            "type": "i64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "DepositVolume",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "depositor",
+            "type": "publicKey"
+          },
+          {
+            "name": "hourly",
+            "type": {
+              "array": ["u64", 24]
+            }
+          },
+          {
+            "name": "lastHour",
+            "type": "i64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "ReferrerAccount",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "referrer",
+            "type": "publicKey"
+          },
+          {
+            "name": "claimable",
+            "type": "u64"
+          },
+          {
+            "name": "totalClaimed",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "RelayerInfo",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "relayer",
+            "type": "publicKey"
+          },
+          {
+            "name": "endpointUrlHash",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "feeSchedule",
+            "type": {
+              "defined": "FeeSchedule"
+            }
+          },
+          {
+            "name": "expirySlot",
+            "type": {
+              "option": "u64"
+            }
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "ReceiptNftConfig",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "enabled",
+            "type": "bool"
+          },
+          {
+            "name": "merkleTree",
+            "type": "publicKey"
+          },
+          {
+            "name": "name",
+            "type": "string"
+          },
+          {
+            "name": "symbol",
+            "type": "string"
+          },
+          {
+            "name": "uri",
+            "type": "string"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "ReceiptNftClaim",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "slot",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "PaymentSchedule",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "owner",
+            "type": "publicKey"
+          },
+          {
+            "name": "scheduleId",
+            "type": "u64"
+          },
+          {
+            "name": "recipient",
+            "type": "publicKey"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "executorFee",
+            "type": "u64"
+          },
+          {
+            "name": "intervalSlots",
+            "type": "u64"
+          },
+          {
+            "name": "nextPaymentSlot",
+            "type": "u64"
+          },
+          {
+            "name": "paymentsRemaining",
+            "type": "u32"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "QueuedAdminAction",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "id",
+            "type": "u64"
+          },
+          {
+            "name": "action",
+            "type": {
+              "defined": "AdminAction"
+            }
+          },
+          {
+            "name": "payer",
+            "type": "publicKey"
+          },
+          {
+            "name": "etaSlot",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    }
+  ],
+  "types": [
+    {
+      "name": "ForceInsertLeaf",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "commitment",
+            "type": {
+              "array": ["u8", 32]
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "ResetPool",
+      "type": {
+        "kind": "struct",
+        "fields": []
+      }
+    },
+    {
+      "name": "FeeBeneficiary",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "tokenAccount",
+            "type": "publicKey"
+          },
+          {
+            "name": "weight",
+            "type": "u16"
+          }
+        ]
+      }
+    },
+    {
+      "name": "TreeCheckpoint",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "leafCount",
+            "type": "u64"
+          },
+          {
+            "name": "root",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "slot",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "AuditEntry",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "actor",
+            "type": "publicKey"
+          },
+          {
+            "name": "slot",
+            "type": "u64"
+          },
+          {
+            "name": "oldValueHash",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "newValueHash",
+            "type": {
+              "array": ["u8", 32]
+            }
+          },
+          {
+            "name": "action",
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": {
+              "array": ["u8", 7]
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "FeeSchedule",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "feeBps",
+            "type": "u16"
+          },
+          {
+            "name": "minFee",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "Payout",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "recipient",
             "type": "publicKey"
           },
-          {
-            "name": "tokenMint",
-            "type": "publicKey"
+          {
+            "name": "amount",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "AdminAction",
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "TransferOwnership",
+            "fields": [
+              {
+                "name": "newAdmin",
+                "type": "publicKey"
+              }
+            ]
+          },
+          {
+            "name": "SetGuardian",
+            "fields": [
+              {
+                "name": "newGuardian",
+                "type": "publicKey"
+              }
+            ]
+          },
+          {
+            "name": "EnableVerifyingKey",
+            "fields": [
+              {
+                "name": "circuitVersion",
+                "type": "u16"
+              }
+            ]
+          },
+          {
+            "name": "SetAdminTimelock",
+            "fields": [
+              {
+                "name": "slots",
+                "type": "u64"
+              }
+            ]
+          },
+          {
+            "name": "AddSwapTarget",
+            "fields": [
+              {
+                "name": "targetProgram",
+                "type": "publicKey"
+              }
+            ]
+          },
+          {
+            "name": "SetWithdrawalLimit",
+            "fields": [
+              {
+                "name": "maxWithdrawalPerEpoch",
+                "type": "u64"
+              },
+              {
+                "name": "withdrawalEpochSlots",
+                "type": "u64"
+              }
+            ]
+          },
+          {
+            "name": "SetComplianceKey",
+            "fields": [
+              {
+                "name": "complianceKey",
+                "type": {
+                  "array": ["u8", 32]
+                }
+              }
+            ]
+          },
+          {
+            "name": "SetKycAttestor",
+            "fields": [
+              {
+                "name": "kycAttestor",
+                "type": "publicKey"
+              }
+            ]
+          },
+          {
+            "name": "SetExclusionRequired",
+            "fields": [
+              {
+                "name": "exclusionRequired",
+                "type": "bool"
+              }
+            ]
+          }
+        ]
+      }
+    },
+    {
+      "name": "ProofSystem",
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "Groth16"
+          },
+          {
+            "name": "Plonk"
+          }
+        ]
+      }
+    },
+    {
+      "name": "PoolMode",
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "Fungible"
+          },
+          {
+            "name": "Nft"
+          }
+        ]
+      }
+    },
+    {
+      "name": "AuditAction",
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "AddRelayer"
+          },
+          {
+            "name": "RenewRelayer"
+          },
+          {
+            "name": "RemoveRelayer"
+          },
+          {
+            "name": "TransferOwnership"
+          },
+          {
+            "name": "InitiateRenounceAdmin"
+          },
+          {
+            "name": "CancelRenounceAdmin"
+          },
+          {
+            "name": "RenounceAdmin"
+          },
+          {
+            "name": "UpdateFeeConfig"
+          },
+          {
+            "name": "SetFeeAuthority"
+          },
+          {
+            "name": "SetFeeBeneficiaries"
+          },
+          {
+            "name": "SetUtilizationFeeCurve"
+          },
+          {
+            "name": "SetReferralShare"
+          },
+          {
+            "name": "SetFeeSwapProgram"
+          },
+          {
+            "name": "SetVerifierProgram"
+          },
+          {
+            "name": "SetProofSystem"
+          },
+          {
+            "name": "RegisterVerifyingKey"
+          },
+          {
+            "name": "WriteVerifyingKeyChunk"
+          },
+          {
+            "name": "SetVerifyingKeyEnabled"
+          },
+          {
+            "name": "FreezeParams"
+          },
+          {
+            "name": "SetGuardian"
+          },
+          {
+            "name": "SetTreeOperator"
+          },
+          {
+            "name": "Pause"
+          },
+          {
+            "name": "Unpause"
+          },
+          {
+            "name": "EnableNftMode"
+          },
+          {
+            "name": "SetReceiptNftConfig"
+          },
+          {
+            "name": "SetUsdLimits"
+          },
+          {
+            "name": "SetArchivedRootsAccepted"
+          },
+          {
+            "name": "SetWithdrawalLimit"
+          },
+          {
+            "name": "SetPauseOnShortfall"
+          },
+          {
+            "name": "SetMaxPauseDuration"
+          },
+          {
+            "name": "SetComplianceKey"
+          },
+          {
+            "name": "SetKycAttestor"
+          },
+          {
+            "name": "SetMaxDailyDeposit"
+          },
+          {
+            "name": "SetExclusionOperator"
+          },
+          {
+            "name": "SetExclusionRequired"
+          },
+          {
+            "name": "SetAdminTimelock"
+          },
+          {
+            "name": "QueueAdminAction"
+          },
+          {
+            "name": "CancelAdminAction"
+          },
+          {
+            "name": "AddSwapTarget"
+          },
+          {
+            "name": "RemoveSwapTarget"
+          }
+        ]
+      }
+    }
+  ],
+  "events": [
+    {
+      "name": "PoolReset",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "admin",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "shardsCleared",
+          "type": "u32",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "AdminChanged",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "oldAdmin",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "newAdmin",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "RelayerAdded",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "relayerAddress",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "endpointUrlHash",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "feeSchedule",
+          "type": {
+            "defined": "FeeSchedule"
+          },
+          "index": false
+        },
+        {
+          "name": "expirySlot",
+          "type": {
+            "option": "u64"
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "RelayerRenewed",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "relayerAddress",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "expirySlot",
+          "type": {
+            "option": "u64"
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "RelayerRemoved",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "relayerAddress",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "FeeConfigUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "feeAuthority",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "protocolFeeBps",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "maxProtocolFee",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "FeeBeneficiariesUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "beneficiaries",
+          "type": {
+            "vec": {
+              "defined": "FeeBeneficiary"
+            }
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "FeesDistributed",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "total",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "shares",
+          "type": {
+            "vec": "u64"
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ReferralAccrued",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "referrer",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ReferralFeesClaimed",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "referrer",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "FeeSwapProgramChanged",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "oldProgram",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "newProgram",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "RelayerFeePaid",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "relayer",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "feeMint",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "feeAmount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "amountPaid",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "WithdrawalLimitUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "maxWithdrawalPerEpoch",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "withdrawalEpochSlots",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "WithdrawalLimitTripped",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "withdrawnInEpoch",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "maxWithdrawalPerEpoch",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "SolvencyMismatch",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "expected",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "actual",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "totalDeposited",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "totalWithdrawn",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "accruedProtocolFees",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "paused",
+          "type": "bool",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "MaxPauseDurationUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "maxPauseSlots",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "PauseOnShortfallUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "enabled",
+          "type": "bool",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "TreeOperatorChanged",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "oldTreeOperator",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "newTreeOperator",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "TreeCheckpointed",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "operator",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "leafCount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "root",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "RootsArchived",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "page",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "firstRoot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "count",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "lost",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ArchivedRootsAcceptedChanged",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "accept",
+          "type": "bool",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "GuardianChanged",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "oldGuardian",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "newGuardian",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "AdminTimelockChanged",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "oldSlots",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "newSlots",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "AdminActionQueued",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "id",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "action",
+          "type": {
+            "defined": "AdminAction"
+          },
+          "index": false
+        },
+        {
+          "name": "etaSlot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "AdminActionExecuted",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "id",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "action",
+          "type": {
+            "defined": "AdminAction"
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "AdminActionCancelled",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "id",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "action",
+          "type": {
+            "defined": "AdminAction"
+          },
+          "index": false
+        },
+        {
+          "name": "by",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "PauseToggled",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "paused",
+          "type": "bool",
+          "index": false
+        },
+        {
+          "name": "by",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "VerifierProgramChanged",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "oldProgram",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "newProgram",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ProofSystemChanged",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "oldProofSystem",
+          "type": {
+            "defined": "ProofSystem"
+          },
+          "index": false
+        },
+        {
+          "name": "newProofSystem",
+          "type": {
+            "defined": "ProofSystem"
+          },
+          "index": false
+        },
+        {
+          "name": "verifierProgramId",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "VerifyingKeyRegistered",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "circuitVersion",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "proofSystem",
+          "type": {
+            "defined": "ProofSystem"
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "VerifyingKeyEnabledChanged",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "circuitVersion",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "enabled",
+          "type": "bool",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ParamGroupsFrozen",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "groups",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "frozenGroups",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "AdminRenounceInitiated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "admin",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "CommitmentRegistered",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "registrant",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "commitment",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "expirySlot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "CommitmentRegistrationClosed",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "commitment",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "funded",
+          "type": "bool",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "Cranked",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "cranker",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "processed",
+          "type": "u32",
+          "index": false
+        },
+        {
+          "name": "reward",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "PaymentScheduleCreated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "schedule",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "recipient",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "intervalSlots",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "firstPaymentSlot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "payments",
+          "type": "u32",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ScheduledPaymentExecuted",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "schedule",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "recipient",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "executor",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "executorFee",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "paymentsRemaining",
+          "type": "u32",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "PaymentScheduleClosed",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "schedule",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "refunded",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "MerkleRootUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "firstLeafIndex",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "leafCount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "newRoot",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "NullifierSpent",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "nullifierHash",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ChangeNoteCommitted",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "commitment",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "leafIndex",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "DepositOccurred",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "user",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "tokenAddress",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "commitment",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "NoteAnnounced",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "commitment",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "ciphertext",
+          "type": "bytes",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ComplianceKeyUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "complianceKey",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "DepositAttested",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "depositor",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "commitment",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "envelopeHash",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "complianceKey",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "KycAttestorUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "kycAttestor",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "KycCredentialIssued",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "holder",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "attestor",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "expiresAt",
+          "type": "i64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "KycCredentialRevoked",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "holder",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "attestor",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "MaxDailyDepositUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "maxDailyDeposit",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ExclusionOperatorUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "exclusionOperator",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ExclusionRootPublished",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "publisher",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "root",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "previousRoot",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ExclusionRequiredUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "exclusionRequired",
+          "type": "bool",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "WithdrawalOccurred",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "nullifierHash",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "recipient",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "tokenAddress",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "SplitWithdrawalOccurred",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "nullifierHash",
+          "type": {
+            "array": ["u8", 32]
           },
-          {
-            "name": "verifierProgramId",
-            "type": "publicKey"
+          "index": false
+        },
+        {
+          "name": "tokenAddress",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "payouts",
+          "type": {
+            "vec": {
+              "defined": "Payout"
+            }
           },
-          {
-            "name": "bump",
-            "type": "u8"
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ShieldedSwapOccurred",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "nullifierHash",
+          "type": {
+            "array": ["u8", 32]
           },
-          {
-            "name": "whitelistedRelayers",
-            "type": {
-              "vec": "publicKey"
-            }
-          }
-        ]
-      }
-    }
-  ],
-  "events": [
+          "index": false
+        },
+        {
+          "name": "targetProgram",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "amountIn",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "amountOut",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "outputCommitment",
+          "type": {
+            "array": ["u8", 32]
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
     {
-      "name": "AdminChanged",
+      "name": "SwapTargetAdded",
       "fields": [
         {
-          "name": "oldAdmin",
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "targetProgram",
           "type": "publicKey",
           "index": false
         },
         {
-          "name": "newAdmin",
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "SwapTargetRemoved",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "targetProgram",
           "type": "publicKey",
           "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
         }
       ]
     },
     {
-      "name": "RelayerAdded",
+      "name": "UsdLimitsUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "priceFeed",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "maxDepositUsd",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "largeWithdrawalUsd",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "flatFeeUsd",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ReceiptNftConfigUpdated",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "enabled",
+          "type": "bool",
+          "index": false
+        },
+        {
+          "name": "merkleTree",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ReceiptNftMinted",
       "fields": [
         {
-          "name": "relayerAddress",
-          "type": "publicKey",
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "depositor",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "leafIndex",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "merkleTree",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "PoolModeChanged",
+      "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "poolMode",
+          "type": {
+            "defined": "PoolMode"
+          },
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
           "index": false
-        }
-      ]
-    },
-    {
-      "name": "RelayerRemoved",
-      "fields": [
+        },
         {
-          "name": "relayerAddress",
-          "type": "publicKey",
+          "name": "unixTimestamp",
+          "type": "i64",
           "index": false
         }
       ]
     },
     {
-      "name": "DepositOccurred",
+      "name": "NftDepositOccurred",
       "fields": [
         {
-          "name": "user",
-          "type": "publicKey",
+          "name": "version",
+          "type": "u8",
           "index": false
         },
         {
-          "name": "tokenAddress",
+          "name": "user",
           "type": "publicKey",
           "index": false
         },
         {
-          "name": "amount",
-          "type": "u64",
+          "name": "mint",
+          "type": "publicKey",
           "index": false
         },
         {
@@ -317,12 +6791,32 @@ export const IDL = {
             "array": ["u8", 32]
           },
           "index": false
+        },
+        {
+          "name": "leafIndex",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
         }
       ]
     },
     {
-      "name": "WithdrawalOccurred",
+      "name": "NftWithdrawalOccurred",
       "fields": [
+        {
+          "name": "version",
+          "type": "u8",
+          "index": false
+        },
         {
           "name": "nullifierHash",
           "type": {
@@ -336,14 +6830,19 @@ export const IDL = {
           "index": false
         },
         {
-          "name": "tokenAddress",
+          "name": "mint",
           "type": "publicKey",
           "index": false
         },
         {
-          "name": "amount",
+          "name": "slot",
           "type": "u64",
           "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
         }
       ]
     }
@@ -372,7 +6871,7 @@ export const IDL = {
     {
       "code": 6004,
       "name": "InvalidZkProof",
-      "msg": "Invalid ZK proof (placeholder check)."
+      "msg": "Invalid ZK proof."
     },
     {
       "code": 6005,
@@ -398,6 +6897,541 @@ export const IDL = {
       "code": 6009,
       "name": "Overflow",
       "msg": "Overflow during arithmetic operation."
+    },
+    {
+      "code": 6010,
+      "name": "FeeExceedsAmount",
+      "msg": "Relayer and protocol fees exceed the withdrawal amount."
+    },
+    {
+      "code": 6011,
+      "name": "MissingRelayerFeeAccount",
+      "msg": "Relayer fee token account is required when a relayer fee is charged."
+    },
+    {
+      "code": 6012,
+      "name": "InvalidRelayerFeeAccount",
+      "msg": "Relayer fee token account must be the relayer's associated token account for the fee mint."
+    },
+    {
+      "code": 6013,
+      "name": "InvalidFeeSwapProgram",
+      "msg": "Fee swap program is not configured or does not match."
+    },
+    {
+      "code": 6014,
+      "name": "FeeSwapOverspent",
+      "msg": "Fee swap spent more than the relayer fee from the vault."
+    },
+    {
+      "code": 6015,
+      "name": "FeeSwapSlippage",
+      "msg": "Fee swap returned less than the relayer's minimum."
+    },
+    {
+      "code": 6016,
+      "name": "RelayerExpired",
+      "msg": "Relayer whitelisting has expired."
+    },
+    {
+      "code": 6017,
+      "name": "PoolPaused",
+      "msg": "The pool is paused."
+    },
+    {
+      "code": 6018,
+      "name": "PoolNotPaused",
+      "msg": "The pool is not paused."
+    },
+    {
+      "code": 6019,
+      "name": "NotAdminOrGuardian",
+      "msg": "Signer is neither the admin nor the guardian."
+    },
+    {
+      "code": 6020,
+      "name": "InvalidRenounceConfirmation",
+      "msg": "Confirmation value does not match RENOUNCE_ADMIN_CONFIRMATION."
+    },
+    {
+      "code": 6021,
+      "name": "RenounceNotInitiated",
+      "msg": "Admin renouncement has not been initiated or cannot complete in the same slot."
+    },
+    {
+      "code": 6022,
+      "name": "ParamsFrozen",
+      "msg": "This parameter group has been frozen."
+    },
+    {
+      "code": 6023,
+      "name": "InvalidParamGroup",
+      "msg": "Unknown parameter group flag."
+    },
+    {
+      "code": 6024,
+      "name": "FeeBpsTooHigh",
+      "msg": "Fee basis points exceed the allowed maximum."
+    },
+    {
+      "code": 6025,
+      "name": "InvalidFeeBeneficiaries",
+      "msg": "Too many fee beneficiaries or a zero total weight."
+    },
+    {
+      "code": 6026,
+      "name": "BeneficiaryAccountMismatch",
+      "msg": "Beneficiary token accounts do not match the configured beneficiaries."
+    },
+    {
+      "code": 6027,
+      "name": "NothingToDistribute",
+      "msg": "No protocol fees to distribute."
+    },
+    {
+      "code": 6028,
+      "name": "NothingToClaim",
+      "msg": "No referral fees are claimable right now."
+    },
+    {
+      "code": 6029,
+      "name": "RegistrationExpired",
+      "msg": "Commitment registration has expired."
+    },
+    {
+      "code": 6030,
+      "name": "RegistrationNotExpired",
+      "msg": "Commitment registration has not expired yet."
+    },
+    {
+      "code": 6031,
+      "name": "InvalidCrankAccounts",
+      "msg": "Crank accounts must be [pending_commitment, registrant] pairs."
+    },
+    {
+      "code": 6032,
+      "name": "NothingToCrank",
+      "msg": "No queued item was due for processing."
+    },
+    {
+      "code": 6033,
+      "name": "NullifierAlreadySpent",
+      "msg": "Nullifier has already been spent."
+    },
+    {
+      "code": 6034,
+      "name": "NullifierShardMismatch",
+      "msg": "Nullifier shard does not cover this nullifier."
+    },
+    {
+      "code": 6035,
+      "name": "InvalidSubtreeSize",
+      "msg": "Subtree must have a power-of-two number of leaves, at most 2^MAX_SUBTREE_HEIGHT, with one amount each."
+    },
+    {
+      "code": 6036,
+      "name": "SubtreeRootMismatch",
+      "msg": "Subtree root does not match its leaves."
+    },
+    {
+      "code": 6037,
+      "name": "MisalignedSubtree",
+      "msg": "Next leaf index is not aligned to the subtree width."
+    },
+    {
+      "code": 6038,
+      "name": "MerkleTreeFull",
+      "msg": "Merkle tree is full."
+    },
+    {
+      "code": 6039,
+      "name": "InvalidProofEncoding",
+      "msg": "Proof is malformed or not encoded for the circuit's proof system."
+    },
+    {
+      "code": 6040,
+      "name": "VerifyingKeyTooLarge",
+      "msg": "Verifying key is too large."
+    },
+    {
+      "code": 6041,
+      "name": "CircuitVersionDisabled",
+      "msg": "Verifying key for this circuit version is disabled."
+    },
+    {
+      "code": 6042,
+      "name": "ProofExpired",
+      "msg": "Proof slot is in the future or older than MAX_PROOF_AGE_SLOTS."
+    },
+    {
+      "code": 6043,
+      "name": "ExternalNullifierMismatch",
+      "msg": "External nullifier does not match this deployment."
+    },
+    {
+      "code": 6044,
+      "name": "InvalidVerifyingKey",
+      "msg": "Verifying key is not in the on-chain layout for its proof system."
+    },
+    {
+      "code": 6045,
+      "name": "MissingAmountRangeCheck",
+      "msg": "Circuit must range-check amounts to exactly 64 bits for a variable-amount pool."
+    },
+    {
+      "code": 6046,
+      "name": "AmountOutOfRange",
+      "msg": "Amount public input exceeds the circuit's range-checked width."
+    },
+    {
+      "code": 6047,
+      "name": "IncompleteVerifyingKey",
+      "msg": "Verifying key has not been fully uploaded."
+    },
+    {
+      "code": 6048,
+      "name": "ChangeCommitmentMismatch",
+      "msg": "Change commitment does not match the proof's public input."
+    },
+    {
+      "code": 6049,
+      "name": "MissingMerkleTree",
+      "msg": "Merkle tree account is required to insert a change note."
+    },
+    {
+      "code": 6050,
+      "name": "InvalidSwapTarget",
+      "msg": "Swap target is this pool, isn't an approved swap target, or isn't the pool the proof names."
+    },
+    {
+      "code": 6051,
+      "name": "SwapOverspent",
+      "msg": "Swap spent more than the input note from the vault."
+    },
+    {
+      "code": 6052,
+      "name": "SwapSlippage",
+      "msg": "Swap returned less than the output note's amount."
+    },
+    {
+      "code": 6053,
+      "name": "OutputCommitmentMismatch",
+      "msg": "Output commitment does not match the proof's public input."
+    },
+    {
+      "code": 6054,
+      "name": "InvalidPayouts",
+      "msg": "A split withdrawal pays 1..=MAX_SPLIT_RECIPIENTS recipients, each a non-zero amount."
+    },
+    {
+      "code": 6055,
+      "name": "PayoutMismatch",
+      "msg": "Payouts do not match the proof's public inputs."
+    },
+    {
+      "code": 6056,
+      "name": "InvalidRecipientAccount",
+      "msg": "Recipient token accounts must follow the payouts, one each: the recipient's associated token account."
+    },
+    {
+      "code": 6057,
+      "name": "InvalidNoteCount",
+      "msg": "Note splits create 2 to MAX_NOTE_SPLIT_OUTPUTS notes; merges spend 2 to MAX_INPUT_NOTES."
+    },
+    {
+      "code": 6058,
+      "name": "InvalidPaymentSchedule",
+      "msg": "A payment schedule needs a non-zero amount, interval and payment count."
+    },
+    {
+      "code": 6059,
+      "name": "PaymentNotDue",
+      "msg": "No scheduled payment is due yet."
+    },
+    {
+      "code": 6060,
+      "name": "ScheduleUnderfunded",
+      "msg": "The schedule's escrow can't cover the payment and the executor fee."
+    },
+    {
+      "code": 6061,
+      "name": "WrongPoolMode",
+      "msg": "This instruction is not available in the pool's mode."
+    },
+    {
+      "code": 6062,
+      "name": "PoolNotEmpty",
+      "msg": "The pool mode can only change before the first deposit."
+    },
+    {
+      "code": 6063,
+      "name": "NotAnNft",
+      "msg": "Mint is not an NFT: supply must be 1 and decimals 0."
+    },
+    {
+      "code": 6064,
+      "name": "MintMismatch",
+      "msg": "NFT mint does not match the proof's public input or the metadata account."
+    },
+    {
+      "code": 6065,
+      "name": "MissingProgrammableAccounts",
+      "msg": "Programmable NFTs need their edition, token records, Token Metadata and the instructions sysvar."
+    },
+    {
+      "code": 6066,
+      "name": "ReceiptNftsDisabled",
+      "msg": "Receipt NFTs are not enabled for this pool."
+    },
+    {
+      "code": 6067,
+      "name": "InvalidReceiptNftConfig",
+      "msg": "Receipt NFT name, symbol or URI is too long."
+    },
+    {
+      "code": 6068,
+      "name": "MissingUsdLimits",
+      "msg": "The pool's USD limits and their price feed must be passed while the limits are set."
+    },
+    {
+      "code": 6069,
+      "name": "InvalidPriceFeed",
+      "msg": "Price feed is not the pool's Pyth price account, or its price is unusable."
+    },
+    {
+      "code": 6070,
+      "name": "StalePrice",
+      "msg": "Pyth price is older than the pool's maximum price age."
+    },
+    {
+      "code": 6071,
+      "name": "PriceTooUncertain",
+      "msg": "Pyth price's confidence interval is wider than the pool accepts."
+    },
+    {
+      "code": 6072,
+      "name": "DepositCapExceeded",
+      "msg": "Deposit is worth more than the pool's USD deposit cap."
+    },
+    {
+      "code": 6073,
+      "name": "LargeWithdrawal",
+      "msg": "Withdrawal is worth more than the pool's USD large-withdrawal threshold."
+    },
+    {
+      "code": 6074,
+      "name": "InvalidUsdLimits",
+      "msg": "Invalid USD limits."
+    },
+    {
+      "code": 6075,
+      "name": "NotTreeOperator",
+      "msg": "Signer is neither the admin nor the tree operator."
+    },
+    {
+      "code": 6076,
+      "name": "CheckpointUnchanged",
+      "msg": "No leaves were inserted since the last checkpoint."
+    },
+    {
+      "code": 6077,
+      "name": "NothingToArchive",
+      "msg": "Every root the tree has had is already archived."
+    },
+    {
+      "code": 6078,
+      "name": "WrongArchivePage",
+      "msg": "Roots are archived in order; this isn't the archive's next page."
+    },
+    {
+      "code": 6079,
+      "name": "InvalidCommitment",
+      "msg": "Commitment is zero or not a canonical BN254 field element."
+    },
+    {
+      "code": 6080,
+      "name": "DuplicateCommitment",
+      "msg": "Commitment is already in the tree."
+    },
+    {
+      "code": 6081,
+      "name": "CommitmentShardMismatch",
+      "msg": "Commitment shard does not cover this commitment."
+    },
+    {
+      "code": 6082,
+      "name": "NotAdmin",
+      "msg": "Signer is not the pool's admin."
+    },
+    {
+      "code": 6083,
+      "name": "NotFeeAuthority",
+      "msg": "Signer is not the fee authority."
+    },
+    {
+      "code": 6084,
+      "name": "NotAccountOwner",
+      "msg": "Signer does not own this account."
+    },
+    {
+      "code": 6085,
+      "name": "RegistrantMismatch",
+      "msg": "Registrant does not match the pending commitment."
+    },
+    {
+      "code": 6086,
+      "name": "TokenMintMismatch",
+      "msg": "Token account or mint is not the pool's mint."
+    },
+    {
+      "code": 6087,
+      "name": "TooManyRelayers",
+      "msg": "The relayer whitelist is full (MAX_RELAYERS)."
+    },
+    {
+      "code": 6088,
+      "name": "NullifierShardCountMismatch",
+      "msg": "Pass one nullifier shard account per nullifier, in order."
+    },
+    {
+      "code": 6089,
+      "name": "InvalidNoteCiphertext",
+      "msg": "Note ciphertext is empty or longer than MAX_NOTE_CIPHERTEXT_LEN."
+    },
+    {
+      "code": 6090,
+      "name": "InvalidWithdrawalLimit",
+      "msg": "A withdrawal limit needs an epoch of at least one slot."
+    },
+    {
+      "code": 6091,
+      "name": "ExitOnly",
+      "msg": "The pool is exit-only after a prolonged pause; only withdrawals and unpausing are allowed."
+    },
+    {
+      "code": 6092,
+      "name": "WithdrawalLimitReached",
+      "msg": "Withdrawals this epoch have reached the limit; try again next epoch."
+    },
+    {
+      "code": 6093,
+      "name": "ComplianceAttestationRequired",
+      "msg": "This pool only takes deposits with a compliance attestation (attest_deposit first)."
+    },
+    {
+      "code": 6094,
+      "name": "ComplianceAttestationMismatch",
+      "msg": "The compliance attestation is another depositor's, for an old compliance key, or already used."
+    },
+    {
+      "code": 6095,
+      "name": "NoComplianceKey",
+      "msg": "The pool has no compliance key to attest to."
+    },
+    {
+      "code": 6096,
+      "name": "KycCredentialRequired",
+      "msg": "This pool only takes deposits from holders of a KYC credential."
+    },
+    {
+      "code": 6097,
+      "name": "KycCredentialInvalid",
+      "msg": "The KYC credential was issued by another attestor or has expired."
+    },
+    {
+      "code": 6098,
+      "name": "NotKycAttestor",
+      "msg": "Only the pool's KYC attestor can issue credentials."
+    },
+    {
+      "code": 6099,
+      "name": "InvalidKycExpiry",
+      "msg": "A KYC credential must expire in the future, or never (0)."
+    },
+    {
+      "code": 6100,
+      "name": "DepositVolumeRequired",
+      "msg": "This pool caps daily deposits per depositor; pass the depositor's deposit volume account."
+    },
+    {
+      "code": 6101,
+      "name": "DailyDepositCapReached",
+      "msg": "This deposit would take the depositor past the pool's daily deposit cap."
+    },
+    {
+      "code": 6102,
+      "name": "NotExclusionOperator",
+      "msg": "Only the admin or the exclusion operator can publish an exclusion root."
+    },
+    {
+      "code": 6103,
+      "name": "InvalidExclusionRoot",
+      "msg": "An exclusion root must be a non-zero field element."
+    },
+    {
+      "code": 6104,
+      "name": "NoExclusionRoot",
+      "msg": "Publish an exclusion root before requiring proofs of non-membership."
+    },
+    {
+      "code": 6105,
+      "name": "RelayerMismatch",
+      "msg": "The proof is bound to another relayer or relayer fee."
+    },
+    {
+      "code": 6106,
+      "name": "RootNotKnown",
+      "msg": "The proof's Merkle root isn't a recent root of the tree, or an archived one it accepts."
+    },
+    {
+      "code": 6107,
+      "name": "ProofSystemUnsupported",
+      "msg": "The proof system is not supported."
+    },
+    {
+      "code": 6108,
+      "name": "AdminTimelockActive",
+      "msg": "While the admin timelock is set, this action must be queued with queue_admin_action."
+    },
+    {
+      "code": 6109,
+      "name": "InvalidAdminTimelock",
+      "msg": "The admin timelock can be at most ProgramState::MAX_ADMIN_TIMELOCK_SLOTS."
+    },
+    {
+      "code": 6110,
+      "name": "AdminActionNotReady",
+      "msg": "The queued admin action's timelock hasn't passed yet."
+    },
+    {
+      "code": 6111,
+      "name": "QueuedActionPayerMismatch",
+      "msg": "payer must be the account that paid for the queued admin action."
+    },
+    {
+      "code": 6112,
+      "name": "MerkleRootMismatch",
+      "msg": "merkle_root doesn't match the proof's root input."
+    },
+    {
+      "code": 6113,
+      "name": "NullifierHashMismatch",
+      "msg": "A nullifier hash doesn't match the proof's input for it."
+    },
+    {
+      "code": 6114,
+      "name": "TooManySwapTargets",
+      "msg": "The swap target list is full (MAX_SWAP_TARGETS)."
+    },
+    {
+      "code": 6115,
+      "name": "SwapTargetAlreadyApproved",
+      "msg": "The program is already an approved swap target."
+    },
+    {
+      "code": 6116,
+      "name": "SwapDepositMismatch",
+      "msg": "The target pool's deposit didn't take exactly the output note's amount."
     }
   ]
-}; 
\ No newline at end of file
+};
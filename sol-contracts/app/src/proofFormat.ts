@@ -0,0 +1,197 @@
+// Conversions between prover serializations and the program's on-chain byte layout.
+// Mirrors programs/privax_protocol/src/proof_format.rs and plonk.rs; see there for the layouts.
+//
+// On-chain: big-endian field elements, G1 = x || y, G2 = x.c1 || x.c0 || y.c1 || y.c0.
+// arkworks: little-endian field elements, G2 as c0 before c1, flags in each point's last byte.
+// snarkjs: decimal strings, projective (z = 1) points, G2 pairs ordered [c0, c1].
+
+import BN from 'bn.js';
+
+export const FIELD_SIZE = 32;
+export const G1_SIZE = 2 * FIELD_SIZE;
+export const G2_SIZE = 4 * FIELD_SIZE;
+export const VK_FIXED_SIZE = G1_SIZE + 3 * G2_SIZE;
+export const PLONK_VK_SIZE = 2 + 3 * FIELD_SIZE + 8 * G1_SIZE + G2_SIZE;
+export const PLONK_PROOF_SIZE = 9 * G1_SIZE + 6 * FIELD_SIZE;
+
+const ARKWORKS_INFINITY_FLAG = 1 << 6;
+const ARKWORKS_FLAG_MASK = 0b1100_0000;
+
+// withdraw's a_proof, b_proof and c_proof arguments
+export interface OnChainProof {
+  aProof: Buffer;
+  bProof: Buffer;
+  cProof: Buffer;
+}
+
+export interface SnarkjsProof {
+  pi_a: string[];
+  pi_b: string[][];
+  pi_c: string[];
+}
+
+export interface SnarkjsVerifyingKey {
+  vk_alpha_1: string[];
+  vk_beta_2: string[][];
+  vk_gamma_2: string[][];
+  vk_delta_2: string[][];
+  IC: string[][];
+}
+
+export interface SnarkjsPlonkProof {
+  A: string[];
+  B: string[];
+  C: string[];
+  Z: string[];
+  T1: string[];
+  T2: string[];
+  T3: string[];
+  Wxi: string[];
+  Wxiw: string[];
+  eval_a: string;
+  eval_b: string;
+  eval_c: string;
+  eval_s1: string;
+  eval_s2: string;
+  eval_zw: string;
+}
+
+export interface SnarkjsPlonkVerifyingKey {
+  power: number;
+  nPublic: number;
+  k1: string;
+  k2: string;
+  w: string;
+  Qm: string[];
+  Ql: string[];
+  Qr: string[];
+  Qo: string[];
+  Qc: string[];
+  S1: string[];
+  S2: string[];
+  S3: string[];
+  X_2: string[][];
+}
+
+const reversed = (field: Buffer): Buffer => Buffer.from(field).reverse();
+
+// [x.c0, x.c1, y.c0, y.c1] indices, rearranged between arkworks and on-chain order
+const G2_ORDER = [1, 0, 3, 2];
+
+function arkworksFieldBytes(point: Buffer): Buffer {
+  const bytes = Buffer.from(point);
+  const last = bytes.length - 1;
+  if (bytes[last] & ARKWORKS_INFINITY_FLAG) {
+    throw new Error('Points at infinity have no on-chain encoding');
+  }
+  bytes[last] &= ~ARKWORKS_FLAG_MASK & 0xff;
+  return bytes;
+}
+
+function coordinates(point: Buffer, count: number): Buffer[] {
+  return Array.from({ length: count }, (_, i) => point.subarray(i * FIELD_SIZE, (i + 1) * FIELD_SIZE));
+}
+
+export function g1FromArkworks(point: Buffer): Buffer {
+  return Buffer.concat(coordinates(arkworksFieldBytes(point), 2).map(reversed));
+}
+
+export function g1ToArkworks(point: Buffer): Buffer {
+  return Buffer.concat(coordinates(point, 2).map(reversed));
+}
+
+export function g2FromArkworks(point: Buffer): Buffer {
+  const fields = coordinates(arkworksFieldBytes(point), 4);
+  return Buffer.concat(G2_ORDER.map((i) => reversed(fields[i])));
+}
+
+export function g2ToArkworks(point: Buffer): Buffer {
+  const fields = coordinates(point, 4);
+  return Buffer.concat(G2_ORDER.map((i) => reversed(fields[i])));
+}
+
+export function proofFromArkworks(proof: Buffer): OnChainProof {
+  if (proof.length !== 2 * G1_SIZE + G2_SIZE) {
+    throw new Error(`arkworks proof must be ${2 * G1_SIZE + G2_SIZE} bytes, got ${proof.length}`);
+  }
+  return {
+    aProof: g1FromArkworks(proof.subarray(0, G1_SIZE)),
+    bProof: g2FromArkworks(proof.subarray(G1_SIZE, G1_SIZE + G2_SIZE)),
+    cProof: g1FromArkworks(proof.subarray(G1_SIZE + G2_SIZE)),
+  };
+}
+
+export function proofToArkworks(proof: OnChainProof): Buffer {
+  return Buffer.concat([g1ToArkworks(proof.aProof), g2ToArkworks(proof.bProof), g1ToArkworks(proof.cProof)]);
+}
+
+export function vkFromArkworks(vk: Buffer): Buffer {
+  const icCount = Number(vk.readBigUInt64LE(VK_FIXED_SIZE));
+  const ic = vk.subarray(VK_FIXED_SIZE + 8);
+  if (ic.length !== icCount * G1_SIZE) {
+    throw new Error('arkworks verifying key length does not match its ic count');
+  }
+  const g2s = coordinates(vk.subarray(G1_SIZE, VK_FIXED_SIZE), 12);
+  return Buffer.concat([
+    g1FromArkworks(vk.subarray(0, G1_SIZE)),
+    ...[0, 1, 2].map((i) => g2FromArkworks(Buffer.concat(g2s.slice(i * 4, i * 4 + 4)))),
+    ...Array.from({ length: icCount }, (_, i) => g1FromArkworks(ic.subarray(i * G1_SIZE, (i + 1) * G1_SIZE))),
+  ]);
+}
+
+export function vkToArkworks(vkData: Buffer): Buffer {
+  const ic = vkData.subarray(VK_FIXED_SIZE);
+  const icCount = ic.length / G1_SIZE;
+  const count = Buffer.alloc(8);
+  count.writeBigUInt64LE(BigInt(icCount));
+  return Buffer.concat([
+    g1ToArkworks(vkData.subarray(0, G1_SIZE)),
+    ...[0, 1, 2].map((i) => g2ToArkworks(vkData.subarray(G1_SIZE + i * G2_SIZE, G1_SIZE + (i + 1) * G2_SIZE))),
+    count,
+    ...Array.from({ length: icCount }, (_, i) => g1ToArkworks(ic.subarray(i * G1_SIZE, (i + 1) * G1_SIZE))),
+  ]);
+}
+
+const field = (decimal: string): Buffer => new BN(decimal, 10).toArrayLike(Buffer, 'be', FIELD_SIZE);
+
+const snarkjsG1 = (point: string[]): Buffer => Buffer.concat([field(point[0]), field(point[1])]);
+
+// snarkjs pairs are [c0, c1]; on-chain wants c1 first
+const snarkjsG2 = (point: string[][]): Buffer =>
+  Buffer.concat([field(point[0][1]), field(point[0][0]), field(point[1][1]), field(point[1][0])]);
+
+export function proofFromSnarkjs(proof: SnarkjsProof): OnChainProof {
+  return { aProof: snarkjsG1(proof.pi_a), bProof: snarkjsG2(proof.pi_b), cProof: snarkjsG1(proof.pi_c) };
+}
+
+// VerifyingKey.vk_data for register_verifying_key
+export function vkFromSnarkjs(vk: SnarkjsVerifyingKey): Buffer {
+  return Buffer.concat([
+    snarkjsG1(vk.vk_alpha_1),
+    snarkjsG2(vk.vk_beta_2),
+    snarkjsG2(vk.vk_gamma_2),
+    snarkjsG2(vk.vk_delta_2),
+    ...vk.IC.map(snarkjsG1),
+  ]);
+}
+
+// PLONK commitments can be the point at infinity (z = 0), which is all zero on-chain
+const snarkjsCommitment = (point: string[]): Buffer => (point[2] === '0' ? Buffer.alloc(G1_SIZE) : snarkjsG1(point));
+
+// withdraw's plonk_proof; a_proof, b_proof and c_proof stay zero
+export function plonkProofFromSnarkjs(proof: SnarkjsPlonkProof): Buffer {
+  return Buffer.concat([
+    ...[proof.A, proof.B, proof.C, proof.Z, proof.T1, proof.T2, proof.T3, proof.Wxi, proof.Wxiw].map(snarkjsCommitment),
+    ...[proof.eval_a, proof.eval_b, proof.eval_c, proof.eval_s1, proof.eval_s2, proof.eval_zw].map(field),
+  ]);
+}
+
+// VerifyingKey.vk_data for register_verifying_key with ProofSystem::Plonk
+export function plonkVkFromSnarkjs(vk: SnarkjsPlonkVerifyingKey): Buffer {
+  return Buffer.concat([
+    Buffer.from([vk.power, vk.nPublic]),
+    ...[vk.k1, vk.k2, vk.w].map(field),
+    ...[vk.Qm, vk.Ql, vk.Qr, vk.Qo, vk.Qc, vk.S1, vk.S2, vk.S3].map(snarkjsCommitment),
+    snarkjsG2(vk.X_2),
+  ]);
+}
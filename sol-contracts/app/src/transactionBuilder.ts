@@ -0,0 +1,125 @@
+import {
+  AddressLookupTableAccount,
+  ComputeBudgetProgram,
+  Connection,
+  PublicKey,
+  Signer,
+  TransactionInstruction,
+  TransactionMessage,
+  VersionedTransaction,
+} from '@solana/web3.js';
+
+// Assembles, prices, signs and sends a v0 transaction around Privax instructions:
+//
+//   const signature = await new TransactionBuilder(connection, wallet)
+//     .add(await program.methods.withdraw(...).accounts({...}).instruction())
+//     .withLookupTables(table)
+//     .send();
+//
+// Unless set explicitly, the compute-unit limit comes from simulating the transaction and the
+// compute-unit price from recent prioritization fees on the accounts it writes.
+
+// Anything that can sign for the fee payer: an anchor Wallet, a wallet adapter, ...
+export interface TransactionPayer {
+  publicKey: PublicKey;
+  signTransaction<T extends VersionedTransaction>(tx: T): Promise<T>;
+}
+
+const MAX_COMPUTE_UNIT_LIMIT = 1_400_000;
+// Headroom over the simulated consumption, which can shift slightly with state changes.
+const COMPUTE_UNIT_MARGIN = 1.1;
+
+export class TransactionBuilder {
+  private instructions: TransactionInstruction[] = [];
+  private signers: Signer[] = [];
+  private lookupTables: AddressLookupTableAccount[] = [];
+  private computeUnitLimit: number | null = null;
+  private computeUnitPrice: number | null = null; // micro-lamports per compute unit
+
+  constructor(private connection: Connection, private payer: TransactionPayer) {}
+
+  add(...instructions: TransactionInstruction[]): this {
+    this.instructions.push(...instructions);
+    return this;
+  }
+
+  // Extra signers besides the payer, e.g. a relayer keypair or a new account.
+  addSigners(...signers: Signer[]): this {
+    this.signers.push(...signers);
+    return this;
+  }
+
+  withLookupTables(...lookupTables: AddressLookupTableAccount[]): this {
+    this.lookupTables.push(...lookupTables);
+    return this;
+  }
+
+  setComputeUnitLimit(units: number): this {
+    this.computeUnitLimit = units;
+    return this;
+  }
+
+  setComputeUnitPrice(microLamports: number): this {
+    this.computeUnitPrice = microLamports;
+    return this;
+  }
+
+  private compile(budget: TransactionInstruction[], blockhash: string): VersionedTransaction {
+    const message = new TransactionMessage({
+      payerKey: this.payer.publicKey,
+      recentBlockhash: blockhash,
+      instructions: [...budget, ...this.instructions],
+    });
+    return new VersionedTransaction(message.compileToV0Message(this.lookupTables));
+  }
+
+  private async simulatedComputeUnits(blockhash: string): Promise<number> {
+    const probe = this.compile([ComputeBudgetProgram.setComputeUnitLimit({ units: MAX_COMPUTE_UNIT_LIMIT })], blockhash);
+    const { value } = await this.connection.simulateTransaction(probe, { sigVerify: false, replaceRecentBlockhash: true });
+    if (value.err) {
+      throw new Error(`Simulation failed: ${JSON.stringify(value.err)}\n${(value.logs ?? []).join('\n')}`);
+    }
+    return Math.min(MAX_COMPUTE_UNIT_LIMIT, Math.ceil((value.unitsConsumed ?? MAX_COMPUTE_UNIT_LIMIT) * COMPUTE_UNIT_MARGIN));
+  }
+
+  // Median recent fee paid to lock the accounts this transaction writes.
+  private async recentComputeUnitPrice(): Promise<number> {
+    const writable = new Map<string, PublicKey>();
+    for (const ix of this.instructions) {
+      for (const meta of ix.keys) {
+        if (meta.isWritable) writable.set(meta.pubkey.toBase58(), meta.pubkey);
+      }
+    }
+    const fees = (await this.connection.getRecentPrioritizationFees({ lockedWritableAccounts: [...writable.values()] }))
+      .map((fee) => fee.prioritizationFee)
+      .sort((a, b) => a - b);
+    return fees.length === 0 ? 0 : fees[Math.floor(fees.length / 2)];
+  }
+
+  // Prices and signs the transaction. Returns it with the blockhash it expires with.
+  async build(): Promise<{ transaction: VersionedTransaction; blockhash: string; lastValidBlockHeight: number }> {
+    const { blockhash, lastValidBlockHeight } = await this.connection.getLatestBlockhash();
+    const units = this.computeUnitLimit ?? (await this.simulatedComputeUnits(blockhash));
+    const price = this.computeUnitPrice ?? (await this.recentComputeUnitPrice());
+    const budget = [ComputeBudgetProgram.setComputeUnitLimit({ units })];
+    if (price > 0) {
+      budget.push(ComputeBudgetProgram.setComputeUnitPrice({ microLamports: price }));
+    }
+
+    const transaction = this.compile(budget, blockhash);
+    if (this.signers.length > 0) {
+      transaction.sign(this.signers);
+    }
+    return { transaction: await this.payer.signTransaction(transaction), blockhash, lastValidBlockHeight };
+  }
+
+  async send(): Promise<string> {
+    const { transaction, blockhash, lastValidBlockHeight } = await this.build();
+    const signature = await this.connection.sendTransaction(transaction);
+    const { value } = await this.connection.confirmTransaction({ signature, blockhash, lastValidBlockHeight });
+    if (value.err) {
+      throw new Error(`Transaction ${signature} failed: ${JSON.stringify(value.err)}`);
+    }
+    return signature;
+  }
+}
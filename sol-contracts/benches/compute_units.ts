@@ -0,0 +1,362 @@
+// Compute-unit benchmarks for the program's hot paths, run against a fresh localnet:
+//
+//   anchor build -- --features cu-log
+//   anchor localnet            # in another shell
+//   anchor run bench
+//
+// Each measured instruction is sent on its own and its consumption read back from the
+// confirmed transaction. With the `cu-log` feature, withdraw also logs the remaining units
+// at each step boundary, which is reported per step. The report is written as JSON to
+// $BENCH_REPORT (default benches/compute-units.json).
+//
+// Tree depth is a compile-time constant (MERKLE_TREE_DEPTH); rebuild with another depth to
+// compare, the report records the depth it ran against. withdraw verifies a proof against a
+// key built so that the generator points satisfy the pairing check (see benchVerifyingKey),
+// which costs what a real proof does without needing the circuits.
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ComputeBudgetProgram, Keypair, PublicKey, SystemProgram, LAMPORTS_PER_SOL, Signer } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createMint, createAssociatedTokenAccount, mintTo } from "@solana/spl-token";
+import { buildPoseidon } from "circomlibjs";
+import { createHash } from "crypto";
+import * as fs from "fs";
+import { G1_SIZE } from "../app/src/proofFormat";
+
+interface BenchResult {
+  instruction: string;
+  params: Record<string, string | number | boolean>;
+  computeUnits: number;
+  steps: Record<string, number>;
+}
+
+// Splits an instruction's consumption at each `cu-step` marker. The marker line follows the
+// "Program consumption: N units remaining" line it closes.
+function stepUnits(logs: string[]): Record<string, number> {
+  const steps: Record<string, number> = {};
+  const budget = logs.map((line) => / consumed \d+ of (\d+) compute units/.exec(line)).find((m) => m !== null);
+  let remaining = budget ? Number(budget[1]) : 0;
+  let reading: number | null = null;
+  for (const line of logs) {
+    const consumption = /Program consumption: (\d+) units remaining/.exec(line);
+    if (consumption) reading = Number(consumption[1]);
+    const step = /Program log: cu-step: (\w+)/.exec(line);
+    if (step && reading !== null) {
+      steps[step[1]] = remaining - reading;
+      remaining = reading;
+      reading = null;
+    }
+  }
+  return steps;
+}
+
+// Mirrors hash_pair / MerkleTree::subtree_root in the program: circomlib Poseidon over
+// 32-byte big-endian nodes.
+async function subtreeRoot(leaves: Buffer[]): Promise<Buffer> {
+  const poseidon = await buildPoseidon();
+  const node = (buffer: Buffer) => BigInt("0x" + buffer.toString("hex"));
+  let level = leaves;
+  while (level.length > 1) {
+    level = Array.from({ length: level.length / 2 }, (_, i) => {
+      const hash = poseidon.F.toObject(poseidon([node(level[2 * i]), node(level[2 * i + 1])])) as bigint;
+      return Buffer.from(hash.toString(16).padStart(64, "0"), "hex");
+    });
+  }
+  return level[0];
+}
+
+// Tree inserts hash with Poseidon at every level, past the default 200k compute units
+const treeInsertBudget = () => ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 });
+
+// BN254 generators in the on-chain layout: G1 as x || y, G2 as x.c1 || x.c0 || y.c1 || y.c0
+const G1_GENERATOR = Buffer.concat([Buffer.alloc(31), Buffer.from([1]), Buffer.alloc(31), Buffer.from([2])]);
+const G2_GENERATOR = Buffer.from(
+  "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2" +
+    "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed" +
+    "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b" +
+    "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+  "hex"
+);
+
+// alpha and a are G1, beta and b are G2, so e(-a, b) · e(alpha, beta) cancels; with every ic
+// point and c at infinity, vk_x is too and the other two pairings are 1. The program still
+// runs every multiplication, addition and pairing a real key and proof take.
+const benchVerifyingKey = (publicInputs: number) =>
+  Buffer.concat([G1_GENERATOR, G2_GENERATOR, G2_GENERATOR, G2_GENERATOR, Buffer.alloc((publicInputs + 1) * G1_SIZE)]);
+const benchProof = { aProof: G1_GENERATOR, bProof: G2_GENERATOR, cProof: Buffer.alloc(G1_SIZE) };
+
+// A distinct dummy commitment; the leading zero byte keeps it below the field modulus, as
+// deposits require.
+function dummyCommitment(n: number): Buffer {
+  return Buffer.concat([Buffer.alloc(1), Buffer.alloc(31, n)]);
+}
+
+describe("compute units", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.PrivaxProtocol as Program;
+
+  const admin = Keypair.generate();
+  const user = Keypair.generate();
+  const recipient = Keypair.generate();
+
+  const AMOUNT = 1_000_000_000;
+  const LEAF_AMOUNT = 1_000_000;
+  // One circuit per input note count, as each count has its own public input layout
+  const circuitVersion = (inputNotes: number) => inputNotes;
+  const MAX_PUBLIC_INPUTS = 12;
+  const results: BenchResult[] = [];
+
+  let tokenMint: PublicKey;
+  let userTokenAccount: PublicKey;
+  let recipientTokenAccount: PublicKey;
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const programStatePDA = pda(Buffer.from("program_state"));
+  const feeConfigPDA = pda(Buffer.from("fee_config"), programStatePDA.toBuffer());
+  const nullifierFilterPDA = pda(Buffer.from("nullifier_filter"), programStatePDA.toBuffer());
+  const merkleTreePDA = pda(Buffer.from("merkle_tree"), programStatePDA.toBuffer());
+  const auditLogPDA = pda(Buffer.from("audit_log"), programStatePDA.toBuffer());
+  const vaultPDA = pda(Buffer.from("program_token_vault"), programStatePDA.toBuffer());
+  const verifyingKeyPDA = (circuitVersion: number) =>
+    pda(Buffer.from("verifying_key"), programStatePDA.toBuffer(), new anchor.BN(circuitVersion).toArrayLike(Buffer, "le", 2));
+  const nullifierPrefix = (nullifier: anchor.BN) => nullifier.toArrayLike(Buffer, "le", 8).subarray(0, 2);
+  const nullifierShardPDA = (nullifier: anchor.BN) =>
+    pda(Buffer.from("nullifier_shard"), programStatePDA.toBuffer(), nullifierPrefix(nullifier));
+  const commitmentShardPDA = (commitment: Buffer) =>
+    pda(Buffer.from("commitment_shard"), programStatePDA.toBuffer(), commitment.subarray(0, 2));
+
+  const measure = async (
+    instruction: string,
+    params: BenchResult["params"],
+    builder: { transaction(): Promise<anchor.web3.Transaction> },
+    signers: Signer[]
+  ) => {
+    const transaction = await builder.transaction();
+    transaction.instructions.unshift(treeInsertBudget());
+    const signature = await provider.sendAndConfirm(transaction, signers, { commitment: "confirmed" });
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    results.push({
+      instruction,
+      params,
+      computeUnits: tx?.meta?.computeUnitsConsumed ?? 0,
+      steps: stepUnits(tx?.meta?.logMessages ?? []),
+    });
+  };
+
+  // Mirrors external_nullifier in the program (variable denomination = 0)
+  const externalNullifier = (circuitVersion: number) => {
+    const digest = createHash("sha256")
+      .update(
+        Buffer.concat([
+          Buffer.from("privax-external-nullifier"),
+          program.programId.toBuffer(),
+          tokenMint.toBuffer(),
+          new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+          new anchor.BN(circuitVersion).toArrayLike(Buffer, "le", 2),
+        ])
+      )
+      .digest();
+    return new anchor.BN(digest.subarray(0, 8), "le");
+  };
+
+  let nextNullifier = 1;
+  const withdraw = async (inputNotes: number) => {
+    const nullifiers = Array.from({ length: inputNotes }, () => new anchor.BN(nextNullifier++));
+    for (const nullifier of nullifiers) {
+      if ((await program.account.nullifierShard.fetchNullable(nullifierShardPDA(nullifier))) !== null) continue;
+      await program.methods
+        .initNullifierShard(Array.from(nullifierPrefix(nullifier)))
+        .accounts({
+          programState: programStatePDA,
+          nullifierShard: nullifierShardPDA(nullifier),
+          payer: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+    }
+    const changeCommitment = inputNotes > 1 ? Buffer.alloc(32, inputNotes) : null;
+    const tree = await program.account.merkleTree.fetch(merkleTreePDA);
+    const root = (tree.roots as number[][])[tree.currentRootIndex.toNumber()];
+    const publicInputs = [
+      new anchor.BN(Buffer.from(root).subarray(0, 8), "le"), // The tree's current root
+      nullifiers[0],
+      new anchor.BN(recipient.publicKey.toBuffer().subarray(0, 8), "le"),
+      new anchor.BN(AMOUNT),
+      externalNullifier(circuitVersion(inputNotes)),
+      new anchor.BN(await provider.connection.getSlot()),
+      new anchor.BN(0), // No relayer fee
+      new anchor.BN(0), // No relayer
+      ...nullifiers.slice(1),
+      ...(changeCommitment ? [new anchor.BN(changeCommitment.subarray(0, 8), "le")] : []),
+    ];
+    const builder = program.methods
+      .withdraw(
+        Array.from(benchProof.aProof),
+        Array.from(benchProof.bProof),
+        Array.from(benchProof.cProof),
+        [...publicInputs, ...Array.from({ length: MAX_PUBLIC_INPUTS - publicInputs.length }, () => new anchor.BN(0))],
+        root,
+        // Full nullifier hashes: each input's bytes (LE), zero-padded to 32
+        Array.from(nullifiers[0].toArrayLike(Buffer, "le", 32)),
+        nullifiers.slice(1).map((nullifier) => Array.from(nullifier.toArrayLike(Buffer, "le", 32))),
+        recipient.publicKey,
+        new anchor.BN(AMOUNT),
+        new anchor.BN(0),
+        Buffer.alloc(0),
+        new anchor.BN(0),
+        circuitVersion(inputNotes),
+        Buffer.alloc(0),
+        changeCommitment === null ? null : Array.from(changeCommitment)
+      )
+      .accounts({
+        programState: programStatePDA,
+        verifyingKey: verifyingKeyPDA(circuitVersion(inputNotes)),
+        feeConfig: feeConfigPDA,
+        nullifierShard: nullifierShardPDA(nullifiers[0]),
+        nullifierFilter: nullifierFilterPDA,
+        merkleTree: merkleTreePDA,
+        rootArchive: null,
+        user: user.publicKey,
+        programTokenVault: vaultPDA,
+        programTokenVaultAuthority: vaultPDA,
+        recipientTokenAccount: recipientTokenAccount,
+        relayerInfo: null,
+        relayerFeeTokenAccount: null,
+        feeSwapProgram: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        nullifiers.slice(1).map((nullifier) => ({ pubkey: nullifierShardPDA(nullifier), isWritable: true, isSigner: false }))
+      );
+    await measure("withdraw", { inputNotes, change: changeCommitment !== null }, builder, [user]);
+  };
+
+  before(async () => {
+    for (const keypair of [admin, user, recipient]) {
+      await provider.connection.requestAirdrop(keypair.publicKey, 10 * LAMPORTS_PER_SOL);
+    }
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    tokenMint = await createMint(provider.connection, admin, admin.publicKey, null, 9);
+    userTokenAccount = await createAssociatedTokenAccount(provider.connection, user, tokenMint, user.publicKey);
+    recipientTokenAccount = await createAssociatedTokenAccount(provider.connection, recipient, tokenMint, recipient.publicKey);
+    await mintTo(provider.connection, admin, tokenMint, userTokenAccount, admin.publicKey, AMOUNT * 100);
+
+    await program.methods
+      .initialize(tokenMint, Keypair.generate().publicKey)
+      .accounts({
+        programState: programStatePDA,
+        feeConfig: feeConfigPDA,
+        nullifierFilter: nullifierFilterPDA,
+        merkleTree: merkleTreePDA,
+        auditLog: auditLogPDA,
+        admin: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions([treeInsertBudget()])
+      .signers([admin])
+      .rpc();
+
+    for (let inputNotes = 1; inputNotes <= 4; inputNotes++) {
+      // The fixed inputs, the other notes' nullifiers and, past one note, the change commitment
+      const publicInputs = 8 + (inputNotes - 1) + (inputNotes > 1 ? 1 : 0);
+      const vkData = benchVerifyingKey(publicInputs);
+      await program.methods
+        .registerVerifyingKey(circuitVersion(inputNotes), { groth16: {} }, 64, vkData.length, vkData)
+        .accounts({
+          programState: programStatePDA,
+          auditLog: auditLogPDA,
+          verifyingKey: verifyingKeyPDA(circuitVersion(inputNotes)),
+          admin: admin.publicKey,
+          payer: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+    }
+  });
+
+  after(async () => {
+    const tree = await program.account.merkleTree.fetch(merkleTreePDA);
+    const report = {
+      programId: program.programId.toBase58(),
+      merkleTreeDepth: (tree.filledSubtrees as number[][]).length,
+      results,
+    };
+    const reportPath = process.env.BENCH_REPORT ?? "benches/compute-units.json";
+    fs.writeFileSync(reportPath, JSON.stringify(report, null, 2) + "\n");
+    console.table(results.map(({ instruction, params, computeUnits }) => ({ instruction, ...params, computeUnits })));
+  });
+
+  // Largest first, so each subtree lands aligned on the one before it.
+  it("measures deposit_subtree per subtree height", async () => {
+    for (let height = 4; height >= 0; height--) {
+      const commitments = Array.from({ length: 1 << height }, (_, i) => dummyCommitment(16 * height + i + 1));
+      // Shards are opened up front so their creation isn't measured
+      for (const commitment of commitments) {
+        if ((await program.account.commitmentShard.fetchNullable(commitmentShardPDA(commitment))) !== null) continue;
+        await program.methods
+          .initCommitmentShard(Array.from(commitment.subarray(0, 2)))
+          .accounts({
+            programState: programStatePDA,
+            commitmentShard: commitmentShardPDA(commitment),
+            payer: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc();
+      }
+      const builder = program.methods
+        .depositSubtree(
+          commitments.map(() => new anchor.BN(LEAF_AMOUNT)),
+          commitments.map((commitment) => Array.from(commitment)),
+          Array.from(await subtreeRoot(commitments))
+        )
+        .accounts({
+          programState: programStatePDA,
+          merkleTree: merkleTreePDA,
+          user: user.publicKey,
+          userTokenAccount: userTokenAccount,
+          programTokenVault: vaultPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(commitments.map((commitment) => ({ pubkey: commitmentShardPDA(commitment), isSigner: false, isWritable: true })));
+      await measure("deposit_subtree", { height, leaves: commitments.length }, builder, [user]);
+    }
+  });
+
+  it("measures deposit (single tree insert)", async () => {
+    for (let i = 0; i < 3; i++) {
+      const builder = program.methods
+        .deposit(new anchor.BN(AMOUNT * 2), Array.from(dummyCommitment(200 + i)))
+        .accounts({
+          programState: programStatePDA,
+          feeConfig: feeConfigPDA,
+          merkleTree: merkleTreePDA,
+          commitmentShard: commitmentShardPDA(dummyCommitment(200 + i)),
+          referrerAccount: null,
+          depositReceipt: null,
+          user: user.publicKey,
+          userTokenAccount: userTokenAccount,
+          tokenMint: tokenMint,
+          programTokenVault: vaultPDA,
+          programTokenVaultAuthority: vaultPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        });
+      await measure("deposit", { run: i }, builder, [user]);
+    }
+  });
+
+  it("measures withdraw per input note count", async () => {
+    for (let inputNotes = 1; inputNotes <= 4; inputNotes++) {
+      await withdraw(inputNotes);
+    }
+  });
+});
@@ -0,0 +1,127 @@
+// privax-ceremony: runs phase 2 of the circuits' trusted setup in public. Anyone can add a
+// contribution to the latest key; the ceremony ends with a random beacon, and anyone can then
+// check the final key against the initial one `snarkjs zkey new` wrote (circuits/scripts/setup.sh
+// rebuilds it reproducibly) and the transcript of contributions.
+mod mpc;
+mod zkey;
+
+use std::path::Path;
+
+use crate::mpc::{Beacon, Transcript};
+use crate::zkey::Zkey;
+
+const DEFAULT_BEACON_ITERATIONS_EXP: u32 = 10;
+
+const USAGE: &str = "\
+Usage: privax-ceremony <command> [options]
+
+Commands:
+  contribute <in.zkey> <out.zkey> --transcript <file> --name <name> [--entropy <text>]
+      Add a contribution to the latest key, with the OS's randomness mixed with --entropy.
+      The transcript is created on the first contribution and extended by every later one.
+  beacon <in.zkey> <out.zkey> --transcript <file> --beacon <hex> [--iterations-exp <n>] [--name <name>]
+      Finish the ceremony with a contribution derived from a public random value (64 hex
+      digits), hashed 2^n times (default 2^10)
+  verify <initial.zkey> <final.zkey> --transcript <file>
+      Check that the final key came from the initial one through every contribution in the
+      transcript
+
+Pass the zkey circuits/scripts/setup.sh builds first (build/keys/<circuit>_0000.zkey) to the
+first contribution. Contributors keep nothing: their secret is gone when the command exits.
+";
+
+struct Options {
+    files: Vec<String>,
+    transcript: Option<String>,
+    name: Option<String>,
+    entropy: String,
+    beacon: Option<String>,
+    iterations_exp: u32,
+}
+
+fn main() {
+    if let Err(err) = run(std::env::args().skip(1).collect()) {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut args = args.into_iter();
+    let command = args.next().unwrap_or_default();
+    let mut options = Options {
+        files: Vec::new(),
+        transcript: None,
+        name: None,
+        entropy: String::new(),
+        beacon: None,
+        iterations_exp: DEFAULT_BEACON_ITERATIONS_EXP,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--transcript" => options.transcript = Some(value()?),
+            "--name" => options.name = Some(value()?),
+            "--entropy" => options.entropy = value()?,
+            "--beacon" => options.beacon = Some(value()?),
+            "--iterations-exp" => options.iterations_exp = value()?.parse().map_err(|_| "invalid --iterations-exp")?,
+            "-h" | "--help" => {
+                print!("{USAGE}");
+                return Ok(());
+            }
+            _ if arg.starts_with("--") => return Err(format!("unexpected argument {arg}\n\n{USAGE}")),
+            _ => options.files.push(arg),
+        }
+    }
+    let [input, output] = &options.files[..] else {
+        return Err(format!("expected two zkey files\n\n{USAGE}"));
+    };
+    let transcript_path = options.transcript.as_deref().ok_or("--transcript is required")?;
+
+    match command.as_str() {
+        "contribute" | "beacon" => {
+            let mut transcript = if Path::new(transcript_path).exists() { read_transcript(transcript_path)? } else { Transcript::default() };
+            let mut zkey = read_zkey(input)?;
+            let (name, beacon, mut rng) = if command == "contribute" {
+                let name = options.name.ok_or("--name is required")?;
+                (name, None, mpc::contribution_rng(options.entropy.as_bytes()))
+            } else {
+                let value = options.beacon.ok_or("--beacon is required")?;
+                let beacon = Beacon { value, iterations_exp: options.iterations_exp };
+                let rng = mpc::beacon_rng(&beacon)?;
+                (options.name.unwrap_or_else(|| "Final beacon".to_string()), Some(beacon), rng)
+            };
+            let bytes = mpc::contribute(&mut zkey, &mut transcript, &name, beacon, &mut rng)?;
+            std::fs::write(output, bytes).map_err(|err| format!("{output}: {err}"))?;
+            let json = serde_json::to_string_pretty(&transcript).expect("the transcript serializes") + "\n";
+            std::fs::write(transcript_path, json).map_err(|err| format!("{transcript_path}: {err}"))?;
+            println!("Contribution {} ({name}) written to {output}", transcript.contributions.len());
+            println!("Its key's sha256, to publish: {}", hex::encode(zkey.hash));
+        }
+        "verify" => {
+            let transcript = read_transcript(transcript_path)?;
+            let report = mpc::verify(&read_zkey(input)?, &read_zkey(output)?, &transcript)?;
+            println!("Initial key {}", transcript.initial_zkey);
+            for line in &report {
+                println!("{line}");
+            }
+            match transcript.contributions.last() {
+                Some(last) if last.beacon.is_some() => println!("OK: {} contributions, ending in a beacon", report.len()),
+                Some(_) => println!("OK: {} contributions, but the ceremony hasn't ended in a beacon", report.len()),
+                None => println!("OK, but nobody has contributed yet"),
+            }
+        }
+        _ => return Err(format!("unknown command {command:?}\n\n{USAGE}")),
+    }
+    Ok(())
+}
+
+fn read_zkey(path: &str) -> Result<Zkey, String> {
+    let bytes = std::fs::read(path).map_err(|err| format!("{path}: {err}"))?;
+    Zkey::parse(&bytes).map_err(|err| format!("{path}: {err}"))
+}
+
+fn read_transcript(path: &str) -> Result<Transcript, String> {
+    let bytes = std::fs::read(path).map_err(|err| format!("{path}: {err}"))?;
+    serde_json::from_slice(&bytes).map_err(|err| format!("{path}: {err}"))
+}
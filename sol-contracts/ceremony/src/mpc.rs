@@ -0,0 +1,218 @@
+// Phase 2 of the Groth16 setup (Bowe, Gabizon and Miers, "A Scalable Multi-party Computation
+// for zk-SNARK Parameters"). A contribution picks a secret d and turns the key's delta into
+// d * delta and its L and H queries into L / d and H / d; the key stays sound as long as one
+// contributor forgot their d. Each contribution proves it knows d without revealing it:
+//
+//   g1_s    a random G1 point
+//   g1_sx   d * g1_s
+//   g2_spx  d * g2_sp, where g2_sp is hashed from the key before the contribution, g1_s and g1_sx
+//
+// so e(g1_s, g2_spx) = e(g1_sx, g2_sp) shows the same d was used in both groups, and
+// e(delta_before, g2_spx) = e(delta_after, g2_sp) that delta moved by it. A beacon is a
+// contribution whose d anyone can recompute from a public random value, to finish the ceremony
+// with randomness no participant could have chosen.
+//
+// The proofs are kept in a JSON transcript next to the keys rather than in the zkey's own
+// contributions section, so `snarkjs zkey verify` doesn't know about them; `verify` here checks
+// a final key against the initial one and the transcript.
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::zkey::{Zkey, H_QUERY, L_QUERY};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Transcript {
+    pub initial_zkey: String, // sha256 of the key `snarkjs zkey new` wrote
+    pub contributions: Vec<Contribution>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Contribution {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub beacon: Option<Beacon>,
+    // Points are arkworks compressed, in hex
+    pub delta_after: String,
+    pub g1_s: String,
+    pub g1_sx: String,
+    pub g2_spx: String,
+    pub zkey: String, // sha256 of the key this contribution wrote
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Beacon {
+    pub value: String, // hex
+    pub iterations_exp: u32,
+}
+
+impl Transcript {
+    // The key the next contribution has to start from.
+    pub fn last_zkey(&self) -> &str {
+        self.contributions.last().map_or(&self.initial_zkey, |contribution| &contribution.zkey)
+    }
+}
+
+// Contributes to `zkey` with randomness from `rng`, appending the contribution to `transcript`,
+// and returns the new key's file.
+pub fn contribute(
+    zkey: &mut Zkey,
+    transcript: &mut Transcript,
+    name: &str,
+    beacon: Option<Beacon>,
+    rng: &mut ChaCha20Rng,
+) -> Result<Vec<u8>, String> {
+    let previous = hex::encode(zkey.hash);
+    if transcript.contributions.is_empty() && transcript.initial_zkey.is_empty() {
+        transcript.initial_zkey = previous.clone();
+    }
+    if transcript.last_zkey() != previous {
+        return Err(format!("the transcript continues from key {}, not from this one ({previous})", transcript.last_zkey()));
+    }
+    let d = secret(rng);
+    let d_inverse = d.inverse().expect("d is not zero");
+
+    let (delta_g1, delta_g2) = zkey.delta()?;
+    let delta_after = (delta_g1 * d).into_affine();
+    zkey.set_delta(delta_after, (delta_g2 * d).into_affine());
+    for kind in [L_QUERY, H_QUERY] {
+        let points: Vec<G1Projective> = zkey.g1_points(kind)?.par_iter().map(|point| *point * d_inverse).collect();
+        zkey.set_g1_points(kind, &G1Projective::normalize_batch(&points));
+    }
+
+    let g1_s = G1Projective::rand(rng).into_affine();
+    let g1_sx = (g1_s * d).into_affine();
+    let g2_spx = (challenge_point(&previous, &g1_s, &g1_sx) * d).into_affine();
+    let bytes = zkey.to_bytes();
+    zkey.hash = Sha256::digest(&bytes).into();
+    transcript.contributions.push(Contribution {
+        name: name.to_string(),
+        beacon,
+        delta_after: encode(&delta_after),
+        g1_s: encode(&g1_s),
+        g1_sx: encode(&g1_sx),
+        g2_spx: encode(&g2_spx),
+        zkey: hex::encode(zkey.hash),
+    });
+    Ok(bytes)
+}
+
+// Randomness for a contribution: the OS's, mixed with whatever the contributor types in, so
+// neither alone decides d.
+pub fn contribution_rng(entropy: &[u8]) -> ChaCha20Rng {
+    let mut os = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut os);
+    ChaCha20Rng::from_seed(Sha256::new().chain_update(os).chain_update(entropy).finalize().into())
+}
+
+// The beacon's randomness: its value hashed 2^iterations_exp times with sha256, so that
+// influencing the outcome would take grinding through that much work per candidate value.
+pub fn beacon_rng(beacon: &Beacon) -> Result<ChaCha20Rng, String> {
+    let mut hash = hex::decode(&beacon.value).ok().filter(|value| value.len() == 32).ok_or("the beacon must be 64 hex digits")?;
+    if beacon.iterations_exp > 63 {
+        return Err("at most 63 as the beacon's iterations exponent".to_string());
+    }
+    for _ in 0..1u64 << beacon.iterations_exp {
+        hash = Sha256::digest(&hash).to_vec();
+    }
+    Ok(ChaCha20Rng::from_seed(hash.try_into().unwrap()))
+}
+
+// Checks that `last` came from `initial` through the transcript's contributions, and returns a
+// line per contribution for the report.
+pub fn verify(initial: &Zkey, last: &Zkey, transcript: &Transcript) -> Result<Vec<String>, String> {
+    if hex::encode(initial.hash) != transcript.initial_zkey {
+        return Err(format!("the transcript starts from key {}, not {}", transcript.initial_zkey, hex::encode(initial.hash)));
+    }
+    if hex::encode(last.hash) != transcript.last_zkey() {
+        return Err(format!("the transcript ends at key {}, not {}", transcript.last_zkey(), hex::encode(last.hash)));
+    }
+    if !initial.same_circuit(last) {
+        return Err("the keys differ in more than delta and the L and H queries".to_string());
+    }
+
+    let mut report = Vec::new();
+    let (mut delta, mut previous) = (initial.delta()?.0, transcript.initial_zkey.clone());
+    for (index, contribution) in transcript.contributions.iter().enumerate() {
+        let failed = |what: &str| format!("contribution {} ({}): {what}", index + 1, contribution.name);
+        let g1 = |hex: &str| decode::<G1Affine>(hex).ok_or_else(|| failed("undecodable point"));
+        let (delta_after, g1_s, g1_sx) = (g1(&contribution.delta_after)?, g1(&contribution.g1_s)?, g1(&contribution.g1_sx)?);
+        let g2_spx = decode::<G2Affine>(&contribution.g2_spx).ok_or_else(|| failed("undecodable point"))?;
+        if g1_s.is_zero() || delta_after.is_zero() {
+            return Err(failed("a point at infinity"));
+        }
+        let g2_sp = challenge_point(&previous, &g1_s, &g1_sx);
+        if !same_ratio((g1_s, g1_sx), (g2_sp, g2_spx)) {
+            return Err(failed("the proof of knowledge doesn't hold"));
+        }
+        if !same_ratio((delta, delta_after), (g2_sp, g2_spx)) {
+            return Err(failed("delta didn't move by the proven secret"));
+        }
+        let mut line = format!("{:>3}. {}  key {}", index + 1, contribution.name, contribution.zkey);
+        if let Some(beacon) = &contribution.beacon {
+            let d = secret(&mut beacon_rng(beacon)?);
+            if (delta * d).into_affine() != delta_after {
+                return Err(failed("delta doesn't match the beacon's value"));
+            }
+            line += &format!("  (beacon {}, 2^{} iterations)", beacon.value, beacon.iterations_exp);
+        }
+        report.push(line);
+        (delta, previous) = (delta_after, contribution.zkey.clone());
+    }
+
+    let (last_delta_g1, last_delta_g2) = last.delta()?;
+    let (initial_delta_g2, generator_g2) = (initial.delta()?.1, G2Affine::generator());
+    if last_delta_g1 != delta || !same_ratio((G1Affine::generator(), last_delta_g1), (generator_g2, last_delta_g2)) {
+        return Err("the final key's delta isn't the transcript's".to_string());
+    }
+    // L and H moved by the inverse of delta's change: checked on a random combination of the points
+    let mut rng = contribution_rng(b"");
+    for kind in [L_QUERY, H_QUERY] {
+        let (before, after) = (initial.g1_points(kind)?, last.g1_points(kind)?);
+        let weights: Vec<Fr> = (0..before.len()).map(|_| Fr::rand(&mut rng)).collect();
+        let before = G1Projective::msm_unchecked(&before, &weights).into_affine();
+        let after = G1Projective::msm_unchecked(&after, &weights).into_affine();
+        if !same_ratio((after, before), (initial_delta_g2, last_delta_g2)) {
+            return Err(format!("section {kind} of the final key doesn't match its delta"));
+        }
+    }
+    Ok(report)
+}
+
+fn secret(rng: &mut ChaCha20Rng) -> Fr {
+    loop {
+        let d = Fr::rand(rng);
+        if !d.is_zero() {
+            return d;
+        }
+    }
+}
+
+// g2_sp: a G2 point nobody knows the discrete log of, derived from what the contribution commits to.
+fn challenge_point(previous_zkey: &str, g1_s: &G1Affine, g1_sx: &G1Affine) -> G2Projective {
+    let seed = Sha256::new().chain_update(previous_zkey).chain_update(encode(g1_s)).chain_update(encode(g1_sx)).finalize();
+    // Samples a random x until it's on the curve and clears the cofactor
+    G2Projective::rand(&mut ChaCha20Rng::from_seed(seed.into()))
+}
+
+// Whether b / a = d / c, i.e. e(a, d) = e(b, c).
+fn same_ratio((a, b): (G1Affine, G1Affine), (c, d): (impl Into<G2Affine>, impl Into<G2Affine>)) -> bool {
+    Bn254::pairing(a, d.into()) == Bn254::pairing(b, c.into())
+}
+
+fn encode(point: &impl CanonicalSerialize) -> String {
+    let mut bytes = Vec::new();
+    point.serialize_compressed(&mut bytes).expect("writing to a Vec");
+    hex::encode(bytes)
+}
+
+fn decode<T: CanonicalDeserialize>(encoded: &str) -> Option<T> {
+    T::deserialize_compressed(&hex::decode(encoded).ok()?[..]).ok()
+}
@@ -0,0 +1,169 @@
+// snarkjs's Groth16 proving keys ("zkey" v1), as `snarkjs zkey new` writes them: sections of
+// (type u32, size u64, body) after the magic and version, little-endian, with every curve
+// coordinate in Montgomery form and the point at infinity as all zeroes. A phase 2 contribution
+// only touches delta (in the Groth16 header) and the L and H queries, so the rest is kept as
+// raw bytes and written back untouched.
+use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, BigInteger256, PrimeField, Zero};
+use sha2::{Digest, Sha256};
+
+const MAGIC: &[u8; 4] = b"zkey";
+const VERSION: u32 = 1;
+const GROTH16: u32 = 1;
+const FIELD_SIZE: usize = 32;
+const G1_SIZE: usize = 2 * FIELD_SIZE;
+const G2_SIZE: usize = 4 * FIELD_SIZE;
+
+pub const HEADER: u32 = 1;
+pub const GROTH16_HEADER: u32 = 2;
+pub const L_QUERY: u32 = 8; // snarkjs's "C" section: one point per private wire
+pub const H_QUERY: u32 = 9;
+
+// In the Groth16 header: n8q, q, n8r, r, nVars, nPublic, domainSize, then alpha1, beta1,
+// beta2 and gamma2 before delta1 and delta2.
+const DELTA_G1: usize = 4 + FIELD_SIZE + 4 + FIELD_SIZE + 3 * 4 + 2 * G1_SIZE + 2 * G2_SIZE;
+const DELTA_G2: usize = DELTA_G1 + G1_SIZE;
+
+pub struct Zkey {
+    sections: Vec<(u32, Vec<u8>)>, // In file order
+    pub hash: [u8; 32],             // sha256 of the file it was read from
+}
+
+impl Zkey {
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let truncated = || "truncated zkey".to_string();
+        let u32_at = |at: usize| bytes.get(at..at + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+        if bytes.get(..4) != Some(MAGIC) || u32_at(4) != Some(VERSION) {
+            return Err("not a zkey v1 file".to_string());
+        }
+        let count = u32_at(8).ok_or_else(truncated)?;
+        let mut sections = Vec::with_capacity(count as usize);
+        let mut at = 12;
+        for _ in 0..count {
+            let kind = u32_at(at).ok_or_else(truncated)?;
+            let size = bytes.get(at + 4..at + 12).ok_or_else(truncated)?;
+            let size = usize::try_from(u64::from_le_bytes(size.try_into().unwrap())).map_err(|_| truncated())?;
+            let body = bytes.get(at + 12..).and_then(|rest| rest.get(..size)).ok_or_else(truncated)?;
+            sections.push((kind, body.to_vec()));
+            at += 12 + size;
+        }
+        let zkey = Zkey { sections, hash: Sha256::digest(bytes).into() };
+
+        if zkey.section(HEADER)?.get(..4) != Some(&GROTH16.to_le_bytes()[..]) {
+            return Err("not a Groth16 key".to_string());
+        }
+        let header = zkey.section(GROTH16_HEADER)?;
+        let modulus = Fq::MODULUS.to_bytes_le();
+        if header.get(..4) != Some(&(FIELD_SIZE as u32).to_le_bytes()[..]) || header.get(4..4 + FIELD_SIZE) != Some(&modulus[..]) {
+            return Err("not over BN254".to_string());
+        }
+        if header.len() < DELTA_G2 + G2_SIZE {
+            return Err(truncated());
+        }
+        Ok(zkey)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.sections.len() as u32).to_le_bytes());
+        for (kind, body) in &self.sections {
+            bytes.extend_from_slice(&kind.to_le_bytes());
+            bytes.extend_from_slice(&(body.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(body);
+        }
+        bytes
+    }
+
+    pub fn section(&self, kind: u32) -> Result<&[u8], String> {
+        self.sections.iter().find(|(other, _)| *other == kind).map(|(_, body)| &body[..]).ok_or(format!("missing section {kind}"))
+    }
+
+    fn section_mut(&mut self, kind: u32) -> &mut Vec<u8> {
+        &mut self.sections.iter_mut().find(|(other, _)| *other == kind).expect("parse checked the section").1
+    }
+
+    pub fn delta(&self) -> Result<(G1Affine, G2Affine), String> {
+        let header = self.section(GROTH16_HEADER)?;
+        Ok((read_g1(&header[DELTA_G1..])?, read_g2(&header[DELTA_G2..])?))
+    }
+
+    pub fn set_delta(&mut self, g1: G1Affine, g2: G2Affine) {
+        let header = self.section_mut(GROTH16_HEADER);
+        header[DELTA_G1..DELTA_G2].copy_from_slice(&write_g1(&g1));
+        header[DELTA_G2..DELTA_G2 + G2_SIZE].copy_from_slice(&write_g2(&g2));
+    }
+
+    pub fn g1_points(&self, kind: u32) -> Result<Vec<G1Affine>, String> {
+        let body = self.section(kind)?;
+        if body.len() % G1_SIZE != 0 {
+            return Err(format!("section {kind} isn't a list of G1 points"));
+        }
+        body.chunks(G1_SIZE).map(read_g1).collect::<Result<_, _>>().map_err(|err| format!("section {kind}: {err}"))
+    }
+
+    pub fn set_g1_points(&mut self, kind: u32, points: &[G1Affine]) {
+        *self.section_mut(kind) = points.iter().flat_map(write_g1).collect();
+    }
+
+    // Whether the two keys agree on everything a contribution leaves alone.
+    pub fn same_circuit(&self, other: &Zkey) -> bool {
+        let kinds = |zkey: &Zkey| zkey.sections.iter().map(|(kind, _)| *kind).collect::<Vec<_>>();
+        kinds(self) == kinds(other)
+            && self.sections.iter().zip(&other.sections).all(|((kind, ours), (_, theirs))| match *kind {
+                GROTH16_HEADER => ours[..DELTA_G1] == theirs[..DELTA_G1] && ours[DELTA_G2 + G2_SIZE..] == theirs[DELTA_G2 + G2_SIZE..],
+                L_QUERY | H_QUERY => ours.len() == theirs.len(),
+                _ => ours == theirs,
+            })
+    }
+}
+
+fn read_fq(bytes: &[u8]) -> Result<Fq, String> {
+    let limbs: Vec<u64> = bytes[..FIELD_SIZE].chunks(8).map(|limb| u64::from_le_bytes(limb.try_into().unwrap())).collect();
+    let montgomery = BigInteger256::new(limbs.try_into().unwrap());
+    if montgomery >= Fq::MODULUS {
+        return Err("coordinate out of range".to_string());
+    }
+    Ok(Fq::new_unchecked(montgomery))
+}
+
+fn read_g1(bytes: &[u8]) -> Result<G1Affine, String> {
+    let (x, y) = (read_fq(bytes)?, read_fq(&bytes[FIELD_SIZE..])?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    point.is_on_curve().then_some(point).ok_or("G1 point not on the curve".to_string())
+}
+
+fn read_g2(bytes: &[u8]) -> Result<G2Affine, String> {
+    let fq2 = |at: usize| Ok::<_, String>(Fq2::new(read_fq(&bytes[at..])?, read_fq(&bytes[at + FIELD_SIZE..])?));
+    let (x, y) = (fq2(0)?, fq2(2 * FIELD_SIZE)?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point).ok_or("invalid G2 point".to_string())
+}
+
+fn write_fq(value: &Fq, out: &mut Vec<u8>) {
+    out.extend(value.0 .0.iter().flat_map(|limb| limb.to_le_bytes()));
+}
+
+fn write_g1(point: &G1Affine) -> Vec<u8> {
+    let mut out = Vec::with_capacity(G1_SIZE);
+    let (x, y) = point.xy().map_or((Fq::zero(), Fq::zero()), |(x, y)| (*x, *y));
+    write_fq(&x, &mut out);
+    write_fq(&y, &mut out);
+    out
+}
+
+fn write_g2(point: &G2Affine) -> Vec<u8> {
+    let mut out = Vec::with_capacity(G2_SIZE);
+    let (x, y) = point.xy().map_or((Fq2::zero(), Fq2::zero()), |(x, y)| (*x, *y));
+    for coordinate in [x.c0, x.c1, y.c0, y.c1] {
+        write_fq(&coordinate, &mut out);
+    }
+    out
+}
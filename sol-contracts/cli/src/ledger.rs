@@ -0,0 +1,228 @@
+// Signing with the Solana app on a Ledger, spoken to directly over Linux hidraw: APDUs are
+// framed into 64-byte HID reports on channel 0x0101. Privax instructions aren't parsed by
+// the app, so it blind-signs and shows only the message hash; sign_message prints the same
+// hash next to the instruction summary so the two can be compared before approving.
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use solana_sdk::hash::hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::{Signer, SignerError};
+use tracing::warn;
+
+const LEDGER_VENDOR_ID: &str = "00002C97";
+// Usage page 0xFFA0: the generic HID interface, as opposed to U2F/FIDO
+const GENERIC_USAGE_PAGE: [u8; 3] = [0x06, 0xA0, 0xFF];
+
+const HID_PACKET_SIZE: usize = 64;
+const CHANNEL: [u8; 2] = [0x01, 0x01];
+const APDU_TAG: u8 = 0x05;
+const MAX_CHUNK_SIZE: usize = 255;
+
+const CLA: u8 = 0xE0;
+const INS_GET_APP_CONFIGURATION: u8 = 0x04;
+const INS_GET_PUBKEY: u8 = 0x05;
+const INS_SIGN_MESSAGE: u8 = 0x06;
+const P1_NON_CONFIRM: u8 = 0x00;
+const P1_CONFIRM: u8 = 0x01;
+const P2_EXTEND: u8 = 0x01;
+const P2_MORE: u8 = 0x02;
+
+const STATUS_OK: u16 = 0x9000;
+const STATUS_USER_CANCEL: u16 = 0x6985;
+const STATUS_INVALID_CLA: u16 = 0x6E00;
+const STATUS_UNIMPLEMENTED: u16 = 0x6D00;
+
+const HARDENED: u32 = 0x8000_0000;
+
+// m/44'/501'[/account'[/change']], as in `usb://ledger?key=account[/change]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DerivationPath {
+    pub account: Option<u32>,
+    pub change: Option<u32>,
+}
+
+impl DerivationPath {
+    pub fn parse(key: &str) -> Result<Self, String> {
+        let index = |s: &str| s.trim_end_matches('\'').parse::<u32>().map_err(|_| format!("invalid key index `{s}`"));
+        match key.split_once('/') {
+            Some((account, change)) => Ok(Self { account: Some(index(account)?), change: Some(index(change)?) }),
+            None => Ok(Self { account: Some(index(key)?), change: None }),
+        }
+    }
+
+    // Component count, then each hardened index big-endian.
+    fn serialize(&self) -> Vec<u8> {
+        let indexes: Vec<u32> = [Some(44), Some(501), self.account, self.account.and(self.change)].into_iter().flatten().collect();
+        let mut bytes = vec![indexes.len() as u8];
+        for index in indexes {
+            bytes.extend_from_slice(&(index | HARDENED).to_be_bytes());
+        }
+        bytes
+    }
+}
+
+impl std::fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "m/44'/501'")?;
+        if let Some(account) = self.account {
+            write!(f, "/{account}'")?;
+            if let Some(change) = self.change {
+                write!(f, "/{change}'")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Transport {
+    device: File,
+}
+
+impl Transport {
+    // The first Ledger's generic HID interface.
+    fn open() -> Result<Self, SignerError> {
+        let entries = fs::read_dir("/sys/class/hidraw").map_err(|_| SignerError::NoDeviceFound)?;
+        for entry in entries.flatten() {
+            let sysfs = entry.path().join("device");
+            let uevent = fs::read_to_string(sysfs.join("uevent")).unwrap_or_default();
+            let descriptor = fs::read(sysfs.join("report_descriptor")).unwrap_or_default();
+            if uevent.to_uppercase().contains(&format!(":{LEDGER_VENDOR_ID}:")) && descriptor.starts_with(&GENERIC_USAGE_PAGE) {
+                let path = PathBuf::from("/dev").join(entry.file_name());
+                let device = OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(&path)
+                    .map_err(|err| SignerError::Connection(format!("{}: {err}", path.display())))?;
+                return Ok(Self { device });
+            }
+        }
+        Err(SignerError::NoDeviceFound)
+    }
+
+    fn exchange(&mut self, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Result<Vec<u8>, SignerError> {
+        self.write(ins, p1, p2, data).map_err(|err| SignerError::Connection(err.to_string()))?;
+        let mut response = self.read().map_err(|err| SignerError::Connection(err.to_string()))?;
+        if response.len() < 2 {
+            return Err(SignerError::Protocol("truncated Ledger response".to_string()));
+        }
+        let status = u16::from_be_bytes([response[response.len() - 2], response[response.len() - 1]]);
+        response.truncate(response.len() - 2);
+        match status {
+            STATUS_OK => Ok(response),
+            STATUS_USER_CANCEL => Err(SignerError::UserCancel("rejected on the Ledger".to_string())),
+            STATUS_INVALID_CLA | STATUS_UNIMPLEMENTED => {
+                Err(SignerError::Protocol("open the Solana app on the Ledger".to_string()))
+            }
+            status => Err(SignerError::Protocol(format!("Ledger returned status {status:#06x}"))),
+        }
+    }
+
+    fn write(&mut self, ins: u8, p1: u8, p2: u8, data: &[u8]) -> std::io::Result<()> {
+        let mut apdu = vec![CLA, ins, p1, p2, data.len() as u8];
+        apdu.extend_from_slice(data);
+        let mut payload = (apdu.len() as u16).to_be_bytes().to_vec();
+        payload.extend_from_slice(&apdu);
+
+        for (sequence, chunk) in payload.chunks(HID_PACKET_SIZE - 5).enumerate() {
+            // Leading 0: hidraw's report number for devices without numbered reports
+            let mut report = [0u8; HID_PACKET_SIZE + 1];
+            report[1..3].copy_from_slice(&CHANNEL);
+            report[3] = APDU_TAG;
+            report[4..6].copy_from_slice(&(sequence as u16).to_be_bytes());
+            report[6..6 + chunk.len()].copy_from_slice(chunk);
+            self.device.write_all(&report)?;
+        }
+        Ok(())
+    }
+
+    fn read(&mut self) -> std::io::Result<Vec<u8>> {
+        let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string());
+        let mut message = Vec::new();
+        let mut expected = None;
+        for sequence in 0u16.. {
+            let mut report = [0u8; HID_PACKET_SIZE];
+            let len = self.device.read(&mut report)?;
+            if len < 5 || report[..2] != CHANNEL || report[2] != APDU_TAG || report[3..5] != sequence.to_be_bytes() {
+                return Err(invalid("unexpected HID report from the Ledger"));
+            }
+            let mut body = &report[5..len];
+            if sequence == 0 {
+                if body.len() < 2 {
+                    return Err(invalid("truncated HID report from the Ledger"));
+                }
+                expected = Some(usize::from(u16::from_be_bytes([body[0], body[1]])));
+                body = &body[2..];
+            }
+            let expected = expected.unwrap_or_default();
+            message.extend_from_slice(body);
+            if message.len() >= expected {
+                message.truncate(expected);
+                break;
+            }
+        }
+        Ok(message)
+    }
+}
+
+pub struct LedgerSigner {
+    path: DerivationPath,
+    pubkey: Pubkey,
+}
+
+impl LedgerSigner {
+    // Connects to the first Ledger and reads the key at `path`. If `expected` is given, the
+    // device must hold that key, so a different Ledger can't silently stand in.
+    pub fn connect(path: DerivationPath, expected: Option<Pubkey>) -> Result<Self, SignerError> {
+        let mut transport = Transport::open()?;
+        let pubkey = transport.exchange(INS_GET_PUBKEY, P1_NON_CONFIRM, 0, &path.serialize())?;
+        let pubkey = Pubkey::try_from(pubkey.as_slice())
+            .map_err(|_| SignerError::Protocol("Ledger returned a malformed public key".to_string()))?;
+        if let Some(expected) = expected.filter(|expected| *expected != pubkey) {
+            return Err(SignerError::Custom(format!("the Ledger holds {pubkey} at {path}, not {expected}")));
+        }
+        // Byte 0 of the app configuration is the blind-signing setting
+        let config = transport.exchange(INS_GET_APP_CONFIGURATION, 0, 0, &[])?;
+        if config.first() == Some(&0) {
+            warn!("blind signing is disabled in the Ledger's Solana app; enable it to sign Privax instructions");
+        }
+        Ok(Self { path, pubkey })
+    }
+}
+
+impl Signer for LedgerSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        eprintln!("Approve on the Ledger ({}); it should show message hash {}", self.path, hash(message));
+
+        // One signer's path, then the message, split across APDUs of at most 255 bytes
+        let mut payload = vec![1];
+        payload.extend_from_slice(&self.path.serialize());
+        payload.extend_from_slice(message);
+        let chunks: Vec<&[u8]> = payload.chunks(MAX_CHUNK_SIZE).collect();
+
+        let mut transport = Transport::open()?;
+        let mut response = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let mut p2 = 0;
+            if i > 0 {
+                p2 |= P2_EXTEND;
+            }
+            if i + 1 < chunks.len() {
+                p2 |= P2_MORE;
+            }
+            response = transport.exchange(INS_SIGN_MESSAGE, P1_CONFIRM, p2, chunk)?;
+        }
+        Signature::try_from(response.as_slice())
+            .map_err(|_| SignerError::Protocol("Ledger returned a malformed signature".to_string()))
+    }
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
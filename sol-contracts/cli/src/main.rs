@@ -0,0 +1,634 @@
+// privax-cli: deposits and admin operations from the command line. Any command can be signed
+// by a keypair file or a Ledger (`--keypair usb://ledger`), and admin commands can be signed
+// on an air-gapped machine with `--sign-only` and sent later with `--submit`. Payment
+// requests and note blobs can be shown as QR codes for a phone to scan, and `payroll` pays a
+// CSV of recipients privately through a relayer; see USAGE.
+mod ledger;
+mod offline;
+mod payroll;
+mod qr;
+mod signer;
+mod summary;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use privax_client::blocking::PrivaxClient;
+use privax_client::config::{self, Cluster};
+use privax_client::instructions::{self, DepositGates, PaymentScheduleParams, UsdLimitsParams};
+use privax_client::logging;
+use privax_client::payment_request::PaymentRequest;
+use privax_client::sns;
+use privax_protocol::{AdminAction, FeeSchedule, ROOT_ARCHIVE_PAGE_ROOTS};
+use solana_sdk::hash::{hash, Hash};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+
+const DEFAULT_KEYPAIR: &str = "~/.config/solana/id.json";
+
+const USAGE: &str = "\
+Usage: privax-cli [options] <command> [args]
+       privax-cli [--keypair <signer>] cosign <file>
+       privax-cli [--cluster <name>] [--url <rpc-url>] [--keypair <signer>] --submit <file>
+       privax-cli note show <file> [--qr]
+       privax-cli payroll status <job-file>
+
+Options:
+  --cluster <name>       localnet, devnet or mainnet; picks the RPC endpoints and program ID
+                         (default PRIVAX_CLUSTER, else the config's default, localnet)
+  --url <rpc-url>        RPC endpoint, instead of the cluster's
+  --keypair <signer>     A keypair file path, or usb://ledger[/<pubkey>][?key=<account>[/<change>]]
+  --sign-only            Sign without network access and write the transaction to --output
+  --output <file>        Where --sign-only writes the partially-signed transaction
+  --blockhash <hash>     Blockhash to sign with; the nonce value when --nonce is given
+  --nonce <account>      Durable nonce account, advanced by the signer, so the file doesn't expire
+  --fee-payer <pubkey>   Fee payer (and rent payer) that signs later, online
+  --submit <file>        Sign a transaction file if the signer is still needed, then send it
+  --label <text>         Who is asking, for `request`
+  --message <text>       What the payment is for, for `request`
+  --qr                   Also render the output of `request` and `note show` as a QR code
+  --relayer <url>        Relayer for `payroll run` (default the profile's relayer_url)
+  --prover <url>         Proving service running the split circuit, for `payroll run`
+                         (default http://localhost:8080)
+  --api-key <key>        The relayer's X-API-Key, for its higher rate limit
+  --circuit-version <n>  Split circuit version `payroll run` proves for (default 1)
+
+Recipients and relayers can be given as .sol domains; they are resolved online, checked against
+the domain's reverse record, and printed before signing.
+
+Progress and warnings are logged to stderr: PRIVAX_LOG sets levels per module (default info,
+e.g. PRIVAX_LOG=warn,privax_cli::payroll=debug) and PRIVAX_LOG_FORMAT=json writes JSON lines.
+Note secrets are redacted from the logs.
+
+Commands:
+  address                                        Print the signer's public key
+  deposit <token-account> <amount> <commitment> [envelope-file]
+                                                 Deposit pool tokens; commitment is 32 bytes of hex.
+                                                 An envelope file, encrypted to the pool's compliance
+                                                 key, is attested to in the same transaction
+  audit-log                                      List the admin actions the audit log still holds
+  init-audit-log                                 Create the audit log of a pool that predates it
+  pause                                          Pause the pool (admin or guardian)
+  unpause                                        Unpause the pool (admin)
+  set-guardian <pubkey>                          Set the guardian (admin)
+  set-tree-operator <pubkey>                     Set the key that runs tree maintenance (admin)
+  set-withdrawal-limit <max-per-epoch> <epoch-slots>
+                                                 Pause the pool once an epoch's withdrawals reach
+                                                 the limit, in base units; 0 turns it off (admin)
+  set-compliance-key <hex|none>                  Require deposits to attest to an envelope encrypted to
+                                                 this X25519 key; none turns it off (admin)
+  set-kyc-attestor <pubkey|none>                 Only take deposits from holders of a KYC credential
+                                                 issued by this key; none opens the pool (admin)
+  issue-kyc <holder> [expires-at]                Issue or renew a KYC credential; expires-at is a unix
+                                                 time, none = never (KYC attestor)
+  revoke-kyc <holder>                            Revoke a KYC credential (its attestor)
+  set-max-daily-deposit <max>                    Cap what one depositor can deposit in any 24 hours,
+                                                 in base units; 0 lifts the cap (admin)
+  set-exclusion-operator <pubkey|none>           Let this key publish exclusion roots; none leaves it to
+                                                 the admin (admin)
+  publish-exclusion-root <root>                  Publish the root of the commitments flagged as illicit;
+                                                 root is 32 bytes of hex (exclusion operator or admin)
+  exclusion-proofs <on|off>                      Whether spend proofs must show their notes aren't in the
+                                                 exclusion set (admin)
+  set-max-pause <slots>                          Let withdrawals resume once a pause lasts this long;
+                                                 0 = never (admin)
+  pause-on-shortfall <on|off>                    Whether reconcile pauses the pool when the vault is short (admin)
+  reconcile                                      Check the vault balance against deposits, withdrawals and fees
+  checkpoint-root                                Pin the tree's current root (tree operator or admin)
+  archive-roots                                  Copy the tree's new roots into the root archive
+                                                 (tree operator or admin)
+  accept-archived-roots                          Let spends prove against archived roots (admin)
+  refuse-archived-roots                          Only accept the tree's recent roots (admin)
+  transfer-ownership <pubkey>                    Hand the admin role over (admin)
+  update-fee-config <fee-bps> <max-fee>          Set the withdrawal fee (fee authority)
+  enable-verifying-key <circuit-version>         Accept proofs for a circuit (admin)
+  disable-verifying-key <circuit-version>        Stop accepting proofs for a circuit (admin)
+  set-admin-timelock <slots>                     Make ownership, guardian, verifying key and swap target
+                                                 changes, and loosening a safeguard, wait this long,
+                                                 through `queue`; lowering it is queued too (admin)
+  queue <action> <args>                          Queue one of these while the timelock is set, with the
+                                                 same arguments as its command, and print its id
+                                                 (admin): transfer-ownership, set-guardian,
+                                                 enable-verifying-key, set-admin-timelock,
+                                                 add-swap-target, set-withdrawal-limit,
+                                                 set-compliance-key, set-kyc-attestor,
+                                                 exclusion-proofs
+  execute-admin-action <id>                      Run a queued action once its timelock has passed (admin)
+  cancel-admin-action <id>                       Drop a queued action (admin or guardian)
+  add-relayer <relayer> <endpoint-url> <fee-bps> <min-fee> [expiry-slot]
+                                                 Whitelist a relayer (admin)
+  renew-relayer <relayer> [expiry-slot]          Change a relayer's expiry; none = never (admin)
+  remove-relayer <relayer> [rent-receiver]       Remove a relayer (admin)
+  add-swap-target <program>                      Let shielded swaps deposit into this pool program (admin)
+  remove-swap-target <program>                   Stop shielded swaps into this pool program (admin)
+  set-usd-limits <price-feed> <max-price-age> <max-confidence-bps> <max-deposit-usd> <large-withdrawal-usd> <flat-fee-usd>
+                                                 Set the USD deposit cap, large-withdrawal
+                                                 threshold and flat fee, in micro-USD priced by a
+                                                 Pyth account; 0 leaves one unset (admin)
+  cosign <file>                                  Add the signer's signature to a transaction file
+  request <amount> <commitment>                  Print a payment request URL for a shielded deposit
+  pay <url> <token-account>                      Deposit as a payment request asks
+  schedule-payment <schedule-id> <recipient> <amount> <executor-fee> <interval-slots> <first-slot> <payments>
+                                                 Set up a recurring payment; fund its escrow by
+                                                 withdrawing notes to it
+  due-payments                                   List payment schedules with a payment due
+  execute-payment <schedule>                     Pay a due installment and take its executor fee
+  cancel-schedule <schedule-id>                  End a schedule and refund its escrow to the signer
+  note show <file>                               Print an encrypted note transfer blob
+  payroll plan <csv> <job-file>                  Plan paying a CSV of `recipient,amount` rows in
+                                                 withdraw_split batches; the job file will hold
+                                                 note secrets, so keep it private
+  payroll run <job-file> <token-account>         Fund, prove and relay the job's batches; resumes
+                                                 where an interrupted run stopped
+  payroll status <job-file>                      Show how far each batch has got
+";
+
+#[derive(Default)]
+struct Options {
+    cluster: Option<Cluster>,
+    url: Option<String>,
+    keypair: Option<String>,
+    sign_only: bool,
+    output: Option<String>,
+    blockhash: Option<Hash>,
+    nonce: Option<Pubkey>,
+    fee_payer: Option<Pubkey>,
+    submit: Option<String>,
+    label: Option<String>,
+    message: Option<String>,
+    qr: bool,
+    relayer: Option<String>,
+    prover: Option<String>,
+    api_key: Option<String>,
+    circuit_version: Option<u16>,
+}
+
+fn main() {
+    if let Err(err) = logging::init("info").and_then(|()| run(std::env::args().skip(1).collect())) {
+        eprintln!("error: {}", logging::redact(&err));
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut options = Options::default();
+    let mut positional = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "-c" | "--cluster" => options.cluster = Some(value()?.parse().map_err(|err: config::ConfigError| err.to_string())?),
+            "-u" | "--url" => options.url = Some(value()?),
+            "-k" | "--keypair" => options.keypair = Some(value()?),
+            "--sign-only" => options.sign_only = true,
+            "--output" => options.output = Some(value()?),
+            "--blockhash" => options.blockhash = Some(value()?.parse().map_err(|_| "invalid --blockhash")?),
+            "--nonce" => options.nonce = Some(pubkey(&value()?)?),
+            "--fee-payer" => options.fee_payer = Some(pubkey(&value()?)?),
+            "--submit" => options.submit = Some(value()?),
+            "--label" => options.label = Some(value()?),
+            "--message" => options.message = Some(value()?),
+            "--qr" => options.qr = true,
+            "--relayer" => options.relayer = Some(value()?),
+            "--prover" => options.prover = Some(value()?),
+            "--api-key" => options.api_key = Some(value()?),
+            "--circuit-version" => options.circuit_version = Some(number(&value()?)?),
+            "-h" | "--help" => {
+                print!("{USAGE}");
+                return Ok(());
+            }
+            _ => positional.push(arg),
+        }
+    }
+    if let Some(path) = options.submit.clone() {
+        return submit(&options, &path);
+    }
+    let Some((command, params)) = positional.split_first() else {
+        return Err(format!("missing command\n\n{USAGE}"));
+    };
+    if let ("note", [show, path]) = (command.as_str(), params) {
+        if show == "show" {
+            return show_note(path, options.qr);
+        }
+    }
+    if let ("payroll", [status, path]) = (command.as_str(), params) {
+        if status == "status" {
+            return payroll::status(path);
+        }
+    }
+
+    let signer = signer::parse(options.keypair.as_deref().unwrap_or(DEFAULT_KEYPAIR))?;
+    let authority = signer.pubkey();
+    let params: Vec<&str> = params.iter().map(String::as_str).collect();
+    match (command.as_str(), params.as_slice()) {
+        ("address", []) => {
+            println!("{authority}");
+            return Ok(());
+        }
+        ("cosign", [path]) => return cosign(&signer, path),
+        _ => {}
+    }
+
+    let fee_payer = options.fee_payer.unwrap_or(authority);
+    if options.sign_only {
+        let instructions = build_instructions(command, &params, authority, fee_payer, None)?;
+        return sign_only(&options, &signer, &instructions, fee_payer);
+    }
+    if matches!(options.fee_payer, Some(payer) if payer != authority) || options.nonce.is_some() {
+        return Err("--fee-payer and --nonce only apply with --sign-only".to_string());
+    }
+    let client = client(&options, signer)?;
+    if let ("request", [amount, commitment]) = (command.as_str(), params.as_slice()) {
+        let (mint, mint_account) = client.pool_mint().map_err(|err| err.to_string())?;
+        let mut request = PaymentRequest::new(mint, number(amount)?, mint_account.decimals, bytes32(commitment)?);
+        request.label = options.label;
+        request.message = options.message;
+        return print_with_qr(&request.to_string(), options.qr);
+    }
+    match (command.as_str(), params.as_slice()) {
+        ("payroll", ["plan", csv, job]) => return payroll::plan(&client, csv, job),
+        ("payroll", ["run", job, token_account]) => {
+            let profile = profile(&options)?;
+            let services = payroll::Services {
+                relayer: options.relayer.or(profile.relayer_url).ok_or("payroll run needs --relayer")?,
+                prover: options.prover.unwrap_or_else(|| "http://localhost:8080".to_string()),
+                api_key: options.api_key,
+                circuit_version: options.circuit_version.unwrap_or(1),
+            };
+            return payroll::run(&client, &services, job, pubkey(token_account)?);
+        }
+        _ => {}
+    }
+    if let ("due-payments", []) = (command.as_str(), params.as_slice()) {
+        let token_mint = client.program_state().map_err(|err| err.to_string())?.token_mint;
+        for (address, schedule) in client.due_payment_schedules().map_err(|err| err.to_string())? {
+            println!(
+                "{address}  {} to {}, {} left, escrow {}",
+                schedule.amount,
+                schedule.recipient,
+                schedule.payments_remaining,
+                instructions::schedule_escrow_address(&address, &token_mint)
+            );
+        }
+        return Ok(());
+    }
+    if let ("audit-log", []) = (command.as_str(), params.as_slice()) {
+        let audit_log = client.audit_log().map_err(|err| err.to_string())?;
+        for entry in audit_log.entries() {
+            let action = entry.action().map_or_else(|| format!("action {}", entry.action), |action| format!("{action:?}"));
+            println!(
+                "slot {}  {action} by {}  old {} new {}",
+                entry.slot,
+                entry.actor,
+                summary::hex(&entry.old_value_hash),
+                summary::hex(&entry.new_value_hash)
+            );
+        }
+        return Ok(());
+    }
+    let instructions = build_instructions(command, &params, authority, fee_payer, Some(&client))?;
+    eprintln!("Signing as {authority}:");
+    print_summary(&instructions);
+    let signature = client.send(&instructions, &[]).map_err(|err| err.to_string())?;
+    println!("{signature}");
+    Ok(())
+}
+
+// `client` is None when signing offline.
+fn build_instructions(
+    command: &str,
+    params: &[&str],
+    authority: Pubkey,
+    fee_payer: Pubkey,
+    client: Option<&PrivaxClient>,
+) -> Result<Vec<Instruction>, String> {
+    Ok(match (command, params) {
+        ("deposit", [token_account, amount, commitment, envelope @ ..]) if envelope.len() <= 1 => {
+            // Needs the pool's mint from the chain
+            let client = client.ok_or("deposit can't be signed offline")?;
+            let state = client.program_state().map_err(|err| err.to_string())?;
+            let usd_price_feed = client.usd_price_feed(&state).map_err(|err| err.to_string())?;
+            let (token_account, amount, commitment) = (pubkey(token_account)?, number(amount)?, bytes32(commitment)?);
+            let mut instructions = Vec::new();
+            if let Some(path) = envelope.first() {
+                let envelope = std::fs::read(path).map_err(|err| format!("failed to read {path}: {err}"))?;
+                instructions.push(instructions::attest_deposit(authority, commitment, hash(&envelope).to_bytes()));
+            }
+            if state.max_daily_deposit > 0 && client.deposit_volume(&authority).map_err(|err| err.to_string())?.is_none() {
+                instructions.push(instructions::init_deposit_volume(fee_payer, authority));
+            }
+            let gates = DepositGates::for_pool(&state);
+            instructions.push(instructions::deposit(authority, token_account, state.token_mint, amount, commitment, usd_price_feed, gates));
+            instructions
+        }
+        ("pay", [url, token_account]) => {
+            let client = client.ok_or("pay can't be signed offline")?;
+            let request: PaymentRequest = url.parse().map_err(|err| format!("{err}"))?;
+            if request.program_id != privax_protocol::ID {
+                return Err(format!("the request is for another Privax deployment ({})", request.program_id));
+            }
+            let (mint, mint_account) = client.pool_mint().map_err(|err| err.to_string())?;
+            if request.mint != mint {
+                return Err(format!("the request asks for mint {}, but the pool holds {mint}", request.mint));
+            }
+            let amount = request
+                .base_units(mint_account.decimals)
+                .ok_or_else(|| format!("invalid amount {} for a mint with {} decimals", request.amount, mint_account.decimals))?;
+            if let Some(label) = &request.label {
+                eprintln!("Payment to {label}{}", request.message.as_ref().map(|m| format!(": {m}")).unwrap_or_default());
+            }
+            let state = client.program_state().map_err(|err| err.to_string())?;
+            let usd_price_feed = client.usd_price_feed(&state).map_err(|err| err.to_string())?;
+            let mut instructions = Vec::new();
+            if state.max_daily_deposit > 0 && client.deposit_volume(&authority).map_err(|err| err.to_string())?.is_none() {
+                instructions.push(instructions::init_deposit_volume(fee_payer, authority));
+            }
+            let gates = DepositGates::for_pool(&state);
+            instructions.push(instructions::deposit(authority, pubkey(token_account)?, mint, amount, request.commitment, usd_price_feed, gates));
+            instructions
+        }
+        ("schedule-payment", [schedule_id, recipient, amount, executor_fee, interval_slots, first_slot, payments]) => {
+            let client = client.ok_or("schedule-payment can't be signed offline")?;
+            let token_mint = client.program_state().map_err(|err| err.to_string())?.token_mint;
+            let params = PaymentScheduleParams {
+                schedule_id: number(schedule_id)?,
+                recipient: address(Some(client), recipient)?,
+                amount: number(amount)?,
+                executor_fee: number(executor_fee)?,
+                interval_slots: number(interval_slots)?,
+                first_payment_slot: number(first_slot)?,
+                payments: number(payments)?,
+            };
+            let schedule = instructions::payment_schedule_address(&authority, params.schedule_id);
+            let escrow = instructions::schedule_escrow_address(&schedule, &token_mint);
+            eprintln!("Fund the schedule by withdrawing to {escrow} with {schedule} as recipient");
+            vec![instructions::create_payment_schedule(authority, token_mint, &params)]
+        }
+        ("execute-payment", [schedule]) => {
+            let client = client.ok_or("execute-payment can't be signed offline")?;
+            let token_mint = client.program_state().map_err(|err| err.to_string())?.token_mint;
+            let schedule = pubkey(schedule)?;
+            let recipient = client.payment_schedule(schedule).map_err(|err| err.to_string())?.recipient;
+            vec![instructions::execute_scheduled_payment(
+                authority,
+                schedule,
+                token_mint,
+                get_associated_token_address(&recipient, &token_mint),
+                get_associated_token_address(&authority, &token_mint),
+            )]
+        }
+        ("cancel-schedule", [schedule_id]) => {
+            let client = client.ok_or("cancel-schedule can't be signed offline")?;
+            let token_mint = client.program_state().map_err(|err| err.to_string())?.token_mint;
+            let owner_token_account = get_associated_token_address(&authority, &token_mint);
+            vec![instructions::cancel_payment_schedule(authority, number(schedule_id)?, token_mint, owner_token_account)]
+        }
+        ("init-audit-log", []) => vec![instructions::init_audit_log(fee_payer)],
+        ("pause", []) => vec![instructions::pause(authority)],
+        ("unpause", []) => vec![instructions::unpause(authority)],
+        ("set-guardian", [guardian]) => vec![instructions::set_guardian(authority, pubkey(guardian)?)],
+        ("set-tree-operator", [operator]) => vec![instructions::set_tree_operator(authority, pubkey(operator)?)],
+        ("set-withdrawal-limit", [max_per_epoch, epoch_slots]) => {
+            vec![instructions::set_withdrawal_limit(authority, number(max_per_epoch)?, number(epoch_slots)?)]
+        }
+        ("set-compliance-key", ["none"]) => vec![instructions::set_compliance_key(authority, [0; 32])],
+        ("set-compliance-key", [key]) => vec![instructions::set_compliance_key(authority, bytes32(key)?)],
+        ("set-kyc-attestor", ["none"]) => vec![instructions::set_kyc_attestor(authority, Pubkey::default())],
+        ("set-kyc-attestor", [attestor]) => vec![instructions::set_kyc_attestor(authority, pubkey(attestor)?)],
+        ("issue-kyc", [holder, expiry @ ..]) if expiry.len() <= 1 => {
+            let expires_at = expiry.first().map(|time| number(time)).transpose()?.unwrap_or(0);
+            vec![instructions::issue_kyc_credential(authority, address(client, holder)?, expires_at)]
+        }
+        ("revoke-kyc", [holder]) => vec![instructions::revoke_kyc_credential(authority, address(client, holder)?)],
+        ("set-max-daily-deposit", [max]) => vec![instructions::set_max_daily_deposit(authority, number(max)?)],
+        ("set-exclusion-operator", ["none"]) => vec![instructions::set_exclusion_operator(authority, Pubkey::default())],
+        ("set-exclusion-operator", [operator]) => vec![instructions::set_exclusion_operator(authority, pubkey(operator)?)],
+        ("publish-exclusion-root", [root]) => vec![instructions::publish_exclusion_root(authority, bytes32(root)?)],
+        ("exclusion-proofs", [setting]) => match *setting {
+            "on" => vec![instructions::set_exclusion_required(authority, true)],
+            "off" => vec![instructions::set_exclusion_required(authority, false)],
+            _ => return Err(format!("expected on or off, got {setting}")),
+        },
+        ("set-max-pause", [slots]) => vec![instructions::set_max_pause_duration(authority, number(slots)?)],
+        ("pause-on-shortfall", [setting]) => match *setting {
+            "on" => vec![instructions::set_pause_on_shortfall(authority, true)],
+            "off" => vec![instructions::set_pause_on_shortfall(authority, false)],
+            _ => return Err(format!("expected on or off, got {setting}")),
+        },
+        ("reconcile", []) => vec![instructions::reconcile()],
+        ("checkpoint-root", []) => vec![instructions::checkpoint_root(authority, fee_payer)],
+        ("archive-roots", []) => {
+            // The page follows from how far the archive has got
+            let client = client.ok_or("archive-roots can't be signed offline")?;
+            let archived_roots = client.merkle_tree().map_err(|err| err.to_string())?.archived_roots;
+            vec![instructions::archive_roots(authority, fee_payer, archived_roots / ROOT_ARCHIVE_PAGE_ROOTS as u64)]
+        }
+        ("accept-archived-roots", []) => vec![instructions::set_archived_roots_accepted(authority, true)],
+        ("refuse-archived-roots", []) => vec![instructions::set_archived_roots_accepted(authority, false)],
+        ("transfer-ownership", [admin]) => vec![instructions::transfer_ownership(authority, pubkey(admin)?)],
+        ("update-fee-config", [fee_bps, max_fee]) => {
+            vec![instructions::update_fee_config(authority, number(fee_bps)?, number(max_fee)?)]
+        }
+        ("enable-verifying-key", [version]) => vec![instructions::set_verifying_key_enabled(authority, number(version)?, true)],
+        ("disable-verifying-key", [version]) => vec![instructions::set_verifying_key_enabled(authority, number(version)?, false)],
+        ("set-admin-timelock", [slots]) => vec![instructions::set_admin_timelock(authority, number(slots)?)],
+        ("queue", [action, args @ ..]) => {
+            // The action's account is addressed by the next id, so needs the pool's state
+            let client = client.ok_or("queue can't be signed offline")?;
+            let id = client.program_state().map_err(|err| err.to_string())?.next_admin_action_id;
+            let action = match (*action, args) {
+                ("transfer-ownership", [arg]) => AdminAction::TransferOwnership { new_admin: pubkey(arg)? },
+                ("set-guardian", [arg]) => AdminAction::SetGuardian { new_guardian: pubkey(arg)? },
+                ("enable-verifying-key", [arg]) => AdminAction::EnableVerifyingKey { circuit_version: number(arg)? },
+                ("set-admin-timelock", [arg]) => AdminAction::SetAdminTimelock { slots: number(arg)? },
+                ("add-swap-target", [arg]) => AdminAction::AddSwapTarget { target_program: pubkey(arg)? },
+                ("set-withdrawal-limit", [max_per_epoch, epoch_slots]) => AdminAction::SetWithdrawalLimit {
+                    max_withdrawal_per_epoch: number(max_per_epoch)?,
+                    withdrawal_epoch_slots: number(epoch_slots)?,
+                },
+                ("set-compliance-key", ["none"]) => AdminAction::SetComplianceKey { compliance_key: [0; 32] },
+                ("set-compliance-key", [key]) => AdminAction::SetComplianceKey { compliance_key: bytes32(key)? },
+                ("set-kyc-attestor", ["none"]) => AdminAction::SetKycAttestor { kyc_attestor: Pubkey::default() },
+                ("set-kyc-attestor", [attestor]) => AdminAction::SetKycAttestor { kyc_attestor: pubkey(attestor)? },
+                ("exclusion-proofs", ["on"]) => AdminAction::SetExclusionRequired { exclusion_required: true },
+                ("exclusion-proofs", ["off"]) => AdminAction::SetExclusionRequired { exclusion_required: false },
+                _ => return Err(format!("can't queue `{action}` with these arguments\n\n{USAGE}")),
+            };
+            eprintln!("queueing admin action {id}");
+            vec![instructions::queue_admin_action(authority, fee_payer, id, action)]
+        }
+        ("execute-admin-action", [id]) => {
+            let client = client.ok_or("execute-admin-action can't be signed offline")?;
+            let queued = client.queued_admin_action(number(id)?).map_err(|err| err.to_string())?;
+            vec![instructions::execute_admin_action(authority, queued.payer, queued.id, &queued.action)]
+        }
+        ("cancel-admin-action", [id]) => {
+            let client = client.ok_or("cancel-admin-action can't be signed offline")?;
+            let queued = client.queued_admin_action(number(id)?).map_err(|err| err.to_string())?;
+            vec![instructions::cancel_admin_action(authority, queued.payer, queued.id)]
+        }
+        ("add-relayer", [relayer, endpoint_url, fee_bps, min_fee, expiry @ ..]) if expiry.len() <= 1 => {
+            let fee_schedule = FeeSchedule { fee_bps: number(fee_bps)?, min_fee: number(min_fee)? };
+            let expiry_slot = expiry.first().map(|slot| number(slot)).transpose()?;
+            let endpoint_url_hash = hash(endpoint_url.as_bytes()).to_bytes();
+            vec![instructions::add_relayer(authority, fee_payer, address(client, relayer)?, endpoint_url_hash, fee_schedule, expiry_slot)]
+        }
+        ("renew-relayer", [relayer, expiry @ ..]) if expiry.len() <= 1 => {
+            let expiry_slot = expiry.first().map(|slot| number(slot)).transpose()?;
+            vec![instructions::renew_relayer(authority, address(client, relayer)?, expiry_slot)]
+        }
+        ("remove-relayer", [relayer, receiver @ ..]) if receiver.len() <= 1 => {
+            let rent_receiver = receiver.first().map(|receiver| pubkey(receiver)).transpose()?.unwrap_or(fee_payer);
+            vec![instructions::remove_relayer(authority, address(client, relayer)?, rent_receiver)]
+        }
+        ("add-swap-target", [program]) => vec![instructions::add_swap_target(authority, pubkey(program)?)],
+        ("remove-swap-target", [program]) => vec![instructions::remove_swap_target(authority, pubkey(program)?)],
+        ("set-usd-limits", [price_feed, max_price_age, max_confidence_bps, max_deposit_usd, large_withdrawal_usd, flat_fee_usd]) => {
+            // Records the pool mint's decimals, so needs the mint from the chain
+            let client = client.ok_or("set-usd-limits can't be signed offline")?;
+            let token_mint = client.program_state().map_err(|err| err.to_string())?.token_mint;
+            let params = UsdLimitsParams {
+                max_price_age: number(max_price_age)?,
+                max_confidence_bps: number(max_confidence_bps)?,
+                max_deposit_usd: number(max_deposit_usd)?,
+                large_withdrawal_usd: number(large_withdrawal_usd)?,
+                flat_fee_usd: number(flat_fee_usd)?,
+            };
+            vec![instructions::set_usd_limits(authority, fee_payer, token_mint, pubkey(price_feed)?, &params)]
+        }
+        _ => return Err(format!("unknown command or wrong arguments: {command} {}\n\n{USAGE}", params.join(" "))),
+    })
+}
+
+fn sign_only(options: &Options, signer: &signer::CliSigner, instructions: &[Instruction], fee_payer: Pubkey) -> Result<(), String> {
+    let output = options.output.as_deref().ok_or("--sign-only needs --output")?;
+    let blockhash = options.blockhash.ok_or("--sign-only needs --blockhash (the nonce value with --nonce)")?;
+    let mut transaction = offline::build(instructions, fee_payer, blockhash, options.nonce.map(|nonce| (nonce, signer.pubkey())));
+
+    eprintln!("Signing offline as {}:", signer.pubkey());
+    print_summary(&offline::instructions(&transaction));
+    offline::sign(&mut transaction, signer)?;
+    offline::write(output, &transaction)?;
+    report_missing(&transaction, output);
+    Ok(())
+}
+
+fn cosign(signer: &signer::CliSigner, path: &str) -> Result<(), String> {
+    let mut transaction = offline::read(path)?;
+    eprintln!("Signing {path} as {}:", signer.pubkey());
+    print_summary(&offline::instructions(&transaction));
+    if !offline::sign(&mut transaction, signer)? {
+        return Err(format!("{} is not a signer of {path}", signer.pubkey()));
+    }
+    offline::write(path, &transaction)?;
+    report_missing(&transaction, path);
+    Ok(())
+}
+
+fn submit(options: &Options, path: &str) -> Result<(), String> {
+    let mut transaction = offline::read(path)?;
+    eprintln!("Submitting {path}:");
+    print_summary(&offline::instructions(&transaction));
+    // The local signer is only needed if the transaction still lacks its signature (typically
+    // the fee payer's)
+    let keypair_needed = !offline::missing_signers(&transaction).is_empty();
+    let payer = match (&options.keypair, keypair_needed) {
+        (Some(uri), _) => Some(signer::parse(uri)?),
+        (None, true) => Some(signer::parse(DEFAULT_KEYPAIR)?),
+        (None, false) => None,
+    };
+    if let Some(payer) = &payer {
+        offline::sign(&mut transaction, payer)?;
+    }
+    let missing = offline::missing_signers(&transaction);
+    if !missing.is_empty() {
+        let missing: Vec<String> = missing.iter().map(Pubkey::to_string).collect();
+        return Err(format!("{path} still needs signatures from {}", missing.join(", ")));
+    }
+    // The client's own payer never signs a transaction that is already complete
+    let client = match payer {
+        Some(payer) => client(options, payer)?,
+        None => client(options, Keypair::new())?,
+    };
+    let signature = client.send_transaction(&transaction).map_err(|err| err.to_string())?;
+    println!("{signature}");
+    Ok(())
+}
+
+// Note blobs are produced encrypted by wallets for the recipient and are passed through as-is.
+fn show_note(path: &str, qr: bool) -> Result<(), String> {
+    let blob = std::fs::read_to_string(path).map_err(|err| format!("failed to read {path}: {err}"))?;
+    print_with_qr(blob.trim(), qr)
+}
+
+fn print_with_qr(text: &str, qr: bool) -> Result<(), String> {
+    if qr {
+        let code = qr::QrCode::encode(text.as_bytes()).map_err(|err| err.to_string())?;
+        print!("{}", code.render());
+    }
+    println!("{text}");
+    Ok(())
+}
+
+fn print_summary(instructions: &[Instruction]) {
+    for instruction in instructions {
+        eprintln!("  - {}", summary::describe(instruction));
+    }
+}
+
+fn report_missing(transaction: &solana_sdk::transaction::Transaction, path: &str) {
+    let missing = offline::missing_signers(transaction);
+    if missing.is_empty() {
+        eprintln!("Wrote {path}; fully signed, ready for --submit");
+    } else {
+        let missing: Vec<String> = missing.iter().map(Pubkey::to_string).collect();
+        eprintln!("Wrote {path}; still needs signatures from {}", missing.join(", "));
+    }
+}
+
+// Over the cluster profile's endpoints, or only --url if given. The profile also comes from
+// PRIVAX_CONFIG and PRIVAX_* variables; see privax-config.
+fn client(options: &Options, payer: impl Signer + Send + Sync + 'static) -> Result<PrivaxClient, String> {
+    PrivaxClient::from_profile(&profile(options)?, payer).map_err(|err| err.to_string())
+}
+
+fn profile(options: &Options) -> Result<config::Profile, String> {
+    let mut profile = config::load_profile(options.cluster).map_err(|err| err.to_string())?;
+    if let Some(url) = &options.url {
+        profile.rpc_urls = vec![url.clone()];
+    }
+    Ok(profile)
+}
+
+fn pubkey(arg: &str) -> Result<Pubkey, String> {
+    arg.parse().map_err(|_| format!("invalid public key `{arg}`"))
+}
+
+// A public key, or a .sol domain resolved through `client`, so not while signing offline. The
+// resolved address is printed for the signer to check.
+fn address(client: Option<&PrivaxClient>, arg: &str) -> Result<Pubkey, String> {
+    if !sns::is_domain(arg) {
+        return pubkey(arg);
+    }
+    let client = client.ok_or_else(|| format!("{arg} can't be resolved offline; pass its public key"))?;
+    let resolved = client.resolve_domain(arg).map_err(|err| err.to_string())?;
+    eprintln!("{arg} resolves to {resolved}");
+    Ok(resolved)
+}
+
+fn number<T: std::str::FromStr>(arg: &str) -> Result<T, String> {
+    arg.parse().map_err(|_| format!("invalid number `{arg}`"))
+}
+
+fn bytes32(arg: &str) -> Result<[u8; 32], String> {
+    let invalid = || format!("expected 32 bytes of hex, got `{arg}`");
+    let hex = arg.strip_prefix("0x").unwrap_or(arg);
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let mut bytes = [0u8; 32];
+    for (byte, pair) in bytes.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).map_err(|_| invalid())?, 16).map_err(|_| invalid())?;
+    }
+    Ok(bytes)
+}
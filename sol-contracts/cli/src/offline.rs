@@ -0,0 +1,98 @@
+// Transaction files for offline signing: an air-gapped machine signs with `--sign-only` and
+// writes the partially-signed transaction, further signers add theirs with `cosign`, and an
+// online machine sends it with `--submit`. Each file is the base64 of the bincode-serialized
+// transaction.
+use std::fs;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+
+// A recent blockhash only lasts about a minute, too little to carry a file off an air-gapped
+// machine; with a durable nonce account the transaction stays valid until the nonce advances.
+pub fn build(instructions: &[Instruction], fee_payer: Pubkey, blockhash: Hash, nonce: Option<(Pubkey, Pubkey)>) -> Transaction {
+    let message = match nonce {
+        Some((nonce_account, nonce_authority)) => {
+            Message::new_with_nonce(instructions.to_vec(), Some(&fee_payer), &nonce_account, &nonce_authority)
+        }
+        None => Message::new(instructions, Some(&fee_payer)),
+    };
+    let mut transaction = Transaction::new_unsigned(message);
+    transaction.message.recent_blockhash = blockhash;
+    transaction
+}
+
+// Adds `signer`'s signature if the transaction needs it. Returns whether it did.
+pub fn sign(transaction: &mut Transaction, signer: &dyn Signer) -> Result<bool, String> {
+    let pubkey = signer.pubkey();
+    let Some(position) = transaction.get_signing_keypair_positions(&[pubkey]).map_err(|err| err.to_string())?[0] else {
+        return Ok(false);
+    };
+    let signature = signer.try_sign_message(&transaction.message_data()).map_err(|err| err.to_string())?;
+    transaction.signatures[position] = signature;
+    Ok(true)
+}
+
+// Signers that still have to sign.
+pub fn missing_signers(transaction: &Transaction) -> Vec<Pubkey> {
+    let required = usize::from(transaction.message.header.num_required_signatures);
+    transaction.message.account_keys[..required]
+        .iter()
+        .zip(&transaction.signatures)
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(key, _)| *key)
+        .collect()
+}
+
+pub fn write(path: &str, transaction: &Transaction) -> Result<(), String> {
+    let bytes = bincode::serialize(transaction).map_err(|err| err.to_string())?;
+    fs::write(path, STANDARD.encode(bytes) + "\n").map_err(|err| format!("failed to write {path}: {err}"))
+}
+
+pub fn read(path: &str) -> Result<Transaction, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("failed to read {path}: {err}"))?;
+    let bytes = STANDARD.decode(text.trim()).map_err(|err| format!("{path} is not a transaction file: {err}"))?;
+    let transaction: Transaction = bincode::deserialize(&bytes).map_err(|err| format!("{path} is not a transaction file: {err}"))?;
+    // Signatures present must be valid ones over this message
+    let message = transaction.message_data();
+    let keys = &transaction.message.account_keys;
+    for (signature, key) in transaction.signatures.iter().zip(keys) {
+        if *signature != Signature::default() && !signature.verify(key.as_ref(), &message) {
+            return Err(format!("{path} carries an invalid signature for {key}"));
+        }
+    }
+    Ok(transaction)
+}
+
+// Instructions of a compiled transaction, for display.
+pub fn instructions(transaction: &Transaction) -> Vec<Instruction> {
+    let message = &transaction.message;
+    message
+        .instructions
+        .iter()
+        .map(|compiled| Instruction {
+            program_id: message.account_keys[usize::from(compiled.program_id_index)],
+            accounts: compiled
+                .accounts
+                .iter()
+                .map(|&index| {
+                    let index = usize::from(index);
+                    let pubkey = message.account_keys[index];
+                    AccountMeta {
+                        pubkey,
+                        is_signer: message.is_signer(index),
+                        is_writable: message.is_writable(index),
+                    }
+                })
+                .collect(),
+            data: compiled.data.clone(),
+        })
+        .collect()
+}
+
@@ -0,0 +1,323 @@
+// `payroll`: pays a CSV of recipients from shielded notes, a withdraw_split per batch, through
+// a relayer and a proving service (circuits/prover.js running the split circuit). The job
+// file (privax_client::payroll) is saved after every step, so `payroll run` can be stopped
+// and run again at any point.
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use privax_client::blocking::PrivaxClient;
+use privax_client::payroll::{self, BatchNote, BatchStep, PayrollBatch, PayrollJob};
+use privax_mobile::note::Note;
+use privax_mobile::prove::{public_input, split_inputs, SplitPayout};
+use privax_mobile::scan::merkle_path;
+use privax_protocol::SPLIT_PUBLIC_INPUTS;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use tracing::{debug, info, warn};
+
+use crate::summary::hex;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+// How long a sent deposit may take to show up at the relayer before the run gives up on it
+const INDEXING_TIMEOUT: Duration = Duration::from_secs(300);
+
+pub struct Services {
+    pub relayer: String,
+    pub prover: String,
+    pub api_key: Option<String>, // Sent as X-API-Key, for the relayer's higher rate limit
+    pub circuit_version: u16,    // Of the split circuit the prover runs
+}
+
+pub fn plan(client: &PrivaxClient, csv_path: &str, job_path: &str) -> Result<(), String> {
+    // An existing job file may hold unspent notes
+    if std::path::Path::new(job_path).exists() {
+        return Err(format!("{job_path} already exists; plan a new payroll into another file"));
+    }
+    let csv = std::fs::read_to_string(csv_path).map_err(|err| format!("failed to read {csv_path}: {err}"))?;
+    // Recipients may be .sol domains; each is checked against its reverse record
+    let entries = payroll::parse_csv_with(&csv, |recipient| client.resolve_address(recipient).map_err(|err| err.to_string()))
+        .map_err(|err| format!("{csv_path}: {err}"))?;
+    let (mint, _) = client.pool_mint().map_err(|err| err.to_string())?;
+    let job = PayrollJob::plan(mint, entries).map_err(|err| err.to_string())?;
+    if let Some(index) = job.batches.iter().position(|batch| batch.payroll_total().is_none()) {
+        return Err(format!("batch {} pays more than a note can hold", index + 1));
+    }
+    job.save(job_path).map_err(|err| err.to_string())?;
+    let recipients: usize = job.batches.iter().map(|batch| batch.entries.len()).sum();
+    info!(recipients, batches = job.batches.len(), "planned payroll; run `payroll run {job_path} <token-account>`");
+    Ok(())
+}
+
+pub fn status(job_path: &str) -> Result<(), String> {
+    let job = PayrollJob::load(job_path).map_err(|err| err.to_string())?;
+    for (index, batch) in job.batches.iter().enumerate() {
+        let total = batch.payroll_total().unwrap_or(u64::MAX);
+        let detail = match batch.next_step() {
+            BatchStep::Done => batch.signature.clone(),
+            BatchStep::Failed => batch.error.clone(),
+            BatchStep::AwaitRelayer => batch.job_id.as_ref().map(|job_id| format!("job {job_id}")),
+            _ => None,
+        };
+        let detail = detail.map(|detail| format!(": {detail}")).unwrap_or_default();
+        println!("batch {}: {} payments, {total} total, {}{detail}", index + 1, batch.entries.len(), batch.next_step());
+    }
+    Ok(())
+}
+
+// Takes every batch as far as it goes; funds deposits from `token_account`. Failed batches are
+// skipped (see `payroll status`), and the run returns once the rest are paid.
+pub fn run(client: &PrivaxClient, services: &Services, job_path: &str, token_account: Pubkey) -> Result<(), String> {
+    let mut job = PayrollJob::load(job_path).map_err(|err| err.to_string())?;
+    let (mint, _) = client.pool_mint().map_err(|err| err.to_string())?;
+    if job.mint != mint {
+        return Err(format!("{job_path} pays from the pool for {}, but this pool holds {mint}", job.mint));
+    }
+    let http = Http::new(services);
+    let mut leaves = Vec::new();
+    for index in 0..job.batches.len() {
+        loop {
+            let batch = &mut job.batches[index];
+            let step = batch.next_step();
+            match step {
+                BatchStep::CreateNote => create_note(&http, mint, batch)?,
+                BatchStep::Deposit => {
+                    // Recorded before sending: a deposit that may have landed is never sent again
+                    batch.deposit_sent = true;
+                    job.save(job_path).map_err(|err| err.to_string())?;
+                    let batch = &mut job.batches[index];
+                    let note = batch.note.as_ref().expect("a note before the deposit");
+                    let amount = Note::parse(note.note.clone()).map_err(|err| err.to_string())?.amount();
+                    let signature = client.deposit(token_account, amount, crate::bytes32(&note.commitment)?);
+                    batch.deposit_signature = Some(signature.map_err(|err| err.to_string())?.to_string());
+                }
+                BatchStep::Submit => submit(client, &http, services, mint, batch, &mut leaves)?,
+                BatchStep::AwaitRelayer => await_relayer(&http, batch)?,
+                BatchStep::Failed | BatchStep::Done => break,
+            }
+            job.save(job_path).map_err(|err| err.to_string())?;
+            info!(batch = index + 1, step = %job.batches[index].next_step(), "payroll batch advanced");
+        }
+    }
+    let failed = job.batches.iter().filter(|batch| batch.next_step() == BatchStep::Failed).count();
+    match failed {
+        0 => Ok(()),
+        _ => Err(format!("{failed} batches failed; see `payroll status {job_path}`")),
+    }
+}
+
+// A note for the batch plus the relayer's fee on that total; the fee grows with the amount,
+// so the quote is repeated until the fee covers itself.
+fn create_note(http: &Http, mint: Pubkey, batch: &mut PayrollBatch) -> Result<(), String> {
+    let total = batch.payroll_total().ok_or("the batch pays more than a note can hold")?;
+    let mut fee = 0;
+    let quote = loop {
+        let amount = total.checked_add(fee).ok_or("the batch and its relayer fee overflow a note")?;
+        let quote = http.quote(mint, amount)?;
+        if number(&quote["fee"])? <= fee {
+            break quote;
+        }
+        fee = number(&quote["fee"])?;
+    };
+    let relayer = text(&quote["relayer"])?.parse().map_err(|_| "the relayer quoted with an invalid address")?;
+    let note = Note::generate(total + fee);
+    batch.note = Some(BatchNote { note: note.encode(), commitment: hex(&note.commitment()), relayer, relayer_fee: fee });
+    Ok(())
+}
+
+fn submit(
+    client: &PrivaxClient,
+    http: &Http,
+    services: &Services,
+    mint: Pubkey,
+    batch: &mut PayrollBatch,
+    leaves: &mut Vec<Vec<u8>>,
+) -> Result<(), String> {
+    let batch_note = batch.note.clone().expect("a note before the split");
+    let note = Note::parse(batch_note.note.clone()).map_err(|err| err.to_string())?;
+    let commitment = note.commitment();
+
+    // The deposit must be indexed, and covered by the on-chain root the proof is checked against
+    let started = Instant::now();
+    let (leaf_index, root) = loop {
+        http.extend_leaves(leaves)?;
+        if let Some(leaf_index) = leaves.iter().position(|leaf| *leaf == commitment) {
+            let latest = http.get("/roots/latest")?;
+            let onchain = &latest["onchain"];
+            if !onchain.is_null() && number(&onchain["next_index"])? > leaf_index as u64 {
+                break (leaf_index, crate::bytes32(text(&onchain["root"])?)?);
+            }
+        }
+        if started.elapsed() > INDEXING_TIMEOUT {
+            return Err(format!(
+                "the relayer hasn't indexed deposit {}; if the deposit never landed, set its deposit_sent to false in the job \
+                 file and run again",
+                batch_note.commitment
+            ));
+        }
+        sleep(POLL_INTERVAL);
+    };
+    let path = merkle_path(leaves.clone(), leaf_index as u64).map_err(|err| err.to_string())?;
+
+    let payouts = batch.payouts().expect("payouts once the batch has a note");
+    let split_payouts = payouts.iter().map(|(recipient, amount)| SplitPayout { recipient: recipient.to_string(), amount: *amount });
+    let proof_slot = client.slot().map_err(|err| err.to_string())?;
+    let input = split_inputs(
+        note.clone(),
+        path,
+        split_payouts.collect(),
+        mint.to_string(),
+        services.circuit_version,
+        proof_slot,
+        Some(batch_note.relayer.to_string()),
+    )
+    .map_err(|err| err.to_string())?;
+    let proof = http.prove(&input)?;
+
+    // The relayer only accepts a fee quoted for the note's total; the one paid was fixed when
+    // the note was created, so a relayer that now asks for more can't be paid from this note.
+    let quote = http.quote(mint, note.amount())?;
+    if text(&quote["relayer"])? != batch_note.relayer.to_string() || number(&quote["fee"])? > batch_note.relayer_fee {
+        return Err(format!(
+            "the relayer now quotes {} for the batch, more than the {} it was funded with",
+            quote["fee"], batch_note.relayer_fee
+        ));
+    }
+
+    // As the program expects them; the root is the on-chain tree's (see localnet's README). The
+    // relayer submits the proof, and its fee is what the payouts pay it.
+    let relayer_fee = payouts.iter().filter(|(recipient, _)| *recipient == batch_note.relayer).map(|(_, amount)| amount).sum();
+    let mut public_inputs = vec![
+        public_input(&root),
+        public_input(&note.nullifier_hash()),
+        note.amount(),
+        privax_protocol::split_external_nullifier(&mint, services.circuit_version),
+        proof_slot,
+        relayer_fee,
+        public_input(batch_note.relayer.as_ref()),
+    ];
+    for (recipient, amount) in &payouts {
+        public_inputs.extend([public_input(recipient.as_ref()), *amount]);
+    }
+    public_inputs.resize(SPLIT_PUBLIC_INPUTS, 0);
+
+    let payouts: Vec<Value> =
+        payouts.iter().map(|(recipient, amount)| json!({"recipient": recipient.to_string(), "amount": amount})).collect();
+    let request = json!({
+        "nullifier_hash": hex(&note.nullifier_hash()),
+        "commitment": batch_note.commitment,
+        "token": mint.to_string(),
+        "payouts": payouts,
+        "proof": {
+            "a": proof["a"],
+            "b": proof["b"],
+            "c": proof["c"],
+            "public_inputs": public_inputs,
+            "circuit_version": services.circuit_version,
+        },
+        "quote": quote,
+    });
+    let accepted = http.post(&format!("{}/withdraw_split", http.relayer), &request)?;
+    batch.job_id = Some(text(&accepted["job_id"])?.to_string());
+    Ok(())
+}
+
+fn await_relayer(http: &Http, batch: &mut PayrollBatch) -> Result<(), String> {
+    let job_id = batch.job_id.clone().expect("a job once submitted");
+    let job = http.get(&format!("/jobs/{job_id}"))?;
+    match (text(&job["status"])?, job["signature"].as_str()) {
+        ("confirmed" | "finalized", Some(signature)) => batch.signature = Some(signature.to_string()),
+        ("failed", _) => batch.error = Some(job["error"].as_str().unwrap_or("failed without a reason").to_string()),
+        _ => sleep(POLL_INTERVAL),
+    }
+    Ok(())
+}
+
+struct Http {
+    client: Client,
+    relayer: String,
+    prover: String,
+    api_key: Option<String>,
+}
+
+impl Http {
+    fn new(services: &Services) -> Self {
+        Self {
+            client: Client::new(),
+            relayer: services.relayer.trim_end_matches('/').to_string(),
+            prover: services.prover.trim_end_matches('/').to_string(),
+            api_key: services.api_key.clone(),
+        }
+    }
+
+    fn get(&self, path: &str) -> Result<Value, String> {
+        self.send(|| self.client.get(format!("{}{path}", self.relayer)))
+    }
+
+    fn post(&self, url: &str, body: &Value) -> Result<Value, String> {
+        self.send(|| self.client.post(url).json(body))
+    }
+
+    fn quote(&self, mint: Pubkey, amount: u64) -> Result<Value, String> {
+        self.get(&format!("/quote?mint={mint}&amount={amount}"))
+    }
+
+    // The proof's a, b and c, hex in the on-chain layout.
+    fn prove(&self, input: &str) -> Result<Value, String> {
+        let input: Value = serde_json::from_str(input).map_err(|err| err.to_string())?;
+        self.post(&format!("{}/prove", self.prover), &json!({ "input": input }))
+    }
+
+    // Appends the commitments indexed since the last call.
+    fn extend_leaves(&self, leaves: &mut Vec<Vec<u8>>) -> Result<(), String> {
+        loop {
+            let page = self.get(&format!("/commitments?from_leaf={}&limit=1000", leaves.len()))?;
+            for entry in page["commitments"].as_array().ok_or("the relayer sent no commitments")? {
+                leaves.push(crate::bytes32(text(&entry["commitment"])?)?.to_vec());
+            }
+            if !page["has_more"].as_bool().unwrap_or(false) {
+                return Ok(());
+            }
+        }
+    }
+
+    // Waits out 429s for as long as Retry-After asks.
+    fn send(&self, request: impl Fn() -> RequestBuilder) -> Result<Value, String> {
+        loop {
+            let mut builder = request();
+            if let Some(api_key) = &self.api_key {
+                builder = builder.header("X-API-Key", api_key);
+            }
+            let response = builder.send().map_err(|err| err.to_string())?;
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                let delay = retry_after(&response).unwrap_or(POLL_INTERVAL);
+                warn!(url = %response.url(), ?delay, "rate limited, waiting");
+                sleep(delay);
+                continue;
+            }
+            let status = response.status();
+            let url = response.url().clone();
+            debug!(%url, %status, "service answered");
+            let body: Value = response.json().unwrap_or(Value::Null);
+            if !status.is_success() {
+                let detail = body.get("detail").or(body.get("error")).unwrap_or(&body);
+                return Err(format!("{url} answered {status}: {detail}"));
+            }
+            return Ok(body);
+        }
+    }
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds = response.headers().get("Retry-After")?.to_str().ok()?.parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+fn text(value: &Value) -> Result<&str, String> {
+    value.as_str().ok_or_else(|| format!("expected a string, got {value}"))
+}
+
+fn number(value: &Value) -> Result<u64, String> {
+    value.as_u64().ok_or_else(|| format!("expected a number, got {value}"))
+}
@@ -0,0 +1,427 @@
+// Minimal QR code encoder (byte mode, versions 1-40) and terminal renderer, for moving payment
+// requests and note blobs to a phone by camera. Follows ISO/IEC 18004: the smallest version
+// that fits at error correction level M (falling back to L for long payloads), with the mask
+// chosen by the standard penalty score.
+
+#[derive(Clone, Copy)]
+enum Ecc {
+    Low,
+    Medium,
+}
+
+impl Ecc {
+    fn ordinal(self) -> usize {
+        match self {
+            Ecc::Low => 0,
+            Ecc::Medium => 1,
+        }
+    }
+
+    fn format_bits(self) -> u32 {
+        match self {
+            Ecc::Low => 1,
+            Ecc::Medium => 0,
+        }
+    }
+}
+
+// Indexed by [ecc ordinal][version]; index 0 is unused.
+const ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 2] = [
+    [
+        0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30, 30, 26, 28, 30, 30, 30,
+        30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28,
+        28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+    ],
+];
+const NUM_ERROR_CORRECTION_BLOCKS: [[u8; 41]; 2] = [
+    [
+        0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14, 15, 16, 17, 18, 19,
+        19, 20, 21, 22, 24, 25,
+    ],
+    [
+        0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33,
+        35, 37, 38, 40, 43, 45, 47, 49,
+    ],
+];
+
+pub struct QrCode {
+    size: usize,
+    modules: Vec<Vec<bool>>, // [y][x], true = dark
+    is_function: Vec<Vec<bool>>,
+}
+
+#[derive(Debug)]
+pub struct DataTooLong(pub usize);
+
+impl std::fmt::Display for DataTooLong {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} bytes is too long for a QR code", self.0)
+    }
+}
+
+impl QrCode {
+    pub fn encode(data: &[u8]) -> Result<Self, DataTooLong> {
+        for ecc in [Ecc::Medium, Ecc::Low] {
+            for version in 1..=40 {
+                let count_bits = if version <= 9 { 8 } else { 16 };
+                let capacity_bits = num_data_codewords(version, ecc) * 8;
+                if data.len() < (1 << count_bits) && 4 + count_bits + data.len() * 8 <= capacity_bits {
+                    return Ok(Self::build(version, ecc, data, count_bits, capacity_bits));
+                }
+            }
+        }
+        Err(DataTooLong(data.len()))
+    }
+
+    fn build(version: usize, ecc: Ecc, data: &[u8], count_bits: usize, capacity_bits: usize) -> Self {
+        let mut bits = BitBuffer::default();
+        bits.append(0b0100, 4); // Byte mode
+        bits.append(data.len() as u32, count_bits);
+        for &byte in data {
+            bits.append(u32::from(byte), 8);
+        }
+        bits.append(0, (capacity_bits - bits.0.len()).min(4)); // Terminator
+        bits.append(0, (8 - (bits.0.len() & 7)) & 7);
+        for pad in [0xEC, 0x11].into_iter().cycle() {
+            if bits.0.len() >= capacity_bits {
+                break;
+            }
+            bits.append(pad, 8);
+        }
+        let codewords: Vec<u8> =
+            bits.0.chunks_exact(8).map(|byte| byte.iter().fold(0u8, |acc, &bit| (acc << 1) | u8::from(bit))).collect();
+
+        let size = version * 4 + 17;
+        let mut qr = Self { size, modules: vec![vec![false; size]; size], is_function: vec![vec![false; size]; size] };
+        qr.draw_function_patterns(version, ecc);
+        qr.draw_codewords(&add_ecc_and_interleave(&codewords, version, ecc));
+
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                qr.apply_mask(mask);
+                qr.draw_format_bits(ecc, mask);
+                let penalty = qr.penalty_score();
+                qr.apply_mask(mask); // XOR undoes it
+                penalty
+            })
+            .unwrap_or(0);
+        qr.apply_mask(mask);
+        qr.draw_format_bits(ecc, mask);
+        qr
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y][x] = dark;
+        self.is_function[y][x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize, ecc: Ecc) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i & 1 == 0);
+            self.set_function(i, 6, i & 1 == 0);
+        }
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            self.draw_finder(x, y);
+        }
+        let positions = alignment_positions(version, size);
+        let last = positions.len().saturating_sub(1);
+        for (i, &y) in positions.iter().enumerate() {
+            for (j, &x) in positions.iter().enumerate() {
+                // The three corners with finder patterns
+                if ![(0, 0), (0, last), (last, 0)].contains(&(i, j)) {
+                    self.draw_alignment(x, y);
+                }
+            }
+        }
+        self.draw_format_bits(ecc, 0); // Placeholder, reserving the area
+        self.draw_version(version);
+    }
+
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let (xx, yy) = (x as i32 + dx, y as i32 + dy);
+                if (0..self.size as i32).contains(&xx) && (0..self.size as i32).contains(&yy) {
+                    let distance = dx.abs().max(dy.abs());
+                    self.set_function(xx as usize, yy as usize, distance != 2 && distance != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_alignment(&mut self, x: usize, y: usize) {
+        for dy in -2i32..=2 {
+            for dx in -2i32..=2 {
+                self.set_function((x as i32 + dx) as usize, (y as i32 + dy) as usize, dx.abs().max(dy.abs()) != 1);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, ecc: Ecc, mask: u32) {
+        let data = (ecc.format_bits() << 3) | mask;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = ((data << 10) | rem) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+        let size = self.size;
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true); // Always dark
+    }
+
+    fn draw_version(&mut self, version: usize) {
+        if version < 7 {
+            return;
+        }
+        let mut rem = version as u32;
+        for _ in 0..12 {
+            rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+        }
+        let bits = ((version as u32) << 12) | rem;
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 != 0;
+            let (a, b) = (self.size - 11 + i % 3, i / 3);
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    // Zigzags up and down two-module columns from the right, skipping function modules.
+    fn draw_codewords(&mut self, data: &[u8]) {
+        let mut i = 0;
+        let mut right = self.size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5; // Skip the vertical timing pattern
+            }
+            for vertical in 0..self.size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { self.size - 1 - vertical } else { vertical };
+                    if !self.is_function[y][x] && i < data.len() * 8 {
+                        self.modules[y][x] = (data[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y).is_multiple_of(2),
+                    1 => y.is_multiple_of(2),
+                    2 => x.is_multiple_of(3),
+                    3 => (x + y).is_multiple_of(3),
+                    4 => (x / 3 + y / 2).is_multiple_of(2),
+                    5 => (x * y % 2 + x * y % 3) == 0,
+                    6 => (x * y % 2 + x * y % 3).is_multiple_of(2),
+                    _ => ((x + y) % 2 + x * y % 3).is_multiple_of(2),
+                };
+                if invert && !self.is_function[y][x] {
+                    self.modules[y][x] ^= true;
+                }
+            }
+        }
+    }
+
+    fn penalty_score(&self) -> usize {
+        let size = self.size;
+        let row = |y: usize| -> Vec<bool> { self.modules[y].clone() };
+        let column = |x: usize| -> Vec<bool> { (0..size).map(|y| self.modules[y][x]).collect() };
+        let mut penalty = 0;
+        for line in (0..size).map(row).chain((0..size).map(column)) {
+            // Runs of five or more same-colored modules
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                } else {
+                    if run >= 5 {
+                        penalty += run - 2;
+                    }
+                    run = 1;
+                }
+            }
+            // Finder-like 1:1:3:1:1 patterns with four light modules on one side
+            const FINDER: [bool; 7] = [true, false, true, true, true, false, true];
+            for start in 0..=size.saturating_sub(7) {
+                if line[start..start + 7] == FINDER {
+                    let light_before = start >= 4 && line[start - 4..start].iter().all(|&dark| !dark);
+                    let light_after = start + 11 <= size && line[start + 7..start + 11].iter().all(|&dark| !dark);
+                    if light_before || light_after {
+                        penalty += 40;
+                    }
+                }
+            }
+        }
+        // 2x2 blocks of one color
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = self.modules[y][x];
+                if color == self.modules[y][x + 1] && color == self.modules[y + 1][x] && color == self.modules[y + 1][x + 1] {
+                    penalty += 3;
+                }
+            }
+        }
+        // Balance of dark and light modules
+        let dark: usize = self.modules.iter().map(|row| row.iter().filter(|&&dark| dark).count()).sum();
+        let total = size * size;
+        let deviation = (dark * 20).abs_diff(total * 10);
+        penalty + deviation.div_ceil(total).saturating_sub(1) * 10
+    }
+
+    // Two modules per character cell using half blocks, with explicit colors so the code reads
+    // the same on light and dark terminal themes, and the four-module quiet zone around it.
+    pub fn render(&self) -> String {
+        const QUIET: usize = 4;
+        let span = self.size + 2 * QUIET;
+        let dark = |x: usize, y: usize| {
+            x >= QUIET && y >= QUIET && x < QUIET + self.size && y < QUIET + self.size && self.modules[y - QUIET][x - QUIET]
+        };
+        let mut out = String::new();
+        for y in (0..span).step_by(2) {
+            for x in 0..span {
+                let fg = if dark(x, y) { 30 } else { 97 };
+                let bg = if y + 1 < span && dark(x, y + 1) { 40 } else { 107 };
+                out.push_str(&format!("\x1b[{fg};{bg}m▀"));
+            }
+            out.push_str("\x1b[0m\n");
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct BitBuffer(Vec<bool>);
+
+impl BitBuffer {
+    fn append(&mut self, value: u32, len: usize) {
+        self.0.extend((0..len).rev().map(|i| (value >> i) & 1 != 0));
+    }
+}
+
+fn num_raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        result -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn num_data_codewords(version: usize, ecc: Ecc) -> usize {
+    num_raw_data_modules(version) / 8
+        - usize::from(ECC_CODEWORDS_PER_BLOCK[ecc.ordinal()][version])
+            * usize::from(NUM_ERROR_CORRECTION_BLOCKS[ecc.ordinal()][version])
+}
+
+fn alignment_positions(version: usize, size: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let count = version / 7 + 2;
+    let step = if version == 32 { 26 } else { (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2 };
+    let mut positions: Vec<usize> = (0..count - 1).map(|i| size - 7 - i * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+// Splits the data into blocks, appends each block's Reed-Solomon codewords and interleaves them.
+fn add_ecc_and_interleave(data: &[u8], version: usize, ecc: Ecc) -> Vec<u8> {
+    let blocks_count = usize::from(NUM_ERROR_CORRECTION_BLOCKS[ecc.ordinal()][version]);
+    let ecc_len = usize::from(ECC_CODEWORDS_PER_BLOCK[ecc.ordinal()][version]);
+    let raw_codewords = num_raw_data_modules(version) / 8;
+    let short_blocks = blocks_count - raw_codewords % blocks_count;
+    let short_len = raw_codewords / blocks_count;
+
+    let divisor = reed_solomon_divisor(ecc_len);
+    let mut blocks = Vec::with_capacity(blocks_count);
+    let mut offset = 0;
+    for i in 0..blocks_count {
+        let data_len = short_len - ecc_len + usize::from(i >= short_blocks);
+        let mut block = data[offset..offset + data_len].to_vec();
+        offset += data_len;
+        let remainder = reed_solomon_remainder(&block, &divisor);
+        if i < short_blocks {
+            block.push(0); // Aligns short blocks with long ones; skipped when interleaving
+        }
+        block.extend_from_slice(&remainder);
+        blocks.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..blocks[0].len() {
+        for (j, block) in blocks.iter().enumerate() {
+            if i != short_len - ecc_len || j >= short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree - 1];
+    result.push(1);
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (x, &y) in result.iter_mut().zip(divisor) {
+            *x ^= gf_multiply(y, factor);
+        }
+    }
+    result
+}
+
+// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z = 0u8;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x1D);
+        z ^= ((y >> i) & 1) * x;
+    }
+    z
+}
@@ -0,0 +1,65 @@
+// Signer URIs, as in the Solana CLI: a keypair file path, or `usb://ledger[/<pubkey>][?key=<account>[/<change>]]`.
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature};
+use solana_sdk::signer::{Signer, SignerError};
+
+use crate::ledger::{DerivationPath, LedgerSigner};
+
+pub enum CliSigner {
+    Keypair(Keypair),
+    Ledger(LedgerSigner),
+}
+
+impl CliSigner {
+    fn inner(&self) -> &dyn Signer {
+        match self {
+            Self::Keypair(keypair) => keypair,
+            Self::Ledger(ledger) => ledger,
+        }
+    }
+}
+
+impl Signer for CliSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        self.inner().try_pubkey()
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.inner().try_sign_message(message)
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.inner().is_interactive()
+    }
+}
+
+pub fn parse(uri: &str) -> Result<CliSigner, String> {
+    let Some(rest) = uri.strip_prefix("usb://") else {
+        let path = expand_home(uri);
+        return read_keypair_file(&path).map(CliSigner::Keypair).map_err(|err| format!("failed to read keypair {path}: {err}"));
+    };
+    let (device, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (manufacturer, expected) = device.split_once('/').unwrap_or((device, ""));
+    if manufacturer != "ledger" {
+        return Err(format!("unsupported hardware wallet `{manufacturer}`"));
+    }
+    let expected = match expected {
+        "" => None,
+        pubkey => Some(pubkey.parse::<Pubkey>().map_err(|_| format!("invalid wallet pubkey `{pubkey}`"))?),
+    };
+    let mut path = DerivationPath::default();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        match pair.split_once('=') {
+            Some(("key", key)) => path = DerivationPath::parse(key)?,
+            _ => return Err(format!("unsupported signer URI parameter `{pair}`")),
+        }
+    }
+    LedgerSigner::connect(path, expected).map(CliSigner::Ledger).map_err(|err| format!("Ledger: {err}"))
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => path.to_string(),
+    }
+}
@@ -0,0 +1,226 @@
+// Plain-language summaries of what a transaction does. A Ledger blind-signs Privax
+// instructions and only shows a hash, so this is what the signer actually reviews.
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use privax_protocol::instruction as ix;
+use privax_protocol::AdminAction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction::SystemInstruction;
+
+pub fn describe(instruction: &Instruction) -> String {
+    if instruction.program_id == solana_sdk::system_program::ID
+        && matches!(bincode::deserialize(&instruction.data), Ok(SystemInstruction::AdvanceNonceAccount))
+    {
+        let nonce_account = instruction.accounts.first().map(|meta| meta.pubkey.to_string()).unwrap_or_default();
+        return format!("advance nonce account {nonce_account}");
+    }
+    if instruction.program_id != privax_protocol::ID {
+        return format!("call program {} ({} accounts)", instruction.program_id, instruction.accounts.len());
+    }
+    let account = |i: usize| instruction.accounts.get(i).map(|meta| meta.pubkey.to_string()).unwrap_or_default();
+    let (discriminator, mut data) = instruction.data.split_at(instruction.data.len().min(8));
+    let described = match discriminator {
+        d if d == ix::Deposit::DISCRIMINATOR => ix::Deposit::deserialize(&mut data).ok().map(|args| {
+            format!("deposit {} tokens from {} as commitment {}", args.amount, account(6), hex(&args.commitment))
+        }),
+        d if d == ix::InitNullifierShard::DISCRIMINATOR => ix::InitNullifierShard::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("create nullifier shard {}", hex(&args.prefix))),
+        d if d == ix::CreatePaymentSchedule::DISCRIMINATOR => ix::CreatePaymentSchedule::deserialize(&mut data).ok().map(|args| {
+            format!(
+                "schedule {} payments of {} tokens to {} every {} slots from slot {}, paying executors {} each",
+                args.payments, args.amount, args.recipient, args.interval_slots, args.first_payment_slot, args.executor_fee
+            )
+        }),
+        d if d == ix::ExecuteScheduledPayment::DISCRIMINATOR => {
+            Some(format!("pay the installment due on schedule {} to {}", account(1), account(3)))
+        }
+        d if d == ix::CancelPaymentSchedule::DISCRIMINATOR => {
+            Some(format!("cancel schedule {}, refunding its escrow to {}", account(1), account(4)))
+        }
+        d if d == ix::InitAuditLog::DISCRIMINATOR => Some("create the pool's audit log".to_string()),
+        d if d == ix::Pause::DISCRIMINATOR => Some("pause the pool".to_string()),
+        d if d == ix::Unpause::DISCRIMINATOR => Some("unpause the pool".to_string()),
+        d if d == ix::SetGuardian::DISCRIMINATOR => {
+            ix::SetGuardian::deserialize(&mut data).ok().map(|args| format!("set the guardian to {}", args.new_guardian))
+        }
+        d if d == ix::SetTreeOperator::DISCRIMINATOR => ix::SetTreeOperator::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("set the tree operator to {}", args.new_tree_operator)),
+        d if d == ix::SetWithdrawalLimit::DISCRIMINATOR => ix::SetWithdrawalLimit::deserialize(&mut data)
+            .ok()
+            .map(|args| withdrawal_limit(args.max_withdrawal_per_epoch, args.withdrawal_epoch_slots)),
+        d if d == ix::SetComplianceKey::DISCRIMINATOR => ix::SetComplianceKey::deserialize(&mut data)
+            .ok()
+            .map(|args| compliance_key(&args.compliance_key)),
+        d if d == ix::AttestDeposit::DISCRIMINATOR => ix::AttestDeposit::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("attest to envelope {} for commitment {}", hex(&args.envelope_hash), hex(&args.commitment))),
+        d if d == ix::SetKycAttestor::DISCRIMINATOR => {
+            ix::SetKycAttestor::deserialize(&mut data).ok().map(|args| kyc_attestor(&args.kyc_attestor))
+        }
+        d if d == ix::IssueKycCredential::DISCRIMINATOR => ix::IssueKycCredential::deserialize(&mut data).ok().map(|args| {
+            match args.expires_at {
+                0 => format!("issue {} a KYC credential that doesn't expire", args.holder),
+                expires_at => format!("issue {} a KYC credential expiring at unix time {expires_at}", args.holder),
+            }
+        }),
+        d if d == ix::RevokeKycCredential::DISCRIMINATOR => Some(format!("revoke the KYC credential at {}", account(1))),
+        d if d == ix::SetMaxDailyDeposit::DISCRIMINATOR => ix::SetMaxDailyDeposit::deserialize(&mut data).ok().map(|args| {
+            match args.max_daily_deposit {
+                0 => "lift the daily cap on each depositor's deposits".to_string(),
+                max => format!("cap each depositor's deposits at {max} tokens per 24 hours"),
+            }
+        }),
+        d if d == ix::InitDepositVolume::DISCRIMINATOR => ix::InitDepositVolume::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("open the daily deposit volume account of {}", args.depositor)),
+        d if d == ix::SetExclusionOperator::DISCRIMINATOR => ix::SetExclusionOperator::deserialize(&mut data).ok().map(|args| {
+            if args.exclusion_operator == Pubkey::default() {
+                "leave publishing exclusion roots to the admin".to_string()
+            } else {
+                format!("let {} publish exclusion roots", args.exclusion_operator)
+            }
+        }),
+        d if d == ix::PublishExclusionRoot::DISCRIMINATOR => ix::PublishExclusionRoot::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("publish exclusion root {}", hex(&args.root))),
+        d if d == ix::SetExclusionRequired::DISCRIMINATOR => ix::SetExclusionRequired::deserialize(&mut data)
+            .ok()
+            .map(|args| exclusion_required(args.exclusion_required)),
+        d if d == ix::SetMaxPauseDuration::DISCRIMINATOR => ix::SetMaxPauseDuration::deserialize(&mut data).ok().map(|args| {
+            match args.max_pause_slots {
+                0 => "let pauses last until the admin unpauses".to_string(),
+                slots => format!("let withdrawals resume once a pause has lasted {slots} slots"),
+            }
+        }),
+        d if d == ix::SetPauseOnShortfall::DISCRIMINATOR => ix::SetPauseOnShortfall::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("{} the pool on a vault shortfall", if args.enabled { "pause" } else { "don't pause" })),
+        d if d == ix::Reconcile::DISCRIMINATOR => Some("check the vault balance against the pool's accounting".to_string()),
+        d if d == ix::CheckpointRoot::DISCRIMINATOR => Some("pin the Merkle tree's current root".to_string()),
+        d if d == ix::ArchiveRoots::DISCRIMINATOR => {
+            ix::ArchiveRoots::deserialize(&mut data).ok().map(|args| format!("archive the tree's new roots into page {}", args.page))
+        }
+        d if d == ix::SetArchivedRootsAccepted::DISCRIMINATOR => ix::SetArchivedRootsAccepted::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("{} proofs against archived roots", if args.accept { "accept" } else { "refuse" })),
+        d if d == ix::TransferOwnership::DISCRIMINATOR => ix::TransferOwnership::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("transfer the admin role to {}", args.new_admin)),
+        d if d == ix::SetVerifyingKeyEnabled::DISCRIMINATOR => ix::SetVerifyingKeyEnabled::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("{} verifying key {}", if args.enabled { "enable" } else { "disable" }, account(1))),
+        d if d == ix::SetAdminTimelock::DISCRIMINATOR => ix::SetAdminTimelock::deserialize(&mut data).ok().map(|args| {
+            match args.slots {
+                0 => "turn the admin timelock off".to_string(),
+                slots => format!("make ownership, guardian and verifying key changes wait {slots} slots"),
+            }
+        }),
+        d if d == ix::QueueAdminAction::DISCRIMINATOR => ix::QueueAdminAction::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("queue admin action {} to {}", account(2), admin_action(&args.action))),
+        d if d == ix::ExecuteAdminAction::DISCRIMINATOR => Some(format!("run queued admin action {}", account(2))),
+        d if d == ix::CancelAdminAction::DISCRIMINATOR => {
+            Some(format!("cancel queued admin action {}, refunding its rent to {}", account(2), account(3)))
+        }
+        d if d == ix::AddRelayer::DISCRIMINATOR => ix::AddRelayer::deserialize(&mut data).ok().map(|args| {
+            format!(
+                "whitelist relayer {} at {} bps (min {}), expiring {}",
+                args.relayer_address,
+                args.fee_schedule.fee_bps,
+                args.fee_schedule.min_fee,
+                expiry(args.expiry_slot)
+            )
+        }),
+        d if d == ix::RenewRelayer::DISCRIMINATOR => ix::RenewRelayer::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("set relayer {} to expire {}", args.relayer_address, expiry(args.expiry_slot))),
+        d if d == ix::RemoveRelayer::DISCRIMINATOR => ix::RemoveRelayer::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("remove relayer {}, refunding rent to {}", args.relayer_address, account(4))),
+        d if d == ix::AddSwapTarget::DISCRIMINATOR => ix::AddSwapTarget::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("let shielded swaps deposit into {}", args.target_program)),
+        d if d == ix::RemoveSwapTarget::DISCRIMINATOR => ix::RemoveSwapTarget::deserialize(&mut data)
+            .ok()
+            .map(|args| format!("stop shielded swaps into {}", args.target_program)),
+        d if d == ix::UpdateFeeConfig::DISCRIMINATOR => ix::UpdateFeeConfig::deserialize(&mut data).ok().map(|args| {
+            format!("set the protocol fee to {} bps, capped at {} tokens", args.protocol_fee_bps, args.max_protocol_fee)
+        }),
+        d if d == ix::SetUsdLimits::DISCRIMINATOR => ix::SetUsdLimits::deserialize(&mut data).ok().map(|args| {
+            format!(
+                "set the USD limits priced by {}: deposit cap {}, large withdrawals above {}, flat fee {}",
+                account(4),
+                usd(args.max_deposit_usd),
+                usd(args.large_withdrawal_usd),
+                usd(args.flat_fee_usd)
+            )
+        }),
+        _ => None,
+    };
+    described.unwrap_or_else(|| format!("unrecognized Privax instruction ({} bytes)", instruction.data.len()))
+}
+
+// Micro-USD, with 0 as unset.
+fn usd(micro_usd: u64) -> String {
+    match micro_usd {
+        0 => "none".to_string(),
+        _ => format!("${}.{:06}", micro_usd / 1_000_000, micro_usd % 1_000_000),
+    }
+}
+
+fn admin_action(action: &AdminAction) -> String {
+    match *action {
+        AdminAction::TransferOwnership { new_admin } => format!("transfer the admin role to {new_admin}"),
+        AdminAction::SetGuardian { new_guardian } => format!("set the guardian to {new_guardian}"),
+        AdminAction::EnableVerifyingKey { circuit_version } => format!("enable the verifying key for circuit {circuit_version}"),
+        AdminAction::SetAdminTimelock { slots } => format!("set the admin timelock to {slots} slots"),
+        AdminAction::AddSwapTarget { target_program } => format!("let shielded swaps deposit into {target_program}"),
+        AdminAction::SetWithdrawalLimit { max_withdrawal_per_epoch, withdrawal_epoch_slots } => {
+            withdrawal_limit(max_withdrawal_per_epoch, withdrawal_epoch_slots)
+        }
+        AdminAction::SetComplianceKey { compliance_key: key } => compliance_key(&key),
+        AdminAction::SetKycAttestor { kyc_attestor: attestor } => kyc_attestor(&attestor),
+        AdminAction::SetExclusionRequired { exclusion_required: required } => exclusion_required(required),
+    }
+}
+
+fn withdrawal_limit(max_withdrawal_per_epoch: u64, withdrawal_epoch_slots: u64) -> String {
+    match max_withdrawal_per_epoch {
+        0 => "turn the withdrawal circuit breaker off".to_string(),
+        max => format!("pause the pool once withdrawals reach {max} tokens in {withdrawal_epoch_slots} slots"),
+    }
+}
+
+fn compliance_key(key: &[u8; 32]) -> String {
+    if *key == [0; 32] {
+        "stop requiring compliance attestations on deposits".to_string()
+    } else {
+        format!("require deposits to attest to envelopes for compliance key {}", hex(key))
+    }
+}
+
+fn kyc_attestor(attestor: &Pubkey) -> String {
+    if *attestor == Pubkey::default() {
+        "open deposits to everyone, without KYC credentials".to_string()
+    } else {
+        format!("only take deposits from holders of KYC credentials issued by {attestor}")
+    }
+}
+
+fn exclusion_required(required: bool) -> String {
+    if required {
+        "require spend proofs to show their notes aren't in the exclusion set".to_string()
+    } else {
+        "stop requiring exclusion proofs on spends".to_string()
+    }
+}
+
+fn expiry(slot: Option<u64>) -> String {
+    slot.map_or("never".to_string(), |slot| format!("at slot {slot}"))
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -0,0 +1,248 @@
+// Typed reads of the pool's accounts for relayers, indexers and frontends that poll them.
+// Reads of several accounts go out as one getMultipleAccounts per MAX_MULTIPLE_ACCOUNTS, and
+// with a TTL each fetched account is served from memory until it expires, so a relayer
+// checking the pool before every withdrawal doesn't refetch it every time. A TTL trades
+// freshness for requests: the tree's root or the vault's balance may be that much behind, so
+// keep it short (a few slots) or invalidate after sending a transaction that changes them.
+// A cached nullifier shard can likewise miss a spend for up to the TTL.
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::TokenAccount;
+use privax_config::Profile;
+use privax_protocol::{FeeConfig, MerkleTree, NullifierShard, ProgramState, RelayerInfo, UsdLimits, VerifyingKey};
+use solana_rpc_client_api::request::MAX_MULTIPLE_ACCOUNTS;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+use crate::instructions;
+use crate::nonblocking::{check_program, decode};
+use crate::rpc_pool::RpcPool;
+use crate::{PrivaxClientError, Result};
+
+// The accounts a withdrawal is checked against, e.g. for a relayer quoting its fee.
+#[derive(Clone)]
+pub struct PoolAccounts {
+    pub program_state: ProgramState,
+    pub fee_config: FeeConfig,
+    pub merkle_tree: MerkleTree,
+    pub vault: TokenAccount,
+}
+
+// Raw accounts by address, each fresh for `ttl` after it was fetched. An account found
+// missing is cached as missing, so polling for a relayer that isn't whitelisted is cached too.
+pub struct AccountCache {
+    ttl: Duration,
+    entries: HashMap<Pubkey, (Instant, Option<Account>)>,
+}
+
+impl AccountCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, entries: HashMap::new() }
+    }
+
+    // The account as last fetched, unless that is older than the TTL at `now`.
+    pub fn get(&self, address: &Pubkey, now: Instant) -> Option<Option<Account>> {
+        let (fetched_at, account) = self.entries.get(address)?;
+        (now.saturating_duration_since(*fetched_at) < self.ttl).then(|| account.clone())
+    }
+
+    pub fn insert(&mut self, address: Pubkey, account: Option<Account>, fetched_at: Instant) {
+        self.entries.insert(address, (fetched_at, account));
+    }
+
+    pub fn invalidate(&mut self, address: &Pubkey) {
+        self.entries.remove(address);
+    }
+
+    // Drops expired entries, which `get` already ignores, to bound the memory of long-running callers.
+    pub fn evict_expired(&mut self, now: Instant) {
+        self.entries.retain(|_, (fetched_at, _)| now.saturating_duration_since(*fetched_at) < self.ttl);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// Whether `shard`, the nullifier's shard account (None if never opened), records the nullifier.
+pub fn shard_contains(address: Pubkey, shard: Option<&Account>, nullifier_hash: &[u8; 32]) -> Result<bool> {
+    let Some(account) = shard else {
+        return Ok(false);
+    };
+    let header: NullifierShard = decode(address, Some(account))?;
+    let used = NullifierShard::SPACE + header.count as usize * NullifierShard::ENTRY_SIZE;
+    let entries = account
+        .data
+        .get(NullifierShard::SPACE..used)
+        .ok_or_else(|| PrivaxClientError::Decode(address, format!("shard holds fewer than {} nullifiers", header.count)))?;
+    Ok(entries.chunks_exact(NullifierShard::ENTRY_SIZE).any(|entry| entry == nullifier_hash))
+}
+
+pub struct PrivaxAccounts {
+    pool: RpcPool,
+    cache: Option<Mutex<AccountCache>>, // None: every read goes to the RPC
+}
+
+impl PrivaxAccounts {
+    pub fn new(pool: RpcPool, ttl: Option<Duration>) -> Self {
+        Self { pool, cache: ttl.map(|ttl| Mutex::new(AccountCache::new(ttl))) }
+    }
+
+    // Over the profile's RPC endpoints; see nonblocking::PrivaxClient::from_profile.
+    pub fn from_profile(profile: &Profile, ttl: Option<Duration>) -> Result<Self> {
+        check_program(profile)?;
+        Ok(Self::new(RpcPool::from_profile(profile, CommitmentConfig::confirmed()), ttl))
+    }
+
+    pub fn pool(&self) -> &RpcPool {
+        &self.pool
+    }
+
+    // The accounts at `addresses`, in order, None where there is no account. Cached ones are
+    // served from memory; the rest are fetched in as few requests as the RPC allows.
+    pub async fn get_multiple(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let now = Instant::now();
+        let mut accounts = vec![None; addresses.len()];
+        let mut missing = Vec::new();
+        match &self.cache {
+            Some(cache) => {
+                let cache = cache.lock().unwrap();
+                for (i, address) in addresses.iter().enumerate() {
+                    match cache.get(address, now) {
+                        Some(account) => accounts[i] = account,
+                        None => missing.push(i),
+                    }
+                }
+            }
+            None => missing.extend(0..addresses.len()),
+        }
+
+        for chunk in missing.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let chunk_addresses: Vec<Pubkey> = chunk.iter().map(|&i| addresses[i]).collect();
+            let fetched = self.pool.call(|rpc| rpc.get_multiple_accounts(&chunk_addresses)).await?;
+            if let Some(cache) = &self.cache {
+                let mut cache = cache.lock().unwrap();
+                cache.evict_expired(now);
+                for (address, account) in chunk_addresses.iter().zip(&fetched) {
+                    cache.insert(*address, account.clone(), now);
+                }
+            }
+            for (&i, account) in chunk.iter().zip(fetched) {
+                accounts[i] = account;
+            }
+        }
+        Ok(accounts)
+    }
+
+    // Makes the next read of `address` go to the RPC, e.g. after a transaction changed it.
+    pub fn invalidate(&self, address: &Pubkey) {
+        if let Some(cache) = &self.cache {
+            cache.lock().unwrap().invalidate(address);
+        }
+    }
+
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.lock().unwrap().entries.clear();
+        }
+    }
+
+    pub async fn program_state(&self) -> Result<ProgramState> {
+        self.get(instructions::program_state_address()).await
+    }
+
+    pub async fn fee_config(&self) -> Result<FeeConfig> {
+        self.get(instructions::fee_config_address()).await
+    }
+
+    pub async fn merkle_tree(&self) -> Result<MerkleTree> {
+        self.get(instructions::merkle_tree_address()).await
+    }
+
+    // The pool's token vault, whose balance sets the withdrawal fee tier.
+    pub async fn vault(&self) -> Result<TokenAccount> {
+        self.get(instructions::vault_address()).await
+    }
+
+    pub async fn usd_limits(&self) -> Result<UsdLimits> {
+        self.get(instructions::usd_limits_address()).await
+    }
+
+    pub async fn verifying_key(&self, circuit_version: u16) -> Result<VerifyingKey> {
+        self.get(instructions::verifying_key_address(circuit_version)).await
+    }
+
+    // None for a relayer that isn't whitelisted.
+    pub async fn relayer_info(&self, relayer: &Pubkey) -> Result<Option<RelayerInfo>> {
+        Ok(self.relayers(std::slice::from_ref(relayer)).await?.pop().flatten())
+    }
+
+    // The program state, fee config, tree and vault in one request.
+    pub async fn pool_accounts(&self) -> Result<PoolAccounts> {
+        let addresses = [
+            instructions::program_state_address(),
+            instructions::fee_config_address(),
+            instructions::merkle_tree_address(),
+            instructions::vault_address(),
+        ];
+        let accounts = self.get_multiple(&addresses).await?;
+        Ok(PoolAccounts {
+            program_state: decode(addresses[0], accounts[0].as_ref())?,
+            fee_config: decode(addresses[1], accounts[1].as_ref())?,
+            merkle_tree: decode(addresses[2], accounts[2].as_ref())?,
+            vault: decode(addresses[3], accounts[3].as_ref())?,
+        })
+    }
+
+    // The keys of `circuit_versions`, in order, None for a version without one.
+    pub async fn verifying_keys(&self, circuit_versions: &[u16]) -> Result<Vec<Option<VerifyingKey>>> {
+        let addresses: Vec<Pubkey> = circuit_versions.iter().map(|version| instructions::verifying_key_address(*version)).collect();
+        self.get_optional(&addresses).await
+    }
+
+    // The whitelist entries of `relayers`, in order, None for one that isn't whitelisted.
+    pub async fn relayers(&self, relayers: &[Pubkey]) -> Result<Vec<Option<RelayerInfo>>> {
+        let addresses: Vec<Pubkey> = relayers.iter().map(instructions::relayer_info_address).collect();
+        self.get_optional(&addresses).await
+    }
+
+    // Whether each nullifier hash has been spent, in order, e.g. for a wallet reconciling its
+    // notes on startup. Notes sharing a shard share its read, so a whole note set takes one
+    // request per MAX_MULTIPLE_ACCOUNTS distinct shards.
+    pub async fn nullifiers_spent(&self, nullifier_hashes: &[[u8; 32]]) -> Result<Vec<bool>> {
+        let mut shards: Vec<Pubkey> =
+            nullifier_hashes.iter().map(|hash| instructions::nullifier_shard_address(instructions::nullifier_prefix(hash))).collect();
+        shards.sort();
+        shards.dedup();
+        let accounts: HashMap<Pubkey, Option<Account>> = shards.iter().copied().zip(self.get_multiple(&shards).await?).collect();
+        nullifier_hashes
+            .iter()
+            .map(|hash| {
+                let address = instructions::nullifier_shard_address(instructions::nullifier_prefix(hash));
+                shard_contains(address, accounts[&address].as_ref(), hash)
+            })
+            .collect()
+    }
+
+    async fn get<T: AccountDeserialize>(&self, address: Pubkey) -> Result<T> {
+        let accounts = self.get_multiple(&[address]).await?;
+        decode(address, accounts[0].as_ref())
+    }
+
+    async fn get_optional<T: AccountDeserialize>(&self, addresses: &[Pubkey]) -> Result<Vec<Option<T>>> {
+        let accounts = self.get_multiple(addresses).await?;
+        addresses
+            .iter()
+            .zip(&accounts)
+            .map(|(address, account)| account.as_ref().map(|account| decode(*address, Some(account))).transpose())
+            .collect()
+    }
+}
@@ -0,0 +1,277 @@
+// Synchronous facade over the async client; each call runs it to completion on a private
+// current-thread runtime. Don't use it from inside another tokio runtime.
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::{Mint, TokenAccount};
+use privax_config::Profile;
+use privax_protocol::{
+    AuditLog, DepositVolume, FeeConfig, MerkleTree, PaymentSchedule, ProgramState, QueuedAdminAction, ReceiptNftConfig, RelayerInfo,
+    RootArchivePage, TreeCheckpoints, UsdLimits, VerifyingKey,
+};
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use tokio::runtime::Runtime;
+
+use crate::accounts::{self, PoolAccounts};
+use crate::estimate::CostEstimate;
+use crate::instructions::{
+    MergeNotesParams, PaymentScheduleParams, ShieldedSwapParams, SplitNoteParams, WithdrawNftParams, WithdrawParams,
+    WithdrawSplitParams,
+};
+use crate::rpc_pool::RpcPool;
+use crate::{nonblocking, Result};
+
+pub struct PrivaxClient {
+    inner: nonblocking::PrivaxClient,
+    runtime: Runtime,
+}
+
+impl PrivaxClient {
+    pub fn new(rpc_url: impl ToString, payer: impl Signer + Send + Sync + 'static) -> Result<Self> {
+        Self::with_pool(RpcPool::single(rpc_url), payer)
+    }
+
+    pub fn with_pool(pool: RpcPool, payer: impl Signer + Send + Sync + 'static) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        Ok(Self { inner: nonblocking::PrivaxClient::with_pool(pool, payer), runtime })
+    }
+
+    // See nonblocking::PrivaxClient::from_profile.
+    pub fn from_profile(profile: &Profile, payer: impl Signer + Send + Sync + 'static) -> Result<Self> {
+        nonblocking::check_program(profile)?;
+        Self::with_pool(RpcPool::from_profile(profile, CommitmentConfig::confirmed()), payer)
+    }
+
+    // Re-probes the pool's endpoints; see RpcPool::check_health.
+    pub fn check_health(&self) -> usize {
+        self.runtime.block_on(self.inner.pool().check_health())
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.inner.payer()
+    }
+
+    pub fn program_state(&self) -> Result<ProgramState> {
+        self.runtime.block_on(self.inner.program_state())
+    }
+
+    pub fn queued_admin_action(&self, id: u64) -> Result<QueuedAdminAction> {
+        self.runtime.block_on(self.inner.queued_admin_action(id))
+    }
+
+    pub fn pool_mint(&self) -> Result<(Pubkey, Mint)> {
+        self.runtime.block_on(self.inner.pool_mint())
+    }
+
+    pub fn slot(&self) -> Result<u64> {
+        self.runtime.block_on(self.inner.slot())
+    }
+
+    pub fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        self.runtime.block_on(self.inner.send(instructions, signers))
+    }
+
+    pub fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        self.runtime.block_on(self.inner.send_transaction(transaction))
+    }
+
+    pub fn deposit(&self, user_token_account: Pubkey, amount: u64, commitment: [u8; 32]) -> Result<Signature> {
+        self.runtime.block_on(self.inner.deposit(user_token_account, amount, commitment))
+    }
+
+    pub fn deposit_attested(
+        &self,
+        user_token_account: Pubkey,
+        amount: u64,
+        commitment: [u8; 32],
+        envelope_hash: [u8; 32],
+    ) -> Result<Signature> {
+        self.runtime.block_on(self.inner.deposit_attested(user_token_account, amount, commitment, envelope_hash))
+    }
+
+    pub fn deposit_volume(&self, depositor: &Pubkey) -> Result<Option<DepositVolume>> {
+        self.runtime.block_on(self.inner.deposit_volume(depositor))
+    }
+
+    pub fn estimate_deposit(&self, user_token_account: Pubkey, amount: u64, commitment: [u8; 32]) -> Result<CostEstimate> {
+        self.runtime.block_on(self.inner.estimate_deposit(user_token_account, amount, commitment))
+    }
+
+    pub fn withdraw(&self, params: &WithdrawParams) -> Result<Signature> {
+        self.runtime.block_on(self.inner.withdraw(params))
+    }
+
+    pub fn estimate_withdraw(&self, params: &WithdrawParams) -> Result<CostEstimate> {
+        self.runtime.block_on(self.inner.estimate_withdraw(params))
+    }
+
+    pub fn withdraw_split(&self, params: &WithdrawSplitParams) -> Result<Signature> {
+        self.runtime.block_on(self.inner.withdraw_split(params))
+    }
+
+    pub fn shielded_swap(&self, params: &ShieldedSwapParams) -> Result<Signature> {
+        self.runtime.block_on(self.inner.shielded_swap(params))
+    }
+
+    pub fn split_note(&self, params: &SplitNoteParams) -> Result<Signature> {
+        self.runtime.block_on(self.inner.split_note(params))
+    }
+
+    pub fn merge_notes(&self, params: &MergeNotesParams) -> Result<Signature> {
+        self.runtime.block_on(self.inner.merge_notes(params))
+    }
+
+    pub fn create_payment_schedule(&self, params: &PaymentScheduleParams) -> Result<Signature> {
+        self.runtime.block_on(self.inner.create_payment_schedule(params))
+    }
+
+    pub fn payment_schedule(&self, address: Pubkey) -> Result<PaymentSchedule> {
+        self.runtime.block_on(self.inner.payment_schedule(address))
+    }
+
+    pub fn due_payment_schedules(&self) -> Result<Vec<(Pubkey, PaymentSchedule)>> {
+        self.runtime.block_on(self.inner.due_payment_schedules())
+    }
+
+    pub fn execute_scheduled_payment(&self, schedule: Pubkey) -> Result<Signature> {
+        self.runtime.block_on(self.inner.execute_scheduled_payment(schedule))
+    }
+
+    pub fn cancel_payment_schedule(&self, schedule_id: u64) -> Result<Signature> {
+        self.runtime.block_on(self.inner.cancel_payment_schedule(schedule_id))
+    }
+
+    pub fn deposit_nft(&self, mint: Pubkey, commitment: [u8; 32]) -> Result<Signature> {
+        self.runtime.block_on(self.inner.deposit_nft(mint, commitment))
+    }
+
+    pub fn withdraw_nft(&self, params: &WithdrawNftParams) -> Result<Signature> {
+        self.runtime.block_on(self.inner.withdraw_nft(params))
+    }
+
+    pub fn receipt_nft_config(&self) -> Result<ReceiptNftConfig> {
+        self.runtime.block_on(self.inner.receipt_nft_config())
+    }
+
+    pub fn claim_receipt_nft(&self, leaf_index: u64) -> Result<Signature> {
+        self.runtime.block_on(self.inner.claim_receipt_nft(leaf_index))
+    }
+
+    pub fn tree_checkpoints(&self) -> Result<TreeCheckpoints> {
+        self.runtime.block_on(self.inner.tree_checkpoints())
+    }
+
+    pub fn audit_log(&self) -> Result<AuditLog> {
+        self.runtime.block_on(self.inner.audit_log())
+    }
+
+    pub fn checkpoint_root(&self) -> Result<Signature> {
+        self.runtime.block_on(self.inner.checkpoint_root())
+    }
+
+    pub fn merkle_tree(&self) -> Result<MerkleTree> {
+        self.runtime.block_on(self.inner.merkle_tree())
+    }
+
+    pub fn root_archive_page(&self, page: u64) -> Result<RootArchivePage> {
+        self.runtime.block_on(self.inner.root_archive_page(page))
+    }
+
+    pub fn archive_roots(&self) -> Result<Signature> {
+        self.runtime.block_on(self.inner.archive_roots())
+    }
+
+    pub fn resolve_domain(&self, domain: &str) -> Result<Pubkey> {
+        self.runtime.block_on(self.inner.resolve_domain(domain))
+    }
+
+    pub fn resolve_address(&self, address: &str) -> Result<Pubkey> {
+        self.runtime.block_on(self.inner.resolve_address(address))
+    }
+
+    pub fn usd_limits(&self) -> Result<UsdLimits> {
+        self.runtime.block_on(self.inner.usd_limits())
+    }
+
+    pub fn usd_price_feed(&self, state: &ProgramState) -> Result<Option<Pubkey>> {
+        self.runtime.block_on(self.inner.usd_price_feed(state))
+    }
+}
+
+// Synchronous accounts::PrivaxAccounts.
+pub struct PrivaxAccounts {
+    inner: accounts::PrivaxAccounts,
+    runtime: Runtime,
+}
+
+impl PrivaxAccounts {
+    pub fn new(pool: RpcPool, ttl: Option<Duration>) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        Ok(Self { inner: accounts::PrivaxAccounts::new(pool, ttl), runtime })
+    }
+
+    pub fn from_profile(profile: &Profile, ttl: Option<Duration>) -> Result<Self> {
+        nonblocking::check_program(profile)?;
+        Self::new(RpcPool::from_profile(profile, CommitmentConfig::confirmed()), ttl)
+    }
+
+    pub fn get_multiple(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.runtime.block_on(self.inner.get_multiple(addresses))
+    }
+
+    pub fn invalidate(&self, address: &Pubkey) {
+        self.inner.invalidate(address)
+    }
+
+    pub fn clear_cache(&self) {
+        self.inner.clear_cache()
+    }
+
+    pub fn program_state(&self) -> Result<ProgramState> {
+        self.runtime.block_on(self.inner.program_state())
+    }
+
+    pub fn fee_config(&self) -> Result<FeeConfig> {
+        self.runtime.block_on(self.inner.fee_config())
+    }
+
+    pub fn merkle_tree(&self) -> Result<MerkleTree> {
+        self.runtime.block_on(self.inner.merkle_tree())
+    }
+
+    pub fn vault(&self) -> Result<TokenAccount> {
+        self.runtime.block_on(self.inner.vault())
+    }
+
+    pub fn usd_limits(&self) -> Result<UsdLimits> {
+        self.runtime.block_on(self.inner.usd_limits())
+    }
+
+    pub fn verifying_key(&self, circuit_version: u16) -> Result<VerifyingKey> {
+        self.runtime.block_on(self.inner.verifying_key(circuit_version))
+    }
+
+    pub fn relayer_info(&self, relayer: &Pubkey) -> Result<Option<RelayerInfo>> {
+        self.runtime.block_on(self.inner.relayer_info(relayer))
+    }
+
+    pub fn pool_accounts(&self) -> Result<PoolAccounts> {
+        self.runtime.block_on(self.inner.pool_accounts())
+    }
+
+    pub fn verifying_keys(&self, circuit_versions: &[u16]) -> Result<Vec<Option<VerifyingKey>>> {
+        self.runtime.block_on(self.inner.verifying_keys(circuit_versions))
+    }
+
+    pub fn relayers(&self, relayers: &[Pubkey]) -> Result<Vec<Option<RelayerInfo>>> {
+        self.runtime.block_on(self.inner.relayers(relayers))
+    }
+
+    pub fn nullifiers_spent(&self, nullifier_hashes: &[[u8; 32]]) -> Result<Vec<bool>> {
+        self.runtime.block_on(self.inner.nullifiers_spent(nullifier_hashes))
+    }
+}
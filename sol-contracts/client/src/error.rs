@@ -0,0 +1,218 @@
+// Typed SDK errors. A failed transaction reports only "custom program error: 0x1772"; this
+// maps such codes back to the PrivaxError variant, or to the Anchor framework error named in
+// the program logs, together with the failing instruction and the offending account.
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use anchor_lang::prelude::Pubkey;
+use privax_protocol::PrivaxError;
+use solana_rpc_client_api::client_error::{Error as RpcClientError, ErrorKind};
+use solana_rpc_client_api::request::{RpcError, RpcResponseErrorData};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PrivaxClientError {
+    #[error("instruction {instruction} failed: {error}{}", caused_by(account))]
+    Program { instruction: u8, error: PrivaxError, account: Option<String>, logs: Vec<String> },
+    #[error("instruction {instruction} failed: {name} ({code}): {message}{}", caused_by(account))]
+    Anchor { instruction: u8, code: u32, name: String, message: String, account: Option<String>, logs: Vec<String> },
+    // A custom error the SDK can't name, e.g. from the token program
+    #[error("instruction {instruction} failed: custom program error {code:#x}")]
+    Custom { instruction: u8, code: u32, logs: Vec<String> },
+    // A simulation that failed before reaching a program, e.g. insufficient funds
+    #[error("simulation failed: {err}")]
+    Simulation { err: TransactionError, logs: Vec<String> },
+    #[error(transparent)]
+    Rpc(Box<RpcClientError>), // Boxed to keep Result small
+    #[error("failed to decode account {0}: {1}")]
+    Decode(Pubkey, String),
+    // Neither a public key nor a .sol domain that resolves safely
+    #[error("invalid address {address}: {reason}")]
+    Address { address: String, reason: &'static str },
+    #[error("failed to start the runtime: {0}")]
+    Runtime(#[from] std::io::Error),
+    #[error(transparent)]
+    Config(#[from] privax_config::ConfigError),
+    // A profile for a deployment under another program ID than the one the SDK was built for
+    #[error("the {cluster} profile's program {program_id} isn't the one this SDK was built for ({})", privax_protocol::ID)]
+    ProgramMismatch { cluster: privax_config::Cluster, program_id: Pubkey },
+}
+
+fn caused_by(account: &Option<String>) -> String {
+    account.as_ref().map(|account| format!(" (account: {account})")).unwrap_or_default()
+}
+
+// In declaration order, so a variant's index is its code minus ERROR_CODE_OFFSET. Variants
+// missing here decode as PrivaxClientError::Anchor with the name from the logs.
+const PRIVAX_ERRORS: &[PrivaxError] = &[
+    PrivaxError::AmountTooSmall,
+    PrivaxError::InvalidPublicInputCount,
+    PrivaxError::RecipientMismatch,
+    PrivaxError::AmountMismatch,
+    PrivaxError::InvalidZkProof,
+    PrivaxError::RelayerAlreadyWhitelisted,
+    PrivaxError::RelayerNotWhitelisted,
+    PrivaxError::InvalidRelayerAddress,
+    PrivaxError::NewAdminIsZero,
+    PrivaxError::Overflow,
+    PrivaxError::FeeExceedsAmount,
+    PrivaxError::MissingRelayerFeeAccount,
+    PrivaxError::InvalidRelayerFeeAccount,
+    PrivaxError::InvalidFeeSwapProgram,
+    PrivaxError::FeeSwapOverspent,
+    PrivaxError::FeeSwapSlippage,
+    PrivaxError::RelayerExpired,
+    PrivaxError::PoolPaused,
+    PrivaxError::PoolNotPaused,
+    PrivaxError::NotAdminOrGuardian,
+    PrivaxError::InvalidRenounceConfirmation,
+    PrivaxError::RenounceNotInitiated,
+    PrivaxError::ParamsFrozen,
+    PrivaxError::InvalidParamGroup,
+    PrivaxError::FeeBpsTooHigh,
+    PrivaxError::InvalidFeeBeneficiaries,
+    PrivaxError::BeneficiaryAccountMismatch,
+    PrivaxError::NothingToDistribute,
+    PrivaxError::NothingToClaim,
+    PrivaxError::RegistrationExpired,
+    PrivaxError::RegistrationNotExpired,
+    PrivaxError::InvalidCrankAccounts,
+    PrivaxError::NothingToCrank,
+    PrivaxError::NullifierAlreadySpent,
+    PrivaxError::NullifierShardMismatch,
+    PrivaxError::InvalidSubtreeSize,
+    PrivaxError::SubtreeRootMismatch,
+    PrivaxError::MisalignedSubtree,
+    PrivaxError::MerkleTreeFull,
+    PrivaxError::InvalidProofEncoding,
+    PrivaxError::VerifyingKeyTooLarge,
+    PrivaxError::CircuitVersionDisabled,
+    PrivaxError::ProofExpired,
+    PrivaxError::ExternalNullifierMismatch,
+    PrivaxError::InvalidVerifyingKey,
+    PrivaxError::MissingAmountRangeCheck,
+    PrivaxError::AmountOutOfRange,
+    PrivaxError::IncompleteVerifyingKey,
+    PrivaxError::ChangeCommitmentMismatch,
+    PrivaxError::MissingMerkleTree,
+    PrivaxError::InvalidSwapTarget,
+    PrivaxError::SwapOverspent,
+    PrivaxError::SwapSlippage,
+    PrivaxError::OutputCommitmentMismatch,
+    PrivaxError::InvalidPayouts,
+    PrivaxError::PayoutMismatch,
+    PrivaxError::InvalidRecipientAccount,
+    PrivaxError::InvalidNoteCount,
+    PrivaxError::InvalidPaymentSchedule,
+    PrivaxError::PaymentNotDue,
+    PrivaxError::ScheduleUnderfunded,
+    PrivaxError::WrongPoolMode,
+    PrivaxError::PoolNotEmpty,
+    PrivaxError::NotAnNft,
+    PrivaxError::MintMismatch,
+    PrivaxError::MissingProgrammableAccounts,
+    PrivaxError::ReceiptNftsDisabled,
+    PrivaxError::InvalidReceiptNftConfig,
+    PrivaxError::MissingUsdLimits,
+    PrivaxError::InvalidPriceFeed,
+    PrivaxError::StalePrice,
+    PrivaxError::PriceTooUncertain,
+    PrivaxError::DepositCapExceeded,
+    PrivaxError::LargeWithdrawal,
+    PrivaxError::InvalidUsdLimits,
+    PrivaxError::NotTreeOperator,
+    PrivaxError::CheckpointUnchanged,
+    PrivaxError::NothingToArchive,
+    PrivaxError::WrongArchivePage,
+    PrivaxError::InvalidCommitment,
+    PrivaxError::DuplicateCommitment,
+    PrivaxError::CommitmentShardMismatch,
+    PrivaxError::NotAdmin,
+    PrivaxError::NotFeeAuthority,
+    PrivaxError::NotAccountOwner,
+    PrivaxError::RegistrantMismatch,
+    PrivaxError::TokenMintMismatch,
+    PrivaxError::TooManyRelayers,
+    PrivaxError::NullifierShardCountMismatch,
+    PrivaxError::InvalidNoteCiphertext,
+    PrivaxError::InvalidWithdrawalLimit,
+    PrivaxError::ExitOnly,
+    PrivaxError::WithdrawalLimitReached,
+    PrivaxError::ComplianceAttestationRequired,
+    PrivaxError::ComplianceAttestationMismatch,
+    PrivaxError::NoComplianceKey,
+    PrivaxError::KycCredentialRequired,
+    PrivaxError::KycCredentialInvalid,
+    PrivaxError::NotKycAttestor,
+    PrivaxError::InvalidKycExpiry,
+    PrivaxError::DepositVolumeRequired,
+    PrivaxError::DailyDepositCapReached,
+    PrivaxError::NotExclusionOperator,
+    PrivaxError::InvalidExclusionRoot,
+    PrivaxError::NoExclusionRoot,
+    PrivaxError::RelayerMismatch,
+    PrivaxError::RootNotKnown,
+];
+
+pub fn privax_error(code: u32) -> Option<PrivaxError> {
+    let index = code.checked_sub(ERROR_CODE_OFFSET)?;
+    PRIVAX_ERRORS.get(index as usize).copied().filter(|error| u32::from(*error) == code)
+}
+
+// The fields of the "AnchorError ... Error Code: X. Error Number: N. Error Message: M." line
+// Anchor logs before failing: (account, name, number, message).
+fn parse_anchor_log(line: &str) -> Option<(Option<String>, String, u32, String)> {
+    let rest = line.strip_prefix("Program log: AnchorError ")?;
+    let (context, rest) = rest.split_once(". Error Code: ")?;
+    let account = context.strip_prefix("caused by account: ").map(str::to_string);
+    let (name, rest) = rest.split_once(". Error Number: ")?;
+    let (number, message) = rest.split_once(". Error Message: ")?;
+    let message = message.strip_suffix('.').unwrap_or(message);
+    Some((account, name.to_string(), number.parse().ok()?, message.to_string()))
+}
+
+impl PrivaxClientError {
+    // Decodes a failed transaction, given whatever logs are available (simulation or
+    // preflight). None if it didn't fail with a custom program error.
+    pub fn from_transaction_error(err: &TransactionError, logs: &[String]) -> Option<Self> {
+        let TransactionError::InstructionError(instruction, InstructionError::Custom(code)) = err else {
+            return None;
+        };
+        let (instruction, code, logs) = (*instruction, *code, logs.to_vec());
+        let logged = logs.iter().rev().filter_map(|line| parse_anchor_log(line)).find(|(_, _, number, _)| *number == code);
+        Some(match (privax_error(code), logged) {
+            (Some(error), logged) => Self::Program { instruction, error, account: logged.and_then(|(account, ..)| account), logs },
+            (None, Some((account, name, _, message))) => Self::Anchor { instruction, code, name, message, account, logs },
+            (None, None) => Self::Custom { instruction, code, logs },
+        })
+    }
+
+    // The PrivaxError behind this error, if any.
+    pub fn privax_error(&self) -> Option<PrivaxError> {
+        match self {
+            Self::Program { error, .. } => Some(*error),
+            _ => None,
+        }
+    }
+
+    pub fn logs(&self) -> &[String] {
+        match self {
+            Self::Program { logs, .. } | Self::Anchor { logs, .. } | Self::Custom { logs, .. } | Self::Simulation { logs, .. } => logs,
+            _ => &[],
+        }
+    }
+}
+
+impl From<RpcClientError> for PrivaxClientError {
+    fn from(err: RpcClientError) -> Self {
+        let logs = match err.kind() {
+            ErrorKind::RpcError(RpcError::RpcResponseError {
+                data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+                ..
+            }) => result.logs.clone().unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        err.get_transaction_error()
+            .and_then(|tx_err| Self::from_transaction_error(&tx_err, &logs))
+            .unwrap_or_else(|| Self::Rpc(Box::new(err)))
+    }
+}
@@ -0,0 +1,35 @@
+// All-in cost of a deposit or withdrawal before sending it, so wallets can show users what
+// they will pay. Computed from a simulation and the current on-chain fee configuration.
+use privax_protocol::{FeeConfig, NullifierShard};
+use solana_sdk::account::Account;
+use solana_sdk::rent::Rent;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CostEstimate {
+    pub compute_units: u64, // Consumed in simulation
+    pub network_fee: u64,   // Signature and priority fees, in lamports
+    pub rent: u64,          // Lamports locked in accounts the transaction creates or grows
+    pub protocol_fee: u64,  // In pool token units
+    pub relayer_fee: u64,   // In pool token units
+    pub net_amount: u64,    // Pool tokens reaching the recipient (withdraw) or the note (deposit)
+}
+
+impl CostEstimate {
+    // What the fee payer spends in SOL.
+    pub fn lamports(&self) -> u64 {
+        self.network_fee.saturating_add(self.rent)
+    }
+}
+
+// Protocol fee the program will charge on a withdrawal of `amount`, at the vault's current
+// utilization.
+pub fn protocol_fee(fee_config: &FeeConfig, vault_balance: u64, outstanding_deposits: u64, amount: u64) -> u64 {
+    fee_config.protocol_fee(amount, fee_config.fee_bps(vault_balance, outstanding_deposits))
+}
+
+// Rent the payer tops a nullifier shard up by to record `spends` more nullifiers; `shard` is
+// None when the shard has yet to be created (its creation rent is included).
+pub fn shard_rent(rent: &Rent, shard: Option<&Account>, spends: usize) -> u64 {
+    let (lamports, len) = shard.map_or((0, NullifierShard::SPACE), |account| (account.lamports, account.data.len()));
+    rent.minimum_balance(len + spends * NullifierShard::ENTRY_SIZE).saturating_sub(lamports)
+}
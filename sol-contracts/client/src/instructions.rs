@@ -0,0 +1,1469 @@
+// Instruction builders and PDA derivations shared by the async and blocking clients.
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use mpl_token_metadata::pda::{find_master_edition_account, find_metadata_account, find_token_record_account};
+use privax_protocol::{
+    AdminAction, CommitmentShard, FeeSchedule, NullifierShard, Payout, ProgramState, MAX_PUBLIC_INPUTS, NFT_PUBLIC_INPUTS,
+    NOTE_TRANSFER_PUBLIC_INPUTS, SPLIT_PUBLIC_INPUTS, SWAP_PUBLIC_INPUTS,
+};
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &privax_protocol::ID).0
+}
+
+pub fn program_state_address() -> Pubkey {
+    pda(&[b"program_state"])
+}
+
+pub fn fee_config_address() -> Pubkey {
+    pda(&[b"fee_config", program_state_address().as_ref()])
+}
+
+pub fn merkle_tree_address() -> Pubkey {
+    pda(&[b"merkle_tree", program_state_address().as_ref()])
+}
+
+pub fn nullifier_filter_address() -> Pubkey {
+    pda(&[b"nullifier_filter", program_state_address().as_ref()])
+}
+
+// The vault is its own token authority.
+pub fn vault_address() -> Pubkey {
+    pda(&[b"program_token_vault", program_state_address().as_ref()])
+}
+
+pub fn verifying_key_address(circuit_version: u16) -> Pubkey {
+    pda(&[b"verifying_key", program_state_address().as_ref(), &circuit_version.to_le_bytes()])
+}
+
+pub fn nullifier_shard_address(prefix: [u8; 2]) -> Pubkey {
+    pda(&[b"nullifier_shard", program_state_address().as_ref(), &prefix])
+}
+
+pub fn commitment_shard_address(prefix: [u8; 2]) -> Pubkey {
+    pda(&[b"commitment_shard", program_state_address().as_ref(), &prefix])
+}
+
+pub fn payment_schedule_address(owner: &Pubkey, schedule_id: u64) -> Pubkey {
+    pda(&[b"payment_schedule", program_state_address().as_ref(), owner.as_ref(), &schedule_id.to_le_bytes()])
+}
+
+// Fund a schedule by withdrawing to this account, the schedule's associated token account,
+// with the schedule as recipient.
+pub fn schedule_escrow_address(schedule: &Pubkey, token_mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(schedule, token_mint)
+}
+
+// Opened by deposit for the deposit at `leaf_index` when its depositor asks for one.
+pub fn deposit_receipt_address(leaf_index: u64) -> Pubkey {
+    pda(&[b"receipt", program_state_address().as_ref(), &leaf_index.to_le_bytes()])
+}
+
+// `id` is ProgramState::next_admin_action_id when the action was queued.
+pub fn queued_admin_action_address(id: u64) -> Pubkey {
+    pda(&[b"queued_admin_action", program_state_address().as_ref(), &id.to_le_bytes()])
+}
+
+pub fn compliance_attestation_address(commitment: &[u8; 32]) -> Pubkey {
+    pda(&[b"compliance_attestation", program_state_address().as_ref(), commitment])
+}
+
+pub fn kyc_credential_address(holder: &Pubkey) -> Pubkey {
+    pda(&[b"kyc_credential", program_state_address().as_ref(), holder.as_ref()])
+}
+
+pub fn deposit_volume_address(depositor: &Pubkey) -> Pubkey {
+    pda(&[b"deposit_volume", program_state_address().as_ref(), depositor.as_ref()])
+}
+
+// Also the receipt NFT tree's delegate.
+pub fn receipt_nft_config_address() -> Pubkey {
+    pda(&[b"receipt_nft_config", program_state_address().as_ref()])
+}
+
+pub fn receipt_nft_claim_address(deposit_receipt: &Pubkey) -> Pubkey {
+    pda(&[b"receipt_nft_claim", deposit_receipt.as_ref()])
+}
+
+pub fn tree_checkpoints_address() -> Pubkey {
+    pda(&[b"tree_checkpoints", program_state_address().as_ref()])
+}
+
+pub fn root_archive_address(page: u64) -> Pubkey {
+    pda(&[b"root_archive", program_state_address().as_ref(), &page.to_le_bytes()])
+}
+
+pub fn usd_limits_address() -> Pubkey {
+    pda(&[b"usd_limits", program_state_address().as_ref()])
+}
+
+pub fn audit_log_address() -> Pubkey {
+    pda(&[b"audit_log", program_state_address().as_ref()])
+}
+
+pub fn relayer_info_address(relayer: &Pubkey) -> Pubkey {
+    pda(&[b"relayer", relayer.as_ref()])
+}
+
+// Shard prefix of a nullifier hash.
+pub fn nullifier_prefix(nullifier_hash: &[u8; 32]) -> [u8; 2] {
+    NullifierShard::prefix_of(nullifier_hash)
+}
+
+// The per-depositor accounts a deposit passes, each required while the pool has the matching
+// setting: the commitment's compliance attestation (a compliance key), the user's KYC credential
+// (a KYC attestor) and the user's deposit volume (a daily deposit cap).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DepositGates {
+    pub compliance_attestation: bool,
+    pub kyc_credential: bool,
+    pub deposit_volume: bool,
+}
+
+impl DepositGates {
+    pub fn for_pool(state: &ProgramState) -> Self {
+        Self {
+            compliance_attestation: state.requires_attestation(),
+            kyc_credential: state.requires_kyc(),
+            deposit_volume: state.max_daily_deposit > 0,
+        }
+    }
+}
+
+// `usd_price_feed` is the pool's Pyth price account, required while the pool has USD limits
+// (UsdLimits::price_feed); the withdrawal params carry it the same way.
+pub fn deposit(
+    user: Pubkey,
+    user_token_account: Pubkey,
+    token_mint: Pubkey,
+    amount: u64,
+    commitment: [u8; 32],
+    usd_price_feed: Option<Pubkey>,
+    gates: DepositGates,
+) -> Instruction {
+    let accounts = privax_protocol::accounts::DepositTokens {
+        program_state: program_state_address(),
+        fee_config: fee_config_address(),
+        merkle_tree: merkle_tree_address(),
+        commitment_shard: commitment_shard_address(CommitmentShard::prefix_of(&commitment)),
+        referrer_account: None,
+        deposit_receipt: None,
+        user,
+        user_token_account,
+        token_mint,
+        program_token_vault: vault_address(),
+        program_token_vault_authority: vault_address(),
+        token_program: anchor_spl::token::ID,
+        system_program: system_program::ID,
+        rent: anchor_lang::solana_program::sysvar::rent::ID,
+        usd_limits: usd_price_feed.map(|_| usd_limits_address()),
+        price_feed: usd_price_feed,
+        compliance_attestation: gates.compliance_attestation.then(|| compliance_attestation_address(&commitment)),
+        kyc_credential: gates.kyc_credential.then(|| kyc_credential_address(&user)),
+        deposit_volume: gates.deposit_volume.then(|| deposit_volume_address(&user)),
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::Deposit { amount, commitment }.data(),
+    }
+}
+
+// Goes before the deposit, normally in its transaction. `envelope_hash` is the SHA-256 of the
+// envelope encrypted to the pool's compliance key.
+pub fn attest_deposit(depositor: Pubkey, commitment: [u8; 32], envelope_hash: [u8; 32]) -> Instruction {
+    let accounts = privax_protocol::accounts::AttestDeposit {
+        program_state: program_state_address(),
+        compliance_attestation: compliance_attestation_address(&commitment),
+        depositor,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::AttestDeposit { commitment, envelope_hash }.data(),
+    }
+}
+
+// Goes in the deposit's transaction, so the note is announced only if the deposit lands.
+pub fn announce_note(commitment: [u8; 32], ciphertext: Vec<u8>) -> Instruction {
+    let accounts = privax_protocol::accounts::AnnounceNote { program_state: program_state_address() };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::AnnounceNote { commitment, ciphertext }.data(),
+    }
+}
+
+pub fn init_nullifier_shard(payer: Pubkey, prefix: [u8; 2]) -> Instruction {
+    let accounts = privax_protocol::accounts::InitNullifierShard {
+        program_state: program_state_address(),
+        nullifier_shard: nullifier_shard_address(prefix),
+        payer,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::InitNullifierShard { prefix }.data(),
+    }
+}
+
+// Read-only, for simulation or CPI: its return data is whether each nullifier is spent, in
+// order. Each one takes a shard account, so a transaction fits a few dozen; to check more
+// without a transaction, see accounts::PrivaxAccounts::nullifiers_spent.
+pub fn check_nullifiers(nullifier_hashes: &[[u8; 32]]) -> Instruction {
+    let accounts = privax_protocol::accounts::CheckNullifiers {
+        program_state: program_state_address(),
+        nullifier_filter: nullifier_filter_address(),
+    };
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(nullifier_hashes.iter().map(|hash| AccountMeta::new_readonly(nullifier_shard_address(nullifier_prefix(hash)), false)));
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: metas,
+        data: privax_protocol::instruction::CheckNullifiers { nullifier_hashes: nullifier_hashes.to_vec() }.data(),
+    }
+}
+
+// Only deposit_subtree needs this first; the other deposits create their shard.
+pub fn init_commitment_shard(payer: Pubkey, prefix: [u8; 2]) -> Instruction {
+    let accounts = privax_protocol::accounts::InitCommitmentShard {
+        program_state: program_state_address(),
+        commitment_shard: commitment_shard_address(prefix),
+        payer,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::InitCommitmentShard { prefix }.data(),
+    }
+}
+
+// Arguments of a withdrawal. `recipient_token_account` must be the recipient's associated
+// token account for the pool mint. The relayer fee, when non-zero, is paid in the pool mint to
+// `relayer_fee_token_account`, the relayer's own associated token account, and the submitting
+// wallet must be a whitelisted relayer. The proof binds both: public_inputs[6] is the fee and
+// [7] the submitter (first 8 bytes, LE), or 0 for a withdrawal without a relayer. Here and in
+// the other spends, `root_archive_page` names the archive page holding the proof's root once it
+// has left the tree's recent roots; the pool must accept archived roots. `merkle_root` and the
+// nullifier hashes are the full values public_inputs[0] and the nullifier inputs stand for; the
+// proof is verified against them.
+#[derive(Clone, Debug)]
+pub struct WithdrawParams {
+    pub a_proof: [u8; 64],
+    pub b_proof: [u8; 128],
+    pub c_proof: [u8; 64],
+    pub public_inputs: [u64; MAX_PUBLIC_INPUTS],
+    pub merkle_root: [u8; 32],
+    pub nullifier_hash: [u8; 32],
+    pub root_archive_page: Option<u64>,
+    pub extra_nullifier_hashes: Vec<[u8; 32]>,
+    pub recipient: Pubkey,
+    pub recipient_token_account: Pubkey,
+    pub amount: u64,
+    pub relayer_fee: u64,
+    pub relayer_fee_token_account: Option<Pubkey>,
+    pub circuit_version: u16,
+    pub plonk_proof: Vec<u8>,
+    pub change_commitment: Option<[u8; 32]>,
+    pub usd_price_feed: Option<Pubkey>,
+}
+
+impl WithdrawParams {
+    // Hashes of the nullifiers spent: the first note's, then the extra notes'.
+    pub fn nullifier_hashes(&self) -> impl Iterator<Item = [u8; 32]> + '_ {
+        std::iter::once(self.nullifier_hash).chain(self.extra_nullifier_hashes.iter().copied())
+    }
+}
+
+pub fn withdraw(submitter: Pubkey, params: &WithdrawParams) -> Instruction {
+    let accounts = privax_protocol::accounts::WithdrawTokens {
+        program_state: program_state_address(),
+        verifying_key: verifying_key_address(params.circuit_version),
+        fee_config: fee_config_address(),
+        nullifier_shard: nullifier_shard_address(nullifier_prefix(&params.nullifier_hash)),
+        nullifier_filter: nullifier_filter_address(),
+        merkle_tree: merkle_tree_address(),
+        root_archive: params.root_archive_page.map(root_archive_address),
+        user: submitter,
+        program_token_vault: vault_address(),
+        program_token_vault_authority: vault_address(),
+        recipient_token_account: params.recipient_token_account,
+        relayer_info: params.relayer_fee_token_account.map(|_| relayer_info_address(&submitter)),
+        relayer_fee_token_account: params.relayer_fee_token_account,
+        fee_swap_program: None,
+        token_program: anchor_spl::token::ID,
+        system_program: system_program::ID,
+        usd_limits: params.usd_price_feed.map(|_| usd_limits_address()),
+        price_feed: params.usd_price_feed,
+    };
+    let mut metas = accounts.to_account_metas(None);
+    // Shards of the extra nullifiers go in the remaining accounts
+    metas.extend(
+        params
+            .extra_nullifier_hashes
+            .iter()
+            .map(|hash| AccountMeta::new(nullifier_shard_address(nullifier_prefix(hash)), false)),
+    );
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: metas,
+        data: privax_protocol::instruction::Withdraw {
+            a_proof: params.a_proof,
+            b_proof: params.b_proof,
+            c_proof: params.c_proof,
+            public_inputs: params.public_inputs,
+            merkle_root: params.merkle_root,
+            nullifier_hash: params.nullifier_hash,
+            extra_nullifier_hashes: params.extra_nullifier_hashes.clone(),
+            recipient_address: params.recipient,
+            amount_to_withdraw: params.amount,
+            relayer_fee: params.relayer_fee,
+            fee_swap_data: Vec::new(),
+            min_fee_out: 0,
+            circuit_version: params.circuit_version,
+            plonk_proof: params.plonk_proof.clone(),
+            change_commitment: params.change_commitment,
+        }
+        .data(),
+    }
+}
+
+// Arguments of a split withdrawal. Each payout goes to the recipient's associated token
+// account for the pool mint, the only account the program pays. A relayer paid through the
+// payouts must be the submitter the proof binds.
+#[derive(Clone, Debug)]
+pub struct WithdrawSplitParams {
+    pub a_proof: [u8; 64],
+    pub b_proof: [u8; 128],
+    pub c_proof: [u8; 64],
+    pub public_inputs: [u64; SPLIT_PUBLIC_INPUTS],
+    pub merkle_root: [u8; 32],
+    pub nullifier_hash: [u8; 32],
+    pub root_archive_page: Option<u64>,
+    pub payouts: Vec<Payout>,
+    pub circuit_version: u16,
+    pub plonk_proof: Vec<u8>,
+    pub usd_price_feed: Option<Pubkey>,
+}
+
+pub fn withdraw_split(submitter: Pubkey, token_mint: Pubkey, params: &WithdrawSplitParams) -> Instruction {
+    let accounts = privax_protocol::accounts::WithdrawSplit {
+        program_state: program_state_address(),
+        verifying_key: verifying_key_address(params.circuit_version),
+        fee_config: fee_config_address(),
+        nullifier_shard: nullifier_shard_address(nullifier_prefix(&params.nullifier_hash)),
+        nullifier_filter: nullifier_filter_address(),
+        merkle_tree: merkle_tree_address(),
+        root_archive: params.root_archive_page.map(root_archive_address),
+        user: submitter,
+        program_token_vault: vault_address(),
+        program_token_vault_authority: vault_address(),
+        token_program: anchor_spl::token::ID,
+        system_program: system_program::ID,
+        usd_limits: params.usd_price_feed.map(|_| usd_limits_address()),
+        price_feed: params.usd_price_feed,
+    };
+    let mut metas = accounts.to_account_metas(None);
+    // Recipients' token accounts go in the remaining accounts, in payout order
+    metas.extend(
+        params
+            .payouts
+            .iter()
+            .map(|payout| AccountMeta::new(get_associated_token_address(&payout.recipient, &token_mint), false)),
+    );
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: metas,
+        data: privax_protocol::instruction::WithdrawSplit {
+            a_proof: params.a_proof,
+            b_proof: params.b_proof,
+            c_proof: params.c_proof,
+            public_inputs: params.public_inputs,
+            merkle_root: params.merkle_root,
+            nullifier_hash: params.nullifier_hash,
+            payouts: params.payouts.clone(),
+            circuit_version: params.circuit_version,
+            plonk_proof: params.plonk_proof.clone(),
+        }
+        .data(),
+    }
+}
+
+// Arguments of a shielded swap into another Privax pool, deployed as `target_program` with
+// `target_mint`. `swap_data` and `swap_accounts` are the configured swap program's instruction
+// and route for `amount_in`, less the protocol fee, from the pool's vault into
+// `swap_output_address(target_mint)`. Any output above `amount_out` goes to
+// `submitter_output_account`, a `target_mint` account of the submitter.
+#[derive(Clone, Debug)]
+pub struct ShieldedSwapParams {
+    pub a_proof: [u8; 64],
+    pub b_proof: [u8; 128],
+    pub c_proof: [u8; 64],
+    pub public_inputs: [u64; SWAP_PUBLIC_INPUTS],
+    pub merkle_root: [u8; 32],
+    pub nullifier_hash: [u8; 32],
+    pub root_archive_page: Option<u64>,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub output_commitment: [u8; 32],
+    pub target_program: Pubkey,
+    pub target_mint: Pubkey,
+    pub swap_program: Pubkey,
+    pub swap_data: Vec<u8>,
+    pub swap_accounts: Vec<AccountMeta>,
+    pub submitter_output_account: Pubkey,
+    pub circuit_version: u16,
+    pub plonk_proof: Vec<u8>,
+}
+
+// The vault authority's associated account for `mint`, where shielded swaps receive their output.
+pub fn swap_output_address(mint: &Pubkey) -> Pubkey {
+    anchor_spl::associated_token::get_associated_token_address(&vault_address(), mint)
+}
+
+// Creates swap_output_address(mint). anchor-spl only exposes the associated token program's
+// CPI, so its Create instruction is built here.
+pub fn create_swap_output_account(payer: Pubkey, mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: anchor_spl::associated_token::ID,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(swap_output_address(&mint), false),
+            AccountMeta::new_readonly(vault_address(), false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(anchor_spl::token::ID, false),
+        ],
+        data: Vec::new(),
+    }
+}
+
+pub fn shielded_swap(submitter: Pubkey, params: &ShieldedSwapParams) -> Instruction {
+    let target_pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &params.target_program).0;
+    let target_state = target_pda(&[b"program_state"]);
+    let target_vault = target_pda(&[b"program_token_vault", target_state.as_ref()]);
+    let accounts = privax_protocol::accounts::ShieldedSwap {
+        program_state: program_state_address(),
+        verifying_key: verifying_key_address(params.circuit_version),
+        fee_config: fee_config_address(),
+        nullifier_shard: nullifier_shard_address(nullifier_prefix(&params.nullifier_hash)),
+        nullifier_filter: nullifier_filter_address(),
+        merkle_tree: merkle_tree_address(),
+        root_archive: params.root_archive_page.map(root_archive_address),
+        submitter,
+        program_token_vault: vault_address(),
+        program_token_vault_authority: vault_address(),
+        swap_program: params.swap_program,
+        swap_output_account: swap_output_address(&params.target_mint),
+        submitter_output_account: params.submitter_output_account,
+        target_program: params.target_program,
+        target_program_state: target_state,
+        target_fee_config: target_pda(&[b"fee_config", target_state.as_ref()]),
+        target_merkle_tree: target_pda(&[b"merkle_tree", target_state.as_ref()]),
+        target_commitment_shard: target_pda(&[
+            b"commitment_shard",
+            target_state.as_ref(),
+            &CommitmentShard::prefix_of(&params.output_commitment),
+        ]),
+        target_vault,
+        target_vault_authority: target_vault,
+        target_token_mint: params.target_mint,
+        token_program: anchor_spl::token::ID,
+        system_program: system_program::ID,
+        rent: anchor_lang::solana_program::sysvar::rent::ID,
+    };
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(params.swap_accounts.iter().cloned());
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: metas,
+        data: privax_protocol::instruction::ShieldedSwap {
+            a_proof: params.a_proof,
+            b_proof: params.b_proof,
+            c_proof: params.c_proof,
+            public_inputs: params.public_inputs,
+            merkle_root: params.merkle_root,
+            nullifier_hash: params.nullifier_hash,
+            amount_in: params.amount_in,
+            amount_out: params.amount_out,
+            output_commitment: params.output_commitment,
+            swap_data: params.swap_data.clone(),
+            circuit_version: params.circuit_version,
+            plonk_proof: params.plonk_proof.clone(),
+        }
+        .data(),
+    }
+}
+
+// Arguments of a note split: one note into `output_commitments`, two to MAX_NOTE_SPLIT_OUTPUTS.
+#[derive(Clone, Debug)]
+pub struct SplitNoteParams {
+    pub a_proof: [u8; 64],
+    pub b_proof: [u8; 128],
+    pub c_proof: [u8; 64],
+    pub public_inputs: [u64; NOTE_TRANSFER_PUBLIC_INPUTS],
+    pub merkle_root: [u8; 32],
+    pub nullifier_hash: [u8; 32],
+    pub root_archive_page: Option<u64>,
+    pub output_commitments: Vec<[u8; 32]>,
+    pub circuit_version: u16,
+    pub plonk_proof: Vec<u8>,
+}
+
+// Arguments of a note merge: two to MAX_INPUT_NOTES notes into one, `nullifier_hash` being the
+// first note's and `extra_nullifier_hashes` the others'.
+#[derive(Clone, Debug)]
+pub struct MergeNotesParams {
+    pub a_proof: [u8; 64],
+    pub b_proof: [u8; 128],
+    pub c_proof: [u8; 64],
+    pub public_inputs: [u64; NOTE_TRANSFER_PUBLIC_INPUTS],
+    pub merkle_root: [u8; 32],
+    pub nullifier_hash: [u8; 32],
+    pub root_archive_page: Option<u64>,
+    pub extra_nullifier_hashes: Vec<[u8; 32]>,
+    pub output_commitment: [u8; 32],
+    pub circuit_version: u16,
+    pub plonk_proof: Vec<u8>,
+}
+
+impl MergeNotesParams {
+    // Hashes of the nullifiers spent: the first note's, then the extra notes'.
+    pub fn nullifier_hashes(&self) -> impl Iterator<Item = [u8; 32]> + '_ {
+        std::iter::once(self.nullifier_hash).chain(self.extra_nullifier_hashes.iter().copied())
+    }
+}
+
+fn shielded_transfer_accounts(payer: Pubkey, circuit_version: u16, first_nullifier: &[u8; 32], root_archive_page: Option<u64>) -> Vec<AccountMeta> {
+    privax_protocol::accounts::ShieldedTransfer {
+        program_state: program_state_address(),
+        verifying_key: verifying_key_address(circuit_version),
+        nullifier_shard: nullifier_shard_address(nullifier_prefix(first_nullifier)),
+        nullifier_filter: nullifier_filter_address(),
+        merkle_tree: merkle_tree_address(),
+        root_archive: root_archive_page.map(root_archive_address),
+        payer,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None)
+}
+
+pub fn split_note(payer: Pubkey, params: &SplitNoteParams) -> Instruction {
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: shielded_transfer_accounts(payer, params.circuit_version, &params.nullifier_hash, params.root_archive_page),
+        data: privax_protocol::instruction::SplitNote {
+            a_proof: params.a_proof,
+            b_proof: params.b_proof,
+            c_proof: params.c_proof,
+            public_inputs: params.public_inputs,
+            merkle_root: params.merkle_root,
+            nullifier_hash: params.nullifier_hash,
+            circuit_version: params.circuit_version,
+            output_commitments: params.output_commitments.clone(),
+            plonk_proof: params.plonk_proof.clone(),
+        }
+        .data(),
+    }
+}
+
+pub fn merge_notes(payer: Pubkey, params: &MergeNotesParams) -> Instruction {
+    let mut metas = shielded_transfer_accounts(payer, params.circuit_version, &params.nullifier_hash, params.root_archive_page);
+    // Shards of the nullifiers after the first go in the remaining accounts
+    metas.extend(
+        params
+            .extra_nullifier_hashes
+            .iter()
+            .map(|hash| AccountMeta::new(nullifier_shard_address(nullifier_prefix(hash)), false)),
+    );
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: metas,
+        data: privax_protocol::instruction::MergeNotes {
+            a_proof: params.a_proof,
+            b_proof: params.b_proof,
+            c_proof: params.c_proof,
+            public_inputs: params.public_inputs,
+            merkle_root: params.merkle_root,
+            nullifier_hash: params.nullifier_hash,
+            circuit_version: params.circuit_version,
+            extra_nullifier_hashes: params.extra_nullifier_hashes.clone(),
+            output_commitment: params.output_commitment,
+            plonk_proof: params.plonk_proof.clone(),
+        }
+        .data(),
+    }
+}
+
+// Terms of a recurring payment: `payments` payments of `amount` to `recipient`, one every
+// `interval_slots` from `first_payment_slot`, each paying `executor_fee` to whoever executes it.
+#[derive(Clone, Debug)]
+pub struct PaymentScheduleParams {
+    pub schedule_id: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub executor_fee: u64,
+    pub interval_slots: u64,
+    pub first_payment_slot: u64,
+    pub payments: u32,
+}
+
+pub fn create_payment_schedule(owner: Pubkey, token_mint: Pubkey, params: &PaymentScheduleParams) -> Instruction {
+    let schedule = payment_schedule_address(&owner, params.schedule_id);
+    let accounts = privax_protocol::accounts::CreatePaymentSchedule {
+        program_state: program_state_address(),
+        payment_schedule: schedule,
+        schedule_escrow: schedule_escrow_address(&schedule, &token_mint),
+        token_mint,
+        owner,
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        system_program: system_program::ID,
+        rent: anchor_lang::solana_program::sysvar::rent::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::CreatePaymentSchedule {
+            schedule_id: params.schedule_id,
+            recipient: params.recipient,
+            amount: params.amount,
+            executor_fee: params.executor_fee,
+            interval_slots: params.interval_slots,
+            first_payment_slot: params.first_payment_slot,
+            payments: params.payments,
+        }
+        .data(),
+    }
+}
+
+pub fn execute_scheduled_payment(
+    executor: Pubkey,
+    schedule: Pubkey,
+    token_mint: Pubkey,
+    recipient_token_account: Pubkey,
+    executor_token_account: Pubkey,
+) -> Instruction {
+    let accounts = privax_protocol::accounts::ExecuteScheduledPayment {
+        program_state: program_state_address(),
+        payment_schedule: schedule,
+        schedule_escrow: schedule_escrow_address(&schedule, &token_mint),
+        recipient_token_account,
+        executor,
+        executor_token_account,
+        token_program: anchor_spl::token::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::ExecuteScheduledPayment {}.data(),
+    }
+}
+
+pub fn cancel_payment_schedule(owner: Pubkey, schedule_id: u64, token_mint: Pubkey, owner_token_account: Pubkey) -> Instruction {
+    let schedule = payment_schedule_address(&owner, schedule_id);
+    let accounts = privax_protocol::accounts::CancelPaymentSchedule {
+        program_state: program_state_address(),
+        payment_schedule: schedule,
+        schedule_escrow: schedule_escrow_address(&schedule, &token_mint),
+        owner,
+        owner_token_account,
+        token_program: anchor_spl::token::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::CancelPaymentSchedule {}.data(),
+    }
+}
+
+// NFT pools hold each NFT at the vault authority's associated token account for its mint.
+pub fn nft_vault_address(mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(&vault_address(), mint)
+}
+
+// Owner of pNFT rule sets (Metaplex Token Auth Rules).
+pub const TOKEN_AUTH_RULES_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+
+// Marks an NFT as programmable, so it moves through Token Metadata with its token records.
+// `authorization_rules` is its rule set, if it has one.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProgrammableNft {
+    pub authorization_rules: Option<Pubkey>,
+}
+
+fn programmable_nft_accounts(
+    mint: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    programmable: Option<&ProgrammableNft>,
+) -> privax_protocol::accounts::ProgrammableNft {
+    let Some(programmable) = programmable else {
+        return privax_protocol::accounts::ProgrammableNft {
+            metadata: None,
+            edition: None,
+            owner_token_record: None,
+            destination_token_record: None,
+            token_metadata_program: None,
+            sysvar_instructions: None,
+            authorization_rules_program: None,
+            authorization_rules: None,
+        };
+    };
+    privax_protocol::accounts::ProgrammableNft {
+        metadata: Some(find_metadata_account(mint).0),
+        edition: Some(find_master_edition_account(mint).0),
+        owner_token_record: Some(find_token_record_account(mint, from).0),
+        destination_token_record: Some(find_token_record_account(mint, to).0),
+        token_metadata_program: Some(mpl_token_metadata::ID),
+        sysvar_instructions: Some(anchor_lang::solana_program::sysvar::instructions::ID),
+        authorization_rules_program: programmable.authorization_rules.map(|_| TOKEN_AUTH_RULES_PROGRAM_ID),
+        authorization_rules: programmable.authorization_rules,
+    }
+}
+
+pub fn enable_nft_mode(admin: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::EnableNftMode {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        merkle_tree: merkle_tree_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::EnableNftMode {}.data(),
+    }
+}
+
+pub fn deposit_nft(
+    user: Pubkey,
+    mint: Pubkey,
+    user_token_account: Pubkey,
+    commitment: [u8; 32],
+    programmable: Option<&ProgrammableNft>,
+) -> Instruction {
+    let nft_vault = nft_vault_address(&mint);
+    let accounts = privax_protocol::accounts::DepositNft {
+        program_state: program_state_address(),
+        merkle_tree: merkle_tree_address(),
+        commitment_shard: commitment_shard_address(CommitmentShard::prefix_of(&commitment)),
+        user,
+        nft_mint: mint,
+        user_token_account,
+        nft_vault,
+        program_token_vault_authority: vault_address(),
+        programmable: programmable_nft_accounts(&mint, &user_token_account, &nft_vault, programmable),
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::DepositNft { commitment }.data(),
+    }
+}
+
+// Arguments of an NFT withdrawal to `recipient`'s associated token account for `mint`.
+#[derive(Clone, Debug)]
+pub struct WithdrawNftParams {
+    pub a_proof: [u8; 64],
+    pub b_proof: [u8; 128],
+    pub c_proof: [u8; 64],
+    pub public_inputs: [u64; NFT_PUBLIC_INPUTS],
+    pub merkle_root: [u8; 32],
+    pub nullifier_hash: [u8; 32],
+    pub root_archive_page: Option<u64>,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub circuit_version: u16,
+    pub plonk_proof: Vec<u8>,
+}
+
+pub fn withdraw_nft(submitter: Pubkey, params: &WithdrawNftParams, programmable: Option<&ProgrammableNft>) -> Instruction {
+    let nft_vault = nft_vault_address(&params.mint);
+    let recipient_token_account = get_associated_token_address(&params.recipient, &params.mint);
+    let accounts = privax_protocol::accounts::WithdrawNft {
+        program_state: program_state_address(),
+        verifying_key: verifying_key_address(params.circuit_version),
+        nullifier_shard: nullifier_shard_address(nullifier_prefix(&params.nullifier_hash)),
+        nullifier_filter: nullifier_filter_address(),
+        merkle_tree: merkle_tree_address(),
+        root_archive: params.root_archive_page.map(root_archive_address),
+        user: submitter,
+        nft_mint: params.mint,
+        nft_vault,
+        program_token_vault_authority: vault_address(),
+        recipient: params.recipient,
+        recipient_token_account,
+        programmable: programmable_nft_accounts(&params.mint, &nft_vault, &recipient_token_account, programmable),
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::WithdrawNft {
+            a_proof: params.a_proof,
+            b_proof: params.b_proof,
+            c_proof: params.c_proof,
+            public_inputs: params.public_inputs,
+            merkle_root: params.merkle_root,
+            nullifier_hash: params.nullifier_hash,
+            circuit_version: params.circuit_version,
+            plonk_proof: params.plonk_proof.clone(),
+        }
+        .data(),
+    }
+}
+
+// Receipt NFT name, symbol and off-chain JSON URI (see ReceiptNftConfig for the limits).
+#[derive(Clone, Debug)]
+pub struct ReceiptNftMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+pub fn set_receipt_nft_config(
+    admin: Pubkey,
+    payer: Pubkey,
+    enabled: bool,
+    merkle_tree: Pubkey,
+    metadata: &ReceiptNftMetadata,
+) -> Instruction {
+    let accounts = privax_protocol::accounts::SetReceiptNftConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        receipt_nft_config: receipt_nft_config_address(),
+        admin,
+        payer,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetReceiptNftConfig {
+            enabled,
+            merkle_tree,
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+        }
+        .data(),
+    }
+}
+
+// `merkle_tree` is the configured receipt tree (ReceiptNftConfig::merkle_tree).
+pub fn claim_receipt_nft(depositor: Pubkey, leaf_index: u64, merkle_tree: Pubkey) -> Instruction {
+    let deposit_receipt = deposit_receipt_address(leaf_index);
+    let accounts = privax_protocol::accounts::ClaimReceiptNft {
+        program_state: program_state_address(),
+        receipt_nft_config: receipt_nft_config_address(),
+        deposit_receipt,
+        receipt_nft_claim: receipt_nft_claim_address(&deposit_receipt),
+        depositor,
+        tree_config: Pubkey::find_program_address(&[merkle_tree.as_ref()], &mpl_bubblegum::ID).0,
+        merkle_tree,
+        bubblegum_program: mpl_bubblegum::ID,
+        log_wrapper: mpl_bubblegum::programs::SPL_NOOP_ID,
+        compression_program: mpl_bubblegum::programs::SPL_ACCOUNT_COMPRESSION_ID,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::ClaimReceiptNft {}.data(),
+    }
+}
+
+// USD limits of the pool, in micro-USD (UsdLimits::USD_DECIMALS); 0 leaves a limit unset.
+#[derive(Clone, Debug)]
+pub struct UsdLimitsParams {
+    pub max_price_age: u64,
+    pub max_confidence_bps: u16,
+    pub max_deposit_usd: u64,
+    pub large_withdrawal_usd: u64,
+    pub flat_fee_usd: u64,
+}
+
+pub fn set_usd_limits(admin: Pubkey, payer: Pubkey, token_mint: Pubkey, price_feed: Pubkey, params: &UsdLimitsParams) -> Instruction {
+    let accounts = privax_protocol::accounts::SetUsdLimits {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        usd_limits: usd_limits_address(),
+        token_mint,
+        price_feed,
+        admin,
+        payer,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetUsdLimits {
+            max_price_age: params.max_price_age,
+            max_confidence_bps: params.max_confidence_bps,
+            max_deposit_usd: params.max_deposit_usd,
+            large_withdrawal_usd: params.large_withdrawal_usd,
+            flat_fee_usd: params.flat_fee_usd,
+        }
+        .data(),
+    }
+}
+
+// Only pools initialized before the audit log existed need this; admin operations fail until then.
+pub fn init_audit_log(payer: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::InitAuditLog {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        payer,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::InitAuditLog {}.data(),
+    }
+}
+
+// Admin operations. `pause` also accepts the guardian as authority; the fee config is
+// signed by its fee authority.
+pub fn pause(authority: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::Pause {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        authority,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::Pause {}.data(),
+    }
+}
+
+pub fn unpause(admin: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::Unpause {}.data(),
+    }
+}
+
+pub fn set_guardian(admin: Pubkey, new_guardian: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetGuardian { new_guardian }.data(),
+    }
+}
+
+pub fn set_admin_timelock(admin: Pubkey, slots: u64) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetAdminTimelock { slots }.data(),
+    }
+}
+
+// `id` must be the pool's current next_admin_action_id.
+pub fn queue_admin_action(admin: Pubkey, payer: Pubkey, id: u64, action: AdminAction) -> Instruction {
+    let accounts = privax_protocol::accounts::QueueAdminAction {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        queued_action: queued_admin_action_address(id),
+        admin,
+        payer,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::QueueAdminAction { action }.data(),
+    }
+}
+
+// `payer` paid for the queued action and gets its rent back.
+pub fn execute_admin_action(admin: Pubkey, payer: Pubkey, id: u64, action: &AdminAction) -> Instruction {
+    let verifying_key = match *action {
+        AdminAction::EnableVerifyingKey { circuit_version } => Some(verifying_key_address(circuit_version)),
+        _ => None,
+    };
+    let accounts = privax_protocol::accounts::ExecuteAdminAction {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        queued_action: queued_admin_action_address(id),
+        payer,
+        admin,
+        verifying_key,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::ExecuteAdminAction {}.data(),
+    }
+}
+
+// `authority` is the admin or the guardian.
+pub fn cancel_admin_action(authority: Pubkey, payer: Pubkey, id: u64) -> Instruction {
+    let accounts = privax_protocol::accounts::CancelAdminAction {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        queued_action: queued_admin_action_address(id),
+        payer,
+        authority,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::CancelAdminAction {}.data(),
+    }
+}
+
+pub fn add_swap_target(admin: Pubkey, target_program: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::AddSwapTarget { target_program }.data(),
+    }
+}
+
+pub fn remove_swap_target(admin: Pubkey, target_program: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::RemoveSwapTarget { target_program }.data(),
+    }
+}
+
+pub fn set_tree_operator(admin: Pubkey, new_tree_operator: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetTreeOperator { new_tree_operator }.data(),
+    }
+}
+
+pub fn set_withdrawal_limit(admin: Pubkey, max_withdrawal_per_epoch: u64, withdrawal_epoch_slots: u64) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetWithdrawalLimit { max_withdrawal_per_epoch, withdrawal_epoch_slots }.data(),
+    }
+}
+
+pub fn set_compliance_key(admin: Pubkey, compliance_key: [u8; 32]) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetComplianceKey { compliance_key }.data(),
+    }
+}
+
+pub fn set_kyc_attestor(admin: Pubkey, kyc_attestor: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetKycAttestor { kyc_attestor }.data(),
+    }
+}
+
+// Also renews an existing credential. `expires_at` is a unix time, or 0 for no expiry.
+pub fn issue_kyc_credential(attestor: Pubkey, holder: Pubkey, expires_at: i64) -> Instruction {
+    let accounts = privax_protocol::accounts::IssueKycCredential {
+        program_state: program_state_address(),
+        kyc_credential: kyc_credential_address(&holder),
+        attestor,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::IssueKycCredential { holder, expires_at }.data(),
+    }
+}
+
+pub fn revoke_kyc_credential(attestor: Pubkey, holder: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::RevokeKycCredential {
+        program_state: program_state_address(),
+        kyc_credential: kyc_credential_address(&holder),
+        attestor,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::RevokeKycCredential {}.data(),
+    }
+}
+
+pub fn set_max_daily_deposit(admin: Pubkey, max_daily_deposit: u64) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetMaxDailyDeposit { max_daily_deposit }.data(),
+    }
+}
+
+// Needed once per depositor before their first deposit while the pool caps daily deposits.
+pub fn init_deposit_volume(payer: Pubkey, depositor: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::InitDepositVolume {
+        program_state: program_state_address(),
+        deposit_volume: deposit_volume_address(&depositor),
+        payer,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::InitDepositVolume { depositor }.data(),
+    }
+}
+
+pub fn set_exclusion_operator(admin: Pubkey, exclusion_operator: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetExclusionOperator { exclusion_operator }.data(),
+    }
+}
+
+// Signed by the admin or the exclusion operator.
+pub fn publish_exclusion_root(operator: Pubkey, root: [u8; 32]) -> Instruction {
+    let accounts = privax_protocol::accounts::PublishExclusionRoot { program_state: program_state_address(), operator };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::PublishExclusionRoot { root }.data(),
+    }
+}
+
+pub fn set_exclusion_required(admin: Pubkey, exclusion_required: bool) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetExclusionRequired { exclusion_required }.data(),
+    }
+}
+
+pub fn set_max_pause_duration(admin: Pubkey, max_pause_slots: u64) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetMaxPauseDuration { max_pause_slots }.data(),
+    }
+}
+
+pub fn set_pause_on_shortfall(admin: Pubkey, enabled: bool) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetPauseOnShortfall { enabled }.data(),
+    }
+}
+
+// Permissionless; anyone can send it, e.g. a keeper after every slot with withdrawals.
+pub fn reconcile() -> Instruction {
+    let accounts = privax_protocol::accounts::Reconcile {
+        program_state: program_state_address(),
+        fee_config: fee_config_address(),
+        program_token_vault: vault_address(),
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::Reconcile {}.data(),
+    }
+}
+
+// Tree maintenance, signed by the tree operator or the admin.
+pub fn checkpoint_root(operator: Pubkey, payer: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::CheckpointRoot {
+        program_state: program_state_address(),
+        merkle_tree: merkle_tree_address(),
+        tree_checkpoints: tree_checkpoints_address(),
+        operator,
+        payer,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::CheckpointRoot {}.data(),
+    }
+}
+
+// `page` must be the one the tree's next unarchived root falls in:
+// MerkleTree::archived_roots / ROOT_ARCHIVE_PAGE_ROOTS.
+pub fn archive_roots(operator: Pubkey, payer: Pubkey, page: u64) -> Instruction {
+    let accounts = privax_protocol::accounts::ArchiveRoots {
+        program_state: program_state_address(),
+        merkle_tree: merkle_tree_address(),
+        root_archive: root_archive_address(page),
+        operator,
+        payer,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::ArchiveRoots { page }.data(),
+    }
+}
+
+pub fn set_archived_roots_accepted(admin: Pubkey, accept: bool) -> Instruction {
+    let accounts = privax_protocol::accounts::SetArchivedRootsAccepted {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        merkle_tree: merkle_tree_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetArchivedRootsAccepted { accept }.data(),
+    }
+}
+
+pub fn transfer_ownership(admin: Pubkey, new_admin: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::TransferOwnership {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::TransferOwnership { new_admin }.data(),
+    }
+}
+
+// The two steps of renouncing the admin; renounce_admin only succeeds in a later slot than
+// initiate_renounce_admin.
+pub fn initiate_renounce_admin(admin: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::InitiateRenounceAdmin { confirmation: privax_protocol::privax_protocol::RENOUNCE_ADMIN_CONFIRMATION }.data(),
+    }
+}
+
+pub fn renounce_admin(admin: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::RenounceAdmin { confirmation: privax_protocol::privax_protocol::RENOUNCE_ADMIN_CONFIRMATION }.data(),
+    }
+}
+
+pub fn update_fee_config(fee_authority: Pubkey, protocol_fee_bps: u16, max_protocol_fee: u64) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateFeeConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        fee_config: fee_config_address(),
+        fee_authority,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::UpdateFeeConfig { protocol_fee_bps, max_protocol_fee }.data(),
+    }
+}
+
+pub fn set_fee_swap_program(fee_authority: Pubkey, fee_swap_program: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateFeeConfig {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        fee_config: fee_config_address(),
+        fee_authority,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetFeeSwapProgram { fee_swap_program }.data(),
+    }
+}
+
+pub fn set_verifying_key_enabled(admin: Pubkey, circuit_version: u16, enabled: bool) -> Instruction {
+    let accounts = privax_protocol::accounts::UpdateVerifyingKey {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        verifying_key: verifying_key_address(circuit_version),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::SetVerifyingKeyEnabled { enabled }.data(),
+    }
+}
+
+// `payer` funds the relayer's registry account, so the admin doesn't have to hold SOL.
+pub fn add_relayer(
+    admin: Pubkey,
+    payer: Pubkey,
+    relayer: Pubkey,
+    endpoint_url_hash: [u8; 32],
+    fee_schedule: FeeSchedule,
+    expiry_slot: Option<u64>,
+) -> Instruction {
+    let accounts = privax_protocol::accounts::AddRelayer {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        relayer_info: relayer_info_address(&relayer),
+        admin,
+        payer,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::AddRelayer { relayer_address: relayer, endpoint_url_hash, fee_schedule, expiry_slot }
+            .data(),
+    }
+}
+
+pub fn renew_relayer(admin: Pubkey, relayer: Pubkey, expiry_slot: Option<u64>) -> Instruction {
+    let accounts = privax_protocol::accounts::RenewRelayer {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        relayer_info: relayer_info_address(&relayer),
+        admin,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::RenewRelayer { relayer_address: relayer, expiry_slot }.data(),
+    }
+}
+
+pub fn remove_relayer(admin: Pubkey, relayer: Pubkey, rent_receiver: Pubkey) -> Instruction {
+    let accounts = privax_protocol::accounts::RemoveRelayer {
+        program_state: program_state_address(),
+        audit_log: audit_log_address(),
+        relayer_info: relayer_info_address(&relayer),
+        admin,
+        rent_receiver,
+    };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_protocol::instruction::RemoveRelayer { relayer_address: relayer }.data(),
+    }
+}
+
+// Test-only instructions of `devnet` program builds; mainnet builds reject them.
+#[cfg(feature = "devnet")]
+pub fn force_insert_leaf(admin: Pubkey, commitment: [u8; 32]) -> Instruction {
+    use privax_protocol::devnet;
+    let accounts =
+        devnet::accounts::ForceInsertLeaf { program_state: program_state_address(), merkle_tree: merkle_tree_address(), admin };
+    Instruction {
+        program_id: privax_protocol::ID,
+        accounts: accounts.to_account_metas(None),
+        data: devnet::instruction::ForceInsertLeaf { commitment }.data(),
+    }
+}
+
+// Empties the tree and nullifier filter, and the nullifier and commitment shards with the given
+// prefixes.
+#[cfg(feature = "devnet")]
+pub fn reset_pool(admin: Pubkey, nullifier_shard_prefixes: &[[u8; 2]], commitment_shard_prefixes: &[[u8; 2]]) -> Instruction {
+    use privax_protocol::devnet;
+    let accounts = devnet::accounts::ResetPool {
+        program_state: program_state_address(),
+        merkle_tree: merkle_tree_address(),
+        nullifier_filter: nullifier_filter_address(),
+        admin,
+    };
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(nullifier_shard_prefixes.iter().map(|prefix| AccountMeta::new(nullifier_shard_address(*prefix), false)));
+    metas.extend(commitment_shard_prefixes.iter().map(|prefix| AccountMeta::new(commitment_shard_address(*prefix), false)));
+    Instruction { program_id: privax_protocol::ID, accounts: metas, data: devnet::instruction::ResetPool {}.data() }
+}
@@ -0,0 +1,32 @@
+// Rust SDK for the Privax Protocol program.
+//
+// `instructions` builds the program's instructions and derives its PDAs without any I/O.
+// `nonblocking::PrivaxClient` sends them over the async (tokio) RPC client through an
+// `rpc_pool::RpcPool`, and with the `blocking` feature `blocking::PrivaxClient` offers the
+// same calls synchronously. Failures come back as `PrivaxClientError`, with program errors
+// decoded into `PrivaxError` variants, and `estimate` reports what a transaction will cost
+// before it is sent. `accounts::PrivaxAccounts` reads the pool's accounts in batches, with an
+// optional TTL cache, for callers that poll them. `payment_request` encodes shielded payment
+// requests as URLs, and `payroll` plans and tracks payroll batches paid through a relayer.
+// `sns` resolves .sol domains given as recipients or relayers. Clients can be built from a
+// `privax_config::Profile`, re-exported as `config`. With the `logging` feature, `logging`
+// sets up the structured logs the command-line tools write.
+pub mod accounts;
+pub mod error;
+pub mod estimate;
+pub mod instructions;
+pub mod nonblocking;
+pub mod payment_request;
+pub mod payroll;
+pub mod rpc_pool;
+pub mod sns;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "logging")]
+pub mod logging;
+
+pub use error::PrivaxClientError;
+pub use privax_config as config;
+
+pub type Result<T> = std::result::Result<T, PrivaxClientError>;
@@ -0,0 +1,154 @@
+// Structured logs for the off-chain tools, through `tracing`. PRIVAX_LOG sets levels per module
+// as a `target=level` list with an optional default level (e.g. `warn,privax_cli=debug`), and
+// PRIVAX_LOG_FORMAT=json writes one JSON object per event instead of text. Logs go to stderr,
+// leaving stdout to the tool's output, and note strings are redacted in either format.
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Write};
+
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::{FormatTime, SystemTime};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+
+pub const LOG_ENV: &str = "PRIVAX_LOG";
+pub const LOG_FORMAT_ENV: &str = "PRIVAX_LOG_FORMAT";
+
+// A backed-up note (privax_mobile::note) is `privax-note-v1-<amount>-<secret>-<nullifier secret>`.
+const NOTE_PREFIX: &str = "privax-note-v1-";
+const REDACTED: &str = "<redacted>";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, String> {
+        match format {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown log format {format:?} (expected text or json)")),
+        }
+    }
+}
+
+// Installs the global subscriber from PRIVAX_LOG and PRIVAX_LOG_FORMAT, with `default_filter`
+// when PRIVAX_LOG is unset. Fails on a malformed setting or if a subscriber is already set.
+pub fn init(default_filter: &str) -> Result<(), String> {
+    let filter = std::env::var(LOG_ENV).unwrap_or_else(|_| default_filter.to_string());
+    let targets: Targets = filter.parse().map_err(|err| format!("invalid {LOG_ENV} {filter:?}: {err}"))?;
+    let format = match std::env::var(LOG_FORMAT_ENV) {
+        Ok(format) => format.parse().map_err(|err| format!("invalid {LOG_FORMAT_ENV}: {err}"))?,
+        Err(_) => LogFormat::Text,
+    };
+    let layer = tracing_subscriber::fmt::layer().with_writer(RedactedStderr);
+    let registry = tracing_subscriber::registry();
+    match format {
+        LogFormat::Text => registry.with(layer.with_filter(targets)).try_init(),
+        LogFormat::Json => registry.with(layer.event_format(JsonFormat).with_filter(targets)).try_init(),
+    }
+    .map_err(|err| err.to_string())
+}
+
+// `text` with the secrets of every note string in it replaced, keeping the prefix so a reader
+// can tell a note was there.
+pub fn redact(text: &str) -> Cow<'_, str> {
+    if !text.contains(NOTE_PREFIX) {
+        return Cow::Borrowed(text);
+    }
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(NOTE_PREFIX) {
+        let (before, note) = rest.split_at(start + NOTE_PREFIX.len());
+        redacted.push_str(before);
+        // The amount and both secrets are digits, hex and dashes; a bare prefix (such as an
+        // already redacted note) is left alone
+        let end = note.find(|c: char| !(c.is_ascii_hexdigit() || c == '-')).unwrap_or(note.len());
+        if end > 0 {
+            redacted.push_str(REDACTED);
+        }
+        rest = &note[end..];
+    }
+    redacted.push_str(rest);
+    Cow::Owned(redacted)
+}
+
+// The fmt layer writes each formatted event in one call, so a note never straddles two writes.
+struct RedactedStderr;
+
+impl<'a> MakeWriter<'a> for RedactedStderr {
+    type Writer = RedactedStderr;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactedStderr
+    }
+}
+
+impl Write for RedactedStderr {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stderr().write_all(redact(&String::from_utf8_lossy(buf)).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+// {"timestamp", "level", "target", "message", ...the event's other fields}
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, _ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let mut timestamp = String::new();
+        SystemTime.format_time(&mut Writer::new(&mut timestamp))?;
+        let metadata = event.metadata();
+        let mut object = Map::new();
+        object.insert("timestamp".to_string(), timestamp.into());
+        object.insert("level".to_string(), metadata.level().to_string().into());
+        object.insert("target".to_string(), metadata.target().into());
+        event.record(&mut JsonFields(&mut object));
+        writeln!(writer, "{}", Value::Object(object))
+    }
+}
+
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonFields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{value:?}").into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+}
@@ -0,0 +1,552 @@
+// Async client over the tokio-based RPC client. Every request goes through an RpcPool, so
+// transient RPC failures are retried and fail over to other endpoints.
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::token::{Mint, TokenAccount};
+use mpl_token_metadata::state::{ProgrammableConfig, TokenStandard};
+use privax_config::Profile;
+use privax_protocol::{
+    AuditLog, DepositVolume, FeeConfig, MerkleTree, PaymentSchedule, ProgramState, QueuedAdminAction, ReceiptNftConfig, RootArchivePage,
+    TreeCheckpoints, UsdLimits, ROOT_ARCHIVE_PAGE_ROOTS,
+};
+use solana_account_decoder::UiAccountEncoding;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+use crate::estimate::{self, CostEstimate};
+use crate::instructions::{
+    self, DepositGates, MergeNotesParams, PaymentScheduleParams, ProgrammableNft, ShieldedSwapParams, SplitNoteParams, WithdrawNftParams,
+    WithdrawParams, WithdrawSplitParams,
+};
+use crate::rpc_pool::RpcPool;
+use crate::sns::{self, Domain};
+use crate::{PrivaxClientError, Result};
+
+pub struct PrivaxClient {
+    pool: RpcPool,
+    payer: Box<dyn Signer + Send + Sync>, // A keypair, or a hardware wallet
+}
+
+impl PrivaxClient {
+    pub fn new(rpc_url: impl ToString, payer: impl Signer + Send + Sync + 'static) -> Self {
+        Self::with_pool(RpcPool::single(rpc_url), payer)
+    }
+
+    pub fn with_pool(pool: RpcPool, payer: impl Signer + Send + Sync + 'static) -> Self {
+        Self { pool, payer: Box::new(payer) }
+    }
+
+    // Over the profile's RPC endpoints. Instructions always target privax_protocol::ID, so a
+    // profile for another program ID is refused rather than sent to the wrong program.
+    pub fn from_profile(profile: &Profile, payer: impl Signer + Send + Sync + 'static) -> Result<Self> {
+        check_program(profile)?;
+        Ok(Self::with_pool(RpcPool::from_profile(profile, CommitmentConfig::confirmed()), payer))
+    }
+
+    pub fn pool(&self) -> &RpcPool {
+        &self.pool
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    pub async fn program_state(&self) -> Result<ProgramState> {
+        let address = instructions::program_state_address();
+        let account = self.pool.call(|rpc| rpc.get_account(&address)).await?;
+        decode(address, Some(&account))
+    }
+
+    // An admin action waiting out the admin timelock, by the id it was queued under.
+    pub async fn queued_admin_action(&self, id: u64) -> Result<QueuedAdminAction> {
+        let address = instructions::queued_admin_action_address(id);
+        let account = self.pool.call(|rpc| rpc.get_account(&address)).await?;
+        decode(address, Some(&account))
+    }
+
+    // The pool's token mint, e.g. for its decimals.
+    pub async fn pool_mint(&self) -> Result<(Pubkey, Mint)> {
+        let address = self.program_state().await?.token_mint;
+        let account = self.pool.call(|rpc| rpc.get_account(&address)).await?;
+        Ok((address, decode(address, Some(&account))?))
+    }
+
+    // The current slot, e.g. for a proof's proofSlot.
+    pub async fn slot(&self) -> Result<u64> {
+        Ok(self.pool.call(|rpc| rpc.get_slot()).await?)
+    }
+
+    // Signs with the payer (and `signers`) and waits for confirmation. Each attempt fetches
+    // its own blockhash, so a retry after "blockhash not found" re-signs against a fresh one.
+    pub async fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        let signature = self
+            .pool
+            .call(|rpc| async move {
+                let blockhash = rpc.get_latest_blockhash().await?;
+                let tx = {
+                    let mut all_signers: Vec<&dyn Signer> = vec![self.payer.as_ref()];
+                    all_signers.extend(signers.iter().map(|signer| *signer as &dyn Signer));
+                    Transaction::new_signed_with_payer(instructions, Some(&self.payer.pubkey()), &all_signers, blockhash)
+                };
+                rpc.send_and_confirm_transaction(&tx).await
+            })
+            .await?;
+        Ok(signature)
+    }
+
+    // Sends a transaction that is already fully signed, e.g. one signed offline.
+    pub async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        Ok(self.pool.call(|rpc| rpc.send_and_confirm_transaction(transaction)).await?)
+    }
+
+    // Simulates `instructions` as the payer would send them. Returns the compute units
+    // consumed and the network fee.
+    async fn simulate(&self, instructions: &[Instruction]) -> Result<(u64, u64)> {
+        let config = RpcSimulateTransactionConfig { sig_verify: false, replace_recent_blockhash: true, ..Default::default() };
+        let (result, network_fee) = self
+            .pool
+            .call(|rpc| {
+                let config = config.clone();
+                async move {
+                    let blockhash = rpc.get_latest_blockhash().await?;
+                    let message = Message::new_with_blockhash(instructions, Some(&self.payer.pubkey()), &blockhash);
+                    let network_fee = rpc.get_fee_for_message(&message).await?;
+                    let result = rpc.simulate_transaction_with_config(&Transaction::new_unsigned(message), config).await?;
+                    Ok((result.value, network_fee))
+                }
+            })
+            .await?;
+        if let Some(err) = result.err {
+            let logs = result.logs.unwrap_or_default();
+            return Err(PrivaxClientError::from_transaction_error(&err, &logs)
+                .unwrap_or(PrivaxClientError::Simulation { err, logs }));
+        }
+        Ok((result.units_consumed.unwrap_or_default(), network_fee))
+    }
+
+    pub async fn deposit(&self, user_token_account: Pubkey, amount: u64, commitment: [u8; 32]) -> Result<Signature> {
+        let state = self.program_state().await?;
+        let usd_price_feed = self.usd_price_feed(&state).await?;
+        let mut ixs: Vec<Instruction> = self.open_deposit_volume(&state).await?.into_iter().collect();
+        let gates = DepositGates::for_pool(&state);
+        ixs.push(instructions::deposit(self.payer.pubkey(), user_token_account, state.token_mint, amount, commitment, usd_price_feed, gates));
+        self.send(&ixs, &[]).await
+    }
+
+    // Opens the payer's deposit volume account before their first deposit into a pool that caps
+    // daily deposits.
+    async fn open_deposit_volume(&self, state: &ProgramState) -> Result<Option<Instruction>> {
+        if state.max_daily_deposit == 0 || self.deposit_volume(&self.payer.pubkey()).await?.is_some() {
+            return Ok(None);
+        }
+        Ok(Some(instructions::init_deposit_volume(self.payer.pubkey(), self.payer.pubkey())))
+    }
+
+    // A depositor's recent deposits, None before their first deposit into a capped pool.
+    pub async fn deposit_volume(&self, depositor: &Pubkey) -> Result<Option<DepositVolume>> {
+        let addresses = [instructions::deposit_volume_address(depositor)];
+        let accounts = self.pool.call(|rpc| rpc.get_multiple_accounts(&addresses)).await?;
+        accounts[0].as_ref().map(|account| decode(addresses[0], Some(account))).transpose()
+    }
+
+    // Deposits into a pool with a compliance key, attesting to `envelope_hash` in the same
+    // transaction.
+    pub async fn deposit_attested(
+        &self,
+        user_token_account: Pubkey,
+        amount: u64,
+        commitment: [u8; 32],
+        envelope_hash: [u8; 32],
+    ) -> Result<Signature> {
+        let state = self.program_state().await?;
+        let usd_price_feed = self.usd_price_feed(&state).await?;
+        let depositor = self.payer.pubkey();
+        let mut ixs: Vec<Instruction> = self.open_deposit_volume(&state).await?.into_iter().collect();
+        ixs.push(instructions::attest_deposit(depositor, commitment, envelope_hash));
+        let gates = DepositGates { compliance_attestation: true, ..DepositGates::for_pool(&state) };
+        ixs.push(instructions::deposit(depositor, user_token_account, state.token_mint, amount, commitment, usd_price_feed, gates));
+        self.send(&ixs, &[]).await
+    }
+
+    // Deposits carry no protocol fee, so only the network cost varies, plus the deposit volume
+    // account's rent on a first deposit into a capped pool.
+    pub async fn estimate_deposit(&self, user_token_account: Pubkey, amount: u64, commitment: [u8; 32]) -> Result<CostEstimate> {
+        let state = self.program_state().await?;
+        let usd_price_feed = self.usd_price_feed(&state).await?;
+        let mut ixs: Vec<Instruction> = self.open_deposit_volume(&state).await?.into_iter().collect();
+        let rent = if ixs.is_empty() {
+            0
+        } else {
+            self.pool.call(|rpc| rpc.get_minimum_balance_for_rent_exemption(DepositVolume::SPACE)).await?
+        };
+        let gates = DepositGates::for_pool(&state);
+        ixs.push(instructions::deposit(self.payer.pubkey(), user_token_account, state.token_mint, amount, commitment, usd_price_feed, gates));
+        let (compute_units, network_fee) = self.simulate(&ixs).await?;
+        Ok(CostEstimate { compute_units, network_fee, rent, net_amount: amount, ..Default::default() })
+    }
+
+    // Opens any nullifier shard the withdrawal needs that doesn't exist yet, in the same transaction.
+    pub async fn withdraw(&self, params: &WithdrawParams) -> Result<Signature> {
+        let mut params = params.clone();
+        if params.usd_price_feed.is_none() {
+            params.usd_price_feed = self.usd_price_feed(&self.program_state().await?).await?;
+        }
+        let params = &params;
+        let shards = shard_spends(params);
+        let addresses: Vec<Pubkey> = shards.iter().map(|(prefix, _)| instructions::nullifier_shard_address(*prefix)).collect();
+        let existing = self.pool.call(|rpc| rpc.get_multiple_accounts(&addresses)).await?;
+        let ixs = self.withdraw_instructions(params, &shards, &existing);
+        self.send(&ixs, &[]).await
+    }
+
+    // Includes the rent for new nullifier shards and for growing existing ones.
+    pub async fn estimate_withdraw(&self, params: &WithdrawParams) -> Result<CostEstimate> {
+        let shards = shard_spends(params);
+        let mut addresses =
+            vec![instructions::program_state_address(), instructions::fee_config_address(), instructions::vault_address(), sysvar::rent::ID];
+        addresses.extend(shards.iter().map(|(prefix, _)| instructions::nullifier_shard_address(*prefix)));
+        let accounts = self.pool.call(|rpc| rpc.get_multiple_accounts(&addresses)).await?;
+
+        let state: ProgramState = decode(addresses[0], accounts[0].as_ref())?;
+        let fee_config: FeeConfig = decode(addresses[1], accounts[1].as_ref())?;
+        let vault: TokenAccount = decode(addresses[2], accounts[2].as_ref())?;
+        let rent: Rent = accounts[3]
+            .as_ref()
+            .and_then(solana_sdk::account::from_account)
+            .ok_or_else(|| PrivaxClientError::Decode(addresses[3], "missing rent sysvar".to_string()))?;
+
+        let mut params = params.clone();
+        if params.usd_price_feed.is_none() {
+            params.usd_price_feed = self.usd_price_feed(&state).await?;
+        }
+        let params = &params;
+
+        let existing = &accounts[4..];
+        let rent = shards.iter().zip(existing).map(|((_, spends), shard)| estimate::shard_rent(&rent, shard.as_ref(), *spends)).sum();
+        let protocol_fee = estimate::protocol_fee(&fee_config, vault.amount, state.outstanding_deposits, params.amount)
+            .saturating_add(self.usd_flat_fee(&state).await?);
+        let (compute_units, network_fee) = self.simulate(&self.withdraw_instructions(params, &shards, existing)).await?;
+        Ok(CostEstimate {
+            compute_units,
+            network_fee,
+            rent,
+            protocol_fee,
+            relayer_fee: params.relayer_fee,
+            net_amount: params.amount.saturating_sub(protocol_fee).saturating_sub(params.relayer_fee),
+        })
+    }
+
+    // Opens the nullifier shard if it doesn't exist yet, in the same transaction.
+    pub async fn withdraw_split(&self, params: &WithdrawSplitParams) -> Result<Signature> {
+        let state = self.program_state().await?;
+        let mut params = params.clone();
+        if params.usd_price_feed.is_none() {
+            params.usd_price_feed = self.usd_price_feed(&state).await?;
+        }
+        let params = &params;
+        let prefix = instructions::nullifier_prefix(&params.nullifier_hash);
+        let addresses = [instructions::nullifier_shard_address(prefix)];
+        let existing = self.pool.call(|rpc| rpc.get_multiple_accounts(&addresses)).await?;
+        let mut ixs = Vec::new();
+        if existing[0].is_none() {
+            ixs.push(instructions::init_nullifier_shard(self.payer.pubkey(), prefix));
+        }
+        ixs.push(instructions::withdraw_split(self.payer.pubkey(), state.token_mint, params));
+        self.send(&ixs, &[]).await
+    }
+
+    // Opens the nullifier shard and the vault's output account for the target mint if they
+    // don't exist yet, in the same transaction.
+    pub async fn shielded_swap(&self, params: &ShieldedSwapParams) -> Result<Signature> {
+        let prefix = instructions::nullifier_prefix(&params.nullifier_hash);
+        let addresses = [instructions::nullifier_shard_address(prefix), instructions::swap_output_address(&params.target_mint)];
+        let existing = self.pool.call(|rpc| rpc.get_multiple_accounts(&addresses)).await?;
+        let mut ixs = Vec::new();
+        if existing[0].is_none() {
+            ixs.push(instructions::init_nullifier_shard(self.payer.pubkey(), prefix));
+        }
+        if existing[1].is_none() {
+            ixs.push(instructions::create_swap_output_account(self.payer.pubkey(), params.target_mint));
+        }
+        ixs.push(instructions::shielded_swap(self.payer.pubkey(), params));
+        self.send(&ixs, &[]).await
+    }
+
+    // Opens the nullifier shard if it doesn't exist yet, in the same transaction.
+    pub async fn split_note(&self, params: &SplitNoteParams) -> Result<Signature> {
+        let mut ixs = self.open_missing_shards(std::iter::once(params.nullifier_hash)).await?;
+        ixs.push(instructions::split_note(self.payer.pubkey(), params));
+        self.send(&ixs, &[]).await
+    }
+
+    // Opens any nullifier shard the merge needs that doesn't exist yet, in the same transaction.
+    pub async fn merge_notes(&self, params: &MergeNotesParams) -> Result<Signature> {
+        let mut ixs = self.open_missing_shards(params.nullifier_hashes()).await?;
+        ixs.push(instructions::merge_notes(self.payer.pubkey(), params));
+        self.send(&ixs, &[]).await
+    }
+
+    // The schedule starts with an empty escrow; fund it by withdrawing notes to
+    // schedule_escrow_address, with the schedule as recipient.
+    pub async fn create_payment_schedule(&self, params: &PaymentScheduleParams) -> Result<Signature> {
+        let token_mint = self.program_state().await?.token_mint;
+        let ix = instructions::create_payment_schedule(self.payer.pubkey(), token_mint, params);
+        self.send(&[ix], &[]).await
+    }
+
+    pub async fn payment_schedule(&self, address: Pubkey) -> Result<PaymentSchedule> {
+        let account = self.pool.call(|rpc| rpc.get_account(&address)).await?;
+        decode(address, Some(&account))
+    }
+
+    // Schedules with a payment due now, for executors to work through.
+    pub async fn due_payment_schedules(&self) -> Result<Vec<(Pubkey, PaymentSchedule)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(PaymentSchedule::SPACE as u64),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, PaymentSchedule::DISCRIMINATOR.to_vec())),
+            ]),
+            account_config: RpcAccountInfoConfig { encoding: Some(UiAccountEncoding::Base64), ..Default::default() },
+            ..Default::default()
+        };
+        let slot = self.pool.call(|rpc| rpc.get_slot()).await?;
+        let accounts = self
+            .pool
+            .call(|rpc| {
+                let config = config.clone();
+                async move { rpc.get_program_accounts_with_config(&privax_protocol::ID, config).await }
+            })
+            .await?;
+        let mut due = Vec::new();
+        for (address, account) in accounts {
+            let schedule: PaymentSchedule = decode(address, Some(&account))?;
+            if schedule.is_due(slot) {
+                due.push((address, schedule));
+            }
+        }
+        Ok(due)
+    }
+
+    // Pays a due installment to the recipient's associated token account and the executor fee
+    // to the payer's.
+    pub async fn execute_scheduled_payment(&self, schedule: Pubkey) -> Result<Signature> {
+        let token_mint = self.program_state().await?.token_mint;
+        let recipient = self.payment_schedule(schedule).await?.recipient;
+        let ix = instructions::execute_scheduled_payment(
+            self.payer.pubkey(),
+            schedule,
+            token_mint,
+            get_associated_token_address(&recipient, &token_mint),
+            get_associated_token_address(&self.payer.pubkey(), &token_mint),
+        );
+        self.send(&[ix], &[]).await
+    }
+
+    // Refunds what's left in the escrow to the payer's associated token account.
+    pub async fn cancel_payment_schedule(&self, schedule_id: u64) -> Result<Signature> {
+        let token_mint = self.program_state().await?.token_mint;
+        let owner_token_account = get_associated_token_address(&self.payer.pubkey(), &token_mint);
+        let ix = instructions::cancel_payment_schedule(self.payer.pubkey(), schedule_id, token_mint, owner_token_account);
+        self.send(&[ix], &[]).await
+    }
+
+    // Deposits the NFT from the payer's associated token account into an NFT pool.
+    pub async fn deposit_nft(&self, mint: Pubkey, commitment: [u8; 32]) -> Result<Signature> {
+        let programmable = self.programmable_nft(mint).await?;
+        let user_token_account = get_associated_token_address(&self.payer.pubkey(), &mint);
+        let ix = instructions::deposit_nft(self.payer.pubkey(), mint, user_token_account, commitment, programmable.as_ref());
+        self.send(&[ix], &[]).await
+    }
+
+    // Opens the nullifier shard if it doesn't exist yet, in the same transaction; the program
+    // opens the recipient's token account.
+    pub async fn withdraw_nft(&self, params: &WithdrawNftParams) -> Result<Signature> {
+        let programmable = self.programmable_nft(params.mint).await?;
+        let mut ixs = self.open_missing_shards(std::iter::once(params.nullifier_hash)).await?;
+        ixs.push(instructions::withdraw_nft(self.payer.pubkey(), params, programmable.as_ref()));
+        self.send(&ixs, &[]).await
+    }
+
+    // Whether an NFT moves through Token Metadata, from its metadata's token standard. An NFT
+    // without metadata is a plain SPL token.
+    async fn programmable_nft(&self, mint: Pubkey) -> Result<Option<ProgrammableNft>> {
+        let addresses = [mpl_token_metadata::pda::find_metadata_account(&mint).0];
+        let accounts = self.pool.call(|rpc| rpc.get_multiple_accounts(&addresses)).await?;
+        if accounts[0].is_none() {
+            return Ok(None);
+        }
+        let metadata: MetadataAccount = decode(addresses[0], accounts[0].as_ref())?;
+        let programmable = matches!(
+            metadata.token_standard,
+            Some(TokenStandard::ProgrammableNonFungible | TokenStandard::ProgrammableNonFungibleEdition)
+        );
+        let authorization_rules = match metadata.programmable_config {
+            Some(ProgrammableConfig::V1 { rule_set }) => rule_set,
+            None => None,
+        };
+        Ok(programmable.then_some(ProgrammableNft { authorization_rules }))
+    }
+
+    pub async fn receipt_nft_config(&self) -> Result<ReceiptNftConfig> {
+        let address = instructions::receipt_nft_config_address();
+        let account = self.pool.call(|rpc| rpc.get_account(&address)).await?;
+        decode(address, Some(&account))
+    }
+
+    pub async fn tree_checkpoints(&self) -> Result<TreeCheckpoints> {
+        let address = instructions::tree_checkpoints_address();
+        let account = self.pool.call(|rpc| rpc.get_account(&address)).await?;
+        decode(address, Some(&account))
+    }
+
+    pub async fn audit_log(&self) -> Result<AuditLog> {
+        let address = instructions::audit_log_address();
+        let account = self.pool.call(|rpc| rpc.get_account(&address)).await?;
+        decode(address, Some(&account))
+    }
+
+    // Pins the current root; the payer signs as the tree operator (or the admin).
+    pub async fn checkpoint_root(&self) -> Result<Signature> {
+        self.send(&[instructions::checkpoint_root(self.payer.pubkey(), self.payer.pubkey())], &[]).await
+    }
+
+    pub async fn merkle_tree(&self) -> Result<MerkleTree> {
+        let address = instructions::merkle_tree_address();
+        let account = self.pool.call(|rpc| rpc.get_account(&address)).await?;
+        decode(address, Some(&account))
+    }
+
+    pub async fn root_archive_page(&self, page: u64) -> Result<RootArchivePage> {
+        let address = instructions::root_archive_address(page);
+        let account = self.pool.call(|rpc| rpc.get_account(&address)).await?;
+        decode(address, Some(&account))
+    }
+
+    // Archives the roots not archived yet, up to the end of their page; the payer signs as the
+    // tree operator (or the admin). Call again while MerkleTree::archived_roots lags root_count.
+    pub async fn archive_roots(&self) -> Result<Signature> {
+        let page = self.merkle_tree().await?.archived_roots / ROOT_ARCHIVE_PAGE_ROOTS as u64;
+        self.send(&[instructions::archive_roots(self.payer.pubkey(), self.payer.pubkey(), page)], &[]).await
+    }
+
+    // The owner of a .sol domain, once its reverse record confirms it (see `sns`).
+    pub async fn resolve_domain(&self, domain: &str) -> Result<Pubkey> {
+        let invalid = |reason| PrivaxClientError::Address { address: domain.to_string(), reason };
+        let parsed = Domain::parse(domain).ok_or_else(|| invalid("not a name.sol or sub.name.sol domain"))?;
+        let addresses = [parsed.address(), parsed.reverse_address()];
+        let accounts = self.pool.call(|rpc| rpc.get_multiple_accounts(&addresses)).await?;
+        parsed.verify(accounts[0].as_ref(), accounts[1].as_ref()).map_err(invalid)
+    }
+
+    // A recipient or relayer as a user gives it: a base58 public key, or a .sol domain.
+    pub async fn resolve_address(&self, address: &str) -> Result<Pubkey> {
+        if sns::is_domain(address) {
+            return self.resolve_domain(address).await;
+        }
+        address
+            .parse()
+            .map_err(|_| PrivaxClientError::Address { address: address.to_string(), reason: "not a public key or a .sol domain" })
+    }
+
+    pub async fn usd_limits(&self) -> Result<UsdLimits> {
+        let address = instructions::usd_limits_address();
+        let account = self.pool.call(|rpc| rpc.get_account(&address)).await?;
+        decode(address, Some(&account))
+    }
+
+    // The Pyth price account deposits and withdrawals must pass while the pool has USD limits.
+    pub async fn usd_price_feed(&self, state: &ProgramState) -> Result<Option<Pubkey>> {
+        if !state.usd_limits {
+            return Ok(None);
+        }
+        Ok(Some(self.usd_limits().await?.price_feed))
+    }
+
+    // The flat USD fee in pool token units at the feed's latest price, as the program would
+    // add it to a withdrawal's protocol fee. The program also rejects a stale price; this doesn't.
+    async fn usd_flat_fee(&self, state: &ProgramState) -> Result<u64> {
+        if !state.usd_limits {
+            return Ok(0);
+        }
+        let limits = self.usd_limits().await?;
+        if limits.flat_fee_usd == 0 {
+            return Ok(0);
+        }
+        let feed = limits.price_feed;
+        let mut account = self.pool.call(|rpc| rpc.get_account(&feed)).await?;
+        let price = pyth_sdk_solana::load_price_feed_from_account(&feed, &mut account)
+            .map_err(|err| PrivaxClientError::Decode(feed, err.to_string()))?
+            .get_price_unchecked();
+        limits.flat_fee(&price).map_err(|err| PrivaxClientError::Decode(feed, err.to_string()))
+    }
+
+    // Claims the receipt NFT for the payer's deposit receipt at `leaf_index`.
+    pub async fn claim_receipt_nft(&self, leaf_index: u64) -> Result<Signature> {
+        let merkle_tree = self.receipt_nft_config().await?.merkle_tree;
+        let ix = instructions::claim_receipt_nft(self.payer.pubkey(), leaf_index, merkle_tree);
+        self.send(&[ix], &[]).await
+    }
+
+    // init_nullifier_shard for each shard of `nullifier_hashes` that doesn't exist yet.
+    async fn open_missing_shards(&self, nullifier_hashes: impl Iterator<Item = [u8; 32]>) -> Result<Vec<Instruction>> {
+        let mut prefixes: Vec<[u8; 2]> = nullifier_hashes.map(|hash| instructions::nullifier_prefix(&hash)).collect();
+        prefixes.sort_unstable();
+        prefixes.dedup();
+        let addresses: Vec<Pubkey> = prefixes.iter().map(|prefix| instructions::nullifier_shard_address(*prefix)).collect();
+        let existing = self.pool.call(|rpc| rpc.get_multiple_accounts(&addresses)).await?;
+        Ok(prefixes
+            .into_iter()
+            .zip(existing)
+            .filter(|(_, account)| account.is_none())
+            .map(|(prefix, _)| instructions::init_nullifier_shard(self.payer.pubkey(), prefix))
+            .collect())
+    }
+
+    // `existing` holds the accounts at the addresses of `shards`.
+    fn withdraw_instructions(&self, params: &WithdrawParams, shards: &[([u8; 2], usize)], existing: &[Option<Account>]) -> Vec<Instruction> {
+        let mut ixs: Vec<Instruction> = shards
+            .iter()
+            .zip(existing)
+            .filter(|(_, account)| account.is_none())
+            .map(|((prefix, _), _)| instructions::init_nullifier_shard(self.payer.pubkey(), *prefix))
+            .collect();
+        ixs.push(instructions::withdraw(self.payer.pubkey(), params));
+        ixs
+    }
+}
+
+// The nullifier shards a withdrawal writes to, with how many of its nullifiers land in each.
+fn shard_spends(params: &WithdrawParams) -> Vec<([u8; 2], usize)> {
+    let mut prefixes: Vec<[u8; 2]> = params.nullifier_hashes().map(|hash| instructions::nullifier_prefix(&hash)).collect();
+    prefixes.sort_unstable();
+    let mut shards: Vec<([u8; 2], usize)> = Vec::new();
+    for prefix in prefixes {
+        match shards.last_mut() {
+            Some((last, spends)) if *last == prefix => *spends += 1,
+            _ => shards.push((prefix, 1)),
+        }
+    }
+    shards
+}
+
+pub(crate) fn check_program(profile: &Profile) -> Result<()> {
+    if profile.program_id != privax_protocol::ID {
+        return Err(PrivaxClientError::ProgramMismatch { cluster: profile.cluster, program_id: profile.program_id });
+    }
+    Ok(())
+}
+
+pub(crate) fn decode<T: AccountDeserialize>(address: Pubkey, account: Option<&Account>) -> Result<T> {
+    let account = account.ok_or_else(|| PrivaxClientError::Decode(address, "account not found".to_string()))?;
+    T::try_deserialize(&mut account.data.as_slice()).map_err(|err| PrivaxClientError::Decode(address, err.to_string()))
+}
@@ -0,0 +1,145 @@
+// Shielded payment requests as Solana Pay–style URLs, e.g. for a merchant's QR code:
+//
+//   privax:<program-id>?amount=12.5&spl-token=<mint>&commitment=<hex>&label=Shop&message=Order%2042
+//
+// The fields follow Solana Pay's transfer requests (`amount` in UI units, `spl-token`, `label`,
+// `message`), plus the commitment the payer deposits. The merchant derived that commitment
+// from a note it holds, so the deposit becomes spendable only by the merchant. The scheme is
+// `privax:` rather than `solana:` so a wallet unaware of Privax can't mistake the request for
+// a plain transfer to the program.
+use std::fmt;
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use url::Url;
+
+pub const SCHEME: &str = "privax";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaymentRequest {
+    pub program_id: Pubkey, // Privax deployment to deposit through
+    pub mint: Pubkey,
+    pub amount: String, // Decimal, in UI units of the mint
+    pub commitment: [u8; 32],
+    pub label: Option<String>,   // Who is asking, e.g. the merchant's name
+    pub message: Option<String>, // What the payment is for
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum PaymentRequestError {
+    #[error("not a privax: URL")]
+    Scheme,
+    #[error("invalid {0} in payment request")]
+    Invalid(&'static str),
+    #[error("payment request is missing {0}")]
+    Missing(&'static str),
+}
+
+impl PaymentRequest {
+    // `base_units` of a mint with `decimals` decimals, written as a UI amount.
+    pub fn new(mint: Pubkey, base_units: u64, decimals: u8, commitment: [u8; 32]) -> Self {
+        Self {
+            program_id: privax_protocol::ID,
+            mint,
+            amount: ui_amount(base_units, decimals),
+            commitment,
+            label: None,
+            message: None,
+        }
+    }
+
+    // The amount in base units, or None if it has more decimals than the mint or overflows.
+    pub fn base_units(&self, decimals: u8) -> Option<u64> {
+        let (whole, fraction) = self.amount.split_once('.').unwrap_or((&self.amount, ""));
+        if fraction.len() > usize::from(decimals) {
+            return None;
+        }
+        let padded = format!("{whole}{fraction:0<width$}", width = usize::from(decimals));
+        padded.parse().ok()
+    }
+}
+
+fn ui_amount(base_units: u64, decimals: u8) -> String {
+    let scale = 10u64.checked_pow(u32::from(decimals));
+    let Some(scale) = scale else {
+        return base_units.to_string();
+    };
+    let fraction = format!("{:0width$}", base_units % scale, width = usize::from(decimals));
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        (base_units / scale).to_string()
+    } else {
+        format!("{}.{fraction}", base_units / scale)
+    }
+}
+
+fn is_decimal(amount: &str) -> bool {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, "0"));
+    !whole.is_empty() && !fraction.is_empty() && (whole.bytes().chain(fraction.bytes())).all(|b| b.is_ascii_digit())
+}
+
+impl fmt::Display for PaymentRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut url = Url::parse(&format!("{SCHEME}:{}", self.program_id)).map_err(|_| fmt::Error)?;
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("amount", &self.amount);
+            query.append_pair("spl-token", &self.mint.to_string());
+            query.append_pair("commitment", &hex(&self.commitment));
+            if let Some(label) = &self.label {
+                query.append_pair("label", label);
+            }
+            if let Some(message) = &self.message {
+                query.append_pair("message", message);
+            }
+        }
+        f.write_str(url.as_str())
+    }
+}
+
+impl FromStr for PaymentRequest {
+    type Err = PaymentRequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let url = Url::parse(s).map_err(|_| PaymentRequestError::Scheme)?;
+        if url.scheme() != SCHEME {
+            return Err(PaymentRequestError::Scheme);
+        }
+        let program_id = url.path().parse().map_err(|_| PaymentRequestError::Invalid("program id"))?;
+        let (mut mint, mut amount, mut commitment, mut label, mut message) = (None, None, None, None, None);
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "spl-token" => mint = Some(value.parse().map_err(|_| PaymentRequestError::Invalid("spl-token"))?),
+                "amount" if is_decimal(&value) => amount = Some(value.into_owned()),
+                "amount" => return Err(PaymentRequestError::Invalid("amount")),
+                "commitment" => commitment = Some(parse_hex32(&value).ok_or(PaymentRequestError::Invalid("commitment"))?),
+                "label" => label = Some(value.into_owned()),
+                "message" => message = Some(value.into_owned()),
+                _ => {} // Solana Pay fields we don't use, e.g. memo
+            }
+        }
+        Ok(Self {
+            program_id,
+            mint: mint.ok_or(PaymentRequestError::Missing("spl-token"))?,
+            amount: amount.ok_or(PaymentRequestError::Missing("amount"))?,
+            commitment: commitment.ok_or(PaymentRequestError::Missing("commitment"))?,
+            label,
+            message,
+        })
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn parse_hex32(s: &str) -> Option<[u8; 32]> {
+    if s.len() != 64 || !s.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (byte, pair) in bytes.iter_mut().zip(s.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(bytes)
+}
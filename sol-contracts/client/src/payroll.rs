@@ -0,0 +1,208 @@
+// Private payroll: a CSV of recipients and amounts, paid out through withdraw_split by a
+// relayer, so no payer's address ever appears next to an employee's.
+//
+// The payroll is planned into batches of up to RECIPIENTS_PER_BATCH employees. Each batch gets
+// its own note for the batch's total plus the relayer's fee, which the relayer takes as the
+// split's last payout. A batch then goes note -> deposit -> relayed split -> paid, and the job
+// file records each step as soon as it is taken, so an interrupted run picks up where it
+// stopped instead of depositing or spending twice. The file holds the notes' secrets until
+// they are spent; keep it as private as a wallet.
+use std::fmt;
+use std::path::Path;
+
+use anchor_lang::prelude::Pubkey;
+use privax_protocol::MAX_SPLIT_RECIPIENTS;
+use serde::{Deserialize, Serialize};
+
+// One payout of a split is the relayer's fee.
+pub const RECIPIENTS_PER_BATCH: usize = MAX_SPLIT_RECIPIENTS - 1;
+
+pub const JOB_FILE_VERSION: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PayrollError {
+    #[error("line {line}: {reason}")]
+    Csv { line: usize, reason: String },
+    #[error("the payroll has no recipients")]
+    Empty,
+    #[error("failed to access the job file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid job file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("job file version {0} isn't supported")]
+    Version(u32),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayrollEntry {
+    #[serde(with = "base58")]
+    pub recipient: Pubkey, // Paid at its associated token account for the pool's mint
+    pub amount: u64,       // Base units, before the protocol fee withdraw_split deducts
+}
+
+// `recipient,amount` rows; a header row, blank lines and `#` comments are skipped. A recipient
+// listed twice is paid twice.
+pub fn parse_csv(text: &str) -> Result<Vec<PayrollEntry>, PayrollError> {
+    parse_csv_with(text, |recipient| recipient.parse().map_err(|_| format!("invalid public key `{recipient}`")))
+}
+
+// As `parse_csv`, with `resolve` turning each recipient into an address, e.g. to accept .sol
+// domains through PrivaxClient::resolve_address.
+pub fn parse_csv_with(
+    text: &str,
+    mut resolve: impl FnMut(&str) -> Result<Pubkey, String>,
+) -> Result<Vec<PayrollEntry>, PayrollError> {
+    let mut entries = Vec::new();
+    for (index, row) in text.lines().enumerate() {
+        let line = index + 1;
+        let row = row.trim();
+        if row.is_empty() || row.starts_with('#') {
+            continue;
+        }
+        let invalid = |reason: String| PayrollError::Csv { line, reason };
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+        let [recipient, amount] = fields.as_slice() else {
+            return Err(invalid(format!("expected `recipient,amount`, got `{row}`")));
+        };
+        if entries.is_empty() && recipient.eq_ignore_ascii_case("recipient") {
+            continue;
+        }
+        let recipient = resolve(recipient).map_err(invalid)?;
+        let amount = match amount.parse() {
+            Ok(amount) if amount > 0 => amount,
+            _ => return Err(invalid(format!("invalid amount `{amount}`"))),
+        };
+        entries.push(PayrollEntry { recipient, amount });
+    }
+    Ok(entries)
+}
+
+// The note funding a batch, recorded before it is deposited.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchNote {
+    pub note: String,       // The encoded note (privax-mobile's `privax-note-v1-...`)
+    pub commitment: String, // Hex
+    #[serde(with = "base58")]
+    pub relayer: Pubkey, // Relayer that quoted the fee, paid as the last payout
+    pub relayer_fee: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayrollBatch {
+    pub entries: Vec<PayrollEntry>,
+    pub note: Option<BatchNote>,
+    pub deposit_sent: bool, // Set before the deposit is sent, so it's never sent twice
+    pub deposit_signature: Option<String>, // None if the run stopped while it was in flight
+    pub job_id: Option<String>,    // The relayer's, once it accepted the split
+    pub signature: Option<String>, // The withdraw_split transaction, once the relayer confirmed it
+    pub error: Option<String>,     // Why the relayer's job failed; the batch needs a look
+}
+
+// What a batch needs next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchStep {
+    CreateNote,
+    Deposit,
+    Submit,
+    AwaitRelayer,
+    Failed,
+    Done,
+}
+
+impl fmt::Display for BatchStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::CreateNote => "planned",
+            Self::Deposit => "note created, not deposited",
+            Self::Submit => "deposited, not submitted",
+            Self::AwaitRelayer => "submitted to the relayer",
+            Self::Failed => "failed",
+            Self::Done => "paid",
+        })
+    }
+}
+
+impl PayrollBatch {
+    // None if it overflows a u64.
+    pub fn payroll_total(&self) -> Option<u64> {
+        self.entries.iter().try_fold(0u64, |total, entry| total.checked_add(entry.amount))
+    }
+
+    pub fn next_step(&self) -> BatchStep {
+        match self {
+            Self { signature: Some(_), .. } => BatchStep::Done,
+            Self { error: Some(_), .. } => BatchStep::Failed,
+            Self { job_id: Some(_), .. } => BatchStep::AwaitRelayer,
+            Self { deposit_sent: true, .. } => BatchStep::Submit,
+            Self { note: Some(_), .. } => BatchStep::Deposit,
+            _ => BatchStep::CreateNote,
+        }
+    }
+
+    // The split's payouts, in proof order: the employees, then the relayer's fee. None until
+    // the batch has a note.
+    pub fn payouts(&self) -> Option<Vec<(Pubkey, u64)>> {
+        let note = self.note.as_ref()?;
+        let employees = self.entries.iter().map(|entry| (entry.recipient, entry.amount));
+        Some(employees.chain([(note.relayer, note.relayer_fee)]).collect())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayrollJob {
+    pub version: u32,
+    #[serde(with = "base58")]
+    pub mint: Pubkey,
+    pub batches: Vec<PayrollBatch>,
+}
+
+impl PayrollJob {
+    pub fn plan(mint: Pubkey, entries: Vec<PayrollEntry>) -> Result<Self, PayrollError> {
+        if entries.is_empty() {
+            return Err(PayrollError::Empty);
+        }
+        let batches = entries
+            .chunks(RECIPIENTS_PER_BATCH)
+            .map(|entries| PayrollBatch { entries: entries.to_vec(), ..Default::default() })
+            .collect();
+        Ok(Self { version: JOB_FILE_VERSION, mint, batches })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PayrollError> {
+        let job: Self = serde_json::from_slice(&std::fs::read(path)?)?;
+        if job.version != JOB_FILE_VERSION {
+            return Err(PayrollError::Version(job.version));
+        }
+        Ok(job)
+    }
+
+    // Written to a temporary file and renamed over the old one, so a crash mid-write leaves
+    // the previous state rather than a truncated file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PayrollError> {
+        let path = path.as_ref();
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.batches.iter().all(|batch| batch.next_step() == BatchStep::Done)
+    }
+}
+
+// Public keys as base58 strings rather than byte arrays, so the file stays readable.
+mod base58 {
+    use anchor_lang::prelude::Pubkey;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(key: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(key)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        key.parse().map_err(|_| de::Error::custom(format!("invalid public key `{key}`")))
+    }
+}
@@ -0,0 +1,154 @@
+// A pool of RPC endpoints with failover and retries, so relayers and indexers survive flaky
+// public RPCs. Requests go to the preferred healthy endpoint; a transient failure marks it
+// unhealthy, moves the preference to the next endpoint and retries after an exponential
+// backoff. check_health re-probes every endpoint and restores the ones that recovered.
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use privax_config::Profile;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::client_error::{Error as RpcClientError, ErrorKind};
+use solana_rpc_client_api::custom_error::{
+    JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE, JSON_RPC_SERVER_ERROR_BLOCK_STATUS_NOT_AVAILABLE_YET,
+    JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+};
+use solana_rpc_client_api::request::RpcError;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::transaction::TransactionError;
+use tracing::warn;
+
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32, // Including the first try
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_attempts: 5, base_delay: Duration::from_millis(200), max_delay: Duration::from_secs(5) }
+    }
+}
+
+impl RetryPolicy {
+    // Delay before retry number `attempt` (1-based): base_delay doubled per attempt, capped.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+// Errors worth retrying, possibly on another endpoint: connectivity, rate limits, a node
+// that is unhealthy or behind, and a blockhash the node hasn't seen yet.
+pub fn is_transient(err: &RpcClientError) -> bool {
+    match err.kind() {
+        ErrorKind::Io(_) => true,
+        ErrorKind::Reqwest(err) => {
+            err.is_timeout()
+                || err.is_connect()
+                || matches!(err.status(), Some(status) if status.as_u16() == 429 || status.is_server_error())
+        }
+        ErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if matches!(
+                *code,
+                JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+                    | JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE
+                    | JSON_RPC_SERVER_ERROR_BLOCK_STATUS_NOT_AVAILABLE_YET
+                    | JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED
+            ) =>
+        {
+            true
+        }
+        kind => matches!(kind.get_transaction_error(), Some(TransactionError::BlockhashNotFound)),
+    }
+}
+
+struct Endpoint {
+    client: RpcClient,
+    healthy: AtomicBool,
+}
+
+pub struct RpcPool {
+    endpoints: Vec<Endpoint>,
+    preferred: AtomicUsize,
+    policy: RetryPolicy,
+}
+
+impl RpcPool {
+    // `urls` in order of preference; must not be empty.
+    pub fn new(urls: &[String], commitment: CommitmentConfig, policy: RetryPolicy) -> Self {
+        assert!(!urls.is_empty(), "RpcPool needs at least one endpoint");
+        let endpoints = urls
+            .iter()
+            .map(|url| Endpoint {
+                client: RpcClient::new_with_commitment(url.clone(), commitment),
+                healthy: AtomicBool::new(true),
+            })
+            .collect();
+        Self { endpoints, preferred: AtomicUsize::new(0), policy }
+    }
+
+    pub fn single(url: impl ToString) -> Self {
+        Self::new(&[url.to_string()], CommitmentConfig::confirmed(), RetryPolicy::default())
+    }
+
+    // Over a cluster profile's endpoints, in its order of preference.
+    pub fn from_profile(profile: &Profile, commitment: CommitmentConfig) -> Self {
+        Self::new(&profile.rpc_urls, commitment, RetryPolicy::default())
+    }
+
+    pub fn policy(&self) -> RetryPolicy {
+        self.policy
+    }
+
+    // The preferred healthy endpoint, for calls that don't need failover.
+    pub fn client(&self) -> &RpcClient {
+        &self.endpoints[self.next_index()].client
+    }
+
+    // Probes every endpoint with getHealth. Returns how many are healthy.
+    pub async fn check_health(&self) -> usize {
+        let mut healthy = 0;
+        for endpoint in &self.endpoints {
+            let ok = endpoint.client.get_health().await.is_ok();
+            endpoint.healthy.store(ok, Ordering::Relaxed);
+            healthy += usize::from(ok);
+        }
+        healthy
+    }
+
+    // First healthy endpoint from the preferred one on; the preferred one if none is healthy.
+    fn next_index(&self) -> usize {
+        let start = self.preferred.load(Ordering::Relaxed);
+        (0..self.endpoints.len())
+            .map(|offset| (start + offset) % self.endpoints.len())
+            .find(|&i| self.endpoints[i].healthy.load(Ordering::Relaxed))
+            .unwrap_or(start)
+    }
+
+    // Runs `op` against the pool, failing over and backing off on transient errors.
+    pub async fn call<'a, T, F, Fut>(&'a self, mut op: F) -> Result<T, RpcClientError>
+    where
+        F: FnMut(&'a RpcClient) -> Fut,
+        Fut: Future<Output = Result<T, RpcClientError>> + 'a,
+    {
+        let mut attempt = 1;
+        loop {
+            let index = self.next_index();
+            let err = match op(&self.endpoints[index].client).await {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            if !is_transient(&err) || attempt >= self.policy.max_attempts {
+                return Err(err);
+            }
+            // By position, since provider URLs often embed an API key
+            warn!(endpoint = index, attempt, error = %err, "RPC call failed, failing over");
+            self.endpoints[index].healthy.store(false, Ordering::Relaxed);
+            self.preferred.store((index + 1) % self.endpoints.len(), Ordering::Relaxed);
+            tokio::time::sleep(self.policy.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// .sol domains (Solana Name Service) as withdrawal recipients and relayers. A withdrawal
+// can't be clawed back, so a domain is only accepted when both of its name accounts agree:
+// the domain account under the .sol TLD, whose owner is the address, and the reverse record
+// the registrar created for it, which must name the same domain. Without I/O here; the
+// clients fetch the two accounts and pass them to `verify`.
+use anchor_lang::prelude::Pubkey;
+use solana_sdk::account::Account;
+use solana_sdk::hash::hashv;
+use solana_sdk::pubkey;
+
+pub const NAME_PROGRAM_ID: Pubkey = pubkey!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+// Parent of every .sol domain
+pub const SOL_TLD: Pubkey = pubkey!("58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx");
+pub const REVERSE_LOOKUP_CLASS: Pubkey = pubkey!("33m47vH6Eav6jr5Ry86XjhRft2jRBLDnDgPSHoquXi2Z");
+
+const HASH_PREFIX: &str = "SPL Name Service";
+// A name account's parent, owner and class, before its data
+const HEADER_LEN: usize = 96;
+
+pub fn is_domain(name: &str) -> bool {
+    name.len() > ".sol".len() && name.to_ascii_lowercase().ends_with(".sol")
+}
+
+fn name_account(name: &str, class: &Pubkey, parent: &Pubkey) -> Pubkey {
+    let hashed_name = hashv(&[HASH_PREFIX.as_bytes(), name.as_bytes()]);
+    Pubkey::find_program_address(&[hashed_name.as_ref(), class.as_ref(), parent.as_ref()], &NAME_PROGRAM_ID).0
+}
+
+// A resolvable domain: `name.sol`, or one level of subdomain, `sub.name.sol`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Domain {
+    pub name: String,        // Lowercase, without ".sol"
+    pub sub: Option<String>, // The subdomain label, if any
+}
+
+impl Domain {
+    pub fn parse(domain: &str) -> Option<Self> {
+        let domain = domain.to_ascii_lowercase();
+        let labels: Vec<&str> = domain.strip_suffix(".sol")?.split('.').collect();
+        if labels.iter().any(|label| label.is_empty()) {
+            return None;
+        }
+        match labels.as_slice() {
+            [name] => Some(Self { name: name.to_string(), sub: None }),
+            [sub, name] => Some(Self { name: name.to_string(), sub: Some(sub.to_string()) }),
+            _ => None,
+        }
+    }
+
+    fn parent_address(&self) -> Pubkey {
+        name_account(&self.name, &Pubkey::default(), &SOL_TLD)
+    }
+
+    // The name account whose owner the domain resolves to. Subdomains are named "\0sub"
+    // under their parent domain.
+    pub fn address(&self) -> Pubkey {
+        match &self.sub {
+            None => self.parent_address(),
+            Some(sub) => name_account(&format!("\0{sub}"), &Pubkey::default(), &self.parent_address()),
+        }
+    }
+
+    // The registrar's reverse record, naming the domain account's domain.
+    pub fn reverse_address(&self) -> Pubkey {
+        let parent = match self.sub {
+            None => Pubkey::default(),
+            Some(_) => self.parent_address(),
+        };
+        name_account(&self.address().to_string(), &REVERSE_LOOKUP_CLASS, &parent)
+    }
+
+    // The address the domain resolves to, from its domain account and reverse record.
+    pub fn verify(&self, domain_account: Option<&Account>, reverse_account: Option<&Account>) -> Result<Pubkey, &'static str> {
+        let domain_account = domain_account.ok_or("the domain isn't registered")?;
+        let data = &domain_account.data;
+        let parent = match self.sub {
+            None => SOL_TLD,
+            Some(_) => self.parent_address(),
+        };
+        if domain_account.owner != NAME_PROGRAM_ID || data.len() < HEADER_LEN || data[..32] != parent.to_bytes() {
+            return Err("its domain account isn't a name service record");
+        }
+        let reverse = reverse_account.ok_or("it has no reverse record")?;
+        if reverse.owner != NAME_PROGRAM_ID || reverse_name(&reverse.data).as_deref() != Some(self.label()) {
+            return Err("its reverse record names another domain");
+        }
+        let owner = Pubkey::try_from(&data[32..64]).expect("32 bytes");
+        // A tokenized domain is held in escrow by the tokenizer program; funds sent to its
+        // escrow would be lost
+        if !owner.is_on_curve() {
+            return Err("it's owned by a program account, such as a tokenized domain's escrow");
+        }
+        Ok(owner)
+    }
+
+    fn label(&self) -> &str {
+        self.sub.as_deref().unwrap_or(&self.name)
+    }
+}
+
+// The name in a reverse record: a Borsh string after the header. Subdomains' keep the
+// leading "\0" of their name account's name.
+fn reverse_name(data: &[u8]) -> Option<String> {
+    let len = u32::from_le_bytes(data.get(HEADER_LEN..HEADER_LEN + 4)?.try_into().ok()?) as usize;
+    let name = std::str::from_utf8(data.get(HEADER_LEN + 4..HEADER_LEN + 4 + len)?).ok()?;
+    Some(name.strip_prefix('\0').unwrap_or(name).to_string())
+}
@@ -0,0 +1,90 @@
+// The TTL cache behind PrivaxAccounts, and reading nullifier shards.
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountSerialize;
+use privax_client::accounts::{shard_contains, AccountCache};
+use privax_client::instructions::nullifier_prefix;
+use privax_protocol::NullifierShard;
+use solana_sdk::account::Account;
+
+fn account(lamports: u64) -> Account {
+    Account { lamports, data: vec![1, 2, 3], owner: privax_protocol::ID, executable: false, rent_epoch: 0 }
+}
+
+#[test]
+fn entries_are_fresh_for_the_ttl() {
+    let start = Instant::now();
+    let ttl = Duration::from_secs(2);
+    let mut cache = AccountCache::new(ttl);
+    let (address, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    assert_eq!(cache.get(&address, start), None);
+    cache.insert(address, Some(account(1)), start);
+    assert_eq!(cache.get(&address, start + ttl / 2), Some(Some(account(1))));
+    assert_eq!(cache.get(&address, start + ttl), None);
+    assert_eq!(cache.get(&other, start), None);
+
+    // A refetch restarts the clock
+    cache.insert(address, Some(account(2)), start + ttl);
+    assert_eq!(cache.get(&address, start + ttl + ttl / 2), Some(Some(account(2))));
+}
+
+#[test]
+fn missing_accounts_are_cached_as_missing() {
+    let now = Instant::now();
+    let mut cache = AccountCache::new(Duration::from_secs(2));
+    let address = Pubkey::new_unique();
+    cache.insert(address, None, now);
+    assert_eq!(cache.get(&address, now), Some(None));
+
+    cache.invalidate(&address);
+    assert_eq!(cache.get(&address, now), None);
+}
+
+#[test]
+fn eviction_drops_only_expired_entries() {
+    let start = Instant::now();
+    let ttl = Duration::from_secs(2);
+    let mut cache = AccountCache::new(ttl);
+    let (old, new) = (Pubkey::new_unique(), Pubkey::new_unique());
+    cache.insert(old, Some(account(1)), start);
+    cache.insert(new, Some(account(2)), start + ttl);
+
+    cache.evict_expired(start + ttl);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get(&new, start + ttl), Some(Some(account(2))));
+}
+
+// A shard holding `nullifiers`, with room for one more as a grown shard may have.
+fn shard(nullifiers: &[[u8; 32]]) -> Account {
+    let header = NullifierShard { prefix: nullifier_prefix(&nullifiers[0]), count: nullifiers.len() as u32, bump: 255 };
+    let mut data = Vec::new();
+    header.try_serialize(&mut data).unwrap();
+    for nullifier in nullifiers {
+        data.extend_from_slice(nullifier);
+    }
+    data.extend_from_slice(&[0; 32]);
+    Account { lamports: 1, data, owner: privax_protocol::ID, executable: false, rent_epoch: 0 }
+}
+
+#[test]
+fn shards_contain_only_their_recorded_nullifiers() {
+    let address = Pubkey::new_unique();
+    let (first, mut second) = ([7; 32], [7; 32]);
+    second[2] = 8;
+    let shard = shard(&[first, second]);
+    assert!(shard_contains(address, Some(&shard), &first).unwrap());
+    assert!(shard_contains(address, Some(&shard), &second).unwrap());
+    // A hash is only recorded if all of it matches, not just the bytes a public input carries
+    let mut other = first;
+    other[31] = 8;
+    assert!(!shard_contains(address, Some(&shard), &other).unwrap());
+    // The spare entry past `count` is zeroes, not a recorded nullifier
+    assert!(!shard_contains(address, Some(&shard), &[0; 32]).unwrap());
+    assert!(!shard_contains(address, None, &first).unwrap());
+
+    let mut truncated = shard.clone();
+    truncated.data.truncate(NullifierShard::SPACE + 32);
+    assert!(shard_contains(address, Some(&truncated), &first).is_err());
+}
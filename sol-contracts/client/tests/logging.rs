@@ -0,0 +1,27 @@
+// Note strings never reach the logs: their amount and secrets are replaced wherever they appear.
+use privax_client::logging::{redact, LogFormat};
+
+const NOTE: &str = "privax-note-v1-1000-0a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8-\
+                    f90a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f9";
+
+#[test]
+fn note_strings_are_redacted() {
+    assert_eq!(redact("nothing secret here"), "nothing secret here");
+    assert_eq!(redact(&format!("loaded {NOTE} from disk")), "loaded privax-note-v1-<redacted> from disk");
+    // Inside a JSON string, and more than once per line
+    let json = format!(r#"{{"message":"spending","note":"{NOTE}","change":"{NOTE}"}}"#);
+    assert_eq!(
+        redact(&json),
+        r#"{"message":"spending","note":"privax-note-v1-<redacted>","change":"privax-note-v1-<redacted>"}"#
+    );
+    assert!(!redact(&json).contains("0a1b2c"));
+    // Redacting twice changes nothing, as when an error already redacted is logged
+    assert_eq!(redact(&redact(NOTE)), redact(NOTE));
+}
+
+#[test]
+fn log_formats() {
+    assert_eq!("text".parse(), Ok(LogFormat::Text));
+    assert_eq!("json".parse(), Ok(LogFormat::Json));
+    assert!("yaml".parse::<LogFormat>().is_err());
+}
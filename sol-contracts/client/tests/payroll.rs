@@ -0,0 +1,67 @@
+// Payroll planning and the job file's resume points.
+use anchor_lang::prelude::Pubkey;
+use privax_client::payroll::{parse_csv, parse_csv_with, BatchNote, BatchStep, PayrollError, PayrollJob, RECIPIENTS_PER_BATCH};
+
+#[test]
+fn csv_rows() {
+    let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let csv = format!("recipient,amount\n# March\n{alice}, 1500\n\n{bob},2500\n");
+    let entries = parse_csv(&csv).unwrap();
+    assert_eq!(entries.iter().map(|entry| (entry.recipient, entry.amount)).collect::<Vec<_>>(), [(alice, 1500), (bob, 2500)]);
+
+    for bad in [format!("{alice},0"), format!("{alice},-1"), "nobody,10".to_string(), format!("{alice},1,2")] {
+        assert!(matches!(parse_csv(&bad), Err(PayrollError::Csv { line: 1, .. })), "{bad}");
+    }
+}
+
+#[test]
+fn csv_recipients_through_a_resolver() {
+    let alice = Pubkey::new_unique();
+    let resolve = |recipient: &str| match recipient {
+        "alice.sol" => Ok(alice),
+        _ => Err(format!("can't resolve `{recipient}`")),
+    };
+    let entries = parse_csv_with("alice.sol,100\n", resolve).unwrap();
+    assert_eq!(entries[0].recipient, alice);
+    assert!(matches!(parse_csv_with("bob.sol,100\n", resolve), Err(PayrollError::Csv { line: 1, .. })));
+}
+
+#[test]
+fn batches_leave_a_payout_for_the_relayer() {
+    let csv: String = (0..200).map(|i| format!("{},{}\n", Pubkey::new_unique(), i + 1)).collect();
+    let job = PayrollJob::plan(Pubkey::new_unique(), parse_csv(&csv).unwrap()).unwrap();
+    assert_eq!(job.batches.len(), 200_usize.div_ceil(RECIPIENTS_PER_BATCH));
+    assert!(job.batches.iter().all(|batch| batch.entries.len() <= RECIPIENTS_PER_BATCH));
+    assert_eq!(job.batches.iter().map(|batch| batch.payroll_total().unwrap()).sum::<u64>(), (1..=200u64).sum::<u64>());
+    assert!(matches!(PayrollJob::plan(Pubkey::new_unique(), Vec::new()), Err(PayrollError::Empty)));
+}
+
+#[test]
+fn job_file_resumes_each_step() {
+    let entries = parse_csv(&format!("{},100\n", Pubkey::new_unique())).unwrap();
+    let mut job = PayrollJob::plan(Pubkey::new_unique(), entries).unwrap();
+    let path = std::env::temp_dir().join(format!("privax-payroll-{}.json", std::process::id()));
+    let relayer = Pubkey::new_unique();
+
+    // Each step is saved and reloaded the way an interrupted run would find it
+    let resumes_at = |job: &PayrollJob| {
+        job.save(&path).unwrap();
+        let loaded = PayrollJob::load(&path).unwrap();
+        assert_eq!(&loaded, job);
+        loaded.batches[0].next_step()
+    };
+    assert_eq!(resumes_at(&job), BatchStep::CreateNote);
+    job.batches[0].note = Some(BatchNote { note: "note".into(), commitment: "00".repeat(32), relayer, relayer_fee: 5 });
+    assert_eq!(resumes_at(&job), BatchStep::Deposit);
+    job.batches[0].deposit_sent = true;
+    assert_eq!(resumes_at(&job), BatchStep::Submit);
+    job.batches[0].job_id = Some("job".into());
+    assert_eq!(resumes_at(&job), BatchStep::AwaitRelayer);
+    job.batches[0].error = Some("dropped".into());
+    assert_eq!(resumes_at(&job), BatchStep::Failed);
+    job.batches[0].signature = Some("signature".into());
+    assert_eq!(resumes_at(&job), BatchStep::Done);
+    assert!(job.is_done());
+    assert_eq!(job.batches[0].payouts().unwrap().last(), Some(&(relayer, 5)));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,48 @@
+// .sol domain resolution: name account derivation and the reverse record check.
+use anchor_lang::prelude::Pubkey;
+use privax_client::sns::{Domain, NAME_PROGRAM_ID, SOL_TLD};
+use solana_sdk::account::Account;
+use solana_sdk::signature::{Keypair, Signer};
+
+fn name_account(parent: &Pubkey, owner: &Pubkey, data: &[u8]) -> Account {
+    let data = [parent.as_ref(), owner.as_ref(), &[0; 32], data].concat();
+    Account { lamports: 1, data, owner: NAME_PROGRAM_ID, executable: false, rent_epoch: 0 }
+}
+
+fn reverse_record(name: &str) -> Account {
+    let data = [&(name.len() as u32).to_le_bytes()[..], name.as_bytes()].concat();
+    name_account(&Pubkey::default(), &Pubkey::default(), &data)
+}
+
+#[test]
+fn domains() {
+    assert_eq!(Domain::parse("Bonfida.sol"), Some(Domain { name: "bonfida".to_string(), sub: None }));
+    assert_eq!(Domain::parse("dex.bonfida.sol"), Some(Domain { name: "bonfida".to_string(), sub: Some("dex".to_string()) }));
+    for bad in ["bonfida", ".sol", "a..sol", "a.b.c.sol"] {
+        assert_eq!(Domain::parse(bad), None, "{bad}");
+    }
+    // As the name service's own SDK derives it
+    let bonfida = Domain::parse("bonfida.sol").unwrap();
+    assert_eq!(bonfida.address().to_string(), "Crf8hzfthWGbGbLTVCiqRqV5MVnbpHB1L9KQMd6gsinb");
+}
+
+#[test]
+fn resolution_needs_the_reverse_record() {
+    let domain = Domain::parse("alice.sol").unwrap();
+    let owner = Keypair::new().pubkey();
+    let account = name_account(&SOL_TLD, &owner, &[]);
+    assert_eq!(domain.verify(Some(&account), Some(&reverse_record("alice"))), Ok(owner));
+
+    assert!(domain.verify(None, Some(&reverse_record("alice"))).is_err());
+    assert!(domain.verify(Some(&account), None).is_err());
+    assert!(domain.verify(Some(&account), Some(&reverse_record("alicia"))).is_err());
+    // Not under the .sol TLD
+    assert!(domain.verify(Some(&name_account(&Pubkey::new_unique(), &owner, &[])), Some(&reverse_record("alice"))).is_err());
+    // Held by a program, as tokenized domains are
+    let escrow = Pubkey::find_program_address(&[b"escrow"], &Pubkey::new_unique()).0;
+    assert!(domain.verify(Some(&name_account(&SOL_TLD, &escrow, &[])), Some(&reverse_record("alice"))).is_err());
+
+    let sub = Domain::parse("pay.alice.sol").unwrap();
+    let account = name_account(&domain.address(), &owner, &[]);
+    assert_eq!(sub.verify(Some(&account), Some(&reverse_record("\0pay"))), Ok(owner));
+}
@@ -0,0 +1,56 @@
+// The SDK's side of the shared test vectors (test-vectors/vectors.json): nullifier shards and
+// the public inputs withdraw instructions carry.
+use anchor_lang::prelude::Pubkey;
+use privax_client::instructions::{self as ix, WithdrawParams};
+use privax_protocol::MAX_PUBLIC_INPUTS;
+use privax_test_vectors::{vectors, WithdrawalVector};
+
+fn params(withdrawal: &WithdrawalVector) -> WithdrawParams {
+    let vectors = vectors();
+    WithdrawParams {
+        a_proof: [0; 64],
+        b_proof: [0; 128],
+        c_proof: [0; 64],
+        public_inputs: withdrawal.public_inputs.as_slice().try_into().unwrap(),
+        merkle_root: vectors.poseidon_tree.root,
+        nullifier_hash: vectors.notes[withdrawal.input_notes[0]].nullifier_hash,
+        root_archive_page: None,
+        extra_nullifier_hashes: withdrawal.input_notes[1..].iter().map(|&i| vectors.notes[i].nullifier_hash).collect(),
+        recipient: withdrawal.recipient.parse().unwrap(),
+        recipient_token_account: Pubkey::new_unique(),
+        amount: withdrawal.amount,
+        relayer_fee: 0,
+        relayer_fee_token_account: None,
+        circuit_version: withdrawal.circuit_version,
+        plonk_proof: Vec::new(),
+        change_commitment: withdrawal.change_note.map(|i| vectors.notes[i].commitment),
+        usd_price_feed: None,
+    }
+}
+
+#[test]
+fn nullifier_shards() {
+    for withdrawal in &vectors().withdrawals {
+        let prefixes: Vec<[u8; 2]> = params(withdrawal).nullifier_hashes().map(|hash| ix::nullifier_prefix(&hash)).collect();
+        assert_eq!(prefixes, withdrawal.nullifier_prefixes);
+    }
+}
+
+#[test]
+fn withdraw_instruction_data() {
+    for withdrawal in &vectors().withdrawals {
+        let params = params(withdrawal);
+        let instruction = ix::withdraw(Pubkey::new_unique(), &params);
+
+        // Discriminator, then a/b/c, then the public inputs
+        let start = 8 + 64 + 128 + 64;
+        assert_eq!(instruction.data[start..start + 8 * MAX_PUBLIC_INPUTS], withdrawal.serialized_public_inputs);
+
+        // The first nullifier's shard is a named account, the others lead the remaining accounts
+        let shards: Vec<Pubkey> = withdrawal.nullifier_prefixes.iter().map(|&prefix| ix::nullifier_shard_address(prefix)).collect();
+        assert_eq!(instruction.accounts[3].pubkey, shards[0]);
+        let named = instruction.accounts.len() - (shards.len() - 1);
+        let extra: Vec<Pubkey> = instruction.accounts[named..].iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(extra, shards[1..]);
+    }
+}
@@ -0,0 +1,242 @@
+// Cluster profiles shared by the CLI, the SDK, the relayer and its indexer: the program ID,
+// the pool's mint and lookup table, RPC endpoints, the relayer URL and fee defaults for
+// localnet, devnet and mainnet.
+//
+// Settings come in layers, each field falling through to the one below it:
+//   1. PRIVAX_* environment variables, applied to whichever profile is picked
+//   2. the TOML file named by PRIVAX_CONFIG, shaped like clusters.toml
+//   3. the built-in profiles in clusters.toml
+// PRIVAX_CLUSTER picks the default cluster. The relayer reads the same files in Python.
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use solana_program::pubkey::Pubkey;
+
+const BUILTIN: &str = include_str!("../clusters.toml");
+const MAX_FEE_BPS: u16 = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Cluster {
+    Localnet,
+    Devnet,
+    Mainnet,
+}
+
+impl Cluster {
+    pub const ALL: [Cluster; 3] = [Cluster::Localnet, Cluster::Devnet, Cluster::Mainnet];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Cluster::Localnet => "localnet",
+            Cluster::Devnet => "devnet",
+            Cluster::Mainnet => "mainnet",
+        }
+    }
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// Also takes the Solana CLI's names and one-letter monikers.
+impl FromStr for Cluster {
+    type Err = ConfigError;
+
+    fn from_str(name: &str) -> Result<Self, ConfigError> {
+        match name {
+            "localnet" | "localhost" | "l" => Ok(Cluster::Localnet),
+            "devnet" | "d" => Ok(Cluster::Devnet),
+            "mainnet" | "mainnet-beta" | "m" => Ok(Cluster::Mainnet),
+            _ => Err(ConfigError::UnknownCluster(name.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Profile {
+    pub cluster: Cluster,
+    pub program_id: Pubkey,
+    pub rpc_urls: Vec<String>, // In order of preference; RpcPool fails over between them
+    pub relayer_url: Option<String>,
+    pub mint: Option<Pubkey>,         // The pool's token mint
+    pub lookup_table: Option<Pubkey>, // Address lookup table for withdraw's accounts
+    pub fees: FeeDefaults,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeDefaults {
+    pub relayer_fee_bps: u16,
+    pub relayer_min_fee: u64,
+    pub quote_ttl_seconds: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("unknown cluster {0:?} (expected localnet, devnet or mainnet)")]
+    UnknownCluster(String),
+    #[error("{path}: {source}")]
+    Read { path: String, source: std::io::Error },
+    #[error("{origin}: {message}")]
+    Parse { origin: String, message: String },
+    #[error("invalid {variable}={value:?}")]
+    Env { variable: &'static str, value: String },
+    #[error("{cluster} profile: invalid {field} {value:?}")]
+    Invalid { cluster: Cluster, field: &'static str, value: String },
+    #[error("{cluster} profile: no {field}")]
+    Missing { cluster: Cluster, field: &'static str },
+}
+
+// One layer's settings for a cluster; unset fields fall through to the layer below.
+#[derive(Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Layer {
+    program_id: Option<String>,
+    rpc_urls: Option<Vec<String>>,
+    relayer_url: Option<String>,
+    mint: Option<String>,
+    lookup_table: Option<String>,
+    #[serde(default)]
+    fees: FeeLayer,
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FeeLayer {
+    relayer_fee_bps: Option<u16>,
+    relayer_min_fee: Option<u64>,
+    quote_ttl_seconds: Option<u64>,
+}
+
+impl Layer {
+    fn over(&self, below: &Layer) -> Layer {
+        Layer {
+            program_id: self.program_id.clone().or_else(|| below.program_id.clone()),
+            rpc_urls: self.rpc_urls.clone().or_else(|| below.rpc_urls.clone()),
+            relayer_url: self.relayer_url.clone().or_else(|| below.relayer_url.clone()),
+            mint: self.mint.clone().or_else(|| below.mint.clone()),
+            lookup_table: self.lookup_table.clone().or_else(|| below.lookup_table.clone()),
+            fees: FeeLayer {
+                relayer_fee_bps: self.fees.relayer_fee_bps.or(below.fees.relayer_fee_bps),
+                relayer_min_fee: self.fees.relayer_min_fee.or(below.fees.relayer_min_fee),
+                quote_ttl_seconds: self.fees.quote_ttl_seconds.or(below.fees.quote_ttl_seconds),
+            },
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    cluster: Option<String>,
+    localnet: Option<Layer>,
+    devnet: Option<Layer>,
+    mainnet: Option<Layer>,
+}
+
+pub struct Config {
+    pub cluster: Cluster, // The profile `profile(None)` returns
+    layers: [Layer; 3],   // Built-ins and PRIVAX_CONFIG, per cluster in Cluster::ALL order
+    env: Layer,
+}
+
+impl Config {
+    // The built-in profiles alone, without PRIVAX_CONFIG or the environment.
+    pub fn builtin() -> Self {
+        let mut config = Config { cluster: Cluster::Localnet, layers: Default::default(), env: Layer::default() };
+        config.layer_toml(BUILTIN, "clusters.toml").expect("built-in clusters.toml is valid");
+        config
+    }
+
+    // The built-in profiles under PRIVAX_CONFIG and the process environment.
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_with(|variable| std::env::var(variable).ok().filter(|value| !value.is_empty()))
+    }
+
+    // As `load`, with the environment read through `env`.
+    pub fn load_with(env: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let mut config = Self::builtin();
+        if let Some(path) = env("PRIVAX_CONFIG") {
+            let toml = std::fs::read_to_string(&path).map_err(|source| ConfigError::Read { path: path.clone(), source })?;
+            config.layer_toml(&toml, &path)?;
+        }
+        if let Some(cluster) = env("PRIVAX_CLUSTER") {
+            config.cluster = cluster.parse()?;
+        }
+        config.env = Layer {
+            program_id: env("PRIVAX_PROGRAM_ID"),
+            rpc_urls: env("PRIVAX_RPC_URL").map(|urls| urls.split(',').map(|url| url.trim().to_string()).collect()),
+            relayer_url: env("PRIVAX_RELAYER_URL"),
+            mint: env("PRIVAX_MINT"),
+            lookup_table: env("PRIVAX_LOOKUP_TABLE"),
+            fees: FeeLayer {
+                relayer_fee_bps: env_number(&env, "PRIVAX_RELAYER_FEE_BPS")?,
+                relayer_min_fee: env_number(&env, "PRIVAX_RELAYER_MIN_FEE")?,
+                quote_ttl_seconds: env_number(&env, "PRIVAX_QUOTE_TTL_SECONDS")?,
+            },
+        };
+        Ok(config)
+    }
+
+    // Layers a TOML file shaped like clusters.toml over the current settings. `origin` names
+    // it in errors.
+    pub fn layer_toml(&mut self, toml: &str, origin: &str) -> Result<(), ConfigError> {
+        let file: File = toml::from_str(toml)
+            .map_err(|err| ConfigError::Parse { origin: origin.to_string(), message: err.to_string().trim_end().to_string() })?;
+        if let Some(cluster) = file.cluster {
+            self.cluster = cluster.parse()?;
+        }
+        for (layer, over) in self.layers.iter_mut().zip([file.localnet, file.devnet, file.mainnet]) {
+            if let Some(over) = over {
+                *layer = over.over(layer);
+            }
+        }
+        Ok(())
+    }
+
+    // The profile for `cluster`, or for the default cluster if None.
+    pub fn profile(&self, cluster: Option<Cluster>) -> Result<Profile, ConfigError> {
+        let cluster = cluster.unwrap_or(self.cluster);
+        let index = Cluster::ALL.iter().position(|c| *c == cluster).unwrap();
+        let layer = self.env.over(&self.layers[index]);
+
+        let address = |field: &'static str, value: String| {
+            value.parse::<Pubkey>().map_err(|_| ConfigError::Invalid { cluster, field, value })
+        };
+        let program_id = layer.program_id.ok_or(ConfigError::Missing { cluster, field: "program_id" })?;
+        let rpc_urls = layer.rpc_urls.filter(|urls| !urls.is_empty()).ok_or(ConfigError::Missing { cluster, field: "rpc_urls" })?;
+        if let Some(url) = rpc_urls.iter().find(|url| url.is_empty()) {
+            return Err(ConfigError::Invalid { cluster, field: "rpc_urls", value: url.clone() });
+        }
+        let fee = |field, value: Option<u64>| value.ok_or(ConfigError::Missing { cluster, field });
+        let relayer_fee_bps = layer.fees.relayer_fee_bps.ok_or(ConfigError::Missing { cluster, field: "fees.relayer_fee_bps" })?;
+        if relayer_fee_bps > MAX_FEE_BPS {
+            return Err(ConfigError::Invalid { cluster, field: "fees.relayer_fee_bps", value: relayer_fee_bps.to_string() });
+        }
+        Ok(Profile {
+            cluster,
+            program_id: address("program_id", program_id)?,
+            rpc_urls,
+            relayer_url: layer.relayer_url,
+            mint: layer.mint.map(|mint| address("mint", mint)).transpose()?,
+            lookup_table: layer.lookup_table.map(|table| address("lookup_table", table)).transpose()?,
+            fees: FeeDefaults {
+                relayer_fee_bps,
+                relayer_min_fee: fee("fees.relayer_min_fee", layer.fees.relayer_min_fee)?,
+                quote_ttl_seconds: fee("fees.quote_ttl_seconds", layer.fees.quote_ttl_seconds)?,
+            },
+        })
+    }
+}
+
+fn env_number<T: FromStr>(env: &impl Fn(&str) -> Option<String>, variable: &'static str) -> Result<Option<T>, ConfigError> {
+    env(variable).map(|value| value.parse().map_err(|_| ConfigError::Env { variable, value })).transpose()
+}
+
+// The profile for `cluster` (or the default one) from the built-ins, PRIVAX_CONFIG and the
+// environment.
+pub fn load_profile(cluster: Option<Cluster>) -> Result<Profile, ConfigError> {
+    Config::load()?.profile(cluster)
+}
@@ -0,0 +1,76 @@
+// Layering of the built-in profiles, a PRIVAX_CONFIG file and PRIVAX_* variables.
+use std::collections::HashMap;
+
+use privax_config::{Cluster, Config, ConfigError};
+
+fn load(variables: &[(&str, &str)]) -> Result<Config, ConfigError> {
+    let variables: HashMap<String, String> = variables.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    Config::load_with(|variable| variables.get(variable).cloned())
+}
+
+#[test]
+fn builtin_profiles_resolve() {
+    let config = Config::builtin();
+    assert_eq!(config.cluster, Cluster::Localnet);
+    for cluster in Cluster::ALL {
+        let profile = config.profile(Some(cluster)).unwrap();
+        assert_eq!(profile.cluster, cluster);
+        assert!(!profile.rpc_urls.is_empty());
+    }
+}
+
+#[test]
+fn cluster_names() {
+    assert_eq!("mainnet-beta".parse::<Cluster>().unwrap(), Cluster::Mainnet);
+    assert_eq!("d".parse::<Cluster>().unwrap(), Cluster::Devnet);
+    assert!(matches!("testnet".parse::<Cluster>(), Err(ConfigError::UnknownCluster(_))));
+}
+
+#[test]
+fn file_overrides_fields_it_sets() {
+    let mut config = Config::builtin();
+    config
+        .layer_toml(
+            "cluster = \"devnet\"\n[devnet]\nrpc_urls = [\"https://a.example\", \"https://b.example\"]\n[devnet.fees]\nrelayer_fee_bps = 25\n",
+            "test.toml",
+        )
+        .unwrap();
+    let builtin = Config::builtin().profile(Some(Cluster::Devnet)).unwrap();
+    let profile = config.profile(None).unwrap();
+    assert_eq!(profile.cluster, Cluster::Devnet);
+    assert_eq!(profile.rpc_urls, ["https://a.example", "https://b.example"]);
+    assert_eq!(profile.fees.relayer_fee_bps, 25);
+    assert_eq!(profile.fees.quote_ttl_seconds, builtin.fees.quote_ttl_seconds);
+    assert_eq!(profile.program_id, builtin.program_id);
+}
+
+#[test]
+fn file_rejects_unknown_fields() {
+    let err = Config::builtin().layer_toml("[devnet]\nrpc_url = \"https://a.example\"\n", "test.toml").unwrap_err();
+    assert!(matches!(err, ConfigError::Parse { .. }), "{err}");
+}
+
+#[test]
+fn environment_overrides_the_picked_profile() {
+    let config = load(&[
+        ("PRIVAX_CLUSTER", "mainnet"),
+        ("PRIVAX_RPC_URL", "https://a.example, https://b.example"),
+        ("PRIVAX_MINT", "So11111111111111111111111111111111111111112"),
+        ("PRIVAX_QUOTE_TTL_SECONDS", "30"),
+    ])
+    .unwrap();
+    let profile = config.profile(None).unwrap();
+    assert_eq!(profile.cluster, Cluster::Mainnet);
+    assert_eq!(profile.rpc_urls, ["https://a.example", "https://b.example"]);
+    assert_eq!(profile.mint.unwrap().to_string(), "So11111111111111111111111111111111111111112");
+    assert_eq!(profile.fees.quote_ttl_seconds, 30);
+}
+
+#[test]
+fn invalid_values_are_errors() {
+    assert!(matches!(load(&[("PRIVAX_RELAYER_FEE_BPS", "lots")]), Err(ConfigError::Env { .. })));
+    let config = load(&[("PRIVAX_PROGRAM_ID", "not-an-address")]).unwrap();
+    assert!(matches!(config.profile(None), Err(ConfigError::Invalid { field: "program_id", .. })));
+    let config = load(&[("PRIVAX_RELAYER_FEE_BPS", "10001")]).unwrap();
+    assert!(matches!(config.profile(None), Err(ConfigError::Invalid { field: "fees.relayer_fee_bps", .. })));
+}
@@ -0,0 +1,233 @@
+// Decoding what the indexer needs from Privax transactions and accounts. Anchor events are
+// logged as "Program data: <base64>" with an 8-byte discriminator, sha256("event:<Name>"),
+// followed by the Borsh-encoded fields; privax_protocol's event structs keep their fields
+// private, so their layouts are spelled out here.
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_sdk::hash::hashv;
+use solana_sdk::pubkey::Pubkey;
+
+const DISCRIMINATOR_LEN: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    // A new leaf. Deposits carry the depositor, mint and amount; change notes don't.
+    Commitment { leaf_index: Option<u64>, commitment: [u8; 32], deposit: Option<(Pubkey, Pubkey, u64)> },
+    Nullifier { nullifier_hash: [u8; 32], recipient: Pubkey, mint: Pubkey, amount: u64 },
+    // A note encrypted for its recipient, announced for a commitment.
+    Note { commitment: [u8; 32], ciphertext: Vec<u8> },
+    // A depositor's attestation to a compliance envelope; the envelope goes to the relayer.
+    Attestation { depositor: Pubkey, commitment: [u8; 32], envelope_hash: [u8; 32] },
+    // The merkle tree account after a slot's last write to it, with the recent roots withdraw
+    // proofs may still use, and whether archived roots are accepted too.
+    Tree { next_index: u64, root: [u8; 32], roots: Vec<[u8; 32]>, accept_archived_roots: bool },
+    // A root archive page after a slot's last write to it: every root archived into it so far.
+    ArchivedRoots { page: u64, roots: Vec<[u8; 32]> },
+}
+
+impl Event {
+    pub fn to_json(&self, slot: u64, signature: Option<&str>) -> Value {
+        match self {
+            Self::Commitment { leaf_index, commitment, deposit } => json!({
+                "kind": "commitment",
+                "slot": slot,
+                "signature": signature,
+                "leaf_index": leaf_index,
+                "commitment": hex(commitment),
+                "user": deposit.map(|(user, _, _)| user.to_string()),
+                "mint": deposit.map(|(_, mint, _)| mint.to_string()),
+                "amount": deposit.map(|(_, _, amount)| amount),
+            }),
+            Self::Nullifier { nullifier_hash, recipient, mint, amount } => json!({
+                "kind": "nullifier",
+                "slot": slot,
+                "signature": signature,
+                "nullifier_hash": hex(nullifier_hash),
+                "recipient": recipient.to_string(),
+                "mint": mint.to_string(),
+                "amount": amount,
+            }),
+            Self::Note { commitment, ciphertext } => json!({
+                "kind": "note",
+                "slot": slot,
+                "signature": signature,
+                "commitment": hex(commitment),
+                "ciphertext": hex(ciphertext),
+            }),
+            Self::Attestation { depositor, commitment, envelope_hash } => json!({
+                "kind": "attestation",
+                "slot": slot,
+                "signature": signature,
+                "user": depositor.to_string(),
+                "commitment": hex(commitment),
+                "envelope_hash": hex(envelope_hash),
+            }),
+            Self::Tree { next_index, root, roots, accept_archived_roots } => json!({
+                "kind": "tree",
+                "slot": slot,
+                "next_index": next_index,
+                "root": hex(root),
+                "roots": roots.iter().map(|root| hex(root)).collect::<Vec<_>>(),
+                "accept_archived_roots": accept_archived_roots,
+            }),
+            Self::ArchivedRoots { page, roots } => json!({
+                "kind": "archived_roots",
+                "slot": slot,
+                "page": page,
+                "roots": roots.iter().map(|root| hex(root)).collect::<Vec<_>>(),
+            }),
+        }
+    }
+}
+
+// Events `program_id` logged in a successful transaction, in order. "Program data" lines are
+// attributed to the innermost program running, so another program's events (or a Privax
+// lookalike CPI'd by one) are ignored.
+pub fn from_logs(program_id: &Pubkey, logs: &[String]) -> Vec<Event> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    // MerkleRootUpdated precedes the deposits whose leaves it inserted.
+    let mut next_leaf: Option<(u64, u64)> = None; // (leaf index, leaves left)
+    for log in logs {
+        if let Some(rest) = log.strip_prefix("Program ") {
+            if let Some(data) = rest.strip_prefix("data: ") {
+                if stack.last() != Some(&program_id.as_str()) {
+                    continue;
+                }
+                let Ok(bytes) = STANDARD.decode(data) else { continue };
+                match decode(&bytes) {
+                    Some(Decoded::RootUpdated { first_leaf_index, leaf_count }) => next_leaf = Some((first_leaf_index, leaf_count)),
+                    Some(Decoded::Event(Event::Commitment { leaf_index: None, commitment, deposit })) => {
+                        let leaf_index = match next_leaf {
+                            Some((index, left)) if left > 0 => {
+                                next_leaf = Some((index + 1, left - 1));
+                                Some(index)
+                            }
+                            _ => None,
+                        };
+                        events.push(Event::Commitment { leaf_index, commitment, deposit });
+                    }
+                    Some(Decoded::Event(event)) => events.push(event),
+                    None => {}
+                }
+            } else if let Some((invoked, _depth)) = rest.split_once(" invoke [").filter(|(id, _)| id.parse::<Pubkey>().is_ok()) {
+                stack.push(invoked);
+            } else if let Some(status) = stack.last().and_then(|top| rest.strip_prefix(top)) {
+                if status == " success" || status.starts_with(" failed") {
+                    stack.pop();
+                }
+            }
+        }
+    }
+    events
+}
+
+// The tree's state from a MerkleTree account's data, or the archived roots from a
+// RootArchivePage's.
+pub fn from_account(data: &[u8]) -> Option<Event> {
+    if let Some(body) = data.strip_prefix(&discriminator("account:RootArchivePage")) {
+        return archived_roots(body);
+    }
+    // Zero-copy layout: next_index u64, current_root_index u64, bump u8, 7 bytes padding,
+    // filled_subtrees and zeros ([u8; 32] x MERKLE_TREE_DEPTH each), the root history, then
+    // root_count u64, archived_roots u64 and accept_archived_roots u8.
+    const DEPTH: usize = 20;
+    const ROOTS: usize = 30;
+    let body = data.strip_prefix(&discriminator("account:MerkleTree"))?;
+    let mut reader = Reader(body);
+    let next_index = reader.u64()?;
+    let current_root_index = usize::try_from(reader.u64()?).ok()?;
+    reader.take(8 + 2 * DEPTH * 32)?;
+    let roots = reader.take(ROOTS * 32)?;
+    let root = roots.chunks_exact(32).nth(current_root_index)?.try_into().ok()?;
+    let roots = nonzero(roots);
+    let accept_archived_roots = reader.take(16).and_then(|_| reader.take(1)).is_some_and(|flag| flag[0] != 0);
+    Some(Event::Tree { next_index, root, roots, accept_archived_roots })
+}
+
+// Zero-copy layout: page u64, count u64, bump u8, 7 bytes padding, then the roots.
+fn archived_roots(body: &[u8]) -> Option<Event> {
+    let mut reader = Reader(body);
+    let page = reader.u64()?;
+    let count = usize::try_from(reader.u64()?).ok()?;
+    reader.take(8)?;
+    let roots = nonzero(reader.take(count.checked_mul(32)?)?);
+    Some(Event::ArchivedRoots { page, roots })
+}
+
+// Unused history slots and roots lost before archiving are zero, which is never a known root.
+fn nonzero(roots: &[u8]) -> Vec<[u8; 32]> {
+    roots.chunks_exact(32).filter(|root| root.iter().any(|&byte| byte != 0)).map(|root| root.try_into().unwrap()).collect()
+}
+
+enum Decoded {
+    Event(Event),
+    RootUpdated { first_leaf_index: u64, leaf_count: u64 },
+}
+
+fn decode(bytes: &[u8]) -> Option<Decoded> {
+    let (tag, body) = (bytes.get(..DISCRIMINATOR_LEN)?, bytes.get(DISCRIMINATOR_LEN..)?);
+    let mut reader = Reader(body);
+    reader.take(1)?; // Event version
+    let decoded = if tag == discriminator("event:DepositOccurred") {
+        let (user, mint, amount, commitment) = (reader.pubkey()?, reader.pubkey()?, reader.u64()?, reader.bytes32()?);
+        Decoded::Event(Event::Commitment { leaf_index: None, commitment, deposit: Some((user, mint, amount)) })
+    } else if tag == discriminator("event:ChangeNoteCommitted") {
+        let (commitment, leaf_index) = (reader.bytes32()?, reader.u64()?);
+        Decoded::Event(Event::Commitment { leaf_index: Some(leaf_index), commitment, deposit: None })
+    } else if tag == discriminator("event:WithdrawalOccurred") {
+        let (nullifier_hash, recipient, mint, amount) = (reader.bytes32()?, reader.pubkey()?, reader.pubkey()?, reader.u64()?);
+        Decoded::Event(Event::Nullifier { nullifier_hash, recipient, mint, amount })
+    } else if tag == discriminator("event:NoteAnnounced") {
+        let commitment = reader.bytes32()?;
+        let len = usize::try_from(reader.u32()?).ok()?;
+        Decoded::Event(Event::Note { commitment, ciphertext: reader.take(len)?.to_vec() })
+    } else if tag == discriminator("event:DepositAttested") {
+        let (depositor, commitment, envelope_hash) = (reader.pubkey()?, reader.bytes32()?, reader.bytes32()?);
+        Decoded::Event(Event::Attestation { depositor, commitment, envelope_hash })
+    } else if tag == discriminator("event:MerkleRootUpdated") {
+        Decoded::RootUpdated { first_leaf_index: reader.u64()?, leaf_count: reader.u64()? }
+    } else {
+        return None;
+    };
+    Some(decoded)
+}
+
+fn discriminator(preimage: &str) -> [u8; DISCRIMINATOR_LEN] {
+    hashv(&[preimage.as_bytes()]).to_bytes()[..DISCRIMINATOR_LEN].try_into().unwrap()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn bytes32(&mut self) -> Option<[u8; 32]> {
+        self.take(32).map(|bytes| bytes.try_into().unwrap())
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        self.bytes32().map(Pubkey::new_from_array)
+    }
+}
@@ -0,0 +1,24 @@
+// Geyser plugin that streams Privax commitments, nullifiers and merkle tree updates straight
+// from a validator to the indexer (the relayer's POST /geyser/events), instead of the indexer
+// subscribing to program logs over RPC and missing whatever a dropped subscription misses.
+//
+// `events` decodes Privax transactions and accounts, `plugin` holds each slot's events until
+// the slot reaches the configured commitment, `sender` delivers them in order, and `metrics`
+// reports on all three.
+pub mod events;
+pub mod metrics;
+pub mod plugin;
+pub mod sender;
+
+use solana_geyser_plugin_interface::geyser_plugin_interface::GeyserPlugin;
+
+pub use plugin::PrivaxGeyserPlugin;
+
+/// # Safety
+///
+/// Called by the validator's plugin manager, which takes ownership of the returned plugin.
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+pub unsafe extern "C" fn _create_plugin() -> *mut dyn GeyserPlugin {
+    Box::into_raw(Box::<PrivaxGeyserPlugin>::default())
+}
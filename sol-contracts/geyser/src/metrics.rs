@@ -0,0 +1,97 @@
+// Operational metrics in Prometheus's text format, served on `metrics_addr` when the plugin
+// config sets one. A bare TcpListener is enough for a scrape every few seconds and keeps an
+// HTTP server's dependencies out of the validator.
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use log::warn;
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    pub queued_batches: AtomicU64, // Slots queued or being delivered
+    pub pending_slots: AtomicU64,  // Slots waiting to reach the configured commitment
+    pub batches_sent: AtomicU64,
+    pub events_sent: AtomicU64,
+    pub batches_dropped: AtomicU64,
+    pub indexer_errors: AtomicU64,
+    pub delivery_micros: AtomicU64, // Summed time from reaching commitment to the indexer's ack
+    pub processed_slot: AtomicU64,  // Highest slot the validator has processed
+    pub committed_slot: AtomicU64,  // Highest slot that reached the configured commitment
+    pub delivered_slot: AtomicU64,  // Highest slot the indexer has acknowledged
+}
+
+impl Metrics {
+    pub fn render(&self) -> String {
+        let get = |value: &AtomicU64| value.load(Ordering::Relaxed);
+        let processed = get(&self.processed_slot);
+        // Most slots have no Privax events to deliver; with nothing queued the indexer is
+        // caught up to the last committed slot.
+        let indexed = match get(&self.queued_batches) {
+            0 => get(&self.committed_slot).max(get(&self.delivered_slot)),
+            _ => get(&self.delivered_slot),
+        };
+        let metrics: [(&str, &str, &str, String); 9] = [
+            ("privax_geyser_queue_depth", "gauge", "Slots queued for or in delivery to the indexer", get(&self.queued_batches).to_string()),
+            ("privax_geyser_pending_slots", "gauge", "Slots waiting to reach the configured commitment", get(&self.pending_slots).to_string()),
+            ("privax_geyser_batches_sent_total", "counter", "Slots delivered to the indexer", get(&self.batches_sent).to_string()),
+            ("privax_geyser_events_sent_total", "counter", "Privax events delivered to the indexer", get(&self.events_sent).to_string()),
+            ("privax_geyser_batches_dropped_total", "counter", "Slots dropped because the queue was full", get(&self.batches_dropped).to_string()),
+            ("privax_geyser_indexer_errors_total", "counter", "Failed deliveries to the indexer, retried", get(&self.indexer_errors).to_string()),
+            ("privax_geyser_processed_slot", "gauge", "Highest slot the validator has processed", processed.to_string()),
+            ("privax_geyser_indexed_slot", "gauge", "Highest slot the indexer has all events up to", indexed.to_string()),
+            ("privax_geyser_lag_slots", "gauge", "Slots the indexer is behind the validator's tip", processed.saturating_sub(indexed).to_string()),
+        ];
+        let mut out = String::new();
+        for (name, kind, help, value) in metrics {
+            let _ = write!(out, "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n");
+        }
+        let name = "privax_geyser_delivery_seconds";
+        let _ = write!(
+            out,
+            "# HELP {name} Time from a slot reaching commitment to the indexer's ack\n# TYPE {name} summary\n\
+             {name}_sum {}\n{name}_count {}\n",
+            get(&self.delivery_micros) as f64 / 1e6,
+            get(&self.batches_sent),
+        );
+        out
+    }
+}
+
+pub fn serve(addr: SocketAddr, metrics: Arc<Metrics>, shutdown: Arc<AtomicBool>) -> io::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(addr)?;
+    // Non-blocking so the thread notices shutdown between connections.
+    listener.set_nonblocking(true)?;
+    thread::Builder::new().name("privax-geyser-metrics".to_string()).spawn(move || {
+        while !shutdown.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if let Err(err) = respond(stream, &metrics) {
+                        warn!("metrics request failed: {err}");
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(100)),
+                Err(err) => warn!("metrics listener failed to accept: {err}"),
+            }
+        }
+    })
+}
+
+fn respond(mut stream: TcpStream, metrics: &Metrics) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut request = [0u8; 1024];
+    let read = stream.read(&mut request)?;
+    let request = String::from_utf8_lossy(&request[..read]);
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+    let (status, body) = if path == "/metrics" { ("200 OK", metrics.render()) } else { ("404 Not Found", String::new()) };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
@@ -0,0 +1,252 @@
+// The plugin itself. Configured by the validator's plugin config file:
+//
+//   {
+//     "libpath": "/path/to/libprivax_geyser.so",
+//     "indexer_url": "http://127.0.0.1:8000",   // the relayer
+//     "program_id": "<Privax program>",         // defaults to privax_protocol::ID
+//     "commitment": "confirmed",                // or "rooted": when a slot's events are sent
+//     "queue_capacity": 10000,                  // slots waiting for the indexer
+//     "metrics_addr": "127.0.0.1:9184"          // optional Prometheus endpoint, GET /metrics
+//   }
+//
+// Transactions and account updates arrive as slots are processed, forks included, so each
+// slot's events wait here until the slot reaches the configured commitment. Slots that are
+// still waiting when a later slot is rooted were on abandoned forks and are dropped.
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use log::{debug, error, info};
+use serde_json::{json, Value};
+use solana_geyser_plugin_interface::geyser_plugin_interface::{
+    GeyserPlugin, GeyserPluginError, ReplicaAccountInfoVersions, ReplicaTransactionInfoVersions, Result, SlotStatus,
+};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::events::{self, Event};
+use crate::metrics::{self, Metrics};
+use crate::sender::{self, Batch};
+
+// privax_protocol's declare_id!; the plugin builds against the validator's Solana version
+// rather than the program's, so it can't depend on the program crate.
+const DEFAULT_PROGRAM_ID: &str = "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS";
+const DEFAULT_QUEUE_CAPACITY: usize = 10_000;
+
+#[derive(Debug, Default)]
+pub struct PrivaxGeyserPlugin {
+    running: Option<Running>,
+}
+
+#[derive(Debug)]
+struct Running {
+    program_id: Pubkey,
+    commitment: SlotStatus,
+    pending: Mutex<BTreeMap<u64, PendingSlot>>,
+    batches: SyncSender<Batch>,
+    metrics: Arc<Metrics>,
+    shutdown: Arc<AtomicBool>,
+    sender: JoinHandle<()>,
+    metrics_server: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug, Default)]
+struct PendingSlot {
+    transactions: Vec<(usize, String, Vec<Event>)>, // (index in block, signature, events)
+    tree: Option<(u64, Event)>,                     // Latest tree state by write version
+    archive: BTreeMap<u64, (u64, Event)>,           // Latest state of each archive page written
+}
+
+impl GeyserPlugin for PrivaxGeyserPlugin {
+    fn name(&self) -> &'static str {
+        "privax-geyser"
+    }
+
+    fn on_load(&mut self, config_file: &str, _is_reload: bool) -> Result<()> {
+        let config: Value = serde_json::from_str(&fs::read_to_string(config_file)?)
+            .map_err(|err| GeyserPluginError::ConfigFileReadError { msg: err.to_string() })?;
+        let invalid = |field: &str| GeyserPluginError::ConfigFileReadError { msg: format!("invalid `{field}`") };
+        let indexer_url = config["indexer_url"].as_str().ok_or_else(|| invalid("indexer_url"))?;
+        let program_id = config["program_id"].as_str().unwrap_or(DEFAULT_PROGRAM_ID);
+        let program_id = Pubkey::from_str(program_id).map_err(|_| invalid("program_id"))?;
+        let commitment = match config["commitment"].as_str() {
+            None | Some("confirmed") => SlotStatus::Confirmed,
+            Some("rooted") => SlotStatus::Rooted,
+            Some(_) => return Err(invalid("commitment")),
+        };
+        let queue_capacity = match &config["queue_capacity"] {
+            Value::Null => DEFAULT_QUEUE_CAPACITY,
+            capacity => capacity.as_u64().and_then(|capacity| usize::try_from(capacity).ok()).ok_or_else(|| invalid("queue_capacity"))?,
+        };
+        let metrics_addr = match config["metrics_addr"].as_str() {
+            None => None,
+            Some(addr) => Some(SocketAddr::from_str(addr).map_err(|_| invalid("metrics_addr"))?),
+        };
+
+        let (batches, receiver) = mpsc::sync_channel(queue_capacity);
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let url = format!("{}/geyser/events", indexer_url.trim_end_matches('/'));
+        info!("streaming Privax program {program_id} to {url} at {} commitment", commitment.as_str());
+        let metrics = Arc::new(Metrics::default());
+        let metrics_server = match metrics_addr {
+            Some(addr) => {
+                info!("serving metrics on http://{addr}/metrics");
+                Some(metrics::serve(addr, metrics.clone(), shutdown.clone())?)
+            }
+            None => None,
+        };
+        let sender = sender::spawn(url, receiver, metrics.clone(), shutdown.clone());
+        self.running = Some(Running {
+            program_id,
+            commitment,
+            pending: Mutex::default(),
+            batches,
+            metrics,
+            shutdown,
+            sender,
+            metrics_server,
+        });
+        Ok(())
+    }
+
+    fn on_unload(&mut self) {
+        if let Some(running) = self.running.take() {
+            running.shutdown.store(true, Ordering::Relaxed);
+            drop(running.batches);
+            if running.sender.join().is_err() {
+                error!("sender thread panicked");
+            }
+            if running.metrics_server.is_some_and(|server| server.join().is_err()) {
+                error!("metrics thread panicked");
+            }
+        }
+    }
+
+    fn update_account(&self, account: ReplicaAccountInfoVersions, slot: u64, is_startup: bool) -> Result<()> {
+        // Snapshot accounts at startup predate anything the indexer would be waiting for.
+        let Some(running) = self.running.as_ref().filter(|_| !is_startup) else {
+            return Ok(());
+        };
+        let (owner, data, write_version) = match account {
+            ReplicaAccountInfoVersions::V0_0_1(account) => (account.owner, account.data, account.write_version),
+            ReplicaAccountInfoVersions::V0_0_2(account) => (account.owner, account.data, account.write_version),
+            ReplicaAccountInfoVersions::V0_0_3(account) => (account.owner, account.data, account.write_version),
+        };
+        if owner != running.program_id.as_ref() {
+            return Ok(());
+        }
+        if let Some(event) = events::from_account(data) {
+            let mut pending = running.pending.lock().unwrap_or_else(PoisonError::into_inner);
+            let slot_pending = pending.entry(slot).or_default();
+            let newer = |latest: Option<&(u64, Event)>| !matches!(latest, Some((version, _)) if *version >= write_version);
+            match event {
+                Event::ArchivedRoots { page, .. } => {
+                    if newer(slot_pending.archive.get(&page)) {
+                        slot_pending.archive.insert(page, (write_version, event));
+                    }
+                }
+                _ => {
+                    if newer(slot_pending.tree.as_ref()) {
+                        slot_pending.tree = Some((write_version, event));
+                    }
+                }
+            }
+            running.metrics.pending_slots.store(pending.len() as u64, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn notify_transaction(&self, transaction: ReplicaTransactionInfoVersions, slot: u64) -> Result<()> {
+        let Some(running) = &self.running else {
+            return Ok(());
+        };
+        let (signature, is_vote, meta, index) = match transaction {
+            ReplicaTransactionInfoVersions::V0_0_1(info) => (info.signature, info.is_vote, info.transaction_status_meta, None),
+            ReplicaTransactionInfoVersions::V0_0_2(info) => {
+                (info.signature, info.is_vote, info.transaction_status_meta, Some(info.index))
+            }
+        };
+        // A failed transaction's logs can still carry the events it emitted before failing.
+        if is_vote || meta.status.is_err() {
+            return Ok(());
+        }
+        let events = events::from_logs(&running.program_id, meta.log_messages.as_deref().unwrap_or_default());
+        if events.is_empty() {
+            return Ok(());
+        }
+        let mut pending = running.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        let transactions = &mut pending.entry(slot).or_default().transactions;
+        // Without a block index, arrival order is the best guess.
+        let index = index.unwrap_or(transactions.len());
+        transactions.push((index, signature.to_string(), events));
+        running.metrics.pending_slots.store(pending.len() as u64, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn update_slot_status(&self, slot: u64, _parent: Option<u64>, status: SlotStatus) -> Result<()> {
+        let Some(running) = &self.running else {
+            return Ok(());
+        };
+        if status == SlotStatus::Processed {
+            running.metrics.processed_slot.fetch_max(slot, Ordering::Relaxed);
+        }
+        if status != running.commitment && status != SlotStatus::Rooted {
+            return Ok(());
+        }
+        running.metrics.committed_slot.fetch_max(slot, Ordering::Relaxed);
+        let mut pending = running.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(events) = pending.remove(&slot) {
+            running.send(slot, events);
+        }
+        if status == SlotStatus::Rooted {
+            let abandoned = pending.range(..slot).count();
+            if abandoned > 0 {
+                debug!("dropping {abandoned} slots below root {slot} from abandoned forks");
+                pending.retain(|&pending_slot, _| pending_slot > slot);
+            }
+        }
+        running.metrics.pending_slots.store(pending.len() as u64, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn account_data_notifications_enabled(&self) -> bool {
+        true
+    }
+
+    fn transaction_notifications_enabled(&self) -> bool {
+        true
+    }
+}
+
+impl Running {
+    fn send(&self, slot: u64, mut pending: PendingSlot) {
+        pending.transactions.sort_by_key(|(index, _, _)| *index);
+        let mut events: Vec<Value> = pending
+            .transactions
+            .iter()
+            .flat_map(|(_, signature, events)| events.iter().map(|event| event.to_json(slot, Some(signature))))
+            .collect();
+        events.extend(pending.archive.into_values().map(|(_, page)| page.to_json(slot, None)));
+        events.extend(pending.tree.map(|(_, tree)| tree.to_json(slot, None)));
+        let batch = Batch { slot, events: events.len(), body: json!({ "slot": slot, "events": events }), queued_at: Instant::now() };
+        // Counted before sending so the sender never decrements below zero.
+        self.metrics.queued_batches.fetch_add(1, Ordering::Relaxed);
+        let result = self.batches.try_send(batch);
+        if result.is_err() {
+            self.metrics.queued_batches.fetch_sub(1, Ordering::Relaxed);
+        }
+        match result {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                self.metrics.batches_dropped.fetch_add(1, Ordering::Relaxed);
+                error!("indexer queue is full, dropped slot {slot}; backfill it over RPC");
+            }
+            Err(TrySendError::Disconnected(_)) => error!("sender thread is gone, dropped slot {slot}"),
+        }
+    }
+}
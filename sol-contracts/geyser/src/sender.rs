@@ -0,0 +1,62 @@
+// Delivers event batches to the indexer from a thread of its own, so a slow or unreachable
+// indexer never stalls the validator. Batches go out in slot order and each is retried with
+// backoff until the indexer accepts it; the indexer ignores events it already has.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use log::warn;
+use serde_json::Value;
+
+use crate::metrics::Metrics;
+
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+// One slot's events, as posted to the indexer.
+pub struct Batch {
+    pub slot: u64,
+    pub events: usize,
+    pub body: Value,
+    pub queued_at: Instant, // When the slot reached the configured commitment
+}
+
+pub fn spawn(url: String, batches: Receiver<Batch>, metrics: Arc<Metrics>, shutdown: Arc<AtomicBool>) -> JoinHandle<()> {
+    thread::Builder::new()
+        .name("privax-geyser".to_string())
+        .spawn(move || {
+            for batch in batches {
+                let mut backoff = Duration::from_secs(1);
+                let delivered = loop {
+                    let Err(err) = post(&url, &batch.body) else { break true };
+                    metrics.indexer_errors.fetch_add(1, Ordering::Relaxed);
+                    if shutdown.load(Ordering::Relaxed) {
+                        warn!("dropping slot {} on shutdown: {err}", batch.slot);
+                        break false;
+                    }
+                    warn!("failed to deliver slot {} to {url}, retrying in {backoff:?}: {err}", batch.slot);
+                    thread::sleep(backoff);
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                };
+                if delivered {
+                    let micros = u64::try_from(batch.queued_at.elapsed().as_micros()).unwrap_or(u64::MAX);
+                    metrics.delivery_micros.fetch_add(micros, Ordering::Relaxed);
+                    metrics.batches_sent.fetch_add(1, Ordering::Relaxed);
+                    metrics.events_sent.fetch_add(batch.events as u64, Ordering::Relaxed);
+                    metrics.delivered_slot.fetch_max(batch.slot, Ordering::Relaxed);
+                }
+                metrics.queued_batches.fetch_sub(1, Ordering::Relaxed);
+            }
+        })
+        .expect("spawning the sender thread")
+}
+
+fn post(url: &str, batch: &Value) -> Result<(), Box<ureq::Error>> {
+    ureq::post(url)
+        .timeout(Duration::from_secs(10))
+        .set("Content-Type", "application/json")
+        .send_string(&batch.to_string())
+        .map(drop)
+        .map_err(Box::new)
+}
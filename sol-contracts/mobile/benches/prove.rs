@@ -0,0 +1,73 @@
+// Withdraw proving time on one core and on every core, the speedup the `parallel` feature buys:
+//
+//   cargo bench --bench prove
+//
+// With PRIVAX_WITHDRAW_CIRCUIT set to a directory holding the compiled withdraw circuit's
+// circuit.r1cs, proving_key.ark and witness.wtns (privax-prover's layout, plus a witness), it
+// proves those. Otherwise it proves a stand-in of the same size: the depth-20 withdraw circuit
+// is about 5,500 constraints, mostly its 22 Poseidon hashes.
+use std::time::{Duration, Instant};
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, ProvingKey};
+use privax_mobile::circom::{parse_witness, CircomCircuit, R1cs};
+use privax_mobile::prove::{check_witness, parse_proving_key, prove_checked};
+
+const STAND_IN_CONSTRAINTS: usize = 5_500;
+const PUBLIC_INPUTS: usize = 5; // merkleRoot, nullifierHash, recipient, amount, externalNullifier
+const RUNS: usize = 5;
+
+fn main() {
+    let (r1cs, proving_key, witness) = match std::env::var("PRIVAX_WITHDRAW_CIRCUIT") {
+        Ok(dir) => {
+            let read = |file: &str| std::fs::read(format!("{dir}/{file}")).unwrap_or_else(|err| panic!("{dir}/{file}: {err}"));
+            let r1cs = R1cs::parse(&read("circuit.r1cs")).unwrap();
+            (r1cs, parse_proving_key(&read("proving_key.ark")).unwrap(), parse_witness(&read("witness.wtns")).unwrap())
+        }
+        Err(_) => stand_in(),
+    };
+    check_witness(&r1cs, &witness).unwrap();
+    println!("{} constraints, {} wires, median of {RUNS} proofs", r1cs.constraints.len(), r1cs.wires);
+
+    let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+    let single = median(&r1cs, &proving_key, &witness, 1);
+    println!("  1 thread:   {:>8.1} ms", single.as_secs_f64() * 1e3);
+    if cores > 1 {
+        let all = median(&r1cs, &proving_key, &witness, cores);
+        let speedup = single.as_secs_f64() / all.as_secs_f64();
+        println!("  {cores} threads: {:>8.1} ms ({speedup:.1}x faster)", all.as_secs_f64() * 1e3);
+    }
+}
+
+// Checking the witness and proving, as proveWithdraw does after parsing.
+fn median(r1cs: &R1cs, proving_key: &ProvingKey<Bn254>, witness: &[Fr], threads: usize) -> Duration {
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            pool.install(|| {
+                let started = Instant::now();
+                check_witness(r1cs, witness).unwrap();
+                prove_checked(proving_key, r1cs, witness).unwrap();
+                started.elapsed()
+            })
+        })
+        .collect();
+    times.sort();
+    times[RUNS / 2]
+}
+
+// A chain of squarings, wire i + 1 = wire i * wire i, with the first wires public.
+fn stand_in() -> (R1cs, ProvingKey<Bn254>, Vec<Fr>) {
+    let wires = STAND_IN_CONSTRAINTS + 2;
+    let one = |wire: usize| vec![(wire, Fr::from(1u64))];
+    let constraints = (1..wires - 1).map(|wire| (one(wire), one(wire), one(wire + 1))).collect();
+    let r1cs = R1cs { wires, public_inputs: PUBLIC_INPUTS, constraints };
+    let mut witness = vec![Fr::from(1u64), Fr::from(3u64)];
+    while witness.len() < wires {
+        let last = *witness.last().unwrap();
+        witness.push(last * last);
+    }
+    let circuit = CircomCircuit { r1cs: &r1cs, witness: None };
+    let proving_key = Groth16::<Bn254>::generate_random_parameters_with_reduction(circuit, &mut rand::thread_rng()).unwrap();
+    (r1cs, proving_key, witness)
+}
@@ -0,0 +1,4 @@
+// Generates the Kotlin and Swift bindings; see lib.rs.
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
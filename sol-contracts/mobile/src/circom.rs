@@ -0,0 +1,156 @@
+// Readers for circom's binary outputs: the constraint system (`circom --r1cs`, "r1cs" v1) and
+// witnesses from its generated witness calculator ("wtns" v2). Both are sections of
+// (type u32, size u64, body) after a magic and version; values are little-endian, and field
+// elements are stored in standard (not Montgomery) form.
+use std::collections::HashMap;
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{PrivaxMobileError, Result};
+
+const FIELD_SIZE: usize = 32;
+// BN254 scalar field modulus, little-endian, as the files' headers carry it.
+const PRIME: [u8; FIELD_SIZE] = [
+    0x01, 0x00, 0x00, 0xf0, 0x93, 0xf5, 0xe1, 0x43, 0x91, 0x70, 0xb9, 0x79, 0x48, 0xe8, 0x33, 0x28,
+    0x5d, 0x58, 0x81, 0x81, 0xb6, 0x45, 0x50, 0xb8, 0x29, 0xa0, 0x31, 0xe1, 0x72, 0x4e, 0x64, 0x30,
+];
+
+// Sparse row of a constraint: (wire, coefficient) pairs.
+pub type Terms = Vec<(usize, Fr)>;
+
+pub struct R1cs {
+    pub wires: usize,
+    pub public_inputs: usize, // Public outputs and inputs, wires 1..=public_inputs
+    pub constraints: Vec<(Terms, Terms, Terms)>, // a * b = c
+}
+
+impl R1cs {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let invalid = |what: &str| PrivaxMobileError::InvalidCircuit(what.to_string());
+        let sections = sections(bytes, b"r1cs", 1).ok_or_else(|| invalid("not an r1cs v1 file"))?;
+        let mut header = Reader(sections.get(&1).copied().ok_or_else(|| invalid("missing header"))?);
+        check_prime(&mut header).ok_or_else(|| invalid("not over the BN254 scalar field"))?;
+        let (wires, outputs, inputs, _private_inputs, _labels, constraint_count) = (|| {
+            Some((header.u32()?, header.u32()?, header.u32()?, header.u32()?, header.u64()?, header.u32()?))
+        })()
+        .ok_or_else(|| invalid("truncated header"))?;
+        let (wires, public_inputs) = (wires as usize, outputs as usize + inputs as usize);
+
+        let mut body = Reader(sections.get(&2).copied().ok_or_else(|| invalid("missing constraints"))?);
+        let mut constraints = Vec::with_capacity(constraint_count as usize);
+        for _ in 0..constraint_count {
+            let mut terms = || -> Option<Terms> {
+                let count = body.u32()?;
+                (0..count).map(|_| Some((body.u32()? as usize, body.field()?))).collect()
+            };
+            let constraint = (|| Some((terms()?, terms()?, terms()?)))().ok_or_else(|| invalid("truncated constraints"))?;
+            let mut all_terms = constraint.0.iter().chain(&constraint.1).chain(&constraint.2);
+            if all_terms.any(|&(wire, _)| wire >= wires) {
+                return Err(invalid("constraint references an unknown wire"));
+            }
+            constraints.push(constraint);
+        }
+        Ok(Self { wires, public_inputs, constraints })
+    }
+
+    // The first constraint `witness` breaks, if any.
+    pub fn unsatisfied(&self, witness: &[Fr]) -> Option<usize> {
+        let eval = |terms: &Terms| terms.iter().map(|&(wire, coeff)| coeff * witness[wire]).sum::<Fr>();
+        let broken = |(a, b, c): &(Terms, Terms, Terms)| eval(a) * eval(b) != eval(c);
+        #[cfg(feature = "parallel")]
+        return self.constraints.par_iter().position_first(broken);
+        #[cfg(not(feature = "parallel"))]
+        self.constraints.iter().position(broken)
+    }
+}
+
+// Reads a witness, one value per wire with wire 0 the constant 1.
+pub fn parse_witness(bytes: &[u8]) -> Result<Vec<Fr>> {
+    let invalid = |what: &str| PrivaxMobileError::InvalidWitness(what.to_string());
+    let sections = sections(bytes, b"wtns", 2).ok_or_else(|| invalid("not a wtns v2 file"))?;
+    let mut header = Reader(sections.get(&1).copied().ok_or_else(|| invalid("missing header"))?);
+    check_prime(&mut header).ok_or_else(|| invalid("not over the BN254 scalar field"))?;
+    let count = header.u32().ok_or_else(|| invalid("truncated header"))?;
+    let mut body = Reader(sections.get(&2).copied().ok_or_else(|| invalid("missing values"))?);
+    (0..count).map(|_| body.field().ok_or_else(|| invalid("truncated values"))).collect()
+}
+
+// The circuit's constraints with an optional assignment: without one it drives the setup,
+// with one the prover.
+pub struct CircomCircuit<'a> {
+    pub r1cs: &'a R1cs,
+    pub witness: Option<&'a [Fr]>,
+}
+
+impl ConstraintSynthesizer<Fr> for CircomCircuit<'_> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> std::result::Result<(), SynthesisError> {
+        let value = |wire: usize| self.witness.map(|witness| witness[wire]).ok_or(SynthesisError::AssignmentMissing);
+        let mut variables = vec![Variable::One];
+        for wire in 1..self.r1cs.wires {
+            let variable = if wire <= self.r1cs.public_inputs {
+                cs.new_input_variable(|| value(wire))?
+            } else {
+                cs.new_witness_variable(|| value(wire))?
+            };
+            variables.push(variable);
+        }
+        let combination = |terms: &Terms| {
+            terms.iter().fold(lc!(), |sum: LinearCombination<Fr>, &(wire, coeff)| sum + (coeff, variables[wire]))
+        };
+        for (a, b, c) in &self.r1cs.constraints {
+            cs.enforce_constraint(combination(a), combination(b), combination(c))?;
+        }
+        Ok(())
+    }
+}
+
+fn sections<'a>(bytes: &'a [u8], magic: &[u8; 4], version: u32) -> Option<HashMap<u32, &'a [u8]>> {
+    let mut reader = Reader(bytes.strip_prefix(magic)?);
+    if reader.u32()? != version {
+        return None;
+    }
+    let mut sections = HashMap::new();
+    for _ in 0..reader.u32()? {
+        let kind = reader.u32()?;
+        let size = usize::try_from(reader.u64()?).ok()?;
+        sections.insert(kind, reader.take(size)?);
+    }
+    Some(sections)
+}
+
+fn check_prime(header: &mut Reader) -> Option<()> {
+    (header.u32()? as usize == FIELD_SIZE && header.take(FIELD_SIZE)? == PRIME).then_some(())
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    // Values at or above the modulus are malformed rather than reduced.
+    fn field(&mut self) -> Option<Fr> {
+        let bytes = self.take(FIELD_SIZE)?;
+        let value = Fr::from_le_bytes_mod_order(bytes);
+        (value.into_bigint().to_bytes_le() == bytes).then_some(value)
+    }
+}
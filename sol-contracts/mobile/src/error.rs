@@ -0,0 +1,24 @@
+// Errors crossing the FFI. Flat, so Kotlin and Swift see one exception type per variant with
+// the message below.
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum PrivaxMobileError {
+    #[error("invalid note: {0}")]
+    InvalidNote(String),
+    #[error("invalid field element: {0}")]
+    InvalidFieldElement(String),
+    #[error("leaf {index} is not in a tree of {leaves} leaves")]
+    UnknownLeaf { index: u64, leaves: u64 },
+    #[error("too many leaves for a depth-{0} tree")]
+    TreeFull(usize),
+    #[error("invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("invalid circuit file: {0}")]
+    InvalidCircuit(String),
+    #[error("invalid witness: {0}")]
+    InvalidWitness(String),
+    #[error("proving failed: {0}")]
+    Proving(String),
+    #[error("invalid payouts: {0}")]
+    InvalidPayouts(String),
+}
@@ -0,0 +1,58 @@
+// Proving on a GPU through an external prover with rapidsnark's command line,
+// `<program> <circuit.zkey> <witness.wtns> <proof.json> <public.json>`, such as rapidsnark's
+// GPU fork or ICICLE's snark prover, for relayer operators who need sub-second proofs in bulk.
+// No GPU library is linked, so this builds without a CUDA toolchain. The prover runs over the
+// circuit's .zkey rather than an arkworks key, and only the proof is read back: the public
+// signals come from the witness.
+use std::path::PathBuf;
+use std::process::Command;
+use std::str::FromStr;
+
+use ark_bn254::Fq;
+use ark_ff::{BigInteger, PrimeField};
+use serde_json::Value;
+
+use crate::circom::{parse_witness, R1cs};
+use crate::prove::{public_signals, WithdrawProof};
+use crate::{PrivaxMobileError, Result};
+
+#[derive(Clone, Debug)]
+pub struct GpuProver {
+    pub program: PathBuf,
+    pub zkey: PathBuf,
+    pub device: Option<usize>, // CUDA device index, through CUDA_VISIBLE_DEVICES; None for the default
+}
+
+impl GpuProver {
+    // Proves a witness that passed prove::check_witness against `r1cs`.
+    pub fn prove(&self, r1cs: &R1cs, witness: &[u8]) -> Result<WithdrawProof> {
+        let failed = |what: String| PrivaxMobileError::Proving(format!("{}: {what}", self.program.display()));
+        let signals = public_signals(r1cs, &parse_witness(witness)?);
+        // Private to this process, and removed with the witness in it when dropped
+        let dir = tempfile::tempdir().map_err(|err| failed(format!("temporary directory: {err}")))?;
+        let paths = ["witness.wtns", "proof.json", "public.json"].map(|file| dir.path().join(file));
+        std::fs::write(&paths[0], witness).map_err(|err| failed(err.to_string()))?;
+
+        let mut command = Command::new(&self.program);
+        command.arg(&self.zkey).args(&paths);
+        if let Some(device) = self.device {
+            command.env("CUDA_VISIBLE_DEVICES", device.to_string());
+        }
+        let output = command.output().map_err(|err| failed(err.to_string()))?;
+        if !output.status.success() {
+            return Err(failed(format!("exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim())));
+        }
+        let proof = std::fs::read(&paths[1]).map_err(|err| failed(format!("proof.json: {err}")))?;
+        let proof: Value = serde_json::from_slice(&proof).map_err(|err| failed(format!("proof.json: {err}")))?;
+        snarkjs_proof(&proof, signals).ok_or_else(|| failed("unexpected proof.json".to_string()))
+    }
+}
+
+// A snarkjs-format proof (decimal coordinates, pi_b's as [c0, c1]) in withdraw's a/b/c layout.
+fn snarkjs_proof(proof: &Value, public_signals: Vec<Vec<u8>>) -> Option<WithdrawProof> {
+    let coordinate = |value: &Value| Some(Fq::from_str(value.as_str()?).ok()?.into_bigint().to_bytes_be());
+    let g1 = |point: &Value| Some([coordinate(&point[0])?, coordinate(&point[1])?].concat());
+    let pi_b = &proof["pi_b"];
+    let b = [coordinate(&pi_b[0][1])?, coordinate(&pi_b[0][0])?, coordinate(&pi_b[1][1])?, coordinate(&pi_b[1][0])?].concat();
+    Some(WithdrawProof { a: g1(&proof["pi_a"])?, b, c: g1(&proof["pi_c"])?, public_signals })
+}
@@ -0,0 +1,25 @@
+// Kotlin/Swift bindings (UniFFI) for the wallet-side layers of Privax, so iOS and Android
+// wallets call the same Poseidon, note format and prover as everything else.
+//
+// `note` creates, encodes and hashes deposit notes, and `tornado` reads and writes them as
+// EVM-mixer-style backup strings. `scan` finds a wallet's notes among the pool's commitments
+// and builds their Merkle paths. `prove` turns a note and its path into the witness
+// calculator's inputs and proves a withdraw with Groth16. `circom` reads the compiled circuit
+// and witness files the prover takes. `gpu`, behind the feature of the same name, proves with
+// an external GPU prover instead, for desktop wallets and servers. Generate bindings from the
+// built library with `cargo run --bin uniffi-bindgen generate --library <lib> --language kotlin`
+// (or `swift`).
+pub mod circom;
+pub mod error;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod note;
+pub mod prove;
+pub mod scan;
+pub mod tornado;
+
+pub use error::PrivaxMobileError;
+
+pub type Result<T> = std::result::Result<T, PrivaxMobileError>;
+
+uniffi::setup_scaffolding!();
@@ -0,0 +1,155 @@
+// Deposit notes: the secrets behind a commitment, hashed as circuits/withdraw.circom does with
+// circomlib's Poseidon over BN254:
+//
+//   commitment    = Poseidon(amount, secret, nullifierSecret)
+//   nullifierHash = Poseidon(nullifierSecret, 1)
+//
+// Field elements cross the FFI as 32 big-endian bytes. A wallet backs a note up as
+//
+//   privax-note-v1-<amount>-<secret hex>-<nullifier secret hex>
+use std::fmt;
+use std::sync::Arc;
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use light_poseidon::{Poseidon, PoseidonHasher};
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::{PrivaxMobileError, Result};
+
+const PREFIX: &str = "privax-note-v1";
+// 31 bytes always fit below the field modulus, as in circuits/utils/merkleTree.ts.
+pub(crate) const SECRET_LEN: usize = 31;
+const NULLIFIER_DOMAIN: u64 = 1;
+
+#[derive(uniffi::Object, Clone, PartialEq, Eq)]
+pub struct Note {
+    amount: u64,
+    secret: [u8; SECRET_LEN],
+    nullifier_secret: [u8; SECRET_LEN],
+}
+
+#[uniffi::export]
+impl Note {
+    // A fresh note for `amount` base units of the pool's mint.
+    #[uniffi::constructor]
+    pub fn generate(amount: u64) -> Arc<Self> {
+        let mut note = Self { amount, secret: [0; SECRET_LEN], nullifier_secret: [0; SECRET_LEN] };
+        OsRng.fill_bytes(&mut note.secret);
+        OsRng.fill_bytes(&mut note.nullifier_secret);
+        Arc::new(note)
+    }
+
+    // Reads a note back from its backup string.
+    #[uniffi::constructor]
+    pub fn parse(encoded: String) -> Result<Arc<Self>> {
+        let invalid = |what: &str| PrivaxMobileError::InvalidNote(what.to_string());
+        let fields = encoded.trim().strip_prefix(PREFIX).and_then(|rest| rest.strip_prefix('-')).ok_or_else(|| invalid("not a privax-note-v1 string"))?;
+        let mut fields = fields.split('-');
+        let (Some(amount), Some(secret), Some(nullifier_secret), None) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+            return Err(invalid("expected amount, secret and nullifier secret"));
+        };
+        Ok(Arc::new(Self {
+            amount: amount.parse().map_err(|_| invalid("amount"))?,
+            secret: parse_secret(secret).ok_or_else(|| invalid("secret"))?,
+            nullifier_secret: parse_secret(nullifier_secret).ok_or_else(|| invalid("nullifier secret"))?,
+        }))
+    }
+
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    // What `deposit` takes as its commitment.
+    pub fn commitment(&self) -> Vec<u8> {
+        field_bytes(self.commitment_field())
+    }
+
+    pub fn nullifier_hash(&self) -> Vec<u8> {
+        field_bytes(self.nullifier_hash_field())
+    }
+
+    pub fn encode(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Note {
+    pub(crate) fn from_secrets(amount: u64, secret: [u8; SECRET_LEN], nullifier_secret: [u8; SECRET_LEN]) -> Self {
+        Self { amount, secret, nullifier_secret }
+    }
+
+    // (secret, nullifier secret), for other encodings of the note.
+    pub(crate) fn secrets(&self) -> (&[u8; SECRET_LEN], &[u8; SECRET_LEN]) {
+        (&self.secret, &self.nullifier_secret)
+    }
+
+    pub fn secret_field(&self) -> Fr {
+        Fr::from_be_bytes_mod_order(&self.secret)
+    }
+
+    pub fn nullifier_secret_field(&self) -> Fr {
+        Fr::from_be_bytes_mod_order(&self.nullifier_secret)
+    }
+
+    pub fn commitment_field(&self) -> Fr {
+        poseidon(&[Fr::from(self.amount), self.secret_field(), self.nullifier_secret_field()])
+    }
+
+    pub fn nullifier_hash_field(&self) -> Fr {
+        poseidon(&[self.nullifier_secret_field(), Fr::from(NULLIFIER_DOMAIN)])
+    }
+}
+
+// Debug output ends up in logs and panic messages, so it names the note by its commitment.
+impl fmt::Debug for Note {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Note").field("amount", &self.amount).field("commitment", &hex(&self.commitment())).finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{PREFIX}-{}-{}-{}", self.amount, hex(&self.secret), hex(&self.nullifier_secret))
+    }
+}
+
+// circomlib's Poseidon for 1 to 12 inputs; callers pass a fixed count within that.
+pub fn poseidon(inputs: &[Fr]) -> Fr {
+    Poseidon::<Fr>::new_circom(inputs.len())
+        .and_then(|mut hasher| hasher.hash(inputs))
+        .expect("circomlib Poseidon supports this input count")
+}
+
+pub fn field_bytes(value: Fr) -> Vec<u8> {
+    value.into_bigint().to_bytes_be()
+}
+
+// Reads 32 big-endian bytes, rejecting values at or above the modulus so each element has
+// one encoding.
+pub fn parse_field(bytes: &[u8]) -> Result<Fr> {
+    if bytes.len() != 32 {
+        return Err(PrivaxMobileError::InvalidFieldElement(format!("expected 32 bytes, got {}", bytes.len())));
+    }
+    let value = Fr::from_be_bytes_mod_order(bytes);
+    if field_bytes(value) != bytes {
+        return Err(PrivaxMobileError::InvalidFieldElement("not below the BN254 scalar field modulus".to_string()));
+    }
+    Ok(value)
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub(crate) fn parse_secret(s: &str) -> Option<[u8; SECRET_LEN]> {
+    if s.len() != 2 * SECRET_LEN || !s.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0u8; SECRET_LEN];
+    for (byte, pair) in bytes.iter_mut().zip(s.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(bytes)
+}
@@ -0,0 +1,189 @@
+// Withdraw proofs. A wallet ships the compiled circuit (.r1cs), its witness calculator and
+// the Groth16 proving key:
+//
+// 1. `withdraw_inputs` gives the witness calculator's input JSON for a note and its path.
+// 2. The witness calculator (circom's generated C++ or wasm) writes the witness (.wtns).
+// 3. `prove_withdraw` proves with arkworks over the .r1cs and returns withdraw's arguments.
+//
+// Split withdrawals go the same way, with `split_inputs` and the split circuit's files.
+//
+// The proving key is arkworks-serialized and must come from a setup over the same .r1cs; its
+// verifying key is what gets registered on-chain, through proof_format::vk_from_arkworks.
+use std::sync::Arc;
+
+use anchor_lang::prelude::Pubkey;
+use ark_bn254::{Bn254, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, ProvingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use privax_protocol::privax_protocol::VARIABLE_DENOMINATION;
+use privax_protocol::MAX_SPLIT_RECIPIENTS;
+use privax_protocol::proof_format;
+use rand::rngs::OsRng;
+
+use crate::circom::{parse_witness, CircomCircuit, R1cs};
+use crate::note::{field_bytes, parse_field, Note};
+use crate::scan::MerklePath;
+use crate::{PrivaxMobileError, Result};
+
+// Groth16 proof in the on-chain layout withdraw takes (a_proof, b_proof, c_proof), with the
+// circuit's public signals as 32 big-endian bytes each.
+#[derive(uniffi::Record, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawProof {
+    pub a: Vec<u8>,
+    pub b: Vec<u8>,
+    pub c: Vec<u8>,
+    pub public_signals: Vec<Vec<u8>>,
+}
+
+// The u64 withdraw takes in place of a 32-byte public input: its first 8 bytes, little-endian.
+#[uniffi::export]
+pub fn public_input(value: &[u8]) -> u64 {
+    let mut first = [0u8; 8];
+    let len = value.len().min(8);
+    first[..len].copy_from_slice(&value[..len]);
+    u64::from_le_bytes(first)
+}
+
+// Witness calculator input (circuits/withdraw.circom's signals, as decimal strings) for
+// withdrawing `note` to `recipient` from the pool for `mint`. `proof_slot` is a recent slot;
+// `relayer`, when set, is the only key that may submit the proof, for `relayer_fee` of the
+// amount (0 without a relayer).
+#[uniffi::export]
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_inputs(
+    note: Arc<Note>,
+    path: MerklePath,
+    recipient: String,
+    mint: String,
+    circuit_version: u16,
+    proof_slot: u64,
+    relayer: Option<String>,
+    relayer_fee: u64,
+) -> Result<String> {
+    let address = |s: &str| s.parse::<Pubkey>().map_err(|_| PrivaxMobileError::InvalidAddress(s.to_string()));
+    let (recipient, mint) = (address(&recipient)?, address(&mint)?);
+    let relayer = relayer.as_deref().map(address).transpose()?;
+    let external_nullifier = privax_protocol::external_nullifier(&mint, VARIABLE_DENOMINATION, circuit_version);
+    let decimal = |value: Fr| format!("\"{}\"", value.into_bigint());
+    let elements = path.elements.iter().map(|element| parse_field(element).map(decimal)).collect::<Result<Vec<_>>>()?;
+    let indices: Vec<String> = path.indices.iter().map(|&right| u8::from(right).to_string()).collect();
+    Ok(format!(
+        r#"{{"secret":{},"nullifierSecret":{},"pathElements":[{}],"pathIndices":[{}],"merkleRoot":{},"nullifierHash":{},"recipient":"{}","amount":"{}","externalNullifier":"{}","proofSlot":"{}","relayerFee":"{}","relayer":"{}"}}"#,
+        decimal(note.secret_field()),
+        decimal(note.nullifier_secret_field()),
+        elements.join(","),
+        indices.join(","),
+        decimal(parse_field(&path.root)?),
+        decimal(note.nullifier_hash_field()),
+        public_input(recipient.as_ref()),
+        note.amount(),
+        external_nullifier,
+        proof_slot,
+        relayer_fee,
+        relayer.map_or(0, |relayer| public_input(relayer.as_ref())),
+    ))
+}
+
+// One payout of a split withdrawal, as circuits/split.circom and withdraw_split take it.
+#[derive(uniffi::Record, Clone, Debug, PartialEq, Eq)]
+pub struct SplitPayout {
+    pub recipient: String,
+    pub amount: u64,
+}
+
+// Witness calculator input for circuits/split.circom: paying `note` out as `payouts`, whose
+// amounts must add up to the note's, from the pool for `mint`. `proof_slot` is a recent slot;
+// the program rejects the proof MAX_PROOF_AGE_SLOTS after it. `relayer`, when set, is the only
+// key that may submit the proof, and its fee is what the payouts pay it. prove_withdraw proves it
+// like a withdrawal, given the split circuit's files.
+#[uniffi::export]
+pub fn split_inputs(
+    note: Arc<Note>,
+    path: MerklePath,
+    payouts: Vec<SplitPayout>,
+    mint: String,
+    circuit_version: u16,
+    proof_slot: u64,
+    relayer: Option<String>,
+) -> Result<String> {
+    let address = |s: &str| s.parse::<Pubkey>().map_err(|_| PrivaxMobileError::InvalidAddress(s.to_string()));
+    if payouts.is_empty() || payouts.len() > MAX_SPLIT_RECIPIENTS {
+        return Err(PrivaxMobileError::InvalidPayouts(format!("expected 1 to {MAX_SPLIT_RECIPIENTS} payouts")));
+    }
+    if payouts.iter().map(|payout| u128::from(payout.amount)).sum::<u128>() != u128::from(note.amount()) {
+        return Err(PrivaxMobileError::InvalidPayouts("the payouts don't add up to the note's amount".to_string()));
+    }
+    let relayer = relayer.as_deref().map(address).transpose()?;
+    let (mut pairs, mut relayer_fee) = (Vec::with_capacity(MAX_SPLIT_RECIPIENTS), 0);
+    for payout in &payouts {
+        let recipient = address(&payout.recipient)?;
+        if Some(recipient) == relayer {
+            relayer_fee += payout.amount;
+        }
+        pairs.push(format!(r#"["{}","{}"]"#, public_input(recipient.as_ref()), payout.amount));
+    }
+    pairs.resize(MAX_SPLIT_RECIPIENTS, r#"["0","0"]"#.to_string());
+
+    let external_nullifier = privax_protocol::split_external_nullifier(&address(&mint)?, circuit_version);
+    let decimal = |value: Fr| format!("\"{}\"", value.into_bigint());
+    let elements = path.elements.iter().map(|element| parse_field(element).map(decimal)).collect::<Result<Vec<_>>>()?;
+    let indices: Vec<String> = path.indices.iter().map(|&right| u8::from(right).to_string()).collect();
+    Ok(format!(
+        r#"{{"secret":{},"nullifierSecret":{},"pathElements":[{}],"pathIndices":[{}],"merkleRoot":{},"nullifierHash":{},"amount":"{}","externalNullifier":"{}","proofSlot":"{}","relayerFee":"{}","relayer":"{}","payouts":[{}]}}"#,
+        decimal(note.secret_field()),
+        decimal(note.nullifier_secret_field()),
+        elements.join(","),
+        indices.join(","),
+        decimal(parse_field(&path.root)?),
+        decimal(note.nullifier_hash_field()),
+        note.amount(),
+        external_nullifier,
+        proof_slot,
+        relayer_fee,
+        relayer.map_or(0, |relayer| public_input(relayer.as_ref())),
+        pairs.join(","),
+    ))
+}
+
+#[uniffi::export]
+pub fn prove_withdraw(proving_key: &[u8], r1cs: &[u8], witness: &[u8]) -> Result<WithdrawProof> {
+    let r1cs = R1cs::parse(r1cs)?;
+    let witness = parse_witness(witness)?;
+    check_witness(&r1cs, &witness)?;
+    let proving_key = parse_proving_key(proving_key)?;
+    prove_checked(&proving_key, &r1cs, &witness)
+}
+
+// The prover doesn't check the witness, and a bad one would only show up as a rejected proof.
+pub fn check_witness(r1cs: &R1cs, witness: &[Fr]) -> Result<()> {
+    if witness.len() != r1cs.wires {
+        return Err(PrivaxMobileError::InvalidWitness(format!("{} values for {} wires", witness.len(), r1cs.wires)));
+    }
+    if let Some(constraint) = r1cs.unsatisfied(witness) {
+        return Err(PrivaxMobileError::InvalidWitness(format!("constraint {constraint} is not satisfied")));
+    }
+    Ok(())
+}
+
+pub fn parse_proving_key(bytes: &[u8]) -> Result<ProvingKey<Bn254>> {
+    ProvingKey::<Bn254>::deserialize_uncompressed(bytes).map_err(|err| PrivaxMobileError::Proving(err.to_string()))
+}
+
+// prove_withdraw's last step, for callers that keep a circuit's files parsed between proofs
+// (the privax-prover service). `witness` must have passed check_witness.
+pub fn prove_checked(proving_key: &ProvingKey<Bn254>, r1cs: &R1cs, witness: &[Fr]) -> Result<WithdrawProof> {
+    let proving = |err: &dyn std::fmt::Display| PrivaxMobileError::Proving(err.to_string());
+    let circuit = CircomCircuit { r1cs, witness: Some(witness) };
+    let proof = Groth16::<Bn254>::create_random_proof_with_reduction(circuit, proving_key, &mut OsRng).map_err(|err| proving(&err))?;
+
+    let mut serialized = Vec::new();
+    proof.serialize_uncompressed(&mut serialized).map_err(|err| proving(&err))?;
+    let (a, b, c) = proof_format::proof_from_arkworks(&serialized).map_err(|err| proving(&err))?;
+    Ok(WithdrawProof { a: a.to_vec(), b: b.to_vec(), c: c.to_vec(), public_signals: public_signals(r1cs, witness) })
+}
+
+// The circuit's public outputs and inputs, 32 big-endian bytes each.
+pub fn public_signals(r1cs: &R1cs, witness: &[Fr]) -> Vec<Vec<u8>> {
+    witness[1..=r1cs.public_inputs].iter().map(|&signal| field_bytes(signal)).collect()
+}
@@ -0,0 +1,69 @@
+// Finding a wallet's notes among the pool's deposits. The wallet passes every commitment in
+// leaf order (from DepositOccurred and ChangeNoteCommitted events, or an indexer) and the
+// nullifier hashes withdrawals have published; nothing here touches the network.
+//
+// Paths follow the commitment tree: Poseidon(left, right) nodes over zero-valued empty
+// leaves, MERKLE_TREE_DEPTH levels, as the circuit and the program (privax_protocol::hash_pair)
+// both hash it, so a path's root is one of the pool's roots.
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use ark_bn254::Fr;
+use privax_protocol::MERKLE_TREE_DEPTH;
+
+use crate::note::{field_bytes, parse_field, poseidon, Note};
+use crate::{PrivaxMobileError, Result};
+
+#[derive(uniffi::Record)]
+pub struct OwnedNote {
+    pub note: Arc<Note>,
+    pub leaf_index: u64,
+    pub spent: bool,
+}
+
+#[derive(uniffi::Record, Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    pub root: Vec<u8>,
+    pub elements: Vec<Vec<u8>>, // Sibling at each level, leaf level first
+    pub indices: Vec<bool>,     // Whether the path node is the right child at each level
+}
+
+// The wallet's notes that are in the tree, with whether a withdrawal spent them. Published
+// nullifiers are the full hashes the program records; a note is spent if its hash is one.
+#[uniffi::export]
+pub fn scan_notes(notes: Vec<Arc<Note>>, leaves: Vec<Vec<u8>>, spent_nullifiers: Vec<Vec<u8>>) -> Vec<OwnedNote> {
+    let published: HashSet<Vec<u8>> = spent_nullifiers.into_iter().collect();
+    let mut owned = Vec::new();
+    for note in notes {
+        let commitment = note.commitment();
+        let spent = published.contains(&note.nullifier_hash());
+        // A note deposited twice sits in the tree twice, but one nullifier spends either copy.
+        for (index, _) in leaves.iter().enumerate().filter(|(_, leaf)| **leaf == commitment) {
+            owned.push(OwnedNote { note: note.clone(), leaf_index: index as u64, spent });
+        }
+    }
+    owned
+}
+
+// The path from leaf `leaf_index` to the root of the tree holding `leaves`.
+#[uniffi::export]
+pub fn merkle_path(leaves: Vec<Vec<u8>>, leaf_index: u64) -> Result<MerklePath> {
+    if leaves.len() > 1 << MERKLE_TREE_DEPTH {
+        return Err(PrivaxMobileError::TreeFull(MERKLE_TREE_DEPTH));
+    }
+    if leaf_index >= leaves.len() as u64 {
+        return Err(PrivaxMobileError::UnknownLeaf { index: leaf_index, leaves: leaves.len() as u64 });
+    }
+    let mut level = leaves.iter().map(|leaf| parse_field(leaf)).collect::<Result<Vec<Fr>>>()?;
+    let mut zero = Fr::from(0u64);
+    let mut index = leaf_index as usize;
+    let (mut elements, mut indices) = (Vec::with_capacity(MERKLE_TREE_DEPTH), Vec::with_capacity(MERKLE_TREE_DEPTH));
+    for _ in 0..MERKLE_TREE_DEPTH {
+        elements.push(field_bytes(level.get(index ^ 1).copied().unwrap_or(zero)));
+        indices.push(index & 1 == 1);
+        level = level.chunks(2).map(|pair| poseidon(&[pair[0], pair.get(1).copied().unwrap_or(zero)])).collect();
+        zero = poseidon(&[zero, zero]);
+        index /= 2;
+    }
+    Ok(MerklePath { root: field_bytes(level[0]), elements, indices })
+}
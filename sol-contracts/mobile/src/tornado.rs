@@ -0,0 +1,58 @@
+// Backup strings in the style of EVM mixers (Tornado Cash and its forks), for users who keep
+// notes that way:
+//
+//   privax-<mint>-<denomination>-<netId>-0x<nullifier secret hex><secret hex>
+//
+// As there, the mint and denomination name the pool and the hex is the note's preimage,
+// nullifier secret first. The denomination is in the mint's base units rather than a decimal
+// amount, since the string doesn't carry the mint's decimals. netId names the cluster, with
+// the Solana token list's chain IDs: 101 mainnet-beta, 102 testnet, 103 devnet. The same note
+// encodes either way; Note::encode stays the wallet's own format.
+use std::sync::Arc;
+
+use anchor_lang::prelude::Pubkey;
+
+use crate::note::{hex, parse_secret, Note, SECRET_LEN};
+use crate::{PrivaxMobileError, Result};
+
+const PREFIX: &str = "privax";
+
+#[derive(uniffi::Record, Clone, Debug, PartialEq, Eq)]
+pub struct TornadoNote {
+    pub mint: String, // Base58
+    pub net_id: u32,
+    pub note: Arc<Note>, // Its amount is the denomination
+}
+
+#[uniffi::export]
+pub fn encode_tornado_note(note: Arc<Note>, mint: String, net_id: u32) -> Result<String> {
+    let mint = mint.parse::<Pubkey>().map_err(|_| PrivaxMobileError::InvalidAddress(mint))?;
+    let (secret, nullifier_secret) = note.secrets();
+    Ok(format!("{PREFIX}-{mint}-{}-{net_id}-0x{}{}", note.amount(), hex(nullifier_secret), hex(secret)))
+}
+
+#[uniffi::export]
+pub fn parse_tornado_note(encoded: String) -> Result<TornadoNote> {
+    let invalid = |what: &str| PrivaxMobileError::InvalidNote(what.to_string());
+    let mut fields = encoded.trim().split('-');
+    let (Some(PREFIX), Some(mint), Some(denomination), Some(net_id), Some(preimage), None) =
+        (fields.next(), fields.next(), fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+        return Err(invalid("expected privax-<mint>-<denomination>-<netId>-0x<preimage>"));
+    };
+    let mint = mint.parse::<Pubkey>().map_err(|_| PrivaxMobileError::InvalidAddress(mint.to_string()))?;
+    let amount = denomination.parse().map_err(|_| invalid("denomination"))?;
+    let net_id = net_id.parse().map_err(|_| invalid("netId"))?;
+    let preimage = preimage.strip_prefix("0x").filter(|hex| hex.len() == 4 * SECRET_LEN && hex.is_ascii());
+    let preimage = preimage.ok_or_else(|| invalid("preimage"))?;
+    let (nullifier_secret, secret) = preimage.split_at(2 * SECRET_LEN);
+    Ok(TornadoNote {
+        mint: mint.to_string(),
+        net_id,
+        note: Arc::new(Note::from_secrets(
+            amount,
+            parse_secret(secret).ok_or_else(|| invalid("secret"))?,
+            parse_secret(nullifier_secret).ok_or_else(|| invalid("nullifier secret"))?,
+        )),
+    })
+}
@@ -0,0 +1,92 @@
+// Field elements, addresses as public inputs, and Groth16 proofs and keys between arkworks and
+// the on-chain layout.
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField, UniformRand};
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use privax_mobile::note::{field_bytes, parse_field};
+use privax_mobile::prove::public_input;
+use privax_protocol::proof_format;
+use proptest::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn field() -> impl Strategy<Value = Fr> {
+    any::<[u8; 32]>().prop_map(|bytes| Fr::from_be_bytes_mod_order(&bytes))
+}
+
+fn uncompressed(value: &impl CanonicalSerialize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.serialize_uncompressed(&mut bytes).unwrap();
+    bytes
+}
+
+proptest! {
+    #[test]
+    fn field_elements_round_trip(value in field()) {
+        let bytes = field_bytes(value);
+        prop_assert_eq!(bytes.len(), 32);
+        prop_assert_eq!(parse_field(&bytes).unwrap(), value);
+    }
+
+    // Addresses are arbitrary 32 bytes: one parses as a field element exactly when it is
+    // below the modulus, never by reducing it.
+    #[test]
+    fn only_canonical_bytes_parse(bytes in any::<[u8; 32]>()) {
+        let canonical = bytes.as_slice() < Fr::MODULUS.to_bytes_be().as_slice();
+        prop_assert_eq!(parse_field(&bytes).is_ok(), canonical);
+        if canonical {
+            prop_assert_eq!(field_bytes(parse_field(&bytes).unwrap()), bytes.to_vec());
+        }
+    }
+
+    // An address goes into the circuit as its first 8 bytes, little-endian; as a field
+    // element that's the same u64 in the low bytes, so the circuit and withdraw agree.
+    #[test]
+    fn addresses_as_public_inputs(address in any::<[u8; 32]>()) {
+        let input = public_input(&address);
+        prop_assert_eq!(input, u64::from_le_bytes(address[..8].try_into().unwrap()));
+        let element = field_bytes(Fr::from(input));
+        prop_assert!(element[..24].iter().all(|&byte| byte == 0));
+        prop_assert_eq!(&element[24..], &input.to_be_bytes()[..]);
+    }
+
+    #[test]
+    fn short_values_are_zero_padded(value in proptest::collection::vec(any::<u8>(), 0..8)) {
+        let mut padded = [0u8; 8];
+        padded[..value.len()].copy_from_slice(&value);
+        prop_assert_eq!(public_input(&value), u64::from_le_bytes(padded));
+    }
+
+    #[test]
+    fn proofs_round_trip(seed in any::<u64>()) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let proof = Proof::<Bn254> { a: G1Affine::rand(&mut rng), b: G2Affine::rand(&mut rng), c: G1Affine::rand(&mut rng) };
+        let (a, b, c) = proof_format::proof_from_arkworks(&uncompressed(&proof)).unwrap();
+
+        // On-chain coordinates are big-endian, x before y
+        prop_assert_eq!(&a[..32], &proof.a.x.into_bigint().to_bytes_be()[..]);
+        prop_assert_eq!(&c[32..], &proof.c.y.into_bigint().to_bytes_be()[..]);
+
+        let arkworks = [proof_format::g1_to_arkworks(&a).as_slice(), &proof_format::g2_to_arkworks(&b), &proof_format::g1_to_arkworks(&c)].concat();
+        prop_assert_eq!(Proof::<Bn254>::deserialize_uncompressed(arkworks.as_slice()).unwrap(), proof);
+    }
+
+    #[test]
+    fn verifying_keys_round_trip(seed in any::<u64>(), public_inputs in 0usize..10) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let vk = VerifyingKey::<Bn254> {
+            alpha_g1: G1Affine::rand(&mut rng),
+            beta_g2: G2Affine::rand(&mut rng),
+            gamma_g2: G2Affine::rand(&mut rng),
+            delta_g2: G2Affine::rand(&mut rng),
+            gamma_abc_g1: (0..=public_inputs).map(|_| G1Affine::rand(&mut rng)).collect(),
+        };
+        let arkworks = uncompressed(&vk);
+        let vk_data = proof_format::vk_from_arkworks(&arkworks).unwrap();
+        prop_assert!(proof_format::check_groth16_vk(&vk_data, public_inputs..=public_inputs).is_ok());
+        // Exported without arkworks' y-sign flags, which it doesn't need to read uncompressed points
+        let exported = proof_format::vk_to_arkworks(&vk_data).unwrap();
+        prop_assert_eq!(VerifyingKey::<Bn254>::deserialize_uncompressed(exported.as_slice()).unwrap(), vk);
+    }
+}
@@ -0,0 +1,50 @@
+// Paths from merkle_path against the tree they came from, for random leaf counts and leaves.
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use privax_mobile::note::{field_bytes, parse_field, poseidon};
+use privax_mobile::scan::merkle_path;
+use privax_protocol::MERKLE_TREE_DEPTH;
+use proptest::prelude::*;
+
+fn leaves() -> impl Strategy<Value = Vec<Vec<u8>>> {
+    proptest::collection::vec(any::<[u8; 32]>().prop_map(|bytes| field_bytes(Fr::from_be_bytes_mod_order(&bytes))), 1..48)
+}
+
+fn root_from_path(leaf: &[u8], elements: &[Vec<u8>], indices: &[bool]) -> Vec<u8> {
+    let node = elements.iter().zip(indices).fold(parse_field(leaf).unwrap(), |node, (sibling, &right)| {
+        let sibling = parse_field(sibling).unwrap();
+        poseidon(&if right { [sibling, node] } else { [node, sibling] })
+    });
+    field_bytes(node)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn paths_verify_against_the_root(leaves in leaves(), probe in any::<prop::sample::Index>(), other in any::<prop::sample::Index>()) {
+        let index = probe.index(leaves.len());
+        let path = merkle_path(leaves.clone(), index as u64).unwrap();
+        prop_assert_eq!(path.elements.len(), MERKLE_TREE_DEPTH);
+        prop_assert!(path.indices.iter().enumerate().all(|(level, &right)| right == ((index >> level) & 1 == 1)));
+        prop_assert_eq!(root_from_path(&leaves[index], &path.elements, &path.indices), path.root.clone());
+
+        // Every leaf's path leads to the same root, and no other leaf fits this one's path
+        let other_index = other.index(leaves.len());
+        prop_assert_eq!(merkle_path(leaves.clone(), other_index as u64).unwrap().root, path.root.clone());
+        if leaves[other_index] != leaves[index] {
+            prop_assert_ne!(root_from_path(&leaves[other_index], &path.elements, &path.indices), path.root);
+        }
+    }
+
+    #[test]
+    fn appending_a_leaf_changes_the_root(leaves in leaves(), leaf in any::<[u8; 32]>()) {
+        let before = merkle_path(leaves.clone(), 0).unwrap().root;
+        let mut grown = leaves.clone();
+        grown.push(field_bytes(Fr::from_be_bytes_mod_order(&leaf)));
+        let after = merkle_path(grown.clone(), leaves.len() as u64).unwrap();
+        // A zero leaf is indistinguishable from an empty slot
+        prop_assert_eq!(after.root != before, grown.last().unwrap().iter().any(|&byte| byte != 0));
+        prop_assert!(merkle_path(leaves.clone(), leaves.len() as u64).is_err());
+    }
+}
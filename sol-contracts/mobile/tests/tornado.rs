@@ -0,0 +1,45 @@
+// Mixer-style backup strings against the wallet's own note format.
+use privax_mobile::note::Note;
+use privax_mobile::tornado::{encode_tornado_note, parse_tornado_note};
+use privax_test_vectors::vectors;
+
+const MINT: &str = "So11111111111111111111111111111111111111112";
+
+#[test]
+fn round_trips_the_vector_notes() {
+    for vector in &vectors().notes {
+        let note = Note::parse(vector.backup.clone()).unwrap();
+        let encoded = encode_tornado_note(note.clone(), MINT.to_string(), 103).unwrap();
+
+        let (secret, nullifier_secret) = (hex(&vector.secret), hex(&vector.nullifier_secret));
+        assert_eq!(encoded, format!("privax-{MINT}-{}-103-0x{nullifier_secret}{secret}", vector.amount));
+
+        let parsed = parse_tornado_note(encoded).unwrap();
+        assert_eq!((parsed.mint.as_str(), parsed.net_id), (MINT, 103));
+        assert_eq!(parsed.note.encode(), vector.backup);
+        assert_eq!(parsed.note.commitment(), vector.commitment);
+    }
+}
+
+#[test]
+fn rejects_malformed_strings() {
+    let note = Note::parse(vectors().notes[0].backup.clone()).unwrap();
+    let encoded = encode_tornado_note(note.clone(), MINT.to_string(), 101).unwrap();
+    let preimage = encoded.rsplit('-').next().unwrap();
+    for bad in [
+        note.encode(),                                   // The wallet's own format
+        encoded.replacen("privax", "tornado", 1),        // Another protocol's note
+        encoded.replacen(MINT, "not-a-mint", 1),         // Unparseable mint
+        encoded.replacen("-101-", "-mainnet-", 1),       // Non-numeric netId
+        encoded.replacen(preimage, &preimage[2..], 1),   // Preimage without 0x
+        encoded[..encoded.len() - 2].to_string(),        // Short preimage
+        format!("{encoded}-1"),                          // Trailing field
+    ] {
+        assert!(parse_tornado_note(bad.clone()).is_err(), "{bad}");
+    }
+    assert!(encode_tornado_note(note, "not-a-mint".to_string(), 101).is_err());
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
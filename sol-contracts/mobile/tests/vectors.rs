@@ -0,0 +1,83 @@
+// The wallet side of the shared test vectors (test-vectors/vectors.json): Poseidon, notes,
+// the circuit's Merkle paths and the witness calculator's input.
+use std::str::FromStr;
+
+use ark_bn254::Fr;
+use privax_mobile::note::{field_bytes, poseidon, Note};
+use privax_mobile::prove::{public_input, withdraw_inputs};
+use privax_mobile::scan::{merkle_path, scan_notes};
+use privax_test_vectors::vectors;
+
+fn field(decimal: &str) -> Fr {
+    Fr::from_str(decimal).unwrap()
+}
+
+#[test]
+fn poseidon_hashes() {
+    for vector in &vectors().poseidon {
+        let inputs: Vec<Fr> = vector.inputs.iter().map(|input| field(input)).collect();
+        assert_eq!(poseidon(&inputs), field(&vector.output), "{vector:?}");
+    }
+}
+
+#[test]
+fn notes() {
+    for vector in &vectors().notes {
+        let note = Note::parse(vector.backup.clone()).unwrap();
+        assert_eq!(note.amount(), vector.amount);
+        assert_eq!(note.encode(), vector.backup);
+        assert_eq!(field_bytes(note.secret_field())[1..], vector.secret);
+        assert_eq!(field_bytes(note.nullifier_secret_field())[1..], vector.nullifier_secret);
+        assert_eq!(note.commitment(), vector.commitment);
+        assert_eq!(note.nullifier_hash(), vector.nullifier_hash);
+    }
+}
+
+#[test]
+fn merkle_paths() {
+    let tree = &vectors().poseidon_tree;
+    let mut zero = Fr::from(0u64);
+    for expected in &tree.zeros {
+        assert_eq!(field_bytes(zero), expected);
+        zero = poseidon(&[zero, zero]);
+    }
+
+    let leaves: Vec<Vec<u8>> = tree.leaves.iter().map(|leaf| leaf.to_vec()).collect();
+    for vector in &tree.paths {
+        let path = merkle_path(leaves.clone(), vector.leaf_index).unwrap();
+        assert_eq!(path.root, tree.root);
+        assert_eq!(path.elements, vector.elements.iter().map(|element| element.to_vec()).collect::<Vec<_>>());
+        assert_eq!(path.indices, vector.indices);
+    }
+}
+
+#[test]
+fn withdrawals() {
+    let vectors = vectors();
+    let tree = &vectors.poseidon_tree;
+    let leaves: Vec<Vec<u8>> = tree.leaves.iter().map(|leaf| leaf.to_vec()).collect();
+    for withdrawal in &vectors.withdrawals {
+        let notes: Vec<_> = withdrawal.input_notes.iter().map(|&i| Note::parse(vectors.notes[i].backup.clone()).unwrap()).collect();
+        assert_eq!(withdrawal.public_inputs[0], public_input(&tree.root));
+        assert_eq!(withdrawal.public_inputs[1], public_input(&notes[0].nullifier_hash()));
+
+        // The withdrawal's nullifiers, as the program publishes them, mark its notes spent
+        assert!(scan_notes(notes.clone(), leaves.clone(), Vec::new()).iter().all(|owned| !owned.spent));
+        let published: Vec<Vec<u8>> = notes.iter().map(|note| note.nullifier_hash()).collect();
+        assert!(scan_notes(notes.clone(), leaves.clone(), published).iter().all(|owned| owned.spent));
+        // A hash matching only in the bytes of its public input is another nullifier
+        let mut spent = vec![withdrawal.public_inputs[1]];
+        spent.extend_from_slice(&withdrawal.public_inputs[8..8 + notes.len() - 1]);
+        let truncated: Vec<Vec<u8>> = spent.iter().map(|input| [input.to_le_bytes().as_slice(), &[0; 24]].concat()).collect();
+        assert!(scan_notes(notes.clone(), leaves.clone(), truncated).iter().all(|owned| !owned.spent));
+
+        if let Some(expected) = &withdrawal.witness_input {
+            let leaf_index = tree.leaves.iter().position(|leaf| leaf[..] == notes[0].commitment()).unwrap() as u64;
+            let path = merkle_path(leaves.clone(), leaf_index).unwrap();
+            let (recipient, mint) = (withdrawal.recipient.clone(), withdrawal.mint.clone());
+            let (version, slot) = (withdrawal.circuit_version, withdrawal.proof_slot);
+            let input = withdraw_inputs(notes[0].clone(), path, recipient, mint, version, slot, None, 0).unwrap();
+            assert_eq!(serde_json::from_str::<serde_json::Value>(&input).unwrap(), *expected);
+        }
+    }
+}
@@ -0,0 +1,580 @@
+// Harness for running the program on solana-program-test's in-process bank.
+//
+// `TestPool::start` boots a bank with the program (and the example privax_escrow, which calls
+// into it, and the stand-ins from `swap`), a funded admin and user, and the pool mint
+// with the user's tokens; `TestPool::initialized` also initializes the pool and registers a
+// Groth16 verifying key for CIRCUIT_VERSION. Instructions come from `privax_client`'s
+// builders where it has them, so the tests cover the SDK's account lists too. Proofs are real
+// ones from `prover`, which the program checks with its pairing check, or, to spend an actual
+// note, from `withdraw_circuit`.
+//
+// The program runs natively by default. Set SBF_OUT_DIR to the directory holding
+// privax_protocol.so and privax_escrow.so (e.g. after `cargo build-sbf`) to run the compiled
+// programs instead; the stand-ins always run natively.
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{system_program, AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use privax_client::instructions::{
+    self as ix, DepositGates, MergeNotesParams, ShieldedSwapParams, SplitNoteParams, WithdrawParams,
+};
+use privax_protocol::privax_protocol::REQUIRED_PUBLIC_INPUTS_COUNT;
+use privax_protocol::{
+    field_input, AuditLog, FeeConfig, MerkleTree, ProgramState, ProofSystem, VerifyingKey, MAX_PUBLIC_INPUTS, MERKLE_TREE_DEPTH,
+    NOTE_TRANSFER_PUBLIC_INPUTS, SWAP_PUBLIC_INPUTS,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+pub mod prover;
+pub mod swap;
+pub mod withdraw_circuit;
+
+pub const CIRCUIT_VERSION: u16 = 1;
+pub const MINT_DECIMALS: u8 = 6;
+pub const USER_BALANCE: u64 = 1_000_000_000;
+
+pub fn program_test() -> ProgramTest {
+    let mut test = ProgramTest::new("privax_protocol", privax_protocol::ID, processor!(privax_protocol::entry));
+    test.add_program("privax_escrow", privax_escrow::ID, processor!(privax_escrow::entry));
+    test.add_builtin_program("swap_program", swap::SWAP_PROGRAM_ID, processor!(swap::process_swap));
+    test.add_builtin_program("target_pool", swap::TARGET_POOL_ID, processor!(swap::process_target_pool));
+    test
+}
+
+// Sends `instructions` in one transaction paid by the context's payer.
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction =
+        Transaction::new_signed_with_payer(instructions, Some(&context.payer.pubkey()), &all_signers, context.last_blockhash);
+    context.banks_client.process_transaction(transaction).await
+}
+
+// The custom error code a transaction failed with, if it failed with one. Compare against
+// `PrivaxError::X.into()` or `anchor_lang::error::ErrorCode::X.into()`.
+pub fn error_code(err: &BanksClientError) -> Option<u32> {
+    match err.unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+        _ => None,
+    }
+}
+
+// Root of a depth-MERKLE_TREE_DEPTH tree holding `leaves` from index 0, computed from scratch
+// to check the program's incremental insert against.
+pub fn expected_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let mut zero = [0u8; 32];
+    let mut level = if leaves.is_empty() { vec![zero] } else { leaves.to_vec() };
+    for _ in 0..MERKLE_TREE_DEPTH {
+        if level.len() % 2 == 1 {
+            level.push(zero);
+        }
+        level = level.chunks_exact(2).map(|pair| privax_protocol::hash_pair(&pair[0], &pair[1])).collect();
+        zero = privax_protocol::hash_pair(&zero, &zero);
+    }
+    level[0]
+}
+
+// The u64 public input standing for a 32-byte value: its first 8 bytes, little-endian.
+pub fn public_input(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+// A nullifier hash the public input `nullifier` stands for: the input's little-endian bytes,
+// then bytes the input doesn't carry, so a proof checked against the input alone would fail.
+// Inputs below 0x30 in their low byte keep it a canonical field element.
+pub fn nullifier_hash_for(nullifier: u64) -> [u8; 32] {
+    let mut hash = [0x2a; 32];
+    hash[..8].copy_from_slice(&nullifier.to_le_bytes());
+    hash
+}
+
+// Proves a withdrawal's public inputs as withdraw passes them to the verifier: each input it
+// uses as a field element, with the root, nullifier hashes and change commitment in full. Call
+// again after changing the inputs.
+pub fn prove_withdraw(params: &mut WithdrawParams) {
+    let extra = params.extra_nullifier_hashes.len();
+    let used = REQUIRED_PUBLIC_INPUTS_COUNT + extra + usize::from(params.change_commitment.is_some());
+    let mut inputs: Vec<[u8; 32]> = params.public_inputs[..used].iter().map(|&input| field_input(input)).collect();
+    inputs[0] = params.merkle_root;
+    inputs[1] = params.nullifier_hash;
+    inputs[REQUIRED_PUBLIC_INPUTS_COUNT..REQUIRED_PUBLIC_INPUTS_COUNT + extra].copy_from_slice(&params.extra_nullifier_hashes);
+    if let Some(commitment) = params.change_commitment {
+        inputs[used - 1] = commitment;
+    }
+    (params.a_proof, params.b_proof, params.c_proof) = prover::prove(&inputs);
+}
+
+// As prove_withdraw, for split_note: the output commitments go in full.
+pub fn prove_split_note(params: &mut SplitNoteParams) {
+    let mut inputs: Vec<[u8; 32]> = params.public_inputs.iter().map(|&input| field_input(input)).collect();
+    inputs[0] = params.merkle_root;
+    inputs[1] = params.nullifier_hash;
+    for (input, commitment) in inputs[2..].iter_mut().zip(&params.output_commitments) {
+        *input = *commitment;
+    }
+    (params.a_proof, params.b_proof, params.c_proof) = prover::prove(&inputs);
+}
+
+// As prove_withdraw, for merge_notes: the nullifier hashes and output commitment go in full.
+pub fn prove_merge_notes(params: &mut MergeNotesParams) {
+    let mut inputs: Vec<[u8; 32]> = params.public_inputs.iter().map(|&input| field_input(input)).collect();
+    inputs[0] = params.merkle_root;
+    inputs[1] = params.nullifier_hash;
+    inputs[2..2 + params.extra_nullifier_hashes.len()].copy_from_slice(&params.extra_nullifier_hashes);
+    inputs[5] = params.output_commitment;
+    (params.a_proof, params.b_proof, params.c_proof) = prover::prove(&inputs);
+}
+
+// As prove_withdraw, for shielded_swap: the output commitment goes in full.
+pub fn prove_shielded_swap(params: &mut ShieldedSwapParams) {
+    let mut inputs: Vec<[u8; 32]> = params.public_inputs.iter().map(|&input| field_input(input)).collect();
+    inputs[0] = params.merkle_root;
+    inputs[1] = params.nullifier_hash;
+    inputs[7] = params.output_commitment;
+    (params.a_proof, params.b_proof, params.c_proof) = prover::prove(&inputs);
+}
+
+// A swap program and a target pool for shielded swaps out of a TestPool (see `swap`). The
+// target pool's vault already exists; the swap program's reserves hold pool tokens in
+// `reserve_in` and target tokens in `reserve_out`.
+pub struct SwapVenue {
+    pub target_mint: Pubkey,
+    pub reserve_in: Pubkey,
+    pub reserve_out: Pubkey,
+    pub submitter_output_account: Pubkey, // The user's target-mint account, for swap surplus
+}
+
+pub struct TestPool {
+    pub context: ProgramTestContext,
+    pub admin: Keypair,
+    pub user: Keypair,
+    pub mint: Pubkey,
+    pub user_token_account: Pubkey,
+}
+
+impl TestPool {
+    // A bank with the program and funded accounts; the pool isn't initialized yet.
+    pub async fn start() -> Self {
+        let admin = Keypair::new();
+        let user = Keypair::new();
+        let mut test = program_test();
+        for key in [admin.pubkey(), user.pubkey()] {
+            test.add_account(key, Account::new(100 * LAMPORTS_PER_SOL, 0, &system_program::ID));
+        }
+        let context = test.start_with_context().await;
+        let mut pool = Self { context, admin, user, mint: Pubkey::default(), user_token_account: Pubkey::default() };
+
+        pool.mint = pool.create_mint().await;
+        let owner = pool.user.pubkey();
+        pool.user_token_account = pool.create_token_account(&owner).await;
+        let (mint, user_token_account) = (pool.mint, pool.user_token_account);
+        pool.mint_to(mint, user_token_account, USER_BALANCE).await;
+        pool
+    }
+
+    // `start`, then initialize the pool and register the verifying key for CIRCUIT_VERSION.
+    pub async fn initialized() -> Self {
+        let mut pool = Self::start().await;
+        let instructions = [pool.initialize_ix(), pool.register_verifying_key_ix(CIRCUIT_VERSION, REQUIRED_PUBLIC_INPUTS_COUNT)];
+        process(&mut pool.context, &instructions, &[&pool.admin]).await.unwrap();
+        pool
+    }
+
+    pub fn initialize_ix(&self) -> Instruction {
+        let accounts = privax_protocol::accounts::Initialize {
+            program_state: ix::program_state_address(),
+            fee_config: ix::fee_config_address(),
+            nullifier_filter: ix::nullifier_filter_address(),
+            merkle_tree: ix::merkle_tree_address(),
+            audit_log: ix::audit_log_address(),
+            admin: self.admin.pubkey(),
+            system_program: system_program::ID,
+        };
+        Instruction {
+            program_id: privax_protocol::ID,
+            accounts: accounts.to_account_metas(None),
+            data: privax_protocol::instruction::Initialize {
+                token_mint_address: self.mint,
+                verifier_program_id: Pubkey::new_unique(),
+            }
+            .data(),
+        }
+    }
+
+    // `prover`'s key for proofs with `public_inputs` inputs.
+    pub fn register_verifying_key_ix(&self, circuit_version: u16, public_inputs: usize) -> Instruction {
+        self.register_vk_data_ix(circuit_version, prover::verifying_key(public_inputs))
+    }
+
+    // A Groth16 key in register_verifying_key's layout, e.g. withdraw_circuit::verifying_key().
+    pub fn register_vk_data_ix(&self, circuit_version: u16, vk_data: Vec<u8>) -> Instruction {
+        let accounts = privax_protocol::accounts::RegisterVerifyingKey {
+            program_state: ix::program_state_address(),
+            audit_log: ix::audit_log_address(),
+            verifying_key: ix::verifying_key_address(circuit_version),
+            admin: self.admin.pubkey(),
+            payer: self.admin.pubkey(),
+            system_program: system_program::ID,
+        };
+        Instruction {
+            program_id: privax_protocol::ID,
+            accounts: accounts.to_account_metas(None),
+            data: privax_protocol::instruction::RegisterVerifyingKey {
+                circuit_version,
+                proof_system: ProofSystem::Groth16,
+                amount_range_bits: VerifyingKey::REQUIRED_AMOUNT_RANGE_BITS,
+                vk_len: vk_data.len() as u16,
+                vk_data,
+            }
+            .data(),
+        }
+    }
+
+    // A new mint with the admin as its authority.
+    pub async fn create_mint(&mut self) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let instructions = [
+            system_instruction::create_account(
+                &self.context.payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint(&spl_token::ID, &mint.pubkey(), &self.admin.pubkey(), None, MINT_DECIMALS)
+                .unwrap(),
+        ];
+        process(&mut self.context, &instructions, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    // Mints `amount` of a create_mint mint into `token_account`.
+    pub async fn mint_to(&mut self, mint: Pubkey, token_account: Pubkey, amount: u64) {
+        let mint_to =
+            spl_token::instruction::mint_to(&spl_token::ID, &mint, &token_account, &self.admin.pubkey(), &[], amount).unwrap();
+        process(&mut self.context, &[mint_to], &[&self.admin]).await.unwrap();
+    }
+
+    pub async fn create_token_account(&mut self, owner: &Pubkey) -> Pubkey {
+        let mint = self.mint;
+        self.create_token_account_for(mint, owner).await
+    }
+
+    // As create_token_account, for another mint.
+    pub async fn create_token_account_for(&mut self, mint: Pubkey, owner: &Pubkey) -> Pubkey {
+        let account = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let instructions = [
+            system_instruction::create_account(
+                &self.context.payer.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(spl_token::state::Account::LEN),
+                spl_token::state::Account::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account3(&spl_token::ID, &account.pubkey(), &mint, owner).unwrap(),
+        ];
+        process(&mut self.context, &instructions, &[&account]).await.unwrap();
+        account.pubkey()
+    }
+
+    // The owner's associated token account for the pool mint, which withdrawals pay into.
+    pub async fn create_associated_token_account(&mut self, owner: &Pubkey) -> Pubkey {
+        let address = get_associated_token_address(owner, &self.mint);
+        let create = Instruction {
+            program_id: anchor_spl::associated_token::ID,
+            accounts: vec![
+                AccountMeta::new(self.context.payer.pubkey(), true),
+                AccountMeta::new(address, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new_readonly(self.mint, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(spl_token::ID, false),
+            ],
+            data: vec![0], // Create
+        };
+        process(&mut self.context, &[create], &[]).await.unwrap();
+        address
+    }
+
+    pub async fn deposit(&mut self, amount: u64, commitment: [u8; 32]) -> Result<(), BanksClientError> {
+        let deposit = ix::deposit(self.user.pubkey(), self.user_token_account, self.mint, amount, commitment, None, DepositGates::default());
+        process(&mut self.context, &[deposit], &[&self.user]).await
+    }
+
+    // A single-note withdrawal of `amount` to `recipient` spending `nullifier`, with public
+    // inputs the program accepts (the current root, this pool's external nullifier and the
+    // current slot) and a proof for them. No relayer: the fee and relayer inputs stay 0.
+    pub async fn withdraw_params(&mut self, nullifier: u64, amount: u64, recipient: &Pubkey, recipient_token_account: Pubkey) -> WithdrawParams {
+        let merkle_root = self.merkle_tree().await.root();
+        let nullifier_hash = nullifier_hash_for(nullifier);
+        let mut public_inputs = [0u64; MAX_PUBLIC_INPUTS];
+        public_inputs[0] = public_input(&merkle_root);
+        public_inputs[1] = nullifier;
+        public_inputs[2] = public_input(recipient.as_ref());
+        public_inputs[3] = amount;
+        public_inputs[4] = privax_protocol::external_nullifier(
+            &self.mint,
+            privax_protocol::privax_protocol::VARIABLE_DENOMINATION,
+            CIRCUIT_VERSION,
+        );
+        public_inputs[5] = self.slot().await;
+        let mut params = WithdrawParams {
+            a_proof: [0; 64],
+            b_proof: [0; 128],
+            c_proof: [0; 64],
+            public_inputs,
+            merkle_root,
+            nullifier_hash,
+            root_archive_page: None,
+            extra_nullifier_hashes: Vec::new(),
+            recipient: *recipient,
+            recipient_token_account,
+            amount,
+            relayer_fee: 0,
+            relayer_fee_token_account: None,
+            circuit_version: CIRCUIT_VERSION,
+            plonk_proof: Vec::new(),
+            change_commitment: None,
+            usd_price_feed: None,
+        };
+        prove_withdraw(&mut params);
+        params
+    }
+
+    // Creates the nullifier shards a withdrawal needs that don't exist yet, paid by the user.
+    pub async fn init_nullifier_shards(&mut self, params: &WithdrawParams) -> Result<(), BanksClientError> {
+        let hashes: Vec<[u8; 32]> = params.nullifier_hashes().collect();
+        self.open_nullifier_shards(&hashes).await
+    }
+
+    // Creates the shards of these nullifier hashes that don't exist yet, paid by the user.
+    pub async fn open_nullifier_shards(&mut self, nullifier_hashes: &[[u8; 32]]) -> Result<(), BanksClientError> {
+        for hash in nullifier_hashes {
+            let prefix = ix::nullifier_prefix(hash);
+            if self.context.banks_client.get_account(ix::nullifier_shard_address(prefix)).await.unwrap().is_none() {
+                let init = ix::init_nullifier_shard(self.user.pubkey(), prefix);
+                process(&mut self.context, &[init], &[&self.user]).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Submits a withdrawal from the user's wallet, creating any nullifier shard it needs first.
+    pub async fn withdraw(&mut self, params: &WithdrawParams) -> Result<(), BanksClientError> {
+        self.init_nullifier_shards(params).await?;
+        let withdraw = ix::withdraw(self.user.pubkey(), params);
+        process(&mut self.context, &[withdraw], &[&self.user]).await
+    }
+
+    // A split of the note behind `nullifier` into `output_commitments`, under the split circuit
+    // registered for `circuit_version`, with inputs the program accepts and a proof for them.
+    pub async fn split_note_params(&mut self, circuit_version: u16, nullifier: u64, output_commitments: Vec<[u8; 32]>) -> SplitNoteParams {
+        let merkle_root = self.merkle_tree().await.root();
+        let mut public_inputs = [0u64; NOTE_TRANSFER_PUBLIC_INPUTS];
+        public_inputs[0] = public_input(&merkle_root);
+        public_inputs[1] = nullifier;
+        for (input, commitment) in public_inputs[2..].iter_mut().zip(&output_commitments) {
+            *input = public_input(commitment);
+        }
+        public_inputs[6] = privax_protocol::note_split_external_nullifier(&self.mint, circuit_version);
+        public_inputs[7] = self.slot().await;
+        let mut params = SplitNoteParams {
+            a_proof: [0; 64],
+            b_proof: [0; 128],
+            c_proof: [0; 64],
+            public_inputs,
+            merkle_root,
+            nullifier_hash: nullifier_hash_for(nullifier),
+            root_archive_page: None,
+            output_commitments,
+            circuit_version,
+            plonk_proof: Vec::new(),
+        };
+        prove_split_note(&mut params);
+        params
+    }
+
+    // A merge of the notes behind `nullifiers` into `output_commitment`, as split_note_params.
+    pub async fn merge_notes_params(&mut self, circuit_version: u16, nullifiers: &[u64], output_commitment: [u8; 32]) -> MergeNotesParams {
+        let merkle_root = self.merkle_tree().await.root();
+        let mut public_inputs = [0u64; NOTE_TRANSFER_PUBLIC_INPUTS];
+        public_inputs[0] = public_input(&merkle_root);
+        public_inputs[1..1 + nullifiers.len()].copy_from_slice(nullifiers);
+        public_inputs[5] = public_input(&output_commitment);
+        public_inputs[6] = privax_protocol::note_merge_external_nullifier(&self.mint, circuit_version);
+        public_inputs[7] = self.slot().await;
+        let mut params = MergeNotesParams {
+            a_proof: [0; 64],
+            b_proof: [0; 128],
+            c_proof: [0; 64],
+            public_inputs,
+            merkle_root,
+            nullifier_hash: nullifier_hash_for(nullifiers[0]),
+            root_archive_page: None,
+            extra_nullifier_hashes: nullifiers[1..].iter().map(|&nullifier| nullifier_hash_for(nullifier)).collect(),
+            output_commitment,
+            circuit_version,
+            plonk_proof: Vec::new(),
+        };
+        prove_merge_notes(&mut params);
+        params
+    }
+
+    // Submits a note split paid by the user, creating its nullifier's shard first.
+    pub async fn split_note(&mut self, params: &SplitNoteParams) -> Result<(), BanksClientError> {
+        self.open_nullifier_shards(&[params.nullifier_hash]).await?;
+        let split = ix::split_note(self.user.pubkey(), params);
+        process(&mut self.context, &[split], &[&self.user]).await
+    }
+
+    // Submits a note merge paid by the user, creating its nullifiers' shards first.
+    pub async fn merge_notes(&mut self, params: &MergeNotesParams) -> Result<(), BanksClientError> {
+        let hashes: Vec<[u8; 32]> = params.nullifier_hashes().collect();
+        self.open_nullifier_shards(&hashes).await?;
+        let merge = ix::merge_notes(self.user.pubkey(), params);
+        process(&mut self.context, &[merge], &[&self.user]).await
+    }
+
+    // Sets up a SwapVenue whose reserves hold `reserve` target tokens and points the pool's
+    // fee_swap_program at its swap program.
+    pub async fn open_swap_venue(&mut self, reserve: u64) -> SwapVenue {
+        let target_mint = self.create_mint().await;
+        let mut vault = vec![0; spl_token::state::Account::LEN];
+        let vault_state = spl_token::state::Account {
+            mint: target_mint,
+            owner: swap::target_vault(),
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        spl_token::state::Account::pack(vault_state, &mut vault).unwrap();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let vault = Account { lamports: rent.minimum_balance(vault.len()), data: vault, owner: spl_token::ID, ..Account::default() };
+        self.context.set_account(&swap::target_vault(), &AccountSharedData::from(vault));
+
+        let (mint, reserve_authority) = (self.mint, swap::reserve_authority());
+        let reserve_in = self.create_token_account_for(mint, &reserve_authority).await;
+        let reserve_out = self.create_token_account_for(target_mint, &reserve_authority).await;
+        self.mint_to(target_mint, reserve_out, reserve).await;
+        let user = self.user.pubkey();
+        let submitter_output_account = self.create_token_account_for(target_mint, &user).await;
+
+        let instructions = [
+            ix::create_swap_output_account(self.context.payer.pubkey(), target_mint),
+            ix::set_fee_swap_program(self.admin.pubkey(), swap::SWAP_PROGRAM_ID),
+            ix::add_swap_target(self.admin.pubkey(), swap::TARGET_POOL_ID),
+        ];
+        process(&mut self.context, &instructions, &[&self.admin]).await.unwrap();
+        SwapVenue { target_mint, reserve_in, reserve_out, submitter_output_account }
+    }
+
+    // A shielded swap of the note behind `nullifier` into `output_commitment` in the venue's
+    // target pool, under the swap circuit registered for `circuit_version`, with inputs the
+    // program accepts and a proof for them. The swap program takes all of `amount_in` (there is
+    // no protocol fee by default) and returns `swapped_out`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn shielded_swap_params(
+        &mut self,
+        circuit_version: u16,
+        venue: &SwapVenue,
+        nullifier: u64,
+        amount_in: u64,
+        amount_out: u64,
+        output_commitment: [u8; 32],
+        swapped_out: u64,
+    ) -> ShieldedSwapParams {
+        let merkle_root = self.merkle_tree().await.root();
+        let mut public_inputs = [0u64; SWAP_PUBLIC_INPUTS];
+        public_inputs[0] = public_input(&merkle_root);
+        public_inputs[1] = nullifier;
+        public_inputs[2] = public_input(swap::TARGET_POOL_ID.as_ref());
+        public_inputs[3] = amount_in;
+        public_inputs[4] = privax_protocol::swap_external_nullifier(&self.mint, circuit_version);
+        public_inputs[5] = self.slot().await;
+        public_inputs[6] = amount_out;
+        public_inputs[7] = public_input(&output_commitment);
+        let swap_output_account = ix::swap_output_address(&venue.target_mint);
+        let mut params = ShieldedSwapParams {
+            a_proof: [0; 64],
+            b_proof: [0; 128],
+            c_proof: [0; 64],
+            public_inputs,
+            merkle_root,
+            nullifier_hash: nullifier_hash_for(nullifier),
+            root_archive_page: None,
+            amount_in,
+            amount_out,
+            output_commitment,
+            target_program: swap::TARGET_POOL_ID,
+            target_mint: venue.target_mint,
+            swap_program: swap::SWAP_PROGRAM_ID,
+            swap_data: swap::swap_data(amount_in, swapped_out),
+            swap_accounts: swap::swap_accounts(ix::vault_address(), venue.reserve_in, venue.reserve_out, swap_output_account),
+            submitter_output_account: venue.submitter_output_account,
+            circuit_version,
+            plonk_proof: Vec::new(),
+        };
+        prove_shielded_swap(&mut params);
+        params
+    }
+
+    // Submits a shielded swap with the user as submitter, creating its nullifier's shard first.
+    pub async fn shielded_swap(&mut self, params: &ShieldedSwapParams) -> Result<(), BanksClientError> {
+        self.open_nullifier_shards(&[params.nullifier_hash]).await?;
+        let swap = ix::shielded_swap(self.user.pubkey(), params);
+        process(&mut self.context, &[swap], &[&self.user]).await
+    }
+
+    pub async fn slot(&mut self) -> u64 {
+        self.context.banks_client.get_sysvar::<Clock>().await.unwrap().slot
+    }
+
+    pub async fn program_state(&mut self) -> ProgramState {
+        self.anchor_account(ix::program_state_address()).await
+    }
+
+    pub async fn fee_config(&mut self) -> FeeConfig {
+        self.anchor_account(ix::fee_config_address()).await
+    }
+
+    pub async fn merkle_tree(&mut self) -> MerkleTree {
+        let account = self.context.banks_client.get_account(ix::merkle_tree_address()).await.unwrap().unwrap();
+        // Past the discriminator; account data isn't guaranteed to be aligned for the struct
+        bytemuck::pod_read_unaligned(&account.data[8..MerkleTree::SPACE])
+    }
+
+    pub async fn verifying_key(&mut self, circuit_version: u16) -> VerifyingKey {
+        let address = ix::verifying_key_address(circuit_version);
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        bytemuck::pod_read_unaligned(&account.data[8..VerifyingKey::SPACE])
+    }
+
+    pub async fn audit_log(&mut self) -> AuditLog {
+        let account = self.context.banks_client.get_account(ix::audit_log_address()).await.unwrap().unwrap();
+        bytemuck::pod_read_unaligned(&account.data[8..AuditLog::SPACE])
+    }
+
+    pub async fn token_balance(&mut self, token_account: Pubkey) -> u64 {
+        let account = self.context.banks_client.get_account(token_account).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    async fn anchor_account<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+}
@@ -0,0 +1,49 @@
+// Real Groth16 proofs for the tests, without the circuits: a circuit whose only constraints are
+// on its public inputs proves any inputs, and the program verifies it like any other key. The
+// setup is seeded by the input count, so a key registered for a count matches every proof made
+// here for that many inputs.
+use ark_bn254::{Bn254, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, ProvingKey};
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable};
+use ark_serialize::CanonicalSerialize;
+use privax_protocol::proof_format::{proof_from_arkworks, vk_from_arkworks, G1_SIZE, G2_SIZE};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+struct PublicInputs(Vec<Fr>);
+
+impl ConstraintSynthesizer<Fr> for PublicInputs {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        for input in self.0 {
+            let variable = cs.new_input_variable(|| Ok(input))?;
+            cs.enforce_constraint(lc!() + variable, lc!() + Variable::One, lc!() + variable)?;
+        }
+        Ok(())
+    }
+}
+
+fn proving_key(public_inputs: usize) -> ProvingKey<Bn254> {
+    let mut rng = StdRng::seed_from_u64(public_inputs as u64);
+    let circuit = PublicInputs(vec![Fr::from(0u64); public_inputs]);
+    Groth16::<Bn254>::generate_random_parameters_with_reduction(circuit, &mut rng).unwrap()
+}
+
+// register_verifying_key's vk_data for proofs with this many public inputs.
+pub fn verifying_key(public_inputs: usize) -> Vec<u8> {
+    let mut vk = Vec::new();
+    proving_key(public_inputs).vk.serialize_uncompressed(&mut vk).unwrap();
+    vk_from_arkworks(&vk).unwrap()
+}
+
+// A proof for these public inputs (32 big-endian bytes each, as the program passes them to the
+// verifier), in withdraw's a_proof, b_proof and c_proof layout.
+pub fn prove(inputs: &[[u8; 32]]) -> ([u8; G1_SIZE], [u8; G2_SIZE], [u8; G1_SIZE]) {
+    let circuit = PublicInputs(inputs.iter().map(|input| Fr::from_be_bytes_mod_order(input)).collect());
+    let mut rng = StdRng::seed_from_u64(0);
+    let proof = Groth16::<Bn254>::create_random_proof_with_reduction(circuit, &proving_key(inputs.len()), &mut rng).unwrap();
+    let mut bytes = Vec::new();
+    proof.serialize_uncompressed(&mut bytes).unwrap();
+    proof_from_arkworks(&bytes).unwrap()
+}
@@ -0,0 +1,168 @@
+// Stand-ins for the programs on either side of a shielded swap, run natively beside the pool.
+//
+// `process_swap` is a swap program trading at the price its instruction names, out of reserves
+// it holds. `process_target_pool` is the other Privax pool the output note goes into. A second
+// deployment of privax_protocol can't run here, since Anchor's entrypoint rejects any program ID
+// but the one it declares, so the stand-in implements the two instructions a swap calls,
+// init_commitment_shard and deposit: same discriminators, account lists, PDAs and shard layout,
+// and a depositor who pays each new shard entry's rent.
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::rent::Rent;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::solana_program::sysvar::Sysvar;
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorDeserialize, Discriminator};
+use anchor_spl::token::spl_token;
+use privax_protocol::instruction::{Deposit, InitCommitmentShard};
+use privax_protocol::CommitmentShard;
+
+pub const SWAP_PROGRAM_ID: Pubkey = Pubkey::new_from_array([0x5a; 32]);
+pub const TARGET_POOL_ID: Pubkey = Pubkey::new_from_array([0x7b; 32]);
+
+// Owns the swap program's reserves.
+pub fn reserve_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"reserve"], &SWAP_PROGRAM_ID).0
+}
+
+// Swaps `amount_in` out of the route's source for `amount_out` from its reserves.
+pub fn swap_data(amount_in: u64, amount_out: u64) -> Vec<u8> {
+    [amount_in.to_le_bytes(), amount_out.to_le_bytes()].concat()
+}
+
+// The route: `source` is its own authority, as the pool's vault is, and `reserve_out` is owned
+// by reserve_authority().
+pub fn swap_accounts(source: Pubkey, reserve_in: Pubkey, reserve_out: Pubkey, destination: Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(source, false),
+        AccountMeta::new(reserve_in, false),
+        AccountMeta::new(reserve_out, false),
+        AccountMeta::new(destination, false),
+        AccountMeta::new_readonly(reserve_authority(), false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+    ]
+}
+
+pub fn process_swap(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [source, reserve_in, reserve_out, destination, reserve_authority, token_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let amount = |range: std::ops::Range<usize>| {
+        let bytes = data.get(range).ok_or(ProgramError::InvalidInstructionData)?;
+        Ok::<_, ProgramError>(u64::from_le_bytes(bytes.try_into().unwrap()))
+    };
+    let (amount_in, amount_out) = (amount(0..8)?, amount(8..16)?);
+
+    let pay_in = spl_token::instruction::transfer(token_program.key, source.key, reserve_in.key, source.key, &[], amount_in)?;
+    invoke(&pay_in, &[source.clone(), reserve_in.clone(), token_program.clone()])?;
+
+    let (authority, bump) = Pubkey::find_program_address(&[b"reserve"], program_id);
+    if *reserve_authority.key != authority {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let pay_out = spl_token::instruction::transfer(token_program.key, reserve_out.key, destination.key, &authority, &[], amount_out)?;
+    invoke_signed(
+        &pay_out,
+        &[reserve_out.clone(), destination.clone(), reserve_authority.clone(), token_program.clone()],
+        &[&[b"reserve", &[bump]]],
+    )
+}
+
+// The target pool's program_state and vault, which is its own authority.
+pub fn target_program_state() -> Pubkey {
+    Pubkey::find_program_address(&[b"program_state"], &TARGET_POOL_ID).0
+}
+
+pub fn target_vault() -> Pubkey {
+    Pubkey::find_program_address(&[b"program_token_vault", target_program_state().as_ref()], &TARGET_POOL_ID).0
+}
+
+pub fn target_commitment_shard(prefix: [u8; 2]) -> Pubkey {
+    Pubkey::find_program_address(&[b"commitment_shard", target_program_state().as_ref(), &prefix], &TARGET_POOL_ID).0
+}
+
+pub fn process_target_pool(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data.len() < 8 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (discriminator, mut args) = data.split_at(8);
+    if discriminator == InitCommitmentShard::DISCRIMINATOR {
+        let args = InitCommitmentShard::deserialize(&mut args).map_err(|_| ProgramError::InvalidInstructionData)?;
+        init_commitment_shard(program_id, accounts, args.prefix)
+    } else if discriminator == Deposit::DISCRIMINATOR {
+        let args = Deposit::deserialize(&mut args).map_err(|_| ProgramError::InvalidInstructionData)?;
+        deposit(program_id, accounts, args.amount, args.commitment)
+    } else {
+        Err(ProgramError::InvalidInstructionData)
+    }
+}
+
+fn init_commitment_shard(program_id: &Pubkey, accounts: &[AccountInfo], prefix: [u8; 2]) -> ProgramResult {
+    let [program_state, shard, payer, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let seeds: &[&[u8]] = &[b"commitment_shard", program_state.key.as_ref(), &prefix];
+    let (address, bump) = Pubkey::find_program_address(seeds, program_id);
+    if *shard.key != address {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let create = system_instruction::create_account(
+        payer.key,
+        shard.key,
+        Rent::get()?.minimum_balance(CommitmentShard::SPACE),
+        CommitmentShard::SPACE as u64,
+        program_id,
+    );
+    let signer_seeds: &[&[u8]] = &[seeds[0], seeds[1], seeds[2], &[bump]];
+    invoke_signed(&create, &[payer.clone(), shard.clone(), system_program.clone()], &[signer_seeds])?;
+
+    let header = CommitmentShard { prefix, count: 0, bump };
+    header.try_serialize(&mut &mut shard.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+// Takes the tokens into the vault and appends the commitment to its shard, which must exist.
+fn deposit(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64, commitment: [u8; 32]) -> ProgramResult {
+    let Some(
+        [program_state, _fee_config, _merkle_tree, shard, _referrer_account, _deposit_receipt, user, user_token_account, _token_mint, vault, vault_authority, token_program, system_program, _rent],
+    ) = accounts.get(..14)
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, program_id).0;
+    if *program_state.key != pda(&[b"program_state"])
+        || *shard.key != pda(&[b"commitment_shard", program_state.key.as_ref(), &commitment[..2]])
+        || *vault.key != pda(&[b"program_token_vault", program_state.key.as_ref()])
+        || vault_authority.key != vault.key
+    {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if shard.owner != program_id || *vault.owner != spl_token::ID {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if !user.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let transfer = spl_token::instruction::transfer(token_program.key, user_token_account.key, vault.key, user.key, &[], amount)?;
+    invoke(&transfer, &[user_token_account.clone(), vault.clone(), user.clone(), token_program.clone()])?;
+
+    let mut header = CommitmentShard::try_deserialize(&mut &shard.try_borrow_data()?[..])?;
+    let used = shard.data_len();
+    if shard.try_borrow_data()?[CommitmentShard::SPACE..].chunks_exact(32).any(|entry| entry == commitment) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let top_up = Rent::get()?.minimum_balance(used + 32).saturating_sub(shard.lamports());
+    if top_up > 0 {
+        let pay = system_instruction::transfer(user.key, shard.key, top_up);
+        invoke(&pay, &[user.clone(), shard.clone(), system_program.clone()])?;
+    }
+    shard.realloc(used + 32, false)?;
+    shard.try_borrow_mut_data()?[used..].copy_from_slice(&commitment);
+    header.count += 1;
+    header.try_serialize(&mut &mut shard.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
@@ -0,0 +1,201 @@
+// circuits/circuits/withdraw.circom in arkworks, so the tests can make real withdrawal proofs
+// without circom: the same constraints (64-bit amount and fee, the note's commitment and
+// nullifier hash, its Merkle path) over circomlib's Poseidon, with the same public inputs in
+// the same order. A proof made here only verifies for a note that is in the tree, spent with
+// its real nullifier hash, unlike `prover`'s, which proves any inputs.
+use std::sync::OnceLock;
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_groth16::{Groth16, ProvingKey};
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+use ark_serialize::CanonicalSerialize;
+use light_poseidon::parameters::bn254_x5::get_poseidon_parameters;
+use privax_protocol::proof_format::{proof_from_arkworks, vk_from_arkworks, G1_SIZE, G2_SIZE};
+use privax_protocol::MERKLE_TREE_DEPTH;
+use privax_test_vectors::WithdrawalVector;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// merkleRoot, nullifierHash, recipient, amount, externalNullifier, proofSlot, relayerFee, relayer
+pub const PUBLIC_INPUTS: usize = 8;
+
+// The circuit's private inputs for one note.
+#[derive(Clone, Debug)]
+pub struct WithdrawWitness {
+    pub secret: Fr,
+    pub nullifier_secret: Fr,
+    pub path_elements: Vec<Fr>,
+    pub path_indices: Vec<bool>, // Whether the path node is the right child at each level
+}
+
+impl WithdrawWitness {
+    // The private inputs of a test vector's witness_input.
+    pub fn from_vector(withdrawal: &WithdrawalVector) -> Self {
+        let input = withdrawal.witness_input.as_ref().expect("single-note withdrawal");
+        let field = |value: Option<&str>| -> Fr { value.unwrap().parse().unwrap() };
+        Self {
+            secret: field(input["secret"].as_str()),
+            nullifier_secret: field(input["nullifierSecret"].as_str()),
+            path_elements: input["pathElements"].as_array().unwrap().iter().map(|element| field(element.as_str())).collect(),
+            path_indices: input["pathIndices"].as_array().unwrap().iter().map(|index| index.as_u64() == Some(1)).collect(),
+        }
+    }
+}
+
+struct Withdraw {
+    witness: WithdrawWitness,
+    inputs: [Fr; PUBLIC_INPUTS],
+}
+
+// A circuit signal: its value and the linear combination of variables that carries it.
+#[derive(Clone)]
+struct Signal {
+    value: Fr,
+    lc: LinearCombination<Fr>,
+}
+
+impl Signal {
+    fn constant(value: Fr) -> Self {
+        Self { value, lc: lc!() + (value, Variable::One) }
+    }
+
+    fn witness(cs: &ConstraintSystemRef<Fr>, value: Fr) -> Result<Self, SynthesisError> {
+        Ok(Self { value, lc: lc!() + cs.new_witness_variable(|| Ok(value))? })
+    }
+
+    fn add(&self, other: &Signal) -> Self {
+        Self { value: self.value + other.value, lc: self.lc.clone() + &other.lc }
+    }
+
+    fn sub(&self, other: &Signal) -> Self {
+        Self { value: self.value - other.value, lc: self.lc.clone() - &other.lc }
+    }
+
+    fn scale(&self, factor: Fr) -> Self {
+        Self { value: self.value * factor, lc: self.lc.clone() * factor }
+    }
+
+    fn mul(&self, cs: &ConstraintSystemRef<Fr>, other: &Signal) -> Result<Self, SynthesisError> {
+        let product = Self::witness(cs, self.value * other.value)?;
+        cs.enforce_constraint(self.lc.clone(), other.lc.clone(), product.lc.clone())?;
+        Ok(product)
+    }
+
+    fn enforce_equal(&self, cs: &ConstraintSystemRef<Fr>, other: &Signal) -> Result<(), SynthesisError> {
+        cs.enforce_constraint(self.lc.clone(), lc!() + Variable::One, other.lc.clone())
+    }
+
+    // bitify.circom's Num2Bits(bits): the signal fits in `bits` bits.
+    fn enforce_bits(&self, cs: &ConstraintSystemRef<Fr>, bits: usize) -> Result<(), SynthesisError> {
+        let le_bits = self.value.into_bigint().to_bits_le();
+        let mut sum = Signal::constant(Fr::from(0u64));
+        let mut weight = Fr::from(1u64);
+        for &bit in le_bits.iter().take(bits) {
+            let bit = Signal::witness(cs, Fr::from(bit))?;
+            bit.enforce_binary(cs)?;
+            sum = sum.add(&bit.scale(weight));
+            weight.double_in_place();
+        }
+        sum.enforce_equal(cs, self)
+    }
+
+    fn enforce_binary(&self, cs: &ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let minus_one = self.sub(&Signal::constant(Fr::from(1u64)));
+        cs.enforce_constraint(self.lc.clone(), minus_one.lc, lc!())
+    }
+}
+
+// circomlib's Poseidon(inputs.len()): x^5 S-boxes, full rounds around partial ones, a zero
+// capacity element first.
+fn poseidon(cs: &ConstraintSystemRef<Fr>, inputs: &[Signal]) -> Result<Signal, SynthesisError> {
+    let width = inputs.len() + 1;
+    let params = get_poseidon_parameters::<Fr>(width as u8).unwrap();
+    let mut state: Vec<Signal> = std::iter::once(Signal::constant(Fr::from(0u64))).chain(inputs.iter().cloned()).collect();
+    let half_full = params.full_rounds / 2;
+    for round in 0..params.full_rounds + params.partial_rounds {
+        for (i, element) in state.iter_mut().enumerate() {
+            *element = element.add(&Signal::constant(params.ark[round * width + i]));
+        }
+        let full = round < half_full || round >= half_full + params.partial_rounds;
+        for element in state.iter_mut().take(if full { width } else { 1 }) {
+            let square = element.mul(cs, element)?;
+            let fourth = square.mul(cs, &square)?;
+            *element = fourth.mul(cs, element)?;
+        }
+        state = params
+            .mds
+            .iter()
+            .map(|row| row.iter().zip(&state).fold(Signal::constant(Fr::from(0u64)), |sum, (&m, element)| sum.add(&element.scale(m))))
+            .collect();
+    }
+    Ok(state.swap_remove(0))
+}
+
+impl ConstraintSynthesizer<Fr> for Withdraw {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|&value| Ok(Signal { value, lc: lc!() + cs.new_input_variable(|| Ok(value))? }))
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+        let [merkle_root, nullifier_hash, _recipient, amount, _external_nullifier, _proof_slot, relayer_fee, _relayer] =
+            <[Signal; PUBLIC_INPUTS]>::try_from(inputs).ok().unwrap();
+        let secret = Signal::witness(&cs, self.witness.secret)?;
+        let nullifier_secret = Signal::witness(&cs, self.witness.nullifier_secret)?;
+
+        amount.enforce_bits(&cs, 64)?;
+        relayer_fee.enforce_bits(&cs, 64)?;
+
+        let commitment = poseidon(&cs, &[amount, secret, nullifier_secret.clone()])?;
+        poseidon(&cs, &[nullifier_secret, Signal::constant(Fr::from(1u64))])?.enforce_equal(&cs, &nullifier_hash)?;
+
+        let mut current = commitment;
+        for (&element, &index) in self.witness.path_elements.iter().zip(&self.witness.path_indices) {
+            let element = Signal::witness(&cs, element)?;
+            let index = Signal::witness(&cs, Fr::from(index))?;
+            index.enforce_binary(&cs)?;
+            // index 0: (current, element); index 1: (element, current)
+            let swap = index.mul(&cs, &element.sub(&current))?;
+            let left = current.add(&swap);
+            let right = element.sub(&swap);
+            current = poseidon(&cs, &[left, right])?;
+        }
+        current.enforce_equal(&cs, &merkle_root)
+    }
+}
+
+fn proving_key() -> &'static ProvingKey<Bn254> {
+    static KEY: OnceLock<ProvingKey<Bn254>> = OnceLock::new();
+    KEY.get_or_init(|| {
+        let witness = WithdrawWitness {
+            secret: Fr::from(0u64),
+            nullifier_secret: Fr::from(0u64),
+            path_elements: vec![Fr::from(0u64); MERKLE_TREE_DEPTH],
+            path_indices: vec![false; MERKLE_TREE_DEPTH],
+        };
+        let circuit = Withdraw { witness, inputs: [Fr::from(0u64); PUBLIC_INPUTS] };
+        let mut rng = StdRng::seed_from_u64(20);
+        Groth16::<Bn254>::generate_random_parameters_with_reduction(circuit, &mut rng).unwrap()
+    })
+}
+
+// register_verifying_key's vk_data for this circuit.
+pub fn verifying_key() -> Vec<u8> {
+    let mut vk = Vec::new();
+    proving_key().vk.serialize_uncompressed(&mut vk).unwrap();
+    vk_from_arkworks(&vk).unwrap()
+}
+
+// A proof that `witness` spends a note for these public inputs (32 big-endian bytes each, as
+// withdraw passes them to the verifier), in withdraw's a_proof, b_proof and c_proof layout.
+pub fn prove(witness: &WithdrawWitness, inputs: &[[u8; 32]]) -> ([u8; G1_SIZE], [u8; G2_SIZE], [u8; G1_SIZE]) {
+    let inputs = inputs.iter().map(|input| Fr::from_be_bytes_mod_order(input)).collect::<Vec<_>>();
+    let circuit = Withdraw { witness: witness.clone(), inputs: inputs.try_into().unwrap() };
+    let mut rng = StdRng::seed_from_u64(0);
+    let proof = Groth16::<Bn254>::create_random_proof_with_reduction(circuit, proving_key(), &mut rng).unwrap();
+    let mut bytes = Vec::new();
+    proof.serialize_uncompressed(&mut bytes).unwrap();
+    proof_from_arkworks(&bytes).unwrap()
+}
@@ -0,0 +1,485 @@
+// Pausing, exit-only mode, the withdrawal circuit breaker, vault reconciliation, exclusion roots,
+// ownership transfer, renouncing the admin, the admin timelock and the audit log of admin actions.
+use anchor_lang::prelude::Pubkey;
+use privax_client::instructions as ix;
+use privax_program_tests::{error_code, process, TestPool, CIRCUIT_VERSION};
+use anchor_spl::token::spl_token;
+use privax_protocol::privax_protocol::REQUIRED_PUBLIC_INPUTS_COUNT;
+use privax_protocol::{audit_value_hash, AdminAction, AuditAction, PrivaxError, ProgramState};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::signature::{Keypair, Signer};
+
+#[tokio::test]
+async fn pause_blocks_deposits_and_withdrawals() {
+    let mut pool = TestPool::initialized().await;
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    let params = pool.withdraw_params(3, 1_000, &recipient.pubkey(), recipient_token_account).await;
+
+    process(&mut pool.context, &[ix::pause(pool.admin.pubkey())], &[&pool.admin]).await.unwrap();
+    assert!(pool.program_state().await.paused);
+
+    let err = pool.deposit(1_000, [2; 32]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::PoolPaused.into()));
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::PoolPaused.into()));
+
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    let err = process(&mut pool.context, &[ix::pause(pool.admin.pubkey())], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::PoolPaused.into()));
+
+    process(&mut pool.context, &[ix::unpause(pool.admin.pubkey())], &[&pool.admin]).await.unwrap();
+    pool.deposit(1_000, [3; 32]).await.unwrap();
+    let params = pool.withdraw_params(3, 1_000, &recipient.pubkey(), recipient_token_account).await;
+    pool.withdraw(&params).await.unwrap();
+
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    let err = process(&mut pool.context, &[ix::unpause(pool.admin.pubkey())], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::PoolNotPaused.into()));
+}
+
+#[tokio::test]
+async fn only_the_admin_or_guardian_can_pause() {
+    let mut pool = TestPool::initialized().await;
+    let guardian = Keypair::new();
+
+    let err = process(&mut pool.context, &[ix::pause(pool.user.pubkey())], &[&pool.user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NotAdminOrGuardian.into()));
+
+    let set_guardian = ix::set_guardian(pool.admin.pubkey(), guardian.pubkey());
+    process(&mut pool.context, &[set_guardian], &[&pool.admin]).await.unwrap();
+    process(&mut pool.context, &[ix::pause(guardian.pubkey())], &[&guardian]).await.unwrap();
+
+    // Unpausing stays with the admin
+    let err = process(&mut pool.context, &[ix::unpause(guardian.pubkey())], &[&guardian]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NotAdmin.into()));
+    process(&mut pool.context, &[ix::unpause(pool.admin.pubkey())], &[&pool.admin]).await.unwrap();
+    assert!(!pool.program_state().await.paused);
+}
+
+#[tokio::test]
+async fn a_prolonged_pause_turns_exit_only() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    process(&mut pool.context, &[ix::set_max_pause_duration(admin, 100)], &[&pool.admin]).await.unwrap();
+    process(&mut pool.context, &[ix::pause(admin)], &[&pool.admin]).await.unwrap();
+
+    let params = pool.withdraw_params(1, 2_000, &recipient.pubkey(), recipient_token_account).await;
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::PoolPaused.into()));
+    // The duration can't be stretched once a pause has begun
+    let err = process(&mut pool.context, &[ix::set_max_pause_duration(admin, 0)], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::PoolPaused.into()));
+
+    let slot = pool.slot().await;
+    pool.context.warp_to_slot(slot + 100).unwrap();
+    let params = pool.withdraw_params(1, 2_000, &recipient.pubkey(), recipient_token_account).await;
+    pool.withdraw(&params).await.unwrap();
+    assert_eq!(pool.token_balance(recipient_token_account).await, 2_000);
+
+    // Only withdrawals resume
+    let err = pool.deposit(1_000, [2; 32]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::PoolPaused.into()));
+    let err = process(&mut pool.context, &[ix::update_fee_config(admin, 10_000, u64::MAX)], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::ExitOnly.into()));
+    let err = process(&mut pool.context, &[ix::set_guardian(admin, Keypair::new().pubkey())], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::ExitOnly.into()));
+
+    process(&mut pool.context, &[ix::unpause(admin)], &[&pool.admin]).await.unwrap();
+    pool.deposit(1_000, [2; 32]).await.unwrap();
+}
+
+#[tokio::test]
+async fn withdrawals_reaching_the_epoch_limit_pause_the_pool() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+
+    let err = process(&mut pool.context, &[ix::set_withdrawal_limit(admin, 3_000, 0)], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::InvalidWithdrawalLimit.into()));
+    process(&mut pool.context, &[ix::set_withdrawal_limit(admin, 3_000, 1_000_000)], &[&pool.admin]).await.unwrap();
+
+    let params = pool.withdraw_params(1, 2_000, &recipient.pubkey(), recipient_token_account).await;
+    pool.withdraw(&params).await.unwrap();
+    assert_eq!(pool.program_state().await.withdrawn_in_epoch, 2_000);
+
+    // A withdrawal past the limit fails and leaves the pool live, however often it is sent
+    let params = pool.withdraw_params(2, 2_000, &recipient.pubkey(), recipient_token_account).await;
+    for _ in 0..2 {
+        let err = pool.withdraw(&params).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(PrivaxError::WithdrawalLimitReached.into()));
+        let state = pool.program_state().await;
+        assert!(!state.paused);
+        assert_eq!(state.withdrawn_in_epoch, 2_000);
+        pool.context.get_new_latest_blockhash().await.unwrap();
+    }
+
+    // The payout that takes the epoch exactly to the limit goes through and pauses the pool
+    let params = pool.withdraw_params(2, 1_000, &recipient.pubkey(), recipient_token_account).await;
+    pool.withdraw(&params).await.unwrap();
+    let state = pool.program_state().await;
+    assert!(state.paused);
+    assert_eq!(state.withdrawn_in_epoch, 3_000);
+    assert_eq!(pool.token_balance(recipient_token_account).await, 3_000);
+
+    let params = pool.withdraw_params(3, 1_000, &recipient.pubkey(), recipient_token_account).await;
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::PoolPaused.into()));
+    process(&mut pool.context, &[ix::unpause(admin)], &[&pool.admin]).await.unwrap();
+    assert_eq!(pool.program_state().await.withdrawn_in_epoch, 0);
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    pool.withdraw(&params).await.unwrap();
+    assert_eq!(pool.token_balance(recipient_token_account).await, 4_000);
+    assert!(!pool.program_state().await.paused);
+}
+
+#[tokio::test]
+async fn changing_the_withdrawal_limit_keeps_the_epoch_count() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    process(&mut pool.context, &[ix::set_withdrawal_limit(admin, 3_000, 1_000_000)], &[&pool.admin]).await.unwrap();
+    let params = pool.withdraw_params(1, 2_000, &recipient.pubkey(), recipient_token_account).await;
+    pool.withdraw(&params).await.unwrap();
+
+    // Lowering the limit, or lengthening the epoch, can't clear what was already withdrawn
+    for (max, epoch_slots) in [(2_500, 1_000_000), (2_500, 2_000_000)] {
+        process(&mut pool.context, &[ix::set_withdrawal_limit(admin, max, epoch_slots)], &[&pool.admin]).await.unwrap();
+        assert_eq!(pool.program_state().await.withdrawn_in_epoch, 2_000);
+    }
+    let params = pool.withdraw_params(2, 1_000, &recipient.pubkey(), recipient_token_account).await;
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::WithdrawalLimitReached.into()));
+}
+
+#[tokio::test]
+async fn reconcile_pauses_only_on_a_vault_shortfall() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    let params = pool.withdraw_params(1, 2_000, &recipient.pubkey(), recipient_token_account).await;
+    pool.withdraw(&params).await.unwrap();
+
+    let state = pool.program_state().await;
+    assert_eq!((state.total_deposited, state.total_withdrawn), (10_000, 2_000));
+    let expected = 8_000 + pool.fee_config().await.accrued_protocol_fees;
+    assert_eq!(pool.token_balance(ix::vault_address()).await, expected);
+    process(&mut pool.context, &[ix::set_pause_on_shortfall(admin, true)], &[&pool.admin]).await.unwrap();
+    process(&mut pool.context, &[ix::reconcile()], &[&pool.user]).await.unwrap();
+    assert!(!pool.program_state().await.paused);
+
+    // Tokens sent straight to the vault are a surplus, which is only reported
+    let donate = spl_token::instruction::transfer(
+        &spl_token::ID,
+        &pool.user_token_account,
+        &ix::vault_address(),
+        &pool.user.pubkey(),
+        &[],
+        500,
+    )
+    .unwrap();
+    process(&mut pool.context, &[donate], &[&pool.user]).await.unwrap();
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    process(&mut pool.context, &[ix::reconcile()], &[&pool.user]).await.unwrap();
+    assert!(!pool.program_state().await.paused);
+
+    // Tokens leaving the vault outside the program's accounting are a shortfall
+    let mut vault = pool.context.banks_client.get_account(ix::vault_address()).await.unwrap().unwrap();
+    let mut token_account = spl_token::state::Account::unpack(&vault.data).unwrap();
+    token_account.amount = expected - 1;
+    spl_token::state::Account::pack(token_account, &mut vault.data).unwrap();
+    pool.context.set_account(&ix::vault_address(), &vault.into());
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    process(&mut pool.context, &[ix::reconcile()], &[&pool.user]).await.unwrap();
+    assert!(pool.program_state().await.paused);
+}
+
+#[tokio::test]
+async fn exclusion_roots_come_from_the_admin_or_exclusion_operator() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    let operator = Keypair::new();
+
+    // Nothing to prove against yet
+    let err = process(&mut pool.context, &[ix::set_exclusion_required(admin, true)], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NoExclusionRoot.into()));
+    let publish = ix::publish_exclusion_root(operator.pubkey(), [1; 32]);
+    let err = process(&mut pool.context, &[publish.clone()], &[&operator]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NotExclusionOperator.into()));
+
+    process(&mut pool.context, &[ix::set_exclusion_operator(admin, operator.pubkey())], &[&pool.admin]).await.unwrap();
+    let err = process(&mut pool.context, &[ix::publish_exclusion_root(operator.pubkey(), [0; 32])], &[&operator])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::InvalidExclusionRoot.into()));
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    process(&mut pool.context, &[publish], &[&operator]).await.unwrap();
+    let published_at = pool.slot().await;
+    pool.context.warp_to_slot(published_at + 10).unwrap();
+    process(&mut pool.context, &[ix::publish_exclusion_root(admin, [2; 32])], &[&pool.admin]).await.unwrap();
+    let state = pool.program_state().await;
+    assert_eq!(state.exclusion_root, [2; 32]);
+    assert_eq!(state.previous_exclusion_root, [1; 32]);
+
+    // Proofs bound to a slot before the latest publish may still use the root it replaced
+    assert_eq!(state.exclusion_root_at(state.exclusion_root_slot), None);
+    process(&mut pool.context, &[ix::set_exclusion_required(admin, true)], &[&pool.admin]).await.unwrap();
+    let state = pool.program_state().await;
+    assert_eq!(state.exclusion_root_at(state.exclusion_root_slot - 1), Some([1; 32]));
+    assert_eq!(state.exclusion_root_at(state.exclusion_root_slot), Some([2; 32]));
+
+    // Only the admin opts the pool in or out
+    let err = process(&mut pool.context, &[ix::set_exclusion_required(operator.pubkey(), false)], &[&operator])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NotAdmin.into()));
+    process(&mut pool.context, &[ix::set_exclusion_required(admin, false)], &[&pool.admin]).await.unwrap();
+    assert!(!pool.program_state().await.exclusion_required);
+}
+
+#[tokio::test]
+async fn ownership_transfer_moves_admin_rights() {
+    let mut pool = TestPool::initialized().await;
+    let old_admin = pool.admin.pubkey();
+    let new_admin = Keypair::new();
+
+    let err = process(&mut pool.context, &[ix::transfer_ownership(old_admin, Pubkey::default())], &[&pool.admin])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NewAdminIsZero.into()));
+
+    let transfer = ix::transfer_ownership(old_admin, new_admin.pubkey());
+    process(&mut pool.context, &[transfer], &[&pool.admin]).await.unwrap();
+    assert_eq!(pool.program_state().await.admin, new_admin.pubkey());
+
+    // The old admin has no rights left
+    let err = process(&mut pool.context, &[ix::transfer_ownership(old_admin, old_admin)], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NotAdmin.into()));
+    let err = process(&mut pool.context, &[ix::pause(old_admin)], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NotAdminOrGuardian.into()));
+
+    process(&mut pool.context, &[ix::pause(new_admin.pubkey())], &[&new_admin]).await.unwrap();
+    process(&mut pool.context, &[ix::unpause(new_admin.pubkey())], &[&new_admin]).await.unwrap();
+    let transfer_back = ix::transfer_ownership(new_admin.pubkey(), old_admin);
+    process(&mut pool.context, &[transfer_back], &[&new_admin]).await.unwrap();
+    assert_eq!(pool.program_state().await.admin, old_admin);
+}
+
+#[tokio::test]
+async fn renouncing_the_admin_freezes_fees() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+
+    process(&mut pool.context, &[ix::initiate_renounce_admin(admin)], &[&pool.admin]).await.unwrap();
+    let slot = pool.slot().await;
+    pool.context.warp_to_slot(slot + 1).unwrap();
+    process(&mut pool.context, &[ix::renounce_admin(admin)], &[&pool.admin]).await.unwrap();
+    let state = pool.program_state().await;
+    assert_eq!(state.admin, Pubkey::default());
+    assert_eq!(state.frozen_groups, ProgramState::PARAM_GROUP_ALL);
+
+    // The admin was also the fee authority, and keeps that role, but can't use it any more
+    assert_eq!(pool.fee_config().await.fee_authority, admin);
+    let err = process(&mut pool.context, &[ix::update_fee_config(admin, 30, 5_000)], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::ParamsFrozen.into()));
+    assert_eq!(pool.fee_config().await.protocol_fee_bps, 0);
+}
+
+#[tokio::test]
+async fn renouncing_the_admin_drops_the_guardian() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    let guardian = Keypair::new();
+    let set_guardian = ix::set_guardian(admin, guardian.pubkey());
+    process(&mut pool.context, &[set_guardian], &[&pool.admin]).await.unwrap();
+
+    process(&mut pool.context, &[ix::initiate_renounce_admin(admin)], &[&pool.admin]).await.unwrap();
+    let slot = pool.slot().await;
+    pool.context.warp_to_slot(slot + 1).unwrap();
+    process(&mut pool.context, &[ix::renounce_admin(admin)], &[&pool.admin]).await.unwrap();
+    assert_eq!(pool.program_state().await.guardian, Pubkey::default());
+
+    // Otherwise the guardian could pause again every time a pause expired
+    let err = process(&mut pool.context, &[ix::pause(guardian.pubkey())], &[&guardian]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NotAdminOrGuardian.into()));
+    assert!(!pool.program_state().await.paused);
+}
+
+#[tokio::test]
+async fn admin_actions_are_recorded_in_the_audit_log() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    let guardian = Keypair::new().pubkey();
+
+    let set_guardian = ix::set_guardian(admin, guardian);
+    process(&mut pool.context, &[set_guardian], &[&pool.admin]).await.unwrap();
+    let update_fee_config = ix::update_fee_config(admin, 30, 5_000);
+    process(&mut pool.context, &[update_fee_config], &[&pool.admin]).await.unwrap();
+
+    let audit_log = pool.audit_log().await;
+    // Registering the verifying key at setup, then the two changes above
+    let entries: Vec<_> = audit_log.entries().copied().collect();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].action(), Some(AuditAction::RegisterVerifyingKey));
+
+    let entry = entries[1];
+    assert_eq!(entry.action(), Some(AuditAction::SetGuardian));
+    assert_eq!(entry.actor, admin);
+    assert_eq!(entry.old_value_hash, audit_value_hash(&Pubkey::default()).unwrap());
+    assert_eq!(entry.new_value_hash, audit_value_hash(&guardian).unwrap());
+
+    let entry = entries[2];
+    assert_eq!(entry.action(), Some(AuditAction::UpdateFeeConfig));
+    assert_eq!(entry.old_value_hash, audit_value_hash(&(0u16, 0u64)).unwrap());
+    assert_eq!(entry.new_value_hash, audit_value_hash(&(30u16, 5_000u64)).unwrap());
+    assert!(entry.slot <= pool.slot().await);
+}
+
+const TIMELOCK_SLOTS: u64 = 100;
+
+// Sets the admin timelock, then queues `action` as admin action 0, paid by the admin.
+async fn queue_with_timelock(pool: &mut TestPool, action: AdminAction) {
+    let admin = pool.admin.pubkey();
+    let instructions = [ix::set_admin_timelock(admin, TIMELOCK_SLOTS), ix::queue_admin_action(admin, admin, 0, action)];
+    process(&mut pool.context, &instructions, &[&pool.admin]).await.unwrap();
+}
+
+#[tokio::test]
+async fn timelocked_admin_actions_wait_out_the_timelock() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    let guardian = Keypair::new().pubkey();
+    let action = AdminAction::SetGuardian { new_guardian: guardian };
+    queue_with_timelock(&mut pool, action).await;
+    let queued_at = pool.slot().await;
+    assert_eq!(pool.program_state().await.next_admin_action_id, 1);
+
+    // Their direct forms are closed, and the timelock can only go up without the queue
+    for direct in [
+        ix::set_guardian(admin, guardian),
+        ix::transfer_ownership(admin, guardian),
+        ix::set_admin_timelock(admin, TIMELOCK_SLOTS - 1),
+    ] {
+        let err = process(&mut pool.context, &[direct], &[&pool.admin]).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(PrivaxError::AdminTimelockActive.into()));
+    }
+    let err = process(&mut pool.context, &[ix::execute_admin_action(admin, admin, 0, &action)], &[&pool.admin])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::AdminActionNotReady.into()));
+
+    pool.context.warp_to_slot(queued_at + TIMELOCK_SLOTS).unwrap();
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    process(&mut pool.context, &[ix::execute_admin_action(admin, admin, 0, &action)], &[&pool.admin]).await.unwrap();
+    assert_eq!(pool.program_state().await.guardian, guardian);
+    let queued = pool.context.banks_client.get_account(ix::queued_admin_action_address(0)).await.unwrap();
+    assert!(queued.is_none());
+
+    let entries: Vec<_> = pool.audit_log().await.entries().map(|entry| entry.action()).collect();
+    assert_eq!(
+        entries[entries.len() - 3..],
+        [Some(AuditAction::SetAdminTimelock), Some(AuditAction::QueueAdminAction), Some(AuditAction::SetGuardian)]
+    );
+}
+
+#[tokio::test]
+async fn the_guardian_can_cancel_queued_admin_actions() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    let guardian = Keypair::new();
+    let set_guardian = ix::set_guardian(admin, guardian.pubkey());
+    process(&mut pool.context, &[set_guardian], &[&pool.admin]).await.unwrap();
+    let action = AdminAction::TransferOwnership { new_admin: pool.user.pubkey() };
+    queue_with_timelock(&mut pool, action).await;
+    let queued_at = pool.slot().await;
+
+    let user = pool.user.pubkey();
+    let err = process(&mut pool.context, &[ix::cancel_admin_action(user, admin, 0)], &[&pool.user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NotAdminOrGuardian.into()));
+    let cancel = ix::cancel_admin_action(guardian.pubkey(), admin, 0);
+    process(&mut pool.context, &[cancel], &[&guardian]).await.unwrap();
+    let queued = pool.context.banks_client.get_account(ix::queued_admin_action_address(0)).await.unwrap();
+    assert!(queued.is_none());
+
+    pool.context.warp_to_slot(queued_at + TIMELOCK_SLOTS).unwrap();
+    let execute = ix::execute_admin_action(admin, admin, 0, &action);
+    let err = process(&mut pool.context, &[execute], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(anchor_lang::error::ErrorCode::AccountNotInitialized.into()));
+    assert_eq!(pool.program_state().await.admin, admin);
+}
+
+#[tokio::test]
+async fn keys_registered_under_a_timelock_are_enabled_through_the_queue() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    let circuit_version = CIRCUIT_VERSION + 1;
+    let action = AdminAction::EnableVerifyingKey { circuit_version };
+    queue_with_timelock(&mut pool, action).await;
+    let queued_at = pool.slot().await;
+
+    let register = pool.register_verifying_key_ix(circuit_version, REQUIRED_PUBLIC_INPUTS_COUNT);
+    process(&mut pool.context, &[register], &[&pool.admin]).await.unwrap();
+    assert!(!pool.verifying_key(circuit_version).await.is_enabled());
+    let enable = ix::set_verifying_key_enabled(admin, circuit_version, true);
+    let err = process(&mut pool.context, &[enable], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::AdminTimelockActive.into()));
+
+    // Disabling a key needs no queue
+    process(&mut pool.context, &[ix::set_verifying_key_enabled(admin, CIRCUIT_VERSION, false)], &[&pool.admin]).await.unwrap();
+    assert!(!pool.verifying_key(CIRCUIT_VERSION).await.is_enabled());
+
+    pool.context.warp_to_slot(queued_at + TIMELOCK_SLOTS).unwrap();
+    process(&mut pool.context, &[ix::execute_admin_action(admin, admin, 0, &action)], &[&pool.admin]).await.unwrap();
+    assert!(pool.verifying_key(circuit_version).await.is_enabled());
+}
+
+#[tokio::test]
+async fn safeguards_tighten_at_once_and_loosen_through_the_queue() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    let attestor = Keypair::new().pubkey();
+    let action = AdminAction::SetWithdrawalLimit { max_withdrawal_per_epoch: 0, withdrawal_epoch_slots: 0 };
+    queue_with_timelock(&mut pool, action).await;
+    let queued_at = pool.slot().await;
+
+    // Turning the safeguards on and lowering the limit need no queue
+    let tighten = [
+        ix::set_withdrawal_limit(admin, 3_000, 1_000_000),
+        ix::set_withdrawal_limit(admin, 2_000, 1_000_000),
+        ix::set_compliance_key(admin, [9; 32]),
+        ix::set_kyc_attestor(admin, attestor),
+    ];
+    process(&mut pool.context, &tighten, &[&pool.admin]).await.unwrap();
+
+    // Loosening, replacing or turning them off do
+    for loosen in [
+        ix::set_withdrawal_limit(admin, 2_001, 1_000_000),
+        ix::set_withdrawal_limit(admin, 2_000, 999_999),
+        ix::set_withdrawal_limit(admin, 0, 0),
+        ix::set_compliance_key(admin, [8; 32]),
+        ix::set_compliance_key(admin, [0; 32]),
+        ix::set_kyc_attestor(admin, admin),
+        ix::set_kyc_attestor(admin, Pubkey::default()),
+        ix::set_exclusion_required(admin, false),
+    ] {
+        let err = process(&mut pool.context, &[loosen], &[&pool.admin]).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(PrivaxError::AdminTimelockActive.into()));
+    }
+    let state = pool.program_state().await;
+    assert_eq!((state.max_withdrawal_per_epoch, state.compliance_key, state.kyc_attestor), (2_000, [9; 32], attestor));
+
+    pool.context.warp_to_slot(queued_at + TIMELOCK_SLOTS).unwrap();
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    process(&mut pool.context, &[ix::execute_admin_action(admin, admin, 0, &action)], &[&pool.admin]).await.unwrap();
+    assert_eq!(pool.program_state().await.max_withdrawal_per_epoch, 0);
+}
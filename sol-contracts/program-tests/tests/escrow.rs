@@ -0,0 +1,223 @@
+// The example privax_escrow program: funded by a withdrawal into it and paid out by a deposit,
+// both through CPI, so these also cover the pool's CPI interface.
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{system_program, AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use privax_client::instructions as ix;
+use privax_escrow::{authority_address, escrow_address, Escrow, EscrowError};
+use privax_program_tests::{error_code, expected_root, process, TestPool, CIRCUIT_VERSION};
+use solana_program_test::BanksClientError;
+use solana_sdk::signature::{Keypair, Signer};
+
+const BUYER_NOTE: [u8; 32] = [1; 32];
+const SELLER_COMMITMENT: [u8; 32] = [5; 32];
+const REFUND_COMMITMENT: [u8; 32] = [6; 32];
+
+struct TestEscrow {
+    address: Pubkey,
+    authority: Pubkey,
+    vault: Pubkey,
+    seller: Keypair,
+}
+
+// The buyer's escrow 0, not opened yet. The pool's user is the buyer.
+fn new_escrow(pool: &TestPool) -> TestEscrow {
+    let address = escrow_address(&pool.user.pubkey(), 0);
+    let authority = authority_address(&address);
+    TestEscrow { address, authority, vault: get_associated_token_address(&authority, &pool.mint), seller: Keypair::new() }
+}
+
+// Opens the escrow for `amount`, after depositing the buyer's note to fund it with.
+async fn open(pool: &mut TestPool, amount: u64) -> TestEscrow {
+    pool.deposit(amount, BUYER_NOTE).await.unwrap();
+    let escrow = new_escrow(pool);
+    let open = open_ix(pool, &escrow, amount, SELLER_COMMITMENT);
+    process(&mut pool.context, &[open], &[&pool.user]).await.unwrap();
+    escrow
+}
+
+fn open_ix(pool: &TestPool, escrow: &TestEscrow, amount: u64, seller_commitment: [u8; 32]) -> Instruction {
+    let accounts = privax_escrow::accounts::OpenEscrow {
+        escrow: escrow.address,
+        authority: escrow.authority,
+        vault: escrow.vault,
+        mint: pool.mint,
+        buyer: pool.user.pubkey(),
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        system_program: system_program::ID,
+    };
+    Instruction {
+        program_id: privax_escrow::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_escrow::instruction::OpenEscrow {
+            escrow_id: 0,
+            seller: escrow.seller.pubkey(),
+            amount,
+            seller_commitment,
+            refund_commitment: REFUND_COMMITMENT,
+        }
+        .data(),
+    }
+}
+
+// Withdraws `amount` from the buyer's note into the escrow, submitted by the bank's payer:
+// the proof names the escrow's authority, so anyone can send it.
+async fn fund(pool: &mut TestPool, escrow: &TestEscrow, nullifier: u64, amount: u64) -> Result<(), BanksClientError> {
+    let params = pool.withdraw_params(nullifier, amount, &escrow.authority, escrow.vault).await;
+    pool.init_nullifier_shards(&params).await?;
+    let accounts = privax_escrow::accounts::FundFromNote {
+        escrow: escrow.address,
+        authority: escrow.authority,
+        vault: escrow.vault,
+        privax_program: privax_protocol::ID,
+        program_state: ix::program_state_address(),
+        verifying_key: ix::verifying_key_address(CIRCUIT_VERSION),
+        fee_config: ix::fee_config_address(),
+        nullifier_shard: ix::nullifier_shard_address(ix::nullifier_prefix(&params.nullifier_hash)),
+        nullifier_filter: ix::nullifier_filter_address(),
+        merkle_tree: ix::merkle_tree_address(),
+        root_archive: None,
+        pool_vault: ix::vault_address(),
+        usd_limits: None,
+        price_feed: None,
+        token_program: anchor_spl::token::ID,
+        system_program: system_program::ID,
+    };
+    let fund = Instruction {
+        program_id: privax_escrow::ID,
+        accounts: accounts.to_account_metas(None),
+        data: privax_escrow::instruction::FundFromNote {
+            a_proof: params.a_proof,
+            b_proof: params.b_proof,
+            c_proof: params.c_proof,
+            public_inputs: params.public_inputs,
+            merkle_root: params.merkle_root,
+            nullifier_hash: params.nullifier_hash,
+            extra_nullifier_hashes: params.extra_nullifier_hashes.clone(),
+            amount_to_withdraw: amount,
+            circuit_version: CIRCUIT_VERSION,
+            plonk_proof: Vec::new(),
+            change_commitment: None,
+        }
+        .data(),
+    };
+    process(&mut pool.context, &[fund], &[]).await
+}
+
+// `release` or `refund`, signed by `signer`, paying `commitment`'s note.
+async fn settle(
+    pool: &mut TestPool,
+    escrow: &TestEscrow,
+    signer: &Keypair,
+    release: bool,
+    commitment: [u8; 32],
+) -> Result<(), BanksClientError> {
+    let accounts = privax_escrow::accounts::Settle {
+        escrow: escrow.address,
+        signer: signer.pubkey(),
+        buyer: pool.user.pubkey(),
+        authority: escrow.authority,
+        vault: escrow.vault,
+        mint: pool.mint,
+        privax_program: privax_protocol::ID,
+        program_state: ix::program_state_address(),
+        fee_config: ix::fee_config_address(),
+        merkle_tree: ix::merkle_tree_address(),
+        commitment_shard: ix::commitment_shard_address([commitment[0], commitment[1]]),
+        pool_vault: ix::vault_address(),
+        usd_limits: None,
+        price_feed: None,
+        token_program: anchor_spl::token::ID,
+        system_program: system_program::ID,
+        rent: sysvar::rent::ID,
+    };
+    let data = if release {
+        privax_escrow::instruction::Release {}.data()
+    } else {
+        privax_escrow::instruction::Refund {}.data()
+    };
+    let settle = Instruction { program_id: privax_escrow::ID, accounts: accounts.to_account_metas(None), data };
+    process(&mut pool.context, &[settle], &[signer]).await
+}
+
+async fn escrow_account(pool: &mut TestPool, escrow: &TestEscrow) -> Escrow {
+    let account = pool.context.banks_client.get_account(escrow.address).await.unwrap().unwrap();
+    Escrow::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+// Nothing of the escrow is left on-chain once it settles.
+async fn assert_closed(pool: &mut TestPool, escrow: &TestEscrow) {
+    for address in [escrow.address, escrow.authority, escrow.vault] {
+        assert!(pool.context.banks_client.get_account(address).await.unwrap().is_none());
+    }
+}
+
+#[tokio::test]
+async fn release_pays_the_seller_a_note_from_the_buyers() {
+    let mut pool = TestPool::initialized().await;
+    let escrow = open(&mut pool, 5_000).await;
+    let buyer = pool.user.insecure_clone();
+    let seller = escrow.seller.insecure_clone();
+
+    let err = settle(&mut pool, &escrow, &buyer, true, SELLER_COMMITMENT).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EscrowError::NotFunded.into()));
+    let err = fund(&mut pool, &escrow, 42, 4_000).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EscrowError::Underfunded.into()));
+
+    fund(&mut pool, &escrow, 42, 5_000).await.unwrap();
+    assert!(escrow_account(&mut pool, &escrow).await.funded);
+    assert_eq!(pool.token_balance(escrow.vault).await, 5_000);
+    let err = fund(&mut pool, &escrow, 43, 5_000).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EscrowError::AlreadyFunded.into()));
+
+    // Once funded, only the buyer can let the seller have it, and the buyer can't take it back
+    let err = settle(&mut pool, &escrow, &seller, true, SELLER_COMMITMENT).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EscrowError::ReleaseNotAllowed.into()));
+    let err = settle(&mut pool, &escrow, &buyer, false, REFUND_COMMITMENT).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EscrowError::RefundNotAllowed.into()));
+
+    // The same release that failed before funding, in a later transaction
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    settle(&mut pool, &escrow, &buyer, true, SELLER_COMMITMENT).await.unwrap();
+    assert_eq!(pool.merkle_tree().await.root(), expected_root(&[BUYER_NOTE, SELLER_COMMITMENT]));
+    assert_eq!(pool.token_balance(ix::vault_address()).await, 5_000);
+    assert_eq!(pool.program_state().await.outstanding_deposits, 5_000);
+    assert_closed(&mut pool, &escrow).await;
+}
+
+#[tokio::test]
+async fn the_seller_can_refund_the_buyer_a_note() {
+    let mut pool = TestPool::initialized().await;
+    let escrow = open(&mut pool, 5_000).await;
+    fund(&mut pool, &escrow, 42, 5_000).await.unwrap();
+
+    let seller = escrow.seller.insecure_clone();
+    settle(&mut pool, &escrow, &seller, false, REFUND_COMMITMENT).await.unwrap();
+    assert_eq!(pool.merkle_tree().await.root(), expected_root(&[BUYER_NOTE, REFUND_COMMITMENT]));
+    assert_eq!(pool.token_balance(ix::vault_address()).await, 5_000);
+    assert_closed(&mut pool, &escrow).await;
+}
+
+#[tokio::test]
+async fn an_unfunded_escrow_closes_without_touching_the_pool() {
+    let mut pool = TestPool::initialized().await;
+    let buyer = pool.user.insecure_clone();
+    let balance_before = pool.context.banks_client.get_balance(buyer.pubkey()).await.unwrap();
+    let escrow = new_escrow(&pool);
+
+    // The pool would refuse to pay out to this commitment, stranding the funds
+    let open = open_ix(&pool, &escrow, 5_000, [0; 32]);
+    let err = process(&mut pool.context, &[open], &[&buyer]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EscrowError::InvalidCommitment.into()));
+
+    let open = open_ix(&pool, &escrow, 5_000, SELLER_COMMITMENT);
+    process(&mut pool.context, &[open], &[&buyer]).await.unwrap();
+    settle(&mut pool, &escrow, &buyer, false, REFUND_COMMITMENT).await.unwrap();
+    assert_eq!(pool.merkle_tree().await.next_index, 0);
+    assert_closed(&mut pool, &escrow).await;
+    // The escrow's rent and the authority's funding all come back; the bank's payer paid the fees
+    assert_eq!(pool.context.banks_client.get_balance(buyer.pubkey()).await.unwrap(), balance_before);
+}
@@ -0,0 +1,501 @@
+// Initialize, deposit, tree inserts, proof checks, withdraw, relayer binding, double-spend rejection, nullifier checks,
+// note announcements, compliance attestations, KYC credentials and daily deposit caps.
+use anchor_lang::error::ErrorCode;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize};
+use privax_client::instructions::{self as ix, DepositGates};
+use privax_program_tests::withdraw_circuit::{self, WithdrawWitness};
+use privax_program_tests::{
+    error_code, expected_root, nullifier_hash_for, process, prove_withdraw, public_input, TestPool, CIRCUIT_VERSION, USER_BALANCE,
+};
+use privax_protocol::privax_protocol::{MAX_PROOF_AGE_SLOTS, VARIABLE_DENOMINATION};
+use privax_protocol::{field_input, ComplianceAttestation, DepositVolume, FeeSchedule, PrivaxError, MAX_NOTE_CIPHERTEXT_LEN};
+use privax_test_vectors::vectors;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+#[tokio::test]
+async fn initialize_creates_an_empty_pool() {
+    let mut pool = TestPool::initialized().await;
+
+    let state = pool.program_state().await;
+    assert_eq!(state.admin, pool.admin.pubkey());
+    assert_eq!(state.token_mint, pool.mint);
+    assert!(!state.paused);
+    assert_eq!(state.outstanding_deposits, 0);
+    assert_eq!(pool.fee_config().await.fee_authority, pool.admin.pubkey());
+
+    let tree = pool.merkle_tree().await;
+    assert_eq!(tree.next_index, 0);
+    assert_eq!(tree.root(), expected_root(&[]));
+
+    // A deployment holds a single pool
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    let initialize = pool.initialize_ix();
+    assert!(privax_program_tests::process(&mut pool.context, &[initialize], &[&pool.admin]).await.is_err());
+}
+
+#[tokio::test]
+async fn deposits_append_commitments_to_the_tree() {
+    let mut pool = TestPool::initialized().await;
+    let vault = privax_client::instructions::vault_address();
+
+    let commitments: Vec<[u8; 32]> = (1..=3).map(|i| [i; 32]).collect();
+    let mut roots = vec![pool.merkle_tree().await.root()];
+    for (i, commitment) in commitments.iter().enumerate() {
+        pool.deposit(1_000, *commitment).await.unwrap();
+
+        let tree = pool.merkle_tree().await;
+        assert_eq!(tree.next_index, i as u64 + 1);
+        assert_eq!(tree.root(), expected_root(&commitments[..=i]));
+        roots.push(tree.root());
+    }
+
+    // Earlier roots stay valid for proofs made against them
+    let tree = pool.merkle_tree().await;
+    assert!(roots.iter().all(|root| tree.is_known_root(root)));
+    assert_eq!(pool.token_balance(vault).await, 3_000);
+    assert_eq!(pool.token_balance(pool.user_token_account).await, USER_BALANCE - 3_000);
+    assert_eq!(pool.program_state().await.outstanding_deposits, 3_000);
+
+    let err = pool.deposit(0, [9; 32]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::AmountTooSmall.into()));
+    // No note opens these, so the deposit could never be withdrawn
+    for commitment in [[0; 32], privax_protocol::proof_format::BN254_SCALAR_FIELD_MODULUS, [0xff; 32]] {
+        let err = pool.deposit(1_000, commitment).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(PrivaxError::InvalidCommitment.into()));
+    }
+    // Two leaves with one commitment would share a nullifier, so only one could be withdrawn
+    let err = pool.deposit(2_000, commitments[0]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::DuplicateCommitment.into()));
+}
+
+#[tokio::test]
+async fn withdraw_pays_the_recipient() {
+    let mut pool = TestPool::initialized().await;
+    let vault = privax_client::instructions::vault_address();
+    pool.deposit(5_000, [1; 32]).await.unwrap();
+
+    let recipient = Keypair::new();
+    // Only the recipient's associated token account is paid, not just any account it owns
+    let other_account = pool.create_token_account(&recipient.pubkey()).await;
+    let params = pool.withdraw_params(42, 5_000, &recipient.pubkey(), other_account).await;
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(ErrorCode::ConstraintAssociated.into()));
+
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    let params = pool.withdraw_params(42, 5_000, &recipient.pubkey(), recipient_token_account).await;
+    pool.withdraw(&params).await.unwrap();
+
+    assert_eq!(pool.token_balance(recipient_token_account).await, 5_000);
+    assert_eq!(pool.token_balance(vault).await, 0);
+    assert_eq!(pool.program_state().await.outstanding_deposits, 0);
+}
+
+#[tokio::test]
+async fn spent_nullifiers_are_rejected() {
+    let mut pool = TestPool::initialized().await;
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    let params = pool.withdraw_params(7, 1_000, &recipient.pubkey(), recipient_token_account).await;
+    pool.withdraw(&params).await.unwrap();
+
+    // The same withdrawal in a later transaction
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NullifierAlreadySpent.into()));
+
+    // And the same nullifier in a fresh proof, to another recipient
+    let other = Keypair::new();
+    let other_token_account = pool.create_associated_token_account(&other.pubkey()).await;
+    let params = pool.withdraw_params(7, 1_000, &other.pubkey(), other_token_account).await;
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NullifierAlreadySpent.into()));
+    assert_eq!(pool.token_balance(other_token_account).await, 0);
+}
+
+#[tokio::test]
+async fn withdrawals_bind_their_whole_change_commitment() {
+    let mut pool = TestPool::initialized().await;
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    // Two notes and a change note: the fixed inputs, one more nullifier and the change commitment
+    let register = pool.register_verifying_key_ix(CIRCUIT_VERSION + 1, 10);
+    process(&mut pool.context, &[register], &[&pool.admin]).await.unwrap();
+
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    let mut params = pool.withdraw_params(7, 3_000, &recipient.pubkey(), recipient_token_account).await;
+    let change = [2; 32];
+    params.circuit_version = CIRCUIT_VERSION + 1;
+    params.extra_nullifier_hashes = vec![nullifier_hash_for(8)];
+    params.public_inputs[4] = privax_protocol::external_nullifier(&pool.mint, 0, CIRCUIT_VERSION + 1);
+    params.public_inputs[8] = 8;
+    params.public_inputs[9] = public_input(&change);
+    params.change_commitment = Some(change);
+    prove_withdraw(&mut params);
+
+    // Bytes past the u64 input's are still the proof's: another change note doesn't verify
+    for byte in [8, 31] {
+        let mut tampered = params.clone();
+        tampered.change_commitment.as_mut().unwrap()[byte] ^= 1;
+        let err = pool.withdraw(&tampered).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(PrivaxError::InvalidZkProof.into()));
+    }
+
+    let next_index = pool.merkle_tree().await.next_index;
+    pool.withdraw(&params).await.unwrap();
+    assert_eq!(pool.token_balance(recipient_token_account).await, 3_000);
+    let tree = pool.merkle_tree().await;
+    assert_eq!(tree.next_index, next_index + 1);
+    assert_eq!(tree.root(), expected_root(&[[1; 32], change]));
+}
+
+#[tokio::test]
+async fn withdrawals_bind_their_whole_root_and_nullifier_hashes() {
+    let mut pool = TestPool::initialized().await;
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    let params = pool.withdraw_params(7, 1_000, &recipient.pubkey(), recipient_token_account).await;
+
+    // The u64 inputs must stand for the full values passed with them
+    let mut tampered = params.clone();
+    tampered.merkle_root[0] ^= 1;
+    let err = pool.withdraw(&tampered).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::MerkleRootMismatch.into()));
+    let mut tampered = params.clone();
+    tampered.nullifier_hash[0] ^= 1;
+    let err = pool.withdraw(&tampered).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NullifierHashMismatch.into()));
+
+    // And bytes past those the u64 inputs carry are still the proof's
+    for byte in [8, 31] {
+        let mut tampered = params.clone();
+        tampered.nullifier_hash[byte] ^= 1;
+        let err = pool.withdraw(&tampered).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(PrivaxError::InvalidZkProof.into()));
+        // A root is only known if all of it matches one
+        let mut tampered = params.clone();
+        tampered.merkle_root[byte] ^= 1;
+        let err = pool.withdraw(&tampered).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(PrivaxError::RootNotKnown.into()));
+    }
+
+    pool.withdraw(&params).await.unwrap();
+    assert_eq!(pool.token_balance(recipient_token_account).await, 1_000);
+}
+
+// A proof from the withdraw circuit itself, for the test vectors' first note, rather than one
+// from `prover`, which proves any inputs.
+#[tokio::test]
+async fn withdraw_verifies_withdraw_circuit_proofs() {
+    let mut pool = TestPool::initialized().await;
+    let vectors = vectors();
+    for note in &vectors.notes[..3] {
+        pool.deposit(note.amount, note.commitment).await.unwrap();
+    }
+    assert_eq!(pool.merkle_tree().await.root(), vectors.poseidon_tree.root);
+    let circuit_version = CIRCUIT_VERSION + 1;
+    let register = pool.register_vk_data_ix(circuit_version, withdraw_circuit::verifying_key());
+    process(&mut pool.context, &[register], &[&pool.admin]).await.unwrap();
+
+    let withdrawal = &vectors.withdrawals[0];
+    let note = &vectors.notes[withdrawal.input_notes[0]];
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    let mut params =
+        pool.withdraw_params(public_input(&note.nullifier_hash), note.amount, &recipient.pubkey(), recipient_token_account).await;
+    params.nullifier_hash = note.nullifier_hash;
+    params.circuit_version = circuit_version;
+    params.public_inputs[4] = privax_protocol::external_nullifier(&pool.mint, VARIABLE_DENOMINATION, circuit_version);
+    let mut inputs: Vec<[u8; 32]> = params.public_inputs[..withdraw_circuit::PUBLIC_INPUTS].iter().map(|&input| field_input(input)).collect();
+    inputs[0] = params.merkle_root;
+    inputs[1] = params.nullifier_hash;
+    (params.a_proof, params.b_proof, params.c_proof) = withdraw_circuit::prove(&WithdrawWitness::from_vector(withdrawal), &inputs);
+
+    // Only the note's real nullifier hash verifies, down to the bytes the u64 input doesn't carry
+    let mut tampered = params.clone();
+    tampered.nullifier_hash[31] ^= 1;
+    let err = pool.withdraw(&tampered).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::InvalidZkProof.into()));
+
+    pool.withdraw(&params).await.unwrap();
+    assert_eq!(pool.token_balance(recipient_token_account).await, note.amount);
+}
+
+#[tokio::test]
+async fn notes_are_announced_alongside_their_deposit() {
+    let mut pool = TestPool::initialized().await;
+    let commitment = [1; 32];
+    let deposit = ix::deposit(pool.user.pubkey(), pool.user_token_account, pool.mint, 10_000, commitment, None, DepositGates::default());
+    let announce = ix::announce_note(commitment, vec![7; MAX_NOTE_CIPHERTEXT_LEN]);
+    let user = pool.user.insecure_clone();
+    process(&mut pool.context, &[deposit, announce], &[&user]).await.unwrap();
+    assert_eq!(pool.merkle_tree().await.next_index, 1);
+
+    for ciphertext in [Vec::new(), vec![7; MAX_NOTE_CIPHERTEXT_LEN + 1]] {
+        let err = process(&mut pool.context, &[ix::announce_note(commitment, ciphertext)], &[]).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(PrivaxError::InvalidNoteCiphertext.into()));
+    }
+}
+
+#[tokio::test]
+async fn compliance_pools_only_take_attested_deposits() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    let user = pool.user.insecure_clone();
+    let attested = DepositGates { compliance_attestation: true, ..Default::default() };
+    process(&mut pool.context, &[ix::set_compliance_key(admin, [9; 32])], &[&pool.admin]).await.unwrap();
+
+    let err = pool.deposit(1_000, [1; 32]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::ComplianceAttestationRequired.into()));
+    // Someone else's attestation doesn't cover the user's deposit
+    process(&mut pool.context, &[ix::attest_deposit(admin, [1; 32], [5; 32])], &[&pool.admin]).await.unwrap();
+    let deposit = ix::deposit(user.pubkey(), pool.user_token_account, pool.mint, 1_000, [1; 32], None, attested);
+    let err = process(&mut pool.context, &[deposit], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::ComplianceAttestationMismatch.into()));
+
+    let attest = ix::attest_deposit(user.pubkey(), [2; 32], [6; 32]);
+    let deposit = ix::deposit(user.pubkey(), pool.user_token_account, pool.mint, 1_000, [2; 32], None, attested);
+    process(&mut pool.context, &[attest, deposit], &[&user]).await.unwrap();
+    let address = ix::compliance_attestation_address(&[2; 32]);
+    let account = pool.context.banks_client.get_account(address).await.unwrap().unwrap();
+    let attestation = ComplianceAttestation::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!((attestation.depositor, attestation.envelope_hash, attestation.compliance_key), (user.pubkey(), [6; 32], [9; 32]));
+    assert_eq!(attestation.leaf_index, 0);
+    assert_ne!(attestation.deposit_slot, 0);
+
+    process(&mut pool.context, &[ix::set_compliance_key(admin, [0; 32])], &[&pool.admin]).await.unwrap();
+    pool.deposit(1_000, [3; 32]).await.unwrap();
+    let err = process(&mut pool.context, &[ix::attest_deposit(user.pubkey(), [4; 32], [6; 32])], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NoComplianceKey.into()));
+}
+
+#[tokio::test]
+async fn institutional_pools_only_take_kyc_holders() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    let user = pool.user.insecure_clone();
+    let kyc = DepositGates { kyc_credential: true, ..Default::default() };
+    process(&mut pool.context, &[ix::set_kyc_attestor(admin, admin)], &[&pool.admin]).await.unwrap();
+
+    let err = pool.deposit(1_000, [1; 32]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::KycCredentialRequired.into()));
+    // Only the attestor issues credentials, and not already expired ones
+    let err = process(&mut pool.context, &[ix::issue_kyc_credential(user.pubkey(), user.pubkey(), 0)], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::NotKycAttestor.into()));
+    let err = process(&mut pool.context, &[ix::issue_kyc_credential(admin, user.pubkey(), 1)], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::InvalidKycExpiry.into()));
+
+    process(&mut pool.context, &[ix::issue_kyc_credential(admin, user.pubkey(), 0)], &[&pool.admin]).await.unwrap();
+    let deposit = ix::deposit(user.pubkey(), pool.user_token_account, pool.mint, 1_000, [1; 32], None, kyc);
+    process(&mut pool.context, &[deposit], &[&user]).await.unwrap();
+
+    // A new attestor voids the old one's credentials, which it can still revoke
+    process(&mut pool.context, &[ix::set_kyc_attestor(admin, Keypair::new().pubkey())], &[&pool.admin]).await.unwrap();
+    let deposit = ix::deposit(user.pubkey(), pool.user_token_account, pool.mint, 1_000, [2; 32], None, kyc);
+    let err = process(&mut pool.context, &[deposit], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::KycCredentialInvalid.into()));
+    process(&mut pool.context, &[ix::revoke_kyc_credential(admin, user.pubkey())], &[&pool.admin]).await.unwrap();
+    let credential = ix::kyc_credential_address(&user.pubkey());
+    assert!(pool.context.banks_client.get_account(credential).await.unwrap().is_none());
+
+    process(&mut pool.context, &[ix::set_kyc_attestor(admin, Default::default())], &[&pool.admin]).await.unwrap();
+    pool.deposit(1_000, [2; 32]).await.unwrap();
+}
+
+#[tokio::test]
+async fn daily_deposit_caps_count_the_last_24_hours() {
+    let mut pool = TestPool::initialized().await;
+    let admin = pool.admin.pubkey();
+    let user = pool.user.insecure_clone();
+    let capped = DepositGates { deposit_volume: true, ..Default::default() };
+    process(&mut pool.context, &[ix::set_max_daily_deposit(admin, 3_000)], &[&pool.admin]).await.unwrap();
+
+    let err = pool.deposit(1_000, [1; 32]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::DepositVolumeRequired.into()));
+    // Anyone can open a depositor's volume account
+    process(&mut pool.context, &[ix::init_deposit_volume(admin, user.pubkey())], &[&pool.admin]).await.unwrap();
+    let deposit = ix::deposit(user.pubkey(), pool.user_token_account, pool.mint, 2_000, [1; 32], None, capped);
+    process(&mut pool.context, &[deposit], &[&user]).await.unwrap();
+    let deposit = ix::deposit(user.pubkey(), pool.user_token_account, pool.mint, 1_500, [2; 32], None, capped);
+    let err = process(&mut pool.context, &[deposit], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::DailyDepositCapReached.into()));
+
+    // Twelve hours on, the first deposit still counts
+    let mut clock = pool.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp += 12 * 3600;
+    pool.context.set_sysvar(&clock);
+    let deposit = ix::deposit(user.pubkey(), pool.user_token_account, pool.mint, 1_000, [2; 32], None, capped);
+    process(&mut pool.context, &[deposit], &[&user]).await.unwrap();
+    let deposit = ix::deposit(user.pubkey(), pool.user_token_account, pool.mint, 1, [3; 32], None, capped);
+    let err = process(&mut pool.context, &[deposit], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::DailyDepositCapReached.into()));
+
+    // A day after it, only the second one does
+    clock.unix_timestamp += 13 * 3600;
+    pool.context.set_sysvar(&clock);
+    let deposit = ix::deposit(user.pubkey(), pool.user_token_account, pool.mint, 2_000, [3; 32], None, capped);
+    process(&mut pool.context, &[deposit], &[&user]).await.unwrap();
+    let volume = ix::deposit_volume_address(&user.pubkey());
+    let account = pool.context.banks_client.get_account(volume).await.unwrap().unwrap();
+    let volume = DepositVolume::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(volume.hourly.iter().sum::<u64>(), 3_000);
+}
+
+// A check_nullifiers instruction's answer in a simulation, or the custom error it failed with.
+#[tokio::test]
+async fn proofs_bind_the_relayer_and_its_fee() {
+    let mut pool = TestPool::initialized().await;
+    pool.deposit(5_000, [1; 32]).await.unwrap();
+
+    let relayer = Keypair::new();
+    let fund = system_instruction::transfer(&pool.admin.pubkey(), &relayer.pubkey(), LAMPORTS_PER_SOL);
+    let fee_schedule = FeeSchedule { fee_bps: 200, min_fee: 0 };
+    let add = ix::add_relayer(pool.admin.pubkey(), pool.admin.pubkey(), relayer.pubkey(), [0; 32], fee_schedule, None);
+    process(&mut pool.context, &[fund, add], &[&pool.admin]).await.unwrap();
+    let relayer_token_account = pool.create_associated_token_account(&relayer.pubkey()).await;
+
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    let mut params = pool.withdraw_params(9, 5_000, &recipient.pubkey(), recipient_token_account).await;
+    params.public_inputs[6] = 100;
+    params.public_inputs[7] = public_input(relayer.pubkey().as_ref());
+    prove_withdraw(&mut params);
+    params.relayer_fee = 100;
+    params.relayer_fee_token_account = Some(relayer_token_account);
+    pool.init_nullifier_shards(&params).await.unwrap();
+
+    // Another submitter can't take over the relayer's proof, even for no fee
+    let mut taken = params.clone();
+    taken.relayer_fee = 0;
+    taken.relayer_fee_token_account = None;
+    let err = pool.withdraw(&taken).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::RelayerMismatch.into()));
+
+    // Nor can the relayer charge more than the proof allows
+    let mut raised = params.clone();
+    raised.relayer_fee = 200;
+    let withdraw = ix::withdraw(relayer.pubkey(), &raised);
+    let err = process(&mut pool.context, &[withdraw], &[&relayer]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::RelayerMismatch.into()));
+
+    let withdraw = ix::withdraw(relayer.pubkey(), &params);
+    process(&mut pool.context, &[withdraw], &[&relayer]).await.unwrap();
+    assert_eq!(pool.token_balance(recipient_token_account).await, 4_900);
+    assert_eq!(pool.token_balance(relayer_token_account).await, 100);
+}
+
+async fn check_nullifiers(pool: &mut TestPool, instruction: Instruction) -> Result<Vec<bool>, Option<u32>> {
+    let payer = &pool.context.payer;
+    let transaction = Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[payer], pool.context.last_blockhash);
+    let simulation = pool.context.banks_client.simulate_transaction(transaction).await.unwrap();
+    match simulation.result.unwrap() {
+        Ok(()) => {
+            let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+            Ok(Vec::<bool>::deserialize(&mut return_data.data.as_slice()).unwrap())
+        }
+        Err(TransactionError::InstructionError(_, InstructionError::Custom(code))) => Err(Some(code)),
+        Err(_) => Err(None),
+    }
+}
+
+#[tokio::test]
+async fn check_nullifiers_reports_spent_ones_in_order() {
+    let mut pool = TestPool::initialized().await;
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    let params = pool.withdraw_params(7, 1_000, &recipient.pubkey(), recipient_token_account).await;
+    pool.withdraw(&params).await.unwrap();
+
+    // 7 is spent; 7 + 2^16 shares its shard but isn't, nor is a hash sharing 7's public input
+    // but not the rest of its bytes; 8's shard was never opened
+    let mut same_input = nullifier_hash_for(7);
+    same_input[31] ^= 1;
+    let nullifiers = [8, 7, 7 + (1 << 16), 7].map(nullifier_hash_for);
+    let spent = check_nullifiers(&mut pool, ix::check_nullifiers(&nullifiers)).await.unwrap();
+    assert_eq!(spent, vec![false, true, false, true]);
+    let spent = check_nullifiers(&mut pool, ix::check_nullifiers(&[same_input])).await.unwrap();
+    assert_eq!(spent, vec![false]);
+
+    // Each nullifier needs its own shard account, which must be the right one
+    let mut missing = ix::check_nullifiers(&nullifiers);
+    missing.accounts.pop();
+    let err = check_nullifiers(&mut pool, missing).await.unwrap_err();
+    assert_eq!(err, Some(PrivaxError::NullifierShardCountMismatch.into()));
+    let mut swapped = ix::check_nullifiers(&[7, 8].map(nullifier_hash_for));
+    swapped.accounts.swap(2, 3);
+    let err = check_nullifiers(&mut pool, swapped).await.unwrap_err();
+    assert_eq!(err, Some(PrivaxError::NullifierShardMismatch.into()));
+}
+
+#[tokio::test]
+async fn proofs_are_checked_before_paying_out() {
+    let mut pool = TestPool::initialized().await;
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let recipient = Keypair::new();
+    let recipient_token_account = pool.create_associated_token_account(&recipient.pubkey()).await;
+    let valid = pool.withdraw_params(1, 1_000, &recipient.pubkey(), recipient_token_account).await;
+
+    // A coordinate outside the BN254 base field
+    let mut params = valid.clone();
+    params.a_proof = [0xff; 64];
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::InvalidProofEncoding.into()));
+
+    // PLONK bytes alongside a Groth16 key
+    let mut params = valid.clone();
+    params.plonk_proof = vec![1; 32];
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::InvalidProofEncoding.into()));
+
+    // A proof bound to another circuit version
+    let mut params = valid.clone();
+    params.public_inputs[4] = privax_protocol::external_nullifier(&pool.mint, 0, CIRCUIT_VERSION + 1);
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::ExternalNullifierMismatch.into()));
+
+    // Public inputs naming another recipient or amount
+    let mut params = valid.clone();
+    params.recipient = Keypair::new().pubkey();
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::RecipientMismatch.into()));
+    let mut params = valid.clone();
+    params.amount += 1;
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::AmountMismatch.into()));
+
+    // A proof for other public inputs
+    let mut params = valid.clone();
+    params.public_inputs[1] = 2;
+    params.nullifier_hash = nullifier_hash_for(2);
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::InvalidZkProof.into()));
+    let mut params = valid.clone();
+    params.c_proof = params.a_proof;
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::InvalidZkProof.into()));
+
+    // A non-zero unused public input
+    let mut params = valid.clone();
+    params.public_inputs[9] = 1;
+    let err = pool.withdraw(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::InvalidPublicInputCount.into()));
+
+    // A proof made too long ago
+    let slot = pool.slot().await;
+    pool.context.warp_to_slot(slot + MAX_PROOF_AGE_SLOTS + 1).unwrap();
+    let err = pool.withdraw(&valid).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::ProofExpired.into()));
+
+    assert_eq!(pool.token_balance(recipient_token_account).await, 0);
+    let fresh = pool.withdraw_params(1, 1_000, &recipient.pubkey(), recipient_token_account).await;
+    pool.withdraw(&fresh).await.unwrap();
+    assert_eq!(pool.token_balance(recipient_token_account).await, 1_000);
+}
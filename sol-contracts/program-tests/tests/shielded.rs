@@ -0,0 +1,49 @@
+// Note splits and merges: the proof binds every output commitment in full, not only the bytes
+// its u64 input carries.
+use privax_program_tests::{error_code, expected_root, process, TestPool, CIRCUIT_VERSION};
+use privax_protocol::{PrivaxError, NOTE_TRANSFER_PUBLIC_INPUTS};
+
+const SPLIT_CIRCUIT: u16 = CIRCUIT_VERSION + 1;
+const MERGE_CIRCUIT: u16 = CIRCUIT_VERSION + 2;
+
+async fn pool_with_key(circuit_version: u16) -> TestPool {
+    let mut pool = TestPool::initialized().await;
+    let register = pool.register_verifying_key_ix(circuit_version, NOTE_TRANSFER_PUBLIC_INPUTS);
+    process(&mut pool.context, &[register], &[&pool.admin]).await.unwrap();
+    pool
+}
+
+#[tokio::test]
+async fn split_notes_bind_their_whole_outputs() {
+    let mut pool = pool_with_key(SPLIT_CIRCUIT).await;
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let params = pool.split_note_params(SPLIT_CIRCUIT, 7, vec![[2; 32], [3; 32]]).await;
+
+    for (output, byte) in [(0, 8), (1, 31)] {
+        let mut tampered = params.clone();
+        tampered.output_commitments[output][byte] ^= 1;
+        let err = pool.split_note(&tampered).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(PrivaxError::InvalidZkProof.into()));
+    }
+
+    pool.split_note(&params).await.unwrap();
+    assert_eq!(pool.merkle_tree().await.root(), expected_root(&[[1; 32], [2; 32], [3; 32]]));
+}
+
+#[tokio::test]
+async fn merged_notes_bind_their_whole_output() {
+    let mut pool = pool_with_key(MERGE_CIRCUIT).await;
+    pool.deposit(4_000, [1; 32]).await.unwrap();
+    pool.deposit(6_000, [2; 32]).await.unwrap();
+    let params = pool.merge_notes_params(MERGE_CIRCUIT, &[7, 8], [3; 32]).await;
+
+    for byte in [8, 31] {
+        let mut tampered = params.clone();
+        tampered.output_commitment[byte] ^= 1;
+        let err = pool.merge_notes(&tampered).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(PrivaxError::InvalidZkProof.into()));
+    }
+
+    pool.merge_notes(&params).await.unwrap();
+    assert_eq!(pool.merkle_tree().await.root(), expected_root(&[[1; 32], [2; 32], [3; 32]]));
+}
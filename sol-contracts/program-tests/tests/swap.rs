@@ -0,0 +1,128 @@
+// Shielded swaps out of the pool and into the stand-in target pool.
+use anchor_lang::AccountDeserialize;
+use privax_client::instructions as ix;
+use privax_program_tests::swap::{target_commitment_shard, target_vault, TARGET_POOL_ID};
+use privax_program_tests::{error_code, process, SwapVenue, TestPool, CIRCUIT_VERSION};
+use privax_protocol::{AdminAction, CommitmentShard, PrivaxError, SWAP_PUBLIC_INPUTS};
+use solana_sdk::signature::Signer;
+
+const SWAP_CIRCUIT: u16 = CIRCUIT_VERSION + 1;
+
+async fn pool_with_venue() -> (TestPool, SwapVenue) {
+    let mut pool = TestPool::initialized().await;
+    let register = pool.register_verifying_key_ix(SWAP_CIRCUIT, SWAP_PUBLIC_INPUTS);
+    process(&mut pool.context, &[register], &[&pool.admin]).await.unwrap();
+    let venue = pool.open_swap_venue(1_000_000).await;
+    (pool, venue)
+}
+
+#[tokio::test]
+async fn swaps_deposit_their_output_note_in_the_target_pool() {
+    let (mut pool, venue) = pool_with_venue().await;
+    let first = [2; 32];
+    let mut second = first;
+    second[31] = 3; // Same shard as the first
+
+    // The first swap opens the output's shard in the target pool, the second appends to it
+    for (nullifier, output_commitment) in [(7, first), (8, second)] {
+        pool.deposit(10_000, [nullifier as u8; 32]).await.unwrap();
+        let params = pool.shielded_swap_params(SWAP_CIRCUIT, &venue, nullifier, 10_000, 4_000, output_commitment, 4_500).await;
+        pool.shielded_swap(&params).await.unwrap();
+    }
+
+    assert_eq!(pool.token_balance(ix::vault_address()).await, 0);
+    assert_eq!(pool.token_balance(venue.reserve_in).await, 20_000);
+    assert_eq!(pool.token_balance(target_vault()).await, 8_000);
+    assert_eq!(pool.token_balance(venue.submitter_output_account).await, 1_000);
+    assert_eq!(pool.token_balance(ix::swap_output_address(&venue.target_mint)).await, 0);
+
+    let shard = pool.context.banks_client.get_account(target_commitment_shard([2, 2])).await.unwrap().unwrap();
+    assert_eq!(CommitmentShard::try_deserialize(&mut shard.data.as_slice()).unwrap().count, 2);
+    assert_eq!(&shard.data[CommitmentShard::SPACE..], [first, second].concat().as_slice());
+}
+
+#[tokio::test]
+async fn swaps_bind_their_whole_output_commitment() {
+    let (mut pool, venue) = pool_with_venue().await;
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let params = pool.shielded_swap_params(SWAP_CIRCUIT, &venue, 7, 10_000, 4_000, [2; 32], 4_000).await;
+
+    for byte in [8, 31] {
+        let mut tampered = params.clone();
+        tampered.output_commitment[byte] ^= 1;
+        let err = pool.shielded_swap(&tampered).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(PrivaxError::InvalidZkProof.into()));
+    }
+
+    pool.shielded_swap(&params).await.unwrap();
+    assert_eq!(pool.token_balance(target_vault()).await, 4_000);
+}
+
+#[tokio::test]
+async fn swaps_returning_less_than_the_output_note_fail() {
+    let (mut pool, venue) = pool_with_venue().await;
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let short = pool.shielded_swap_params(SWAP_CIRCUIT, &venue, 7, 10_000, 4_000, [2; 32], 3_999).await;
+    let err = pool.shielded_swap(&short).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::SwapSlippage.into()));
+    assert_eq!(pool.token_balance(ix::vault_address()).await, 10_000);
+    assert_eq!(pool.token_balance(target_vault()).await, 0);
+
+    // Nothing was spent, so the note can still be swapped at a better price
+    let params = pool.shielded_swap_params(SWAP_CIRCUIT, &venue, 7, 10_000, 4_000, [2; 32], 4_000).await;
+    pool.shielded_swap(&params).await.unwrap();
+    assert_eq!(pool.token_balance(target_vault()).await, 4_000);
+}
+
+#[tokio::test]
+async fn swaps_only_go_into_approved_pools() {
+    let (mut pool, venue) = pool_with_venue().await;
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let admin = pool.admin.pubkey();
+    process(&mut pool.context, &[ix::remove_swap_target(admin, TARGET_POOL_ID)], &[&pool.admin]).await.unwrap();
+    assert!(pool.program_state().await.swap_targets.is_empty());
+
+    let params = pool.shielded_swap_params(SWAP_CIRCUIT, &venue, 7, 10_000, 4_000, [2; 32], 4_000).await;
+    let err = pool.shielded_swap(&params).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::InvalidSwapTarget.into()));
+    assert_eq!(pool.token_balance(ix::vault_address()).await, 10_000);
+
+    // This pool can't be a target, and an approved one can't be added twice
+    let err = process(&mut pool.context, &[ix::add_swap_target(admin, privax_protocol::ID)], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::InvalidSwapTarget.into()));
+    process(&mut pool.context, &[ix::add_swap_target(admin, TARGET_POOL_ID)], &[&pool.admin]).await.unwrap();
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    let err = process(&mut pool.context, &[ix::add_swap_target(admin, TARGET_POOL_ID)], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::SwapTargetAlreadyApproved.into()));
+
+    // The refused swap, in a later transaction
+    pool.shielded_swap(&params).await.unwrap();
+    assert_eq!(pool.token_balance(target_vault()).await, 4_000);
+}
+
+#[tokio::test]
+async fn swap_targets_are_added_through_the_queue_under_a_timelock() {
+    let (mut pool, venue) = pool_with_venue().await;
+    let admin = pool.admin.pubkey();
+    let timelock = 100;
+    let action = AdminAction::AddSwapTarget { target_program: TARGET_POOL_ID };
+    let instructions = [
+        ix::remove_swap_target(admin, TARGET_POOL_ID),
+        ix::set_admin_timelock(admin, timelock),
+        ix::queue_admin_action(admin, admin, 0, action),
+    ];
+    process(&mut pool.context, &instructions, &[&pool.admin]).await.unwrap();
+    let queued_at = pool.slot().await;
+    let err = process(&mut pool.context, &[ix::add_swap_target(admin, TARGET_POOL_ID)], &[&pool.admin]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(PrivaxError::AdminTimelockActive.into()));
+
+    pool.context.warp_to_slot(queued_at + timelock).unwrap();
+    pool.context.get_new_latest_blockhash().await.unwrap();
+    process(&mut pool.context, &[ix::execute_admin_action(admin, admin, 0, &action)], &[&pool.admin]).await.unwrap();
+    assert_eq!(pool.program_state().await.swap_targets, [TARGET_POOL_ID]);
+
+    pool.deposit(10_000, [1; 32]).await.unwrap();
+    let params = pool.shielded_swap_params(SWAP_CIRCUIT, &venue, 7, 10_000, 4_000, [2; 32], 4_000).await;
+    pool.shielded_swap(&params).await.unwrap();
+    assert_eq!(pool.token_balance(target_vault()).await, 4_000);
+}
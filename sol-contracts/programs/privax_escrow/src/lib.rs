@@ -0,0 +1,360 @@
+#![allow(clippy::result_large_err)]
+
+// An example of a program built on Privax, kept in the workspace as living documentation of
+// the CPI interface and run by program-tests: a private escrow. The buyer opens an escrow for
+// a seller and funds it by withdrawing one of their notes straight into it. Releasing it
+// deposits the funds back into the pool as a note for the seller; the seller can instead
+// refund it, as a note for the buyer. No wallet of either party ever holds the funds, so the
+// pool's anonymity set covers both ends of the trade.
+//
+// Both CPIs sign with the escrow's authority PDA: it is the withdrawal's recipient and
+// submitter, and the deposit's depositor. As the pool's `user` it also pays for the shard
+// entries a withdrawal and a deposit add, so the buyer funds it with AUTHORITY_FUNDING when
+// opening the escrow and gets what's left back when it closes.
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount};
+use privax_protocol::cpi::accounts::{DepositTokens, WithdrawTokens};
+use privax_protocol::program::PrivaxProtocol;
+use privax_protocol::MAX_PUBLIC_INPUTS;
+
+declare_id!("51WY6bahWhxdanybJFjk8V5neNJ8A3S1Lgpn6u3oxnss");
+
+pub const AUTHORITY_FUNDING: u64 = 10_000_000; // 0.01 SOL
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("A commitment must be a nonzero canonical BN254 scalar.")]
+    InvalidCommitment,
+    #[msg("The escrow is already funded.")]
+    AlreadyFunded,
+    #[msg("The escrow isn't funded yet.")]
+    NotFunded,
+    #[msg("The withdrawal paid less than the escrowed amount.")]
+    Underfunded,
+    #[msg("Only the buyer can release the escrow.")]
+    ReleaseNotAllowed,
+    #[msg("Only the seller, or the buyer before it's funded, can refund the escrow.")]
+    RefundNotAllowed,
+}
+
+#[program]
+pub mod privax_escrow {
+    use super::*;
+
+    // The commitments are the notes each outcome pays: the seller's and the buyer's own, made
+    // off-chain like any deposit's.
+    pub fn open_escrow(
+        ctx: Context<OpenEscrow>,
+        escrow_id: u64,
+        seller: Pubkey,
+        amount: u64,
+        seller_commitment: [u8; 32],
+        refund_commitment: [u8; 32],
+    ) -> Result<()> {
+        // The pool would reject these at settlement, leaving the funds stuck in the escrow
+        for commitment in [&seller_commitment, &refund_commitment] {
+            let canonical = privax_protocol::proof_format::is_canonical_scalar(commitment);
+            require!(commitment != &[0u8; 32] && canonical, EscrowError::InvalidCommitment);
+        }
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.buyer = ctx.accounts.buyer.key();
+        escrow.seller = seller;
+        escrow.mint = ctx.accounts.mint.key();
+        escrow.id = escrow_id;
+        escrow.amount = amount;
+        escrow.seller_commitment = seller_commitment;
+        escrow.refund_commitment = refund_commitment;
+        escrow.funded = false;
+        escrow.bump = *ctx.bumps.get("escrow").unwrap();
+        escrow.authority_bump = *ctx.bumps.get("authority").unwrap();
+
+        let cpi_accounts = system_program::Transfer {
+            from: ctx.accounts.buyer.to_account_info(),
+            to: ctx.accounts.authority.to_account_info(),
+        };
+        system_program::transfer(CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts), AUTHORITY_FUNDING)
+    }
+
+    // Funds the escrow with a Privax withdrawal to its authority. The proof is made as for any
+    // withdrawal, with the authority as recipient; the arguments are withdraw's, minus those
+    // this program fixes: no relayer (the relayer and relayerFee inputs are 0), and the authority
+    // as recipient. amount_to_withdraw may exceed the escrowed amount to cover the pool's fee;
+    // any surplus is paid out with the rest.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fund_from_note<'info>(
+        ctx: Context<'_, '_, '_, 'info, FundFromNote<'info>>,
+        a_proof: [u8; 64],
+        b_proof: [u8; 128],
+        c_proof: [u8; 64],
+        public_inputs: [u64; MAX_PUBLIC_INPUTS],
+        merkle_root: [u8; 32],
+        nullifier_hash: [u8; 32],
+        extra_nullifier_hashes: Vec<[u8; 32]>,
+        amount_to_withdraw: u64,
+        circuit_version: u16,
+        plonk_proof: Vec<u8>,
+        change_commitment: Option<[u8; 32]>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.escrow.funded, EscrowError::AlreadyFunded);
+        let escrow_key = ctx.accounts.escrow.key();
+        let seeds = &[b"escrow_authority".as_ref(), escrow_key.as_ref(), &[ctx.accounts.escrow.authority_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let accounts = &ctx.accounts;
+        let cpi_accounts = WithdrawTokens {
+            program_state: accounts.program_state.to_account_info(),
+            verifying_key: accounts.verifying_key.to_account_info(),
+            fee_config: accounts.fee_config.to_account_info(),
+            nullifier_shard: accounts.nullifier_shard.to_account_info(),
+            nullifier_filter: accounts.nullifier_filter.to_account_info(),
+            merkle_tree: accounts.merkle_tree.to_account_info(),
+            root_archive: accounts.root_archive.as_ref().map(|account| account.to_account_info()),
+            user: accounts.authority.to_account_info(),
+            // The pool's vault is its own authority
+            program_token_vault: accounts.pool_vault.to_account_info(),
+            program_token_vault_authority: accounts.pool_vault.to_account_info(),
+            recipient_token_account: accounts.vault.to_account_info(),
+            relayer_info: None,
+            relayer_fee_token_account: None,
+            fee_swap_program: None,
+            token_program: accounts.token_program.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            usd_limits: accounts.usd_limits.as_ref().map(|account| account.to_account_info()),
+            price_feed: accounts.price_feed.as_ref().map(|account| account.to_account_info()),
+        };
+        // Shards of a multi-note spend's extra nullifiers, as withdraw takes them
+        let cpi_ctx = CpiContext::new_with_signer(accounts.privax_program.to_account_info(), cpi_accounts, signer_seeds)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        privax_protocol::cpi::withdraw(
+            cpi_ctx,
+            a_proof,
+            b_proof,
+            c_proof,
+            public_inputs,
+            merkle_root,
+            nullifier_hash,
+            extra_nullifier_hashes,
+            accounts.authority.key(),
+            amount_to_withdraw,
+            0,          // relayer_fee
+            Vec::new(), // fee_swap_data
+            0,          // min_fee_out
+            circuit_version,
+            plonk_proof,
+            change_commitment,
+        )?;
+
+        ctx.accounts.vault.reload()?;
+        require!(ctx.accounts.vault.amount >= ctx.accounts.escrow.amount, EscrowError::Underfunded);
+        ctx.accounts.escrow.funded = true;
+        Ok(())
+    }
+
+    // Pays the escrow out as the seller's note.
+    pub fn release(ctx: Context<Settle>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        require!(ctx.accounts.signer.key() == escrow.buyer, EscrowError::ReleaseNotAllowed);
+        require!(escrow.funded, EscrowError::NotFunded);
+        let commitment = escrow.seller_commitment;
+        settle(ctx, commitment)
+    }
+
+    // Pays the escrow back as the buyer's note, or just closes it if it was never funded.
+    pub fn refund(ctx: Context<Settle>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        let signer = ctx.accounts.signer.key();
+        require!(signer == escrow.seller || (signer == escrow.buyer && !escrow.funded), EscrowError::RefundNotAllowed);
+        let commitment = escrow.refund_commitment;
+        settle(ctx, commitment)
+    }
+}
+
+// Deposits whatever the vault holds as a note for `commitment`, then closes the vault and
+// returns the authority's lamports to the buyer; the escrow account itself closes on exit.
+fn settle(ctx: Context<Settle>, commitment: [u8; 32]) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let escrow_key = accounts.escrow.key();
+    let seeds = &[b"escrow_authority".as_ref(), escrow_key.as_ref(), &[accounts.escrow.authority_bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    let amount = accounts.vault.amount;
+    if amount > 0 {
+        let cpi_accounts = DepositTokens {
+            program_state: accounts.program_state.to_account_info(),
+            fee_config: accounts.fee_config.to_account_info(),
+            merkle_tree: accounts.merkle_tree.to_account_info(),
+            commitment_shard: accounts.commitment_shard.to_account_info(),
+            referrer_account: None,
+            deposit_receipt: None, // A receipt would name the escrow's authority as depositor on-chain for good
+            user: accounts.authority.to_account_info(),
+            user_token_account: accounts.vault.to_account_info(),
+            token_mint: accounts.mint.to_account_info(),
+            program_token_vault: accounts.pool_vault.to_account_info(),
+            program_token_vault_authority: accounts.pool_vault.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            rent: accounts.rent.to_account_info(),
+            usd_limits: accounts.usd_limits.as_ref().map(|account| account.to_account_info()),
+            price_feed: accounts.price_feed.as_ref().map(|account| account.to_account_info()),
+            compliance_attestation: None, // Escrows don't attest, so they can't settle into pools with a compliance key
+            kyc_credential: None, // Nor do they hold KYC credentials, for institutional pools
+            deposit_volume: None, // Nor into pools with a daily deposit cap, which need the depositor's volume account
+        };
+        let cpi_ctx = CpiContext::new_with_signer(accounts.privax_program.to_account_info(), cpi_accounts, signer_seeds);
+        privax_protocol::cpi::deposit(cpi_ctx, amount, commitment)?;
+    }
+
+    let cpi_accounts = CloseAccount {
+        account: accounts.vault.to_account_info(),
+        destination: accounts.buyer.to_account_info(),
+        authority: accounts.authority.to_account_info(),
+    };
+    token::close_account(CpiContext::new_with_signer(accounts.token_program.to_account_info(), cpi_accounts, signer_seeds))?;
+    let cpi_accounts = system_program::Transfer {
+        from: accounts.authority.to_account_info(),
+        to: accounts.buyer.to_account_info(),
+    };
+    let cpi_program = accounts.system_program.to_account_info();
+    system_program::transfer(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), accounts.authority.lamports())
+}
+
+#[account]
+pub struct Escrow {
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub mint: Pubkey, // The pool's
+    pub id: u64,      // Tells apart a buyer's escrows
+    pub amount: u64,
+    pub seller_commitment: [u8; 32],
+    pub refund_commitment: [u8; 32],
+    pub funded: bool,
+    pub bump: u8,
+    pub authority_bump: u8,
+}
+
+impl Escrow {
+    // 3 Pubkeys + 2 u64s + 2 commitments + bool + 2 bumps
+    pub const SPACE: usize = 8 + 3 * 32 + 2 * 8 + 2 * 32 + 1 + 2;
+}
+
+pub fn escrow_address(buyer: &Pubkey, escrow_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"escrow", buyer.as_ref(), &escrow_id.to_le_bytes()], &ID).0
+}
+
+pub fn authority_address(escrow: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"escrow_authority", escrow.as_ref()], &ID).0
+}
+
+#[derive(Accounts)]
+#[instruction(escrow_id: u64)]
+pub struct OpenEscrow<'info> {
+    #[account(
+        init,
+        payer = buyer,
+        space = Escrow::SPACE,
+        seeds = [b"escrow", buyer.key().as_ref(), &escrow_id.to_le_bytes()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    /// CHECK: The escrow's authority PDA, a plain system account holding AUTHORITY_FUNDING.
+    #[account(mut, seeds = [b"escrow_authority", escrow.key().as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+    // The authority's associated token account, the only account a withdrawal to it can pay
+    #[account(init, payer = buyer, associated_token::mint = mint, associated_token::authority = authority)]
+    pub vault: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>, // The pool's; its deposit and withdraw check it
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// The escrow's accounts, then withdraw's own.
+#[derive(Accounts)]
+pub struct FundFromNote<'info> {
+    #[account(mut, seeds = [b"escrow", escrow.buyer.as_ref(), &escrow.id.to_le_bytes()], bump = escrow.bump)]
+    pub escrow: Account<'info, Escrow>,
+    /// CHECK: The escrow's authority PDA; signs the withdrawal.
+    #[account(mut, seeds = [b"escrow_authority", escrow.key().as_ref()], bump = escrow.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut, associated_token::mint = escrow.mint, associated_token::authority = authority)]
+    pub vault: Account<'info, TokenAccount>,
+    pub privax_program: Program<'info, PrivaxProtocol>,
+    /// CHECK: Checked by the pool's withdraw, like the pool accounts below.
+    #[account(mut)]
+    pub program_state: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's withdraw.
+    pub verifying_key: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's withdraw.
+    #[account(mut)]
+    pub fee_config: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's withdraw.
+    #[account(mut)]
+    pub nullifier_shard: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's withdraw.
+    #[account(mut)]
+    pub nullifier_filter: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's withdraw, against the proof's root; takes any change note.
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's withdraw; the archive page holding an archived proof root.
+    pub root_archive: Option<UncheckedAccount<'info>>,
+    /// CHECK: Checked by the pool's withdraw.
+    #[account(mut)]
+    pub pool_vault: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's withdraw; required while the pool has USD limits.
+    pub usd_limits: Option<UncheckedAccount<'info>>,
+    /// CHECK: Checked by the pool's withdraw.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// The escrow's accounts, then deposit's own.
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    #[account(
+        mut,
+        close = buyer,
+        has_one = buyer,
+        seeds = [b"escrow", escrow.buyer.as_ref(), &escrow.id.to_le_bytes()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    pub signer: Signer<'info>, // The buyer to release; the seller, or the unfunded escrow's buyer, to refund
+    #[account(mut)] // Gets back the escrow's rent and the authority's lamports
+    pub buyer: SystemAccount<'info>,
+    /// CHECK: The escrow's authority PDA; signs the deposit.
+    #[account(mut, seeds = [b"escrow_authority", escrow.key().as_ref()], bump = escrow.authority_bump)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut, associated_token::mint = escrow.mint, associated_token::authority = authority)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(address = escrow.mint)]
+    pub mint: Account<'info, Mint>,
+    pub privax_program: Program<'info, PrivaxProtocol>,
+    /// CHECK: Checked by the pool's deposit, like the pool accounts below.
+    #[account(mut)]
+    pub program_state: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's deposit.
+    #[account(mut)]
+    pub fee_config: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's deposit.
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's deposit; the shard for the paid commitment's prefix.
+    #[account(mut)]
+    pub commitment_shard: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's deposit.
+    #[account(mut)]
+    pub pool_vault: UncheckedAccount<'info>,
+    /// CHECK: Checked by the pool's deposit; required while the pool has USD limits.
+    pub usd_limits: Option<UncheckedAccount<'info>>,
+    /// CHECK: Checked by the pool's deposit.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
@@ -0,0 +1,81 @@
+#![allow(clippy::result_large_err)]
+
+// A stand-in for the proof verifier a pool names in verifier_program_id, for localnet, devnet
+// and CI: deploy it, point the pool at it, and end-to-end tests and demos spend notes with the
+// fixed test proof below instead of proving. It accepts nothing else, but everyone knows the
+// test proof, so never point a pool holding real funds at it.
+//
+// `verify_proof` is the verifier interface: the circuit's VerifyingKey account, the proof in
+// withdraw's a/b/c (or PLONK) encoding and the public inputs, succeeding only for a valid
+// proof. privax_protocol doesn't call it: the pool checks Groth16 proofs itself with the
+// alt_bn128 syscalls and rejects the test proof, so spending in tests against the pool takes a
+// `devnet` build and its all-zero proof instead.
+use anchor_lang::prelude::*;
+
+declare_id!("EkRE7RWwAXyZWZFGfrEPWmuSyaERNAzP25B3ea3UAQdU");
+
+// In the test proof's first coordinate, after a zero byte: the coordinate stays below the
+// BN254 base field modulus, so the pool's encoding checks pass, but (x, 0) is not on the curve
+// and no real proof ever carries it.
+pub const TEST_PROOF_MAGIC: [u8; 31] = *b"privax mock verifier test proof";
+
+// The proof system values of privax_protocol::ProofSystem
+pub const GROTH16: u8 = 0;
+pub const PLONK: u8 = 1;
+
+#[error_code]
+pub enum MockVerifierError {
+    #[msg("Only the mock verifier's test proof is accepted.")]
+    NotTheTestProof,
+    #[msg("Unknown proof system.")]
+    UnknownProofSystem,
+}
+
+#[program]
+pub mod privax_mock_verifier {
+    use super::*;
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_proof(
+        _ctx: Context<VerifyProof>,
+        proof_system: u8,
+        a_proof: [u8; 64],
+        b_proof: [u8; 128],
+        c_proof: [u8; 64],
+        plonk_proof: Vec<u8>,
+        public_inputs: Vec<u64>,
+    ) -> Result<()> {
+        require!(proof_system == GROTH16 || proof_system == PLONK, MockVerifierError::UnknownProofSystem);
+        require!(is_test_proof(proof_system, &a_proof, &b_proof, &c_proof, &plonk_proof), MockVerifierError::NotTheTestProof);
+        msg!("Accepted the test proof for {} public inputs", public_inputs.len());
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct VerifyProof<'info> {
+    /// CHECK: The circuit's privax_protocol VerifyingKey; a real verifier reads the key from it, the mock ignores it.
+    pub verifying_key: UncheckedAccount<'info>,
+}
+
+// withdraw's a_proof, b_proof and c_proof for the Groth16 test proof.
+pub fn test_proof() -> ([u8; 64], [u8; 128], [u8; 64]) {
+    let mut a_proof = [0; 64];
+    a_proof[1..32].copy_from_slice(&TEST_PROOF_MAGIC);
+    (a_proof, [0; 128], [0; 64])
+}
+
+// The PLONK test proof is the magic itself, with the Groth16 slots zero as the pool requires.
+pub fn is_test_proof(proof_system: u8, a_proof: &[u8; 64], b_proof: &[u8; 128], c_proof: &[u8; 64], plonk_proof: &[u8]) -> bool {
+    match proof_system {
+        GROTH16 => {
+            let (a, b, c) = test_proof();
+            plonk_proof.is_empty() && (a_proof, b_proof, c_proof) == (&a, &b, &c)
+        }
+        PLONK => {
+            let unused_zero = a_proof.iter().chain(b_proof.iter()).chain(c_proof.iter()).all(|&b| b == 0);
+            unused_zero && plonk_proof == TEST_PROOF_MAGIC
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,33 @@
+// The mock verifier must take its test proof and nothing else, whichever proof system it's for.
+use privax_mock_verifier::{is_test_proof, test_proof, GROTH16, PLONK, TEST_PROOF_MAGIC};
+
+#[test]
+fn accepts_the_groth16_test_proof() {
+    let (a, b, c) = test_proof();
+    assert!(is_test_proof(GROTH16, &a, &b, &c, &[]));
+    assert!(!is_test_proof(PLONK, &a, &b, &c, &[]));
+    assert!(!is_test_proof(GROTH16, &a, &b, &c, &TEST_PROOF_MAGIC));
+}
+
+#[test]
+fn rejects_anything_else() {
+    assert!(!is_test_proof(GROTH16, &[0; 64], &[0; 128], &[0; 64], &[]));
+    let (a, b, c) = test_proof();
+    for byte in 0..64 {
+        let mut tampered = a;
+        tampered[byte] ^= 1;
+        assert!(!is_test_proof(GROTH16, &tampered, &b, &c, &[]));
+    }
+    let mut tampered = c;
+    tampered[63] = 1;
+    assert!(!is_test_proof(GROTH16, &a, &b, &tampered, &[]));
+    assert!(!is_test_proof(2, &a, &b, &c, &[]));
+}
+
+#[test]
+fn accepts_the_plonk_test_proof() {
+    assert!(is_test_proof(PLONK, &[0; 64], &[0; 128], &[0; 64], &TEST_PROOF_MAGIC));
+    assert!(!is_test_proof(PLONK, &[0; 64], &[0; 128], &[0; 64], &TEST_PROOF_MAGIC[1..]));
+    let (a, b, c) = test_proof();
+    assert!(!is_test_proof(PLONK, &a, &b, &c, &TEST_PROOF_MAGIC));
+}
@@ -0,0 +1,8 @@
+// Whole instructions against a pool: arbitrary arguments, account lists and flags.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: privax_fuzz::Input| {
+    privax_fuzz::run(&input);
+});
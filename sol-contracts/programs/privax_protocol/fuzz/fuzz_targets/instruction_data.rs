@@ -0,0 +1,10 @@
+// Arbitrary argument bytes for each instruction, and arbitrary raw instruction data.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (u8, &[u8])| {
+    let (selector, bytes) = input;
+    privax_fuzz::dispatch(&privax_fuzz::instruction_data(selector, bytes));
+    privax_fuzz::dispatch(bytes);
+});
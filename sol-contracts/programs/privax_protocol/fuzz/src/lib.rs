@@ -0,0 +1,509 @@
+// Harness behind the cargo-fuzz targets in fuzz_targets/.
+//
+// `instruction_data` hands arbitrary bytes to the program's dispatcher with no accounts, so
+// every instruction's argument deserialization sees them before account validation stops it.
+// `accounts` runs a whole instruction: the fuzzer picks the instruction, its argument bytes,
+// and which accounts fill which positions with what signer/writable flags (duplicates
+// included), against an initialized pool whose state it also varies within what the program
+// itself allows. Accounts go through the runtime's input serialization and
+// `entrypoint::deserialize`, so reallocs and closes behave as they do on-chain.
+//
+// Off-chain there's no runtime behind the syscalls: `FuzzStubs` serves the clock and rent
+// sysvars and accepts every CPI without running it, so token and system transfers report
+// success without moving anything. A crash is a panic or arithmetic overflow in the program
+// (cargo fuzz builds with overflow checks), or lamports appearing or vanishing.
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Once, OnceLock};
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::{self, ProgramResult, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::program_stubs::{self, SyscallStubs};
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{system_program, Discriminator};
+use anchor_spl::token::spl_token;
+use arbitrary::Arbitrary;
+use privax_protocol::instruction as ix;
+use privax_protocol::proof_format::{G1_SIZE, VK_FIXED_SIZE};
+use privax_protocol::{
+    AuditLog, FeeConfig, MerkleTree, NullifierFilter, NullifierShard, ProgramState, ProofSystem, RelayerInfo, VerifyingKey,
+};
+
+macro_rules! discriminators {
+    ($($name:ident),* $(,)?) => {
+        // Every instruction's discriminator, in declaration order.
+        pub const INSTRUCTIONS: &[[u8; 8]] = &[$(ix::$name::DISCRIMINATOR),*];
+    };
+}
+
+discriminators!(
+    Initialize, InitAuditLog, AddRelayer, RenewRelayer, RemoveRelayer, TransferOwnership, InitiateRenounceAdmin,
+    CancelRenounceAdmin, RenounceAdmin, UpdateFeeConfig, SetFeeAuthority, SetFeeBeneficiaries, DistributeFees,
+    SetUtilizationFeeCurve, SetReferralShare, RegisterReferrer, ClaimReferralFees, CloseDepositReceipt,
+    SetFeeSwapProgram, SetVerifierProgram, SetProofSystem, RegisterVerifyingKey, WriteVerifyingKeyChunk,
+    SetVerifyingKeyEnabled, FreezeParams, SetGuardian, Pause, Unpause, SetWithdrawalLimit, SetComplianceKey,
+    SetKycAttestor, IssueKycCredential, RevokeKycCredential, SetMaxDailyDeposit, InitDepositVolume, SetExclusionOperator,
+    PublishExclusionRoot, SetExclusionRequired, SetMaxPauseDuration, SetPauseOnShortfall, Reconcile, Deposit, AnnounceNote,
+    AttestDeposit,
+    RegisterCommitment, FundCommitment, ReclaimExpiredRegistration, DepositSubtree, Crank, InitNullifierShard,
+    InitCommitmentShard, CheckNullifiers, Withdraw,
+    WithdrawSplit, ShieldedSwap, SplitNote, MergeNotes, CreatePaymentSchedule, ExecuteScheduledPayment, CancelPaymentSchedule,
+    EnableNftMode, DepositNft, WithdrawNft, SetReceiptNftConfig, ClaimReceiptNft, SetUsdLimits,
+    SetTreeOperator, CheckpointRoot, ArchiveRoots, SetArchivedRootsAccepted,
+);
+
+pub const CIRCUIT_VERSION: u16 = 1;
+const SIGNERS: u8 = 4;
+const SIGNER_LAMPORTS: u64 = 10_000_000_000;
+
+// The instruction `selector` picks with `args` as its arguments.
+pub fn instruction_data(selector: u8, args: &[u8]) -> Vec<u8> {
+    let mut data = INSTRUCTIONS[usize::from(selector) % INSTRUCTIONS.len()].to_vec();
+    data.extend_from_slice(args);
+    data
+}
+
+// Dispatches `data` with no accounts; the result doesn't matter, only that it returns.
+pub fn dispatch(data: &[u8]) {
+    install_stubs();
+    let _ = privax_protocol::entry(&privax_protocol::ID, &[], data);
+}
+
+#[derive(Arbitrary, Debug)]
+pub struct Input {
+    pub selector: u8,
+    pub args: Vec<u8>,
+    pub accounts: Vec<AccountInput>,
+    pub pool: PoolInput,
+    pub slot: u64,
+}
+
+#[derive(Arbitrary, Debug)]
+pub enum AccountInput {
+    Account { key: Key, is_signer: bool, is_writable: bool },
+    Duplicate(u8), // Of the account at this position, if there is one
+}
+
+// Accounts the pool has, plus anything else.
+#[derive(Arbitrary, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    ProgramState,
+    FeeConfig,
+    MerkleTree,
+    NullifierFilter,
+    AuditLog,
+    Vault,
+    VerifyingKey(u16),
+    NullifierShard([u8; 2]),
+    RelayerInfo(u8),
+    Signer(u8),
+    TokenAccount(u8), // Pool-mint account of the signer with this index
+    Mint,
+    SystemProgram,
+    TokenProgram,
+    PrivaxProgram,
+    RentSysvar,
+    Other([u8; 32], Owner, u16), // Empty account of this many bytes
+}
+
+#[derive(Arbitrary, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Owner {
+    System,
+    Token,
+    Other([u8; 32]),
+}
+
+// Pool state reachable through the program's own instructions.
+#[derive(Arbitrary, Debug)]
+pub struct PoolInput {
+    pub paused: bool,
+    pub renounced: bool,
+    pub frozen_groups: u8,
+    pub relayers: u8,
+    pub protocol_fee_bps: u16,
+    pub max_protocol_fee: u64,
+    pub accrued_protocol_fees: u64,
+    pub outstanding_deposits: u64,
+    pub vault_balance: u64,
+    pub token_balances: [u64; SIGNERS as usize],
+    pub leaves: u8,
+    pub key_enabled: bool,
+}
+
+pub fn run(input: &Input) {
+    install_stubs();
+    SLOT.store(input.slot, Ordering::Relaxed);
+    let pool = Pool::new(&input.pool);
+
+    // As in a transaction, a key listed twice is one account, with the union of its flags.
+    let mut metas: Vec<Meta> = Vec::with_capacity(input.accounts.len());
+    for account in input.accounts.iter().take(usize::from(u8::MAX)) {
+        let meta = match account {
+            AccountInput::Account { key, is_signer, is_writable } => {
+                let account = pool.account(*key);
+                let is_signer = *is_signer || matches!(key, Key::Signer(_));
+                let first = metas.iter().position(|meta| matches!(meta, Meta::Account(seen, ..) if seen.key == account.key));
+                if let Some(Meta::Account(_, signer, writable)) = first.map(|index| &mut metas[index]) {
+                    *signer |= is_signer;
+                    *writable |= *is_writable;
+                    Meta::Duplicate(first.unwrap() as u8)
+                } else {
+                    Meta::Account(account, is_signer, *is_writable)
+                }
+            }
+            AccountInput::Duplicate(index) => match metas.get(usize::from(*index)) {
+                Some(Meta::Account(..)) => Meta::Duplicate(*index),
+                Some(Meta::Duplicate(original)) => Meta::Duplicate(*original),
+                None => continue,
+            },
+        };
+        metas.push(meta);
+    }
+
+    let mut input_buffer = serialize(&metas, &instruction_data(input.selector, &input.args));
+    // SAFETY: the buffer is laid out as the runtime serializes program input, and outlives
+    // the accounts borrowed from it.
+    let (program_id, accounts, data) = unsafe { entrypoint::deserialize(input_buffer.as_mut_ptr() as *mut u8) };
+    let lamports_before = total_lamports(&accounts);
+    let _ = privax_protocol::entry(program_id, &accounts, data);
+    assert_eq!(total_lamports(&accounts), lamports_before, "lamports were created or destroyed");
+}
+
+enum Meta {
+    Account(Account, bool, bool),
+    Duplicate(u8),
+}
+
+struct Account {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    executable: bool,
+}
+
+// Serializes accounts and instruction data as the runtime does for a program's entrypoint,
+// into u128s so the buffer is aligned like the VM's input region.
+fn serialize(metas: &[Meta], data: &[u8]) -> Vec<u128> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(metas.len() as u64).to_le_bytes());
+    for meta in metas {
+        match meta {
+            Meta::Duplicate(index) => {
+                bytes.push(*index);
+                bytes.extend_from_slice(&[0; 7]);
+            }
+            Meta::Account(account, is_signer, is_writable) => {
+                bytes.extend_from_slice(&[NON_DUP_MARKER, u8::from(*is_signer), u8::from(*is_writable), u8::from(account.executable)]);
+                bytes.extend_from_slice(&[0; 4]); // original data length, filled in on deserialize
+                bytes.extend_from_slice(account.key.as_ref());
+                bytes.extend_from_slice(account.owner.as_ref());
+                bytes.extend_from_slice(&account.lamports.to_le_bytes());
+                bytes.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+                bytes.extend_from_slice(&account.data);
+                bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+                bytes.resize(bytes.len().next_multiple_of(BPF_ALIGN_OF_U128), 0);
+                bytes.extend_from_slice(&0u64.to_le_bytes()); // rent epoch
+            }
+        }
+    }
+    bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(data);
+    bytes.extend_from_slice(privax_protocol::ID.as_ref());
+
+    let mut buffer = vec![0u128; bytes.len().div_ceil(16)];
+    bytemuck::cast_slice_mut::<u128, u8>(&mut buffer)[..bytes.len()].copy_from_slice(&bytes);
+    buffer
+}
+
+// Sum over distinct accounts; duplicates share their original's lamports.
+fn total_lamports(accounts: &[AccountInfo]) -> u128 {
+    let mut seen: Vec<&Pubkey> = Vec::new();
+    let mut total = 0u128;
+    for account in accounts {
+        if !seen.contains(&account.key) {
+            seen.push(account.key);
+            total += u128::from(account.lamports());
+        }
+    }
+    total
+}
+
+struct Addresses {
+    program_state: (Pubkey, u8),
+    fee_config: (Pubkey, u8),
+    merkle_tree: (Pubkey, u8),
+    nullifier_filter: (Pubkey, u8),
+    audit_log: (Pubkey, u8),
+    vault: (Pubkey, u8),
+    verifying_key: (Pubkey, u8),
+    mint: Pubkey,
+}
+
+fn pda(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &privax_protocol::ID)
+}
+
+fn addresses() -> &'static Addresses {
+    static ADDRESSES: OnceLock<Addresses> = OnceLock::new();
+    ADDRESSES.get_or_init(|| {
+        let program_state = pda(&[b"program_state"]);
+        let state = program_state.0;
+        Addresses {
+            program_state,
+            fee_config: pda(&[b"fee_config", state.as_ref()]),
+            merkle_tree: pda(&[b"merkle_tree", state.as_ref()]),
+            nullifier_filter: pda(&[b"nullifier_filter", state.as_ref()]),
+            audit_log: pda(&[b"audit_log", state.as_ref()]),
+            vault: pda(&[b"program_token_vault", state.as_ref()]),
+            verifying_key: pda(&[b"verifying_key", state.as_ref(), &CIRCUIT_VERSION.to_le_bytes()]),
+            mint: Pubkey::new_from_array([0x6d; 32]),
+        }
+    })
+}
+
+fn signer(index: u8) -> Pubkey {
+    let mut key = [0x5a; 32];
+    key[0] = index % SIGNERS;
+    Pubkey::new_from_array(key)
+}
+
+fn token_account_address(index: u8) -> Pubkey {
+    let mut key = [0x7a; 32];
+    key[0] = index % SIGNERS;
+    Pubkey::new_from_array(key)
+}
+
+// An initialized pool: signer 0 is the admin and fee authority, signer 1 the guardian, and
+// the first `relayers` signers are whitelisted.
+struct Pool<'a> {
+    input: &'a PoolInput,
+}
+
+impl<'a> Pool<'a> {
+    fn new(input: &'a PoolInput) -> Self {
+        Self { input }
+    }
+
+    fn account(&self, key: Key) -> Account {
+        let addresses = addresses();
+        let rent = Rent::default();
+        let program_account = |key: Pubkey, data: Vec<u8>| Account {
+            key,
+            owner: privax_protocol::ID,
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            executable: false,
+        };
+        match key {
+            Key::ProgramState => program_account(addresses.program_state.0, self.program_state()),
+            Key::FeeConfig => program_account(addresses.fee_config.0, self.fee_config()),
+            Key::MerkleTree => program_account(addresses.merkle_tree.0, self.merkle_tree()),
+            Key::NullifierFilter => {
+                let mut filter: NullifierFilter = bytemuck::Zeroable::zeroed();
+                filter.bump = addresses.nullifier_filter.1;
+                program_account(addresses.nullifier_filter.0, zero_copy_data(&filter))
+            }
+            Key::AuditLog => {
+                let mut audit_log: AuditLog = bytemuck::Zeroable::zeroed();
+                audit_log.bump = addresses.audit_log.1;
+                program_account(addresses.audit_log.0, zero_copy_data(&audit_log))
+            }
+            Key::VerifyingKey(version) if version == CIRCUIT_VERSION => {
+                program_account(addresses.verifying_key.0, self.verifying_key())
+            }
+            Key::VerifyingKey(version) => {
+                let state = addresses.program_state.0;
+                Self::empty(pda(&[b"verifying_key", state.as_ref(), &version.to_le_bytes()]).0, system_program::ID, 0)
+            }
+            Key::NullifierShard(prefix) => {
+                let (address, bump) = pda(&[b"nullifier_shard", addresses.program_state.0.as_ref(), &prefix]);
+                program_account(address, anchor_data(&NullifierShard { prefix, count: 0, bump }))
+            }
+            Key::RelayerInfo(index) => {
+                let relayer = signer(index);
+                let (address, bump) = pda(&[b"relayer", relayer.as_ref()]);
+                let info = RelayerInfo { relayer, bump, ..Default::default() };
+                program_account(address, anchor_data(&info))
+            }
+            Key::Signer(index) => Self::empty(signer(index), system_program::ID, SIGNER_LAMPORTS),
+            Key::TokenAccount(index) => {
+                let owner = signer(index);
+                let balance = self.input.token_balances[usize::from(index % SIGNERS)];
+                token_account(token_account_address(index), owner, balance)
+            }
+            Key::Vault => token_account(addresses.vault.0, addresses.vault.0, self.input.vault_balance),
+            Key::Mint => {
+                let mint = spl_token::state::Mint {
+                    mint_authority: Some(signer(0)).into(),
+                    supply: u64::MAX,
+                    decimals: 6,
+                    is_initialized: true,
+                    freeze_authority: None.into(),
+                };
+                packed(addresses.mint, mint)
+            }
+            Key::SystemProgram => Self::program(system_program::ID),
+            Key::TokenProgram => Self::program(spl_token::ID),
+            Key::PrivaxProgram => Self::program(privax_protocol::ID),
+            Key::RentSysvar => {
+                let mut data = Vec::with_capacity(17);
+                data.extend_from_slice(&rent.lamports_per_byte_year.to_le_bytes());
+                data.extend_from_slice(&rent.exemption_threshold.to_le_bytes());
+                data.push(rent.burn_percent);
+                Account { key: sysvar::rent::ID, owner: sysvar::ID, lamports: 1, data, executable: false }
+            }
+            Key::Other(key, owner, len) => {
+                let owner = match owner {
+                    Owner::System => system_program::ID,
+                    Owner::Token => spl_token::ID,
+                    Owner::Other(owner) => Pubkey::new_from_array(owner),
+                };
+                let mut account = Self::empty(Pubkey::new_from_array(key), owner, 0);
+                account.data = vec![0; usize::from(len) % 1024];
+                account.lamports = rent.minimum_balance(account.data.len());
+                account
+            }
+        }
+    }
+
+    fn empty(key: Pubkey, owner: Pubkey, lamports: u64) -> Account {
+        Account { key, owner, lamports, data: Vec::new(), executable: false }
+    }
+
+    fn program(key: Pubkey) -> Account {
+        Account { key, owner: bpf_loader_upgradeable_id(), lamports: 1, data: Vec::new(), executable: true }
+    }
+
+    fn program_state(&self) -> Vec<u8> {
+        let input = self.input;
+        let addresses = addresses();
+        let relayers = usize::from(input.relayers) % (ProgramState::MAX_RELAYERS + 1);
+        let state = ProgramState {
+            admin: if input.renounced { Pubkey::default() } else { signer(0) },
+            token_mint: addresses.mint,
+            bump: addresses.program_state.1,
+            whitelisted_relayers: (0..relayers).map(|i| signer(i as u8)).collect(),
+            guardian: signer(1),
+            paused: input.paused,
+            renounced: input.renounced,
+            frozen_groups: input.frozen_groups & ProgramState::PARAM_GROUP_ALL,
+            outstanding_deposits: input.outstanding_deposits,
+            proof_system: ProofSystem::Groth16,
+            ..Default::default()
+        };
+        let mut data = anchor_data(&state);
+        data.resize(ProgramState::SPACE, 0);
+        data
+    }
+
+    fn fee_config(&self) -> Vec<u8> {
+        let input = self.input;
+        let config = FeeConfig {
+            fee_authority: signer(0),
+            protocol_fee_bps: input.protocol_fee_bps % (FeeConfig::MAX_PROTOCOL_FEE_BPS + 1),
+            max_protocol_fee: input.max_protocol_fee,
+            accrued_protocol_fees: input.accrued_protocol_fees,
+            bump: addresses().fee_config.1,
+            ..Default::default()
+        };
+        let mut data = anchor_data(&config);
+        data.resize(FeeConfig::SPACE, 0);
+        data
+    }
+
+    fn merkle_tree(&self) -> Vec<u8> {
+        let mut tree: MerkleTree = bytemuck::Zeroable::zeroed();
+        tree.init(addresses().merkle_tree.1);
+        for leaf in 0..self.input.leaves {
+            tree.append([leaf; 32]).unwrap();
+        }
+        zero_copy_data(&tree)
+    }
+
+    // A Groth16 key shaped for the eight fixed public inputs. Its points aren't on the curve, so
+    // no proof verifies against it and instructions run up to the pairing check.
+    fn verifying_key(&self) -> Vec<u8> {
+        let mut key: VerifyingKey = bytemuck::Zeroable::zeroed();
+        let len = VK_FIXED_SIZE + 9 * G1_SIZE;
+        key.circuit_version = CIRCUIT_VERSION;
+        key.vk_len = len as u16;
+        key.written = len as u16;
+        key.proof_system = ProofSystem::Groth16 as u8;
+        key.enabled = u8::from(self.input.key_enabled);
+        key.amount_range_bits = VerifyingKey::REQUIRED_AMOUNT_RANGE_BITS;
+        key.bump = addresses().verifying_key.1;
+        key.vk_data[..len].fill(1);
+        zero_copy_data(&key)
+    }
+}
+
+fn anchor_data<T: AccountSerialize>(account: &T) -> Vec<u8> {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    data
+}
+
+fn zero_copy_data<T: bytemuck::Pod + Discriminator>(account: &T) -> Vec<u8> {
+    [T::DISCRIMINATOR.as_slice(), bytemuck::bytes_of(account)].concat()
+}
+
+fn token_account(key: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let account = spl_token::state::Account {
+        mint: addresses().mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    };
+    packed(key, account)
+}
+
+fn packed<T: Pack>(key: Pubkey, value: T) -> Account {
+    let mut data = vec![0; T::LEN];
+    T::pack(value, &mut data).unwrap();
+    Account { key, owner: spl_token::ID, lamports: Rent::default().minimum_balance(T::LEN), data, executable: false }
+}
+
+fn bpf_loader_upgradeable_id() -> Pubkey {
+    anchor_lang::solana_program::bpf_loader_upgradeable::ID
+}
+
+static SLOT: AtomicU64 = AtomicU64::new(0);
+
+// Syscalls for running the program natively: sysvars from the input, CPIs accepted unrun,
+// and logging dropped to keep iterations fast.
+struct FuzzStubs;
+
+impl SyscallStubs for FuzzStubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_compute_units(&self) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_invoke_signed(&self, _instruction: &Instruction, _account_infos: &[AccountInfo], _signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+        Ok(())
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock { slot: SLOT.load(Ordering::Relaxed), ..Default::default() };
+        // SAFETY: Clock::get passes a pointer to a Clock.
+        unsafe { *(var_addr as *mut Clock) = clock };
+        entrypoint::SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // SAFETY: Rent::get passes a pointer to a Rent.
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        entrypoint::SUCCESS
+    }
+}
+
+fn install_stubs() {
+    static STUBS: Once = Once::new();
+    STUBS.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(FuzzStubs));
+    });
+}
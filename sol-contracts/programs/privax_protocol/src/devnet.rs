@@ -0,0 +1,198 @@
+// Test-only instructions, compiled in with the `devnet` feature and never into mainnet builds.
+//
+// `force_insert_leaf` appends a commitment without a deposit and `reset_pool` empties the tree,
+// the nullifier set and the set of inserted commitments, so end-to-end tests can set up and
+// tear down pool state cheaply. With the feature on, `verify_proof` also accepts the all-zero
+// proof (see `is_mock_proof`), so withdrawals can be exercised without generating real proofs.
+//
+// Anchor's #[program] macro can't feature-gate instructions, so these are dispatched from the
+// program's fallback on their Anchor-style discriminators and are absent from the IDL. Build
+// them with `accounts` and `instruction` below, as with the program's own modules.
+use std::collections::{BTreeMap, BTreeSet};
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::{
+    CommitmentShard, DepositOccurred, MerkleTree, NullifierFilter, NullifierShard, PrivaxError, ProgramState, EVENT_VERSION,
+};
+
+pub mod accounts {
+    pub use super::__client_accounts_force_insert_leaf::*;
+    pub use super::__client_accounts_reset_pool::*;
+}
+
+pub mod instruction {
+    use super::*;
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct ForceInsertLeaf {
+        pub commitment: [u8; 32],
+    }
+
+    // sha256("global:force_insert_leaf")[..8], as Anchor would derive it
+    impl Discriminator for ForceInsertLeaf {
+        const DISCRIMINATOR: [u8; 8] = [249, 20, 216, 108, 71, 177, 187, 153];
+    }
+
+    impl anchor_lang::InstructionData for ForceInsertLeaf {}
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct ResetPool {}
+
+    // sha256("global:reset_pool")[..8]
+    impl Discriminator for ResetPool {
+        const DISCRIMINATOR: [u8; 8] = [108, 172, 93, 91, 146, 8, 155, 112];
+    }
+
+    impl anchor_lang::InstructionData for ResetPool {}
+}
+
+#[event]
+pub struct PoolReset {
+    version: u8,
+    admin: Pubkey,
+    shards_cleared: u32,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+// Groth16 slots and PLONK proof all zero: never a valid encoding of a real proof.
+pub fn is_mock_proof(a_proof: &[u8; 64], b_proof: &[u8; 128], c_proof: &[u8; 64], plonk_proof: &[u8]) -> bool {
+    plonk_proof.is_empty() && a_proof.iter().chain(b_proof.iter()).chain(c_proof.iter()).all(|&b| b == 0)
+}
+
+// Called from the program's fallback with the full instruction data.
+pub fn dispatch(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> Result<()> {
+    if data.len() < 8 {
+        return Err(ErrorCode::InstructionMissing.into());
+    }
+    let (discriminator, ix_data) = data.split_at(8);
+    match <[u8; 8]>::try_from(discriminator).unwrap() {
+        instruction::ForceInsertLeaf::DISCRIMINATOR => {
+            msg!("Instruction: ForceInsertLeaf (devnet)");
+            let ix = instruction::ForceInsertLeaf::deserialize(&mut &ix_data[..])
+                .map_err(|_| ErrorCode::InstructionDidNotDeserialize)?;
+            run::<ForceInsertLeaf>(program_id, accounts, ix_data, |ctx| force_insert_leaf(ctx, ix.commitment))
+        }
+        instruction::ResetPool::DISCRIMINATOR => {
+            msg!("Instruction: ResetPool (devnet)");
+            run::<ResetPool>(program_id, accounts, ix_data, reset_pool)
+        }
+        _ => Err(ErrorCode::InstructionFallbackNotFound.into()),
+    }
+}
+
+// What #[program] generates for each instruction: validate the accounts, run the handler, then
+// write the accounts back.
+fn run<'info, T: Accounts<'info> + AccountsExit<'info>>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+    ix_data: &[u8],
+    handler: impl FnOnce(Context<'_, '_, '_, 'info, T>) -> Result<()>,
+) -> Result<()> {
+    let mut remaining_accounts = accounts;
+    let mut bumps = BTreeMap::new();
+    let mut accounts = T::try_accounts(program_id, &mut remaining_accounts, ix_data, &mut bumps, &mut BTreeSet::new())?;
+    handler(Context::new(program_id, &mut accounts, remaining_accounts, bumps))?;
+    accounts.exit(program_id)
+}
+
+// Appends a commitment as if it had been deposited, without moving tokens. The DepositOccurred
+// event (amount 0) keeps indexers and relayers in step; withdrawing the note only succeeds if
+// the vault holds the tokens.
+fn force_insert_leaf(ctx: Context<ForceInsertLeaf>, commitment: [u8; 32]) -> Result<()> {
+    ctx.accounts.merkle_tree.load_mut()?.append(commitment)?;
+    let clock = Clock::get()?;
+    emit!(DepositOccurred {
+        version: EVENT_VERSION,
+        user: ctx.accounts.admin.key(),
+        token_address: ctx.accounts.program_state.token_mint,
+        amount: 0,
+        commitment,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+// Empties the tree, the nullifier filter and every nullifier or commitment shard passed as a
+// remaining account, whose rent above the empty size goes back to the admin. Tokens stay in the vault.
+// Indexers have to be rebuilt from scratch afterwards.
+fn reset_pool<'info>(ctx: Context<'_, '_, '_, 'info, ResetPool<'info>>) -> Result<()> {
+    {
+        let mut tree = ctx.accounts.merkle_tree.load_mut()?;
+        let bump = tree.bump;
+        tree.next_index = 0;
+        tree.current_root_index = 0;
+        tree.roots.fill([0; 32]);
+        // Archive pages are overwritten from page 0 as the new tree's roots come in
+        tree.archived_roots = 0;
+        tree.init(bump);
+    }
+    {
+        let mut filter = ctx.accounts.nullifier_filter.load_mut()?;
+        filter.inserted = 0;
+        filter.bits.fill(0);
+    }
+
+    let admin = ctx.accounts.admin.to_account_info();
+    let rent = Rent::get()?;
+    for info in ctx.remaining_accounts {
+        // Both kinds of shard have the same header; only their seed differs
+        let (seed, prefix, bump, mismatch) = if info.try_borrow_data()?.starts_with(&CommitmentShard::DISCRIMINATOR) {
+            let mut shard = Account::<CommitmentShard>::try_from(info)?;
+            shard.count = 0;
+            shard.exit(&crate::ID)?;
+            (&b"commitment_shard"[..], shard.prefix, shard.bump, PrivaxError::CommitmentShardMismatch)
+        } else {
+            let mut shard = Account::<NullifierShard>::try_from(info)?;
+            shard.count = 0;
+            shard.exit(&crate::ID)?;
+            (&b"nullifier_shard"[..], shard.prefix, shard.bump, PrivaxError::NullifierShardMismatch)
+        };
+        let expected =
+            Pubkey::create_program_address(&[seed, ctx.accounts.program_state.key().as_ref(), &prefix, &[bump]], &crate::ID)
+                .map_err(|_| mismatch)?;
+        require_keys_eq!(expected, info.key(), mismatch);
+
+        info.realloc(NullifierShard::SPACE, false)?;
+        let refund = info.lamports().saturating_sub(rent.minimum_balance(NullifierShard::SPACE));
+        **info.try_borrow_mut_lamports()? -= refund;
+        let admin_lamports = admin.lamports().checked_add(refund).ok_or(PrivaxError::Overflow)?;
+        **admin.try_borrow_mut_lamports()? = admin_lamports;
+    }
+
+    ctx.accounts.program_state.outstanding_deposits = 0;
+    let clock = Clock::get()?;
+    emit!(PoolReset {
+        version: EVENT_VERSION,
+        admin: admin.key(),
+        shards_cleared: ctx.remaining_accounts.len() as u32,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ForceInsertLeaf<'info> {
+    #[account(has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetPool<'info> {
+    #[account(mut, has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    #[account(mut, seeds = [b"nullifier_filter", program_state.key().as_ref()], bump = nullifier_filter.load()?.bump)]
+    pub nullifier_filter: AccountLoader<'info, NullifierFilter>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    // Followed by the nullifier and commitment shards to empty, as remaining accounts.
+}
@@ -0,0 +1,72 @@
+// Groth16 verification over BN254 with the alt_bn128 syscalls, against a key in the on-chain
+// layout (see proof_format). A proof (a, b, c) is valid for public inputs x when
+//
+//   e(-a, b) · e(alpha, beta) · e(vk_x, gamma) · e(c, delta) == 1,  vk_x = ic[0] + Σ x_i · ic[i + 1]
+//
+// which is one multi-pairing call. Public inputs are scalars, 32 big-endian bytes each.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::alt_bn128::prelude::{
+    alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing, AltBn128Error,
+};
+
+use crate::proof_format::{
+    is_canonical_scalar, BN254_BASE_FIELD_MODULUS, FIELD_SIZE, G1_SIZE, G2_SIZE, VK_FIXED_SIZE,
+};
+use crate::PrivaxError;
+
+// Verifies a proof against `vk_data`, whose key must have one ic point per public input plus
+// one. Points off the curve or outside their subgroup make the proof invalid rather than an
+// error, as does a public input that isn't a canonical scalar.
+pub fn verify(
+    vk_data: &[u8],
+    a: &[u8; G1_SIZE],
+    b: &[u8; G2_SIZE],
+    c: &[u8; G1_SIZE],
+    public_inputs: &[[u8; FIELD_SIZE]],
+) -> Result<bool> {
+    let ic = vk_data.get(VK_FIXED_SIZE..).ok_or(PrivaxError::InvalidVerifyingKey)?;
+    require!(ic.len() == (public_inputs.len() + 1) * G1_SIZE, PrivaxError::InvalidPublicInputCount);
+    if !public_inputs.iter().all(is_canonical_scalar) {
+        return Ok(false);
+    }
+    Ok(pairing_check(vk_data, a, b, c, public_inputs).unwrap_or(false))
+}
+
+fn pairing_check(
+    vk_data: &[u8],
+    a: &[u8; G1_SIZE],
+    b: &[u8; G2_SIZE],
+    c: &[u8; G1_SIZE],
+    public_inputs: &[[u8; FIELD_SIZE]],
+) -> std::result::Result<bool, AltBn128Error> {
+    let (alpha, rest) = vk_data.split_at(G1_SIZE);
+    let (beta, rest) = rest.split_at(G2_SIZE);
+    let (gamma, rest) = rest.split_at(G2_SIZE);
+    let (delta, ic) = rest.split_at(G2_SIZE);
+
+    let (ic0, ic) = ic.split_at(G1_SIZE);
+    let mut vk_x = ic0.to_vec();
+    for (input, point) in public_inputs.iter().zip(ic.chunks_exact(G1_SIZE)) {
+        let term = alt_bn128_multiplication(&[point, input.as_slice()].concat())?;
+        vk_x = alt_bn128_addition(&[vk_x.as_slice(), &term].concat())?;
+    }
+
+    let pairs = [&negate_g1(a)[..], b, alpha, beta, &vk_x, gamma, c, delta].concat();
+    let result = alt_bn128_pairing(&pairs)?;
+    Ok(result.last() == Some(&1) && result[..result.len() - 1].iter().all(|&byte| byte == 0))
+}
+
+// -(x, y) = (x, p - y); the point at infinity (all zero) is its own negation.
+pub(crate) fn negate_g1(point: &[u8; G1_SIZE]) -> [u8; G1_SIZE] {
+    let mut negated = *point;
+    if point.iter().all(|&byte| byte == 0) {
+        return negated;
+    }
+    let mut borrow = 0u16;
+    for i in (0..FIELD_SIZE).rev() {
+        let difference = u16::from(BN254_BASE_FIELD_MODULUS[i]).wrapping_sub(u16::from(point[FIELD_SIZE + i]) + borrow);
+        negated[FIELD_SIZE + i] = difference as u8;
+        borrow = u16::from(difference > 0xff);
+    }
+    negated
+}
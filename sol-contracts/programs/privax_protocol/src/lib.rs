@@ -1,5 +1,24 @@
+#![allow(clippy::result_large_err)]
+
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::associated_token::{get_associated_token_address, AssociatedToken};
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use light_poseidon::{Poseidon, PoseidonHasher};
+use mpl_bubblegum::instructions::{MintV1Cpi, MintV1CpiAccounts, MintV1InstructionArgs};
+use mpl_bubblegum::types::{MetadataArgs, TokenProgramVersion};
+use mpl_token_metadata::instruction::{builders, InstructionBuilder, TransferArgs};
+use mpl_token_metadata::state::TokenStandard;
+use pyth_sdk_solana::{load_price_feed_from_account_info, Price};
+
+#[cfg(feature = "devnet")]
+pub mod devnet;
+pub mod groth16;
+pub mod plonk;
+pub mod proof_format;
 
 // Declare the program ID. Replace with your actual program ID when deploying.
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
@@ -15,7 +34,7 @@ pub enum PrivaxError {
     RecipientMismatch,
     #[msg("Amount mismatch in proof inputs.")]
     AmountMismatch,
-    #[msg("Invalid ZK proof (placeholder check).")]
+    #[msg("Invalid ZK proof.")]
     InvalidZkProof,
     #[msg("Relayer already whitelisted.")]
     RelayerAlreadyWhitelisted,
@@ -27,6 +46,239 @@ pub enum PrivaxError {
     NewAdminIsZero,
     #[msg("Overflow during arithmetic operation.")]
     Overflow,
+    #[msg("Relayer and protocol fees exceed the withdrawal amount.")]
+    FeeExceedsAmount,
+    #[msg("Relayer fee token account is required when a relayer fee is charged.")]
+    MissingRelayerFeeAccount,
+    #[msg("Relayer fee token account must be the relayer's associated token account for the fee mint.")]
+    InvalidRelayerFeeAccount,
+    #[msg("Fee swap program is not configured or does not match.")]
+    InvalidFeeSwapProgram,
+    #[msg("Fee swap spent more than the relayer fee from the vault.")]
+    FeeSwapOverspent,
+    #[msg("Fee swap returned less than the relayer's minimum.")]
+    FeeSwapSlippage,
+    #[msg("Relayer whitelisting has expired.")]
+    RelayerExpired,
+    #[msg("The pool is paused.")]
+    PoolPaused,
+    #[msg("The pool is not paused.")]
+    PoolNotPaused,
+    #[msg("Signer is neither the admin nor the guardian.")]
+    NotAdminOrGuardian,
+    #[msg("Confirmation value does not match RENOUNCE_ADMIN_CONFIRMATION.")]
+    InvalidRenounceConfirmation,
+    #[msg("Admin renouncement has not been initiated or cannot complete in the same slot.")]
+    RenounceNotInitiated,
+    #[msg("This parameter group has been frozen.")]
+    ParamsFrozen,
+    #[msg("Unknown parameter group flag.")]
+    InvalidParamGroup,
+    #[msg("Fee basis points exceed the allowed maximum.")]
+    FeeBpsTooHigh,
+    #[msg("Too many fee beneficiaries or a zero total weight.")]
+    InvalidFeeBeneficiaries,
+    #[msg("Beneficiary token accounts do not match the configured beneficiaries.")]
+    BeneficiaryAccountMismatch,
+    #[msg("No protocol fees to distribute.")]
+    NothingToDistribute,
+    #[msg("No referral fees are claimable right now.")]
+    NothingToClaim,
+    #[msg("Commitment registration has expired.")]
+    RegistrationExpired,
+    #[msg("Commitment registration has not expired yet.")]
+    RegistrationNotExpired,
+    #[msg("Crank accounts must be [pending_commitment, registrant] pairs.")]
+    InvalidCrankAccounts,
+    #[msg("No queued item was due for processing.")]
+    NothingToCrank,
+    #[msg("Nullifier has already been spent.")]
+    NullifierAlreadySpent,
+    #[msg("Nullifier shard does not cover this nullifier.")]
+    NullifierShardMismatch,
+    #[msg("Subtree must have a power-of-two number of leaves, at most 2^MAX_SUBTREE_HEIGHT, with one amount each.")]
+    InvalidSubtreeSize,
+    #[msg("Subtree root does not match its leaves.")]
+    SubtreeRootMismatch,
+    #[msg("Next leaf index is not aligned to the subtree width.")]
+    MisalignedSubtree,
+    #[msg("Merkle tree is full.")]
+    MerkleTreeFull,
+    #[msg("Proof is malformed or not encoded for the circuit's proof system.")]
+    InvalidProofEncoding,
+    #[msg("Verifying key is too large.")]
+    VerifyingKeyTooLarge,
+    #[msg("Verifying key for this circuit version is disabled.")]
+    CircuitVersionDisabled,
+    #[msg("Proof slot is in the future or older than MAX_PROOF_AGE_SLOTS.")]
+    ProofExpired,
+    #[msg("External nullifier does not match this deployment.")]
+    ExternalNullifierMismatch,
+    #[msg("Verifying key is not in the on-chain layout for its proof system.")]
+    InvalidVerifyingKey,
+    #[msg("Circuit must range-check amounts to exactly 64 bits for a variable-amount pool.")]
+    MissingAmountRangeCheck,
+    #[msg("Amount public input exceeds the circuit's range-checked width.")]
+    AmountOutOfRange,
+    #[msg("Verifying key has not been fully uploaded.")]
+    IncompleteVerifyingKey,
+    #[msg("Change commitment does not match the proof's public input.")]
+    ChangeCommitmentMismatch,
+    #[msg("Merkle tree account is required to insert a change note.")]
+    MissingMerkleTree,
+    #[msg("Swap target is this pool, isn't an approved swap target, or isn't the pool the proof names.")]
+    InvalidSwapTarget,
+    #[msg("Swap spent more than the input note from the vault.")]
+    SwapOverspent,
+    #[msg("Swap returned less than the output note's amount.")]
+    SwapSlippage,
+    #[msg("Output commitment does not match the proof's public input.")]
+    OutputCommitmentMismatch,
+    #[msg("A split withdrawal pays 1..=MAX_SPLIT_RECIPIENTS recipients, each a non-zero amount.")]
+    InvalidPayouts,
+    #[msg("Payouts do not match the proof's public inputs.")]
+    PayoutMismatch,
+    #[msg("Recipient token accounts must follow the payouts, one each: the recipient's associated token account.")]
+    InvalidRecipientAccount,
+    #[msg("Note splits create 2 to MAX_NOTE_SPLIT_OUTPUTS notes; merges spend 2 to MAX_INPUT_NOTES.")]
+    InvalidNoteCount,
+    #[msg("A payment schedule needs a non-zero amount, interval and payment count.")]
+    InvalidPaymentSchedule,
+    #[msg("No scheduled payment is due yet.")]
+    PaymentNotDue,
+    #[msg("The schedule's escrow can't cover the payment and the executor fee.")]
+    ScheduleUnderfunded,
+    #[msg("This instruction is not available in the pool's mode.")]
+    WrongPoolMode,
+    #[msg("The pool mode can only change before the first deposit.")]
+    PoolNotEmpty,
+    #[msg("Mint is not an NFT: supply must be 1 and decimals 0.")]
+    NotAnNft,
+    #[msg("NFT mint does not match the proof's public input or the metadata account.")]
+    MintMismatch,
+    #[msg("Programmable NFTs need their edition, token records, Token Metadata and the instructions sysvar.")]
+    MissingProgrammableAccounts,
+    #[msg("Receipt NFTs are not enabled for this pool.")]
+    ReceiptNftsDisabled,
+    #[msg("Receipt NFT name, symbol or URI is too long.")]
+    InvalidReceiptNftConfig,
+    #[msg("The pool's USD limits and their price feed must be passed while the limits are set.")]
+    MissingUsdLimits,
+    #[msg("Price feed is not the pool's Pyth price account, or its price is unusable.")]
+    InvalidPriceFeed,
+    #[msg("Pyth price is older than the pool's maximum price age.")]
+    StalePrice,
+    #[msg("Pyth price's confidence interval is wider than the pool accepts.")]
+    PriceTooUncertain,
+    #[msg("Deposit is worth more than the pool's USD deposit cap.")]
+    DepositCapExceeded,
+    #[msg("Withdrawal is worth more than the pool's USD large-withdrawal threshold.")]
+    LargeWithdrawal,
+    #[msg("Invalid USD limits.")]
+    InvalidUsdLimits,
+    #[msg("Signer is neither the admin nor the tree operator.")]
+    NotTreeOperator,
+    #[msg("No leaves were inserted since the last checkpoint.")]
+    CheckpointUnchanged,
+    #[msg("Every root the tree has had is already archived.")]
+    NothingToArchive,
+    #[msg("Roots are archived in order; this isn't the archive's next page.")]
+    WrongArchivePage,
+    #[msg("Commitment is zero or not a canonical BN254 field element.")]
+    InvalidCommitment,
+    #[msg("Commitment is already in the tree.")]
+    DuplicateCommitment,
+    #[msg("Commitment shard does not cover this commitment.")]
+    CommitmentShardMismatch,
+    #[msg("Signer is not the pool's admin.")]
+    NotAdmin,
+    #[msg("Signer is not the fee authority.")]
+    NotFeeAuthority,
+    #[msg("Signer does not own this account.")]
+    NotAccountOwner,
+    #[msg("Registrant does not match the pending commitment.")]
+    RegistrantMismatch,
+    #[msg("Token account or mint is not the pool's mint.")]
+    TokenMintMismatch,
+    #[msg("The relayer whitelist is full (MAX_RELAYERS).")]
+    TooManyRelayers,
+    #[msg("Pass one nullifier shard account per nullifier, in order.")]
+    NullifierShardCountMismatch,
+    #[msg("Note ciphertext is empty or longer than MAX_NOTE_CIPHERTEXT_LEN.")]
+    InvalidNoteCiphertext,
+    #[msg("A withdrawal limit needs an epoch of at least one slot.")]
+    InvalidWithdrawalLimit,
+    #[msg("The pool is exit-only after a prolonged pause; only withdrawals and unpausing are allowed.")]
+    ExitOnly,
+    #[msg("Withdrawals this epoch have reached the limit; try again next epoch.")]
+    WithdrawalLimitReached,
+    #[msg("This pool only takes deposits with a compliance attestation (attest_deposit first).")]
+    ComplianceAttestationRequired,
+    #[msg("The compliance attestation is another depositor's, for an old compliance key, or already used.")]
+    ComplianceAttestationMismatch,
+    #[msg("The pool has no compliance key to attest to.")]
+    NoComplianceKey,
+    #[msg("This pool only takes deposits from holders of a KYC credential.")]
+    KycCredentialRequired,
+    #[msg("The KYC credential was issued by another attestor or has expired.")]
+    KycCredentialInvalid,
+    #[msg("Only the pool's KYC attestor can issue credentials.")]
+    NotKycAttestor,
+    #[msg("A KYC credential must expire in the future, or never (0).")]
+    InvalidKycExpiry,
+    #[msg("This pool caps daily deposits per depositor; pass the depositor's deposit volume account.")]
+    DepositVolumeRequired,
+    #[msg("This deposit would take the depositor past the pool's daily deposit cap.")]
+    DailyDepositCapReached,
+    #[msg("Only the admin or the exclusion operator can publish an exclusion root.")]
+    NotExclusionOperator,
+    #[msg("An exclusion root must be a non-zero field element.")]
+    InvalidExclusionRoot,
+    #[msg("Publish an exclusion root before requiring proofs of non-membership.")]
+    NoExclusionRoot,
+    #[msg("The proof is bound to another relayer or relayer fee.")]
+    RelayerMismatch,
+    #[msg("The proof's Merkle root isn't a recent root of the tree, or an archived one it accepts.")]
+    RootNotKnown,
+    #[msg("The proof system is not supported.")]
+    ProofSystemUnsupported,
+    #[msg("While the admin timelock is set, this action must be queued with queue_admin_action.")]
+    AdminTimelockActive,
+    #[msg("The admin timelock can be at most ProgramState::MAX_ADMIN_TIMELOCK_SLOTS.")]
+    InvalidAdminTimelock,
+    #[msg("The queued admin action's timelock hasn't passed yet.")]
+    AdminActionNotReady,
+    #[msg("payer must be the account that paid for the queued admin action.")]
+    QueuedActionPayerMismatch,
+    #[msg("merkle_root doesn't match the proof's root input.")]
+    MerkleRootMismatch,
+    #[msg("A nullifier hash doesn't match the proof's input for it.")]
+    NullifierHashMismatch,
+    #[msg("The swap target list is full (MAX_SWAP_TARGETS).")]
+    TooManySwapTargets,
+    #[msg("The program is already an approved swap target.")]
+    SwapTargetAlreadyApproved,
+    #[msg("The target pool's deposit didn't take exactly the output note's amount.")]
+    SwapDepositMismatch,
+}
+
+// Proof system a circuit's withdraw proofs use. Groth16 proofs arrive as the (a, b, c) points;
+// PLONK proofs don't fit that shape and arrive in withdraw's plonk_proof instead, with the
+// Groth16 slots zero (see plonk for the layout).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum ProofSystem {
+    #[default]
+    Groth16,
+    Plonk,
+}
+
+// What a pool's notes hold. A fungible pool holds its token_mint; an NFT pool holds any number
+// of NFTs, one per note, each note committing to its NFT's mint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum PoolMode {
+    #[default]
+    Fungible,
+    Nft,
 }
 
 // --- Program State Account ---
@@ -39,6 +291,36 @@ pub struct ProgramState {
     pub bump: u8,
     // Whitelisted relayers - using a Vec for simplicity in showcase, consider BTreeMap for production
     pub whitelisted_relayers: Vec<Pubkey>,
+    pub guardian: Pubkey,       // Can pause but never change parameters (default = no guardian)
+    pub paused: bool,           // Blocks deposits and withdrawals while set
+    pub paused_at_slot: u64,    // Slot of the most recent pause
+    pub renounce_initiated_slot: u64, // Slot of a pending renounce_admin (0 = none)
+    pub renounced: bool,        // Admin permanently removed; parameters are frozen
+    pub frozen_groups: u8,      // Bitmask of PARAM_GROUP_* flags that can no longer be changed
+    pub outstanding_deposits: u64, // Deposited minus withdrawn: what the pool owes note holders
+    pub proof_system: ProofSystem, // Proof system of the current circuit; each VerifyingKey records its own
+    pub pool_mode: PoolMode,    // Set once, before the first deposit, by enable_nft_mode
+    pub usd_limits: bool,       // UsdLimits has a limit set; deposits and withdrawals must pass it
+    pub tree_operator: Pubkey,  // Runs tree maintenance; never moves funds (default = none)
+    pub max_withdrawal_per_epoch: u64, // Circuit breaker: withdrawal volume per epoch that pauses the pool (0 = off)
+    pub withdrawal_epoch_slots: u64,   // Length of a circuit breaker epoch
+    pub withdrawal_epoch: u64,         // Epoch withdrawn_in_epoch counts: slot / withdrawal_epoch_slots
+    pub withdrawn_in_epoch: u64,       // Withdrawal volume so far in withdrawal_epoch
+    pub total_deposited: u64,   // Lifetime deposit volume, checked against the vault by `reconcile`
+    pub total_withdrawn: u64,   // Lifetime withdrawal volume, protocol fees included
+    pub pause_on_shortfall: bool, // `reconcile` pauses the pool when the vault holds less than it should
+    pub max_pause_slots: u64,   // A pause longer than this turns exit-only (0 = pauses never do)
+    pub compliance_key: [u8; 32], // X25519 key deposits must attest to (zero = no attestations)
+    pub kyc_attestor: Pubkey,   // Issues the credentials depositors need (default = permissionless pool)
+    pub max_daily_deposit: u64, // Most a depositor can deposit in any 24 hours, in base units (0 = no cap)
+    pub exclusion_operator: Pubkey, // Publishes exclusion roots alongside the admin (default = admin only)
+    pub exclusion_root: [u8; 32], // Root of the commitments flagged as illicit (zero = none published)
+    pub previous_exclusion_root: [u8; 32], // Still accepted for proofs bound to a slot before the latest publish
+    pub exclusion_root_slot: u64, // Slot exclusion_root was published at
+    pub exclusion_required: bool, // Spend proofs must show their notes aren't under exclusion_root
+    pub admin_timelock_slots: u64, // How long queued AdminActions wait (0 = no timelock; the admin acts directly)
+    pub next_admin_action_id: u64, // Id of the next queue_admin_action, which seeds its account
+    pub swap_targets: Vec<Pubkey>, // Pools shielded_swap may deposit into with the vault authority's signature
 }
 
 impl ProgramState {
@@ -48,261 +330,6342 @@ impl ProgramState {
     // Pubkey (verifier_program_id) = 32
     // u8 (bump) = 1
     // Vec<Pubkey> for whitelisted_relayers: 4 (for Vec prefix) + N * 32. Let's assume max 10 relayers for showcase.
+    // Pubkey (guardian) = 32
+    // bool (paused) = 1
+    // u64 (paused_at_slot) = 8
+    // u64 (renounce_initiated_slot) = 8
+    // bool (renounced) = 1
+    // u8 (frozen_groups) = 1
+    // u64 (outstanding_deposits) = 8
+    // ProofSystem (proof_system) = 1
+    // PoolMode (pool_mode) = 1
+    // bool (usd_limits) = 1
+    // Pubkey (tree_operator) = 32
+    // u64 (max_withdrawal_per_epoch, withdrawal_epoch_slots, withdrawal_epoch, withdrawn_in_epoch) = 8 each
+    // u64 (total_deposited, total_withdrawn) = 8 each
+    // bool (pause_on_shortfall) = 1
+    // u64 (max_pause_slots) = 8
+    // [u8; 32] (compliance_key) = 32
+    // Pubkey (kyc_attestor) = 32
+    // u64 (max_daily_deposit) = 8
+    // Pubkey (exclusion_operator) = 32
+    // [u8; 32] (exclusion_root, previous_exclusion_root) = 32 each
+    // u64 (exclusion_root_slot) = 8
+    // bool (exclusion_required) = 1
+    // u64 (admin_timelock_slots, next_admin_action_id) = 8 each
+    // Vec<Pubkey> for swap_targets: 4 + MAX_SWAP_TARGETS * 32
     pub const MAX_RELAYERS: usize = 10;
-    pub const SPACE: usize = 8 + 32 + 32 + 32 + 1 + (4 + Self::MAX_RELAYERS * 32);
-}
+    pub const MAX_SWAP_TARGETS: usize = 4;
+    pub const SPACE: usize =
+        8 + 32 + 32 + 32 + 1 + (4 + Self::MAX_RELAYERS * 32) + 32 + 1 + 8 + 8 + 1 + 1 + 8 + 1 + 1 + 1 + 32 + 8 * 4 + 8 * 2 + 1 + 8 + 32 + 32
+            + 8 + 32 + 32 * 2 + 8 + 1 + 8 * 2 + (4 + Self::MAX_SWAP_TARGETS * 32);
 
-// --- Events (emitted via `emit!`) ---
-#[event]
-pub struct AdminChanged {
-    old_admin: Pubkey,
-    new_admin: Pubkey,
+    // Parameter groups the admin can irreversibly freeze with `freeze_params`.
+    pub const PARAM_GROUP_FEES: u8 = 1 << 0;
+    pub const PARAM_GROUP_VERIFIER: u8 = 1 << 1;
+    pub const PARAM_GROUP_RELAYERS: u8 = 1 << 2;
+    pub const PARAM_GROUP_BREAKER: u8 = 1 << 3; // The withdrawal circuit breaker
+    pub const PARAM_GROUP_ALL: u8 =
+        Self::PARAM_GROUP_FEES | Self::PARAM_GROUP_VERIFIER | Self::PARAM_GROUP_RELAYERS | Self::PARAM_GROUP_BREAKER;
+
+    // Once the admin is renounced nobody can unpause, so pauses lapse on their own (~1 day).
+    pub const PAUSE_EXPIRY_SLOTS_AFTER_RENOUNCE: u64 = 216_000;
+    // Longest admin timelock (~30 days), so a typo can't lock the admin out for good.
+    pub const MAX_ADMIN_TIMELOCK_SLOTS: u64 = 6_480_000;
+
+    pub fn require_not_frozen(&self, group: u8) -> Result<()> {
+        require!(self.frozen_groups & group == 0, PrivaxError::ParamsFrozen);
+        Ok(())
+    }
+
+    // For the direct forms of the AdminActions, which only run while there is no timelock.
+    pub fn require_no_admin_timelock(&self) -> Result<()> {
+        require!(self.admin_timelock_slots == 0, PrivaxError::AdminTimelockActive);
+        Ok(())
+    }
+
+    pub fn record_deposit(&mut self, amount: u64) -> Result<()> {
+        self.outstanding_deposits = self.outstanding_deposits.checked_add(amount).ok_or(PrivaxError::Overflow)?;
+        self.total_deposited = self.total_deposited.checked_add(amount).ok_or(PrivaxError::Overflow)?;
+        Ok(())
+    }
+
+    // `amount` is the note value leaving the pool: what the recipients and relayer got plus the
+    // protocol fee, which stays in the vault as accrued fees until distributed.
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        self.outstanding_deposits = self.outstanding_deposits.saturating_sub(amount);
+        self.total_withdrawn = self.total_withdrawn.checked_add(amount).ok_or(PrivaxError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn requires_attestation(&self) -> bool {
+        self.compliance_key != [0; 32]
+    }
+
+    pub fn requires_kyc(&self) -> bool {
+        self.kyc_attestor != Pubkey::default()
+    }
+
+    pub fn is_exclusion_publisher(&self, key: &Pubkey) -> bool {
+        *key == self.admin || (self.exclusion_operator != Pubkey::default() && *key == self.exclusion_operator)
+    }
+
+    // The exclusion root a spend proof bound to `proof_slot` was made against, when the pool
+    // requires one. Proofs made before the latest publish may still use the root it replaced,
+    // which MAX_PROOF_AGE_SLOTS keeps to a short window.
+    pub fn exclusion_root_at(&self, proof_slot: u64) -> Option<[u8; 32]> {
+        if !self.exclusion_required {
+            return None;
+        }
+        if proof_slot < self.exclusion_root_slot && self.previous_exclusion_root != [0; 32] {
+            Some(self.previous_exclusion_root)
+        } else {
+            Some(self.exclusion_root)
+        }
+    }
+
+    pub fn is_tree_maintainer(&self, key: &Pubkey) -> bool {
+        *key == self.admin || (self.tree_operator != Pubkey::default() && *key == self.tree_operator)
+    }
+
+    pub fn is_paused(&self, slot: u64) -> bool {
+        if !self.paused {
+            return false;
+        }
+        !self.renounced || slot < self.paused_at_slot.saturating_add(Self::PAUSE_EXPIRY_SLOTS_AFTER_RENOUNCE)
+    }
+
+    // A pause that has lasted max_pause_slots without the admin unpausing lets notes out again:
+    // withdrawals resume, while deposits and parameter changes stay blocked until an unpause.
+    pub fn is_exit_only(&self, slot: u64) -> bool {
+        self.max_pause_slots != 0
+            && self.is_paused(slot)
+            && slot >= self.paused_at_slot.saturating_add(self.max_pause_slots)
+    }
+
+    pub fn withdrawals_paused(&self, slot: u64) -> bool {
+        self.is_paused(slot) && !self.is_exit_only(slot)
+    }
+
+    pub fn require_not_exit_only(&self) -> Result<()> {
+        require!(!self.is_exit_only(Clock::get()?.slot), PrivaxError::ExitOnly);
+        Ok(())
+    }
+
+    // Counts `amount` toward the current epoch's withdrawal volume. A withdrawal that would take
+    // the volume past the limit fails without counting. Returns whether this one took the volume
+    // to the limit; the caller then trips the breaker.
+    pub fn admit_withdrawal(&mut self, amount: u64, slot: u64) -> Result<bool> {
+        if self.max_withdrawal_per_epoch == 0 {
+            return Ok(false);
+        }
+        let epoch = slot / self.withdrawal_epoch_slots;
+        if epoch != self.withdrawal_epoch {
+            self.withdrawal_epoch = epoch;
+            self.withdrawn_in_epoch = 0;
+        }
+        let withdrawn = self.withdrawn_in_epoch.checked_add(amount).ok_or(PrivaxError::WithdrawalLimitReached)?;
+        require!(withdrawn <= self.max_withdrawal_per_epoch, PrivaxError::WithdrawalLimitReached);
+        self.withdrawn_in_epoch = withdrawn;
+        Ok(withdrawn == self.max_withdrawal_per_epoch)
+    }
 }
 
-#[event]
-pub struct RelayerAdded {
-    relayer_address: Pubkey,
+// --- Fee Configuration Account ---
+// Kept apart from ProgramState so the fee authority can tune fees without being able to
+// touch relayer or verifier configuration.
+#[account]
+#[derive(Default)]
+pub struct FeeConfig {
+    pub fee_authority: Pubkey,         // May update this account (defaults to the admin at initialize)
+    pub protocol_fee_bps: u16,         // Protocol fee on withdrawals, in basis points
+    pub max_protocol_fee: u64,         // Cap on the protocol fee per withdrawal, in pool token units
+    pub fee_swap_program: Pubkey,      // Swap program allowed to convert relayer fees to SOL (default = disabled)
+    pub accrued_protocol_fees: u64,    // Protocol fees held in the vault and not yet distributed
+    pub referral_share_bps: u16,       // Share of a deposit's protocol fee credited to its referrer
+    pub owed_referral_fees: u64,       // Referral credit not yet claimed; reserved out of accrued fees
+    pub utilization_kink_bps: u16,     // Utilization above which the withdrawal fee starts rising
+    pub max_utilization_fee_bps: u16,  // Extra fee added at 100% utilization, on top of protocol_fee_bps
+    pub bump: u8,
+    pub beneficiaries: Vec<FeeBeneficiary>, // Recipients of distribute_fees, split by weight
 }
 
-#[event]
-pub struct RelayerRemoved {
-    relayer_address: Pubkey,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct FeeBeneficiary {
+    pub token_account: Pubkey, // Pool-mint token account receiving this share
+    pub weight: u16,
 }
 
-#[event]
-pub struct DepositOccurred {
-    user: Pubkey,
-    token_address: Pubkey, // Mint address of the token
-    amount: u64,
-    commitment: [u8; 32], // bytes32 commitment
+impl FeeConfig {
+    // Pubkey (fee_authority) = 32
+    // u16 (protocol_fee_bps) = 2
+    // u64 (max_protocol_fee) = 8
+    // Pubkey (fee_swap_program) = 32
+    // u64 (accrued_protocol_fees) = 8
+    // u16 (referral_share_bps) = 2
+    // u64 (owed_referral_fees) = 8
+    // u16 (utilization_kink_bps) = 2
+    // u16 (max_utilization_fee_bps) = 2
+    // u8 (bump) = 1
+    // Vec<FeeBeneficiary>: 4 + N * (32 + 2)
+    pub const MAX_BENEFICIARIES: usize = 5;
+    pub const SPACE: usize = 8 + 32 + 2 + 8 + 32 + 8 + 2 + 8 + 2 + 2 + 1 + (4 + Self::MAX_BENEFICIARIES * (32 + 2));
+    pub const MAX_PROTOCOL_FEE_BPS: u16 = 1_000; // 10%
+
+    // Utilization is the share of outstanding deposits not backed by liquid vault balance
+    // (non-zero once funds are deployed elsewhere). Past the kink, the fee rises linearly
+    // to protocol_fee_bps + max_utilization_fee_bps at full utilization.
+    pub fn fee_bps(&self, liquid: u64, liabilities: u64) -> u16 {
+        if liabilities == 0 || liquid >= liabilities {
+            return self.protocol_fee_bps;
+        }
+        let utilization_bps = ((liabilities - liquid) as u128 * 10_000 / liabilities as u128) as u16;
+        if utilization_bps <= self.utilization_kink_bps {
+            return self.protocol_fee_bps;
+        }
+        let extra = (utilization_bps - self.utilization_kink_bps) as u128 * self.max_utilization_fee_bps as u128
+            / (10_000 - self.utilization_kink_bps) as u128;
+        self.protocol_fee_bps.saturating_add(extra as u16)
+    }
+
+    pub fn protocol_fee(&self, amount: u64, fee_bps: u16) -> u64 {
+        let fee = (amount as u128) * (fee_bps as u128) / 10_000;
+        (fee as u64).min(self.max_protocol_fee)
+    }
+
+    // Withdrawals can't be linked back to deposits, so a referrer is credited up front with
+    // its share of the base protocol fee the deposited amount will pay on the way out.
+    pub fn referral_credit(&self, amount: u64) -> u64 {
+        let fee = self.protocol_fee(amount, self.protocol_fee_bps);
+        ((fee as u128) * (self.referral_share_bps as u128) / 10_000) as u64
+    }
 }
 
-#[event]
-pub struct WithdrawalOccurred {
-    nullifier_hash: [u8; 32], // bytes32 nullifierHash
-    recipient: Pubkey,
-    token_address: Pubkey, // Mint address of the token
-    amount: u64,
+// --- USD Limits Account ---
+// Limits and a fee set in USD rather than pool token units, so they keep their meaning as the
+// token's price moves: a cap on each deposit, a threshold above which a withdrawal is refused,
+// and a flat fee added to each withdrawal's protocol fee. They are converted at execution time
+// with the Pyth price of the pool mint. Amounts are micro-USD (USD_DECIMALS); 0 = not set.
+#[account]
+#[derive(Default)]
+pub struct UsdLimits {
+    pub price_feed: Pubkey,        // Pyth price account of the pool mint in USD
+    pub max_price_age: u64,        // Seconds since the price was published
+    pub max_confidence_bps: u16,   // Widest accepted confidence interval, relative to the price
+    pub mint_decimals: u8,
+    pub max_deposit_usd: u64,
+    pub large_withdrawal_usd: u64,
+    pub flat_fee_usd: u64,
+    pub bump: u8,
 }
 
-// --- Program Entry Point and Instructions ---
-#[program]
-pub mod privax_protocol {
-    use super::*; // Import items from parent module
+impl UsdLimits {
+    pub const USD_DECIMALS: u32 = 6;
 
-    pub const REQUIRED_PUBLIC_INPUTS_COUNT: usize = 5;
+    // Pubkey (price_feed) = 32
+    // u64 (max_price_age) = 8
+    // u16 (max_confidence_bps) = 2
+    // u8 (mint_decimals) = 1
+    // u64 (max_deposit_usd, large_withdrawal_usd, flat_fee_usd) = 8 each
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 32 + 8 + 2 + 1 + 8 * 3 + 1;
 
-    pub fn initialize(
-        ctx: Context<Initialize>,
-        token_mint_address: Pubkey,
-        verifier_program_id: Pubkey, // Placeholder
-    ) -> Result<()> {
-        let state = &mut ctx.accounts.program_state;
-        state.admin = *ctx.accounts.admin.key;
-        state.token_mint = token_mint_address;
-        state.verifier_program_id = verifier_program_id; // Store for potential future use
-        state.whitelisted_relayers = Vec::new();
-        state.bump = *ctx.bumps.get("program_state").unwrap();
+    pub fn is_set(&self) -> bool {
+        self.max_deposit_usd > 0 || self.large_withdrawal_usd > 0 || self.flat_fee_usd > 0
+    }
 
-        emit!(AdminChanged {
-            old_admin: Pubkey::default(), // System program as placeholder for "address(0)"
-            new_admin: state.admin,
-        });
-        Ok(())
+    // The current price from `price_feed`, which must be the configured account.
+    pub fn price(&self, price_feed: &AccountInfo, now: i64) -> Result<Price> {
+        require_keys_eq!(price_feed.key(), self.price_feed, PrivaxError::InvalidPriceFeed);
+        let feed = load_price_feed_from_account_info(price_feed).map_err(|_| PrivaxError::InvalidPriceFeed)?;
+        let price = feed.get_price_no_older_than(now, self.max_price_age).ok_or(PrivaxError::StalePrice)?;
+        require!(price.price > 0, PrivaxError::InvalidPriceFeed);
+        let max_conf = (price.price as u128) * (self.max_confidence_bps as u128) / 10_000;
+        require!(price.conf as u128 <= max_conf, PrivaxError::PriceTooUncertain);
+        Ok(price)
     }
 
-    pub fn add_relayer(ctx: Context<ManageRelayers>, relayer_address: Pubkey) -> Result<()> {
-        let state = &mut ctx.accounts.program_state;
-        require!(relayer_address != Pubkey::default(), PrivaxError::InvalidRelayerAddress);
-        require!(!state.whitelisted_relayers.contains(&relayer_address), PrivaxError::RelayerAlreadyWhitelisted);
-        
-        // Ensure we don't exceed max relayers if using a fixed-size Vec or check capacity
-        if state.whitelisted_relayers.len() >= ProgramState::MAX_RELAYERS {
-            // For showcase, we might just error out or handle it differently
-            return err!(ProgramError::AccountDataTooSmall); // Or a custom error
+    // Pool token units worth `usd` micro-USD at `price`, rounded down for limits and up for fees.
+    pub fn to_tokens(&self, usd: u64, price: &Price, round_up: bool) -> Result<u64> {
+        let pow10 = |exp: u32| 10u128.checked_pow(exp).ok_or(PrivaxError::Overflow);
+        let mut numerator = (usd as u128).checked_mul(pow10(self.mint_decimals.into())?).ok_or(PrivaxError::Overflow)?;
+        let mut denominator = (price.price as u128) * pow10(Self::USD_DECIMALS)?;
+        if price.expo < 0 {
+            numerator = numerator.checked_mul(pow10(price.expo.unsigned_abs())?).ok_or(PrivaxError::Overflow)?;
+        } else {
+            denominator = denominator.checked_mul(pow10(price.expo as u32)?).ok_or(PrivaxError::Overflow)?;
         }
-        state.whitelisted_relayers.push(relayer_address);
+        let tokens = match round_up {
+            true => numerator.div_ceil(denominator),
+            false => numerator / denominator,
+        };
+        Ok(u64::try_from(tokens).unwrap_or(u64::MAX))
+    }
 
-        emit!(RelayerAdded { relayer_address });
+    pub fn check_deposit(&self, amount: u64, price: &Price) -> Result<()> {
+        if self.max_deposit_usd > 0 {
+            require!(amount <= self.to_tokens(self.max_deposit_usd, price, false)?, PrivaxError::DepositCapExceeded);
+        }
         Ok(())
     }
 
-    pub fn remove_relayer(ctx: Context<ManageRelayers>, relayer_address: Pubkey) -> Result<()> {
-        let state = &mut ctx.accounts.program_state;
-        require!(state.whitelisted_relayers.contains(&relayer_address), PrivaxError::RelayerNotWhitelisted);
-        state.whitelisted_relayers.retain(|&x| x != relayer_address);
-
-        emit!(RelayerRemoved { relayer_address });
+    pub fn check_withdrawal(&self, amount: u64, price: &Price) -> Result<()> {
+        if self.large_withdrawal_usd > 0 {
+            require!(amount <= self.to_tokens(self.large_withdrawal_usd, price, false)?, PrivaxError::LargeWithdrawal);
+        }
         Ok(())
     }
 
-    pub fn transfer_ownership(ctx: Context<TransferOwnership>, new_admin: Pubkey) -> Result<()> {
-        let state = &mut ctx.accounts.program_state;
-        require!(new_admin != Pubkey::default(), PrivaxError::NewAdminIsZero);
-        
-        let old_admin = state.admin;
-        state.admin = new_admin;
-
-        emit!(AdminChanged { old_admin, new_admin });
-        Ok(())
+    pub fn flat_fee(&self, price: &Price) -> Result<u64> {
+        self.to_tokens(self.flat_fee_usd, price, true)
     }
+}
 
-    pub fn deposit(
-        ctx: Context<DepositTokens>,
-        amount: u64,
-        commitment: [u8; 32],
-    ) -> Result<()> {
-        require!(amount > 0, PrivaxError::AmountTooSmall);
+// --- Tree Checkpoints Account ---
+// Roots the tree operator pinned, with the leaf count and slot they were taken at. A client
+// or indexer rebuilding the tree from events can check itself against one without replaying
+// from genesis, and the pins outlive MERKLE_ROOT_HISTORY_SIZE. Oldest overwritten first.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct TreeCheckpoint {
+    pub leaf_count: u64,
+    pub root: [u8; 32],
+    pub slot: u64,
+}
 
-        // Transfer tokens from user to program's vault PDA
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.user_token_account.to_account_info(),
-            to: ctx.accounts.program_token_vault.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
+#[account]
+#[derive(Default)]
+pub struct TreeCheckpoints {
+    pub count: u64, // Checkpoints ever taken; the latest is at (count - 1) % MAX_TREE_CHECKPOINTS
+    pub checkpoints: [TreeCheckpoint; MAX_TREE_CHECKPOINTS],
+    pub bump: u8,
+}
 
-        emit!(DepositOccurred {
-            user: *ctx.accounts.user.key,
-            token_address: ctx.accounts.program_state.token_mint,
-            amount,
-            commitment,
-        });
+pub const MAX_TREE_CHECKPOINTS: usize = 16;
+
+impl TreeCheckpoints {
+    // u64 (count) = 8
+    // TreeCheckpoint (leaf_count, root, slot) = 8 + 32 + 8 each
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 8 + (8 + 32 + 8) * MAX_TREE_CHECKPOINTS + 1;
+
+    pub fn latest(&self) -> Option<&TreeCheckpoint> {
+        let count = usize::try_from(self.count).ok()?;
+        count.checked_sub(1).map(|last| &self.checkpoints[last % MAX_TREE_CHECKPOINTS])
+    }
+
+    pub fn push(&mut self, checkpoint: TreeCheckpoint) -> Result<()> {
+        self.checkpoints[self.count as usize % MAX_TREE_CHECKPOINTS] = checkpoint;
+        self.count = self.count.checked_add(1).ok_or(PrivaxError::Overflow)?;
         Ok(())
     }
+}
 
-    #[allow(unused_variables)] // For a_proof, b_proof, c_proof if verifier is placeholder
-    pub fn withdraw(
-        ctx: Context<WithdrawTokens>,
-        a_proof: Vec<u8>, // Placeholder for actual proof structure (e.g., [u64; 2])
-        b_proof: Vec<u8>, // Placeholder
-        c_proof: Vec<u8>, // Placeholder
-        public_inputs: Vec<u64>, // Assuming public inputs are u64 for simplicity
-        recipient_address: Pubkey,
-        amount_to_withdraw: u64,
-    ) -> Result<()> {
-        require!(amount_to_withdraw > 0, PrivaxError::AmountTooSmall);
-        require!(public_inputs.len() == REQUIRED_PUBLIC_INPUTS_COUNT, PrivaxError::InvalidPublicInputCount);
+// --- Audit Log Account ---
+// Every admin-gated change, kept in an account so the history doesn't depend on RPC nodes
+// holding on to transaction logs. An entry records who made the change, what it was and the
+// slot, plus hashes of the changed value before and after (audit_value_hash of its borsh
+// encoding), which anyone holding the values can check. Oldest overwritten first.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum AuditAction {
+    #[default]
+    AddRelayer,
+    RenewRelayer,
+    RemoveRelayer,
+    TransferOwnership,
+    InitiateRenounceAdmin,
+    CancelRenounceAdmin,
+    RenounceAdmin,
+    UpdateFeeConfig,
+    SetFeeAuthority,
+    SetFeeBeneficiaries,
+    SetUtilizationFeeCurve,
+    SetReferralShare,
+    SetFeeSwapProgram,
+    SetVerifierProgram,
+    SetProofSystem,
+    RegisterVerifyingKey,
+    WriteVerifyingKeyChunk,
+    SetVerifyingKeyEnabled,
+    FreezeParams,
+    SetGuardian,
+    SetTreeOperator,
+    Pause,
+    Unpause,
+    EnableNftMode,
+    SetReceiptNftConfig,
+    SetUsdLimits,
+    SetArchivedRootsAccepted,
+    SetWithdrawalLimit,
+    SetPauseOnShortfall,
+    SetMaxPauseDuration,
+    SetComplianceKey,
+    SetKycAttestor,
+    SetMaxDailyDeposit,
+    SetExclusionOperator,
+    SetExclusionRequired,
+    SetAdminTimelock,
+    QueueAdminAction,
+    CancelAdminAction,
+    AddSwapTarget,
+    RemoveSwapTarget,
+}
 
-        // Public inputs expected order (as u64 for this example):
-        // public_inputs[0]: merkleRoot (u64 representation)
-        // public_inputs[1]: nullifierHash (u64 representation of bytes32)
-        // public_inputs[2]: recipient (u64 representation of Pubkey)
-        // public_inputs[3]: amountToWithdraw (u64)
-        // public_inputs[4]: externalNullifier (u64, e.g., program_id as u64)
+#[zero_copy]
+pub struct AuditEntry {
+    pub actor: Pubkey,
+    pub slot: u64,
+    pub old_value_hash: [u8; 32],
+    pub new_value_hash: [u8; 32],
+    pub action: u8, // AuditAction
+    pub _padding: [u8; 7],
+}
 
-        // Validate recipient and amount from public inputs
-        // This requires careful conversion if Pubkey/amounts are not directly u64 in ZK circuit
-        // For showcase, we assume they are compatible or a conversion function exists.
-        // Example: Convert recipient_address to u64 for comparison (highly simplified)
-        let recipient_as_u64_bytes = recipient_address.to_bytes();
-        let mut recipient_u64_array = [0u8; 8];
-        recipient_u64_array.copy_from_slice(&recipient_as_u64_bytes[0..8]); // Highly simplified, not robust
-        let recipient_input_check = u64::from_le_bytes(recipient_u64_array);
+#[account(zero_copy)]
+pub struct AuditLog {
+    pub count: u64, // Entries ever recorded; the latest is at (count - 1) % MAX_AUDIT_ENTRIES
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    pub entries: [AuditEntry; MAX_AUDIT_ENTRIES],
+}
 
-        require!(recipient_input_check == public_inputs[2], PrivaxError::RecipientMismatch);
-        require!(amount_to_withdraw == public_inputs[3], PrivaxError::AmountMismatch);
+pub const MAX_AUDIT_ENTRIES: usize = 64;
 
-        // --- ZK Proof Verification Placeholder ---
-        // In a real contract, you would make a CPI to a verifier program.
-        // let cpi_accounts = VerifyProofAccounts { ... };
-        // let cpi_program = ctx.accounts.verifier_program.to_account_info();
-        // verify_zk_proof_cpi(CpiContext::new(cpi_program, cpi_accounts), proof_params)?;
-        // For showcase, we simulate a valid proof. Replace with actual CPI.
-        let is_valid_proof = true; // Placeholder
-        require!(is_valid_proof, PrivaxError::InvalidZkProof);
-        // --- End ZK Proof Verification Placeholder ---
+// sha256 of a value's borsh encoding, as audit entries hold it.
+pub fn audit_value_hash<T: AnchorSerialize + ?Sized>(value: &T) -> Result<[u8; 32]> {
+    Ok(anchor_lang::solana_program::hash::hash(&value.try_to_vec()?).to_bytes())
+}
 
-        // Extract nullifierHash (assuming it's public_inputs[1] and needs conversion to [u8; 32])
-        let nullifier_hash_u64 = public_inputs[1];
-        let nullifier_hash_bytes: [u8; 32] = unsafe { std::mem::transmute(nullifier_hash_u64.to_le_bytes().try_into().unwrap_or_else(|_| [0u8;32])) }; // Highly unsafe, for demo only
+impl AuditEntry {
+    pub fn new<T: AnchorSerialize + ?Sized, U: AnchorSerialize + ?Sized>(
+        actor: Pubkey,
+        action: AuditAction,
+        slot: u64,
+        old_value: &T,
+        new_value: &U,
+    ) -> Result<Self> {
+        Ok(Self {
+            actor,
+            slot,
+            old_value_hash: audit_value_hash(old_value)?,
+            new_value_hash: audit_value_hash(new_value)?,
+            action: action as u8,
+            _padding: [0; 7],
+        })
+    }
 
-        // Transfer tokens from program's vault to recipient
-        let seeds = &[b"program_token_vault".as_ref(), ctx.accounts.program_state.to_account_info().key.as_ref(), &[ctx.accounts.program_state.bump]];
-        let signer_seeds = &[&seeds[..]];
+    // None for a byte no AuditAction has.
+    pub fn action(&self) -> Option<AuditAction> {
+        AuditAction::try_from_slice(&[self.action]).ok()
+    }
+}
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.program_token_vault.to_account_info(),
-            to: ctx.accounts.recipient_token_account.to_account_info(),
-            authority: ctx.accounts.program_token_vault_authority.to_account_info(), // The PDA is the authority
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        token::transfer(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), amount_to_withdraw)?;
+impl AuditLog {
+    pub const SPACE: usize = 8 + std::mem::size_of::<AuditLog>();
 
-        emit!(WithdrawalOccurred {
-            nullifier_hash: nullifier_hash_bytes,
-            recipient: recipient_address,
-            token_address: ctx.accounts.program_state.token_mint,
-            amount: amount_to_withdraw,
-        });
+    // Retained entries, oldest first.
+    pub fn entries(&self) -> impl Iterator<Item = &AuditEntry> {
+        let count = self.count as usize;
+        let retained = count.min(MAX_AUDIT_ENTRIES);
+        (count - retained..count).map(move |i| &self.entries[i % MAX_AUDIT_ENTRIES])
+    }
+
+    pub fn push(&mut self, entry: AuditEntry) -> Result<()> {
+        self.entries[self.count as usize % MAX_AUDIT_ENTRIES] = entry;
+        self.count = self.count.checked_add(1).ok_or(PrivaxError::Overflow)?;
         Ok(())
     }
 }
 
-// --- Account Structs for Instructions ---
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(init, payer = admin, space = ProgramState::SPACE, seeds = [b"program_state"], bump)]
-    pub program_state: Account<'info, ProgramState>,
-    #[account(mut)]
-    pub admin: Signer<'info>,
-    pub system_program: Program<'info, System>,
+// --- Deposit Receipt Account ---
+// Optional on-chain proof that a deposit happened, for escrow-style integrators.
+// The depositor can close it to reclaim rent once it is no longer needed.
+#[account]
+#[derive(Default)]
+pub struct DepositReceipt {
+    pub depositor: Pubkey,
+    pub leaf_index: u64,
+    pub slot: u64,
+    pub amount: u64,
+    pub commitment: [u8; 32],
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct ManageRelayers<'info> {
-    #[account(mut, has_one = admin, seeds = [b"program_state"], bump = program_state.bump)]
-    pub program_state: Account<'info, ProgramState>,
-    pub admin: Signer<'info>,
+impl DepositReceipt {
+    // Pubkey (depositor) = 32
+    // u64 (leaf_index) = 8
+    // u64 (slot) = 8
+    // u64 (amount) = 8
+    // [u8; 32] (commitment) = 32
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 8 + 32 + 1;
 }
 
-#[derive(Accounts)]
-pub struct TransferOwnership<'info> {
-    #[account(mut, has_one = admin, seeds = [b"program_state"], bump = program_state.bump)]
-    pub program_state: Account<'info, ProgramState>,
-    pub admin: Signer<'info>,
+// --- Merkle Tree Account ---
+// Incremental Merkle tree of deposit commitments with a short history of recent roots.
+// Roots are numbered in the order the tree had them, the empty tree's being 0; root n sits
+// in roots[n % MERKLE_ROOT_HISTORY_SIZE] until it is overwritten.
+#[account(zero_copy)]
+pub struct MerkleTree {
+    pub next_index: u64, // Leaf index the next commitment takes
+    pub current_root_index: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    pub filled_subtrees: [[u8; 32]; MERKLE_TREE_DEPTH],
+    pub zeros: [[u8; 32]; MERKLE_TREE_DEPTH], // zeros[i] = root of an empty subtree of height i
+    pub roots: [[u8; 32]; MERKLE_ROOT_HISTORY_SIZE],
+    pub root_count: u64,     // Roots the tree has had, so the next root's number
+    pub archived_roots: u64, // Roots numbered below this have been through archive_roots
+    pub accept_archived_roots: u8, // bool; whether proofs may use an archived root
+    pub _reserved: [u8; 7],
 }
 
-#[derive(Accounts)]
-pub struct DepositTokens<'info> {
-    #[account(seeds = [b"program_state"], bump = program_state.bump)]
-    pub program_state: Account<'info, ProgramState>,
-    #[account(mut)] // User who is depositing
-    pub user: Signer<'info>,
-    #[account(mut, constraint = user_token_account.mint == program_state.token_mint)]
-    pub user_token_account: Account<'info, TokenAccount>,
-    #[account(
-        init_if_needed, // Initialize if it doesn't exist
-        payer = user,
-        token::mint = program_state.token_mint,
-        token::authority = program_token_vault_authority, // PDA will be authority
-        seeds = [b"program_token_vault", program_state.key().as_ref()], 
-        bump
-    )]
-    pub program_token_vault: Account<'info, TokenAccount>,
-    /// CHECK: This is the PDA authority for the program_token_vault, derived from program_state key.
-    #[account(seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
-    pub program_token_vault_authority: UncheckedAccount<'info>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+pub const MERKLE_TREE_DEPTH: usize = 20;
+pub const MERKLE_ROOT_HISTORY_SIZE: usize = 30;
+
+// Node hash: circomlib's Poseidon over BN254, as the circuits hash their Merkle paths, so the
+// program's roots are the roots proofs are made against. Nodes are 32 big-endian bytes;
+// commitments are checked canonical on deposit and Poseidon outputs always are.
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut poseidon = Poseidon::<Fr>::new_circom(2).expect("Poseidon takes two inputs");
+    let node = poseidon
+        .hash(&[Fr::from_be_bytes_mod_order(left), Fr::from_be_bytes_mod_order(right)])
+        .expect("two inputs, as configured");
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&node.into_bigint().to_bytes_be());
+    bytes
 }
 
-#[derive(Accounts)]
-pub struct WithdrawTokens<'info> {
-    #[account(seeds = [b"program_state"], bump = program_state.bump)]
-    pub program_state: Account<'info, ProgramState>,
-    #[account(mut)] // User initiating the withdrawal (signer of the transaction)
-    pub user: Signer<'info>,
-    #[account(mut, token::mint = program_state.token_mint, seeds = [b"program_token_vault", program_state.key().as_ref()], bump)] // program_token_vault.bump? No, use state bump for seed consistency
-    pub program_token_vault: Account<'info, TokenAccount>,
-    /// CHECK: This is the PDA authority for the program_token_vault
-    #[account(seeds = [b"program_token_vault", program_state.key().as_ref()], bump)] // This bump should be the one used to create the vault authority PDA
-    pub program_token_vault_authority: UncheckedAccount<'info>,
-    #[account(mut, token::mint = program_state.token_mint)] // Recipient's token account
-    pub recipient_token_account: Account<'info, TokenAccount>,
-    // pub verifier_program: UncheckedAccount<'info>, // For CPI to a verifier program
-    pub token_program: Program<'info, Token>,
-} 
\ No newline at end of file
+impl MerkleTree {
+    pub const SPACE: usize = 8 + std::mem::size_of::<MerkleTree>();
+    pub const MAX_SUBTREE_HEIGHT: usize = 4; // 16 leaves keeps deposit_subtree within one transaction
+
+    pub fn init(&mut self, bump: u8) {
+        let mut zero = [0u8; 32];
+        for height in 0..MERKLE_TREE_DEPTH {
+            self.zeros[height] = zero;
+            self.filled_subtrees[height] = zero;
+            zero = hash_pair(&zero, &zero);
+        }
+        self.roots[0] = zero;
+        self.root_count = 1;
+        self.bump = bump;
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.roots[self.current_root_index as usize]
+    }
+
+    pub fn is_known_root(&self, root: &[u8; 32]) -> bool {
+        root != &[0u8; 32] && self.roots.iter().any(|known| known == root)
+    }
+
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<u64> {
+        self.append_subtree(leaf, 0)
+    }
+
+    // Inserts a full subtree of 2^height leaves by its root and returns its first leaf index.
+    // The tree must be aligned to the subtree's width.
+    pub fn append_subtree(&mut self, subtree_root: [u8; 32], height: usize) -> Result<u64> {
+        let width = 1u64 << height;
+        require!(self.next_index & (width - 1) == 0, PrivaxError::MisalignedSubtree);
+        let end = self.next_index.checked_add(width).ok_or(PrivaxError::Overflow)?;
+        require!(end <= 1u64 << MERKLE_TREE_DEPTH, PrivaxError::MerkleTreeFull);
+
+        // One hash per level on the path to the root: empty siblings come from the zeros
+        // table filled at init, so nothing is recomputed per insert.
+        let first_index = self.next_index;
+        let mut index = first_index >> height;
+        let mut node = subtree_root;
+        for level in height..MERKLE_TREE_DEPTH {
+            node = if index & 1 == 0 {
+                self.filled_subtrees[level] = node;
+                hash_pair(&node, &self.zeros[level])
+            } else {
+                hash_pair(&self.filled_subtrees[level], &node)
+            };
+            index >>= 1;
+        }
+
+        self.current_root_index = (self.current_root_index + 1) % MERKLE_ROOT_HISTORY_SIZE as u64;
+        self.roots[self.current_root_index as usize] = node;
+        self.root_count = self.root_count.checked_add(1).ok_or(PrivaxError::Overflow)?;
+        self.next_index = end;
+
+        // Emitted here so no insertion path can change the root without indexers seeing it.
+        emit!(MerkleRootUpdated { version: EVENT_VERSION, first_leaf_index: first_index, leaf_count: width, new_root: node });
+        Ok(first_index)
+    }
+
+    // Root number `number`, while the history still holds it.
+    pub fn root_at(&self, number: u64) -> Option<[u8; 32]> {
+        let oldest = self.root_count.saturating_sub(MERKLE_ROOT_HISTORY_SIZE as u64);
+        (oldest..self.root_count).contains(&number).then(|| self.roots[(number % MERKLE_ROOT_HISTORY_SIZE as u64) as usize])
+    }
+
+    pub fn accepts_archived_roots(&self) -> bool {
+        self.accept_archived_roots != 0
+    }
+
+    // Whether a spend's Merkle root is a recent root or, when the tree accepts archived roots,
+    // one in `archive`. All 32 bytes must match: roots sharing their first 8 bytes are different roots.
+    pub fn accepts_root(&self, root: &[u8; 32], archive: Option<&RootArchivePage>) -> bool {
+        self.is_known_root(root) || (self.accepts_archived_roots() && archive.is_some_and(|page| page.contains(root)))
+    }
+
+    // Copies the roots not archived yet into `page`, which must be the archive's next page,
+    // up to the end of the page. Roots the history lost before they were archived are left
+    // zero in the page. Returns how many roots were archived and how many of those were lost.
+    pub fn archive_into(&mut self, page: &mut RootArchivePage, page_index: u64) -> Result<(u64, u64)> {
+        require!(self.archived_roots < self.root_count, PrivaxError::NothingToArchive);
+        require!(page_index == self.archived_roots / ROOT_ARCHIVE_PAGE_ROOTS as u64, PrivaxError::WrongArchivePage);
+        let page_start = page_index.checked_mul(ROOT_ARCHIVE_PAGE_ROOTS as u64).ok_or(PrivaxError::Overflow)?;
+        let end = self.root_count.min(page_start.checked_add(ROOT_ARCHIVE_PAGE_ROOTS as u64).ok_or(PrivaxError::Overflow)?);
+        let mut lost = 0;
+        for number in self.archived_roots..end {
+            match self.root_at(number) {
+                Some(root) => page.roots[(number % ROOT_ARCHIVE_PAGE_ROOTS as u64) as usize] = root,
+                None => lost += 1,
+            }
+        }
+        let archived = end.checked_sub(self.archived_roots).ok_or(PrivaxError::Overflow)?;
+        page.page = page_index;
+        page.count = end.checked_sub(page_start).ok_or(PrivaxError::Overflow)?;
+        self.archived_roots = end;
+        Ok((archived, lost))
+    }
+
+    // Root of a full subtree over `leaves`, whose length must be a power of two.
+    pub fn subtree_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+        let mut level = leaves.to_vec();
+        while level.len() > 1 {
+            level = level.chunks_exact(2).map(|pair| hash_pair(&pair[0], &pair[1])).collect();
+        }
+        level[0]
+    }
+}
+
+// --- Root Archive ---
+// Append-only pages of the tree's past roots, so a proof against a root that has left
+// MERKLE_ROOT_HISTORY_SIZE can still be checked when the tree accepts archived roots. Page n
+// holds roots n * ROOT_ARCHIVE_PAGE_ROOTS onwards; archive_roots fills them in order.
+#[account(zero_copy)]
+pub struct RootArchivePage {
+    pub page: u64,
+    pub count: u64, // Roots archived into this page so far
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    pub roots: [[u8; 32]; ROOT_ARCHIVE_PAGE_ROOTS], // Zero where a root was lost before archiving
+}
+
+pub const ROOT_ARCHIVE_PAGE_ROOTS: usize = 256;
+
+impl RootArchivePage {
+    pub const SPACE: usize = 8 + std::mem::size_of::<RootArchivePage>();
+
+    pub fn roots(&self) -> &[[u8; 32]] {
+        &self.roots[..self.count as usize]
+    }
+
+    pub fn contains(&self, root: &[u8; 32]) -> bool {
+        root != &[0u8; 32] && self.roots().contains(root)
+    }
+}
+
+// --- Verifying Key Account ---
+// One per circuit version, so notes created under an older circuit stay spendable after an
+// upgrade. Withdrawals name the circuit version their proof was generated for.
+//
+// Zero-copy so withdraw reads the header and only the key elements a verification step
+// needs, in place, instead of copying the whole key onto the heap. Keys larger than one
+// transaction are uploaded in chunks and only enabled once complete and validated.
+#[account(zero_copy)]
+pub struct VerifyingKey {
+    pub circuit_version: u16,
+    pub vk_len: u16,  // Declared length of vk_data
+    pub written: u16, // Bytes of vk_data uploaded so far
+    pub proof_system: u8, // ProofSystem
+    pub enabled: u8,  // bool; the admin can retire a circuit, e.g. if it turns out to be unsound
+    pub amount_range_bits: u8, // Width the circuit range-checks every note and withdrawal amount to
+    pub bump: u8,
+    pub vk_data: [u8; MAX_VK_LEN],
+}
+
+pub const MAX_VK_LEN: usize = 2048;
+
+impl VerifyingKey {
+    pub const SPACE: usize = 8 + std::mem::size_of::<VerifyingKey>();
+    // Variable-amount pools need every amount range-checked to exactly u64, so no value can
+    // wrap around the field (a "negative" note) and no amount exceeds what SPL tokens can hold.
+    pub const REQUIRED_AMOUNT_RANGE_BITS: u8 = 64;
+
+    pub fn proof_system(&self) -> ProofSystem {
+        if self.proof_system == ProofSystem::Plonk as u8 {
+            ProofSystem::Plonk
+        } else {
+            ProofSystem::Groth16
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled != 0
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.written == self.vk_len
+    }
+
+    pub fn vk_data(&self) -> &[u8] {
+        &self.vk_data[..usize::from(self.written)]
+    }
+
+    // What an audit entry hashes for a change to the key.
+    pub fn audit_value(&self) -> (u16, u8, u8, Vec<u8>) {
+        (self.circuit_version, self.proof_system, self.enabled, self.vk_data().to_vec())
+    }
+
+    // Appends the next chunk of vk_data. Once the last byte is in, the key is validated and
+    // enabled; returns whether that happened.
+    pub fn append_chunk(&mut self, chunk: &[u8]) -> Result<bool> {
+        let start = usize::from(self.written);
+        let end = start + chunk.len();
+        require!(end <= usize::from(self.vk_len), PrivaxError::VerifyingKeyTooLarge);
+        self.vk_data[start..end].copy_from_slice(chunk);
+        self.written = end as u16;
+        if !self.is_complete() {
+            return Ok(false);
+        }
+        // Single-note circuits up to the largest multi-note shape withdraw accepts, plus the
+        // exclusion root input of exclusion-aware circuits
+        let input_counts = privax_protocol::REQUIRED_PUBLIC_INPUTS_COUNT..=MAX_PUBLIC_INPUTS + 1;
+        match self.proof_system() {
+            ProofSystem::Groth16 => proof_format::check_groth16_vk(self.vk_data(), input_counts)?,
+            ProofSystem::Plonk => plonk::check_vk(self.vk_data(), input_counts)?,
+        }
+        self.enabled = 1;
+        Ok(true)
+    }
+}
+
+// --- Nullifier Accounts ---
+// Spent nullifiers are packed into shards keyed by their first two bytes instead of one PDA
+// each, so a withdrawal only pays rent for 32 more bytes rather than a whole account. The
+// 32-byte entries follow the header and are not part of the borsh layout.
+#[account]
+#[derive(Default)]
+pub struct NullifierShard {
+    pub prefix: [u8; 2],
+    pub count: u32,
+    pub bump: u8,
+}
+
+impl NullifierShard {
+    // [u8; 2] (prefix) = 2
+    // u32 (count) = 4
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 2 + 4 + 1;
+    pub const ENTRY_SIZE: usize = 32;
+
+    pub fn prefix_of(nullifier_hash: &[u8; 32]) -> [u8; 2] {
+        [nullifier_hash[0], nullifier_hash[1]]
+    }
+}
+
+// Every commitment deposited into the tree, sharded the same way. Two deposits of one
+// commitment would leave a single note able to spend only one of them.
+#[account]
+#[derive(Default)]
+pub struct CommitmentShard {
+    pub prefix: [u8; 2],
+    pub count: u32,
+    pub bump: u8,
+}
+
+impl CommitmentShard {
+    // [u8; 2] (prefix) = 2
+    // u32 (count) = 4
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 2 + 4 + 1;
+    pub const ENTRY_SIZE: usize = 32;
+
+    pub fn prefix_of(commitment: &[u8; 32]) -> [u8; 2] {
+        [commitment[0], commitment[1]]
+    }
+}
+
+// Bloom filter over every spent nullifier. Relayers read it to skip withdrawals that would
+// fail on the nullifier PDA; a hit only means "maybe spent", so the program never rejects on it.
+#[account(zero_copy)]
+pub struct NullifierFilter {
+    pub inserted: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    pub bits: [u8; 8192], // 65_536 bits, so each index is one u16
+}
+
+impl NullifierFilter {
+    pub const SPACE: usize = 8 + std::mem::size_of::<NullifierFilter>();
+    pub const HASH_COUNT: usize = 4;
+
+    // Bit positions for a nullifier: consecutive u16s of its sha256.
+    fn bit_indices(nullifier_hash: &[u8; 32]) -> [usize; Self::HASH_COUNT] {
+        let digest = anchor_lang::solana_program::hash::hash(nullifier_hash).to_bytes();
+        let mut indices = [0usize; Self::HASH_COUNT];
+        for (i, index) in indices.iter_mut().enumerate() {
+            *index = u16::from_le_bytes([digest[2 * i], digest[2 * i + 1]]) as usize;
+        }
+        indices
+    }
+
+    pub fn insert(&mut self, nullifier_hash: &[u8; 32]) {
+        for index in Self::bit_indices(nullifier_hash) {
+            self.bits[index / 8] |= 1 << (index % 8);
+        }
+        self.inserted = self.inserted.saturating_add(1);
+    }
+
+    pub fn might_contain(&self, nullifier_hash: &[u8; 32]) -> bool {
+        Self::bit_indices(nullifier_hash).iter().all(|&index| self.bits[index / 8] & (1 << (index % 8)) != 0)
+    }
+}
+
+// --- Pending Commitment Account ---
+// First half of a two-phase deposit: a commitment and amount registered for review, to be
+// funded later by a (possibly different) signer before it expires.
+#[account]
+#[derive(Default)]
+pub struct PendingCommitment {
+    pub registrant: Pubkey, // Paid the rent; receives it back when funded or reclaimed
+    pub commitment: [u8; 32],
+    pub amount: u64,
+    pub expiry_slot: u64,
+    pub bump: u8,
+}
+
+impl PendingCommitment {
+    // Pubkey (registrant) = 32
+    // [u8; 32] (commitment) = 32
+    // u64 (amount) = 8
+    // u64 (expiry_slot) = 8
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 32 + 32 + 8 + 8 + 1;
+    pub const TTL_SLOTS: u64 = 432_000; // ~2 days
+    // Paid to whoever cranks an expired registration, out of its reclaimed rent.
+    pub const CRANK_REWARD_LAMPORTS: u64 = 10_000;
+}
+
+// --- Compliance Attestation Account ---
+// Binds a deposit to an envelope of origin information encrypted to the pool's compliance key.
+// The envelope itself is kept off-chain; this holds its hash, and the deposit fills in where
+// the commitment landed.
+#[account]
+#[derive(Default)]
+pub struct ComplianceAttestation {
+    pub depositor: Pubkey,
+    pub commitment: [u8; 32],
+    pub envelope_hash: [u8; 32], // SHA-256 of the envelope
+    pub compliance_key: [u8; 32], // The key the envelope is encrypted to
+    pub leaf_index: u64,
+    pub deposit_slot: u64,   // 0 until the deposit lands
+    pub bump: u8,
+}
+
+impl ComplianceAttestation {
+    // Pubkey (depositor) = 32
+    // [u8; 32] (commitment, envelope_hash, compliance_key) = 32 each
+    // u64 (leaf_index) = 8
+    // u64 (deposit_slot) = 8
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 32 + 32 * 3 + 8 + 8 + 1;
+}
+
+// --- KYC Credential Account ---
+// Issued by the pool's KYC attestor to a wallet it has verified. Institutional pools only take
+// deposits from holders; revoking closes the account. Credentials are only valid under the
+// attestor that issued them, so replacing the attestor voids them all.
+#[account]
+#[derive(Default)]
+pub struct KycCredential {
+    pub holder: Pubkey,
+    pub attestor: Pubkey,
+    pub issued_at: i64,
+    pub expires_at: i64,     // Unix time the credential lapses (0 = never)
+    pub bump: u8,
+}
+
+impl KycCredential {
+    // Pubkey (holder, attestor) = 32 each
+    // i64 (issued_at, expires_at) = 8 each
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 32 * 2 + 8 * 2 + 1;
+
+    pub fn is_valid(&self, attestor: &Pubkey, now: i64) -> bool {
+        self.attestor == *attestor && (self.expires_at == 0 || now < self.expires_at)
+    }
+}
+
+// --- Deposit Volume Account ---
+// A depositor's deposits over the last day, in hourly buckets, for pools that cap how much one
+// address can deposit in 24 hours. The window rolls forward an hour at a time.
+#[account]
+#[derive(Default)]
+pub struct DepositVolume {
+    pub depositor: Pubkey,
+    pub hourly: [u64; 24],   // Volume per hour of the window, indexed by unix hour mod 24
+    pub last_hour: i64,      // Unix hour (time / 3600) of the latest deposit
+    pub bump: u8,
+}
+
+impl DepositVolume {
+    // Pubkey (depositor) = 32
+    // [u64; 24] (hourly) = 8 * 24
+    // i64 (last_hour) = 8
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 32 + 8 * Self::WINDOW_HOURS + 8 + 1;
+    pub const WINDOW_HOURS: usize = 24;
+
+    // Adds a deposit made at `now`, unless it would take the last 24 hours past `cap`.
+    pub fn record(&mut self, amount: u64, cap: u64, now: i64) -> Result<()> {
+        let hour = now.div_euclid(3600).max(self.last_hour);
+        if hour - self.last_hour >= Self::WINDOW_HOURS as i64 {
+            self.hourly = [0; Self::WINDOW_HOURS];
+        } else {
+            // Hours with no deposits since the last one
+            for stale in self.last_hour + 1..=hour {
+                self.hourly[stale.rem_euclid(Self::WINDOW_HOURS as i64) as usize] = 0;
+            }
+        }
+        self.last_hour = hour;
+        let total = self.hourly.iter().try_fold(amount, |sum, &volume| sum.checked_add(volume)).ok_or(PrivaxError::Overflow)?;
+        require!(total <= cap, PrivaxError::DailyDepositCapReached);
+        let current = &mut self.hourly[hour.rem_euclid(Self::WINDOW_HOURS as i64) as usize];
+        *current = current.checked_add(amount).ok_or(PrivaxError::Overflow)?;
+        Ok(())
+    }
+}
+
+// --- Referrer Account ---
+#[account]
+#[derive(Default)]
+pub struct ReferrerAccount {
+    pub referrer: Pubkey,
+    pub claimable: u64,     // Credited referral fees not yet claimed, in pool token units
+    pub total_claimed: u64,
+    pub bump: u8,
+}
+
+impl ReferrerAccount {
+    // Pubkey (referrer) = 32
+    // u64 (claimable) = 8
+    // u64 (total_claimed) = 8
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 1;
+}
+
+// --- Relayer Registry Account ---
+// Per-relayer PDA holding discovery metadata, so wallets can find relayers on-chain.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct FeeSchedule {
+    pub fee_bps: u16,  // Fee charged by the relayer, in basis points of the withdrawal amount
+    pub min_fee: u64,  // Floor applied to small withdrawals, in pool token units
+}
+
+#[account]
+#[derive(Default)]
+pub struct RelayerInfo {
+    pub relayer: Pubkey,
+    pub endpoint_url_hash: [u8; 32], // Hash of the relayer's API endpoint URL (URL itself is published off-chain)
+    pub fee_schedule: FeeSchedule,
+    pub expiry_slot: Option<u64>, // Whitelisting lapses at this slot unless renewed (None = no expiry)
+    pub bump: u8,
+}
+
+impl RelayerInfo {
+    // Pubkey (relayer) = 32
+    // [u8; 32] (endpoint_url_hash) = 32
+    // FeeSchedule = 2 + 8
+    // Option<u64> (expiry_slot) = 1 + 8
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 32 + 32 + (2 + 8) + (1 + 8) + 1;
+
+    pub fn is_active(&self, slot: u64) -> bool {
+        match self.expiry_slot {
+            Some(expiry) => slot < expiry,
+            None => true,
+        }
+    }
+}
+
+// --- Receipt NFT Config Account ---
+// Lets depositors who opened a DepositReceipt claim a compressed NFT for it, e.g. for loyalty
+// programs. The receipt NFTs go into a Bubblegum tree the admin creates and delegates to this
+// account. They are never needed to withdraw.
+#[account]
+#[derive(Default)]
+pub struct ReceiptNftConfig {
+    pub enabled: bool,
+    pub merkle_tree: Pubkey, // Bubblegum tree; this account must be its tree delegate
+    pub name: String,        // Each receipt is named "<name> #<leaf index>"
+    pub symbol: String,
+    pub uri: String,         // Off-chain JSON shared by every receipt
+    pub bump: u8,
+}
+
+impl ReceiptNftConfig {
+    // Token Metadata's limits, less room in the name for " #" and a leaf index below 2^20.
+    pub const MAX_NAME_LEN: usize = 22;
+    pub const MAX_SYMBOL_LEN: usize = 10;
+    pub const MAX_URI_LEN: usize = 200;
+
+    // bool (enabled) = 1
+    // Pubkey (merkle_tree) = 32
+    // String (name, symbol, uri) = 4 + length each
+    // u8 (bump) = 1
+    pub const SPACE: usize =
+        8 + 1 + 32 + (4 + Self::MAX_NAME_LEN) + (4 + Self::MAX_SYMBOL_LEN) + (4 + Self::MAX_URI_LEN) + 1;
+}
+
+// Marks a deposit receipt's NFT as claimed; its address is derived from the receipt's.
+#[account]
+#[derive(Default)]
+pub struct ReceiptNftClaim {
+    pub slot: u64,
+    pub bump: u8,
+}
+
+impl ReceiptNftClaim {
+    pub const SPACE: usize = 8 + 8 + 1;
+}
+
+// --- Payment Schedule Account ---
+// A recurring payment (e.g. monthly rent) paid from an escrow token account owned by the
+// schedule. The escrow is funded by withdrawing notes to it, so the payer's deposits stay
+// unlinked; anyone may execute a due payment and earn the executor fee.
+#[account]
+#[derive(Default)]
+pub struct PaymentSchedule {
+    pub owner: Pubkey,       // Creates, funds and cancels the schedule; best a fresh key
+    pub schedule_id: u64,    // Lets one owner run several schedules
+    pub recipient: Pubkey,   // Paid into its token account for the pool mint
+    pub amount: u64,         // Per payment, in pool token units
+    pub executor_fee: u64,   // Per payment, to whoever executes it, on top of `amount`
+    pub interval_slots: u64,
+    pub next_payment_slot: u64,
+    pub payments_remaining: u32,
+    pub bump: u8,
+}
+
+impl PaymentSchedule {
+    // Pubkey (owner) = 32
+    // u64 (schedule_id) = 8
+    // Pubkey (recipient) = 32
+    // u64 (amount, executor_fee, interval_slots, next_payment_slot) = 4 * 8
+    // u32 (payments_remaining) = 4
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 32 + 8 + 32 + 4 * 8 + 4 + 1;
+
+    pub fn is_due(&self, slot: u64) -> bool {
+        self.payments_remaining > 0 && slot >= self.next_payment_slot
+    }
+}
+
+// --- Queued Admin Action Account ---
+// Admin actions that hand over control of the pool, change which proofs it accepts or weaken
+// its safeguards. Once the admin sets a timelock, they only run through queue_admin_action
+// and, when the timelock has passed, execute_admin_action; until then the guardian (or the
+// admin) can cancel them.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AdminAction {
+    TransferOwnership { new_admin: Pubkey },
+    SetGuardian { new_guardian: Pubkey },
+    EnableVerifyingKey { circuit_version: u16 }, // Keys completed under a timelock start disabled
+    SetAdminTimelock { slots: u64 },             // Raising it needs no queue, lowering it does
+    AddSwapTarget { target_program: Pubkey },    // Removing one needs no queue
+    // Turning these safeguards on or tightening them needs no queue; loosening, changing or
+    // turning them off does
+    SetWithdrawalLimit { max_withdrawal_per_epoch: u64, withdrawal_epoch_slots: u64 },
+    SetComplianceKey { compliance_key: [u8; 32] },
+    SetKycAttestor { kyc_attestor: Pubkey },
+    SetExclusionRequired { exclusion_required: bool },
+}
+
+impl AdminAction {
+    // u8 (variant) = 1, largest payload Pubkey = 32
+    pub const SPACE: usize = 1 + 32;
+}
+
+#[account]
+pub struct QueuedAdminAction {
+    pub id: u64,
+    pub action: AdminAction,
+    pub payer: Pubkey,  // Paid the rent; receives it back when the action runs or is cancelled
+    pub eta_slot: u64,  // First slot execute_admin_action accepts
+    pub bump: u8,
+}
+
+impl QueuedAdminAction {
+    // u64 (id) = 8
+    // AdminAction (action) = AdminAction::SPACE
+    // Pubkey (payer) = 32
+    // u64 (eta_slot) = 8
+    // u8 (bump) = 1
+    pub const SPACE: usize = 8 + 8 + AdminAction::SPACE + 32 + 8 + 1;
+}
+
+// --- Events (emitted via `emit!`) ---
+// Every event leads with this; bump it whenever any event's fields change so indexers can
+// pick the matching layout. Events emitted before versioning have no version field (v0).
+// v2 ends every event but MerkleRootUpdated with the Clock's slot and unix_timestamp.
+pub const EVENT_VERSION: u8 = 2;
+
+#[event]
+pub struct AdminChanged {
+    version: u8,
+    old_admin: Pubkey,
+    new_admin: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct RelayerAdded {
+    version: u8,
+    relayer_address: Pubkey,
+    endpoint_url_hash: [u8; 32],
+    fee_schedule: FeeSchedule,
+    expiry_slot: Option<u64>,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct RelayerRenewed {
+    version: u8,
+    relayer_address: Pubkey,
+    expiry_slot: Option<u64>,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct RelayerRemoved {
+    version: u8,
+    relayer_address: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct FeeConfigUpdated {
+    version: u8,
+    fee_authority: Pubkey,
+    protocol_fee_bps: u16,
+    max_protocol_fee: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct FeeBeneficiariesUpdated {
+    version: u8,
+    beneficiaries: Vec<FeeBeneficiary>,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct FeesDistributed {
+    version: u8,
+    total: u64,
+    shares: Vec<u64>, // Amount paid to each beneficiary, in configured order
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct ReferralAccrued {
+    version: u8,
+    referrer: Pubkey,
+    amount: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct ReferralFeesClaimed {
+    version: u8,
+    referrer: Pubkey,
+    amount: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct FeeSwapProgramChanged {
+    version: u8,
+    old_program: Pubkey,
+    new_program: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct RelayerFeePaid {
+    version: u8,
+    relayer: Pubkey,
+    fee_mint: Pubkey,  // Mint the relayer was paid in (pool mint, or native mint when swapped)
+    fee_amount: u64,   // Fee taken from the withdrawal, in pool token units
+    amount_paid: u64,  // Amount actually received by the relayer, in fee_mint units
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalLimitUpdated {
+    version: u8,
+    max_withdrawal_per_epoch: u64,
+    withdrawal_epoch_slots: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+// The circuit breaker paused the pool after a withdrawal of `amount` took the epoch's volume to
+// the limit.
+#[event]
+pub struct WithdrawalLimitTripped {
+    version: u8,
+    amount: u64,
+    withdrawn_in_epoch: u64,
+    max_withdrawal_per_epoch: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+// `reconcile` found the vault balance differing from what the counters account for. A surplus
+// is benign (tokens sent to the vault directly, swaps spending less than allowed); a shortfall
+// means tokens left the vault without being accounted for.
+#[event]
+pub struct SolvencyMismatch {
+    version: u8,
+    expected: u64,      // total_deposited - total_withdrawn + accrued_protocol_fees
+    actual: u64,        // Vault balance
+    total_deposited: u64,
+    total_withdrawn: u64,
+    accrued_protocol_fees: u64,
+    paused: bool,       // Whether this call paused the pool
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct MaxPauseDurationUpdated {
+    version: u8,
+    max_pause_slots: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct PauseOnShortfallUpdated {
+    version: u8,
+    enabled: bool,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct TreeOperatorChanged {
+    version: u8,
+    old_tree_operator: Pubkey,
+    new_tree_operator: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct TreeCheckpointed {
+    version: u8,
+    operator: Pubkey,
+    leaf_count: u64,
+    root: [u8; 32],
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct RootsArchived {
+    version: u8,
+    page: u64,
+    first_root: u64, // Number of the first root archived
+    count: u64,
+    lost: u64, // Of those, how many had left the history before they could be archived
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct ArchivedRootsAcceptedChanged {
+    version: u8,
+    accept: bool,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct GuardianChanged {
+    version: u8,
+    old_guardian: Pubkey,
+    new_guardian: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct AdminTimelockChanged {
+    version: u8,
+    old_slots: u64,
+    new_slots: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct AdminActionQueued {
+    version: u8,
+    id: u64,
+    action: AdminAction,
+    eta_slot: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct AdminActionExecuted {
+    version: u8,
+    id: u64,
+    action: AdminAction,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct AdminActionCancelled {
+    version: u8,
+    id: u64,
+    action: AdminAction,
+    by: Pubkey, // The admin or the guardian
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct PauseToggled {
+    version: u8,
+    paused: bool,
+    by: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct VerifierProgramChanged {
+    version: u8,
+    old_program: Pubkey,
+    new_program: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct ProofSystemChanged {
+    version: u8,
+    old_proof_system: ProofSystem,
+    new_proof_system: ProofSystem,
+    verifier_program_id: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct VerifyingKeyRegistered {
+    version: u8,
+    circuit_version: u16,
+    proof_system: ProofSystem,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct VerifyingKeyEnabledChanged {
+    version: u8,
+    circuit_version: u16,
+    enabled: bool,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct ParamGroupsFrozen {
+    version: u8,
+    groups: u8,          // Groups frozen by this call
+    frozen_groups: u8,   // All frozen groups after this call
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct AdminRenounceInitiated {
+    version: u8,
+    admin: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct CommitmentRegistered {
+    version: u8,
+    registrant: Pubkey,
+    commitment: [u8; 32],
+    amount: u64,
+    expiry_slot: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct CommitmentRegistrationClosed {
+    version: u8,
+    commitment: [u8; 32],
+    funded: bool, // false when reclaimed after expiry
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct Cranked {
+    version: u8,
+    cranker: Pubkey,
+    processed: u32,
+    reward: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct PaymentScheduleCreated {
+    version: u8,
+    schedule: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+    interval_slots: u64,
+    first_payment_slot: u64,
+    payments: u32,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct ScheduledPaymentExecuted {
+    version: u8,
+    schedule: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+    executor: Pubkey,
+    executor_fee: u64,
+    payments_remaining: u32,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct PaymentScheduleClosed {
+    version: u8,
+    schedule: Pubkey,
+    refunded: u64, // Escrow balance returned to the owner
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+// Emitted by the tree itself, which doesn't read the Clock; the deposit or change note events
+// logged alongside it carry the time.
+#[event]
+pub struct MerkleRootUpdated {
+    version: u8,
+    first_leaf_index: u64,
+    leaf_count: u64, // Leaves inserted by this update: 1, or the width of a subtree
+    new_root: [u8; 32],
+}
+
+#[event]
+pub struct NullifierSpent {
+    version: u8,
+    nullifier_hash: [u8; 32],
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+// A note created without a deposit: withdraw's change, or an output of split_note or merge_notes.
+#[event]
+pub struct ChangeNoteCommitted {
+    version: u8,
+    commitment: [u8; 32],
+    leaf_index: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct DepositOccurred {
+    version: u8,
+    user: Pubkey,
+    token_address: Pubkey, // Mint address of the token
+    amount: u64,
+    commitment: [u8; 32], // bytes32 commitment
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+// A note encrypted for its recipient, e.g. a merchant's viewing key, announced alongside the
+// deposit of its commitment. The program doesn't read the ciphertext.
+#[event]
+pub struct NoteAnnounced {
+    version: u8,
+    commitment: [u8; 32],
+    ciphertext: Vec<u8>,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+// Fits an ephemeral key, a tag and a note with a short memo, and a deposit in the same transaction.
+pub const MAX_NOTE_CIPHERTEXT_LEN: usize = 320;
+
+#[event]
+pub struct ComplianceKeyUpdated {
+    version: u8,
+    compliance_key: [u8; 32],
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct DepositAttested {
+    version: u8,
+    depositor: Pubkey,
+    commitment: [u8; 32],
+    envelope_hash: [u8; 32],
+    compliance_key: [u8; 32],
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct KycAttestorUpdated {
+    version: u8,
+    kyc_attestor: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct KycCredentialIssued {
+    version: u8,
+    holder: Pubkey,
+    attestor: Pubkey,
+    expires_at: i64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct KycCredentialRevoked {
+    version: u8,
+    holder: Pubkey,
+    attestor: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct MaxDailyDepositUpdated {
+    version: u8,
+    max_daily_deposit: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct ExclusionOperatorUpdated {
+    version: u8,
+    exclusion_operator: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct ExclusionRootPublished {
+    version: u8,
+    publisher: Pubkey,
+    root: [u8; 32],
+    previous_root: [u8; 32],
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct ExclusionRequiredUpdated {
+    version: u8,
+    exclusion_required: bool,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalOccurred {
+    version: u8,
+    nullifier_hash: [u8; 32], // bytes32 nullifierHash
+    recipient: Pubkey,
+    token_address: Pubkey, // Mint address of the token
+    amount: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+// One recipient of a split withdrawal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Payout {
+    pub recipient: Pubkey, // Owner of the pool-mint token account paid
+    pub amount: u64,       // Before the protocol fee, as for a withdrawal of this amount
+}
+
+#[event]
+pub struct SplitWithdrawalOccurred {
+    version: u8,
+    nullifier_hash: [u8; 32],
+    token_address: Pubkey, // Mint address of the token
+    payouts: Vec<Payout>,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+// Spends a note here and deposits a note in another pool (see shielded_swap).
+#[event]
+pub struct ShieldedSwapOccurred {
+    version: u8,
+    nullifier_hash: [u8; 32],
+    target_program: Pubkey, // Pool the output note went into
+    amount_in: u64,         // Input note, in this pool's mint
+    amount_out: u64,        // Output note, in the target pool's mint
+    output_commitment: [u8; 32],
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct SwapTargetAdded {
+    version: u8,
+    target_program: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct SwapTargetRemoved {
+    version: u8,
+    target_program: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct UsdLimitsUpdated {
+    version: u8,
+    price_feed: Pubkey,
+    max_deposit_usd: u64,
+    large_withdrawal_usd: u64,
+    flat_fee_usd: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct ReceiptNftConfigUpdated {
+    version: u8,
+    enabled: bool,
+    merkle_tree: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct ReceiptNftMinted {
+    version: u8,
+    depositor: Pubkey,
+    leaf_index: u64, // The deposit's, in the pool's tree
+    merkle_tree: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct PoolModeChanged {
+    version: u8,
+    pool_mode: PoolMode,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+// The mint is public at both ends of an NFT's stay in the pool; only the link between the
+// depositor and the withdrawal's recipient is hidden.
+#[event]
+pub struct NftDepositOccurred {
+    version: u8,
+    user: Pubkey,
+    mint: Pubkey,
+    commitment: [u8; 32],
+    leaf_index: u64,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+#[event]
+pub struct NftWithdrawalOccurred {
+    version: u8,
+    nullifier_hash: [u8; 32],
+    recipient: Pubkey,
+    mint: Pubkey,
+    slot: u64,
+    unix_timestamp: i64,
+}
+
+// withdraw's fixed-size public input array: the eight single-note inputs, up to
+// MAX_INPUT_NOTES - 1 further nullifiers and a change commitment. Unused slots are zero.
+pub const MAX_PUBLIC_INPUTS: usize = 12;
+// shielded_swap's public inputs; the layout is in its body.
+pub const SWAP_PUBLIC_INPUTS: usize = 8;
+// Most recipients one withdraw_split proof can pay, and its public inputs: seven fixed ones,
+// then a (recipient, amount) pair per recipient. A literal, since the IDL can't size arrays
+// with an expression.
+pub const MAX_SPLIT_RECIPIENTS: usize = 4;
+pub const SPLIT_PUBLIC_INPUTS: usize = 15;
+const _: () = assert!(SPLIT_PUBLIC_INPUTS == 7 + 2 * MAX_SPLIT_RECIPIENTS);
+// split_note and merge_notes share a public input count: root, externalNullifier and proofSlot,
+// plus one nullifier and four output commitments, or four nullifiers and one output commitment.
+pub const MAX_NOTE_SPLIT_OUTPUTS: usize = 4;
+pub const NOTE_TRANSFER_PUBLIC_INPUTS: usize = 8;
+// withdraw_nft's public inputs; the layout is in its body.
+pub const NFT_PUBLIC_INPUTS: usize = 6;
+
+// --- Program Entry Point and Instructions ---
+#[program]
+pub mod privax_protocol {
+    use super::*; // Import items from parent module
+
+    pub const REQUIRED_PUBLIC_INPUTS_COUNT: usize = 8;
+    // Most notes a single withdraw proof can spend (one nullifier each).
+    pub const MAX_INPUT_NOTES: usize = 4;
+    // Proofs commit to the slot they were generated at and go stale after this many slots
+    // (~10 minutes), so they can't be stockpiled and submitted under later fee conditions.
+    pub const MAX_PROOF_AGE_SLOTS: u64 = 1_500;
+    // Pools take any amount; denominated pools would pass their fixed note size instead.
+    pub const VARIABLE_DENOMINATION: u64 = 0;
+    // Must be passed to both renounce steps, so renouncing can't happen by accident.
+    pub const RENOUNCE_ADMIN_CONFIRMATION: u64 = 0x5245_4E4F_554E_4345; // "RENOUNCE"
+
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        token_mint_address: Pubkey,
+        verifier_program_id: Pubkey, // Placeholder
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.admin = *ctx.accounts.admin.key;
+        state.token_mint = token_mint_address;
+        state.verifier_program_id = verifier_program_id; // Store for potential future use
+        state.whitelisted_relayers = Vec::new();
+        state.guardian = Pubkey::default();
+        state.tree_operator = Pubkey::default();
+        state.paused = false;
+        state.renounced = false;
+        state.proof_system = ProofSystem::Groth16;
+        state.bump = *ctx.bumps.get("program_state").unwrap();
+
+        let fee_config = &mut ctx.accounts.fee_config;
+        fee_config.fee_authority = state.admin;
+        fee_config.protocol_fee_bps = 0;
+        fee_config.max_protocol_fee = 0;
+        fee_config.fee_swap_program = Pubkey::default();
+        fee_config.beneficiaries = Vec::new();
+        fee_config.bump = *ctx.bumps.get("fee_config").unwrap();
+
+        ctx.accounts.nullifier_filter.load_init()?.bump = *ctx.bumps.get("nullifier_filter").unwrap();
+        ctx.accounts.merkle_tree.load_init()?.init(*ctx.bumps.get("merkle_tree").unwrap());
+        ctx.accounts.audit_log.load_init()?.bump = *ctx.bumps.get("audit_log").unwrap();
+
+        let clock = Clock::get()?;
+        emit!(AdminChanged {
+            version: EVENT_VERSION,
+            old_admin: Pubkey::default(), // System program as placeholder for "address(0)"
+            new_admin: state.admin,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Permissionless: creates the audit log for a pool initialized before there was one. Until
+    // it exists, every admin-gated instruction fails on the missing account.
+    pub fn init_audit_log(ctx: Context<InitAuditLog>) -> Result<()> {
+        ctx.accounts.audit_log.load_init()?.bump = *ctx.bumps.get("audit_log").unwrap();
+        Ok(())
+    }
+
+    pub fn add_relayer(
+        ctx: Context<AddRelayer>,
+        relayer_address: Pubkey,
+        endpoint_url_hash: [u8; 32],
+        fee_schedule: FeeSchedule,
+        expiry_slot: Option<u64>,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        state.require_not_frozen(ProgramState::PARAM_GROUP_RELAYERS)?;
+        require!(relayer_address != Pubkey::default(), PrivaxError::InvalidRelayerAddress);
+        require!(!state.whitelisted_relayers.contains(&relayer_address), PrivaxError::RelayerAlreadyWhitelisted);
+        
+        require!(state.whitelisted_relayers.len() < ProgramState::MAX_RELAYERS, PrivaxError::TooManyRelayers);
+        state.whitelisted_relayers.push(relayer_address);
+
+        let info = &mut ctx.accounts.relayer_info;
+        info.relayer = relayer_address;
+        info.endpoint_url_hash = endpoint_url_hash;
+        info.fee_schedule = fee_schedule;
+        info.expiry_slot = expiry_slot;
+        info.bump = *ctx.bumps.get("relayer_info").unwrap();
+        let actor = ctx.accounts.admin.key();
+        record_audit(&ctx.accounts.audit_log, actor, AuditAction::AddRelayer, &None::<RelayerInfo>, &Some(info.clone().into_inner()))?;
+
+        let clock = Clock::get()?;
+        emit!(RelayerAdded {
+            version: EVENT_VERSION,
+            relayer_address,
+            endpoint_url_hash,
+            fee_schedule,
+            expiry_slot,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn renew_relayer(ctx: Context<RenewRelayer>, relayer_address: Pubkey, expiry_slot: Option<u64>) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_RELAYERS)?;
+        let old_info = Some(ctx.accounts.relayer_info.clone().into_inner());
+        ctx.accounts.relayer_info.expiry_slot = expiry_slot;
+        let new_info = Some(ctx.accounts.relayer_info.clone().into_inner());
+        record_audit(&ctx.accounts.audit_log, ctx.accounts.admin.key(), AuditAction::RenewRelayer, &old_info, &new_info)?;
+
+        let clock = Clock::get()?;
+        emit!(RelayerRenewed {
+            version: EVENT_VERSION,
+            relayer_address,
+            expiry_slot,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn remove_relayer(ctx: Context<RemoveRelayer>, relayer_address: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        state.require_not_frozen(ProgramState::PARAM_GROUP_RELAYERS)?;
+        require!(state.whitelisted_relayers.contains(&relayer_address), PrivaxError::RelayerNotWhitelisted);
+        state.whitelisted_relayers.retain(|&x| x != relayer_address);
+        let old_info = Some(ctx.accounts.relayer_info.clone().into_inner());
+        record_audit(&ctx.accounts.audit_log, ctx.accounts.admin.key(), AuditAction::RemoveRelayer, &old_info, &None::<RelayerInfo>)?;
+
+        let clock = Clock::get()?;
+        emit!(RelayerRemoved { version: EVENT_VERSION, relayer_address, slot: clock.slot, unix_timestamp: clock.unix_timestamp });
+        Ok(())
+    }
+
+    pub fn transfer_ownership(ctx: Context<TransferOwnership>, new_admin: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        state.require_no_admin_timelock()?;
+        change_admin(state, &ctx.accounts.audit_log, new_admin)
+    }
+
+    // Step 1 of 2: record the intent to renounce. Nothing changes until renounce_admin.
+    pub fn initiate_renounce_admin(ctx: Context<UpdateConfig>, confirmation: u64) -> Result<()> {
+        require!(confirmation == RENOUNCE_ADMIN_CONFIRMATION, PrivaxError::InvalidRenounceConfirmation);
+        let state = &mut ctx.accounts.program_state;
+        let clock = Clock::get()?;
+        let slot = clock.slot;
+        let old_slot = state.renounce_initiated_slot;
+        state.renounce_initiated_slot = slot;
+        record_audit(&ctx.accounts.audit_log, state.admin, AuditAction::InitiateRenounceAdmin, &old_slot, &slot)?;
+
+        emit!(AdminRenounceInitiated { version: EVENT_VERSION, admin: state.admin, slot, unix_timestamp: clock.unix_timestamp });
+        Ok(())
+    }
+
+    pub fn cancel_renounce_admin(ctx: Context<UpdateConfig>) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let old_slot = state.renounce_initiated_slot;
+        state.renounce_initiated_slot = 0;
+        record_audit(&ctx.accounts.audit_log, state.admin, AuditAction::CancelRenounceAdmin, &old_slot, &0u64)?;
+        Ok(())
+    }
+
+    // Step 2 of 2: permanently drop the admin. Every admin-gated instruction becomes
+    // uncallable, and any pause (even one set by the guardian) expires on its own. The guardian
+    // goes with the admin, so it can't pause again each time a pause expires. Every parameter
+    // group is frozen too, so the fee authority can't change fees after the admin is gone.
+    pub fn renounce_admin(ctx: Context<UpdateConfig>, confirmation: u64) -> Result<()> {
+        require!(confirmation == RENOUNCE_ADMIN_CONFIRMATION, PrivaxError::InvalidRenounceConfirmation);
+        let state = &mut ctx.accounts.program_state;
+        let clock = Clock::get()?;
+        require!(
+            state.renounce_initiated_slot != 0 && clock.slot > state.renounce_initiated_slot,
+            PrivaxError::RenounceNotInitiated
+        );
+
+        let old_admin = state.admin;
+        state.admin = Pubkey::default();
+        state.renounced = true;
+        state.renounce_initiated_slot = 0;
+        state.frozen_groups = ProgramState::PARAM_GROUP_ALL;
+        let old_guardian = state.guardian;
+        state.guardian = Pubkey::default();
+        record_audit(&ctx.accounts.audit_log, old_admin, AuditAction::RenounceAdmin, &old_admin, &state.admin)?;
+
+        emit!(AdminChanged {
+            version: EVENT_VERSION,
+            old_admin,
+            new_admin: Pubkey::default(),
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        if old_guardian != Pubkey::default() {
+            emit!(GuardianChanged {
+                version: EVENT_VERSION,
+                old_guardian,
+                new_guardian: Pubkey::default(),
+                slot: clock.slot,
+                unix_timestamp: clock.unix_timestamp,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn update_fee_config(ctx: Context<UpdateFeeConfig>, protocol_fee_bps: u16, max_protocol_fee: u64) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_FEES)?;
+        require!(protocol_fee_bps <= FeeConfig::MAX_PROTOCOL_FEE_BPS, PrivaxError::FeeBpsTooHigh);
+        let fee_config = &mut ctx.accounts.fee_config;
+        let old_value = (fee_config.protocol_fee_bps, fee_config.max_protocol_fee);
+        fee_config.protocol_fee_bps = protocol_fee_bps;
+        fee_config.max_protocol_fee = max_protocol_fee;
+        let new_value = (protocol_fee_bps, max_protocol_fee);
+        let actor = ctx.accounts.fee_authority.key();
+        record_audit(&ctx.accounts.audit_log, actor, AuditAction::UpdateFeeConfig, &old_value, &new_value)?;
+
+        let clock = Clock::get()?;
+        emit!(FeeConfigUpdated {
+            version: EVENT_VERSION,
+            fee_authority: fee_config.fee_authority,
+            protocol_fee_bps,
+            max_protocol_fee,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn set_fee_authority(ctx: Context<UpdateFeeConfig>, new_fee_authority: Pubkey) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_FEES)?;
+        let fee_config = &mut ctx.accounts.fee_config;
+        let old_fee_authority = fee_config.fee_authority;
+        fee_config.fee_authority = new_fee_authority;
+        let audit_log = &ctx.accounts.audit_log;
+        record_audit(audit_log, old_fee_authority, AuditAction::SetFeeAuthority, &old_fee_authority, &new_fee_authority)?;
+
+        let clock = Clock::get()?;
+        emit!(FeeConfigUpdated {
+            version: EVENT_VERSION,
+            fee_authority: new_fee_authority,
+            protocol_fee_bps: fee_config.protocol_fee_bps,
+            max_protocol_fee: fee_config.max_protocol_fee,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn set_fee_beneficiaries(ctx: Context<UpdateFeeConfig>, beneficiaries: Vec<FeeBeneficiary>) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_FEES)?;
+        let total_weight: u32 = beneficiaries.iter().map(|b| b.weight as u32).sum();
+        require!(
+            beneficiaries.len() <= FeeConfig::MAX_BENEFICIARIES && total_weight > 0,
+            PrivaxError::InvalidFeeBeneficiaries
+        );
+        let actor = ctx.accounts.fee_authority.key();
+        let old_beneficiaries = &ctx.accounts.fee_config.beneficiaries;
+        record_audit(&ctx.accounts.audit_log, actor, AuditAction::SetFeeBeneficiaries, old_beneficiaries, &beneficiaries)?;
+        ctx.accounts.fee_config.beneficiaries = beneficiaries.clone();
+
+        let clock = Clock::get()?;
+        emit!(FeeBeneficiariesUpdated {
+            version: EVENT_VERSION,
+            beneficiaries,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Permissionless: pays accrued protocol fees out of the vault to the configured
+    // beneficiaries, whose token accounts are passed as remaining accounts in order.
+    pub fn distribute_fees<'info>(ctx: Context<'_, '_, '_, 'info, DistributeFees<'info>>) -> Result<()> {
+        // Fees owed to referrers stay in the vault for claim_referral_fees.
+        let fee_config = &ctx.accounts.fee_config;
+        let total = fee_config.accrued_protocol_fees.saturating_sub(fee_config.owed_referral_fees);
+        require!(total > 0, PrivaxError::NothingToDistribute);
+        let beneficiaries = ctx.accounts.fee_config.beneficiaries.clone();
+        require!(!beneficiaries.is_empty(), PrivaxError::InvalidFeeBeneficiaries);
+        require!(ctx.remaining_accounts.len() == beneficiaries.len(), PrivaxError::BeneficiaryAccountMismatch);
+        let total_weight: u128 = beneficiaries.iter().map(|b| b.weight as u128).sum();
+
+        let program_state_key = ctx.accounts.program_state.key();
+        let vault_authority_bump = *ctx.bumps.get("program_token_vault_authority").unwrap();
+        let seeds = &[b"program_token_vault".as_ref(), program_state_key.as_ref(), &[vault_authority_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let mut shares = Vec::with_capacity(beneficiaries.len());
+        let mut paid: u64 = 0;
+        for (i, (beneficiary, account)) in beneficiaries.iter().zip(ctx.remaining_accounts.iter()).enumerate() {
+            require!(account.key() == beneficiary.token_account, PrivaxError::BeneficiaryAccountMismatch);
+            let token_account = Account::<TokenAccount>::try_from(account)?;
+            require!(token_account.mint == ctx.accounts.program_state.token_mint, PrivaxError::BeneficiaryAccountMismatch);
+
+            // The last beneficiary takes the rounding remainder so nothing is left behind.
+            let share = if i + 1 == beneficiaries.len() {
+                total.checked_sub(paid).ok_or(PrivaxError::Overflow)?
+            } else {
+                ((total as u128) * (beneficiary.weight as u128) / total_weight) as u64
+            };
+            if share > 0 {
+                let cpi_accounts = Transfer {
+                    from: ctx.accounts.program_token_vault.to_account_info(),
+                    to: account.clone(),
+                    authority: ctx.accounts.program_token_vault_authority.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                token::transfer(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), share)?;
+            }
+            paid = paid.checked_add(share).ok_or(PrivaxError::Overflow)?;
+            shares.push(share);
+        }
+        let fee_config = &mut ctx.accounts.fee_config;
+        fee_config.accrued_protocol_fees = fee_config.accrued_protocol_fees.checked_sub(total).ok_or(PrivaxError::Overflow)?;
+
+        let clock = Clock::get()?;
+        emit!(FeesDistributed { version: EVENT_VERSION, total, shares, slot: clock.slot, unix_timestamp: clock.unix_timestamp });
+        Ok(())
+    }
+
+    pub fn set_utilization_fee_curve(
+        ctx: Context<UpdateFeeConfig>,
+        utilization_kink_bps: u16,
+        max_utilization_fee_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_FEES)?;
+        require!(
+            utilization_kink_bps < 10_000 && max_utilization_fee_bps <= FeeConfig::MAX_PROTOCOL_FEE_BPS,
+            PrivaxError::FeeBpsTooHigh
+        );
+        let fee_config = &mut ctx.accounts.fee_config;
+        let old_value = (fee_config.utilization_kink_bps, fee_config.max_utilization_fee_bps);
+        fee_config.utilization_kink_bps = utilization_kink_bps;
+        fee_config.max_utilization_fee_bps = max_utilization_fee_bps;
+        let new_value = (utilization_kink_bps, max_utilization_fee_bps);
+        let actor = ctx.accounts.fee_authority.key();
+        record_audit(&ctx.accounts.audit_log, actor, AuditAction::SetUtilizationFeeCurve, &old_value, &new_value)?;
+        Ok(())
+    }
+
+    pub fn set_referral_share(ctx: Context<UpdateFeeConfig>, referral_share_bps: u16) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_FEES)?;
+        require!(referral_share_bps <= 10_000, PrivaxError::FeeBpsTooHigh);
+        let old_share = ctx.accounts.fee_config.referral_share_bps;
+        ctx.accounts.fee_config.referral_share_bps = referral_share_bps;
+        let actor = ctx.accounts.fee_authority.key();
+        record_audit(&ctx.accounts.audit_log, actor, AuditAction::SetReferralShare, &old_share, &referral_share_bps)?;
+        Ok(())
+    }
+
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        let referrer_account = &mut ctx.accounts.referrer_account;
+        referrer_account.referrer = ctx.accounts.referrer.key();
+        referrer_account.bump = *ctx.bumps.get("referrer_account").unwrap();
+        Ok(())
+    }
+
+    // Claims are paid from accrued protocol fees, so a referrer may have to wait until
+    // enough withdrawals have happened to cover its credit.
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        let fee_config = &ctx.accounts.fee_config;
+        let amount = ctx.accounts.referrer_account.claimable.min(fee_config.accrued_protocol_fees);
+        require!(amount > 0, PrivaxError::NothingToClaim);
+
+        let program_state_key = ctx.accounts.program_state.key();
+        let vault_authority_bump = *ctx.bumps.get("program_token_vault_authority").unwrap();
+        let seeds = &[b"program_token_vault".as_ref(), program_state_key.as_ref(), &[vault_authority_bump]];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.program_token_vault.to_account_info(),
+            to: ctx.accounts.referrer_token_account.to_account_info(),
+            authority: ctx.accounts.program_token_vault_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), amount)?;
+
+        let fee_config = &mut ctx.accounts.fee_config;
+        fee_config.accrued_protocol_fees = fee_config.accrued_protocol_fees.checked_sub(amount).ok_or(PrivaxError::Overflow)?;
+        fee_config.owed_referral_fees = fee_config.owed_referral_fees.saturating_sub(amount);
+        let referrer_account = &mut ctx.accounts.referrer_account;
+        referrer_account.claimable = referrer_account.claimable.checked_sub(amount).ok_or(PrivaxError::Overflow)?;
+        referrer_account.total_claimed = referrer_account.total_claimed.saturating_add(amount);
+
+        let clock = Clock::get()?;
+        emit!(ReferralFeesClaimed {
+            version: EVENT_VERSION,
+            referrer: referrer_account.referrer,
+            amount,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn close_deposit_receipt(_ctx: Context<CloseDepositReceipt>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn set_fee_swap_program(ctx: Context<UpdateFeeConfig>, fee_swap_program: Pubkey) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_FEES)?;
+        let fee_config = &mut ctx.accounts.fee_config;
+        let old_program = fee_config.fee_swap_program;
+        fee_config.fee_swap_program = fee_swap_program;
+        let actor = ctx.accounts.fee_authority.key();
+        record_audit(&ctx.accounts.audit_log, actor, AuditAction::SetFeeSwapProgram, &old_program, &fee_swap_program)?;
+
+        let clock = Clock::get()?;
+        emit!(FeeSwapProgramChanged {
+            version: EVENT_VERSION,
+            old_program,
+            new_program: fee_swap_program,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn set_verifier_program(ctx: Context<UpdateConfig>, verifier_program_id: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        state.require_not_frozen(ProgramState::PARAM_GROUP_VERIFIER)?;
+        let old_program = state.verifier_program_id;
+        state.verifier_program_id = verifier_program_id;
+        let actor = ctx.accounts.admin.key();
+        record_audit(&ctx.accounts.audit_log, actor, AuditAction::SetVerifierProgram, &old_program, &verifier_program_id)?;
+
+        let clock = Clock::get()?;
+        emit!(VerifierProgramChanged {
+            version: EVENT_VERSION,
+            old_program,
+            new_program: verifier_program_id,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Switches the pool to another proof system together with the verifier program for it,
+    // so a migration is a single config change.
+    pub fn set_proof_system(
+        ctx: Context<UpdateConfig>,
+        proof_system: ProofSystem,
+        verifier_program_id: Pubkey,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        state.require_not_frozen(ProgramState::PARAM_GROUP_VERIFIER)?;
+        let old_value = (state.proof_system, state.verifier_program_id);
+        let old_proof_system = state.proof_system;
+        state.proof_system = proof_system;
+        state.verifier_program_id = verifier_program_id;
+        let new_value = (proof_system, verifier_program_id);
+        record_audit(&ctx.accounts.audit_log, ctx.accounts.admin.key(), AuditAction::SetProofSystem, &old_value, &new_value)?;
+
+        let clock = Clock::get()?;
+        emit!(ProofSystemChanged {
+            version: EVENT_VERSION,
+            old_proof_system,
+            new_proof_system: proof_system,
+            verifier_program_id,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // vk_data is the first chunk of a vk_len-byte key; if it's shorter, the rest follows via
+    // write_verifying_key_chunk and the circuit stays disabled until then.
+    pub fn register_verifying_key(
+        ctx: Context<RegisterVerifyingKey>,
+        circuit_version: u16,
+        proof_system: ProofSystem,
+        amount_range_bits: u8,
+        vk_len: u16,
+        vk_data: Vec<u8>,
+    ) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_VERIFIER)?;
+        require!(usize::from(vk_len) <= MAX_VK_LEN, PrivaxError::VerifyingKeyTooLarge);
+        // Every pool is variable-amount (VARIABLE_DENOMINATION), so every circuit needs the range check.
+        require!(
+            amount_range_bits == VerifyingKey::REQUIRED_AMOUNT_RANGE_BITS,
+            PrivaxError::MissingAmountRangeCheck
+        );
+        let mut verifying_key = ctx.accounts.verifying_key.load_init()?;
+        let old_value = verifying_key.audit_value();
+        verifying_key.circuit_version = circuit_version;
+        verifying_key.vk_len = vk_len;
+        verifying_key.proof_system = proof_system as u8;
+        verifying_key.amount_range_bits = amount_range_bits;
+        verifying_key.bump = *ctx.bumps.get("verifying_key").unwrap();
+
+        let complete = verifying_key.append_chunk(&vk_data)?;
+        if ctx.accounts.program_state.admin_timelock_slots > 0 {
+            verifying_key.enabled = 0; // Enabled through the queue (AdminAction::EnableVerifyingKey)
+        }
+        let new_value = verifying_key.audit_value();
+        record_audit(&ctx.accounts.audit_log, ctx.accounts.admin.key(), AuditAction::RegisterVerifyingKey, &old_value, &new_value)?;
+        if complete {
+            let clock = Clock::get()?;
+            emit!(VerifyingKeyRegistered {
+                version: EVENT_VERSION,
+                circuit_version,
+                proof_system,
+                slot: clock.slot,
+                unix_timestamp: clock.unix_timestamp,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn write_verifying_key_chunk(ctx: Context<UpdateVerifyingKey>, chunk: Vec<u8>) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_VERIFIER)?;
+        let mut verifying_key = ctx.accounts.verifying_key.load_mut()?;
+        require!(!verifying_key.is_complete(), PrivaxError::InvalidVerifyingKey); // Already uploaded
+        let old_value = verifying_key.audit_value();
+
+        let complete = verifying_key.append_chunk(&chunk)?;
+        if ctx.accounts.program_state.admin_timelock_slots > 0 {
+            verifying_key.enabled = 0; // As in register_verifying_key
+        }
+        let new_value = verifying_key.audit_value();
+        record_audit(&ctx.accounts.audit_log, ctx.accounts.admin.key(), AuditAction::WriteVerifyingKeyChunk, &old_value, &new_value)?;
+        if complete {
+            let clock = Clock::get()?;
+            emit!(VerifyingKeyRegistered {
+                version: EVENT_VERSION,
+                circuit_version: verifying_key.circuit_version,
+                proof_system: verifying_key.proof_system(),
+                slot: clock.slot,
+                unix_timestamp: clock.unix_timestamp,
+            });
+        }
+        Ok(())
+    }
+
+    // Disabling a key is always immediate; enabling one is an AdminAction.
+    pub fn set_verifying_key_enabled(ctx: Context<UpdateVerifyingKey>, enabled: bool) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_VERIFIER)?;
+        if enabled {
+            ctx.accounts.program_state.require_no_admin_timelock()?;
+        }
+        change_verifying_key_enabled(&ctx.accounts.verifying_key, &ctx.accounts.audit_log, ctx.accounts.admin.key(), enabled)
+    }
+
+    // Irreversible: there is deliberately no unfreeze instruction.
+    pub fn freeze_params(ctx: Context<UpdateConfig>, groups: u8) -> Result<()> {
+        require!(groups != 0 && groups & !ProgramState::PARAM_GROUP_ALL == 0, PrivaxError::InvalidParamGroup);
+        let state = &mut ctx.accounts.program_state;
+        let old_groups = state.frozen_groups;
+        state.frozen_groups |= groups;
+        record_audit(&ctx.accounts.audit_log, state.admin, AuditAction::FreezeParams, &old_groups, &state.frozen_groups)?;
+
+        let clock = Clock::get()?;
+        emit!(ParamGroupsFrozen {
+            version: EVENT_VERSION,
+            groups,
+            frozen_groups: state.frozen_groups,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn set_guardian(ctx: Context<UpdateConfig>, new_guardian: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        state.require_no_admin_timelock()?;
+        change_guardian(state, &ctx.accounts.audit_log, new_guardian)
+    }
+
+    // Sets how long queued AdminActions wait before they can run. Raising the timelock takes
+    // effect at once; lowering or removing it is itself an AdminAction, so a stolen admin key
+    // can't switch it off.
+    pub fn set_admin_timelock(ctx: Context<UpdateConfig>, slots: u64) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        require!(slots >= state.admin_timelock_slots, PrivaxError::AdminTimelockActive);
+        change_admin_timelock(state, &ctx.accounts.audit_log, slots)
+    }
+
+    // Queues an AdminAction to run once the timelock has passed. The queue is public, so note
+    // holders see what is coming and can leave before it does.
+    pub fn queue_admin_action(ctx: Context<QueueAdminAction>, action: AdminAction) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        match action {
+            AdminAction::TransferOwnership { new_admin } => require!(new_admin != Pubkey::default(), PrivaxError::NewAdminIsZero),
+            AdminAction::SetAdminTimelock { slots } => {
+                require!(slots <= ProgramState::MAX_ADMIN_TIMELOCK_SLOTS, PrivaxError::InvalidAdminTimelock)
+            }
+            AdminAction::AddSwapTarget { target_program } => check_swap_target(state, target_program)?,
+            AdminAction::SetWithdrawalLimit { max_withdrawal_per_epoch, withdrawal_epoch_slots } => {
+                require!(max_withdrawal_per_epoch == 0 || withdrawal_epoch_slots > 0, PrivaxError::InvalidWithdrawalLimit)
+            }
+            AdminAction::SetGuardian { .. }
+            | AdminAction::EnableVerifyingKey { .. }
+            | AdminAction::SetComplianceKey { .. }
+            | AdminAction::SetKycAttestor { .. }
+            | AdminAction::SetExclusionRequired { .. } => {}
+        }
+        let clock = Clock::get()?;
+        let id = state.next_admin_action_id;
+        state.next_admin_action_id = id.checked_add(1).ok_or(PrivaxError::Overflow)?;
+        let eta_slot = clock.slot.checked_add(state.admin_timelock_slots).ok_or(PrivaxError::Overflow)?;
+
+        let queued = &mut ctx.accounts.queued_action;
+        queued.id = id;
+        queued.action = action;
+        queued.payer = ctx.accounts.payer.key();
+        queued.eta_slot = eta_slot;
+        queued.bump = *ctx.bumps.get("queued_action").unwrap();
+        let audit_log = &ctx.accounts.audit_log;
+        record_audit(audit_log, state.admin, AuditAction::QueueAdminAction, &None::<AdminAction>, &(id, action, eta_slot))?;
+
+        emit!(AdminActionQueued {
+            version: EVENT_VERSION,
+            id,
+            action,
+            eta_slot,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Runs a queued AdminAction whose timelock has passed, with the same checks, audit entry
+    // and events as its direct form. EnableVerifyingKey takes the key as verifying_key.
+    pub fn execute_admin_action(ctx: Context<ExecuteAdminAction>) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        let queued = &ctx.accounts.queued_action;
+        let clock = Clock::get()?;
+        require!(clock.slot >= queued.eta_slot, PrivaxError::AdminActionNotReady);
+
+        let audit_log = &ctx.accounts.audit_log;
+        match queued.action {
+            AdminAction::TransferOwnership { new_admin } => change_admin(state, audit_log, new_admin)?,
+            AdminAction::SetGuardian { new_guardian } => change_guardian(state, audit_log, new_guardian)?,
+            AdminAction::EnableVerifyingKey { circuit_version } => {
+                state.require_not_frozen(ProgramState::PARAM_GROUP_VERIFIER)?;
+                let verifying_key = ctx.accounts.verifying_key.as_ref().ok_or(PrivaxError::InvalidVerifyingKey)?;
+                require!(verifying_key.load()?.circuit_version == circuit_version, PrivaxError::InvalidVerifyingKey);
+                change_verifying_key_enabled(verifying_key, audit_log, state.admin, true)?;
+            }
+            AdminAction::SetAdminTimelock { slots } => change_admin_timelock(state, audit_log, slots)?,
+            AdminAction::AddSwapTarget { target_program } => approve_swap_target(state, audit_log, target_program)?,
+            AdminAction::SetWithdrawalLimit { max_withdrawal_per_epoch, withdrawal_epoch_slots } => {
+                change_withdrawal_limit(state, audit_log, max_withdrawal_per_epoch, withdrawal_epoch_slots)?
+            }
+            AdminAction::SetComplianceKey { compliance_key } => change_compliance_key(state, audit_log, compliance_key)?,
+            AdminAction::SetKycAttestor { kyc_attestor } => change_kyc_attestor(state, audit_log, kyc_attestor)?,
+            AdminAction::SetExclusionRequired { exclusion_required } => {
+                change_exclusion_required(state, audit_log, exclusion_required)?
+            }
+        }
+
+        emit!(AdminActionExecuted {
+            version: EVENT_VERSION,
+            id: queued.id,
+            action: queued.action,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // The guardian's veto over queued AdminActions; the admin can withdraw its own as well.
+    pub fn cancel_admin_action(ctx: Context<CancelAdminAction>) -> Result<()> {
+        let queued = &ctx.accounts.queued_action;
+        let actor = ctx.accounts.authority.key();
+        let old_value = (queued.id, queued.action, queued.eta_slot);
+        record_audit(&ctx.accounts.audit_log, actor, AuditAction::CancelAdminAction, &old_value, &None::<AdminAction>)?;
+
+        let clock = Clock::get()?;
+        emit!(AdminActionCancelled {
+            version: EVENT_VERSION,
+            id: queued.id,
+            action: queued.action,
+            by: actor,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // The tree operator lets maintenance automation run without the admin key. It can only
+    // sign instructions gated by ProgramState::is_tree_maintainer, and none of those move funds.
+    pub fn set_tree_operator(ctx: Context<UpdateConfig>, new_tree_operator: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        let old_tree_operator = state.tree_operator;
+        state.tree_operator = new_tree_operator;
+        let audit_log = &ctx.accounts.audit_log;
+        record_audit(audit_log, state.admin, AuditAction::SetTreeOperator, &old_tree_operator, &new_tree_operator)?;
+
+        let clock = Clock::get()?;
+        emit!(TreeOperatorChanged {
+            version: EVENT_VERSION,
+            old_tree_operator,
+            new_tree_operator,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Either the admin or the guardian can pause; only the admin can unpause.
+    pub fn pause(ctx: Context<Pause>) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let clock = Clock::get()?;
+        require!(!state.is_paused(clock.slot), PrivaxError::PoolPaused);
+        let old_value = (state.paused, state.paused_at_slot);
+        state.paused = true;
+        state.paused_at_slot = clock.slot;
+        let actor = ctx.accounts.authority.key();
+        record_audit(&ctx.accounts.audit_log, actor, AuditAction::Pause, &old_value, &(state.paused, state.paused_at_slot))?;
+
+        emit!(PauseToggled {
+            version: EVENT_VERSION,
+            paused: true,
+            by: ctx.accounts.authority.key(),
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Unpausing also clears the epoch's withdrawal volume: after a circuit breaker trip, the
+    // admin resuming the pool has reviewed the withdrawals that led up to it.
+    pub fn unpause(ctx: Context<UpdateConfig>) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        require!(state.paused, PrivaxError::PoolNotPaused);
+        let old_value = (state.paused, state.paused_at_slot);
+        state.paused = false;
+        state.withdrawn_in_epoch = 0;
+        record_audit(&ctx.accounts.audit_log, state.admin, AuditAction::Unpause, &old_value, &(state.paused, state.paused_at_slot))?;
+
+        let clock = Clock::get()?;
+        emit!(PauseToggled {
+            version: EVENT_VERSION,
+            paused: false,
+            by: ctx.accounts.admin.key(),
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Circuit breaker against an undetected flaw in the proof system: once withdrawals in an
+    // epoch of `withdrawal_epoch_slots` reach `max_withdrawal_per_epoch`, the pool pauses until
+    // the admin unpauses it. A zero limit turns the breaker off. Turning it on, or lowering the
+    // limit without shortening the epoch, takes effect at once; any other change is an
+    // AdminAction.
+    pub fn set_withdrawal_limit(
+        ctx: Context<UpdateConfig>,
+        max_withdrawal_per_epoch: u64,
+        withdrawal_epoch_slots: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        let tightens = max_withdrawal_per_epoch > 0
+            && (state.max_withdrawal_per_epoch == 0
+                || (max_withdrawal_per_epoch <= state.max_withdrawal_per_epoch
+                    && withdrawal_epoch_slots >= state.withdrawal_epoch_slots));
+        if !tightens {
+            state.require_no_admin_timelock()?;
+        }
+        change_withdrawal_limit(state, &ctx.accounts.audit_log, max_withdrawal_per_epoch, withdrawal_epoch_slots)
+    }
+
+    // Institutional pools: with a compliance key set, every deposit needs an attestation bound to
+    // an envelope encrypted to the key. Zero turns attestations off. Rotating the key voids
+    // attestations made to the old one that haven't been deposited yet. Setting the first key
+    // takes effect at once; rotating or clearing it is an AdminAction.
+    pub fn set_compliance_key(ctx: Context<UpdateConfig>, compliance_key: [u8; 32]) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        if state.compliance_key != [0; 32] || compliance_key == [0; 32] {
+            state.require_no_admin_timelock()?;
+        }
+        change_compliance_key(state, &ctx.accounts.audit_log, compliance_key)
+    }
+
+    // Makes this an institutional pool: deposits then need a KYC credential issued by
+    // `kyc_attestor`. The default key opens the pool to everyone again. Naming the first
+    // attestor takes effect at once; replacing it or opening the pool is an AdminAction.
+    pub fn set_kyc_attestor(ctx: Context<UpdateConfig>, kyc_attestor: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        if state.kyc_attestor != Pubkey::default() || kyc_attestor == Pubkey::default() {
+            state.require_no_admin_timelock()?;
+        }
+        change_kyc_attestor(state, &ctx.accounts.audit_log, kyc_attestor)
+    }
+
+    // Issues or renews a holder's credential. The credential is a PDA of the holder rather than
+    // a token, so it can't be transferred.
+    pub fn issue_kyc_credential(ctx: Context<IssueKycCredential>, holder: Pubkey, expires_at: i64) -> Result<()> {
+        let state = &ctx.accounts.program_state;
+        require!(state.requires_kyc() && ctx.accounts.attestor.key() == state.kyc_attestor, PrivaxError::NotKycAttestor);
+        let clock = Clock::get()?;
+        require!(expires_at == 0 || expires_at > clock.unix_timestamp, PrivaxError::InvalidKycExpiry);
+
+        let credential = &mut ctx.accounts.kyc_credential;
+        credential.holder = holder;
+        credential.attestor = state.kyc_attestor;
+        credential.issued_at = clock.unix_timestamp;
+        credential.expires_at = expires_at;
+        credential.bump = *ctx.bumps.get("kyc_credential").unwrap();
+
+        emit!(KycCredentialIssued {
+            version: EVENT_VERSION,
+            holder,
+            attestor: credential.attestor,
+            expires_at,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Only the issuing attestor can revoke, and gets the rent back. A replaced attestor's
+    // credentials are already void; revoking them just reclaims the rent.
+    pub fn revoke_kyc_credential(ctx: Context<RevokeKycCredential>) -> Result<()> {
+        let credential = &ctx.accounts.kyc_credential;
+        let clock = Clock::get()?;
+        emit!(KycCredentialRevoked {
+            version: EVENT_VERSION,
+            holder: credential.holder,
+            attestor: credential.attestor,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Caps what one depositor can put in over any 24 hours, so the pool can't wash a large sum
+    // quickly from one address. Depositors then need a DepositVolume account (see
+    // init_deposit_volume). Zero lifts the cap.
+    pub fn set_max_daily_deposit(ctx: Context<UpdateConfig>, max_daily_deposit: u64) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        let old_max = state.max_daily_deposit;
+        state.max_daily_deposit = max_daily_deposit;
+        record_audit(&ctx.accounts.audit_log, state.admin, AuditAction::SetMaxDailyDeposit, &old_max, &max_daily_deposit)?;
+
+        let clock = Clock::get()?;
+        emit!(MaxDailyDepositUpdated {
+            version: EVENT_VERSION,
+            max_daily_deposit,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Permissionless, so a wallet or relayer can open a depositor's volume account ahead of the
+    // first deposit into a capped pool.
+    pub fn init_deposit_volume(ctx: Context<InitDepositVolume>, depositor: Pubkey) -> Result<()> {
+        let volume = &mut ctx.accounts.deposit_volume;
+        volume.depositor = depositor;
+        volume.bump = *ctx.bumps.get("deposit_volume").unwrap();
+        Ok(())
+    }
+
+    // Lets a compliance provider publish exclusion roots without holding the admin key. The
+    // default key leaves publishing to the admin.
+    pub fn set_exclusion_operator(ctx: Context<UpdateConfig>, exclusion_operator: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        let old_operator = state.exclusion_operator;
+        state.exclusion_operator = exclusion_operator;
+        record_audit(&ctx.accounts.audit_log, state.admin, AuditAction::SetExclusionOperator, &old_operator, &exclusion_operator)?;
+
+        let clock = Clock::get()?;
+        emit!(ExclusionOperatorUpdated {
+            version: EVENT_VERSION,
+            exclusion_operator,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Publishes the root of the sorted Merkle tree of commitments flagged as illicit (see
+    // circuits/circuits/exclusion.circom). The root it replaces stays usable for proofs made
+    // before this slot, so publishing doesn't void proofs already in flight.
+    pub fn publish_exclusion_root(ctx: Context<PublishExclusionRoot>, root: [u8; 32]) -> Result<()> {
+        require!(root != [0; 32] && proof_format::is_canonical_scalar(&root), PrivaxError::InvalidExclusionRoot);
+        let state = &mut ctx.accounts.program_state;
+        let clock = Clock::get()?;
+        let previous_root = state.exclusion_root;
+        state.previous_exclusion_root = previous_root;
+        state.exclusion_root = root;
+        state.exclusion_root_slot = clock.slot;
+
+        emit!(ExclusionRootPublished {
+            version: EVENT_VERSION,
+            publisher: ctx.accounts.operator.key(),
+            root,
+            previous_root,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Opts the pool into exclusion proofs: every spend proof then carries the exclusion root as
+    // its last public input, so the pool's verifying keys must be for exclusion-aware circuits
+    // before this is turned on. Opting in takes effect at once; opting out is an AdminAction.
+    pub fn set_exclusion_required(ctx: Context<UpdateConfig>, exclusion_required: bool) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        if !exclusion_required {
+            state.require_no_admin_timelock()?;
+        }
+        change_exclusion_required(state, &ctx.accounts.audit_log, exclusion_required)
+    }
+
+    // Protects note holders from an admin who pauses the pool and disappears: once a pause has
+    // lasted `max_pause_slots`, withdrawals resume (see ProgramState::is_exit_only). A pause runs
+    // under the duration in force when it began, so this can't be changed while paused. Zero
+    // lets pauses last until the admin unpauses.
+    pub fn set_max_pause_duration(ctx: Context<UpdateConfig>, max_pause_slots: u64) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let clock = Clock::get()?;
+        require!(!state.is_paused(clock.slot), PrivaxError::PoolPaused);
+        let old_value = state.max_pause_slots;
+        state.max_pause_slots = max_pause_slots;
+        record_audit(&ctx.accounts.audit_log, state.admin, AuditAction::SetMaxPauseDuration, &old_value, &max_pause_slots)?;
+
+        emit!(MaxPauseDurationUpdated {
+            version: EVENT_VERSION,
+            max_pause_slots,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Whether a shortfall found by `reconcile` pauses the pool. Off by default: the vault only
+    // comes up short through a bug or a compromised authority, and a pause stops the bleeding
+    // at the cost of stopping withdrawals too.
+    pub fn set_pause_on_shortfall(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        let old_value = state.pause_on_shortfall;
+        state.pause_on_shortfall = enabled;
+        record_audit(&ctx.accounts.audit_log, state.admin, AuditAction::SetPauseOnShortfall, &old_value, &enabled)?;
+
+        let clock = Clock::get()?;
+        emit!(PauseOnShortfallUpdated {
+            version: EVENT_VERSION,
+            enabled,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Permissionless solvency check: the vault should hold every deposit not yet withdrawn plus
+    // the protocol fees not yet distributed. Any difference is reported; a shortfall also pauses
+    // the pool when pause_on_shortfall is set. The instruction succeeds so the pause sticks.
+    pub fn reconcile(ctx: Context<Reconcile>) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        require!(state.pool_mode == PoolMode::Fungible, PrivaxError::WrongPoolMode);
+        let accrued_protocol_fees = ctx.accounts.fee_config.accrued_protocol_fees;
+        let expected = state
+            .total_deposited
+            .checked_sub(state.total_withdrawn)
+            .and_then(|owed| owed.checked_add(accrued_protocol_fees))
+            .ok_or(PrivaxError::Overflow)?;
+        let actual = ctx.accounts.program_token_vault.amount;
+        if actual == expected {
+            return Ok(());
+        }
+
+        let clock = Clock::get()?;
+        let pause = actual < expected && state.pause_on_shortfall && !state.is_paused(clock.slot);
+        if pause {
+            state.paused = true;
+            state.paused_at_slot = clock.slot;
+        }
+        emit!(SolvencyMismatch {
+            version: EVENT_VERSION,
+            expected,
+            actual,
+            total_deposited: state.total_deposited,
+            total_withdrawn: state.total_withdrawn,
+            accrued_protocol_fees,
+            paused: pause,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        if pause {
+            emit!(PauseToggled {
+                version: EVENT_VERSION,
+                paused: true,
+                by: crate::ID, // The program itself
+                slot: clock.slot,
+                unix_timestamp: clock.unix_timestamp,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn deposit(
+        ctx: Context<DepositTokens>,
+        amount: u64,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.program_state.is_paused(clock.slot), PrivaxError::PoolPaused);
+        require!(amount > 0, PrivaxError::AmountTooSmall);
+        check_commitment(&commitment)?;
+        if let Some((limits, price)) = usd_price(&ctx.accounts.program_state, &ctx.accounts.usd_limits, &ctx.accounts.price_feed)? {
+            limits.check_deposit(amount, &price)?;
+        }
+        if ctx.accounts.program_state.requires_attestation() {
+            let attestation = ctx.accounts.compliance_attestation.as_ref().ok_or(PrivaxError::ComplianceAttestationRequired)?;
+            require!(
+                attestation.depositor == ctx.accounts.user.key()
+                    && attestation.compliance_key == ctx.accounts.program_state.compliance_key
+                    && attestation.deposit_slot == 0,
+                PrivaxError::ComplianceAttestationMismatch
+            );
+        }
+        if ctx.accounts.program_state.requires_kyc() {
+            let credential = ctx.accounts.kyc_credential.as_ref().ok_or(PrivaxError::KycCredentialRequired)?;
+            require!(
+                credential.is_valid(&ctx.accounts.program_state.kyc_attestor, clock.unix_timestamp),
+                PrivaxError::KycCredentialInvalid
+            );
+        }
+        record_deposit_volume(&ctx.accounts.program_state, &mut ctx.accounts.deposit_volume, amount)?;
+
+        // Transfer tokens from user to program's vault PDA
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.user_token_account.to_account_info(),
+            to: ctx.accounts.program_token_vault.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        ctx.accounts.program_state.record_deposit(amount)?;
+        // The shard's address is derived from the commitment's prefix, so this only fills in a new shard
+        let shard = &mut ctx.accounts.commitment_shard;
+        shard.prefix = CommitmentShard::prefix_of(&commitment);
+        shard.bump = *ctx.bumps.get("commitment_shard").unwrap();
+        record_commitment(shard, &ctx.accounts.user, &ctx.accounts.system_program, &commitment)?;
+        let leaf_index = ctx.accounts.merkle_tree.load_mut()?.append(commitment)?;
+
+        if let Some(receipt) = ctx.accounts.deposit_receipt.as_mut() {
+            receipt.depositor = ctx.accounts.user.key();
+            receipt.leaf_index = leaf_index;
+            receipt.slot = clock.slot;
+            receipt.amount = amount;
+            receipt.commitment = commitment;
+            receipt.bump = *ctx.bumps.get("deposit_receipt").unwrap();
+        }
+        if ctx.accounts.program_state.requires_attestation() {
+            if let Some(attestation) = ctx.accounts.compliance_attestation.as_mut() {
+                attestation.leaf_index = leaf_index;
+                attestation.deposit_slot = clock.slot;
+            }
+        }
+
+        if let Some(referrer_account) = ctx.accounts.referrer_account.as_mut() {
+            let credit = ctx.accounts.fee_config.referral_credit(amount);
+            if credit > 0 {
+                referrer_account.claimable = referrer_account.claimable.checked_add(credit).ok_or(PrivaxError::Overflow)?;
+                let fee_config = &mut ctx.accounts.fee_config;
+                fee_config.owed_referral_fees = fee_config.owed_referral_fees.checked_add(credit).ok_or(PrivaxError::Overflow)?;
+                emit!(ReferralAccrued {
+                    version: EVENT_VERSION,
+                    referrer: referrer_account.referrer,
+                    amount: credit,
+                    slot: clock.slot,
+                    unix_timestamp: clock.unix_timestamp,
+                });
+            }
+        }
+
+        emit!(DepositOccurred {
+            version: EVENT_VERSION,
+            user: *ctx.accounts.user.key,
+            token_address: ctx.accounts.program_state.token_mint,
+            amount,
+            commitment,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Permissionless and stateless: logs a note's ciphertext for whoever can decrypt it. Anyone
+    // can announce anything for any commitment, so a recipient checks that the note opens the
+    // commitment and that the deposit's amount matches before trusting it.
+    pub fn announce_note(_ctx: Context<AnnounceNote>, commitment: [u8; 32], ciphertext: Vec<u8>) -> Result<()> {
+        require!(!ciphertext.is_empty() && ciphertext.len() <= MAX_NOTE_CIPHERTEXT_LEN, PrivaxError::InvalidNoteCiphertext);
+        let clock = Clock::get()?;
+        emit!(NoteAnnounced { version: EVENT_VERSION, commitment, ciphertext, slot: clock.slot, unix_timestamp: clock.unix_timestamp });
+        Ok(())
+    }
+
+    // Records the hash of a compliance envelope for a commitment the signer is about to deposit,
+    // normally in the deposit's own transaction. The envelope is encrypted to the pool's
+    // compliance key and handed to the compliance operator off-chain.
+    pub fn attest_deposit(ctx: Context<AttestDeposit>, commitment: [u8; 32], envelope_hash: [u8; 32]) -> Result<()> {
+        require!(ctx.accounts.program_state.requires_attestation(), PrivaxError::NoComplianceKey);
+        let compliance_key = ctx.accounts.program_state.compliance_key;
+        let attestation = &mut ctx.accounts.compliance_attestation;
+        attestation.depositor = ctx.accounts.depositor.key();
+        attestation.commitment = commitment;
+        attestation.envelope_hash = envelope_hash;
+        attestation.compliance_key = compliance_key;
+        attestation.bump = *ctx.bumps.get("compliance_attestation").unwrap();
+
+        let clock = Clock::get()?;
+        emit!(DepositAttested {
+            version: EVENT_VERSION,
+            depositor: attestation.depositor,
+            commitment,
+            envelope_hash,
+            compliance_key,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn register_commitment(ctx: Context<RegisterCommitment>, commitment: [u8; 32], amount: u64) -> Result<()> {
+        require!(amount > 0, PrivaxError::AmountTooSmall);
+        check_commitment(&commitment)?;
+        let clock = Clock::get()?;
+        let expiry_slot = clock.slot.checked_add(PendingCommitment::TTL_SLOTS).ok_or(PrivaxError::Overflow)?;
+        let pending = &mut ctx.accounts.pending_commitment;
+        pending.registrant = ctx.accounts.registrant.key();
+        pending.commitment = commitment;
+        pending.amount = amount;
+        pending.expiry_slot = expiry_slot;
+        pending.bump = *ctx.bumps.get("pending_commitment").unwrap();
+
+        emit!(CommitmentRegistered {
+            version: EVENT_VERSION,
+            registrant: pending.registrant,
+            commitment,
+            amount,
+            expiry_slot,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Second half of a two-phase deposit: any signer can fund a registered commitment with
+    // exactly the registered amount.
+    pub fn fund_commitment(ctx: Context<FundCommitment>) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.program_state.is_paused(clock.slot), PrivaxError::PoolPaused);
+        require!(!ctx.accounts.program_state.requires_attestation(), PrivaxError::ComplianceAttestationRequired);
+        require!(!ctx.accounts.program_state.requires_kyc(), PrivaxError::KycCredentialRequired);
+        let pending = &ctx.accounts.pending_commitment;
+        require!(clock.slot < pending.expiry_slot, PrivaxError::RegistrationExpired);
+        let (amount, commitment) = (pending.amount, pending.commitment);
+        if let Some((limits, price)) = usd_price(&ctx.accounts.program_state, &ctx.accounts.usd_limits, &ctx.accounts.price_feed)? {
+            limits.check_deposit(amount, &price)?;
+        }
+        // Counts against the funder, who is the one moving the tokens in
+        record_deposit_volume(&ctx.accounts.program_state, &mut ctx.accounts.deposit_volume, amount)?;
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.funder_token_account.to_account_info(),
+            to: ctx.accounts.program_token_vault.to_account_info(),
+            authority: ctx.accounts.funder.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+        ctx.accounts.program_state.record_deposit(amount)?;
+        // The shard's address is derived from the commitment's prefix, so this only fills in a new shard
+        let shard = &mut ctx.accounts.commitment_shard;
+        shard.prefix = CommitmentShard::prefix_of(&commitment);
+        shard.bump = *ctx.bumps.get("commitment_shard").unwrap();
+        record_commitment(shard, &ctx.accounts.funder, &ctx.accounts.system_program, &commitment)?;
+        ctx.accounts.merkle_tree.load_mut()?.append(commitment)?;
+
+        emit!(CommitmentRegistrationClosed {
+            version: EVENT_VERSION,
+            commitment,
+            funded: true,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        emit!(DepositOccurred {
+            version: EVENT_VERSION,
+            user: ctx.accounts.funder.key(),
+            token_address: ctx.accounts.program_state.token_mint,
+            amount,
+            commitment,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Permissionless: once a registration expires unfunded, anyone can close it and the
+    // rent goes back to the registrant.
+    pub fn reclaim_expired_registration(ctx: Context<ReclaimExpiredRegistration>) -> Result<()> {
+        let pending = &ctx.accounts.pending_commitment;
+        let clock = Clock::get()?;
+        require!(clock.slot >= pending.expiry_slot, PrivaxError::RegistrationNotExpired);
+
+        emit!(CommitmentRegistrationClosed {
+            version: EVENT_VERSION,
+            commitment: pending.commitment,
+            funded: false,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Deposits a batch of commitments as one aligned subtree, so a busy depositor (e.g. an
+    // exchange) needs a single tree update instead of one per leaf. `subtree_root` is the root
+    // the caller precomputed; it is recomputed from the leaves and must match.
+    pub fn deposit_subtree<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositSubtree<'info>>,
+        amounts: Vec<u64>,
+        commitments: Vec<[u8; 32]>,
+        subtree_root: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.program_state.is_paused(clock.slot), PrivaxError::PoolPaused);
+        require!(!ctx.accounts.program_state.requires_attestation(), PrivaxError::ComplianceAttestationRequired);
+        require!(!ctx.accounts.program_state.requires_kyc(), PrivaxError::KycCredentialRequired);
+        let leaves = commitments.len();
+        require!(
+            leaves.is_power_of_two() && leaves <= 1 << MerkleTree::MAX_SUBTREE_HEIGHT && amounts.len() == leaves,
+            PrivaxError::InvalidSubtreeSize
+        );
+        require!(amounts.iter().all(|&amount| amount > 0), PrivaxError::AmountTooSmall);
+        commitments.iter().try_for_each(check_commitment)?;
+        require!(MerkleTree::subtree_root(&commitments) == subtree_root, PrivaxError::SubtreeRootMismatch);
+        // The cap applies to each note, as if it were deposited on its own
+        if let Some((limits, price)) = usd_price(&ctx.accounts.program_state, &ctx.accounts.usd_limits, &ctx.accounts.price_feed)? {
+            amounts.iter().try_for_each(|&amount| limits.check_deposit(amount, &price))?;
+        }
+
+        let total = amounts.iter().try_fold(0u64, |sum, &amount| sum.checked_add(amount)).ok_or(PrivaxError::Overflow)?;
+        record_deposit_volume(&ctx.accounts.program_state, &mut ctx.accounts.deposit_volume, total)?;
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.user_token_account.to_account_info(),
+            to: ctx.accounts.program_token_vault.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), total)?;
+
+        ctx.accounts.program_state.record_deposit(total)?;
+        // One existing shard per commitment, in order, as remaining accounts
+        require!(ctx.remaining_accounts.len() == leaves, PrivaxError::CommitmentShardMismatch);
+        let program_state_key = ctx.accounts.program_state.key();
+        for (commitment, shard_info) in commitments.iter().zip(ctx.remaining_accounts) {
+            let mut shard = Account::<CommitmentShard>::try_from(shard_info)?;
+            let expected = Pubkey::create_program_address(
+                &[b"commitment_shard", program_state_key.as_ref(), shard.prefix.as_ref(), &[shard.bump]],
+                &crate::ID,
+            )
+            .map_err(|_| PrivaxError::CommitmentShardMismatch)?;
+            require_keys_eq!(expected, shard_info.key(), PrivaxError::CommitmentShardMismatch);
+            record_commitment(&mut shard, &ctx.accounts.user, &ctx.accounts.system_program, commitment)?;
+            shard.exit(&crate::ID)?;
+        }
+        ctx.accounts.merkle_tree.load_mut()?.append_subtree(subtree_root, leaves.trailing_zeros() as usize)?;
+
+        for (amount, commitment) in amounts.into_iter().zip(commitments) {
+            emit!(DepositOccurred {
+                version: EVENT_VERSION,
+                user: ctx.accounts.user.key(),
+                token_address: ctx.accounts.program_state.token_mint,
+                amount,
+                commitment,
+                slot: clock.slot,
+                unix_timestamp: clock.unix_timestamp,
+            });
+        }
+        Ok(())
+    }
+
+    // Permissionless queue processing so deferred work doesn't wait on the admin. Each
+    // item is a [pending_commitment, registrant] pair in remaining_accounts; items that
+    // aren't due yet are skipped rather than failing the whole batch. The cranker is paid
+    // a small reward per processed item.
+    pub fn crank<'info>(ctx: Context<'_, '_, '_, 'info, Crank<'info>>) -> Result<()> {
+        let pairs = ctx.remaining_accounts.chunks_exact(2);
+        require!(
+            !ctx.remaining_accounts.is_empty() && pairs.remainder().is_empty(),
+            PrivaxError::InvalidCrankAccounts
+        );
+        let clock = Clock::get()?;
+        let program_state_key = ctx.accounts.program_state.key();
+        let cranker = ctx.accounts.cranker.to_account_info();
+
+        let mut processed: u32 = 0;
+        let mut reward: u64 = 0;
+        for pair in pairs {
+            if let Some(paid) = close_expired_registration(&pair[0], &pair[1], &cranker, &program_state_key, &clock)? {
+                processed = processed.checked_add(1).ok_or(PrivaxError::Overflow)?;
+                reward = reward.saturating_add(paid);
+            }
+        }
+        require!(processed > 0, PrivaxError::NothingToCrank);
+
+        emit!(Cranked {
+            version: EVENT_VERSION,
+            cranker: cranker.key(),
+            processed,
+            reward,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Permissionless: anyone can open the shard for a nullifier prefix ahead of a withdrawal.
+    pub fn init_nullifier_shard(ctx: Context<InitNullifierShard>, prefix: [u8; 2]) -> Result<()> {
+        let shard = &mut ctx.accounts.nullifier_shard;
+        shard.prefix = prefix;
+        shard.count = 0;
+        shard.bump = *ctx.bumps.get("nullifier_shard").unwrap();
+        Ok(())
+    }
+
+    // Permissionless, like init_nullifier_shard; deposit_subtree needs its commitments' shards
+    // to exist, while the single-commitment deposits create theirs.
+    pub fn init_commitment_shard(ctx: Context<InitCommitmentShard>, prefix: [u8; 2]) -> Result<()> {
+        let shard = &mut ctx.accounts.commitment_shard;
+        shard.prefix = prefix;
+        shard.count = 0;
+        shard.bump = *ctx.bumps.get("commitment_shard").unwrap();
+        Ok(())
+    }
+
+    // Read-only: whether each nullifier has been spent, returned in order, for wallets
+    // reconciling their notes through a simulation and for programs checking notes over CPI.
+    // Remaining accounts are each nullifier's shard, in order; a shard that was never opened
+    // holds no spent nullifiers. The filter answers most unspent ones without their shard.
+    pub fn check_nullifiers<'info>(
+        ctx: Context<'_, '_, '_, 'info, CheckNullifiers<'info>>,
+        nullifier_hashes: Vec<[u8; 32]>,
+    ) -> Result<Vec<bool>> {
+        require!(ctx.remaining_accounts.len() == nullifier_hashes.len(), PrivaxError::NullifierShardCountMismatch);
+        let filter = ctx.accounts.nullifier_filter.load()?;
+        let program_state_key = ctx.accounts.program_state.key();
+        let mut spent = Vec::with_capacity(nullifier_hashes.len());
+        for (nullifier_hash, shard_info) in nullifier_hashes.iter().zip(ctx.remaining_accounts) {
+            if !filter.might_contain(nullifier_hash) {
+                spent.push(false);
+                continue;
+            }
+            let prefix = NullifierShard::prefix_of(nullifier_hash);
+            if shard_info.owner != &crate::ID {
+                let (expected, _) =
+                    Pubkey::find_program_address(&[b"nullifier_shard", program_state_key.as_ref(), prefix.as_ref()], &crate::ID);
+                require_keys_eq!(expected, shard_info.key(), PrivaxError::NullifierShardMismatch);
+                spent.push(false);
+                continue;
+            }
+            let shard = Account::<NullifierShard>::try_from(shard_info)?;
+            let expected = Pubkey::create_program_address(
+                &[b"nullifier_shard", program_state_key.as_ref(), prefix.as_ref(), &[shard.bump]],
+                &crate::ID,
+            )
+            .map_err(|_| PrivaxError::NullifierShardMismatch)?;
+            require_keys_eq!(expected, shard_info.key(), PrivaxError::NullifierShardMismatch);
+            let used = NullifierShard::SPACE + shard.count as usize * NullifierShard::ENTRY_SIZE;
+            let data = shard_info.try_borrow_data()?;
+            spent.push(data[NullifierShard::SPACE..used].chunks_exact(NullifierShard::ENTRY_SIZE).any(|entry| entry == nullifier_hash));
+        }
+        Ok(spent)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawTokens<'info>>,
+        a_proof: [u8; 64],  // Groth16 A: uncompressed G1 point, big-endian x || y
+        b_proof: [u8; 128], // Groth16 B: uncompressed G2 point, big-endian x.c1 || x.c0 || y.c1 || y.c0
+        c_proof: [u8; 64],  // Groth16 C: uncompressed G1 point
+        public_inputs: [u64; MAX_PUBLIC_INPUTS], // Fixed-size so decoding doesn't allocate; layout below
+        merkle_root: [u8; 32], // The root public_inputs[0] stands for, in full
+        nullifier_hash: [u8; 32], // The first note's nullifier hash, which public_inputs[1] stands for
+        extra_nullifier_hashes: Vec<[u8; 32]>, // Multi-note spends: the other notes' nullifier hashes, in order
+        recipient_address: Pubkey,
+        amount_to_withdraw: u64,
+        relayer_fee: u64, // Portion of amount_to_withdraw paid to the relayer, in pool token units
+        fee_swap_data: Vec<u8>, // Empty = pay fee in pool mint; otherwise instruction data for the fee swap CPI
+        min_fee_out: u64, // Minimum lamports (wSOL) the relayer accepts when the fee is swapped
+        circuit_version: u16, // Circuit the proof was generated for; selects the verifying key
+        plonk_proof: Vec<u8>, // PLONK-family circuits only; must be empty for Groth16
+        change_commitment: Option<[u8; 32]>, // Multi-note spends: commitment for the change note, if any
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let slot = clock.slot;
+        require!(!ctx.accounts.program_state.withdrawals_paused(slot), PrivaxError::PoolPaused);
+        require!(amount_to_withdraw > 0, PrivaxError::AmountTooSmall);
+        let fee_bps = ctx.accounts.fee_config.fee_bps(
+            ctx.accounts.program_token_vault.amount,
+            ctx.accounts.program_state.outstanding_deposits,
+        );
+        let flat_fee = match usd_price(&ctx.accounts.program_state, &ctx.accounts.usd_limits, &ctx.accounts.price_feed)? {
+            Some((limits, price)) => {
+                limits.check_withdrawal(amount_to_withdraw, &price)?;
+                limits.flat_fee(&price)?
+            }
+            None => 0,
+        };
+        let protocol_fee =
+            ctx.accounts.fee_config.protocol_fee(amount_to_withdraw, fee_bps).checked_add(flat_fee).ok_or(PrivaxError::Overflow)?;
+        let total_fees = relayer_fee.checked_add(protocol_fee).ok_or(PrivaxError::Overflow)?;
+        require!(total_fees <= amount_to_withdraw, PrivaxError::FeeExceedsAmount);
+        // A proof may spend up to MAX_INPUT_NOTES notes: the first nullifier sits at index 1 and
+        // the others follow the fixed inputs, then the change commitment if there is one.
+        // Slots past those must be zero so each proof has exactly one encoding.
+        let input_notes = 1 + extra_nullifier_hashes.len();
+        require!(input_notes <= MAX_INPUT_NOTES, PrivaxError::InvalidPublicInputCount);
+        let extra_nullifier_count = input_notes - 1;
+        let used_inputs = REQUIRED_PUBLIC_INPUTS_COUNT + extra_nullifier_count + usize::from(change_commitment.is_some());
+        require!(public_inputs[used_inputs..].iter().all(|&input| input == 0), PrivaxError::InvalidPublicInputCount);
+        let extra_nullifiers = &public_inputs[REQUIRED_PUBLIC_INPUTS_COUNT..REQUIRED_PUBLIC_INPUTS_COUNT + extra_nullifier_count];
+        if let Some(commitment) = change_commitment {
+            require!(public_inputs[used_inputs - 1] == commitment_input(&commitment), PrivaxError::ChangeCommitmentMismatch);
+        }
+        require!(public_inputs[0] == commitment_input(&merkle_root), PrivaxError::MerkleRootMismatch);
+        let nullifier_inputs = std::iter::once(&public_inputs[1]).chain(extra_nullifiers);
+        let nullifier_hashes = std::iter::once(&nullifier_hash).chain(&extra_nullifier_hashes);
+        require!(
+            nullifier_inputs.zip(nullifier_hashes).all(|(&input, hash)| input == commitment_input(hash)),
+            PrivaxError::NullifierHashMismatch
+        );
+        // Shards for the extra nullifiers lead the remaining accounts; any fee swap route follows.
+        require!(ctx.remaining_accounts.len() >= extra_nullifier_count, PrivaxError::NullifierShardMismatch);
+        let (extra_shards, swap_accounts) = ctx.remaining_accounts.split_at(extra_nullifier_count);
+        log_step("fees_and_layout");
+
+        // Public inputs expected order (as u64 for this example):
+        // public_inputs[0]: merkleRoot (u64 representation; merkle_root in the proof's inputs)
+        // public_inputs[1]: nullifierHash (u64 representation of bytes32; in full in the proof's inputs)
+        // public_inputs[2]: recipient (u64 representation of Pubkey)
+        // public_inputs[3]: amountToWithdraw (u64)
+        // public_inputs[4]: externalNullifier (u64, see external_nullifier)
+        // public_inputs[5]: proofSlot (u64, a recent slot at proving time)
+        // public_inputs[6]: relayerFee (u64, the relayer_fee this withdrawal pays)
+        // public_inputs[7]: relayer (u64 representation of the submitting relayer's Pubkey; 0 = none)
+        // public_inputs[8..]: further nullifierHashes, then the changeCommitment (multi-note spends;
+        //   their u64 representations here, the full values in the proof's inputs)
+
+        let verifying_key = ctx.accounts.verifying_key.load()?;
+        let expected_external_nullifier = external_nullifier(
+            &ctx.accounts.program_state.token_mint,
+            VARIABLE_DENOMINATION,
+            verifying_key.circuit_version,
+        );
+        require!(public_inputs[4] == expected_external_nullifier, PrivaxError::ExternalNullifierMismatch);
+
+        let proof_slot = public_inputs[5];
+        require!(
+            proof_slot <= slot && slot - proof_slot <= MAX_PROOF_AGE_SLOTS,
+            PrivaxError::ProofExpired
+        );
+        check_root(&ctx.accounts.merkle_tree, &ctx.accounts.root_archive, &merkle_root)?;
+
+        // Validate recipient and amount from public inputs
+        // This requires careful conversion if Pubkey/amounts are not directly u64 in ZK circuit
+        // For showcase, the recipient input is the first 8 bytes (LE) of its key (highly simplified, not robust)
+        let recipient_input_check = u64::from_le_bytes(recipient_address.as_ref()[..8].try_into().unwrap());
+
+        require!(recipient_input_check == public_inputs[2], PrivaxError::RecipientMismatch);
+        require!(amount_to_withdraw == public_inputs[3], PrivaxError::AmountMismatch);
+        // The relayer can't raise its own fee or take over someone else's proof
+        check_relayer_inputs(public_inputs[6], public_inputs[7], relayer_fee, &ctx.accounts.user.key())?;
+        log_step("public_inputs");
+
+        let _ = circuit_version; // Only used to derive the verifying_key account
+        require!(verifying_key.is_enabled(), PrivaxError::CircuitVersionDisabled);
+        let mut proof_inputs = spend_inputs(&ctx.accounts.program_state, &public_inputs[..used_inputs], proof_slot);
+        // The proof binds the root, nullifier hashes and change commitment in full, not just the
+        // bytes their u64 inputs carry
+        proof_inputs[0] = merkle_root;
+        proof_inputs[1] = nullifier_hash;
+        proof_inputs[REQUIRED_PUBLIC_INPUTS_COUNT..REQUIRED_PUBLIC_INPUTS_COUNT + extra_nullifier_count]
+            .copy_from_slice(&extra_nullifier_hashes);
+        if let Some(commitment) = change_commitment {
+            proof_inputs[used_inputs - 1] = commitment;
+        }
+        let is_valid_proof = verify_proof(&verifying_key, &a_proof, &b_proof, &c_proof, &plonk_proof, &proof_inputs)?;
+        require!(is_valid_proof, PrivaxError::InvalidZkProof);
+        log_step("verify_proof");
+
+        if ctx.accounts.program_state.admit_withdrawal(amount_to_withdraw, slot)? {
+            trip_withdrawal_limit(&mut ctx.accounts.program_state, amount_to_withdraw, &clock);
+        }
+
+        record_nullifiers(
+            &mut ctx.accounts.nullifier_shard,
+            extra_shards,
+            &ctx.accounts.nullifier_filter,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            &ctx.accounts.program_state.key(),
+            std::iter::once(nullifier_hash).chain(extra_nullifier_hashes.iter().copied()),
+        )?;
+
+        // The unspent remainder of the input notes goes back into the tree as a new note.
+        if let Some(commitment) = change_commitment {
+            let leaf_index = ctx.accounts.merkle_tree.load_mut()?.append(commitment)?;
+            emit!(ChangeNoteCommitted {
+                version: EVENT_VERSION,
+                commitment,
+                leaf_index,
+                slot: clock.slot,
+                unix_timestamp: clock.unix_timestamp,
+            });
+        }
+        log_step("nullifiers_and_change");
+
+        // Transfer tokens from program's vault to recipient
+        let program_state_key = ctx.accounts.program_state.key();
+        let vault_authority_bump = *ctx.bumps.get("program_token_vault_authority").unwrap();
+        let seeds = &[b"program_token_vault".as_ref(), program_state_key.as_ref(), &[vault_authority_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.program_token_vault.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: ctx.accounts.program_token_vault_authority.to_account_info(), // The PDA is the authority
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let recipient_amount = amount_to_withdraw.checked_sub(total_fees).ok_or(PrivaxError::Overflow)?;
+        token::transfer(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), recipient_amount)?;
+
+        if relayer_fee > 0 {
+            pay_relayer_fee(&ctx, swap_accounts, signer_seeds, relayer_fee, &fee_swap_data, min_fee_out)?;
+        }
+        log_step("transfers");
+
+        // Saturating so that an accounting discrepancy can never block exits.
+        let state = &mut ctx.accounts.program_state;
+        state.record_withdrawal(amount_to_withdraw)?;
+
+        // The protocol fee stays in the vault until distributed.
+        let fee_config = &mut ctx.accounts.fee_config;
+        fee_config.accrued_protocol_fees = fee_config.accrued_protocol_fees.checked_add(protocol_fee).ok_or(PrivaxError::Overflow)?;
+
+        emit!(WithdrawalOccurred {
+            version: EVENT_VERSION,
+            nullifier_hash,
+            recipient: recipient_address,
+            token_address: ctx.accounts.program_state.token_mint,
+            amount: amount_to_withdraw,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Pays one note out to several recipients at once, e.g. three contractors from one note.
+    // The proof binds every recipient and amount, and the amounts must add up to the note. The
+    // note as a whole is held to the USD threshold, as withdraw's amount is, and each payout
+    // bears the protocol fee as a withdrawal of its amount would; a relayer is paid by
+    // naming it as one of the recipients, and the proof binds that relayer as the submitter.
+    // The recipients' associated token accounts for the pool mint follow as remaining
+    // accounts, in payout order.
+    #[allow(clippy::too_many_arguments)]
+    pub fn withdraw_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawSplit<'info>>,
+        a_proof: [u8; 64],
+        b_proof: [u8; 128],
+        c_proof: [u8; 64],
+        public_inputs: [u64; SPLIT_PUBLIC_INPUTS],
+        merkle_root: [u8; 32], // The root public_inputs[0] stands for, in full
+        nullifier_hash: [u8; 32], // The nullifier hash public_inputs[1] stands for, in full
+        payouts: Vec<Payout>,
+        circuit_version: u16, // Split circuit the proof was generated for; selects the verifying key
+        plonk_proof: Vec<u8>, // PLONK-family circuits only; must be empty for Groth16
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let slot = clock.slot;
+        require!(!ctx.accounts.program_state.withdrawals_paused(slot), PrivaxError::PoolPaused);
+        require!(
+            (1..=MAX_SPLIT_RECIPIENTS).contains(&payouts.len()) && payouts.iter().all(|payout| payout.amount > 0),
+            PrivaxError::InvalidPayouts
+        );
+        require!(ctx.remaining_accounts.len() == payouts.len(), PrivaxError::InvalidRecipientAccount);
+
+        // Public inputs expected order (as u64, like withdraw's):
+        // public_inputs[0]: merkleRoot (merkle_root in full in the proof's inputs)
+        // public_inputs[1]: nullifierHash (nullifier_hash in full in the proof's inputs)
+        // public_inputs[2]: amount (the note's, equal to the sum of the payouts)
+        // public_inputs[3]: externalNullifier (see split_external_nullifier)
+        // public_inputs[4]: proofSlot
+        // public_inputs[5]: relayerFee (what the payouts pay the relayer)
+        // public_inputs[6]: relayer (first 8 bytes, LE, of the submitting relayer; 0 = none)
+        // public_inputs[7 + 2i], [8 + 2i]: recipient i (first 8 bytes, LE) and its amount;
+        //                                  zero past the last payout
+        let verifying_key = ctx.accounts.verifying_key.load()?;
+        let expected_external_nullifier =
+            split_external_nullifier(&ctx.accounts.program_state.token_mint, verifying_key.circuit_version);
+        require!(public_inputs[3] == expected_external_nullifier, PrivaxError::ExternalNullifierMismatch);
+        let proof_slot = public_inputs[4];
+        require!(
+            proof_slot <= slot && slot - proof_slot <= MAX_PROOF_AGE_SLOTS,
+            PrivaxError::ProofExpired
+        );
+        require!(public_inputs[0] == commitment_input(&merkle_root), PrivaxError::MerkleRootMismatch);
+        require!(public_inputs[1] == commitment_input(&nullifier_hash), PrivaxError::NullifierHashMismatch);
+        check_root(&ctx.accounts.merkle_tree, &ctx.accounts.root_archive, &merkle_root)?;
+        let mut total: u64 = 0;
+        for (i, pair) in public_inputs[7..].chunks_exact(2).enumerate() {
+            let expected = match payouts.get(i) {
+                Some(payout) => [u64::from_le_bytes(payout.recipient.as_ref()[..8].try_into().unwrap()), payout.amount],
+                None => [0, 0],
+            };
+            require!(pair == expected, PrivaxError::PayoutMismatch);
+            total = total.checked_add(expected[1]).ok_or(PrivaxError::Overflow)?;
+        }
+        require!(public_inputs[2] == total, PrivaxError::AmountMismatch);
+        let submitter = ctx.accounts.user.key();
+        let relayer_fee = payouts.iter().filter(|payout| payout.recipient == submitter).try_fold(0u64, |paid, payout| {
+            paid.checked_add(payout.amount).ok_or(PrivaxError::Overflow)
+        })?;
+        check_relayer_inputs(public_inputs[5], public_inputs[6], relayer_fee, &submitter)?;
+
+        let _ = circuit_version; // Only used to derive the verifying_key account
+        require!(verifying_key.is_enabled(), PrivaxError::CircuitVersionDisabled);
+        let mut proof_inputs = spend_inputs(&ctx.accounts.program_state, &public_inputs, proof_slot);
+        proof_inputs[0] = merkle_root;
+        proof_inputs[1] = nullifier_hash;
+        let is_valid_proof = verify_proof(&verifying_key, &a_proof, &b_proof, &c_proof, &plonk_proof, &proof_inputs)?;
+        require!(is_valid_proof, PrivaxError::InvalidZkProof);
+        drop(verifying_key);
+
+        if ctx.accounts.program_state.admit_withdrawal(total, clock.slot)? {
+            trip_withdrawal_limit(&mut ctx.accounts.program_state, total, &clock);
+        }
+
+        record_nullifier(
+            &mut ctx.accounts.nullifier_shard,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            &nullifier_hash,
+        )?;
+        ctx.accounts.nullifier_filter.load_mut()?.insert(&nullifier_hash);
+
+        // Fee rate from the vault before any payout, so every recipient pays the same rate.
+        let fee_bps = ctx.accounts.fee_config.fee_bps(
+            ctx.accounts.program_token_vault.amount,
+            ctx.accounts.program_state.outstanding_deposits,
+        );
+        // The payouts' total, the relayer's included, is held to the USD threshold once, so
+        // splitting a note can't get it under; each payout pays the flat fee, as a withdrawal would
+        let flat_fee = match usd_price(&ctx.accounts.program_state, &ctx.accounts.usd_limits, &ctx.accounts.price_feed)? {
+            Some((limits, price)) => {
+                limits.check_withdrawal(total, &price)?;
+                limits.flat_fee(&price)?
+            }
+            None => 0,
+        };
+        let program_state_key = ctx.accounts.program_state.key();
+        let vault_authority_bump = *ctx.bumps.get("program_token_vault_authority").unwrap();
+        let seeds = &[b"program_token_vault".as_ref(), program_state_key.as_ref(), &[vault_authority_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let mut protocol_fees: u64 = 0;
+        for (payout, recipient_info) in payouts.iter().zip(ctx.remaining_accounts) {
+            let recipient_account = Account::<TokenAccount>::try_from(recipient_info)?;
+            let token_mint = ctx.accounts.program_state.token_mint;
+            require!(
+                recipient_account.owner == payout.recipient
+                    && recipient_account.mint == token_mint
+                    && recipient_info.key() == get_associated_token_address(&payout.recipient, &token_mint),
+                PrivaxError::InvalidRecipientAccount
+            );
+            let protocol_fee =
+                ctx.accounts.fee_config.protocol_fee(payout.amount, fee_bps).checked_add(flat_fee).ok_or(PrivaxError::Overflow)?;
+            require!(protocol_fee <= payout.amount, PrivaxError::FeeExceedsAmount);
+            protocol_fees = protocol_fees.checked_add(protocol_fee).ok_or(PrivaxError::Overflow)?;
+
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.program_token_vault.to_account_info(),
+                to: recipient_info.clone(),
+                authority: ctx.accounts.program_token_vault_authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let recipient_amount = payout.amount.checked_sub(protocol_fee).ok_or(PrivaxError::Overflow)?;
+            token::transfer(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), recipient_amount)?;
+        }
+
+        // Saturating so that an accounting discrepancy can never block exits.
+        let state = &mut ctx.accounts.program_state;
+        state.record_withdrawal(total)?;
+        let fee_config = &mut ctx.accounts.fee_config;
+        fee_config.accrued_protocol_fees = fee_config.accrued_protocol_fees.checked_add(protocol_fees).ok_or(PrivaxError::Overflow)?;
+
+        emit!(SplitWithdrawalOccurred {
+            version: EVENT_VERSION,
+            nullifier_hash,
+            token_address: ctx.accounts.program_state.token_mint,
+            payouts,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Approves a pool for shielded_swap to deposit into. The deposit runs with this pool's vault
+    // authority as signer, so only pools the admin vouches for get it.
+    pub fn add_swap_target(ctx: Context<UpdateConfig>, target_program: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        state.require_no_admin_timelock()?;
+        approve_swap_target(state, &ctx.accounts.audit_log, target_program)
+    }
+
+    pub fn remove_swap_target(ctx: Context<UpdateConfig>, target_program: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        state.require_not_exit_only()?;
+        require!(state.swap_targets.contains(&target_program), PrivaxError::InvalidSwapTarget);
+        state.swap_targets.retain(|&x| x != target_program);
+        record_audit(&ctx.accounts.audit_log, state.admin, AuditAction::RemoveSwapTarget, &target_program, &None::<Pubkey>)?;
+
+        let clock = Clock::get()?;
+        emit!(SwapTargetRemoved { version: EVENT_VERSION, target_program, slot: clock.slot, unix_timestamp: clock.unix_timestamp });
+        Ok(())
+    }
+
+    // Spends a note in this pool and deposits a new one into another Privax pool (a separate
+    // deployment with its own mint, approved with add_swap_target), swapping the tokens through
+    // the configured swap program in between, so users change assets without leaving the
+    // shielded set. The proof fixes the
+    // output note's amount, which is the least the swap may return; anything above it goes to
+    // the submitter, who also pays for the transaction.
+    #[allow(clippy::too_many_arguments)]
+    pub fn shielded_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, ShieldedSwap<'info>>,
+        a_proof: [u8; 64],
+        b_proof: [u8; 128],
+        c_proof: [u8; 64],
+        public_inputs: [u64; SWAP_PUBLIC_INPUTS],
+        merkle_root: [u8; 32],         // The root public_inputs[0] stands for, in full
+        nullifier_hash: [u8; 32],      // The nullifier hash public_inputs[1] stands for, in full
+        amount_in: u64,                // Input note, in this pool's mint
+        amount_out: u64,               // Output note, in the target pool's mint
+        output_commitment: [u8; 32],   // Deposited into the target pool
+        swap_data: Vec<u8>,            // Instruction data for the swap program; its route follows as remaining accounts
+        circuit_version: u16,          // Swap circuit the proof was generated for; selects the verifying key
+        plonk_proof: Vec<u8>,          // PLONK-family circuits only; must be empty for Groth16
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let slot = clock.slot;
+        require!(!ctx.accounts.program_state.is_paused(slot), PrivaxError::PoolPaused);
+        require!(amount_in > 0 && amount_out > 0, PrivaxError::AmountTooSmall);
+        let target_program = ctx.accounts.target_program.key();
+        require!(
+            target_program != crate::ID && ctx.accounts.program_state.swap_targets.contains(&target_program),
+            PrivaxError::InvalidSwapTarget
+        );
+
+        // Public inputs expected order (as u64, like withdraw's):
+        // public_inputs[0]: merkleRoot (merkle_root in full in the proof's inputs)
+        // public_inputs[1]: nullifierHash (nullifier_hash in full in the proof's inputs)
+        // public_inputs[2]: targetPool (first 8 bytes, LE, of the target program ID)
+        // public_inputs[3]: amountIn
+        // public_inputs[4]: externalNullifier (see swap_external_nullifier)
+        // public_inputs[5]: proofSlot
+        // public_inputs[6]: amountOut
+        // public_inputs[7]: outputCommitment (in full in the proof's inputs)
+        let verifying_key = ctx.accounts.verifying_key.load()?;
+        let expected_external_nullifier =
+            swap_external_nullifier(&ctx.accounts.program_state.token_mint, verifying_key.circuit_version);
+        require!(public_inputs[4] == expected_external_nullifier, PrivaxError::ExternalNullifierMismatch);
+        let proof_slot = public_inputs[5];
+        require!(
+            proof_slot <= slot && slot - proof_slot <= MAX_PROOF_AGE_SLOTS,
+            PrivaxError::ProofExpired
+        );
+        check_root(&ctx.accounts.merkle_tree, &ctx.accounts.root_archive, &merkle_root)?;
+        let target_input = u64::from_le_bytes(target_program.as_ref()[..8].try_into().unwrap());
+        require!(public_inputs[2] == target_input, PrivaxError::InvalidSwapTarget);
+        require!(public_inputs[3] == amount_in && public_inputs[6] == amount_out, PrivaxError::AmountMismatch);
+        require!(public_inputs[7] == commitment_input(&output_commitment), PrivaxError::OutputCommitmentMismatch);
+        require!(public_inputs[0] == commitment_input(&merkle_root), PrivaxError::MerkleRootMismatch);
+        require!(public_inputs[1] == commitment_input(&nullifier_hash), PrivaxError::NullifierHashMismatch);
+
+        let _ = circuit_version; // Only used to derive the verifying_key account
+        require!(verifying_key.is_enabled(), PrivaxError::CircuitVersionDisabled);
+        let mut proof_inputs = spend_inputs(&ctx.accounts.program_state, &public_inputs, proof_slot);
+        // The proof binds the root, nullifier hash and output commitment in full, not just their
+        // u64 inputs' bytes
+        proof_inputs[0] = merkle_root;
+        proof_inputs[1] = nullifier_hash;
+        proof_inputs[7] = output_commitment;
+        let is_valid_proof = verify_proof(&verifying_key, &a_proof, &b_proof, &c_proof, &plonk_proof, &proof_inputs)?;
+        require!(is_valid_proof, PrivaxError::InvalidZkProof);
+        drop(verifying_key);
+
+        // A swap moves amount_in out of the vault like a withdrawal, so it counts toward the limit
+        if ctx.accounts.program_state.admit_withdrawal(amount_in, clock.slot)? {
+            trip_withdrawal_limit(&mut ctx.accounts.program_state, amount_in, &clock);
+        }
+
+        record_nullifier(
+            &mut ctx.accounts.nullifier_shard,
+            &ctx.accounts.submitter,
+            &ctx.accounts.system_program,
+            &nullifier_hash,
+        )?;
+        ctx.accounts.nullifier_filter.load_mut()?.insert(&nullifier_hash);
+
+        // The protocol fee is charged as on a withdrawal and stays in the vault.
+        let fee_bps = ctx.accounts.fee_config.fee_bps(
+            ctx.accounts.program_token_vault.amount,
+            ctx.accounts.program_state.outstanding_deposits,
+        );
+        let protocol_fee = ctx.accounts.fee_config.protocol_fee(amount_in, fee_bps);
+        let swap_amount = amount_in.checked_sub(protocol_fee).ok_or(PrivaxError::FeeExceedsAmount)?;
+
+        let swap_program = &ctx.accounts.swap_program;
+        let fee_swap_program = ctx.accounts.fee_config.fee_swap_program;
+        require!(
+            fee_swap_program != Pubkey::default() && swap_program.key() == fee_swap_program,
+            PrivaxError::InvalidFeeSwapProgram
+        );
+        let program_state_key = ctx.accounts.program_state.key();
+        let vault_authority_bump = *ctx.bumps.get("program_token_vault_authority").unwrap();
+        let seeds = &[b"program_token_vault".as_ref(), program_state_key.as_ref(), &[vault_authority_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let vault_before = ctx.accounts.program_token_vault.amount;
+        let output_before = ctx.accounts.swap_output_account.amount;
+        let vault_authority = ctx.accounts.program_token_vault_authority.key();
+        invoke_swap(swap_program, ctx.remaining_accounts, &vault_authority, &swap_data, signer_seeds)?;
+
+        // As with fee swaps, measure what actually moved rather than trust the swap program.
+        ctx.accounts.program_token_vault.reload()?;
+        ctx.accounts.swap_output_account.reload()?;
+        let spent = vault_before.checked_sub(ctx.accounts.program_token_vault.amount).ok_or(PrivaxError::Overflow)?;
+        require!(spent <= swap_amount, PrivaxError::SwapOverspent);
+        let output_after_swap = ctx.accounts.swap_output_account.amount;
+        let received = output_after_swap.checked_sub(output_before).ok_or(PrivaxError::Overflow)?;
+        require!(received >= amount_out, PrivaxError::SwapSlippage);
+
+        // The output note enters the target pool through its own deposit instruction, with this
+        // pool's vault authority as the depositor. The authority can't pay for accounts, so the
+        // submitter opens the output commitment's shard if need be and prepays the entry the
+        // deposit appends to it. The target vault must already exist.
+        let accounts = &ctx.accounts;
+        let target_shard = accounts.target_commitment_shard.to_account_info();
+        if target_shard.data_is_empty() {
+            let init_shard_ix = anchor_lang::solana_program::instruction::Instruction {
+                program_id: target_program,
+                accounts: vec![
+                    AccountMeta::new_readonly(accounts.target_program_state.key(), false),
+                    AccountMeta::new(target_shard.key(), false),
+                    AccountMeta::new(accounts.submitter.key(), true),
+                    AccountMeta::new_readonly(accounts.system_program.key(), false),
+                ],
+                data: anchor_lang::InstructionData::data(&crate::instruction::InitCommitmentShard {
+                    prefix: [output_commitment[0], output_commitment[1]],
+                }),
+            };
+            anchor_lang::solana_program::program::invoke(
+                &init_shard_ix,
+                &[
+                    accounts.target_program_state.to_account_info(),
+                    target_shard.clone(),
+                    accounts.submitter.to_account_info(),
+                    accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+        let entry_rent = Rent::get()?
+            .minimum_balance(target_shard.data_len() + output_commitment.len())
+            .saturating_sub(target_shard.lamports());
+        if entry_rent > 0 {
+            let cpi_accounts = anchor_lang::system_program::Transfer {
+                from: accounts.submitter.to_account_info(),
+                to: target_shard.clone(),
+            };
+            anchor_lang::system_program::transfer(
+                CpiContext::new(accounts.system_program.to_account_info(), cpi_accounts),
+                entry_rent,
+            )?;
+        }
+        let deposit_ix = anchor_lang::solana_program::instruction::Instruction {
+            program_id: target_program,
+            accounts: vec![
+                AccountMeta::new(accounts.target_program_state.key(), false),
+                AccountMeta::new(accounts.target_fee_config.key(), false),
+                AccountMeta::new(accounts.target_merkle_tree.key(), false),
+                AccountMeta::new(target_shard.key(), false),
+                AccountMeta::new_readonly(target_program, false), // No referrer_account
+                AccountMeta::new_readonly(target_program, false), // No deposit_receipt
+                AccountMeta::new(vault_authority, true),
+                AccountMeta::new(accounts.swap_output_account.key(), false),
+                AccountMeta::new_readonly(accounts.target_token_mint.key(), false),
+                AccountMeta::new(accounts.target_vault.key(), false),
+                AccountMeta::new_readonly(accounts.target_vault_authority.key(), false),
+                AccountMeta::new_readonly(accounts.token_program.key(), false),
+                AccountMeta::new_readonly(accounts.system_program.key(), false),
+                AccountMeta::new_readonly(accounts.rent.key(), false),
+            ],
+            data: anchor_lang::InstructionData::data(&crate::instruction::Deposit {
+                amount: amount_out,
+                commitment: output_commitment,
+            }),
+        };
+        anchor_lang::solana_program::program::invoke_signed(
+            &deposit_ix,
+            &[
+                accounts.target_program_state.to_account_info(),
+                accounts.target_fee_config.to_account_info(),
+                accounts.target_merkle_tree.to_account_info(),
+                target_shard,
+                accounts.target_program.to_account_info(),
+                accounts.program_token_vault_authority.to_account_info(),
+                accounts.swap_output_account.to_account_info(),
+                accounts.target_token_mint.to_account_info(),
+                accounts.target_vault.to_account_info(),
+                accounts.target_vault_authority.to_account_info(),
+                accounts.token_program.to_account_info(),
+                accounts.system_program.to_account_info(),
+                accounts.rent.to_account_info(),
+            ],
+            signer_seeds,
+        )?;
+
+        // The target pool is approved, but the output note is only backed if its deposit took
+        // exactly amount_out.
+        ctx.accounts.swap_output_account.reload()?;
+        let deposited = output_after_swap.checked_sub(ctx.accounts.swap_output_account.amount);
+        require!(deposited == Some(amount_out), PrivaxError::SwapDepositMismatch);
+
+        let accounts = &ctx.accounts;
+        let surplus = received.checked_sub(amount_out).ok_or(PrivaxError::Overflow)?;
+        if surplus > 0 {
+            let cpi_accounts = Transfer {
+                from: accounts.swap_output_account.to_account_info(),
+                to: accounts.submitter_output_account.to_account_info(),
+                authority: accounts.program_token_vault_authority.to_account_info(),
+            };
+            let cpi_program = accounts.token_program.to_account_info();
+            token::transfer(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), surplus)?;
+        }
+
+        let state = &mut ctx.accounts.program_state;
+        state.record_withdrawal(amount_in)?;
+        let fee_config = &mut ctx.accounts.fee_config;
+        fee_config.accrued_protocol_fees = fee_config.accrued_protocol_fees.checked_add(protocol_fee).ok_or(PrivaxError::Overflow)?;
+
+        emit!(ShieldedSwapOccurred {
+            version: EVENT_VERSION,
+            nullifier_hash,
+            target_program,
+            amount_in,
+            amount_out,
+            output_commitment,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Spends one note and commits its amount to several new notes in the same pool, e.g. to
+    // break a large note into amounts that withdraw without standing out. Nothing leaves the
+    // vault, so no fee is charged; the payer covers the nullifier's rent. The proof shows the
+    // outputs add up to the input.
+    #[allow(clippy::too_many_arguments)]
+    pub fn split_note(
+        ctx: Context<ShieldedTransfer>,
+        a_proof: [u8; 64],
+        b_proof: [u8; 128],
+        c_proof: [u8; 64],
+        public_inputs: [u64; NOTE_TRANSFER_PUBLIC_INPUTS],
+        merkle_root: [u8; 32], // The root public_inputs[0] stands for, in full
+        nullifier_hash: [u8; 32], // The nullifier hash public_inputs[1] stands for, in full
+        circuit_version: u16, // Note split circuit the proof was generated for; selects the verifying key
+        output_commitments: Vec<[u8; 32]>,
+        plonk_proof: Vec<u8>, // PLONK-family circuits only; must be empty for Groth16
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let slot = clock.slot;
+        require!(!ctx.accounts.program_state.is_paused(slot), PrivaxError::PoolPaused);
+        require!(
+            (2..=MAX_NOTE_SPLIT_OUTPUTS).contains(&output_commitments.len()),
+            PrivaxError::InvalidNoteCount
+        );
+
+        // Public inputs expected order (as u64, like withdraw's):
+        // public_inputs[0]: merkleRoot (merkle_root in full in the proof's inputs)
+        // public_inputs[1]: nullifierHash (nullifier_hash in full in the proof's inputs)
+        // public_inputs[2..6]: outputCommitments, zero past the last (in full in the proof's inputs)
+        // public_inputs[6]: externalNullifier (see note_split_external_nullifier)
+        // public_inputs[7]: proofSlot
+        for (i, &input) in public_inputs[2..2 + MAX_NOTE_SPLIT_OUTPUTS].iter().enumerate() {
+            let expected = output_commitments.get(i).map_or(0, commitment_input);
+            require!(input == expected, PrivaxError::OutputCommitmentMismatch);
+        }
+        require!(public_inputs[0] == commitment_input(&merkle_root), PrivaxError::MerkleRootMismatch);
+        require!(public_inputs[1] == commitment_input(&nullifier_hash), PrivaxError::NullifierHashMismatch);
+        let verifying_key = ctx.accounts.verifying_key.load()?;
+        let expected_external_nullifier =
+            note_split_external_nullifier(&ctx.accounts.program_state.token_mint, verifying_key.circuit_version);
+        require!(public_inputs[6] == expected_external_nullifier, PrivaxError::ExternalNullifierMismatch);
+        let proof_slot = public_inputs[7];
+        require!(
+            proof_slot <= slot && slot - proof_slot <= MAX_PROOF_AGE_SLOTS,
+            PrivaxError::ProofExpired
+        );
+        check_root(&ctx.accounts.merkle_tree, &ctx.accounts.root_archive, &merkle_root)?;
+
+        let _ = circuit_version; // Only used to derive the verifying_key account
+        require!(verifying_key.is_enabled(), PrivaxError::CircuitVersionDisabled);
+        let mut proof_inputs = spend_inputs(&ctx.accounts.program_state, &public_inputs, proof_slot);
+        // The proof binds the root, nullifier hash and each output commitment in full, not just
+        // their u64 inputs' bytes
+        proof_inputs[0] = merkle_root;
+        proof_inputs[1] = nullifier_hash;
+        for (input, commitment) in proof_inputs[2..].iter_mut().zip(&output_commitments) {
+            *input = *commitment;
+        }
+        let is_valid_proof = verify_proof(&verifying_key, &a_proof, &b_proof, &c_proof, &plonk_proof, &proof_inputs)?;
+        require!(is_valid_proof, PrivaxError::InvalidZkProof);
+        drop(verifying_key);
+
+        record_nullifiers(
+            &mut ctx.accounts.nullifier_shard,
+            &[],
+            &ctx.accounts.nullifier_filter,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            &ctx.accounts.program_state.key(),
+            std::iter::once(nullifier_hash),
+        )?;
+        let mut merkle_tree = ctx.accounts.merkle_tree.load_mut()?;
+        for commitment in output_commitments {
+            let leaf_index = merkle_tree.append(commitment)?;
+            emit!(ChangeNoteCommitted {
+                version: EVENT_VERSION,
+                commitment,
+                leaf_index,
+                slot: clock.slot,
+                unix_timestamp: clock.unix_timestamp,
+            });
+        }
+        Ok(())
+    }
+
+    // The reverse of split_note: spends several notes and commits their total to one new note,
+    // so dust can be consolidated before a withdrawal. The shards for the nullifiers after the
+    // first follow as remaining accounts, as in withdraw.
+    #[allow(clippy::too_many_arguments)]
+    pub fn merge_notes<'info>(
+        ctx: Context<'_, '_, '_, 'info, ShieldedTransfer<'info>>,
+        a_proof: [u8; 64],
+        b_proof: [u8; 128],
+        c_proof: [u8; 64],
+        public_inputs: [u64; NOTE_TRANSFER_PUBLIC_INPUTS],
+        merkle_root: [u8; 32], // The root public_inputs[0] stands for, in full
+        nullifier_hash: [u8; 32], // The first note's nullifier hash, which public_inputs[1] stands for
+        circuit_version: u16, // Note merge circuit the proof was generated for; selects the verifying key
+        extra_nullifier_hashes: Vec<[u8; 32]>, // The other notes' nullifier hashes, in order
+        output_commitment: [u8; 32],
+        plonk_proof: Vec<u8>, // PLONK-family circuits only; must be empty for Groth16
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let slot = clock.slot;
+        require!(!ctx.accounts.program_state.is_paused(slot), PrivaxError::PoolPaused);
+        let input_notes = 1 + extra_nullifier_hashes.len();
+        require!((2..=MAX_INPUT_NOTES).contains(&input_notes), PrivaxError::InvalidNoteCount);
+        require!(ctx.remaining_accounts.len() == input_notes - 1, PrivaxError::NullifierShardMismatch);
+
+        // Public inputs expected order (as u64, like withdraw's):
+        // public_inputs[0]: merkleRoot (merkle_root in full in the proof's inputs)
+        // public_inputs[1..5]: nullifierHashes, zero past the last (in full in the proof's inputs)
+        // public_inputs[5]: outputCommitment (in full in the proof's inputs)
+        // public_inputs[6]: externalNullifier (see note_merge_external_nullifier)
+        // public_inputs[7]: proofSlot
+        let nullifiers = &public_inputs[1..1 + input_notes];
+        require!(
+            public_inputs[1 + input_notes..1 + MAX_INPUT_NOTES].iter().all(|&input| input == 0),
+            PrivaxError::InvalidPublicInputCount
+        );
+        require!(public_inputs[5] == commitment_input(&output_commitment), PrivaxError::OutputCommitmentMismatch);
+        require!(public_inputs[0] == commitment_input(&merkle_root), PrivaxError::MerkleRootMismatch);
+        let nullifier_hashes = std::iter::once(&nullifier_hash).chain(&extra_nullifier_hashes);
+        require!(
+            nullifiers.iter().zip(nullifier_hashes).all(|(&input, hash)| input == commitment_input(hash)),
+            PrivaxError::NullifierHashMismatch
+        );
+        let verifying_key = ctx.accounts.verifying_key.load()?;
+        let expected_external_nullifier =
+            note_merge_external_nullifier(&ctx.accounts.program_state.token_mint, verifying_key.circuit_version);
+        require!(public_inputs[6] == expected_external_nullifier, PrivaxError::ExternalNullifierMismatch);
+        let proof_slot = public_inputs[7];
+        require!(
+            proof_slot <= slot && slot - proof_slot <= MAX_PROOF_AGE_SLOTS,
+            PrivaxError::ProofExpired
+        );
+        check_root(&ctx.accounts.merkle_tree, &ctx.accounts.root_archive, &merkle_root)?;
+
+        let _ = circuit_version; // Only used to derive the verifying_key account
+        require!(verifying_key.is_enabled(), PrivaxError::CircuitVersionDisabled);
+        let mut proof_inputs = spend_inputs(&ctx.accounts.program_state, &public_inputs, proof_slot);
+        // The proof binds the root, nullifier hashes and output commitment in full, not just their
+        // u64 inputs' bytes
+        proof_inputs[0] = merkle_root;
+        proof_inputs[1] = nullifier_hash;
+        proof_inputs[2..1 + input_notes].copy_from_slice(&extra_nullifier_hashes);
+        proof_inputs[5] = output_commitment;
+        let is_valid_proof = verify_proof(&verifying_key, &a_proof, &b_proof, &c_proof, &plonk_proof, &proof_inputs)?;
+        require!(is_valid_proof, PrivaxError::InvalidZkProof);
+        drop(verifying_key);
+
+        record_nullifiers(
+            &mut ctx.accounts.nullifier_shard,
+            ctx.remaining_accounts,
+            &ctx.accounts.nullifier_filter,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            &ctx.accounts.program_state.key(),
+            std::iter::once(nullifier_hash).chain(extra_nullifier_hashes.iter().copied()),
+        )?;
+        let leaf_index = ctx.accounts.merkle_tree.load_mut()?.append(output_commitment)?;
+        emit!(ChangeNoteCommitted {
+            version: EVENT_VERSION,
+            commitment: output_commitment,
+            leaf_index,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Sets up a recurring payment of `amount` to `recipient` every `interval_slots`, starting at
+    // `first_payment_slot`, with an empty escrow. Fund it by withdrawing notes with the escrow
+    // as recipient token account (and the schedule as recipient): a proof is only valid for
+    // MAX_PROOF_AGE_SLOTS, so executions can't carry proofs made in advance.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_payment_schedule(
+        ctx: Context<CreatePaymentSchedule>,
+        schedule_id: u64,
+        recipient: Pubkey,
+        amount: u64,
+        executor_fee: u64,
+        interval_slots: u64,
+        first_payment_slot: u64,
+        payments: u32,
+    ) -> Result<()> {
+        require!(amount > 0 && interval_slots > 0 && payments > 0, PrivaxError::InvalidPaymentSchedule);
+        let schedule = &mut ctx.accounts.payment_schedule;
+        schedule.owner = ctx.accounts.owner.key();
+        schedule.schedule_id = schedule_id;
+        schedule.recipient = recipient;
+        schedule.amount = amount;
+        schedule.executor_fee = executor_fee;
+        schedule.interval_slots = interval_slots;
+        schedule.next_payment_slot = first_payment_slot;
+        schedule.payments_remaining = payments;
+        schedule.bump = *ctx.bumps.get("payment_schedule").unwrap();
+
+        let clock = Clock::get()?;
+        emit!(PaymentScheduleCreated {
+            version: EVENT_VERSION,
+            schedule: schedule.key(),
+            recipient,
+            amount,
+            interval_slots,
+            first_payment_slot,
+            payments,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Permissionless, like crank: relayers (or anyone) pay a due installment out of the escrow
+    // and take the executor fee. One payment per call; a schedule that fell behind catches up
+    // over several calls.
+    pub fn execute_scheduled_payment(ctx: Context<ExecuteScheduledPayment>) -> Result<()> {
+        let clock = Clock::get()?;
+        let slot = clock.slot;
+        let schedule = &ctx.accounts.payment_schedule;
+        require!(schedule.is_due(slot), PrivaxError::PaymentNotDue);
+        let total = schedule.amount.checked_add(schedule.executor_fee).ok_or(PrivaxError::Overflow)?;
+        require!(ctx.accounts.schedule_escrow.amount >= total, PrivaxError::ScheduleUnderfunded);
+
+        let program_state_key = ctx.accounts.program_state.key();
+        let schedule_id = schedule.schedule_id.to_le_bytes();
+        let seeds = &[
+            b"payment_schedule".as_ref(),
+            program_state_key.as_ref(),
+            schedule.owner.as_ref(),
+            &schedule_id,
+            &[schedule.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let payments = [
+            (ctx.accounts.recipient_token_account.to_account_info(), schedule.amount),
+            (ctx.accounts.executor_token_account.to_account_info(), schedule.executor_fee),
+        ];
+        for (to, amount) in payments {
+            if amount == 0 {
+                continue;
+            }
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.schedule_escrow.to_account_info(),
+                to,
+                authority: ctx.accounts.payment_schedule.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), amount)?;
+        }
+
+        let schedule = &mut ctx.accounts.payment_schedule;
+        schedule.payments_remaining = schedule.payments_remaining.checked_sub(1).ok_or(PrivaxError::Overflow)?;
+        schedule.next_payment_slot = schedule.next_payment_slot.saturating_add(schedule.interval_slots);
+        emit!(ScheduledPaymentExecuted {
+            version: EVENT_VERSION,
+            schedule: schedule.key(),
+            recipient: schedule.recipient,
+            amount: schedule.amount,
+            executor: ctx.accounts.executor.key(),
+            executor_fee: schedule.executor_fee,
+            payments_remaining: schedule.payments_remaining,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Ends a schedule, finished or not: the escrow's balance goes back to the owner and both
+    // accounts are closed to them.
+    pub fn cancel_payment_schedule(ctx: Context<CancelPaymentSchedule>) -> Result<()> {
+        let program_state_key = ctx.accounts.program_state.key();
+        let schedule = &ctx.accounts.payment_schedule;
+        let schedule_id = schedule.schedule_id.to_le_bytes();
+        let seeds = &[
+            b"payment_schedule".as_ref(),
+            program_state_key.as_ref(),
+            schedule.owner.as_ref(),
+            &schedule_id,
+            &[schedule.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+
+        let refunded = ctx.accounts.schedule_escrow.amount;
+        if refunded > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.schedule_escrow.to_account_info(),
+                to: ctx.accounts.owner_token_account.to_account_info(),
+                authority: ctx.accounts.payment_schedule.to_account_info(),
+            };
+            token::transfer(CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer_seeds), refunded)?;
+        }
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.schedule_escrow.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.payment_schedule.to_account_info(),
+        };
+        token::close_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+
+        let clock = Clock::get()?;
+        emit!(PaymentScheduleClosed {
+            version: EVENT_VERSION,
+            schedule: schedule.key(),
+            refunded,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Turns a freshly initialized pool into an NFT pool. The pool's token_mint is cleared, which
+    // shuts every fungible instruction out through its mint and vault constraints; deposit_nft and
+    // withdraw_nft take over, each NFT held in its own vault at the vault authority's ATA.
+    pub fn enable_nft_mode(ctx: Context<EnableNftMode>) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        require!(ctx.accounts.merkle_tree.load()?.next_index == 0, PrivaxError::PoolNotEmpty);
+        let state = &mut ctx.accounts.program_state;
+        require!(state.pool_mode == PoolMode::Fungible, PrivaxError::WrongPoolMode);
+        let old_value = (state.pool_mode, state.token_mint);
+        state.pool_mode = PoolMode::Nft;
+        state.token_mint = Pubkey::default();
+        let new_value = (state.pool_mode, state.token_mint);
+        record_audit(&ctx.accounts.audit_log, state.admin, AuditAction::EnableNftMode, &old_value, &new_value)?;
+
+        let clock = Clock::get()?;
+        emit!(PoolModeChanged {
+            version: EVENT_VERSION,
+            pool_mode: PoolMode::Nft,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Deposits one NFT against a note committing to its mint (see nft_withdraw.circom).
+    // Programmable NFTs move through Token Metadata and need the `programmable` accounts.
+    pub fn deposit_nft(ctx: Context<DepositNft>, commitment: [u8; 32]) -> Result<()> {
+        let state = &ctx.accounts.program_state;
+        require!(state.pool_mode == PoolMode::Nft, PrivaxError::WrongPoolMode);
+        let clock = Clock::get()?;
+        require!(!state.is_paused(clock.slot), PrivaxError::PoolPaused);
+        require!(!state.requires_attestation(), PrivaxError::ComplianceAttestationRequired);
+        require!(!state.requires_kyc(), PrivaxError::KycCredentialRequired);
+        let mint = &ctx.accounts.nft_mint;
+        require!(mint.supply == 1 && mint.decimals == 0, PrivaxError::NotAnNft);
+        check_commitment(&commitment)?;
+
+        transfer_nft(
+            NftTransfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.nft_vault.to_account_info(),
+                to_owner: ctx.accounts.program_token_vault_authority.to_account_info(),
+                mint: mint.to_account_info(),
+                payer: ctx.accounts.user.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            &ctx.accounts.programmable,
+            &[],
+        )?;
+
+        // The shard's address is derived from the commitment's prefix, so this only fills in a new shard
+        let shard = &mut ctx.accounts.commitment_shard;
+        shard.prefix = CommitmentShard::prefix_of(&commitment);
+        shard.bump = *ctx.bumps.get("commitment_shard").unwrap();
+        record_commitment(shard, &ctx.accounts.user, &ctx.accounts.system_program, &commitment)?;
+        let leaf_index = ctx.accounts.merkle_tree.load_mut()?.append(commitment)?;
+        emit!(NftDepositOccurred {
+            version: EVENT_VERSION,
+            user: ctx.accounts.user.key(),
+            mint: mint.key(),
+            commitment,
+            leaf_index,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn withdraw_nft(
+        ctx: Context<WithdrawNft>,
+        a_proof: [u8; 64],
+        b_proof: [u8; 128],
+        c_proof: [u8; 64],
+        public_inputs: [u64; NFT_PUBLIC_INPUTS],
+        merkle_root: [u8; 32], // The root public_inputs[0] stands for, in full
+        nullifier_hash: [u8; 32], // The nullifier hash public_inputs[1] stands for, in full
+        circuit_version: u16, // NFT circuit the proof was generated for; selects the verifying key
+        plonk_proof: Vec<u8>, // PLONK-family circuits only; must be empty for Groth16
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let slot = clock.slot;
+        let state = &ctx.accounts.program_state;
+        require!(state.pool_mode == PoolMode::Nft, PrivaxError::WrongPoolMode);
+        require!(!state.withdrawals_paused(slot), PrivaxError::PoolPaused);
+
+        // Public inputs expected order (as u64, like withdraw's):
+        // public_inputs[0]: merkleRoot (merkle_root in full in the proof's inputs)
+        // public_inputs[1]: nullifierHash (nullifier_hash in full in the proof's inputs)
+        // public_inputs[2]: recipient (first 8 bytes, LE)
+        // public_inputs[3]: mint (first 8 bytes, LE)
+        // public_inputs[4]: externalNullifier (see nft_external_nullifier)
+        // public_inputs[5]: proofSlot
+        let recipient = ctx.accounts.recipient.key();
+        let mint = ctx.accounts.nft_mint.key();
+        require!(public_inputs[2] == commitment_input(&recipient.to_bytes()), PrivaxError::RecipientMismatch);
+        require!(public_inputs[3] == commitment_input(&mint.to_bytes()), PrivaxError::MintMismatch);
+        require!(public_inputs[0] == commitment_input(&merkle_root), PrivaxError::MerkleRootMismatch);
+        require!(public_inputs[1] == commitment_input(&nullifier_hash), PrivaxError::NullifierHashMismatch);
+        let verifying_key = ctx.accounts.verifying_key.load()?;
+        let expected_external_nullifier = nft_external_nullifier(verifying_key.circuit_version);
+        require!(public_inputs[4] == expected_external_nullifier, PrivaxError::ExternalNullifierMismatch);
+        let proof_slot = public_inputs[5];
+        require!(
+            proof_slot <= slot && slot - proof_slot <= MAX_PROOF_AGE_SLOTS,
+            PrivaxError::ProofExpired
+        );
+        check_root(&ctx.accounts.merkle_tree, &ctx.accounts.root_archive, &merkle_root)?;
+
+        let _ = circuit_version; // Only used to derive the verifying_key account
+        require!(verifying_key.is_enabled(), PrivaxError::CircuitVersionDisabled);
+        let mut proof_inputs = spend_inputs(&ctx.accounts.program_state, &public_inputs, proof_slot);
+        proof_inputs[0] = merkle_root;
+        proof_inputs[1] = nullifier_hash;
+        let is_valid_proof = verify_proof(&verifying_key, &a_proof, &b_proof, &c_proof, &plonk_proof, &proof_inputs)?;
+        require!(is_valid_proof, PrivaxError::InvalidZkProof);
+        drop(verifying_key);
+
+        let program_state_key = ctx.accounts.program_state.key();
+        record_nullifiers(
+            &mut ctx.accounts.nullifier_shard,
+            &[],
+            &ctx.accounts.nullifier_filter,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            &program_state_key,
+            std::iter::once(nullifier_hash),
+        )?;
+
+        let vault_authority_bump = *ctx.bumps.get("program_token_vault_authority").unwrap();
+        let seeds = &[b"program_token_vault".as_ref(), program_state_key.as_ref(), &[vault_authority_bump]];
+        transfer_nft(
+            NftTransfer {
+                from: ctx.accounts.nft_vault.to_account_info(),
+                authority: ctx.accounts.program_token_vault_authority.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                to_owner: ctx.accounts.recipient.to_account_info(),
+                mint: ctx.accounts.nft_mint.to_account_info(),
+                payer: ctx.accounts.user.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            &ctx.accounts.programmable,
+            &[&seeds[..]],
+        )?;
+
+        emit!(NftWithdrawalOccurred {
+            version: EVENT_VERSION,
+            nullifier_hash,
+            recipient,
+            mint,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Creates or updates the receipt NFT config. Create the Bubblegum tree first and make the
+    // config account (receipt_nft_config_address) its tree delegate.
+    pub fn set_receipt_nft_config(
+        ctx: Context<SetReceiptNftConfig>,
+        enabled: bool,
+        merkle_tree: Pubkey,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        require!(
+            name.len() <= ReceiptNftConfig::MAX_NAME_LEN
+                && symbol.len() <= ReceiptNftConfig::MAX_SYMBOL_LEN
+                && uri.len() <= ReceiptNftConfig::MAX_URI_LEN,
+            PrivaxError::InvalidReceiptNftConfig
+        );
+        let config = &mut ctx.accounts.receipt_nft_config;
+        let old_config = config.clone().into_inner();
+        config.enabled = enabled;
+        config.merkle_tree = merkle_tree;
+        config.name = name;
+        config.symbol = symbol;
+        config.uri = uri;
+        config.bump = *ctx.bumps.get("receipt_nft_config").unwrap();
+        let actor = ctx.accounts.admin.key();
+        record_audit(&ctx.accounts.audit_log, actor, AuditAction::SetReceiptNftConfig, &old_config, &**config)?;
+
+        let clock = Clock::get()?;
+        emit!(ReceiptNftConfigUpdated {
+            version: EVENT_VERSION,
+            enabled,
+            merkle_tree,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Mints the receipt NFT for a deposit receipt to its depositor, once per receipt. The NFT
+    // names the deposit's leaf index, so, like the receipt, it links the depositor to the note.
+    pub fn claim_receipt_nft(ctx: Context<ClaimReceiptNft>) -> Result<()> {
+        let config = &ctx.accounts.receipt_nft_config;
+        require!(config.enabled, PrivaxError::ReceiptNftsDisabled);
+        let receipt = &ctx.accounts.deposit_receipt;
+        let metadata = MetadataArgs {
+            name: format!("{} #{}", config.name, receipt.leaf_index),
+            symbol: config.symbol.clone(),
+            uri: config.uri.clone(),
+            seller_fee_basis_points: 0,
+            primary_sale_happened: false,
+            is_mutable: false,
+            edition_nonce: None,
+            token_standard: Some(mpl_bubblegum::types::TokenStandard::NonFungible),
+            collection: None,
+            uses: None,
+            token_program_version: TokenProgramVersion::Original,
+            creators: Vec::new(),
+        };
+
+        let program_state_key = ctx.accounts.program_state.key();
+        let seeds = &[b"receipt_nft_config".as_ref(), program_state_key.as_ref(), &[config.bump]];
+        let depositor = ctx.accounts.depositor.to_account_info();
+        let config_info = config.to_account_info();
+        MintV1Cpi::new(
+            &ctx.accounts.bubblegum_program.to_account_info(),
+            MintV1CpiAccounts {
+                tree_config: &ctx.accounts.tree_config.to_account_info(),
+                leaf_owner: &depositor,
+                leaf_delegate: &depositor,
+                merkle_tree: &ctx.accounts.merkle_tree.to_account_info(),
+                payer: &depositor,
+                tree_creator_or_delegate: &config_info,
+                log_wrapper: &ctx.accounts.log_wrapper.to_account_info(),
+                compression_program: &ctx.accounts.compression_program.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            MintV1InstructionArgs { metadata },
+        )
+        .invoke_signed(&[&seeds[..]])?;
+
+        let clock = Clock::get()?;
+        let claim = &mut ctx.accounts.receipt_nft_claim;
+        claim.slot = clock.slot;
+        claim.bump = *ctx.bumps.get("receipt_nft_claim").unwrap();
+
+        emit!(ReceiptNftMinted {
+            version: EVENT_VERSION,
+            depositor: receipt.depositor,
+            leaf_index: receipt.leaf_index,
+            merkle_tree: config.merkle_tree,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Sets the pool's USD limits and the Pyth account pricing its mint; all-zero limits turn
+    // them off. While any is set, deposits and withdrawals must pass both accounts. The feed
+    // must give a usable price now, so a wrong account fails here rather than at the next deposit.
+    pub fn set_usd_limits(
+        ctx: Context<SetUsdLimits>,
+        max_price_age: u64,
+        max_confidence_bps: u16,
+        max_deposit_usd: u64,
+        large_withdrawal_usd: u64,
+        flat_fee_usd: u64,
+    ) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        // The flat fee is a fee, so freezing fees freezes these too
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_FEES)?;
+        require!(ctx.accounts.program_state.pool_mode == PoolMode::Fungible, PrivaxError::WrongPoolMode);
+        require!(max_price_age > 0 && max_confidence_bps <= 10_000, PrivaxError::InvalidUsdLimits);
+        let limits = &mut ctx.accounts.usd_limits;
+        let old_limits = limits.clone().into_inner();
+        limits.price_feed = ctx.accounts.price_feed.key();
+        limits.max_price_age = max_price_age;
+        limits.max_confidence_bps = max_confidence_bps;
+        limits.mint_decimals = ctx.accounts.token_mint.decimals;
+        limits.max_deposit_usd = max_deposit_usd;
+        limits.large_withdrawal_usd = large_withdrawal_usd;
+        limits.flat_fee_usd = flat_fee_usd;
+        limits.bump = *ctx.bumps.get("usd_limits").unwrap();
+        let clock = Clock::get()?;
+        if limits.is_set() {
+            limits.price(&ctx.accounts.price_feed, clock.unix_timestamp)?;
+        }
+        ctx.accounts.program_state.usd_limits = limits.is_set();
+        record_audit(&ctx.accounts.audit_log, ctx.accounts.admin.key(), AuditAction::SetUsdLimits, &old_limits, &**limits)?;
+
+        emit!(UsdLimitsUpdated {
+            version: EVENT_VERSION,
+            price_feed: limits.price_feed,
+            max_deposit_usd,
+            large_withdrawal_usd,
+            flat_fee_usd,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Pins the tree's current root in TreeCheckpoints. Admin or tree operator; the payer
+    // funds the account on the first checkpoint.
+    pub fn checkpoint_root(ctx: Context<CheckpointRoot>) -> Result<()> {
+        let tree = ctx.accounts.merkle_tree.load()?;
+        let clock = Clock::get()?;
+        let checkpoint = TreeCheckpoint { leaf_count: tree.next_index, root: tree.root(), slot: clock.slot };
+        let checkpoints = &mut ctx.accounts.tree_checkpoints;
+        if let Some(latest) = checkpoints.latest() {
+            require!(checkpoint.leaf_count > latest.leaf_count, PrivaxError::CheckpointUnchanged);
+        }
+        checkpoints.push(checkpoint)?;
+        checkpoints.bump = *ctx.bumps.get("tree_checkpoints").unwrap();
+
+        emit!(TreeCheckpointed {
+            version: EVENT_VERSION,
+            operator: ctx.accounts.operator.key(),
+            leaf_count: checkpoint.leaf_count,
+            root: checkpoint.root,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Copies the tree's roots into the next archive page before MERKLE_ROOT_HISTORY_SIZE
+    // newer ones push them out. Admin or tree operator; the payer funds each page. A crank
+    // calling this at least once per MERKLE_ROOT_HISTORY_SIZE inserts archives every root.
+    pub fn archive_roots(ctx: Context<ArchiveRoots>, page: u64) -> Result<()> {
+        let mut tree = ctx.accounts.merkle_tree.load_mut()?;
+        let first_root = tree.archived_roots;
+        // A page created by this call has no discriminator until the instruction exits
+        let mut archive = match ctx.accounts.root_archive.load_mut() {
+            Ok(archive) => archive,
+            Err(_) => ctx.accounts.root_archive.load_init()?,
+        };
+        let (count, lost) = tree.archive_into(&mut archive, page)?;
+        archive.bump = *ctx.bumps.get("root_archive").unwrap();
+
+        let clock = Clock::get()?;
+        emit!(RootsArchived {
+            version: EVENT_VERSION,
+            page,
+            first_root,
+            count,
+            lost,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Whether proofs against archived roots are honored as well as the recent history. Kept on
+    // the tree so relayers, which check proof roots, see it with the roots it applies to. Off
+    // by default: an old root is one an attacker has had longer to find a collision against.
+    pub fn set_archived_roots_accepted(ctx: Context<SetArchivedRootsAccepted>, accept: bool) -> Result<()> {
+        ctx.accounts.program_state.require_not_exit_only()?;
+        ctx.accounts.program_state.require_not_frozen(ProgramState::PARAM_GROUP_VERIFIER)?;
+        let mut tree = ctx.accounts.merkle_tree.load_mut()?;
+        let old_accept = tree.accept_archived_roots != 0;
+        tree.accept_archived_roots = u8::from(accept);
+        record_audit(&ctx.accounts.audit_log, ctx.accounts.admin.key(), AuditAction::SetArchivedRootsAccepted, &old_accept, &accept)?;
+
+        let clock = Clock::get()?;
+        emit!(ArchivedRootsAcceptedChanged { version: EVENT_VERSION, accept, slot: clock.slot, unix_timestamp: clock.unix_timestamp });
+        Ok(())
+    }
+
+    // Unknown discriminators land here. Devnet builds route them to the test instructions in
+    // `devnet`; everywhere else they fail as they would without a fallback.
+    pub fn fallback(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> Result<()> {
+        #[cfg(feature = "devnet")]
+        return devnet::dispatch(program_id, accounts, data);
+        #[cfg(not(feature = "devnet"))]
+        {
+            let _ = (program_id, accounts, data);
+            Err(anchor_lang::error::ErrorCode::InstructionFallbackNotFound.into())
+        }
+    }
+}
+
+// Marks the end of a withdraw step for benches/compute_units.ts. Only built with the
+// `cu-log` feature; the step name is logged after the remaining-units reading it closes.
+#[cfg(feature = "cu-log")]
+fn log_step(step: &str) {
+    anchor_lang::solana_program::log::sol_log_compute_units();
+    msg!("cu-step: {}", step);
+}
+
+#[cfg(not(feature = "cu-log"))]
+fn log_step(_step: &str) {}
+
+// Domain separator for nullifiers: binds a proof to this program, pool mint, denomination and
+// circuit version, so a proof made for another deployment (devnet, a fork) never verifies here.
+// First 8 bytes (LE) of a sha256, to fit the u64 public inputs.
+pub fn external_nullifier(token_mint: &Pubkey, denomination: u64, circuit_version: u16) -> u64 {
+    let digest = anchor_lang::solana_program::hash::hashv(&[
+        b"privax-external-nullifier",
+        crate::ID.as_ref(),
+        token_mint.as_ref(),
+        &denomination.to_le_bytes(),
+        &circuit_version.to_le_bytes(),
+    ])
+    .to_bytes();
+    u64::from_le_bytes(digest[..8].try_into().unwrap())
+}
+
+// As external_nullifier, for the swap circuit, so withdraw and shielded_swap never accept each
+// other's proofs even if they share a verifying key.
+pub fn swap_external_nullifier(token_mint: &Pubkey, circuit_version: u16) -> u64 {
+    domain_external_nullifier(b"privax-swap-external-nullifier", token_mint, circuit_version)
+}
+
+// As swap_external_nullifier, for withdraw_split's circuit.
+pub fn split_external_nullifier(token_mint: &Pubkey, circuit_version: u16) -> u64 {
+    domain_external_nullifier(b"privax-split-external-nullifier", token_mint, circuit_version)
+}
+
+// As swap_external_nullifier, for the note split and merge circuits. Both leave the vault
+// alone, so their proofs must never pass for a withdrawal's or for each other's.
+pub fn note_split_external_nullifier(token_mint: &Pubkey, circuit_version: u16) -> u64 {
+    domain_external_nullifier(b"privax-note-split-external-nullifier", token_mint, circuit_version)
+}
+
+pub fn note_merge_external_nullifier(token_mint: &Pubkey, circuit_version: u16) -> u64 {
+    domain_external_nullifier(b"privax-note-merge-external-nullifier", token_mint, circuit_version)
+}
+
+// As swap_external_nullifier, for withdraw_nft's circuit. An NFT pool has no token_mint (each
+// note names its own mint as a public input), so the default key stands in for it.
+pub fn nft_external_nullifier(circuit_version: u16) -> u64 {
+    domain_external_nullifier(b"privax-nft-external-nullifier", &Pubkey::default(), circuit_version)
+}
+
+fn domain_external_nullifier(domain: &[u8], token_mint: &Pubkey, circuit_version: u16) -> u64 {
+    let digest = anchor_lang::solana_program::hash::hashv(&[
+        domain,
+        crate::ID.as_ref(),
+        token_mint.as_ref(),
+        &circuit_version.to_le_bytes(),
+    ])
+    .to_bytes();
+    u64::from_le_bytes(digest[..8].try_into().unwrap())
+}
+
+// --- ZK Proof Verification ---
+// Checks a proof against the circuit's verifying key and the public inputs as the circuit
+// takes them: field elements, 32 big-endian bytes each (see spend_inputs). Both proof systems
+// end in an alt_bn128 pairing check; a proof in the other system's slots is malformed.
+fn verify_proof(
+    verifying_key: &VerifyingKey,
+    a_proof: &[u8; 64],
+    b_proof: &[u8; 128],
+    c_proof: &[u8; 64],
+    plonk_proof: &[u8],
+    public_inputs: &[[u8; 32]],
+) -> Result<bool> {
+    #[cfg(feature = "devnet")]
+    if devnet::is_mock_proof(a_proof, b_proof, c_proof, plonk_proof) {
+        return Ok(true);
+    }
+    match verifying_key.proof_system() {
+        ProofSystem::Groth16 => {
+            require!(plonk_proof.is_empty(), PrivaxError::InvalidProofEncoding);
+            proof_format::check_coordinates(a_proof)?;
+            proof_format::check_coordinates(b_proof)?;
+            proof_format::check_coordinates(c_proof)?;
+            groth16::verify(verifying_key.vk_data(), a_proof, b_proof, c_proof, public_inputs)
+        }
+        ProofSystem::Plonk => {
+            let unused_zero = a_proof.iter().chain(b_proof).chain(c_proof).all(|&byte| byte == 0);
+            require!(unused_zero, PrivaxError::InvalidProofEncoding);
+            plonk::verify(verifying_key.vk_data(), plonk_proof, public_inputs)
+        }
+    }
+}
+
+// Closes an expired, unfunded registration: the cranker takes its reward out of the rent
+// and the registrant gets the rest. Returns None when the registration isn't due yet.
+fn close_expired_registration<'info>(
+    pending_info: &AccountInfo<'info>,
+    registrant_info: &AccountInfo<'info>,
+    cranker_info: &AccountInfo<'info>,
+    program_state_key: &Pubkey,
+    clock: &Clock,
+) -> Result<Option<u64>> {
+    let pending = Account::<PendingCommitment>::try_from(pending_info)?;
+    let expected = Pubkey::create_program_address(
+        &[b"pending_commitment", program_state_key.as_ref(), pending.commitment.as_ref(), &[pending.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| PrivaxError::InvalidCrankAccounts)?;
+    require_keys_eq!(expected, pending_info.key(), PrivaxError::InvalidCrankAccounts);
+    require_keys_eq!(pending.registrant, registrant_info.key(), PrivaxError::InvalidCrankAccounts);
+    if clock.slot < pending.expiry_slot {
+        return Ok(None);
+    }
+    let commitment = pending.commitment;
+    drop(pending);
+
+    let lamports = pending_info.lamports();
+    let reward = lamports.min(PendingCommitment::CRANK_REWARD_LAMPORTS);
+    **cranker_info.try_borrow_mut_lamports()? = cranker_info.lamports().checked_add(reward).ok_or(PrivaxError::Overflow)?;
+    **registrant_info.try_borrow_mut_lamports()? =
+        registrant_info.lamports().checked_add(lamports - reward).ok_or(PrivaxError::Overflow)?;
+    **pending_info.try_borrow_mut_lamports()? = 0;
+    pending_info.assign(&System::id());
+    pending_info.realloc(0, false)?;
+
+    emit!(CommitmentRegistrationClosed {
+        version: EVENT_VERSION,
+        commitment,
+        funded: false,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(Some(reward))
+}
+
+// A note's commitment is a Poseidon output, so one that is zero or at least the field
+// modulus has no note that opens it, and the tokens deposited against it could never leave.
+fn check_commitment(commitment: &[u8; 32]) -> Result<()> {
+    require!(commitment != &[0u8; 32] && proof_format::is_canonical_scalar(commitment), PrivaxError::InvalidCommitment);
+    Ok(())
+}
+
+// The effects of the AdminActions, shared by their direct forms and execute_admin_action.
+fn change_admin(state: &mut ProgramState, audit_log: &AccountLoader<AuditLog>, new_admin: Pubkey) -> Result<()> {
+    require!(new_admin != Pubkey::default(), PrivaxError::NewAdminIsZero);
+    let old_admin = state.admin;
+    state.admin = new_admin;
+    record_audit(audit_log, old_admin, AuditAction::TransferOwnership, &old_admin, &new_admin)?;
+
+    let clock = Clock::get()?;
+    emit!(AdminChanged { version: EVENT_VERSION, old_admin, new_admin, slot: clock.slot, unix_timestamp: clock.unix_timestamp });
+    Ok(())
+}
+
+fn change_guardian(state: &mut ProgramState, audit_log: &AccountLoader<AuditLog>, new_guardian: Pubkey) -> Result<()> {
+    let old_guardian = state.guardian;
+    state.guardian = new_guardian;
+    record_audit(audit_log, state.admin, AuditAction::SetGuardian, &old_guardian, &new_guardian)?;
+
+    let clock = Clock::get()?;
+    emit!(GuardianChanged {
+        version: EVENT_VERSION,
+        old_guardian,
+        new_guardian,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+fn change_verifying_key_enabled(
+    verifying_key: &AccountLoader<VerifyingKey>,
+    audit_log: &AccountLoader<AuditLog>,
+    admin: Pubkey,
+    enabled: bool,
+) -> Result<()> {
+    let mut verifying_key = verifying_key.load_mut()?;
+    require!(!enabled || verifying_key.is_complete(), PrivaxError::IncompleteVerifyingKey);
+    let old_value = verifying_key.audit_value();
+    verifying_key.enabled = u8::from(enabled);
+    let new_value = verifying_key.audit_value();
+    record_audit(audit_log, admin, AuditAction::SetVerifyingKeyEnabled, &old_value, &new_value)?;
+
+    let clock = Clock::get()?;
+    emit!(VerifyingKeyEnabledChanged {
+        version: EVENT_VERSION,
+        circuit_version: verifying_key.circuit_version,
+        enabled,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+fn change_withdrawal_limit(
+    state: &mut ProgramState,
+    audit_log: &AccountLoader<AuditLog>,
+    max_withdrawal_per_epoch: u64,
+    withdrawal_epoch_slots: u64,
+) -> Result<()> {
+    state.require_not_frozen(ProgramState::PARAM_GROUP_BREAKER)?;
+    require!(max_withdrawal_per_epoch == 0 || withdrawal_epoch_slots > 0, PrivaxError::InvalidWithdrawalLimit);
+    let clock = Clock::get()?;
+    // Epoch numbers depend on the epoch length, so the current epoch is renumbered, but what it
+    // has counted carries over; otherwise a change could clear the count.
+    let counting = state.max_withdrawal_per_epoch > 0
+        && clock.slot.checked_div(state.withdrawal_epoch_slots) == Some(state.withdrawal_epoch);
+    if !counting || max_withdrawal_per_epoch == 0 {
+        state.withdrawn_in_epoch = 0;
+    }
+    let old_value = (state.max_withdrawal_per_epoch, state.withdrawal_epoch_slots);
+    state.max_withdrawal_per_epoch = max_withdrawal_per_epoch;
+    state.withdrawal_epoch_slots = withdrawal_epoch_slots;
+    state.withdrawal_epoch = clock.slot.checked_div(withdrawal_epoch_slots).unwrap_or(0);
+    let new_value = (max_withdrawal_per_epoch, withdrawal_epoch_slots);
+    record_audit(audit_log, state.admin, AuditAction::SetWithdrawalLimit, &old_value, &new_value)?;
+
+    emit!(WithdrawalLimitUpdated {
+        version: EVENT_VERSION,
+        max_withdrawal_per_epoch,
+        withdrawal_epoch_slots,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+fn change_compliance_key(state: &mut ProgramState, audit_log: &AccountLoader<AuditLog>, compliance_key: [u8; 32]) -> Result<()> {
+    let old_key = state.compliance_key;
+    state.compliance_key = compliance_key;
+    record_audit(audit_log, state.admin, AuditAction::SetComplianceKey, &old_key, &compliance_key)?;
+
+    let clock = Clock::get()?;
+    emit!(ComplianceKeyUpdated {
+        version: EVENT_VERSION,
+        compliance_key,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+fn change_kyc_attestor(state: &mut ProgramState, audit_log: &AccountLoader<AuditLog>, kyc_attestor: Pubkey) -> Result<()> {
+    let old_attestor = state.kyc_attestor;
+    state.kyc_attestor = kyc_attestor;
+    record_audit(audit_log, state.admin, AuditAction::SetKycAttestor, &old_attestor, &kyc_attestor)?;
+
+    let clock = Clock::get()?;
+    emit!(KycAttestorUpdated {
+        version: EVENT_VERSION,
+        kyc_attestor,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+fn change_exclusion_required(state: &mut ProgramState, audit_log: &AccountLoader<AuditLog>, exclusion_required: bool) -> Result<()> {
+    require!(!exclusion_required || state.exclusion_root != [0; 32], PrivaxError::NoExclusionRoot);
+    let old_value = state.exclusion_required;
+    state.exclusion_required = exclusion_required;
+    record_audit(audit_log, state.admin, AuditAction::SetExclusionRequired, &old_value, &exclusion_required)?;
+
+    let clock = Clock::get()?;
+    emit!(ExclusionRequiredUpdated {
+        version: EVENT_VERSION,
+        exclusion_required,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+// A swap target must be another program, and new to the list.
+fn check_swap_target(state: &ProgramState, target_program: Pubkey) -> Result<()> {
+    require!(target_program != crate::ID && target_program != Pubkey::default(), PrivaxError::InvalidSwapTarget);
+    require!(!state.swap_targets.contains(&target_program), PrivaxError::SwapTargetAlreadyApproved);
+    require!(state.swap_targets.len() < ProgramState::MAX_SWAP_TARGETS, PrivaxError::TooManySwapTargets);
+    Ok(())
+}
+
+fn approve_swap_target(state: &mut ProgramState, audit_log: &AccountLoader<AuditLog>, target_program: Pubkey) -> Result<()> {
+    check_swap_target(state, target_program)?;
+    state.swap_targets.push(target_program);
+    record_audit(audit_log, state.admin, AuditAction::AddSwapTarget, &None::<Pubkey>, &target_program)?;
+
+    let clock = Clock::get()?;
+    emit!(SwapTargetAdded { version: EVENT_VERSION, target_program, slot: clock.slot, unix_timestamp: clock.unix_timestamp });
+    Ok(())
+}
+
+fn change_admin_timelock(state: &mut ProgramState, audit_log: &AccountLoader<AuditLog>, slots: u64) -> Result<()> {
+    require!(slots <= ProgramState::MAX_ADMIN_TIMELOCK_SLOTS, PrivaxError::InvalidAdminTimelock);
+    let old_slots = state.admin_timelock_slots;
+    state.admin_timelock_slots = slots;
+    record_audit(audit_log, state.admin, AuditAction::SetAdminTimelock, &old_slots, &slots)?;
+
+    let clock = Clock::get()?;
+    emit!(AdminTimelockChanged {
+        version: EVENT_VERSION,
+        old_slots,
+        new_slots: slots,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+fn record_audit<T: AnchorSerialize + ?Sized, U: AnchorSerialize + ?Sized>(
+    audit_log: &AccountLoader<AuditLog>,
+    actor: Pubkey,
+    action: AuditAction,
+    old_value: &T,
+    new_value: &U,
+) -> Result<()> {
+    let entry = AuditEntry::new(actor, action, Clock::get()?.slot, old_value, new_value)?;
+    audit_log.load_mut()?.push(entry)
+}
+
+// u64 representation of a commitment, like the other 32-byte inputs: its first 8 bytes (LE).
+fn commitment_input(commitment: &[u8; 32]) -> u64 {
+    u64::from_le_bytes(commitment[..8].try_into().unwrap())
+}
+
+// A spend's Merkle root, in full, must be one the tree still accepts; `root_archive` is the page
+// holding it when it has left the recent roots.
+fn check_root(
+    merkle_tree: &AccountLoader<MerkleTree>,
+    root_archive: &Option<AccountLoader<RootArchivePage>>,
+    merkle_root: &[u8; 32],
+) -> Result<()> {
+    let archive = root_archive.as_ref().map(AccountLoader::load).transpose()?;
+    require!(merkle_tree.load()?.accepts_root(merkle_root, archive.as_deref()), PrivaxError::RootNotKnown);
+    Ok(())
+}
+
+// A withdrawal proof names the relayer allowed to submit it and the fee it may take: a proof
+// for no relayer (0) pays no fee and anyone can submit it.
+fn check_relayer_inputs(fee_input: u64, relayer_input: u64, relayer_fee: u64, submitter: &Pubkey) -> Result<()> {
+    require!(fee_input == relayer_fee, PrivaxError::RelayerMismatch);
+    if relayer_input == 0 {
+        require!(relayer_fee == 0, PrivaxError::RelayerMismatch);
+    } else {
+        require!(relayer_input == commitment_input(&submitter.to_bytes()), PrivaxError::RelayerMismatch);
+    }
+    Ok(())
+}
+
+// The public inputs a spend proof is verified against: those it arrived with, plus the exclusion
+// root its notes were proven absent from when the pool requires one. The program supplies the
+// root, in full, so a proof against any other exclusion set fails verification.
+fn spend_inputs(state: &ProgramState, public_inputs: &[u64], proof_slot: u64) -> Vec<[u8; 32]> {
+    let mut inputs: Vec<[u8; 32]> = public_inputs.iter().map(|&input| field_input(input)).collect();
+    if let Some(root) = state.exclusion_root_at(proof_slot) {
+        inputs.push(root);
+    }
+    inputs
+}
+
+// A u64 public input as the field element the proof is checked against.
+pub fn field_input(input: u64) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[24..].copy_from_slice(&input.to_be_bytes());
+    bytes
+}
+
+// Appends a 32-byte entry to a shard whose `count` entries follow its `header` bytes, unless
+// the entry is already there; returns whether it was appended. The shard grows by one entry
+// and the payer tops up its rent.
+fn append_shard_entry<'info>(
+    info: &AccountInfo<'info>,
+    header: usize,
+    count: u32,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    entry: &[u8; 32],
+) -> Result<bool> {
+    let used = header + count as usize * entry.len();
+    if info.try_borrow_data()?[header..used].chunks_exact(entry.len()).any(|existing| existing == entry) {
+        return Ok(false);
+    }
+
+    let new_len = used + entry.len();
+    let top_up = Rent::get()?.minimum_balance(new_len).saturating_sub(info.lamports());
+    if top_up > 0 {
+        let cpi_accounts = anchor_lang::system_program::Transfer { from: payer.to_account_info(), to: info.clone() };
+        anchor_lang::system_program::transfer(CpiContext::new(system_program.to_account_info(), cpi_accounts), top_up)?;
+    }
+    info.realloc(new_len, false)?;
+    info.try_borrow_mut_data()?[used..new_len].copy_from_slice(entry);
+    Ok(true)
+}
+
+// Appends a nullifier to its shard, failing if it is already there.
+fn record_nullifier<'info>(
+    shard: &mut Account<'info, NullifierShard>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    nullifier_hash: &[u8; 32],
+) -> Result<()> {
+    require!(shard.prefix == NullifierShard::prefix_of(nullifier_hash), PrivaxError::NullifierShardMismatch);
+    let appended = append_shard_entry(&shard.to_account_info(), NullifierShard::SPACE, shard.count, payer, system_program, nullifier_hash)?;
+    require!(appended, PrivaxError::NullifierAlreadySpent);
+    shard.count = shard.count.checked_add(1).ok_or(PrivaxError::Overflow)?;
+
+    let clock = Clock::get()?;
+    emit!(NullifierSpent {
+        version: EVENT_VERSION,
+        nullifier_hash: *nullifier_hash,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+// Appends a deposited commitment to its shard, failing if it is already in the tree.
+fn record_commitment<'info>(
+    shard: &mut Account<'info, CommitmentShard>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    commitment: &[u8; 32],
+) -> Result<()> {
+    require!(shard.prefix == CommitmentShard::prefix_of(commitment), PrivaxError::CommitmentShardMismatch);
+    let appended = append_shard_entry(&shard.to_account_info(), CommitmentShard::SPACE, shard.count, payer, system_program, commitment)?;
+    require!(appended, PrivaxError::DuplicateCommitment);
+    shard.count = shard.count.checked_add(1).ok_or(PrivaxError::Overflow)?;
+    Ok(())
+}
+
+// Records a spend's nullifiers: the first in `shard`, each further one in the matching entry
+// of `extra_shards`, and all of them in the filter.
+fn record_nullifiers<'info>(
+    shard: &mut Account<'info, NullifierShard>,
+    extra_shards: &[AccountInfo<'info>],
+    filter: &AccountLoader<'info, NullifierFilter>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    program_state_key: &Pubkey,
+    mut nullifiers: impl Iterator<Item = [u8; 32]>,
+) -> Result<()> {
+    let first = nullifiers.next().ok_or(PrivaxError::InvalidPublicInputCount)?;
+    record_nullifier(shard, payer, system_program, &first)?;
+    filter.load_mut()?.insert(&first);
+    if extra_shards.is_empty() {
+        return Ok(());
+    }
+
+    // Extra nullifiers may land in the same shard as the first, so write it back first and
+    // reload it afterwards rather than keep two diverging copies of its header.
+    shard.exit(&crate::ID)?;
+    for (nullifier, shard_info) in nullifiers.zip(extra_shards) {
+        let mut extra = Account::<NullifierShard>::try_from(shard_info)?;
+        let expected = Pubkey::create_program_address(
+            &[b"nullifier_shard", program_state_key.as_ref(), extra.prefix.as_ref(), &[extra.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| PrivaxError::NullifierShardMismatch)?;
+        require_keys_eq!(expected, shard_info.key(), PrivaxError::NullifierShardMismatch);
+        record_nullifier(&mut extra, payer, system_program, &nullifier)?;
+        extra.exit(&crate::ID)?;
+        filter.load_mut()?.insert(&nullifier);
+    }
+    shard.reload()?;
+    Ok(())
+}
+
+// Pauses the pool behind a withdrawal that took the epoch's volume to the limit. The pause is
+// part of the withdrawal's instruction, so it only sticks if the withdrawal pays out. An
+// exit-only pool is already paused, and pausing it again would restart its wait, so its
+// withdrawals just fail until the next epoch.
+fn trip_withdrawal_limit(state: &mut Account<ProgramState>, amount: u64, clock: &Clock) {
+    if state.is_exit_only(clock.slot) {
+        return;
+    }
+    state.paused = true;
+    state.paused_at_slot = clock.slot;
+    emit!(WithdrawalLimitTripped {
+        version: EVENT_VERSION,
+        amount,
+        withdrawn_in_epoch: state.withdrawn_in_epoch,
+        max_withdrawal_per_epoch: state.max_withdrawal_per_epoch,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    emit!(PauseToggled {
+        version: EVENT_VERSION,
+        paused: true,
+        by: crate::ID, // The program itself
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+}
+
+// The pool's USD limits and the price to apply them at, while any limit is set. Deposit and
+// withdrawal instructions take both accounts as optional ones, so they are only needed then.
+fn usd_price<'a>(
+    state: &ProgramState,
+    usd_limits: &'a Option<Account<UsdLimits>>,
+    price_feed: &Option<UncheckedAccount>,
+) -> Result<Option<(&'a UsdLimits, Price)>> {
+    if !state.usd_limits {
+        return Ok(None);
+    }
+    let (Some(limits), Some(price_feed)) = (usd_limits, price_feed) else {
+        return err!(PrivaxError::MissingUsdLimits);
+    };
+    let price = limits.price(price_feed, Clock::get()?.unix_timestamp)?;
+    Ok(Some((limits, price)))
+}
+
+// Counts a deposit against its depositor's rolling daily volume, while the pool caps it.
+fn record_deposit_volume(state: &ProgramState, deposit_volume: &mut Option<Account<DepositVolume>>, amount: u64) -> Result<()> {
+    if state.max_daily_deposit == 0 {
+        return Ok(());
+    }
+    let volume = deposit_volume.as_mut().ok_or(PrivaxError::DepositVolumeRequired)?;
+    volume.record(amount, state.max_daily_deposit, Clock::get()?.unix_timestamp)
+}
+
+// Pays the relayer's share of a withdrawal from the vault. With no swap data the fee is
+// transferred in the pool mint; otherwise the configured swap program is invoked with the
+// vault authority as signer and the relayer receives wrapped SOL in its fee account.
+fn pay_relayer_fee<'info>(
+    ctx: &Context<'_, '_, '_, 'info, WithdrawTokens<'info>>,
+    swap_accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+    relayer_fee: u64,
+    fee_swap_data: &[u8],
+    min_fee_out: u64,
+) -> Result<()> {
+    let state = &ctx.accounts.program_state;
+    let relayer = ctx.accounts.user.key();
+    require!(state.whitelisted_relayers.contains(&relayer), PrivaxError::RelayerNotWhitelisted);
+    let relayer_info = ctx.accounts.relayer_info.as_ref().ok_or(PrivaxError::RelayerNotWhitelisted)?;
+    let clock = Clock::get()?;
+    require!(relayer_info.is_active(clock.slot), PrivaxError::RelayerExpired);
+    let fee_account = ctx
+        .accounts
+        .relayer_fee_token_account
+        .as_ref()
+        .ok_or(PrivaxError::MissingRelayerFeeAccount)?;
+    // The relayer's associated token account for the fee mint, which is checked below
+    require!(
+        fee_account.owner == relayer && fee_account.key() == get_associated_token_address(&relayer, &fee_account.mint),
+        PrivaxError::InvalidRelayerFeeAccount
+    );
+
+    if fee_swap_data.is_empty() {
+        require!(fee_account.mint == state.token_mint, PrivaxError::InvalidRelayerFeeAccount);
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.program_token_vault.to_account_info(),
+            to: fee_account.to_account_info(),
+            authority: ctx.accounts.program_token_vault_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), relayer_fee)?;
+
+        emit!(RelayerFeePaid {
+            version: EVENT_VERSION,
+            relayer,
+            fee_mint: state.token_mint,
+            fee_amount: relayer_fee,
+            amount_paid: relayer_fee,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        });
+        return Ok(());
+    }
+
+    require!(fee_account.mint == token::spl_token::native_mint::ID, PrivaxError::InvalidRelayerFeeAccount);
+    let swap_program = ctx.accounts.fee_swap_program.as_ref().ok_or(PrivaxError::InvalidFeeSwapProgram)?;
+    let fee_config = &ctx.accounts.fee_config;
+    require!(
+        fee_config.fee_swap_program != Pubkey::default() && swap_program.key() == fee_config.fee_swap_program,
+        PrivaxError::InvalidFeeSwapProgram
+    );
+
+    let vault_before = ctx.accounts.program_token_vault.amount;
+    let fee_before = fee_account.amount;
+
+    let vault_authority = ctx.accounts.program_token_vault_authority.key();
+    invoke_swap(swap_program, swap_accounts, &vault_authority, fee_swap_data, signer_seeds)?;
+
+    // Never trust the swap program's accounting: measure what actually moved.
+    let mut vault = ctx.accounts.program_token_vault.clone();
+    vault.reload()?;
+    let mut fee_account = fee_account.clone();
+    fee_account.reload()?;
+    let spent = vault_before.checked_sub(vault.amount).ok_or(PrivaxError::Overflow)?;
+    require!(spent <= relayer_fee, PrivaxError::FeeSwapOverspent);
+    let received = fee_account.amount.checked_sub(fee_before).ok_or(PrivaxError::Overflow)?;
+    require!(received >= min_fee_out, PrivaxError::FeeSwapSlippage);
+
+    emit!(RelayerFeePaid {
+        version: EVENT_VERSION,
+        relayer,
+        fee_mint: fee_account.mint,
+        fee_amount: relayer_fee,
+        amount_paid: received,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+// Invokes the configured swap program with the vault authority as signer. The swap route's
+// accounts are passed through as remaining accounts; only the vault authority PDA is promoted
+// to signer.
+fn invoke_swap<'info>(
+    swap_program: &AccountInfo<'info>,
+    swap_accounts: &[AccountInfo<'info>],
+    vault_authority: &Pubkey,
+    swap_data: &[u8],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = swap_accounts
+        .iter()
+        .map(|acc| AccountMeta {
+            pubkey: acc.key(),
+            is_signer: acc.key == vault_authority,
+            is_writable: acc.is_writable,
+        })
+        .collect();
+    let swap_ix = anchor_lang::solana_program::instruction::Instruction {
+        program_id: swap_program.key(),
+        accounts: metas,
+        data: swap_data.to_vec(),
+    };
+    anchor_lang::solana_program::program::invoke_signed(&swap_ix, swap_accounts, signer_seeds)?;
+    Ok(())
+}
+
+// The two ends of an NFT transfer: `authority` owns `from`, `to` is `to_owner`'s ATA.
+struct NftTransfer<'info> {
+    from: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    to_owner: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    associated_token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+}
+
+// Moves one NFT. Plain NFTs are an SPL transfer; programmable ones sit frozen in their token
+// accounts and only move through Token Metadata, which also updates their token records and
+// enforces their rule set.
+fn transfer_nft<'info>(
+    transfer: NftTransfer<'info>,
+    programmable: &ProgrammableNft<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let is_programmable = match &programmable.metadata {
+        Some(metadata) => {
+            require_keys_eq!(metadata.mint, transfer.mint.key(), PrivaxError::MintMismatch);
+            matches!(
+                metadata.token_standard,
+                Some(TokenStandard::ProgrammableNonFungible | TokenStandard::ProgrammableNonFungibleEdition)
+            )
+        }
+        None => false,
+    };
+    if !is_programmable {
+        let cpi_accounts = Transfer { from: transfer.from, to: transfer.to, authority: transfer.authority };
+        return token::transfer(CpiContext::new_with_signer(transfer.token_program, cpi_accounts, signer_seeds), 1);
+    }
+
+    let (
+        Some(metadata),
+        Some(edition),
+        Some(owner_token_record),
+        Some(destination_token_record),
+        Some(token_metadata_program),
+        Some(sysvar_instructions),
+    ) = (
+        &programmable.metadata,
+        &programmable.edition,
+        &programmable.owner_token_record,
+        &programmable.destination_token_record,
+        &programmable.token_metadata_program,
+        &programmable.sysvar_instructions,
+    )
+    else {
+        return err!(PrivaxError::MissingProgrammableAccounts);
+    };
+    let rules_program = programmable.authorization_rules_program.as_ref();
+    let rules = programmable.authorization_rules.as_ref();
+    let instruction = builders::Transfer {
+        token: transfer.from.key(),
+        token_owner: transfer.authority.key(),
+        destination: transfer.to.key(),
+        destination_owner: transfer.to_owner.key(),
+        mint: transfer.mint.key(),
+        metadata: metadata.key(),
+        edition: Some(edition.key()),
+        owner_token_record: Some(owner_token_record.key()),
+        destination_token_record: Some(destination_token_record.key()),
+        authority: transfer.authority.key(),
+        payer: transfer.payer.key(),
+        system_program: transfer.system_program.key(),
+        sysvar_instructions: sysvar_instructions.key(),
+        spl_token_program: transfer.token_program.key(),
+        spl_ata_program: transfer.associated_token_program.key(),
+        authorization_rules_program: rules_program.map(|account| account.key()),
+        authorization_rules: rules.map(|account| account.key()),
+        args: TransferArgs::V1 { amount: 1, authorization_data: None },
+    }
+    .instruction();
+    let mut infos = vec![
+        transfer.from,
+        transfer.authority,
+        transfer.to,
+        transfer.to_owner,
+        transfer.mint,
+        metadata.to_account_info(),
+        edition.to_account_info(),
+        owner_token_record.to_account_info(),
+        destination_token_record.to_account_info(),
+        transfer.payer,
+        transfer.system_program,
+        sysvar_instructions.to_account_info(),
+        transfer.token_program,
+        transfer.associated_token_program,
+        token_metadata_program.to_account_info(),
+    ];
+    infos.extend(rules_program.into_iter().chain(rules).map(|account| account.to_account_info()));
+    anchor_lang::solana_program::program::invoke_signed(&instruction, &infos, signer_seeds)?;
+    Ok(())
+}
+
+// --- Account Structs for Instructions ---
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = admin, space = ProgramState::SPACE, seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(init, payer = admin, space = FeeConfig::SPACE, seeds = [b"fee_config", program_state.key().as_ref()], bump)]
+    pub fee_config: Account<'info, FeeConfig>,
+    #[account(init, payer = admin, space = NullifierFilter::SPACE, seeds = [b"nullifier_filter", program_state.key().as_ref()], bump)]
+    pub nullifier_filter: AccountLoader<'info, NullifierFilter>,
+    #[account(init, payer = admin, space = MerkleTree::SPACE, seeds = [b"merkle_tree", program_state.key().as_ref()], bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    #[account(init, payer = admin, space = AuditLog::SPACE, seeds = [b"audit_log", program_state.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitAuditLog<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(init, payer = payer, space = AuditLog::SPACE, seeds = [b"audit_log", program_state.key().as_ref()], bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(relayer_address: Pubkey)]
+pub struct AddRelayer<'info> {
+    #[account(mut, has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(init, payer = payer, space = RelayerInfo::SPACE, seeds = [b"relayer", relayer_address.as_ref()], bump)]
+    pub relayer_info: Account<'info, RelayerInfo>,
+    // Admin only authorizes; rent comes from a separate payer so the admin can be a
+    // governance PDA that holds data and cannot fund accounts (e.g. SPL Governance).
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(relayer_address: Pubkey)]
+pub struct RenewRelayer<'info> {
+    #[account(has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(mut, seeds = [b"relayer", relayer_address.as_ref()], bump = relayer_info.bump)]
+    pub relayer_info: Account<'info, RelayerInfo>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(relayer_address: Pubkey)]
+pub struct RemoveRelayer<'info> {
+    #[account(mut, has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(mut, close = rent_receiver, seeds = [b"relayer", relayer_address.as_ref()], bump = relayer_info.bump)]
+    pub relayer_info: Account<'info, RelayerInfo>,
+    pub admin: Signer<'info>,
+    /// CHECK: Only receives the closed account's lamports.
+    #[account(mut)]
+    pub rent_receiver: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferOwnership<'info> {
+    #[account(mut, has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeConfig<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        mut,
+        has_one = fee_authority @ PrivaxError::NotFeeAuthority,
+        seeds = [b"fee_config", program_state.key().as_ref()],
+        bump = fee_config.bump
+    )]
+    pub fee_config: Account<'info, FeeConfig>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    pub fee_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeFees<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"fee_config", program_state.key().as_ref()], bump = fee_config.bump)]
+    pub fee_config: Account<'info, FeeConfig>,
+    #[account(mut, token::mint = program_state.token_mint, seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault: Account<'info, TokenAccount>,
+    /// CHECK: This is the PDA authority for the program_token_vault
+    #[account(seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(init, payer = referrer, space = ReferrerAccount::SPACE, seeds = [b"referrer", referrer.key().as_ref()], bump)]
+    pub referrer_account: Account<'info, ReferrerAccount>,
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"fee_config", program_state.key().as_ref()], bump = fee_config.bump)]
+    pub fee_config: Account<'info, FeeConfig>,
+    #[account(
+        mut,
+        has_one = referrer @ PrivaxError::NotAccountOwner,
+        seeds = [b"referrer", referrer.key().as_ref()],
+        bump = referrer_account.bump
+    )]
+    pub referrer_account: Account<'info, ReferrerAccount>,
+    pub referrer: Signer<'info>,
+    #[account(mut, token::mint = program_state.token_mint)]
+    pub referrer_token_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = program_state.token_mint, seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault: Account<'info, TokenAccount>,
+    /// CHECK: This is the PDA authority for the program_token_vault
+    #[account(seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDepositReceipt<'info> {
+    #[account(mut, close = depositor, has_one = depositor @ PrivaxError::NotAccountOwner)]
+    pub deposit_receipt: Account<'info, DepositReceipt>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32])]
+pub struct RegisterCommitment<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        init,
+        payer = registrant,
+        space = PendingCommitment::SPACE,
+        seeds = [b"pending_commitment", program_state.key().as_ref(), commitment.as_ref()],
+        bump
+    )]
+    pub pending_commitment: Account<'info, PendingCommitment>,
+    #[account(mut)]
+    pub registrant: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundCommitment<'info> {
+    #[account(mut, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = CommitmentShard::SPACE,
+        seeds = [b"commitment_shard", program_state.key().as_ref(), &pending_commitment.commitment[..2]],
+        bump
+    )]
+    pub commitment_shard: Account<'info, CommitmentShard>,
+    #[account(
+        mut,
+        close = registrant,
+        has_one = registrant @ PrivaxError::RegistrantMismatch,
+        seeds = [b"pending_commitment", program_state.key().as_ref(), pending_commitment.commitment.as_ref()],
+        bump = pending_commitment.bump
+    )]
+    pub pending_commitment: Account<'info, PendingCommitment>,
+    /// CHECK: Receives the registration rent; checked by has_one.
+    #[account(mut)]
+    pub registrant: UncheckedAccount<'info>,
+    #[account(mut)] // Pays the rent of the commitment's shard entry
+    pub funder: Signer<'info>,
+    #[account(mut, constraint = funder_token_account.mint == program_state.token_mint @ PrivaxError::TokenMintMismatch)]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = program_state.token_mint, seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"usd_limits", program_state.key().as_ref()], bump = usd_limits.bump)] // Required while program_state.usd_limits
+    pub usd_limits: Option<Account<'info, UsdLimits>>,
+    /// CHECK: Checked against usd_limits.price_feed and parsed as a Pyth price account.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [b"deposit_volume", program_state.key().as_ref(), funder.key().as_ref()],
+        bump = deposit_volume.bump
+    )] // Required while program_state.max_daily_deposit is set
+    pub deposit_volume: Option<Account<'info, DepositVolume>>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimExpiredRegistration<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        mut,
+        close = registrant,
+        has_one = registrant @ PrivaxError::RegistrantMismatch,
+        seeds = [b"pending_commitment", program_state.key().as_ref(), pending_commitment.commitment.as_ref()],
+        bump = pending_commitment.bump
+    )]
+    pub pending_commitment: Account<'info, PendingCommitment>,
+    /// CHECK: Receives the registration rent; checked by has_one.
+    #[account(mut)]
+    pub registrant: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(circuit_version: u16)]
+pub struct RegisterVerifyingKey<'info> {
+    #[account(has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(
+        init,
+        payer = payer,
+        space = VerifyingKey::SPACE,
+        seeds = [b"verifying_key", program_state.key().as_ref(), &circuit_version.to_le_bytes()],
+        bump
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKey>,
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVerifyingKey<'info> {
+    #[account(has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        mut,
+        seeds = [b"verifying_key", program_state.key().as_ref(), &verifying_key.load()?.circuit_version.to_le_bytes()],
+        bump = verifying_key.load()?.bump
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKey>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    pub admin: Signer<'info>,
+}
+
+// Remaining accounts: the commitment shard of each commitment, in order; create missing ones
+// with init_commitment_shard.
+#[derive(Accounts)]
+pub struct DepositSubtree<'info> {
+    #[account(mut, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    #[account(mut)] // Pays the rent of the commitments' shard entries
+    pub user: Signer<'info>,
+    #[account(mut, constraint = user_token_account.mint == program_state.token_mint @ PrivaxError::TokenMintMismatch)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = program_state.token_mint, seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"usd_limits", program_state.key().as_ref()], bump = usd_limits.bump)] // Required while program_state.usd_limits
+    pub usd_limits: Option<Account<'info, UsdLimits>>,
+    /// CHECK: Checked against usd_limits.price_feed and parsed as a Pyth price account.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [b"deposit_volume", program_state.key().as_ref(), user.key().as_ref()],
+        bump = deposit_volume.bump
+    )] // Required while program_state.max_daily_deposit is set
+    pub deposit_volume: Option<Account<'info, DepositVolume>>,
+}
+
+#[derive(Accounts)]
+#[instruction(prefix: [u8; 2])]
+pub struct InitNullifierShard<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        init,
+        payer = payer,
+        space = NullifierShard::SPACE,
+        seeds = [b"nullifier_shard", program_state.key().as_ref(), prefix.as_ref()],
+        bump
+    )]
+    pub nullifier_shard: Account<'info, NullifierShard>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32])]
+pub struct AttestDeposit<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        init,
+        payer = depositor,
+        space = ComplianceAttestation::SPACE,
+        seeds = [b"compliance_attestation", program_state.key().as_ref(), commitment.as_ref()],
+        bump
+    )]
+    pub compliance_attestation: Account<'info, ComplianceAttestation>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(holder: Pubkey)]
+pub struct IssueKycCredential<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        init_if_needed,
+        payer = attestor,
+        space = KycCredential::SPACE,
+        seeds = [b"kyc_credential", program_state.key().as_ref(), holder.as_ref()],
+        bump
+    )]
+    pub kyc_credential: Account<'info, KycCredential>,
+    #[account(mut)]
+    pub attestor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeKycCredential<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        mut,
+        seeds = [b"kyc_credential", program_state.key().as_ref(), kyc_credential.holder.as_ref()],
+        bump = kyc_credential.bump,
+        has_one = attestor @ PrivaxError::NotKycAttestor,
+        close = attestor
+    )]
+    pub kyc_credential: Account<'info, KycCredential>,
+    #[account(mut)]
+    pub attestor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(depositor: Pubkey)]
+pub struct InitDepositVolume<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        init,
+        payer = payer,
+        space = DepositVolume::SPACE,
+        seeds = [b"deposit_volume", program_state.key().as_ref(), depositor.as_ref()],
+        bump
+    )]
+    pub deposit_volume: Account<'info, DepositVolume>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PublishExclusionRoot<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump = program_state.bump,
+        constraint = program_state.is_exclusion_publisher(&operator.key()) @ PrivaxError::NotExclusionOperator
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AnnounceNote<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[derive(Accounts)]
+pub struct CheckNullifiers<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(seeds = [b"nullifier_filter", program_state.key().as_ref()], bump = nullifier_filter.load()?.bump)]
+    pub nullifier_filter: AccountLoader<'info, NullifierFilter>,
+}
+
+#[derive(Accounts)]
+pub struct Reconcile<'info> {
+    #[account(mut, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(seeds = [b"fee_config", program_state.key().as_ref()], bump = fee_config.bump)]
+    pub fee_config: Account<'info, FeeConfig>,
+    #[account(token::mint = program_state.token_mint, seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(prefix: [u8; 2])]
+pub struct InitCommitmentShard<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        init,
+        payer = payer,
+        space = CommitmentShard::SPACE,
+        seeds = [b"commitment_shard", program_state.key().as_ref(), prefix.as_ref()],
+        bump
+    )]
+    pub commitment_shard: Account<'info, CommitmentShard>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Crank<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueAdminAction<'info> {
+    #[account(mut, has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(
+        init,
+        payer = payer,
+        space = QueuedAdminAction::SPACE,
+        seeds = [b"queued_admin_action", program_state.key().as_ref(), &program_state.next_admin_action_id.to_le_bytes()],
+        bump
+    )]
+    pub queued_action: Account<'info, QueuedAdminAction>,
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteAdminAction<'info> {
+    #[account(mut, has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(
+        mut,
+        close = payer,
+        has_one = payer @ PrivaxError::QueuedActionPayerMismatch,
+        seeds = [b"queued_admin_action", program_state.key().as_ref(), &queued_action.id.to_le_bytes()],
+        bump = queued_action.bump
+    )]
+    pub queued_action: Account<'info, QueuedAdminAction>,
+    /// CHECK: Receives the queued action's rent; checked by has_one.
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"verifying_key", program_state.key().as_ref(), &verifying_key.load()?.circuit_version.to_le_bytes()],
+        bump = verifying_key.load()?.bump
+    )] // Required by AdminAction::EnableVerifyingKey
+    pub verifying_key: Option<AccountLoader<'info, VerifyingKey>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAdminAction<'info> {
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump,
+        constraint = authority.key() == program_state.admin
+            || (program_state.guardian != Pubkey::default() && authority.key() == program_state.guardian)
+            @ PrivaxError::NotAdminOrGuardian
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(
+        mut,
+        close = payer,
+        has_one = payer @ PrivaxError::QueuedActionPayerMismatch,
+        seeds = [b"queued_admin_action", program_state.key().as_ref(), &queued_action.id.to_le_bytes()],
+        bump = queued_action.bump
+    )]
+    pub queued_action: Account<'info, QueuedAdminAction>,
+    /// CHECK: Receives the queued action's rent; checked by has_one.
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump = program_state.bump,
+        constraint = authority.key() == program_state.admin
+            || (program_state.guardian != Pubkey::default() && authority.key() == program_state.guardian)
+            @ PrivaxError::NotAdminOrGuardian
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, commitment: [u8; 32])]
+pub struct DepositTokens<'info> {
+    #[account(mut, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"fee_config", program_state.key().as_ref()], bump = fee_config.bump)]
+    pub fee_config: Account<'info, FeeConfig>,
+    #[account(mut, seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = CommitmentShard::SPACE,
+        seeds = [b"commitment_shard", program_state.key().as_ref(), &commitment[..2]],
+        bump
+    )]
+    pub commitment_shard: Account<'info, CommitmentShard>,
+    #[account(mut)] // Credited with a share of this deposit's protocol fee when present
+    pub referrer_account: Option<Account<'info, ReferrerAccount>>,
+    #[account(
+        init,
+        payer = user,
+        space = DepositReceipt::SPACE,
+        seeds = [b"receipt", program_state.key().as_ref(), &merkle_tree.load()?.next_index.to_le_bytes()],
+        bump
+    )]
+    pub deposit_receipt: Option<Account<'info, DepositReceipt>>,
+    #[account(mut)] // User who is depositing
+    pub user: Signer<'info>,
+    #[account(mut, constraint = user_token_account.mint == program_state.token_mint @ PrivaxError::TokenMintMismatch)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    #[account(address = program_state.token_mint @ PrivaxError::TokenMintMismatch)]
+    pub token_mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed, // Initialize if it doesn't exist
+        payer = user,
+        token::mint = token_mint,
+        token::authority = program_token_vault_authority, // PDA will be authority
+        seeds = [b"program_token_vault", program_state.key().as_ref()], 
+        bump
+    )]
+    pub program_token_vault: Account<'info, TokenAccount>,
+    /// CHECK: This is the PDA authority for the program_token_vault, derived from program_state key.
+    #[account(seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    #[account(seeds = [b"usd_limits", program_state.key().as_ref()], bump = usd_limits.bump)] // Required while program_state.usd_limits
+    pub usd_limits: Option<Account<'info, UsdLimits>>,
+    /// CHECK: Checked against usd_limits.price_feed and parsed as a Pyth price account.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [b"compliance_attestation", program_state.key().as_ref(), commitment.as_ref()],
+        bump = compliance_attestation.bump
+    )] // Required while program_state.compliance_key is set
+    pub compliance_attestation: Option<Account<'info, ComplianceAttestation>>,
+    #[account(
+        seeds = [b"kyc_credential", program_state.key().as_ref(), user.key().as_ref()],
+        bump = kyc_credential.bump
+    )] // Required while program_state.kyc_attestor is set
+    pub kyc_credential: Option<Account<'info, KycCredential>>,
+    #[account(
+        mut,
+        seeds = [b"deposit_volume", program_state.key().as_ref(), user.key().as_ref()],
+        bump = deposit_volume.bump
+    )] // Required while program_state.max_daily_deposit is set
+    pub deposit_volume: Option<Account<'info, DepositVolume>>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    a_proof: [u8; 64],
+    b_proof: [u8; 128],
+    c_proof: [u8; 64],
+    public_inputs: [u64; MAX_PUBLIC_INPUTS],
+    merkle_root: [u8; 32],
+    nullifier_hash: [u8; 32],
+    extra_nullifier_hashes: Vec<[u8; 32]>,
+    recipient_address: Pubkey,
+    amount_to_withdraw: u64,
+    relayer_fee: u64,
+    fee_swap_data: Vec<u8>,
+    min_fee_out: u64,
+    circuit_version: u16
+)]
+pub struct WithdrawTokens<'info> {
+    #[account(mut, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        seeds = [b"verifying_key", program_state.key().as_ref(), &circuit_version.to_le_bytes()],
+        bump = verifying_key.load()?.bump
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKey>,
+    #[account(mut, seeds = [b"fee_config", program_state.key().as_ref()], bump = fee_config.bump)]
+    pub fee_config: Account<'info, FeeConfig>,
+    // Shard for nullifier_hash's prefix; create it first with init_nullifier_shard.
+    #[account(
+        mut,
+        seeds = [b"nullifier_shard", program_state.key().as_ref(), &nullifier_hash[..2]],
+        bump = nullifier_shard.bump
+    )]
+    pub nullifier_shard: Account<'info, NullifierShard>,
+    #[account(mut, seeds = [b"nullifier_filter", program_state.key().as_ref()], bump = nullifier_filter.load()?.bump)]
+    pub nullifier_filter: AccountLoader<'info, NullifierFilter>,
+    #[account(mut, seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)] // Also takes any change note
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    // Archive page holding the proof's root once it has left the tree's recent roots
+    #[account(
+        seeds = [b"root_archive", program_state.key().as_ref(), &root_archive.load()?.page.to_le_bytes()],
+        bump = root_archive.load()?.bump
+    )]
+    pub root_archive: Option<AccountLoader<'info, RootArchivePage>>,
+    #[account(mut)] // User initiating the withdrawal (signer of the transaction)
+    pub user: Signer<'info>,
+    #[account(mut, token::mint = program_state.token_mint, seeds = [b"program_token_vault", program_state.key().as_ref()], bump)] // program_token_vault.bump? No, use state bump for seed consistency
+    pub program_token_vault: Account<'info, TokenAccount>,
+    /// CHECK: This is the PDA authority for the program_token_vault
+    #[account(seeds = [b"program_token_vault", program_state.key().as_ref()], bump)] // This bump should be the one used to create the vault authority PDA
+    pub program_token_vault_authority: UncheckedAccount<'info>,
+    // The recipient's associated token account for the pool mint, so whoever builds the
+    // transaction can't send the withdrawal anywhere else
+    #[account(mut, associated_token::mint = program_state.token_mint, associated_token::authority = recipient_address)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[account(seeds = [b"relayer", user.key().as_ref()], bump = relayer_info.bump)] // Required when a relayer fee is charged
+    pub relayer_info: Option<Account<'info, RelayerInfo>>,
+    #[account(mut)] // Relayer's fee account: pool mint, or wSOL when the fee is swapped
+    pub relayer_fee_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Compared against fee_config.fee_swap_program before being invoked.
+    #[account(executable)]
+    pub fee_swap_program: Option<UncheckedAccount<'info>>,
+    // pub verifier_program: UncheckedAccount<'info>, // For CPI to a verifier program
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"usd_limits", program_state.key().as_ref()], bump = usd_limits.bump)] // Required while program_state.usd_limits
+    pub usd_limits: Option<Account<'info, UsdLimits>>,
+    /// CHECK: Checked against usd_limits.price_feed and parsed as a Pyth price account.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    a_proof: [u8; 64],
+    b_proof: [u8; 128],
+    c_proof: [u8; 64],
+    public_inputs: [u64; SWAP_PUBLIC_INPUTS],
+    merkle_root: [u8; 32],
+    nullifier_hash: [u8; 32],
+    amount_in: u64,
+    amount_out: u64,
+    output_commitment: [u8; 32],
+    swap_data: Vec<u8>,
+    circuit_version: u16
+)]
+pub struct ShieldedSwap<'info> {
+    #[account(mut, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        seeds = [b"verifying_key", program_state.key().as_ref(), &circuit_version.to_le_bytes()],
+        bump = verifying_key.load()?.bump
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKey>,
+    #[account(mut, seeds = [b"fee_config", program_state.key().as_ref()], bump = fee_config.bump)]
+    pub fee_config: Account<'info, FeeConfig>,
+    #[account(
+        mut,
+        seeds = [b"nullifier_shard", program_state.key().as_ref(), &nullifier_hash[..2]],
+        bump = nullifier_shard.bump
+    )]
+    pub nullifier_shard: Account<'info, NullifierShard>,
+    #[account(mut, seeds = [b"nullifier_filter", program_state.key().as_ref()], bump = nullifier_filter.load()?.bump)]
+    pub nullifier_filter: AccountLoader<'info, NullifierFilter>,
+    #[account(seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    // Archive page holding the proof's root once it has left the tree's recent roots
+    #[account(
+        seeds = [b"root_archive", program_state.key().as_ref(), &root_archive.load()?.page.to_le_bytes()],
+        bump = root_archive.load()?.bump
+    )]
+    pub root_archive: Option<AccountLoader<'info, RootArchivePage>>,
+    #[account(mut)]
+    pub submitter: Signer<'info>,
+    #[account(mut, token::mint = program_state.token_mint, seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault: Account<'info, TokenAccount>,
+    /// CHECK: PDA authority of the vault; signs the swap and, as depositor, the target pool's deposit
+    #[account(mut, seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Compared against fee_config.fee_swap_program before being invoked.
+    #[account(executable)]
+    pub swap_program: UncheckedAccount<'info>,
+    // Receives the swap's output and pays the target pool's deposit from it
+    #[account(mut, token::mint = target_token_mint, token::authority = program_token_vault_authority)]
+    pub swap_output_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = target_token_mint, token::authority = submitter)] // Takes any swap surplus
+    pub submitter_output_account: Account<'info, TokenAccount>,
+    /// CHECK: The target pool's program; the proof names it and it must be in swap_targets.
+    #[account(executable)]
+    pub target_program: UncheckedAccount<'info>,
+    /// CHECK: Checked by the target pool's deposit, like the target accounts below.
+    #[account(mut)]
+    pub target_program_state: UncheckedAccount<'info>,
+    /// CHECK: Checked by the target pool's deposit.
+    #[account(mut)]
+    pub target_fee_config: UncheckedAccount<'info>,
+    /// CHECK: Checked by the target pool's deposit.
+    #[account(mut)]
+    pub target_merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: The output commitment's shard in the target pool; opened by shielded_swap if it doesn't exist yet
+    #[account(
+        mut,
+        seeds = [b"commitment_shard", target_program_state.key().as_ref(), &output_commitment[..2]],
+        seeds::program = target_program.key(),
+        bump
+    )]
+    pub target_commitment_shard: UncheckedAccount<'info>,
+    /// CHECK: Checked by the target pool's deposit.
+    #[account(mut)]
+    pub target_vault: UncheckedAccount<'info>,
+    /// CHECK: Checked by the target pool's deposit.
+    pub target_vault_authority: UncheckedAccount<'info>,
+    pub target_token_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    a_proof: [u8; 64],
+    b_proof: [u8; 128],
+    c_proof: [u8; 64],
+    public_inputs: [u64; SPLIT_PUBLIC_INPUTS],
+    merkle_root: [u8; 32],
+    nullifier_hash: [u8; 32],
+    payouts: Vec<Payout>,
+    circuit_version: u16
+)]
+pub struct WithdrawSplit<'info> {
+    #[account(mut, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        seeds = [b"verifying_key", program_state.key().as_ref(), &circuit_version.to_le_bytes()],
+        bump = verifying_key.load()?.bump
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKey>,
+    #[account(mut, seeds = [b"fee_config", program_state.key().as_ref()], bump = fee_config.bump)]
+    pub fee_config: Account<'info, FeeConfig>,
+    #[account(
+        mut,
+        seeds = [b"nullifier_shard", program_state.key().as_ref(), &nullifier_hash[..2]],
+        bump = nullifier_shard.bump
+    )]
+    pub nullifier_shard: Account<'info, NullifierShard>,
+    #[account(mut, seeds = [b"nullifier_filter", program_state.key().as_ref()], bump = nullifier_filter.load()?.bump)]
+    pub nullifier_filter: AccountLoader<'info, NullifierFilter>,
+    #[account(seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    // Archive page holding the proof's root once it has left the tree's recent roots
+    #[account(
+        seeds = [b"root_archive", program_state.key().as_ref(), &root_archive.load()?.page.to_le_bytes()],
+        bump = root_archive.load()?.bump
+    )]
+    pub root_archive: Option<AccountLoader<'info, RootArchivePage>>,
+    #[account(mut)] // Submits the transaction and pays the nullifier's rent
+    pub user: Signer<'info>,
+    #[account(mut, token::mint = program_state.token_mint, seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault: Account<'info, TokenAccount>,
+    /// CHECK: This is the PDA authority for the program_token_vault
+    #[account(seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"usd_limits", program_state.key().as_ref()], bump = usd_limits.bump)] // Required while program_state.usd_limits
+    pub usd_limits: Option<Account<'info, UsdLimits>>,
+    /// CHECK: Checked against usd_limits.price_feed and parsed as a Pyth price account.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    a_proof: [u8; 64],
+    b_proof: [u8; 128],
+    c_proof: [u8; 64],
+    public_inputs: [u64; NOTE_TRANSFER_PUBLIC_INPUTS],
+    merkle_root: [u8; 32],
+    nullifier_hash: [u8; 32],
+    circuit_version: u16
+)]
+pub struct ShieldedTransfer<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        seeds = [b"verifying_key", program_state.key().as_ref(), &circuit_version.to_le_bytes()],
+        bump = verifying_key.load()?.bump
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKey>,
+    // Shard of the first nullifier; merge_notes takes the others' shards as remaining accounts.
+    #[account(
+        mut,
+        seeds = [b"nullifier_shard", program_state.key().as_ref(), &nullifier_hash[..2]],
+        bump = nullifier_shard.bump
+    )]
+    pub nullifier_shard: Account<'info, NullifierShard>,
+    #[account(mut, seeds = [b"nullifier_filter", program_state.key().as_ref()], bump = nullifier_filter.load()?.bump)]
+    pub nullifier_filter: AccountLoader<'info, NullifierFilter>,
+    #[account(mut, seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    // Archive page holding the proof's root once it has left the tree's recent roots
+    #[account(
+        seeds = [b"root_archive", program_state.key().as_ref(), &root_archive.load()?.page.to_le_bytes()],
+        bump = root_archive.load()?.bump
+    )]
+    pub root_archive: Option<AccountLoader<'info, RootArchivePage>>,
+    #[account(mut)] // Submits the transaction and pays the nullifiers' rent
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(schedule_id: u64)]
+pub struct CreatePaymentSchedule<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        init,
+        payer = owner,
+        space = PaymentSchedule::SPACE,
+        seeds = [b"payment_schedule", program_state.key().as_ref(), owner.key().as_ref(), &schedule_id.to_le_bytes()],
+        bump
+    )]
+    pub payment_schedule: Account<'info, PaymentSchedule>,
+    // The schedule's associated token account, so withdrawals naming the schedule as recipient
+    // can fund it
+    #[account(init, payer = owner, associated_token::mint = token_mint, associated_token::authority = payment_schedule)]
+    pub schedule_escrow: Account<'info, TokenAccount>,
+    #[account(address = program_state.token_mint @ PrivaxError::TokenMintMismatch)]
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteScheduledPayment<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        mut,
+        seeds = [
+            b"payment_schedule",
+            program_state.key().as_ref(),
+            payment_schedule.owner.as_ref(),
+            &payment_schedule.schedule_id.to_le_bytes()
+        ],
+        bump = payment_schedule.bump
+    )]
+    pub payment_schedule: Account<'info, PaymentSchedule>,
+    #[account(mut, associated_token::mint = program_state.token_mint, associated_token::authority = payment_schedule)]
+    pub schedule_escrow: Account<'info, TokenAccount>,
+    #[account(mut, associated_token::mint = program_state.token_mint, associated_token::authority = payment_schedule.recipient)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub executor: Signer<'info>,
+    #[account(mut, token::mint = program_state.token_mint)] // Takes the executor fee
+    pub executor_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelPaymentSchedule<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ PrivaxError::NotAccountOwner,
+        seeds = [
+            b"payment_schedule",
+            program_state.key().as_ref(),
+            owner.key().as_ref(),
+            &payment_schedule.schedule_id.to_le_bytes()
+        ],
+        bump = payment_schedule.bump
+    )]
+    pub payment_schedule: Account<'info, PaymentSchedule>,
+    #[account(mut, associated_token::mint = program_state.token_mint, associated_token::authority = payment_schedule)]
+    pub schedule_escrow: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, token::mint = program_state.token_mint)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct EnableNftMode<'info> {
+    #[account(mut, has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    pub admin: Signer<'info>,
+}
+
+// Needed, all but the rule set, to move a programmable NFT; left out for plain NFTs.
+#[derive(Accounts)]
+pub struct ProgrammableNft<'info> {
+    #[account(mut)] // Checked against the NFT's mint; its token_standard picks the transfer path
+    pub metadata: Option<Account<'info, MetadataAccount>>,
+    /// CHECK: Master edition; checked by Token Metadata.
+    pub edition: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of the NFT's current token account; checked by Token Metadata.
+    #[account(mut)]
+    pub owner_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of the destination token account, created by Token Metadata if needed.
+    #[account(mut)]
+    pub destination_token_record: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    /// CHECK: The instructions sysvar, by address.
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+    /// CHECK: Only for NFTs with a rule set; checked by Token Metadata.
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Only for NFTs with a rule set; checked by Token Metadata.
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32])]
+pub struct DepositNft<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = CommitmentShard::SPACE,
+        seeds = [b"commitment_shard", program_state.key().as_ref(), &commitment[..2]],
+        bump
+    )]
+    pub commitment_shard: Account<'info, CommitmentShard>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub nft_mint: Account<'info, Mint>,
+    #[account(mut, token::mint = nft_mint, token::authority = user)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    // Each NFT gets its own vault: the vault authority's ATA for its mint.
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = nft_mint,
+        associated_token::authority = program_token_vault_authority
+    )]
+    pub nft_vault: Account<'info, TokenAccount>,
+    /// CHECK: This is the PDA authority for the NFT vaults
+    #[account(seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault_authority: UncheckedAccount<'info>,
+    pub programmable: ProgrammableNft<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    a_proof: [u8; 64],
+    b_proof: [u8; 128],
+    c_proof: [u8; 64],
+    public_inputs: [u64; NFT_PUBLIC_INPUTS],
+    merkle_root: [u8; 32],
+    nullifier_hash: [u8; 32],
+    circuit_version: u16
+)]
+pub struct WithdrawNft<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        seeds = [b"verifying_key", program_state.key().as_ref(), &circuit_version.to_le_bytes()],
+        bump = verifying_key.load()?.bump
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKey>,
+    #[account(
+        mut,
+        seeds = [b"nullifier_shard", program_state.key().as_ref(), &nullifier_hash[..2]],
+        bump = nullifier_shard.bump
+    )]
+    pub nullifier_shard: Account<'info, NullifierShard>,
+    #[account(mut, seeds = [b"nullifier_filter", program_state.key().as_ref()], bump = nullifier_filter.load()?.bump)]
+    pub nullifier_filter: AccountLoader<'info, NullifierFilter>,
+    #[account(seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    // Archive page holding the proof's root once it has left the tree's recent roots
+    #[account(
+        seeds = [b"root_archive", program_state.key().as_ref(), &root_archive.load()?.page.to_le_bytes()],
+        bump = root_archive.load()?.bump
+    )]
+    pub root_archive: Option<AccountLoader<'info, RootArchivePage>>,
+    #[account(mut)] // Submits the transaction and pays the nullifier's and the recipient ATA's rent
+    pub user: Signer<'info>,
+    pub nft_mint: Account<'info, Mint>,
+    #[account(mut, associated_token::mint = nft_mint, associated_token::authority = program_token_vault_authority)]
+    pub nft_vault: Account<'info, TokenAccount>,
+    /// CHECK: This is the PDA authority for the NFT vaults
+    #[account(seeds = [b"program_token_vault", program_state.key().as_ref()], bump)]
+    pub program_token_vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Any wallet; bound by the proof's recipient input.
+    pub recipient: UncheckedAccount<'info>,
+    #[account(init_if_needed, payer = user, associated_token::mint = nft_mint, associated_token::authority = recipient)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub programmable: ProgrammableNft<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetReceiptNftConfig<'info> {
+    #[account(has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ReceiptNftConfig::SPACE,
+        seeds = [b"receipt_nft_config", program_state.key().as_ref()],
+        bump
+    )]
+    pub receipt_nft_config: Account<'info, ReceiptNftConfig>,
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReceiptNft<'info> {
+    #[account(seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(seeds = [b"receipt_nft_config", program_state.key().as_ref()], bump = receipt_nft_config.bump)]
+    pub receipt_nft_config: Account<'info, ReceiptNftConfig>,
+    #[account(has_one = depositor @ PrivaxError::NotAccountOwner)]
+    pub deposit_receipt: Account<'info, DepositReceipt>,
+    #[account(
+        init,
+        payer = depositor,
+        space = ReceiptNftClaim::SPACE,
+        seeds = [b"receipt_nft_claim", deposit_receipt.key().as_ref()],
+        bump
+    )]
+    pub receipt_nft_claim: Account<'info, ReceiptNftClaim>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    /// CHECK: The tree's Bubblegum config; checked by Bubblegum.
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
+    /// CHECK: The configured Bubblegum tree, by address.
+    #[account(mut, address = receipt_nft_config.merkle_tree @ PrivaxError::InvalidReceiptNftConfig)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: Bubblegum, by address.
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+    /// CHECK: SPL Noop, by address.
+    #[account(address = mpl_bubblegum::programs::SPL_NOOP_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: SPL Account Compression, by address.
+    #[account(address = mpl_bubblegum::programs::SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetUsdLimits<'info> {
+    #[account(mut, has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UsdLimits::SPACE,
+        seeds = [b"usd_limits", program_state.key().as_ref()],
+        bump
+    )]
+    pub usd_limits: Account<'info, UsdLimits>,
+    #[account(address = program_state.token_mint @ PrivaxError::TokenMintMismatch)]
+    pub token_mint: Account<'info, Mint>,
+    /// CHECK: Parsed as a Pyth price account when any limit is set.
+    pub price_feed: UncheckedAccount<'info>,
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Tree maintenance: signed by the admin or the tree operator.
+#[derive(Accounts)]
+pub struct CheckpointRoot<'info> {
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump,
+        constraint = program_state.is_tree_maintainer(&operator.key()) @ PrivaxError::NotTreeOperator
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TreeCheckpoints::SPACE,
+        seeds = [b"tree_checkpoints", program_state.key().as_ref()],
+        bump
+    )]
+    pub tree_checkpoints: Account<'info, TreeCheckpoints>,
+    pub operator: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(page: u64)]
+pub struct ArchiveRoots<'info> {
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump,
+        constraint = program_state.is_tree_maintainer(&operator.key()) @ PrivaxError::NotTreeOperator
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = RootArchivePage::SPACE,
+        seeds = [b"root_archive", program_state.key().as_ref(), &page.to_le_bytes()],
+        bump
+    )]
+    pub root_archive: AccountLoader<'info, RootArchivePage>,
+    pub operator: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetArchivedRootsAccepted<'info> {
+    #[account(has_one = admin @ PrivaxError::NotAdmin, seeds = [b"program_state"], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"merkle_tree", program_state.key().as_ref()], bump = merkle_tree.load()?.bump)]
+    pub merkle_tree: AccountLoader<'info, MerkleTree>,
+    #[account(mut, seeds = [b"audit_log", program_state.key().as_ref()], bump = audit_log.load()?.bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    pub admin: Signer<'info>,
+}
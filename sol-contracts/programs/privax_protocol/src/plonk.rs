@@ -0,0 +1,204 @@
+// PLONK verification over BN254 with the alt_bn128 syscalls, for keys and proofs as snarkjs
+// produces them (the Keccak256 transcript of its verifier, one opening per point, no custom
+// gates). Points and field elements use the on-chain layout of proof_format.
+//
+// VK    = power (u8) || n_public (u8) || k1 || k2 || w (scalars)
+//         || Qm || Ql || Qr || Qo || Qc || S1 || S2 || S3 (G1) || X_2 (G2)
+// Proof = A || B || C || Z || T1 || T2 || T3 || Wxi || Wxiw (G1)
+//         || eval_a || eval_b || eval_c || eval_s1 || eval_s2 || eval_zw (scalars)
+//
+// where the domain has 2^power elements generated by w, and X_2 = tau · G2 from the setup.
+// Challenges are Keccak256 of the concatenated transcript items read as a big-endian scalar
+// mod r; a point is hashed as its 64 on-chain bytes.
+use std::ops::RangeInclusive;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::alt_bn128::prelude::{
+    alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing, AltBn128Error,
+};
+use anchor_lang::solana_program::keccak;
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, Field, One, PrimeField};
+
+use crate::groth16::negate_g1;
+use crate::proof_format::{check_coordinates, is_canonical_scalar, FIELD_SIZE, G1_SIZE, G2_SIZE};
+use crate::PrivaxError;
+
+const VK_SCALARS: usize = 2;
+const VK_COMMITMENTS: usize = VK_SCALARS + 3 * FIELD_SIZE;
+const VK_X2: usize = VK_COMMITMENTS + 8 * G1_SIZE;
+pub const VK_SIZE: usize = VK_X2 + G2_SIZE;
+
+const PROOF_EVALUATIONS: usize = 9 * G1_SIZE;
+pub const PROOF_SIZE: usize = PROOF_EVALUATIONS + 6 * FIELD_SIZE;
+
+// Largest domain a key may declare; snarkjs' powers of tau stop at 2^28.
+const MAX_POWER: u8 = 28;
+
+const G1_GENERATOR: [u8; G1_SIZE] = {
+    let mut point = [0u8; G1_SIZE];
+    point[FIELD_SIZE - 1] = 1;
+    point[G1_SIZE - 1] = 2;
+    point
+};
+
+// The BN254 G2 generator, x.c1 || x.c0 || y.c1 || y.c0.
+pub const G2_GENERATOR: [u8; G2_SIZE] = [
+    0x19, 0x8e, 0x93, 0x93, 0x92, 0x0d, 0x48, 0x3a, 0x72, 0x60, 0xbf, 0xb7, 0x31, 0xfb, 0x5d, 0x25,
+    0xf1, 0xaa, 0x49, 0x33, 0x35, 0xa9, 0xe7, 0x12, 0x97, 0xe4, 0x85, 0xb7, 0xae, 0xf3, 0x12, 0xc2,
+    0x18, 0x00, 0xde, 0xef, 0x12, 0x1f, 0x1e, 0x76, 0x42, 0x6a, 0x00, 0x66, 0x5e, 0x5c, 0x44, 0x79,
+    0x67, 0x43, 0x22, 0xd4, 0xf7, 0x5e, 0xda, 0xdd, 0x46, 0xde, 0xbd, 0x5c, 0xd9, 0x92, 0xf6, 0xed,
+    0x09, 0x06, 0x89, 0xd0, 0x58, 0x5f, 0xf0, 0x75, 0xec, 0x9e, 0x99, 0xad, 0x69, 0x0c, 0x33, 0x95,
+    0xbc, 0x4b, 0x31, 0x33, 0x70, 0xb3, 0x8e, 0xf3, 0x55, 0xac, 0xda, 0xdc, 0xd1, 0x22, 0x97, 0x5b,
+    0x12, 0xc8, 0x5e, 0xa5, 0xdb, 0x8c, 0x6d, 0xeb, 0x4a, 0xab, 0x71, 0x80, 0x8d, 0xcb, 0x40, 0x8f,
+    0xe3, 0xd1, 0xe7, 0x69, 0x0c, 0x43, 0xd3, 0x7b, 0x4c, 0xe6, 0xcc, 0x01, 0x66, 0xfa, 0x7d, 0xaa,
+];
+
+// Checks `vk_data` is an on-chain PLONK VK for a public input count in `public_input_counts`,
+// over a domain that w really generates.
+pub fn check_vk(vk_data: &[u8], public_input_counts: RangeInclusive<usize>) -> Result<()> {
+    require!(vk_data.len() == VK_SIZE, PrivaxError::InvalidVerifyingKey);
+    let (power, n_public) = (vk_data[0], usize::from(vk_data[1]));
+    require!((1..=MAX_POWER).contains(&power), PrivaxError::InvalidVerifyingKey);
+    require!(
+        public_input_counts.contains(&n_public) && n_public <= 1 << power,
+        PrivaxError::InvalidVerifyingKey
+    );
+    let mut scalars = vk_data[VK_SCALARS..VK_COMMITMENTS].chunks_exact(FIELD_SIZE);
+    require!(
+        scalars.all(|value| is_canonical_scalar(value.try_into().unwrap())),
+        PrivaxError::InvalidVerifyingKey
+    );
+    check_coordinates(&vk_data[VK_COMMITMENTS..]).map_err(|_| error!(PrivaxError::InvalidVerifyingKey))?;
+
+    // w must have order exactly 2^power
+    let mut w = scalar(&vk_data[VK_SCALARS + 2 * FIELD_SIZE..VK_COMMITMENTS]);
+    for _ in 1..power {
+        w.square_in_place();
+    }
+    require!(w != Fr::one() && w.square() == Fr::one(), PrivaxError::InvalidVerifyingKey);
+    Ok(())
+}
+
+// Verifies a PROOF_SIZE-byte proof against `vk_data` (as check_vk accepts it), which fixes the
+// public input count. Points off the curve or outside their subgroup make the proof invalid
+// rather than an error, as do public inputs and evaluations that aren't canonical scalars.
+pub fn verify(vk_data: &[u8], proof: &[u8], public_inputs: &[[u8; FIELD_SIZE]]) -> Result<bool> {
+    require!(vk_data.len() == VK_SIZE, PrivaxError::InvalidVerifyingKey);
+    require!(public_inputs.len() == usize::from(vk_data[1]), PrivaxError::InvalidPublicInputCount);
+    require!(proof.len() == PROOF_SIZE, PrivaxError::InvalidProofEncoding);
+    check_coordinates(&proof[..PROOF_EVALUATIONS])?;
+    let mut evaluations = proof[PROOF_EVALUATIONS..].chunks_exact(FIELD_SIZE);
+    if !public_inputs.iter().all(is_canonical_scalar)
+        || !evaluations.all(|value| is_canonical_scalar(value.try_into().unwrap()))
+    {
+        return Ok(false);
+    }
+    Ok(pairing_check(vk_data, proof, public_inputs).unwrap_or(false))
+}
+
+fn scalar(bytes: &[u8]) -> Fr {
+    Fr::from_be_bytes_mod_order(bytes)
+}
+
+fn scalar_bytes(value: Fr) -> Vec<u8> {
+    value.into_bigint().to_bytes_be()
+}
+
+fn challenge(items: &[&[u8]]) -> Fr {
+    scalar(&keccak::hashv(items).to_bytes())
+}
+
+fn pairing_check(vk_data: &[u8], proof: &[u8], public_inputs: &[[u8; FIELD_SIZE]]) -> std::result::Result<bool, AltBn128Error> {
+    let vk_scalar = |i: usize| scalar(&vk_data[VK_SCALARS + i * FIELD_SIZE..VK_SCALARS + (i + 1) * FIELD_SIZE]);
+    let vk_point = |i: usize| &vk_data[VK_COMMITMENTS + i * G1_SIZE..VK_COMMITMENTS + (i + 1) * G1_SIZE];
+    let proof_point = |i: usize| &proof[i * G1_SIZE..(i + 1) * G1_SIZE];
+    let evaluation = |i: usize| &proof[PROOF_EVALUATIONS + i * FIELD_SIZE..PROOF_EVALUATIONS + (i + 1) * FIELD_SIZE];
+
+    let (power, n_public) = (vk_data[0], usize::from(vk_data[1]));
+    let (k1, k2, w) = (vk_scalar(0), vk_scalar(1), vk_scalar(2));
+    let [qm, ql, qr, qo, qc, s1_commitment, s2_commitment, s3_commitment] = [0, 1, 2, 3, 4, 5, 6, 7].map(vk_point);
+    let x_2 = &vk_data[VK_X2..];
+    let [a_commitment, b_commitment, c_commitment, z_commitment, t1, t2, t3, w_xi, w_xiw] =
+        [0, 1, 2, 3, 4, 5, 6, 7, 8].map(proof_point);
+    let [a, b, c, s1, s2, zw] = [0, 1, 2, 3, 4, 5].map(|i| scalar(evaluation(i)));
+
+    // Fiat-Shamir challenges
+    let mut transcript: Vec<&[u8]> = vec![qm, ql, qr, qo, qc, s1_commitment, s2_commitment, s3_commitment];
+    transcript.extend(public_inputs.iter().map(|input| input.as_slice()));
+    transcript.extend([a_commitment, b_commitment, c_commitment]);
+    let beta = challenge(&transcript);
+    let gamma = challenge(&[&scalar_bytes(beta)]);
+    let alpha = challenge(&[&scalar_bytes(beta), &scalar_bytes(gamma), z_commitment]);
+    let xi = challenge(&[&scalar_bytes(alpha), t1, t2, t3]);
+    let xi_bytes = scalar_bytes(xi);
+    let mut transcript = vec![xi_bytes.as_slice()];
+    transcript.extend((0..6).map(evaluation));
+    let v1 = challenge(&transcript);
+    let [v2, v3, v4, v5] = [2, 3, 4, 5].map(|power| v1.pow([power]));
+    let u = challenge(&[w_xi, w_xiw]);
+
+    // Vanishing polynomial and the public inputs' Lagrange polynomials at xi
+    let xin = xi.pow([1u64 << power]);
+    let zh = xin - Fr::one();
+    let domain_size = Fr::from(1u64 << power);
+    let mut lagrange = Vec::with_capacity(n_public.max(1));
+    let mut root = Fr::one();
+    for _ in 0..n_public.max(1) {
+        let denominator = (domain_size * (xi - root)).inverse().ok_or(AltBn128Error::UnexpectedError)?;
+        lagrange.push(root * zh * denominator);
+        root *= w;
+    }
+    let l1 = lagrange[0];
+    let pi = -public_inputs.iter().zip(&lagrange).map(|(input, l)| scalar(input) * l).sum::<Fr>();
+
+    let alpha_squared = alpha.square();
+    let permutation_ab = (a + beta * s1 + gamma) * (b + beta * s2 + gamma);
+    let r0 = pi - l1 * alpha_squared - alpha * permutation_ab * (c + gamma) * zw;
+
+    // F = D + v1·A + v2·B + v3·C + v4·S1 + v5·S2, with D the linearization commitment
+    let beta_xi = beta * xi;
+    let z_factor = alpha * (a + beta_xi + gamma) * (b + beta_xi * k1 + gamma) * (c + beta_xi * k2 + gamma)
+        + alpha_squared * l1
+        + u;
+    let s3_factor = alpha * beta * zw * permutation_ab;
+    let terms = [
+        (qm, a * b),
+        (ql, a),
+        (qr, b),
+        (qo, c),
+        (qc, Fr::one()),
+        (z_commitment, z_factor),
+        (s3_commitment, -s3_factor),
+        (t1, -zh),
+        (t2, -zh * xin),
+        (t3, -zh * xin * xin),
+        (a_commitment, v1),
+        (b_commitment, v2),
+        (c_commitment, v3),
+        (s1_commitment, v4),
+        (s2_commitment, v5),
+    ];
+    // F - E, with E = G1 · (-r0 + v1·a + v2·b + v3·c + v4·s1 + v5·s2 + u·zw)
+    let e = -r0 + v1 * a + v2 * b + v3 * c + v4 * s1 + v5 * s2 + u * zw;
+    let mut f_minus_e = multiply(&G1_GENERATOR, -e)?;
+    for (point, factor) in terms {
+        f_minus_e = add(&f_minus_e, &multiply(point, factor)?)?;
+    }
+
+    // e(-(Wxi + u·Wxiw), X_2) · e(xi·Wxi + u·xi·w·Wxiw + F - E, G2) == 1
+    let opening = add(w_xi, &multiply(w_xiw, u)?)?;
+    let shifted = add(&multiply(w_xi, xi)?, &multiply(w_xiw, u * xi * w)?)?;
+    let shifted = add(&shifted, &f_minus_e)?;
+    let pairs = [&negate_g1(opening.as_slice().try_into().unwrap())[..], x_2, &shifted, &G2_GENERATOR].concat();
+    let result = alt_bn128_pairing(&pairs)?;
+    Ok(result.last() == Some(&1) && result[..result.len() - 1].iter().all(|&byte| byte == 0))
+}
+
+fn multiply(point: &[u8], factor: Fr) -> std::result::Result<Vec<u8>, AltBn128Error> {
+    alt_bn128_multiplication(&[point, &scalar_bytes(factor)].concat())
+}
+
+fn add(p: &[u8], q: &[u8]) -> std::result::Result<Vec<u8>, AltBn128Error> {
+    alt_bn128_addition(&[p, q].concat())
+}
@@ -0,0 +1,166 @@
+// On-chain byte layout for Groth16 proofs and verifying keys, plus conversions from the
+// arkworks serialization. app/src/proofFormat.ts mirrors this for the SDK (and snarkjs).
+//
+// On-chain layout (the alt_bn128 syscalls' convention):
+// - field elements are 32 bytes, big-endian
+// - G1 = x || y (64 bytes)
+// - G2 = x.c1 || x.c0 || y.c1 || y.c0 (128 bytes)
+// - VK = alpha (G1) || beta (G2) || gamma (G2) || delta (G2) || ic[0..n] (G1 each)
+//
+// arkworks (`CanonicalSerialize`, uncompressed) writes each field element little-endian,
+// G2 coordinates as c0 before c1, a u64 LE length before ic, and keeps flags in the top
+// bits of a point's last byte.
+use std::ops::RangeInclusive;
+
+use anchor_lang::prelude::*;
+
+use crate::PrivaxError;
+
+pub const FIELD_SIZE: usize = 32;
+pub const G1_SIZE: usize = 2 * FIELD_SIZE;
+pub const G2_SIZE: usize = 4 * FIELD_SIZE;
+// alpha, beta, gamma, delta, then one ic point per public input plus one.
+pub const VK_FIXED_SIZE: usize = G1_SIZE + 3 * G2_SIZE;
+
+// BN254 base field modulus, big-endian; point coordinates must be below it.
+pub const BN254_BASE_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+// BN254 scalar field modulus, big-endian; the circuits' values (commitments, hashes) are
+// below it.
+pub const BN254_SCALAR_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+// Whether big-endian `value` is a scalar in its one canonical encoding.
+pub fn is_canonical_scalar(value: &[u8; FIELD_SIZE]) -> bool {
+    value < &BN254_SCALAR_FIELD_MODULUS
+}
+
+// arkworks flag bits in the last byte of an uncompressed point.
+const ARKWORKS_INFINITY_FLAG: u8 = 1 << 6;
+const ARKWORKS_FLAG_MASK: u8 = 0b1100_0000;
+
+// Checks every big-endian coordinate in `points` is a canonical field element.
+pub fn check_coordinates(points: &[u8]) -> Result<()> {
+    let coordinates = points.chunks_exact(FIELD_SIZE);
+    require!(coordinates.remainder().is_empty(), PrivaxError::InvalidProofEncoding);
+    for coordinate in coordinates {
+        require!(coordinate < &BN254_BASE_FIELD_MODULUS[..], PrivaxError::InvalidProofEncoding);
+    }
+    Ok(())
+}
+
+// Checks `vk_data` is an on-chain Groth16 VK for a public input count in `public_input_counts`.
+pub fn check_groth16_vk(vk_data: &[u8], public_input_counts: RangeInclusive<usize>) -> Result<()> {
+    let ic = vk_data.get(VK_FIXED_SIZE..).ok_or(PrivaxError::InvalidVerifyingKey)?;
+    let ic_points = ic.chunks_exact(G1_SIZE);
+    // One ic point per public input plus one
+    let input_count = ic_points.len().checked_sub(1);
+    require!(
+        ic_points.remainder().is_empty() && matches!(input_count, Some(count) if public_input_counts.contains(&count)),
+        PrivaxError::InvalidVerifyingKey
+    );
+    check_coordinates(vk_data).map_err(|_| error!(PrivaxError::InvalidVerifyingKey))
+}
+
+fn reversed(field: &[u8]) -> [u8; FIELD_SIZE] {
+    let mut out = [0u8; FIELD_SIZE];
+    out.copy_from_slice(field);
+    out.reverse();
+    out
+}
+
+// Strips arkworks flags; points at infinity have no on-chain encoding here.
+fn arkworks_field_bytes(point: &[u8]) -> Result<Vec<u8>> {
+    let flags = point[point.len() - 1] & ARKWORKS_FLAG_MASK;
+    require!(flags & ARKWORKS_INFINITY_FLAG == 0, PrivaxError::InvalidProofEncoding);
+    let mut bytes = point.to_vec();
+    *bytes.last_mut().unwrap() &= !ARKWORKS_FLAG_MASK;
+    Ok(bytes)
+}
+
+pub fn g1_from_arkworks(point: &[u8; G1_SIZE]) -> Result<[u8; G1_SIZE]> {
+    let bytes = arkworks_field_bytes(point)?;
+    let mut out = [0u8; G1_SIZE];
+    out[..FIELD_SIZE].copy_from_slice(&reversed(&bytes[..FIELD_SIZE]));
+    out[FIELD_SIZE..].copy_from_slice(&reversed(&bytes[FIELD_SIZE..]));
+    check_coordinates(&out)?;
+    Ok(out)
+}
+
+pub fn g1_to_arkworks(point: &[u8; G1_SIZE]) -> [u8; G1_SIZE] {
+    let mut out = [0u8; G1_SIZE];
+    out[..FIELD_SIZE].copy_from_slice(&reversed(&point[..FIELD_SIZE]));
+    out[FIELD_SIZE..].copy_from_slice(&reversed(&point[FIELD_SIZE..]));
+    out
+}
+
+pub fn g2_from_arkworks(point: &[u8; G2_SIZE]) -> Result<[u8; G2_SIZE]> {
+    let bytes = arkworks_field_bytes(point)?;
+    let mut out = [0u8; G2_SIZE];
+    // arkworks: x.c0, x.c1, y.c0, y.c1; on-chain: x.c1, x.c0, y.c1, y.c0
+    for (i, source) in [1usize, 0, 3, 2].into_iter().enumerate() {
+        out[i * FIELD_SIZE..(i + 1) * FIELD_SIZE]
+            .copy_from_slice(&reversed(&bytes[source * FIELD_SIZE..(source + 1) * FIELD_SIZE]));
+    }
+    check_coordinates(&out)?;
+    Ok(out)
+}
+
+pub fn g2_to_arkworks(point: &[u8; G2_SIZE]) -> [u8; G2_SIZE] {
+    let mut out = [0u8; G2_SIZE];
+    for (i, source) in [1usize, 0, 3, 2].into_iter().enumerate() {
+        out[i * FIELD_SIZE..(i + 1) * FIELD_SIZE]
+            .copy_from_slice(&reversed(&point[source * FIELD_SIZE..(source + 1) * FIELD_SIZE]));
+    }
+    out
+}
+
+// Converts an arkworks-serialized Groth16 proof (A || B || C) into withdraw's a/b/c arguments.
+pub fn proof_from_arkworks(proof: &[u8]) -> Result<([u8; G1_SIZE], [u8; G2_SIZE], [u8; G1_SIZE])> {
+    require!(proof.len() == 2 * G1_SIZE + G2_SIZE, PrivaxError::InvalidProofEncoding);
+    let a = g1_from_arkworks(proof[..G1_SIZE].try_into().unwrap())?;
+    let b = g2_from_arkworks(proof[G1_SIZE..G1_SIZE + G2_SIZE].try_into().unwrap())?;
+    let c = g1_from_arkworks(proof[G1_SIZE + G2_SIZE..].try_into().unwrap())?;
+    Ok((a, b, c))
+}
+
+// Converts an arkworks-serialized Groth16 VerifyingKey into the on-chain layout.
+pub fn vk_from_arkworks(vk: &[u8]) -> Result<Vec<u8>> {
+    require!(vk.len() >= VK_FIXED_SIZE + 8, PrivaxError::InvalidVerifyingKey);
+    let (fixed, rest) = vk.split_at(VK_FIXED_SIZE);
+    let ic_count = u64::from_le_bytes(rest[..8].try_into().unwrap()) as usize;
+    let ic = &rest[8..];
+    require!(ic.len() == ic_count.saturating_mul(G1_SIZE), PrivaxError::InvalidVerifyingKey);
+
+    let mut out = Vec::with_capacity(VK_FIXED_SIZE + ic.len());
+    out.extend_from_slice(&g1_from_arkworks(fixed[..G1_SIZE].try_into().unwrap())?);
+    for g2 in fixed[G1_SIZE..].chunks_exact(G2_SIZE) {
+        out.extend_from_slice(&g2_from_arkworks(g2.try_into().unwrap())?);
+    }
+    for g1 in ic.chunks_exact(G1_SIZE) {
+        out.extend_from_slice(&g1_from_arkworks(g1.try_into().unwrap())?);
+    }
+    Ok(out)
+}
+
+// Inverse of vk_from_arkworks, for exporting a registered key back to arkworks tooling.
+pub fn vk_to_arkworks(vk_data: &[u8]) -> Result<Vec<u8>> {
+    require!(vk_data.len() >= VK_FIXED_SIZE, PrivaxError::InvalidVerifyingKey);
+    let (fixed, ic) = vk_data.split_at(VK_FIXED_SIZE);
+    require!(ic.chunks_exact(G1_SIZE).remainder().is_empty(), PrivaxError::InvalidVerifyingKey);
+    let mut out = Vec::with_capacity(vk_data.len() + 8);
+    out.extend_from_slice(&g1_to_arkworks(fixed[..G1_SIZE].try_into().unwrap()));
+    for g2 in fixed[G1_SIZE..].chunks_exact(G2_SIZE) {
+        out.extend_from_slice(&g2_to_arkworks(g2.try_into().unwrap()));
+    }
+    out.extend_from_slice(&((ic.len() / G1_SIZE) as u64).to_le_bytes());
+    for g1 in ic.chunks_exact(G1_SIZE) {
+        out.extend_from_slice(&g1_to_arkworks(g1.try_into().unwrap()));
+    }
+    Ok(out)
+}
@@ -0,0 +1,25 @@
+// The audit log keeps the newest MAX_AUDIT_ENTRIES entries, oldest first, and its value hashes
+// are what a client holding the old and new values recomputes.
+use anchor_lang::prelude::Pubkey;
+use privax_protocol::{audit_value_hash, AuditAction, AuditEntry, AuditLog, MAX_AUDIT_ENTRIES};
+
+#[test]
+fn audit_log_keeps_the_newest_entries_in_order() {
+    let mut audit_log: AuditLog = bytemuck::Zeroable::zeroed();
+    assert_eq!(audit_log.entries().count(), 0);
+
+    let actor = Pubkey::new_unique();
+    for slot in 1..=(MAX_AUDIT_ENTRIES as u64 + 5) {
+        let entry = AuditEntry::new(actor, AuditAction::SetReferralShare, slot, &(slot as u16 - 1), &(slot as u16)).unwrap();
+        audit_log.push(entry).unwrap();
+    }
+    let slots: Vec<u64> = audit_log.entries().map(|entry| entry.slot).collect();
+    assert_eq!(slots, (6..=MAX_AUDIT_ENTRIES as u64 + 5).collect::<Vec<_>>());
+
+    let latest = audit_log.entries().last().unwrap();
+    assert_eq!(latest.action(), Some(AuditAction::SetReferralShare));
+    assert_eq!(latest.actor, actor);
+    let slot = latest.slot as u16;
+    assert_eq!(latest.old_value_hash, audit_value_hash(&(slot - 1)).unwrap());
+    assert_eq!(latest.new_value_hash, audit_value_hash(&slot).unwrap());
+}
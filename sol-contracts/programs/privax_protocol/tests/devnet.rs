@@ -0,0 +1,31 @@
+// The devnet instructions are dispatched by hand, so their discriminators must match what
+// Anchor would derive, and the mock verifier must only take the all-zero proof.
+#![cfg(feature = "devnet")]
+
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::{Discriminator, InstructionData};
+use privax_protocol::devnet::{self, instruction};
+
+fn sighash(name: &str) -> [u8; 8] {
+    hash(format!("global:{name}").as_bytes()).to_bytes()[..8].try_into().unwrap()
+}
+
+#[test]
+fn discriminators_match_anchor() {
+    assert_eq!(instruction::ForceInsertLeaf::DISCRIMINATOR, sighash("force_insert_leaf"));
+    assert_eq!(instruction::ResetPool::DISCRIMINATOR, sighash("reset_pool"));
+
+    let data = instruction::ForceInsertLeaf { commitment: [7; 32] }.data();
+    assert_eq!(data[..8], sighash("force_insert_leaf"));
+    assert_eq!(data[8..], [7; 32]);
+}
+
+#[test]
+fn only_the_zero_proof_is_mocked() {
+    assert!(devnet::is_mock_proof(&[0; 64], &[0; 128], &[0; 64], &[]));
+
+    let mut c = [0; 64];
+    c[63] = 1;
+    assert!(!devnet::is_mock_proof(&[0; 64], &[0; 128], &c, &[]));
+    assert!(!devnet::is_mock_proof(&[0; 64], &[0; 128], &[0; 64], &[0; 32]));
+}
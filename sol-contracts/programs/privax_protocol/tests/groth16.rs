@@ -0,0 +1,110 @@
+// The on-chain Groth16 check against arkworks proofs, for a circuit that is nothing but its
+// public inputs: a valid proof verifies, and a change to any input, the proof or the input
+// count doesn't.
+use anchor_lang::error::Error;
+use ark_bn254::{Bn254, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::Groth16;
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable};
+use ark_serialize::CanonicalSerialize;
+use privax_protocol::groth16::verify;
+use privax_protocol::proof_format::{proof_from_arkworks, vk_from_arkworks, BN254_SCALAR_FIELD_MODULUS};
+use privax_protocol::PrivaxError;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+struct PublicInputs(Vec<Fr>);
+
+impl ConstraintSynthesizer<Fr> for PublicInputs {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        for input in self.0 {
+            let variable = cs.new_input_variable(|| Ok(input))?;
+            cs.enforce_constraint(lc!() + variable, lc!() + Variable::One, lc!() + variable)?;
+        }
+        Ok(())
+    }
+}
+
+struct Proof {
+    vk_data: Vec<u8>,
+    a: [u8; 64],
+    b: [u8; 128],
+    c: [u8; 64],
+}
+
+// A key for inputs.len() public inputs and a proof for these inputs, in the on-chain layout.
+fn prove(inputs: &[[u8; 32]]) -> Proof {
+    let mut rng = StdRng::seed_from_u64(7);
+    let circuit = || PublicInputs(inputs.iter().map(|input| Fr::from_be_bytes_mod_order(input)).collect());
+    let proving_key = Groth16::<Bn254>::generate_random_parameters_with_reduction(circuit(), &mut rng).unwrap();
+    let proof = Groth16::<Bn254>::create_random_proof_with_reduction(circuit(), &proving_key, &mut rng).unwrap();
+    let (mut vk, mut proof_bytes) = (Vec::new(), Vec::new());
+    proving_key.vk.serialize_uncompressed(&mut vk).unwrap();
+    proof.serialize_uncompressed(&mut proof_bytes).unwrap();
+    let (a, b, c) = proof_from_arkworks(&proof_bytes).unwrap();
+    Proof { vk_data: vk_from_arkworks(&vk).unwrap(), a, b, c }
+}
+
+// Eight u64 inputs as withdraw's fixed inputs would be, then a full-width commitment.
+fn inputs() -> Vec<[u8; 32]> {
+    let mut inputs: Vec<[u8; 32]> = (1..=8u64)
+        .map(|input| {
+            let mut bytes = [0u8; 32];
+            bytes[24..].copy_from_slice(&(input * 1_000_003).to_be_bytes());
+            bytes
+        })
+        .collect();
+    inputs.push(privax_test_vectors::vectors().notes[0].commitment);
+    inputs
+}
+
+#[test]
+fn accepts_a_valid_proof() {
+    let inputs = inputs();
+    let proof = prove(&inputs);
+    assert!(verify(&proof.vk_data, &proof.a, &proof.b, &proof.c, &inputs).unwrap());
+}
+
+#[test]
+fn rejects_changed_public_inputs() {
+    let inputs = inputs();
+    let proof = prove(&inputs);
+    for (input, byte) in [(0, 31), (3, 24), (8, 31), (8, 1), (8, 0)] {
+        let mut changed = inputs.clone();
+        changed[input][byte] ^= 1;
+        assert!(!verify(&proof.vk_data, &proof.a, &proof.b, &proof.c, &changed).unwrap(), "input {input}, byte {byte}");
+    }
+    // Another encoding of the same scalar isn't accepted either
+    let mut changed = inputs.clone();
+    changed[0] = BN254_SCALAR_FIELD_MODULUS;
+    assert!(!verify(&proof.vk_data, &proof.a, &proof.b, &proof.c, &changed).unwrap());
+}
+
+#[test]
+fn rejects_a_changed_proof() {
+    let inputs = inputs();
+    let proof = prove(&inputs);
+    assert!(!verify(&proof.vk_data, &proof.c, &proof.b, &proof.a, &inputs).unwrap());
+    // Off the curve
+    let mut a = proof.a;
+    a[63] ^= 1;
+    assert!(!verify(&proof.vk_data, &a, &proof.b, &proof.c, &inputs).unwrap());
+    // A proof for other inputs under the same key
+    let mut other = inputs.clone();
+    other[8][5] ^= 1;
+    let other = prove(&other);
+    assert!(!verify(&proof.vk_data, &other.a, &other.b, &other.c, &inputs).unwrap());
+}
+
+#[test]
+fn needs_one_input_per_key_input() {
+    let inputs = inputs();
+    let proof = prove(&inputs);
+    for count in [inputs.len() - 1, inputs.len() + 1] {
+        let mut resized = inputs.clone();
+        resized.resize(count, [0; 32]);
+        let err = verify(&proof.vk_data, &proof.a, &proof.b, &proof.c, &resized).unwrap_err();
+        assert_eq!(err, Error::from(PrivaxError::InvalidPublicInputCount));
+    }
+}
@@ -0,0 +1,184 @@
+// The incremental tree against one rebuilt from its leaves: any mix of single and subtree
+// inserts gives the same root, each leaf's path verifies against it, and recent roots stay known.
+// Checkpoints of the tree keep the newest MAX_TREE_CHECKPOINTS, and the root archive every root
+// it gets to before the history drops it. A spend's root is accepted only if all of it matches.
+use privax_protocol::{
+    hash_pair, MerkleTree, PrivaxError, RootArchivePage, TreeCheckpoint, TreeCheckpoints, MAX_TREE_CHECKPOINTS,
+    MERKLE_ROOT_HISTORY_SIZE, MERKLE_TREE_DEPTH, ROOT_ARCHIVE_PAGE_ROOTS,
+};
+use proptest::prelude::*;
+
+// Every level of a tree holding `leaves` from index 0, leaves first and the root last.
+fn levels(leaves: &[[u8; 32]]) -> Vec<Vec<[u8; 32]>> {
+    let mut zero = [0u8; 32];
+    let mut levels = vec![leaves.to_vec()];
+    for _ in 0..MERKLE_TREE_DEPTH {
+        let level = levels.last().unwrap();
+        let next = level.chunks(2).map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&zero))).collect::<Vec<_>>();
+        zero = hash_pair(&zero, &zero);
+        levels.push(if next.is_empty() { vec![zero] } else { next });
+    }
+    levels
+}
+
+// Sibling at each level for the leaf at `index`, leaf level first.
+fn path(levels: &[Vec<[u8; 32]>], mut index: usize) -> Vec<[u8; 32]> {
+    let mut zero = [0u8; 32];
+    let mut path = Vec::with_capacity(MERKLE_TREE_DEPTH);
+    for level in &levels[..MERKLE_TREE_DEPTH] {
+        path.push(*level.get(index ^ 1).unwrap_or(&zero));
+        zero = hash_pair(&zero, &zero);
+        index /= 2;
+    }
+    path
+}
+
+fn root_from_path(leaf: [u8; 32], mut index: usize, path: &[[u8; 32]]) -> [u8; 32] {
+    path.iter().fold(leaf, |node, sibling| {
+        let parent = if index & 1 == 0 { hash_pair(&node, sibling) } else { hash_pair(sibling, &node) };
+        index /= 2;
+        parent
+    })
+}
+
+fn empty_tree() -> MerkleTree {
+    let mut tree: MerkleTree = bytemuck::Zeroable::zeroed();
+    tree.init(0);
+    tree
+}
+
+// Inserts: a subtree height and enough leaves for the tallest subtree.
+fn inserts() -> impl Strategy<Value = Vec<(usize, Vec<[u8; 32]>)>> {
+    let width = 1 << MerkleTree::MAX_SUBTREE_HEIGHT;
+    proptest::collection::vec((0..=MerkleTree::MAX_SUBTREE_HEIGHT, proptest::collection::vec(any::<[u8; 32]>(), width)), 0..24)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn inserts_match_a_rebuilt_tree(inserts in inserts(), probe in any::<prop::sample::Index>()) {
+        let mut tree = empty_tree();
+        let mut leaves = Vec::new();
+        let mut roots = vec![tree.root()];
+        for (height, candidates) in inserts {
+            let subtree = &candidates[..1 << height];
+            let before = tree.root();
+            match tree.append_subtree(MerkleTree::subtree_root(subtree), height) {
+                Ok(first_index) => {
+                    prop_assert_eq!(first_index, leaves.len() as u64);
+                    leaves.extend_from_slice(subtree);
+                    roots.push(tree.root());
+                }
+                Err(err) => {
+                    // Only a subtree that isn't aligned to its width is refused, and nothing changes
+                    prop_assert_eq!(err, PrivaxError::MisalignedSubtree.into());
+                    prop_assert!(leaves.len() % subtree.len() != 0);
+                    prop_assert_eq!(tree.root(), before);
+                }
+            }
+        }
+
+        let levels = levels(&leaves);
+        let root = levels[MERKLE_TREE_DEPTH][0];
+        prop_assert_eq!(tree.root(), root);
+        prop_assert_eq!(tree.next_index, leaves.len() as u64);
+        for known in roots.iter().rev().take(MERKLE_ROOT_HISTORY_SIZE) {
+            prop_assert!(tree.is_known_root(known));
+        }
+
+        if !leaves.is_empty() {
+            let index = probe.index(leaves.len());
+            let path = path(&levels, index);
+            prop_assert_eq!(root_from_path(leaves[index], index, &path), root);
+            let mut forged = leaves[index];
+            forged[0] ^= 1;
+            prop_assert_ne!(root_from_path(forged, index, &path), root);
+        }
+    }
+
+    #[test]
+    fn subtree_root_is_the_rebuilt_subtree(height in 0..=MerkleTree::MAX_SUBTREE_HEIGHT, leaves in proptest::collection::vec(any::<[u8; 32]>(), 16)) {
+        let leaves = &leaves[..1 << height];
+        prop_assert_eq!(MerkleTree::subtree_root(leaves), levels(leaves)[height][0]);
+    }
+}
+
+#[test]
+fn checkpoints_keep_the_newest() {
+    let mut checkpoints = TreeCheckpoints::default();
+    assert!(checkpoints.latest().is_none());
+    for leaf_count in 1..=(MAX_TREE_CHECKPOINTS as u64 + 3) {
+        checkpoints.push(TreeCheckpoint { leaf_count, root: [leaf_count as u8; 32], slot: leaf_count * 10 }).unwrap();
+        assert_eq!(checkpoints.latest().unwrap().leaf_count, leaf_count);
+    }
+    let mut kept: Vec<u64> = checkpoints.checkpoints.iter().map(|checkpoint| checkpoint.leaf_count).collect();
+    kept.sort();
+    assert_eq!(kept, (4..=MAX_TREE_CHECKPOINTS as u64 + 3).collect::<Vec<_>>());
+}
+
+#[test]
+fn archive_pages_fill_in_order() {
+    let mut tree = empty_tree();
+    let mut page: RootArchivePage = bytemuck::Zeroable::zeroed();
+    let mut roots = vec![tree.root()];
+    assert_eq!(tree.archive_into(&mut page, 0).unwrap(), (1, 0));
+    assert_eq!(tree.archive_into(&mut page, 0).unwrap_err(), PrivaxError::NothingToArchive.into());
+
+    for leaf in 1..=5u8 {
+        tree.append([leaf; 32]).unwrap();
+        roots.push(tree.root());
+    }
+    assert_eq!(tree.archive_into(&mut page, 1).unwrap_err(), PrivaxError::WrongArchivePage.into());
+    assert_eq!(tree.archive_into(&mut page, 0).unwrap(), (5, 0));
+    assert_eq!(page.roots(), &roots[..]);
+
+    // Roots pushed out of the history before archiving are lost; the rest still make it
+    for _ in 0..MERKLE_ROOT_HISTORY_SIZE + 3 {
+        tree.append([6; 32]).unwrap();
+        roots.push(tree.root());
+    }
+    assert_eq!(tree.archive_into(&mut page, 0).unwrap(), (MERKLE_ROOT_HISTORY_SIZE as u64 + 3, 3));
+    assert!(page.roots()[6..9].iter().all(|root| root == &[0; 32]));
+    assert!(roots[9..].iter().all(|root| page.contains(root)));
+    assert!(!page.contains(&roots[7]));
+
+    // The next page takes over once this one is full
+    let mut next: RootArchivePage = bytemuck::Zeroable::zeroed();
+    while tree.root_count <= ROOT_ARCHIVE_PAGE_ROOTS as u64 {
+        tree.append(tree.root()).unwrap();
+        let page_index = tree.archived_roots / ROOT_ARCHIVE_PAGE_ROOTS as u64;
+        tree.archive_into(if page_index == 0 { &mut page } else { &mut next }, page_index).unwrap();
+    }
+    assert_eq!(page.count, ROOT_ARCHIVE_PAGE_ROOTS as u64);
+    assert_eq!((next.page, next.count), (1, 1));
+    assert!(next.contains(&tree.root()));
+}
+
+#[test]
+fn accepted_roots_match_in_full() {
+    let mut tree = empty_tree();
+    tree.append([1; 32]).unwrap();
+    let archived = tree.root();
+    let mut page: RootArchivePage = bytemuck::Zeroable::zeroed();
+    tree.archive_into(&mut page, 0).unwrap();
+    for _ in 0..MERKLE_ROOT_HISTORY_SIZE {
+        tree.append([2; 32]).unwrap();
+    }
+    let recent = tree.root();
+    assert!(tree.accepts_root(&recent, None));
+    assert!(!tree.accepts_root(&archived, Some(&page)));
+    tree.accept_archived_roots = 1;
+    assert!(tree.accepts_root(&archived, Some(&page)));
+    assert!(!tree.accepts_root(&archived, None));
+
+    // A root agreeing in its first 8 bytes (the proof's u64 input) is another root
+    for (mut root, archive) in [(recent, None), (archived, Some(&page))] {
+        root[31] ^= 1;
+        assert!(!tree.accepts_root(&root, archive));
+        root[31] ^= 1;
+        root[8] ^= 1;
+        assert!(!tree.accepts_root(&root, archive));
+    }
+    assert!(!tree.accepts_root(&[0; 32], Some(&page)));
+}
@@ -0,0 +1,311 @@
+// The on-chain PLONK check against proofs from a small honest prover, for a circuit that
+// exposes its public inputs and multiplies the first two of them through copy constraints:
+// a valid proof verifies, and a change to any input, the proof or the input count doesn't.
+use anchor_lang::error::Error;
+use anchor_lang::solana_program::keccak;
+use ark_bn254::{Fr, G1Affine, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, EvaluationDomain, Evaluations, Polynomial, Radix2EvaluationDomain};
+use ark_serialize::CanonicalSerialize;
+use privax_protocol::plonk::{check_vk, verify, G2_GENERATOR, PROOF_SIZE, VK_SIZE};
+use privax_protocol::proof_format::{g2_from_arkworks, BN254_SCALAR_FIELD_MODULUS};
+use privax_protocol::PrivaxError;
+
+type Poly = DensePolynomial<Fr>;
+
+const K: [u64; 3] = [1, 2, 3];
+
+struct Proof {
+    vk_data: Vec<u8>,
+    proof: Vec<u8>,
+}
+
+fn scalar_bytes(value: Fr) -> Vec<u8> {
+    value.into_bigint().to_bytes_be()
+}
+
+fn challenge(items: &[&[u8]]) -> Fr {
+    Fr::from_be_bytes_mod_order(&keccak::hashv(items).to_bytes())
+}
+
+fn constant(value: Fr) -> Poly {
+    Poly::from_coefficients_vec(vec![value])
+}
+
+// Everything is committed to with a known tau; the verifier only ever sees the points.
+struct Setup {
+    tau: Fr,
+}
+
+impl Setup {
+    fn commit(&self, poly: &Poly) -> Vec<u8> {
+        let point = (G1Affine::generator() * poly.evaluate(&self.tau)).into_affine();
+        match point.xy() {
+            Some((x, y)) => [x.into_bigint().to_bytes_be(), y.into_bigint().to_bytes_be()].concat(),
+            None => vec![0; 64],
+        }
+    }
+
+    fn x_2(&self) -> [u8; 128] {
+        let mut point = Vec::new();
+        (G2Affine::generator() * self.tau).into_affine().serialize_uncompressed(&mut point).unwrap();
+        g2_from_arkworks(point.as_slice().try_into().unwrap()).unwrap()
+    }
+}
+
+// A key for inputs.len() public inputs and a proof for these inputs, in the on-chain layout.
+// Row i < m exposes input i in wire a; row m computes inputs[0] · inputs[1] from copies of them.
+fn prove(inputs: &[[u8; 32]]) -> Proof {
+    let setup = Setup { tau: Fr::from(0x5eed_u64) };
+    let m = inputs.len();
+    let domain = Radix2EvaluationDomain::<Fr>::new(m + 1).unwrap();
+    let n = domain.size();
+    let w = domain.group_gen;
+    let roots: Vec<Fr> = domain.elements().collect();
+    let interpolate = |evals: Vec<Fr>| Evaluations::from_vec_and_domain(evals, domain).interpolate();
+    let values: Vec<Fr> = inputs.iter().map(|input| Fr::from_be_bytes_mod_order(input)).collect();
+
+    // Selectors and witness, one row each
+    let (mut qm, mut ql, mut qo) = (vec![Fr::zero(); n], vec![Fr::zero(); n], vec![Fr::zero(); n]);
+    let mut wires = [vec![Fr::zero(); n], vec![Fr::zero(); n], vec![Fr::zero(); n]];
+    for (row, value) in values.iter().enumerate() {
+        ql[row] = Fr::one();
+        wires[0][row] = *value;
+    }
+    qm[m] = Fr::one();
+    qo[m] = -Fr::one();
+    wires[0][m] = values[0];
+    wires[1][m] = values[1];
+    wires[2][m] = values[0] * values[1];
+
+    // The copy constraints swap the copied positions in the identity permutation
+    let ids: Vec<Vec<Fr>> = K.iter().map(|&k| roots.iter().map(|root| Fr::from(k) * root).collect()).collect();
+    let mut sigmas = ids.clone();
+    for (column, row) in [(0, 0), (1, 1)] {
+        sigmas[column][m] = ids[0][row];
+        sigmas[0][row] = ids[column][m];
+    }
+
+    let [qm_poly, ql_poly, qr_poly, qo_poly, qc_poly] =
+        [qm, ql, vec![Fr::zero(); n], qo, vec![Fr::zero(); n]].map(&interpolate);
+    let sigma_polys: Vec<Poly> = sigmas.iter().cloned().map(&interpolate).collect();
+    let (k1, k2) = (Fr::from(K[1]), Fr::from(K[2]));
+
+    let commitments: Vec<Vec<u8>> = [&qm_poly, &ql_poly, &qr_poly, &qo_poly, &qc_poly]
+        .into_iter()
+        .chain(&sigma_polys)
+        .map(|poly| setup.commit(poly))
+        .collect();
+    let mut vk_data = vec![n.trailing_zeros() as u8, m as u8];
+    for value in [k1, k2, w] {
+        vk_data.extend(scalar_bytes(value));
+    }
+    vk_data.extend(commitments.concat());
+    vk_data.extend(setup.x_2());
+
+    // Round 1: the wires
+    let [a_poly, b_poly, c_poly] = wires.clone().map(&interpolate);
+    let wire_commitments: Vec<Vec<u8>> = [&a_poly, &b_poly, &c_poly].map(|poly| setup.commit(poly)).to_vec();
+    let mut transcript: Vec<&[u8]> = commitments.iter().map(Vec::as_slice).collect();
+    transcript.extend(inputs.iter().map(|input| input.as_slice()));
+    transcript.extend(wire_commitments.iter().map(Vec::as_slice));
+    let beta = challenge(&transcript);
+    let gamma = challenge(&[&scalar_bytes(beta)]);
+
+    // Round 2: the permutation accumulator
+    let mut z = vec![Fr::one(); n];
+    for row in 0..n - 1 {
+        let (mut numerator, mut denominator) = (Fr::one(), Fr::one());
+        for column in 0..3 {
+            numerator *= wires[column][row] + beta * ids[column][row] + gamma;
+            denominator *= wires[column][row] + beta * sigmas[column][row] + gamma;
+        }
+        z[row + 1] = z[row] * numerator * denominator.inverse().unwrap();
+    }
+    let z_poly = interpolate(z);
+    let z_commitment = setup.commit(&z_poly);
+    let alpha = challenge(&[&scalar_bytes(beta), &scalar_bytes(gamma), &z_commitment]);
+
+    // Round 3: the quotient, split into three n-coefficient parts
+    let mut unit = vec![Fr::zero(); n];
+    unit[0] = Fr::one();
+    let l1_poly = interpolate(unit);
+    let mut pi = vec![Fr::zero(); n];
+    for (row, value) in values.iter().enumerate() {
+        pi[row] = -*value;
+    }
+    let pi_poly = interpolate(pi);
+    let z_shifted = Poly::from_coefficients_vec(
+        z_poly.coeffs.iter().zip(domain.elements()).map(|(coefficient, power)| *coefficient * power).collect(),
+    );
+    let linear = |k: Fr| Poly::from_coefficients_vec(vec![gamma, beta * k]);
+    let gate = &(&(&(&qm_poly * &(&a_poly * &b_poly)) + &(&ql_poly * &a_poly)) + &(&qr_poly * &b_poly))
+        + &(&(&(&qo_poly * &c_poly) + &qc_poly) + &pi_poly);
+    let ids_product = &(&(&z_poly * &(&a_poly + &linear(Fr::one()))) * &(&b_poly + &linear(k1)))
+        * &(&c_poly + &linear(k2));
+    let sigma_term = |wire: &Poly, sigma: &Poly| wire + &(&(sigma * beta) + &constant(gamma));
+    let sigmas_product = &(&(&z_shifted * &sigma_term(&a_poly, &sigma_polys[0])) * &sigma_term(&b_poly, &sigma_polys[1]))
+        * &sigma_term(&c_poly, &sigma_polys[2]);
+    let boundary = &(&z_poly - &constant(Fr::one())) * &l1_poly;
+    let numerator = &(&gate + &(&(&ids_product - &sigmas_product) * alpha)) + &(&boundary * alpha.square());
+    let (t_poly, remainder) = numerator.divide_by_vanishing_poly(domain).unwrap();
+    assert!(remainder.is_zero());
+    let mut t_coefficients = t_poly.coeffs.clone();
+    t_coefficients.resize(3 * n, Fr::zero());
+    let t_parts: Vec<Poly> = t_coefficients.chunks(n).map(Poly::from_coefficients_slice).collect();
+    let t_commitments: Vec<Vec<u8>> = t_parts.iter().map(|part| setup.commit(part)).collect();
+    let xi = challenge(&[&scalar_bytes(alpha), &t_commitments[0], &t_commitments[1], &t_commitments[2]]);
+
+    // Round 4: evaluations at xi
+    let [a, b, c] = [&a_poly, &b_poly, &c_poly].map(|poly| poly.evaluate(&xi));
+    let (s1, s2, zw) = (sigma_polys[0].evaluate(&xi), sigma_polys[1].evaluate(&xi), z_poly.evaluate(&(xi * w)));
+    let evaluations: Vec<u8> = [a, b, c, s1, s2, zw].into_iter().flat_map(scalar_bytes).collect();
+    let xi_bytes = scalar_bytes(xi);
+    let mut transcript = vec![xi_bytes.as_slice()];
+    transcript.extend(evaluations.chunks(32));
+    let v1 = challenge(&transcript);
+    let v: Vec<Fr> = (1..=5).map(|power| v1.pow([power])).collect();
+
+    // Round 5: the openings, of the linearization (which vanishes at xi) with the wires and
+    // sigmas batched in, and of z at xi · w
+    let xin = xi.pow([n as u64]);
+    let zh = xin - Fr::one();
+    let l1 = l1_poly.evaluate(&xi);
+    let permutation_ab = (a + beta * s1 + gamma) * (b + beta * s2 + gamma);
+    let r0 = pi_poly.evaluate(&xi) - l1 * alpha.square() - alpha * permutation_ab * (c + gamma) * zw;
+    let z_factor = alpha * (a + beta * xi + gamma) * (b + beta * k1 * xi + gamma) * (c + beta * k2 * xi + gamma)
+        + alpha.square() * l1;
+    let t_combined = &(&t_parts[0] + &(&t_parts[1] * xin)) + &(&t_parts[2] * xin.square());
+    let linearization = [
+        (&qm_poly, a * b),
+        (&ql_poly, a),
+        (&qr_poly, b),
+        (&qo_poly, c),
+        (&qc_poly, Fr::one()),
+        (&z_poly, z_factor),
+        (&sigma_polys[2], -alpha * beta * zw * permutation_ab),
+        (&t_combined, -zh),
+    ]
+    .into_iter()
+    .fold(constant(r0), |sum, (poly, factor)| &sum + &(poly * factor));
+    let opened = [(&a_poly, a), (&b_poly, b), (&c_poly, c), (&sigma_polys[0], s1), (&sigma_polys[1], s2)]
+        .into_iter()
+        .zip(&v)
+        .fold(linearization, |sum, ((poly, value), factor)| &sum + &(&(poly - &constant(value)) * *factor));
+    assert!(opened.evaluate(&xi).is_zero());
+    let w_xi = &opened / &Poly::from_coefficients_vec(vec![-xi, Fr::one()]);
+    let w_xiw = &(&z_poly - &constant(zw)) / &Poly::from_coefficients_vec(vec![-xi * w, Fr::one()]);
+
+    let mut proof = wire_commitments.concat();
+    proof.extend(z_commitment);
+    proof.extend(t_commitments.concat());
+    proof.extend(setup.commit(&w_xi));
+    proof.extend(setup.commit(&w_xiw));
+    proof.extend(evaluations);
+    Proof { vk_data, proof }
+}
+
+// Eight u64 inputs as withdraw's fixed inputs would be, then a full-width commitment.
+fn inputs() -> Vec<[u8; 32]> {
+    let mut inputs: Vec<[u8; 32]> = (1..=8u64)
+        .map(|input| {
+            let mut bytes = [0u8; 32];
+            bytes[24..].copy_from_slice(&(input * 1_000_003).to_be_bytes());
+            bytes
+        })
+        .collect();
+    inputs.push(privax_test_vectors::vectors().notes[0].commitment);
+    inputs
+}
+
+#[test]
+fn accepts_a_valid_proof() {
+    let inputs = inputs();
+    let proof = prove(&inputs);
+    assert_eq!((proof.vk_data.len(), proof.proof.len()), (VK_SIZE, PROOF_SIZE));
+    check_vk(&proof.vk_data, 8..=13).unwrap();
+    assert!(verify(&proof.vk_data, &proof.proof, &inputs).unwrap());
+}
+
+#[test]
+fn rejects_changed_public_inputs() {
+    let inputs = inputs();
+    let proof = prove(&inputs);
+    for (input, byte) in [(0, 31), (1, 24), (3, 24), (8, 31), (8, 1), (8, 0)] {
+        let mut changed = inputs.clone();
+        changed[input][byte] ^= 1;
+        assert!(!verify(&proof.vk_data, &proof.proof, &changed).unwrap(), "input {input}, byte {byte}");
+    }
+    // Another encoding of the same scalar isn't accepted either
+    let mut changed = inputs.clone();
+    changed[0] = BN254_SCALAR_FIELD_MODULUS;
+    assert!(!verify(&proof.vk_data, &proof.proof, &changed).unwrap());
+}
+
+#[test]
+fn rejects_a_changed_proof() {
+    let inputs = inputs();
+    let proof = prove(&inputs);
+    // Each commitment and each evaluation
+    for offset in (0..PROOF_SIZE).step_by(32) {
+        let mut changed = proof.proof.clone();
+        changed[offset + 31] ^= 1;
+        assert!(!verify(&proof.vk_data, &changed, &inputs).unwrap(), "offset {offset}");
+    }
+    // Swapped commitments, both on the curve
+    let mut swapped = proof.proof.clone();
+    swapped[..128].rotate_left(64);
+    assert!(!verify(&proof.vk_data, &swapped, &inputs).unwrap());
+    // An evaluation that isn't a canonical scalar
+    let mut changed = proof.proof.clone();
+    changed[PROOF_SIZE - 32..].copy_from_slice(&BN254_SCALAR_FIELD_MODULUS);
+    assert!(!verify(&proof.vk_data, &changed, &inputs).unwrap());
+    // A proof for other inputs under the same key
+    let mut other = inputs.clone();
+    other[8][5] ^= 1;
+    let other = prove(&other);
+    assert!(!verify(&proof.vk_data, &other.proof, &inputs).unwrap());
+    // Truncated
+    let err = verify(&proof.vk_data, &proof.proof[..PROOF_SIZE - 1], &inputs).unwrap_err();
+    assert_eq!(err, Error::from(PrivaxError::InvalidProofEncoding));
+}
+
+#[test]
+fn needs_one_input_per_key_input() {
+    let inputs = inputs();
+    let proof = prove(&inputs);
+    for count in [inputs.len() - 1, inputs.len() + 1] {
+        let mut resized = inputs.clone();
+        resized.resize(count, [0; 32]);
+        let err = verify(&proof.vk_data, &proof.proof, &resized).unwrap_err();
+        assert_eq!(err, Error::from(PrivaxError::InvalidPublicInputCount));
+    }
+}
+
+#[test]
+fn checks_the_key() {
+    let proof = prove(&inputs());
+    let invalid = Error::from(PrivaxError::InvalidVerifyingKey);
+    // A public input count the program doesn't take
+    assert_eq!(check_vk(&proof.vk_data, 10..=13).unwrap_err(), invalid);
+    // A domain w doesn't generate
+    for power in [0, 3, 5, 29] {
+        let mut changed = proof.vk_data.clone();
+        changed[0] = power;
+        assert_eq!(check_vk(&changed, 8..=13).unwrap_err(), invalid, "power {power}");
+    }
+    // A non-canonical k1
+    let mut changed = proof.vk_data.clone();
+    changed[2..34].copy_from_slice(&BN254_SCALAR_FIELD_MODULUS);
+    assert_eq!(check_vk(&changed, 8..=13).unwrap_err(), invalid);
+    assert_eq!(check_vk(&proof.vk_data[..VK_SIZE - 1], 8..=13).unwrap_err(), invalid);
+}
+
+#[test]
+fn g2_generator_matches_arkworks() {
+    let mut point = Vec::new();
+    G2Affine::generator().serialize_uncompressed(&mut point).unwrap();
+    assert_eq!(g2_from_arkworks(point.as_slice().try_into().unwrap()).unwrap(), G2_GENERATOR);
+}
@@ -0,0 +1,98 @@
+// Round trips between the arkworks serialization and the on-chain proof and key layout, and
+// rejection of what has no on-chain encoding, scalars included.
+use privax_protocol::proof_format::*;
+use proptest::prelude::*;
+
+// A big-endian coordinate below the BN254 base field modulus (whose top byte is 0x30).
+fn coordinate() -> impl Strategy<Value = [u8; FIELD_SIZE]> {
+    any::<[u8; FIELD_SIZE]>().prop_map(|mut bytes| {
+        bytes[0] %= BN254_BASE_FIELD_MODULUS[0];
+        bytes
+    })
+}
+
+fn points<const N: usize>() -> impl Strategy<Value = [u8; N]> {
+    proptest::collection::vec(coordinate(), N / FIELD_SIZE).prop_map(|coordinates| coordinates.concat().try_into().unwrap())
+}
+
+proptest! {
+    #[test]
+    fn g1_round_trips(point in points::<G1_SIZE>()) {
+        prop_assert_eq!(g1_from_arkworks(&g1_to_arkworks(&point)).unwrap(), point);
+    }
+
+    #[test]
+    fn g2_round_trips(point in points::<G2_SIZE>()) {
+        prop_assert_eq!(g2_from_arkworks(&g2_to_arkworks(&point)).unwrap(), point);
+    }
+
+    #[test]
+    fn proofs_round_trip(a in points::<G1_SIZE>(), b in points::<G2_SIZE>(), c in points::<G1_SIZE>()) {
+        let arkworks = [g1_to_arkworks(&a).as_slice(), &g2_to_arkworks(&b), &g1_to_arkworks(&c)].concat();
+        prop_assert_eq!(proof_from_arkworks(&arkworks).unwrap(), (a, b, c));
+    }
+
+    #[test]
+    fn verifying_keys_round_trip(
+        fixed in proptest::collection::vec(coordinate(), VK_FIXED_SIZE / FIELD_SIZE),
+        ic in proptest::collection::vec(points::<G1_SIZE>(), 1..12),
+    ) {
+        let vk_data = [fixed.concat(), ic.concat()].concat();
+        let arkworks = vk_to_arkworks(&vk_data).unwrap();
+        prop_assert_eq!(arkworks.len(), vk_data.len() + 8);
+        prop_assert_eq!(vk_from_arkworks(&arkworks).unwrap(), vk_data.clone());
+        prop_assert!(check_groth16_vk(&vk_data, ic.len() - 1..=ic.len() - 1).is_ok());
+    }
+
+    #[test]
+    fn non_canonical_coordinates_are_rejected(
+        point in points::<G1_SIZE>(),
+        index in 0..G1_SIZE / FIELD_SIZE,
+        excess in any::<[u8; FIELD_SIZE]>(),
+    ) {
+        // A value above the modulus in one coordinate, below the bits arkworks keeps flags in
+        let mut point = point;
+        let coordinate = &mut point[index * FIELD_SIZE..(index + 1) * FIELD_SIZE];
+        coordinate.copy_from_slice(&excess);
+        coordinate[0] = BN254_BASE_FIELD_MODULUS[0] + 1 + coordinate[0] % 0x0f;
+        prop_assert!(check_coordinates(&point).is_err());
+        prop_assert!(g1_from_arkworks(&g1_to_arkworks(&point)).is_err());
+    }
+
+    #[test]
+    fn points_at_infinity_are_rejected(point in points::<G1_SIZE>()) {
+        let mut arkworks = g1_to_arkworks(&point);
+        arkworks[G1_SIZE - 1] |= 1 << 6;
+        prop_assert!(g1_from_arkworks(&arkworks).is_err());
+    }
+
+    #[test]
+    fn arbitrary_bytes_never_panic(bytes in proptest::collection::vec(any::<u8>(), 0..1024)) {
+        let _ = proof_from_arkworks(&bytes);
+        let _ = vk_from_arkworks(&bytes);
+        let _ = vk_to_arkworks(&bytes);
+        let _ = check_groth16_vk(&bytes, 0..=16);
+        let _ = check_coordinates(&bytes);
+    }
+}
+
+#[test]
+fn scalars_stop_below_the_modulus() {
+    let mut largest = BN254_SCALAR_FIELD_MODULUS;
+    largest[FIELD_SIZE - 1] -= 1;
+    assert!(is_canonical_scalar(&largest));
+    assert!(is_canonical_scalar(&[0; FIELD_SIZE]));
+    assert!(!is_canonical_scalar(&BN254_SCALAR_FIELD_MODULUS));
+    assert!(!is_canonical_scalar(&BN254_BASE_FIELD_MODULUS));
+    assert!(!is_canonical_scalar(&[0xff; FIELD_SIZE]));
+}
+
+// The pool checks encodings before it would call the verifier, so the mock verifier's test
+// proof has to pass them.
+#[test]
+fn the_mock_verifiers_test_proof_is_well_encoded() {
+    let (a, b, c) = privax_mock_verifier::test_proof();
+    check_coordinates(&a).unwrap();
+    check_coordinates(&b).unwrap();
+    check_coordinates(&c).unwrap();
+}
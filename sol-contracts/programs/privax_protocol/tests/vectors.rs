@@ -0,0 +1,78 @@
+// The program's side of the shared test vectors (test-vectors/vectors.json): the commitment
+// tree's zeros and roots, external nullifiers and the public-input layout withdraw checks.
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AnchorSerialize;
+use privax_protocol::privax_protocol::{REQUIRED_PUBLIC_INPUTS_COUNT, VARIABLE_DENOMINATION};
+use privax_protocol::{external_nullifier, MerkleTree, NullifierShard, MAX_PUBLIC_INPUTS, MERKLE_TREE_DEPTH};
+use privax_test_vectors::vectors;
+
+fn public_input(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+#[test]
+fn merkle_tree_roots() {
+    let tree = &vectors().poseidon_tree;
+    let mut merkle_tree: MerkleTree = bytemuck::Zeroable::zeroed();
+    merkle_tree.init(0);
+    assert_eq!(merkle_tree.zeros[..], tree.zeros[..MERKLE_TREE_DEPTH]);
+    assert_eq!(merkle_tree.root(), tree.roots[0]);
+    for (i, leaf) in tree.leaves.iter().enumerate() {
+        assert_eq!(merkle_tree.append(*leaf).unwrap(), i as u64);
+        assert_eq!(merkle_tree.root(), tree.roots[i + 1]);
+    }
+
+    // A subtree insert lands on the same root as inserting its leaves one by one
+    let mut merkle_tree: MerkleTree = bytemuck::Zeroable::zeroed();
+    merkle_tree.init(0);
+    merkle_tree.append_subtree(MerkleTree::subtree_root(&tree.leaves[..2]), 1).unwrap();
+    assert_eq!(merkle_tree.root(), tree.roots[2]);
+}
+
+#[test]
+fn external_nullifiers() {
+    let vectors = vectors();
+    assert_eq!(vectors.program_id, privax_protocol::ID.to_string());
+    for vector in &vectors.external_nullifiers {
+        let mint: Pubkey = vector.mint.parse().unwrap();
+        assert_eq!(external_nullifier(&mint, vector.denomination, vector.circuit_version), vector.value, "{vector:?}");
+    }
+}
+
+#[test]
+fn withdraw_public_inputs() {
+    let vectors = vectors();
+    for withdrawal in &vectors.withdrawals {
+        let inputs = &withdrawal.public_inputs;
+        assert_eq!(inputs.len(), MAX_PUBLIC_INPUTS);
+        let notes: Vec<_> = withdrawal.input_notes.iter().map(|&i| &vectors.notes[i]).collect();
+
+        let recipient: Pubkey = withdrawal.recipient.parse().unwrap();
+        let mint: Pubkey = withdrawal.mint.parse().unwrap();
+        assert_eq!(inputs[0], public_input(&vectors.poseidon_tree.root));
+        assert_eq!(inputs[2], public_input(recipient.as_ref()));
+        assert_eq!(inputs[3], withdrawal.amount);
+        assert_eq!(inputs[4], external_nullifier(&mint, VARIABLE_DENOMINATION, withdrawal.circuit_version));
+        assert_eq!(inputs[5], withdrawal.proof_slot);
+        // Withdrawn without a relayer: no fee, and anyone may submit
+        assert_eq!((inputs[6], inputs[7]), (0, 0));
+
+        // The first nullifier at 1, the others after the fixed inputs, then the change commitment
+        let mut nullifier_inputs = vec![inputs[1]];
+        nullifier_inputs.extend_from_slice(&inputs[REQUIRED_PUBLIC_INPUTS_COUNT..REQUIRED_PUBLIC_INPUTS_COUNT + notes.len() - 1]);
+        let mut used = REQUIRED_PUBLIC_INPUTS_COUNT + notes.len() - 1;
+        for ((note, input), prefix) in notes.iter().zip(&nullifier_inputs).zip(&withdrawal.nullifier_prefixes) {
+            assert_eq!(*input, public_input(&note.nullifier_hash));
+            // Shards go by the full hash the program records
+            assert_eq!(NullifierShard::prefix_of(&note.nullifier_hash), *prefix);
+        }
+        if let Some(change) = withdrawal.change_note {
+            assert_eq!(inputs[used], public_input(&vectors.notes[change].commitment));
+            used += 1;
+        }
+        assert!(inputs[used..].iter().all(|&input| input == 0));
+
+        let inputs: [u64; MAX_PUBLIC_INPUTS] = inputs.as_slice().try_into().unwrap();
+        assert_eq!(inputs.try_to_vec().unwrap(), withdrawal.serialized_public_inputs);
+    }
+}
@@ -0,0 +1,45 @@
+// The circuit breaker admits withdrawals up to the epoch's limit and no further: the one that
+// reaches it exactly trips the breaker, and one that would pass it is refused uncounted.
+use anchor_lang::error::Error;
+use privax_protocol::{PrivaxError, ProgramState};
+
+fn limited(max_withdrawal_per_epoch: u64) -> ProgramState {
+    ProgramState { max_withdrawal_per_epoch, withdrawal_epoch_slots: 100, ..Default::default() }
+}
+
+#[test]
+fn trips_when_the_total_reaches_the_limit() {
+    let mut state = limited(3_000);
+    assert!(!state.admit_withdrawal(2_000, 10).unwrap());
+    assert_eq!(state.admit_withdrawal(1_001, 10).unwrap_err(), Error::from(PrivaxError::WithdrawalLimitReached));
+    assert_eq!(state.withdrawn_in_epoch, 2_000);
+    assert!(state.admit_withdrawal(1_000, 10).unwrap());
+    assert_eq!(state.withdrawn_in_epoch, 3_000);
+    assert_eq!(state.admit_withdrawal(1, 10).unwrap_err(), Error::from(PrivaxError::WithdrawalLimitReached));
+
+    // A single withdrawal of the whole limit trips it too
+    let mut state = limited(3_000);
+    assert!(state.admit_withdrawal(3_000, 10).unwrap());
+    assert_eq!(limited(3_000).admit_withdrawal(3_001, 10).unwrap_err(), Error::from(PrivaxError::WithdrawalLimitReached));
+}
+
+#[test]
+fn refuses_a_total_that_overflows() {
+    let mut state = limited(u64::MAX);
+    assert!(!state.admit_withdrawal(u64::MAX - 1, 10).unwrap());
+    assert_eq!(state.admit_withdrawal(2, 10).unwrap_err(), Error::from(PrivaxError::WithdrawalLimitReached));
+    assert_eq!(state.withdrawn_in_epoch, u64::MAX - 1);
+    assert!(state.admit_withdrawal(1, 10).unwrap());
+}
+
+#[test]
+fn starts_each_epoch_from_zero() {
+    let mut state = limited(3_000);
+    assert!(!state.admit_withdrawal(2_500, 99).unwrap());
+    assert!(!state.admit_withdrawal(2_500, 100).unwrap());
+    assert_eq!((state.withdrawal_epoch, state.withdrawn_in_epoch), (1, 2_500));
+    // And a zero limit counts nothing
+    let mut state = limited(0);
+    assert!(!state.admit_withdrawal(u64::MAX, 10).unwrap());
+    assert_eq!(state.withdrawn_in_epoch, 0);
+}
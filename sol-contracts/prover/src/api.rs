@@ -0,0 +1,154 @@
+// The HTTP API, JSON in and out:
+//
+//   POST   /jobs       { "circuit", "witness": base64 .wtns, "priority": "low" | "normal" | "high" }
+//                      -> 202 { id, status, position }
+//   GET    /jobs/<id>  -> { id, circuit, priority, status, position | proof | error, proving_ms }
+//   DELETE /jobs/<id>  forgets the job and its proof
+//   GET    /healthz, /readyz (503 while the queue is full), /metrics
+//
+// A proof is withdraw's a, b and c, and the circuit's public signals, as hex.
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::sync::Arc;
+
+use base64::Engine;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response};
+use tracing::{debug, info, warn};
+
+use crate::backend::{Backend, Circuit};
+use crate::queue::{JobView, Priority, Queue, Status};
+
+// A witness is 32 bytes per wire; the split circuit's is a few MB as base64.
+const MAX_BODY_BYTES: u64 = 32 << 20;
+const API_KEY_HEADER: &str = "X-Api-Key";
+
+pub struct State {
+    pub queue: Arc<Queue>,
+    pub circuits: HashMap<String, Arc<Circuit>>,
+    pub backend: Backend,
+    pub workers: usize,
+    pub api_keys: HashSet<String>, // Allowed to submit high-priority jobs
+}
+
+pub fn handle(state: &State, mut request: Request) {
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let (status, body) = match (&method, url.as_str()) {
+        (Method::Get, "/healthz") => (200, health(state)),
+        (Method::Get, "/readyz") => match state.queue.is_full() {
+            false => (200, json!({ "status": "ready" })),
+            true => (503, json!({ "status": "not ready", "reason": "the queue is full" })),
+        },
+        (Method::Get, "/metrics") => {
+            let mut metrics = String::new();
+            state.queue.render_metrics(&mut metrics);
+            let content_type = Header::from_bytes("Content-Type", "text/plain; version=0.0.4").unwrap();
+            let _ = request.respond(Response::from_string(metrics).with_header(content_type));
+            return;
+        }
+        (Method::Post, "/jobs") => submit(state, &mut request),
+        (_, path) if path.starts_with("/jobs/") => {
+            let id = &path["/jobs/".len()..];
+            match method {
+                Method::Get => match state.queue.get(id) {
+                    Some(job) => (200, job_json(&job)),
+                    None => (404, json!({ "error": "unknown job" })),
+                },
+                Method::Delete if state.queue.delete(id) => (204, Value::Null),
+                Method::Delete => (404, json!({ "error": "unknown job" })),
+                _ => (405, json!({ "error": "method not allowed" })),
+            }
+        }
+        _ => (404, json!({ "error": "not found" })),
+    };
+    debug!(%method, url, status, "request");
+    let response = match body {
+        Value::Null => Response::from_string(String::new()),
+        body => Response::from_string(body.to_string())
+            .with_header(Header::from_bytes("Content-Type", "application/json").unwrap()),
+    };
+    if let Err(err) = request.respond(response.with_status_code(status)) {
+        warn!(error = %err, "failed to send a response");
+    }
+}
+
+fn health(state: &State) -> Value {
+    let circuits: Vec<Value> = state
+        .circuits
+        .values()
+        .map(|circuit| json!({ "name": circuit.name, "public_inputs": circuit.r1cs.public_inputs, "wires": circuit.r1cs.wires }))
+        .collect();
+    json!({ "status": "ok", "backend": state.backend.name(), "workers": state.workers, "circuits": circuits })
+}
+
+fn submit(state: &State, request: &mut Request) -> (u16, Value) {
+    let error = |status: u16, message: String| (status, json!({ "error": message }));
+    let mut body = Vec::new();
+    if let Err(err) = request.as_reader().take(MAX_BODY_BYTES + 1).read_to_end(&mut body) {
+        return error(400, format!("failed to read the request: {err}"));
+    }
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return error(413, "request body too large".to_string());
+    }
+    let body: Value = match serde_json::from_slice(&body) {
+        Ok(body) => body,
+        Err(err) => return error(400, format!("invalid JSON: {err}")),
+    };
+    let priority = match body["priority"].as_str().map(str::parse::<Priority>).unwrap_or(Ok(Priority::Normal)) {
+        Ok(priority) => priority,
+        Err(err) => return error(400, err),
+    };
+    if priority == Priority::High {
+        let key = request.headers().iter().find(|header| header.field.equiv(API_KEY_HEADER)).map(|header| header.value.as_str());
+        match key {
+            None => return error(401, format!("high priority needs an {API_KEY_HEADER}")),
+            Some(key) if !state.api_keys.contains(key) => return error(401, "unknown API key".to_string()),
+            Some(_) => {}
+        }
+    }
+    let Some(circuit) = body["circuit"].as_str().and_then(|name| state.circuits.get(name)) else {
+        let names: Vec<&str> = state.circuits.keys().map(String::as_str).collect();
+        return error(400, format!("expected \"circuit\", one of {}", names.join(", ")));
+    };
+    let witness = match body["witness"].as_str().map(|witness| base64::engine::general_purpose::STANDARD.decode(witness)) {
+        Some(Ok(witness)) => witness,
+        Some(Err(err)) => return error(400, format!("invalid witness base64: {err}")),
+        None => return error(400, "expected the .wtns file as base64 in \"witness\"".to_string()),
+    };
+    if let Err(err) = circuit.check(&witness) {
+        return error(422, err);
+    }
+    let Some(id) = state.queue.submit(&circuit.name, witness, priority) else {
+        return error(503, "the queue is full; retry later".to_string());
+    };
+    info!(job = id, circuit = circuit.name, priority = priority.name(), "job queued");
+    match state.queue.get(&id) {
+        Some(job) => (202, job_json(&job)),
+        None => error(500, "job vanished".to_string()),
+    }
+}
+
+fn job_json(job: &JobView) -> Value {
+    let hex = |bytes: &[u8]| bytes.iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+    let mut out = json!({ "id": job.id, "circuit": job.circuit, "priority": job.priority.name() });
+    let fields = match &job.status {
+        Status::Queued { position } => json!({ "status": "queued", "position": position }),
+        Status::Proving => json!({ "status": "proving" }),
+        Status::Done(proof) => json!({
+            "status": "done",
+            "proof": {
+                "a": hex(&proof.a),
+                "b": hex(&proof.b),
+                "c": hex(&proof.c),
+                "public_signals": proof.public_signals.iter().map(|signal| hex(signal)).collect::<Vec<_>>(),
+            },
+        }),
+        Status::Failed(err) => json!({ "status": "failed", "error": err }),
+    };
+    out.as_object_mut().unwrap().extend(fields.as_object().unwrap().clone());
+    if let Some(proving_time) = job.proving_time {
+        out["proving_ms"] = json!(proving_time.as_millis() as u64);
+    }
+    out
+}
@@ -0,0 +1,78 @@
+// Proving backends. `cpu` proves in-process with arkworks over an arkworks proving key, the same
+// prover the mobile bindings run; one proof already spreads over every core. `gpu` proves with
+// an external CUDA prover through privax_mobile::gpu, one device per worker.
+use std::path::{Path, PathBuf};
+
+use ark_bn254::Bn254;
+use ark_groth16::ProvingKey;
+use privax_mobile::circom::{parse_witness, R1cs};
+use privax_mobile::gpu::GpuProver;
+use privax_mobile::prove::{self, WithdrawProof};
+
+pub const R1CS_FILE: &str = "circuit.r1cs";
+pub const ARKWORKS_KEY_FILE: &str = "proving_key.ark";
+pub const ZKEY_FILE: &str = "circuit.zkey";
+
+#[derive(Clone, Debug)]
+pub enum Backend {
+    Cpu,
+    Gpu { program: PathBuf, devices: Vec<usize> }, // Worker n proves on devices[n]
+}
+
+impl Backend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Cpu => "cpu",
+            Backend::Gpu { .. } => "gpu",
+        }
+    }
+}
+
+// A circuit's files, loaded once at startup.
+pub struct Circuit {
+    pub name: String,
+    pub r1cs: R1cs,
+    proving_key: Option<ProvingKey<Bn254>>, // cpu
+    zkey: PathBuf,                          // gpu
+}
+
+impl Circuit {
+    // From a directory holding circuit.r1cs, plus proving_key.ark for the cpu backend or
+    // circuit.zkey for the gpu one.
+    pub fn load(name: &str, dir: &Path, backend: &Backend) -> Result<Self, String> {
+        let read = |file: &str| {
+            let path = dir.join(file);
+            std::fs::read(&path).map_err(|err| format!("{}: {err}", path.display()))
+        };
+        let r1cs = R1cs::parse(&read(R1CS_FILE)?).map_err(|err| format!("{name}: {err}"))?;
+        let zkey = dir.join(ZKEY_FILE);
+        let proving_key = match backend {
+            Backend::Cpu => Some(prove::parse_proving_key(&read(ARKWORKS_KEY_FILE)?).map_err(|err| format!("{name}: {err}"))?),
+            Backend::Gpu { .. } if !zkey.is_file() => return Err(format!("{}: not found", zkey.display())),
+            Backend::Gpu { .. } => None,
+        };
+        Ok(Circuit { name: name.to_string(), r1cs, proving_key, zkey })
+    }
+
+    // Rejects a witness that doesn't satisfy the circuit, before it takes a place in the queue.
+    pub fn check(&self, witness: &[u8]) -> Result<(), String> {
+        let witness = parse_witness(witness).map_err(|err| err.to_string())?;
+        prove::check_witness(&self.r1cs, &witness).map_err(|err| err.to_string())
+    }
+
+    // Proves a witness that passed `check`. `worker` picks the gpu backend's device.
+    pub fn prove(&self, backend: &Backend, worker: usize, witness: &[u8]) -> Result<WithdrawProof, String> {
+        match (backend, &self.proving_key) {
+            (Backend::Cpu, Some(proving_key)) => {
+                let witness = parse_witness(witness).map_err(|err| err.to_string())?;
+                prove::prove_checked(proving_key, &self.r1cs, &witness).map_err(|err| err.to_string())
+            }
+            (Backend::Cpu, None) => Err(format!("{} was loaded without an arkworks proving key", self.name)),
+            (Backend::Gpu { program, devices }, _) => {
+                let prover = GpuProver { program: program.clone(), zkey: self.zkey.clone(), device: devices.get(worker).copied() };
+                prover.prove(&self.r1cs, witness).map_err(|err| err.to_string())
+            }
+        }
+    }
+}
+
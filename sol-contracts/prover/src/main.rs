@@ -0,0 +1,167 @@
+// privax-prover: proves withdrawals for wallets that can't, such as low-end phones. A wallet
+// computes the witness itself (the witness calculator is cheap) and uploads it; the service
+// queues the job by priority and a pool of workers runs the Groth16 prover on the CPU or on
+// GPUs. The witness holds the note's secrets, so run it where wallets would trust it with them.
+mod api;
+mod backend;
+mod queue;
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use privax_client::logging;
+use tracing::{info, warn};
+
+use crate::backend::{Backend, Circuit};
+use crate::queue::Queue;
+
+const HTTP_THREADS: usize = 4;
+
+const USAGE: &str = "\
+Usage: privax-prover --circuit <name>=<dir> [options]
+
+Serves Groth16 proofs over HTTP from witnesses wallets upload: POST /jobs queues one, and
+GET /jobs/<id> returns its proof when done.
+
+Options:
+  --circuit <name>=<dir>  A circuit to prove, e.g. withdraw=build/withdraw; repeat for several.
+                          The directory holds circuit.r1cs, plus proving_key.ark (arkworks,
+                          uncompressed) for the cpu backend or circuit.zkey for the gpu one
+  --listen <addr>         Address to serve on (default 0.0.0.0:8080)
+  --backend <cpu|gpu>     Prove in-process with arkworks (default), or with --gpu-prover
+  --gpu-prover <program>  CUDA prover with rapidsnark's command line, run once per proof
+  --gpu-devices <list>    CUDA devices to prove on, one worker each, e.g. 0,1,3 (default 0)
+  --workers <n>           Proofs in parallel on the CPU (default 1: one proof already uses
+                          every core)
+  --queue-size <n>        Jobs that may wait before submissions are refused (default 256)
+  --retention <seconds>   How long finished jobs and their proofs are kept (default 600)
+
+PRIVAX_PROVER_API_KEYS lists the comma-separated keys (sent as X-Api-Key) allowed to queue
+high-priority jobs. Logs go to stderr; PRIVAX_LOG and PRIVAX_LOG_FORMAT set them up.
+";
+
+struct Options {
+    circuits: Vec<(String, PathBuf)>,
+    listen: String,
+    backend: String,
+    gpu_prover: Option<PathBuf>,
+    gpu_devices: Vec<usize>,
+    workers: usize,
+    queue_size: usize,
+    retention: Duration,
+}
+
+fn main() {
+    if let Err(err) = logging::init("info").and_then(|()| run(std::env::args().skip(1).collect())) {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut options = Options {
+        circuits: Vec::new(),
+        listen: "0.0.0.0:8080".to_string(),
+        backend: "cpu".to_string(),
+        gpu_prover: None,
+        gpu_devices: vec![0],
+        workers: 1,
+        queue_size: 256,
+        retention: Duration::from_secs(600),
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        let number = |value: String| value.parse::<usize>().ok().filter(|&n| n > 0).ok_or(format!("invalid {arg}"));
+        match arg.as_str() {
+            "--circuit" => {
+                let value = value()?;
+                let (name, dir) = value.split_once('=').ok_or("expected --circuit <name>=<dir>")?;
+                options.circuits.push((name.to_string(), PathBuf::from(dir)));
+            }
+            "--listen" => options.listen = value()?,
+            "--backend" => options.backend = value()?,
+            "--gpu-prover" => options.gpu_prover = Some(PathBuf::from(value()?)),
+            "--gpu-devices" => {
+                let devices = value()?.split(',').map(|device| device.trim().parse()).collect::<Result<Vec<usize>, _>>();
+                options.gpu_devices = devices.ok().filter(|devices| !devices.is_empty()).ok_or("invalid --gpu-devices")?;
+            }
+            "--workers" => options.workers = number(value()?)?,
+            "--queue-size" => options.queue_size = number(value()?)?,
+            "--retention" => options.retention = Duration::from_secs(number(value()?)? as u64),
+            "-h" | "--help" => {
+                print!("{USAGE}");
+                return Ok(());
+            }
+            _ => return Err(format!("unexpected argument {arg}\n\n{USAGE}")),
+        }
+    }
+    let backend = match (options.backend.as_str(), options.gpu_prover) {
+        ("cpu", _) => Backend::Cpu,
+        ("gpu", Some(program)) => {
+            options.workers = options.gpu_devices.len();
+            Backend::Gpu { program, devices: options.gpu_devices }
+        }
+        ("gpu", None) => return Err("--backend gpu needs --gpu-prover".to_string()),
+        (other, _) => return Err(format!("unknown backend {other:?} (expected cpu or gpu)")),
+    };
+    if options.circuits.is_empty() {
+        return Err(format!("no circuits to prove\n\n{USAGE}"));
+    }
+
+    let mut circuits = HashMap::new();
+    for (name, dir) in &options.circuits {
+        let started = Instant::now();
+        let circuit = Circuit::load(name, dir, &backend)?;
+        info!(circuit = name, wires = circuit.r1cs.wires, load_ms = started.elapsed().as_millis() as u64, "circuit loaded");
+        circuits.insert(name.clone(), Arc::new(circuit));
+    }
+    let api_keys: HashSet<String> = std::env::var("PRIVAX_PROVER_API_KEYS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect();
+    let queue = Arc::new(Queue::new(options.queue_size, options.retention));
+    let state = Arc::new(api::State { queue: queue.clone(), circuits, backend, workers: options.workers, api_keys });
+
+    for worker in 0..options.workers {
+        let state = state.clone();
+        thread::Builder::new()
+            .name(format!("privax-prover-{worker}"))
+            .spawn(move || loop {
+                let work = state.queue.take();
+                let circuit = &state.circuits[&work.circuit];
+                let started = Instant::now();
+                let result = circuit.prove(&state.backend, worker, &work.witness);
+                let elapsed_ms = started.elapsed().as_millis() as u64;
+                match &result {
+                    Ok(_) => info!(job = work.id, circuit = work.circuit, worker, elapsed_ms, "proved"),
+                    Err(err) => warn!(job = work.id, circuit = work.circuit, worker, error = err, "proving failed"),
+                }
+                state.queue.finish(&work.id, result);
+            })
+            .map_err(|err| format!("failed to start a worker: {err}"))?;
+    }
+
+    let server = Arc::new(tiny_http::Server::http(&options.listen).map_err(|err| format!("{}: {err}", options.listen))?);
+    info!(listen = options.listen, backend = state.backend.name(), workers = options.workers, "prover listening");
+    let handlers: Vec<_> = (0..HTTP_THREADS)
+        .map(|_| {
+            let (server, state) = (server.clone(), state.clone());
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    api::handle(&state, request);
+                }
+            })
+        })
+        .collect();
+    for handler in handlers {
+        let _ = handler.join();
+    }
+    Ok(())
+}
@@ -0,0 +1,228 @@
+// Proving jobs, in memory. Workers take the highest-priority job first and, within a priority,
+// the oldest. The priority is strict, so low-priority jobs wait for as long as higher ones keep
+// arriving. A job's witness holds the note's secrets, so it is dropped as soon as a worker takes
+// the job, and finished jobs are forgotten after the retention period.
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::Write as _;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use privax_mobile::prove::WithdrawProof;
+use rand::RngCore;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 3] = [Priority::Low, Priority::Normal, Priority::High];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+        }
+    }
+}
+
+impl std::str::FromStr for Priority {
+    type Err = String;
+
+    fn from_str(priority: &str) -> Result<Self, String> {
+        Priority::ALL
+            .into_iter()
+            .find(|candidate| candidate.name() == priority)
+            .ok_or(format!("unknown priority {priority:?} (expected low, normal or high)"))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Status {
+    Queued { position: usize }, // Jobs ahead of this one
+    Proving,
+    Done(WithdrawProof),
+    Failed(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct JobView {
+    pub id: String,
+    pub circuit: String,
+    pub priority: Priority,
+    pub status: Status,
+    pub proving_time: Option<Duration>,
+}
+
+// A job a worker has taken.
+pub struct Work {
+    pub id: String,
+    pub circuit: String,
+    pub witness: Vec<u8>,
+}
+
+struct Job {
+    circuit: String,
+    priority: Priority,
+    sequence: u64,
+    witness: Option<Vec<u8>>, // Until a worker takes the job
+    result: Option<Result<WithdrawProof, String>>,
+    started: Option<Instant>,
+    proving_time: Option<Duration>,
+    finished: Option<Instant>,
+}
+
+// Heap entry; the greatest is the next job to prove. Sequence numbers are unique, so the id
+// never decides the order.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Pending {
+    priority: Priority,
+    sequence: Reverse<u64>,
+    id: String,
+}
+
+#[derive(Default)]
+struct Inner {
+    pending: BinaryHeap<Pending>,
+    jobs: HashMap<String, Job>,
+    next_sequence: u64,
+    proved: u64,
+    failed: u64,
+    rejected: u64,
+    proving_time: Duration,
+}
+
+pub struct Queue {
+    inner: Mutex<Inner>,
+    work: Condvar,
+    capacity: usize,
+    retention: Duration,
+}
+
+impl Queue {
+    pub fn new(capacity: usize, retention: Duration) -> Self {
+        Queue { inner: Mutex::default(), work: Condvar::new(), capacity, retention }
+    }
+
+    // The new job's id, or None when `capacity` jobs are already waiting.
+    pub fn submit(&self, circuit: &str, witness: Vec<u8>, priority: Priority) -> Option<String> {
+        let mut inner = self.inner.lock().unwrap();
+        self.forget_expired(&mut inner);
+        if inner.pending.len() >= self.capacity {
+            inner.rejected += 1;
+            return None;
+        }
+        let mut bytes = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let id = bytes.iter().fold(String::new(), |mut id, byte| {
+            let _ = write!(id, "{byte:02x}");
+            id
+        });
+        let sequence = inner.next_sequence;
+        inner.next_sequence += 1;
+        inner.pending.push(Pending { priority, sequence: Reverse(sequence), id: id.clone() });
+        let job = Job {
+            circuit: circuit.to_string(),
+            priority,
+            sequence,
+            witness: Some(witness),
+            result: None,
+            started: None,
+            proving_time: None,
+            finished: None,
+        };
+        inner.jobs.insert(id.clone(), job);
+        self.work.notify_one();
+        Some(id)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.inner.lock().unwrap().pending.len() >= self.capacity
+    }
+
+    // Blocks until there is a job to prove.
+    pub fn take(&self) -> Work {
+        let mut inner = self.inner.lock().unwrap();
+        loop {
+            if let Some(pending) = inner.pending.pop() {
+                let job = inner.jobs.get_mut(&pending.id).expect("pending jobs are in the table");
+                job.started = Some(Instant::now());
+                let witness = job.witness.take().unwrap_or_default();
+                return Work { id: pending.id, circuit: job.circuit.clone(), witness };
+            }
+            inner = self.work.wait(inner).unwrap();
+        }
+    }
+
+    pub fn finish(&self, id: &str, result: Result<WithdrawProof, String>) {
+        let mut inner = self.inner.lock().unwrap();
+        match &result {
+            Ok(_) => inner.proved += 1,
+            Err(_) => inner.failed += 1,
+        }
+        let Some(job) = inner.jobs.get_mut(id) else { return };
+        let proving_time = job.started.map(|started| started.elapsed()).unwrap_or_default();
+        job.proving_time = Some(proving_time);
+        job.finished = Some(Instant::now());
+        job.result = Some(result);
+        inner.proving_time += proving_time;
+    }
+
+    pub fn get(&self, id: &str) -> Option<JobView> {
+        let mut inner = self.inner.lock().unwrap();
+        self.forget_expired(&mut inner);
+        let job = inner.jobs.get(id)?;
+        let status = match (&job.result, job.started) {
+            (Some(Ok(proof)), _) => Status::Done(proof.clone()),
+            (Some(Err(err)), _) => Status::Failed(err.clone()),
+            (None, Some(_)) => Status::Proving,
+            (None, None) => {
+                let key = (job.priority, Reverse(job.sequence));
+                let position = inner.pending.iter().filter(|other| (other.priority, other.sequence) > key).count();
+                Status::Queued { position }
+            }
+        };
+        Some(JobView { id: id.to_string(), circuit: job.circuit.clone(), priority: job.priority, status, proving_time: job.proving_time })
+    }
+
+    // Forgets a job and its proof. A job already being proved finishes, but its result is dropped.
+    pub fn delete(&self, id: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner.pending.retain(|pending| pending.id != id);
+        inner.jobs.remove(id).is_some()
+    }
+
+    pub fn render_metrics(&self, out: &mut String) {
+        let inner = self.inner.lock().unwrap();
+        let mut waiting: HashMap<Priority, usize> = HashMap::new();
+        for pending in &inner.pending {
+            *waiting.entry(pending.priority).or_default() += 1;
+        }
+        let proving = inner.jobs.values().filter(|job| job.started.is_some() && job.result.is_none()).count();
+
+        let name = "privax_prover_queue_depth";
+        let _ = writeln!(out, "# HELP {name} Jobs waiting for a worker, by priority\n# TYPE {name} gauge");
+        for priority in Priority::ALL {
+            let _ = writeln!(out, "{name}{{priority=\"{}\"}} {}", priority.name(), waiting.get(&priority).unwrap_or(&0));
+        }
+        let metrics: [(&str, &str, &str, String); 5] = [
+            ("privax_prover_jobs_proving", "gauge", "Jobs being proved", proving.to_string()),
+            ("privax_prover_proofs_total", "counter", "Jobs proved", inner.proved.to_string()),
+            ("privax_prover_failures_total", "counter", "Jobs that failed to prove", inner.failed.to_string()),
+            ("privax_prover_rejected_total", "counter", "Jobs turned away because the queue was full", inner.rejected.to_string()),
+            ("privax_prover_proving_seconds_total", "counter", "Time spent proving", inner.proving_time.as_secs_f64().to_string()),
+        ];
+        for (name, kind, help, value) in metrics {
+            let _ = write!(out, "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n");
+        }
+    }
+
+    fn forget_expired(&self, inner: &mut Inner) {
+        let retention = self.retention;
+        inner.jobs.retain(|_, job| job.finished.is_none_or(|finished| finished.elapsed() < retention));
+    }
+}
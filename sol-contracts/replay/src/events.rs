@@ -0,0 +1,91 @@
+// The events replay needs from a transaction's logs. Anchor logs each event as
+// "Program data: <base64>": sha256("event:<Name>")[..8], a version byte, then the Borsh fields.
+// The program's event structs keep their fields private, so the layouts are spelled out here.
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_sdk::hash::hashv;
+use solana_sdk::pubkey::Pubkey;
+
+const DISCRIMINATOR_LEN: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    // `leaf_count` leaves from `first_leaf_index` went into the tree, giving `new_root`. Their
+    // commitments are the next `leaf_count` Leaf events of the same transaction.
+    TreeUpdated { first_leaf_index: u64, leaf_count: u64, new_root: [u8; 32] },
+    // A deposited commitment, or a change note (which also logs its index).
+    Leaf { commitment: [u8; 32], leaf_index: Option<u64> },
+    NullifierSpent { nullifier_hash: [u8; 32] },
+}
+
+// Events `program_id` itself logged, in order. "Program data" lines belong to the innermost
+// program running, so events of programs Privax calls, or of programs calling it, are skipped.
+// Fails if the runtime truncated the logs, since events would be missing.
+pub fn from_logs(program_id: &Pubkey, logs: &[String]) -> Result<Vec<Event>, String> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for log in logs {
+        if log == "Log truncated" {
+            return Err("logs were truncated".to_string());
+        }
+        let Some(rest) = log.strip_prefix("Program ") else { continue };
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if stack.last() == Some(&program_id.as_str()) {
+                let bytes = STANDARD.decode(data).map_err(|err| format!("bad event data: {err}"))?;
+                events.extend(decode(&bytes));
+            }
+        } else if let Some((invoked, _depth)) = rest.split_once(" invoke [").filter(|(id, _)| id.parse::<Pubkey>().is_ok()) {
+            stack.push(invoked);
+        } else if let Some(status) = stack.last().and_then(|top| rest.strip_prefix(top)) {
+            if status == " success" || status.starts_with(" failed") {
+                stack.pop();
+            }
+        }
+    }
+    Ok(events)
+}
+
+fn decode(bytes: &[u8]) -> Option<Event> {
+    let (tag, body) = (bytes.get(..DISCRIMINATOR_LEN)?, bytes.get(DISCRIMINATOR_LEN..)?);
+    let mut reader = Reader(body);
+    reader.take(1)?; // Event version
+    let event = if tag == discriminator("event:MerkleRootUpdated") {
+        Event::TreeUpdated { first_leaf_index: reader.u64()?, leaf_count: reader.u64()?, new_root: reader.bytes32()? }
+    } else if tag == discriminator("event:DepositOccurred") {
+        reader.take(32 + 32 + 8)?; // user, token_address, amount
+        Event::Leaf { commitment: reader.bytes32()?, leaf_index: None }
+    } else if tag == discriminator("event:ChangeNoteCommitted") {
+        Event::Leaf { commitment: reader.bytes32()?, leaf_index: Some(reader.u64()?) }
+    } else if tag == discriminator("event:NullifierSpent") {
+        Event::NullifierSpent { nullifier_hash: reader.bytes32()? }
+    } else {
+        return None;
+    };
+    Some(event)
+}
+
+fn discriminator(preimage: &str) -> [u8; DISCRIMINATOR_LEN] {
+    hashv(&[preimage.as_bytes()]).to_bytes()[..DISCRIMINATOR_LEN].try_into().unwrap()
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn bytes32(&mut self) -> Option<[u8; 32]> {
+        self.take(32).map(|bytes| bytes.try_into().unwrap())
+    }
+}
@@ -0,0 +1,133 @@
+// Reading the program's transaction history and its live accounts over RPC.
+use std::collections::BTreeMap;
+
+use anchor_lang::Discriminator;
+use privax_client::instructions;
+use privax_client::rpc_pool::RpcPool;
+use privax_protocol::{MerkleTree, NullifierFilter, NullifierShard};
+use solana_account_decoder::UiAccountEncoding;
+use solana_rpc_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::events::{self, Event};
+use crate::state::Live;
+
+// getSignaturesForAddress's page size limit
+const PAGE_SIZE: usize = 1_000;
+
+pub struct Transaction {
+    pub signature: Signature,
+    pub slot: u64,
+    pub succeeded: bool,
+}
+
+// Transactions that invoked the program after `until` (all of them if None), oldest first.
+pub async fn transactions_since(pool: &RpcPool, until: Option<Signature>) -> Result<Vec<Transaction>, String> {
+    let mut transactions = Vec::new();
+    let mut before = None;
+    loop {
+        let page = pool
+            .call(|rpc| {
+                let config = GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(PAGE_SIZE),
+                    commitment: Some(rpc.commitment()),
+                };
+                rpc.get_signatures_for_address_with_config(&privax_protocol::ID, config)
+            })
+            .await
+            .map_err(|err| format!("getSignaturesForAddress: {err}"))?;
+        let full = page.len() == PAGE_SIZE;
+        for status in page {
+            let signature = status.signature.parse().map_err(|_| format!("bad signature {}", status.signature))?;
+            transactions.push(Transaction { signature, slot: status.slot, succeeded: status.err.is_none() });
+            before = Some(signature);
+        }
+        if !full {
+            break;
+        }
+    }
+    transactions.reverse();
+    Ok(transactions)
+}
+
+// The program's events in a successful transaction.
+pub async fn events(pool: &RpcPool, signature: &Signature) -> Result<Vec<Event>, String> {
+    let transaction = pool
+        .call(|rpc| {
+            let config = RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(rpc.commitment()),
+                max_supported_transaction_version: Some(0),
+            };
+            rpc.get_transaction_with_config(signature, config)
+        })
+        .await
+        .map_err(|err| format!("getTransaction {signature}: {err}"))?;
+    let logs: Option<Vec<String>> = transaction.transaction.meta.and_then(|meta| meta.log_messages.into());
+    let logs = logs.ok_or(format!("{signature}: the node returned no logs"))?;
+    events::from_logs(&privax_protocol::ID, &logs).map_err(|err| format!("{signature}: {err}"))
+}
+
+// The tree, filter and nullifier shards, and the slot range they were read over: the tree and
+// filter at the first slot, the shards at some slot up to the second.
+pub async fn live(pool: &RpcPool) -> Result<(Live, u64), String> {
+    let addresses = [instructions::merkle_tree_address(), instructions::nullifier_filter_address()];
+    let response = pool
+        .call(|rpc| rpc.get_multiple_accounts_with_commitment(&addresses, rpc.commitment()))
+        .await
+        .map_err(|err| format!("getMultipleAccounts: {err}"))?;
+    let slot = response.context.slot;
+    let [Some(tree), Some(filter)] = <[_; 2]>::try_from(response.value).map_err(|_| "getMultipleAccounts: wrong account count")? else {
+        return Err("the pool isn't initialized".to_string());
+    };
+    let tree: MerkleTree = zero_copy(&tree.data, MerkleTree::DISCRIMINATOR, MerkleTree::SPACE).ok_or("bad merkle tree account")?;
+    let filter: NullifierFilter =
+        zero_copy(&filter.data, NullifierFilter::DISCRIMINATOR, NullifierFilter::SPACE).ok_or("bad nullifier filter account")?;
+
+    let accounts = pool
+        .call(|rpc| {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &NullifierShard::DISCRIMINATOR))]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(rpc.commitment()),
+                    min_context_slot: Some(slot),
+                    ..RpcAccountInfoConfig::default()
+                },
+                with_context: None,
+            };
+            rpc.get_program_accounts_with_config(&privax_protocol::ID, config)
+        })
+        .await
+        .map_err(|err| format!("getProgramAccounts: {err}"))?;
+    let until_slot = pool.call(|rpc| rpc.get_slot()).await.map_err(|err| format!("getSlot: {err}"))?;
+
+    let mut shards = BTreeMap::new();
+    for (address, account) in accounts {
+        let (prefix, entries) = shard_entries(&account.data).ok_or(format!("bad nullifier shard {address}"))?;
+        shards.insert(prefix, entries);
+    }
+    Ok((Live { slot, tree, filter, shards }, until_slot))
+}
+
+// A zero-copy account's struct, checked against its discriminator. Account data isn't
+// guaranteed to be aligned for the struct, so it is copied out.
+fn zero_copy<T: bytemuck::Pod>(data: &[u8], discriminator: [u8; 8], space: usize) -> Option<T> {
+    if data.len() < space || data[..8] != discriminator {
+        return None;
+    }
+    Some(bytemuck::pod_read_unaligned(&data[8..space]))
+}
+
+// A shard's prefix and its `count` entries, which follow the Borsh header.
+fn shard_entries(data: &[u8]) -> Option<([u8; 2], Vec<[u8; 32]>)> {
+    let prefix = data.get(8..10)?.try_into().ok()?;
+    let count = u32::from_le_bytes(data.get(10..14)?.try_into().ok()?) as usize;
+    let entries = data.get(NullifierShard::SPACE..NullifierShard::SPACE + count * NullifierShard::ENTRY_SIZE)?;
+    Some((prefix, entries.chunks_exact(NullifierShard::ENTRY_SIZE).map(|entry| entry.try_into().unwrap()).collect()))
+}
@@ -0,0 +1,186 @@
+// privax-replay: rebuilds the merkle tree and nullifier set from the program's transaction
+// history alone and diffs them against the live accounts. --output writes the rebuilt leaves
+// and nullifiers, e.g. to restore an indexer from; --watch keeps following new transactions
+// and re-checks, as a consistency monitor. The RPC node must have the program's full history.
+mod events;
+mod history;
+mod state;
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anchor_lang::solana_program::program_stubs::{self, SyscallStubs};
+use privax_client::config::{self, Cluster};
+use privax_client::logging;
+use privax_client::rpc_pool::{RetryPolicy, RpcPool};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+use tracing::{debug, warn};
+
+use crate::history::Transaction;
+use crate::state::{hex, Replay};
+
+// Attempts at reading the accounts without a transaction landing in between
+const SNAPSHOT_ATTEMPTS: usize = 5;
+
+const USAGE: &str = "\
+Usage: privax-replay [options]
+
+Replays every Privax transaction's events to rebuild the merkle tree and nullifier set, and
+compares them with the live merkle tree, nullifier filter and nullifier shard accounts.
+Exits with status 1 if they differ and 2 on errors.
+
+Options:
+  --cluster <name>     localnet, devnet or mainnet, for its RPC endpoints (default
+                       PRIVAX_CLUSTER, else the config's default)
+  --url <rpc-url>      RPC endpoint with full transaction history, instead of the cluster's;
+                       repeat to fail over between several
+  --output <file>      Write the rebuilt leaves and nullifiers as JSON, after every check
+  --watch <seconds>    Keep following new transactions and re-check at this interval
+
+Logs go to stderr; PRIVAX_LOG sets their levels (default info) and PRIVAX_LOG_FORMAT=json
+writes them as JSON lines.
+";
+
+#[derive(Default)]
+struct Options {
+    cluster: Option<Cluster>,
+    urls: Vec<String>,
+    output: Option<String>,
+    watch: Option<Duration>,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    program_stubs::set_syscall_stubs(Box::new(QuietStubs));
+    if let Err(err) = logging::init("info") {
+        eprintln!("error: {err}");
+        std::process::exit(2);
+    }
+    match run(std::env::args().skip(1).collect()).await {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(2);
+        }
+    }
+}
+
+// Whether the last check found the accounts consistent with the history.
+async fn run(args: Vec<String>) -> Result<bool, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "-c" | "--cluster" => options.cluster = Some(value()?.parse().map_err(|err: config::ConfigError| err.to_string())?),
+            "-u" | "--url" => options.urls.push(value()?),
+            "--output" => options.output = Some(value()?),
+            "--watch" => {
+                let seconds: u64 = value()?.parse().map_err(|_| "invalid --watch")?;
+                options.watch = Some(Duration::from_secs(seconds.max(1)));
+            }
+            "-h" | "--help" => {
+                print!("{USAGE}");
+                return Ok(true);
+            }
+            _ => return Err(format!("unexpected argument {arg}\n\n{USAGE}")),
+        }
+    }
+    let profile = config::load_profile(options.cluster).map_err(|err| err.to_string())?;
+    if profile.program_id != privax_protocol::ID {
+        let (cluster, program_id) = (profile.cluster, profile.program_id);
+        return Err(format!("the {cluster} profile's program {program_id} isn't the one this build replays ({})", privax_protocol::ID));
+    }
+    if options.urls.is_empty() {
+        options.urls = profile.rpc_urls;
+    }
+
+    // Finalized, so the history never loses a transaction it has already been replayed from.
+    let pool = RpcPool::new(&options.urls, CommitmentConfig::finalized(), RetryPolicy::default());
+    let mut follower = Follower { pool, replay: Replay::default(), queue: VecDeque::new(), until: None };
+    loop {
+        let (slot, differences) = follower.check().await?;
+        let replay = &follower.replay;
+        let summary = format!(
+            "slot {slot}: {} transactions, {} leaves (root {}), {} nullifiers",
+            replay.transactions,
+            replay.leaves.len(),
+            hex(&replay.tree.root()),
+            replay.nullifier_count()
+        );
+        if differences.is_empty() {
+            println!("{summary}: consistent");
+        } else {
+            println!("{summary}: {} differences", differences.len());
+            for difference in &differences {
+                println!("  {difference}");
+            }
+        }
+        if let Some(path) = &options.output {
+            let json = serde_json::to_string_pretty(&replay.to_json(slot)).unwrap();
+            std::fs::write(path, json).map_err(|err| format!("{path}: {err}"))?;
+        }
+        let Some(interval) = options.watch else {
+            return Ok(differences.is_empty());
+        };
+        tokio::time::sleep(interval).await;
+    }
+}
+
+struct Follower {
+    pool: RpcPool,
+    replay: Replay,
+    queue: VecDeque<Transaction>, // Successful transactions not replayed yet, oldest first
+    until: Option<Signature>,     // Newest transaction listed so far
+}
+
+impl Follower {
+    // Reads the live accounts, replays the history up to the slot they were read at, and
+    // returns that slot and the differences.
+    async fn check(&mut self) -> Result<(u64, Vec<String>), String> {
+        let mut attempt = 1;
+        let live = loop {
+            let (live, until_slot) = history::live(&self.pool).await?;
+            self.list_new_transactions().await?;
+            // The shards are read after the tree and filter, so a transaction in between
+            // would show in one and not the other.
+            let between = self.queue.iter().any(|transaction| transaction.slot > live.slot && transaction.slot <= until_slot);
+            if !between {
+                break live;
+            }
+            if attempt == SNAPSHOT_ATTEMPTS {
+                warn!(slot = live.slot, attempts = attempt, "a transaction kept landing between account reads; comparing anyway");
+                break live;
+            }
+            debug!(attempt, tree_slot = live.slot, shards_slot = until_slot, "a transaction landed between account reads, retrying");
+            attempt += 1;
+        };
+        while let Some(transaction) = self.queue.front().filter(|transaction| transaction.slot <= live.slot) {
+            let events = history::events(&self.pool, &transaction.signature).await?;
+            self.replay.apply(&transaction.signature.to_string(), events)?;
+            self.queue.pop_front();
+        }
+        Ok((live.slot, self.replay.diff(&live)))
+    }
+
+    async fn list_new_transactions(&mut self) -> Result<(), String> {
+        let transactions = history::transactions_since(&self.pool, self.until).await?;
+        if let Some(newest) = transactions.last() {
+            debug!(count = transactions.len(), newest = %newest.signature, "listed new transactions");
+            self.until = Some(newest.signature);
+        }
+        self.queue.extend(transactions.into_iter().filter(|transaction| transaction.succeeded));
+        Ok(())
+    }
+}
+
+// Replayed inserts go through MerkleTree::append_subtree, which emits MerkleRootUpdated; the
+// default off-chain syscall stubs would print each one.
+struct QuietStubs;
+
+impl SyscallStubs for QuietStubs {
+    fn sol_log(&self, _message: &str) {}
+    fn sol_log_data(&self, _data: &[&[u8]]) {}
+}
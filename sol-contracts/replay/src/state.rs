@@ -0,0 +1,219 @@
+// The tree and nullifier set rebuilt from events, and how they differ from the live accounts.
+//
+// Inserts go through the program's own MerkleTree, so the rebuilt account has the same
+// filled subtrees and root history as the real one would after the same inserts; likewise
+// spent nullifiers go into a NullifierFilter. Inserts are applied in leaf order rather than
+// in the order their transactions were listed, which RPC nodes don't guarantee within a slot.
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use bytemuck::Zeroable;
+use privax_protocol::{MerkleTree, NullifierFilter, NullifierShard, MERKLE_TREE_DEPTH};
+use serde_json::{json, Value};
+
+use crate::events::Event;
+
+pub struct Replay {
+    pub tree: MerkleTree,
+    pub leaves: Vec<[u8; 32]>,
+    pub filter: NullifierFilter,
+    pub nullifiers: BTreeMap<[u8; 2], Vec<[u8; 32]>>, // By shard prefix, in spending order
+    spent: HashSet<[u8; 32]>,
+    pending: BTreeMap<u64, Insert>, // Complete inserts waiting for an earlier one, by first leaf
+    pub transactions: u64,
+}
+
+// An insert's leaves as its transaction logged them.
+struct Insert {
+    leaves: Vec<[u8; 32]>,
+    new_root: [u8; 32],
+    signature: String,
+}
+
+// The accounts a replay is checked against, read at `slot`.
+pub struct Live {
+    pub slot: u64,
+    pub tree: MerkleTree,
+    pub filter: NullifierFilter,
+    pub shards: BTreeMap<[u8; 2], Vec<[u8; 32]>>,
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        let mut tree = MerkleTree::zeroed();
+        tree.init(0);
+        Self {
+            tree,
+            leaves: Vec::new(),
+            filter: NullifierFilter::zeroed(),
+            nullifiers: BTreeMap::new(),
+            spent: HashSet::new(),
+            pending: BTreeMap::new(),
+            transactions: 0,
+        }
+    }
+}
+
+impl Replay {
+    pub fn nullifier_count(&self) -> usize {
+        self.spent.len()
+    }
+
+    // Applies one successful transaction's events. Errors mean the history contradicts
+    // itself or the program: a leaf without its tree update, a root the leaves don't give,
+    // a nullifier spent twice.
+    pub fn apply(&mut self, signature: &str, events: Vec<Event>) -> Result<(), String> {
+        let mut current: Option<(u64, u64, Insert)> = None; // (first leaf, leaf count, leaves so far)
+        for event in events {
+            match event {
+                Event::TreeUpdated { first_leaf_index, leaf_count, new_root } => {
+                    if current.is_some() {
+                        return Err(format!("{signature}: tree update before the previous one's leaves"));
+                    }
+                    let insert = Insert { leaves: Vec::new(), new_root, signature: signature.to_string() };
+                    current = Some((first_leaf_index, leaf_count, insert));
+                }
+                Event::Leaf { commitment, leaf_index } => {
+                    let Some((first, count, insert)) = current.as_mut() else {
+                        return Err(format!("{signature}: commitment {} without a tree update", hex(&commitment)));
+                    };
+                    let index = *first + insert.leaves.len() as u64;
+                    if leaf_index.is_some_and(|logged| logged != index) {
+                        return Err(format!("{signature}: change note logged at leaf {leaf_index:?}, expected {index}"));
+                    }
+                    insert.leaves.push(commitment);
+                    if insert.leaves.len() as u64 == *count {
+                        let (first, _, insert) = current.take().unwrap();
+                        if first < self.tree.next_index || self.pending.insert(first, insert).is_some() {
+                            return Err(format!("{signature}: leaf {first} inserted twice"));
+                        }
+                    }
+                }
+                Event::NullifierSpent { nullifier_hash } => {
+                    if !self.spent.insert(nullifier_hash) {
+                        return Err(format!("{signature}: nullifier {} spent twice", hex(&nullifier_hash)));
+                    }
+                    self.filter.insert(&nullifier_hash);
+                    self.nullifiers.entry(NullifierShard::prefix_of(&nullifier_hash)).or_default().push(nullifier_hash);
+                }
+            }
+        }
+        if let Some((first, count, insert)) = current {
+            return Err(format!("{signature}: insert at leaf {first} logged {} of {count} leaves", insert.leaves.len()));
+        }
+        self.transactions += 1;
+        self.apply_pending()
+    }
+
+    fn apply_pending(&mut self) -> Result<(), String> {
+        while let Some(insert) = self.pending.remove(&self.tree.next_index) {
+            let first = self.tree.next_index;
+            let width = insert.leaves.len();
+            if !width.is_power_of_two() {
+                return Err(format!("{}: insert at leaf {first} has {width} leaves", insert.signature));
+            }
+            let subtree_root = MerkleTree::subtree_root(&insert.leaves);
+            self.tree
+                .append_subtree(subtree_root, width.trailing_zeros() as usize)
+                .map_err(|err| format!("{}: insert at leaf {first}: {err}", insert.signature))?;
+            if self.tree.root() != insert.new_root {
+                return Err(format!(
+                    "{}: leaves from {first} give root {}, the program logged {}",
+                    insert.signature,
+                    hex(&self.tree.root()),
+                    hex(&insert.new_root)
+                ));
+            }
+            self.leaves.extend(insert.leaves);
+        }
+        Ok(())
+    }
+
+    // Every way `live` differs from the replay; empty if they agree.
+    pub fn diff(&self, live: &Live) -> Vec<String> {
+        let mut differences = Vec::new();
+        if let Some((&first, insert)) = self.pending.first_key_value() {
+            differences.push(format!(
+                "no history for leaves {}..{first}, inserted before {}",
+                self.tree.next_index, insert.signature
+            ));
+        }
+
+        let (tree, expected) = (&live.tree, &self.tree);
+        if tree.next_index != expected.next_index {
+            differences.push(format!("tree next_index is {}, replay has {}", tree.next_index, expected.next_index));
+        }
+        if tree.root() != expected.root() {
+            differences.push(format!("tree root is {}, replay has {}", hex(&tree.root()), hex(&expected.root())));
+        }
+        if tree.current_root_index != expected.current_root_index {
+            differences.push(format!(
+                "tree current_root_index is {}, replay has {}",
+                tree.current_root_index, expected.current_root_index
+            ));
+        }
+        for level in 0..MERKLE_TREE_DEPTH {
+            if tree.filled_subtrees[level] != expected.filled_subtrees[level] {
+                differences.push(format!("tree filled_subtrees[{level}] differs"));
+            }
+            if tree.zeros[level] != expected.zeros[level] {
+                differences.push(format!("tree zeros[{level}] differs"));
+            }
+        }
+        for (index, (root, replayed)) in tree.roots.iter().zip(&expected.roots).enumerate() {
+            if root != replayed {
+                differences.push(format!("tree roots[{index}] is {}, replay has {}", hex(root), hex(replayed)));
+            }
+        }
+
+        if live.filter.inserted != self.filter.inserted {
+            differences.push(format!(
+                "nullifier filter counts {} inserts, replay has {}",
+                live.filter.inserted, self.filter.inserted
+            ));
+        }
+        let differing_bits: u32 =
+            live.filter.bits.iter().zip(&self.filter.bits).map(|(bits, replayed)| (bits ^ replayed).count_ones()).sum();
+        if differing_bits > 0 {
+            differences.push(format!("nullifier filter differs in {differing_bits} bits"));
+        }
+
+        let no_entries = Vec::new();
+        let prefixes: BTreeSet<_> = live.shards.keys().chain(self.nullifiers.keys()).collect();
+        for prefix in prefixes {
+            let entries = live.shards.get(prefix).unwrap_or(&no_entries);
+            let replayed = self.nullifiers.get(prefix).unwrap_or(&no_entries);
+            let shard = hex(prefix);
+            if !live.shards.contains_key(prefix) {
+                differences.push(format!("nullifier shard {shard} doesn't exist, replay has {} nullifiers for it", replayed.len()));
+                continue;
+            }
+            let stored: HashSet<_> = entries.iter().collect();
+            if stored.len() != entries.len() {
+                differences.push(format!("nullifier shard {shard} holds a nullifier more than once"));
+            }
+            for nullifier in replayed.iter().filter(|nullifier| !stored.contains(nullifier)) {
+                differences.push(format!("nullifier {} is missing from shard {shard}", hex(nullifier)));
+            }
+            for nullifier in entries.iter().filter(|nullifier| !self.spent.contains(*nullifier)) {
+                differences.push(format!("nullifier {} in shard {shard} was never spent", hex(nullifier)));
+            }
+        }
+        differences
+    }
+
+    // The rebuilt leaves and nullifiers, enough to restore an indexer from.
+    pub fn to_json(&self, slot: u64) -> Value {
+        json!({
+            "slot": slot,
+            "transactions": self.transactions,
+            "next_index": self.tree.next_index,
+            "root": hex(&self.tree.root()),
+            "leaves": self.leaves.iter().map(|leaf| hex(leaf)).collect::<Vec<_>>(),
+            "nullifiers": self.nullifiers.values().flatten().map(|nullifier| hex(nullifier)).collect::<Vec<_>>(),
+        })
+    }
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
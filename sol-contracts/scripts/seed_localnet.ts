@@ -0,0 +1,215 @@
+// Seeds a fresh localnet (see localnet/ at the repo root) with a ready-to-use pool:
+//
+//   ANCHOR_PROVIDER_URL=http://127.0.0.1:8899 ANCHOR_WALLET=admin.json yarn seed:localnet
+//
+// Creates the pool mint, initializes the program with the mock verifier the validator deploys
+// as its verifier program, registers a Groth16 verifying key, whitelists the relayer, funds a
+// test user and makes a few deposits. Everything an
+// integrator needs afterwards (addresses, the user's keypair, the deposited commitments) is
+// written to $SEED_OUTPUT (default localnet.json). A deployment holds a single pool, so
+// re-running against a seeded validator only tops up the user and adds deposits.
+//
+// On-chain proof verification is still a placeholder that checks encodings only, so by default
+// the verifying key registered here is placeholder bytes of the right shape. VK_PAYLOAD names a
+// payload from the circuits' setup (circuits/artifacts/<circuit>/vk_payload.json) to register
+// instead.
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ComputeBudgetProgram, Keypair, PublicKey, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createMint, getOrCreateAssociatedTokenAccount, mintTo } from "@solana/spl-token";
+import { createHash, randomBytes } from "crypto";
+import * as fs from "fs";
+import * as path from "path";
+import { IDL } from "../app/src/privax_protocol";
+import { MOCK_VERIFIER_PROGRAM_ID } from "../app/src/mockVerifier";
+import { G1_SIZE, VK_FIXED_SIZE } from "../app/src/proofFormat";
+
+const PROGRAM_ID = new PublicKey("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+const DECIMALS = 9;
+const CIRCUIT_VERSION = 1;
+const AMOUNT_RANGE_BITS = 64;
+// Eight public inputs: the verifying key carries one IC point per input plus one
+const PLACEHOLDER_VK_DATA = Buffer.alloc(VK_FIXED_SIZE + 9 * G1_SIZE, 1);
+const USER_BALANCE = 1_000_000 * 10 ** DECIMALS;
+const DEPOSIT_AMOUNTS = [1, 5, 10, 50].map((tokens) => tokens * 10 ** DECIMALS);
+// Tree inserts hash with Poseidon at every level, past the default 200k compute units
+const treeInsertBudget = () => ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 });
+
+const env = (name: string, fallback?: string) => {
+  const value = process.env[name] ?? fallback;
+  if (value === undefined) throw new Error(`${name} is not set`);
+  return value;
+};
+
+const loadKeypair = (file: string) => Keypair.fromSecretKey(Uint8Array.from(JSON.parse(fs.readFileSync(file, "utf8"))));
+
+// Loads the keypair at file, generating and saving one on first use
+const keypairFile = (file: string) => {
+  if (fs.existsSync(file)) return loadKeypair(file);
+  const keypair = Keypair.generate();
+  fs.mkdirSync(path.dirname(file), { recursive: true });
+  fs.writeFileSync(file, JSON.stringify(Array.from(keypair.secretKey)));
+  return keypair;
+};
+
+async function main() {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const connection = provider.connection;
+  // @ts-ignore - the generated IDL isn't typed against Program's generics
+  const program = new Program(IDL, PROGRAM_ID, provider) as Program;
+  const admin = (provider.wallet as anchor.Wallet).payer;
+
+  const outputPath = env("SEED_OUTPUT", "localnet.json");
+  const keysDir = env("SEED_KEYS_DIR", path.join(path.dirname(outputPath), "keys"));
+  const user = keypairFile(path.join(keysDir, "user.json"));
+  const mintKeypair = keypairFile(path.join(keysDir, "mint.json"));
+  const relayer = loadKeypair(env("RELAYER_KEYPAIR"));
+  const relayerUrl = env("RELAYER_URL", "http://relayer:8000");
+  const feeSchedule = {
+    feeBps: Number(env("RELAYER_FEE_BPS", "50")),
+    minFee: new anchor.BN(env("RELAYER_MIN_FEE", "0")),
+  };
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, PROGRAM_ID)[0];
+  const programStatePDA = pda(Buffer.from("program_state"));
+  const feeConfigPDA = pda(Buffer.from("fee_config"), programStatePDA.toBuffer());
+  const nullifierFilterPDA = pda(Buffer.from("nullifier_filter"), programStatePDA.toBuffer());
+  const merkleTreePDA = pda(Buffer.from("merkle_tree"), programStatePDA.toBuffer());
+  const auditLogPDA = pda(Buffer.from("audit_log"), programStatePDA.toBuffer());
+  const vaultPDA = pda(Buffer.from("program_token_vault"), programStatePDA.toBuffer());
+  const verifyingKeyPDA = pda(
+    Buffer.from("verifying_key"),
+    programStatePDA.toBuffer(),
+    new anchor.BN(CIRCUIT_VERSION).toArrayLike(Buffer, "le", 2)
+  );
+  const relayerInfoPDA = pda(Buffer.from("relayer"), relayer.publicKey.toBuffer());
+
+  for (const keypair of [admin, user, relayer]) {
+    if ((await connection.getBalance(keypair.publicKey)) < LAMPORTS_PER_SOL) {
+      await connection.confirmTransaction(await connection.requestAirdrop(keypair.publicKey, 100 * LAMPORTS_PER_SOL));
+    }
+  }
+
+  if ((await connection.getAccountInfo(mintKeypair.publicKey)) === null) {
+    await createMint(connection, admin, admin.publicKey, null, DECIMALS, mintKeypair);
+    console.log(`Created mint ${mintKeypair.publicKey.toBase58()}`);
+  }
+  const tokenMint = mintKeypair.publicKey;
+
+  const state = await program.account.programState.fetchNullable(programStatePDA);
+  if (state === null) {
+    await program.methods
+      .initialize(tokenMint, MOCK_VERIFIER_PROGRAM_ID)
+      .accounts({
+        programState: programStatePDA,
+        feeConfig: feeConfigPDA,
+        nullifierFilter: nullifierFilterPDA,
+        merkleTree: merkleTreePDA,
+        auditLog: auditLogPDA,
+        admin: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions([treeInsertBudget()])
+      .rpc();
+    console.log(`Initialized the pool for ${tokenMint.toBase58()}`);
+  } else if (!(state.tokenMint as PublicKey).equals(tokenMint)) {
+    throw new Error(`The program is already initialized for mint ${(state.tokenMint as PublicKey).toBase58()}`);
+  }
+
+  if ((await connection.getAccountInfo(verifyingKeyPDA)) === null) {
+    const payloadFile = process.env.VK_PAYLOAD;
+    const vkData = payloadFile
+      ? Buffer.from(JSON.parse(fs.readFileSync(payloadFile, "utf8")).vkData, "hex")
+      : PLACEHOLDER_VK_DATA;
+    await program.methods
+      .registerVerifyingKey(CIRCUIT_VERSION, { groth16: {} }, AMOUNT_RANGE_BITS, vkData.length, vkData)
+      .accounts({
+        programState: programStatePDA,
+        auditLog: auditLogPDA,
+        verifyingKey: verifyingKeyPDA,
+        admin: admin.publicKey,
+        payer: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    console.log(`Registered ${payloadFile ?? "a placeholder verifying key"} for circuit version ${CIRCUIT_VERSION}`);
+  }
+
+  if ((await connection.getAccountInfo(relayerInfoPDA)) === null) {
+    const urlHash = createHash("sha256").update(relayerUrl).digest();
+    await program.methods
+      .addRelayer(relayer.publicKey, Array.from(urlHash), feeSchedule, null)
+      .accounts({
+        programState: programStatePDA,
+        auditLog: auditLogPDA,
+        relayerInfo: relayerInfoPDA,
+        admin: admin.publicKey,
+        payer: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    console.log(`Whitelisted relayer ${relayer.publicKey.toBase58()} at ${relayerUrl}`);
+  }
+
+  const userTokenAccount = await getOrCreateAssociatedTokenAccount(connection, user, tokenMint, user.publicKey);
+  await mintTo(connection, admin, tokenMint, userTokenAccount.address, admin, USER_BALANCE);
+
+  const deposits = [];
+  for (const amount of DEPOSIT_AMOUNTS) {
+    const commitment = randomBytes(32);
+    commitment[0] &= 0x1f; // Keeps it below the scalar field modulus, as deposits require
+    const signature = await program.methods
+      .deposit(new anchor.BN(amount), Array.from(commitment))
+      .accounts({
+        programState: programStatePDA,
+        feeConfig: feeConfigPDA,
+        merkleTree: merkleTreePDA,
+        commitmentShard: PublicKey.findProgramAddressSync(
+          [Buffer.from("commitment_shard"), programStatePDA.toBuffer(), commitment.subarray(0, 2)],
+          program.programId
+        )[0],
+        referrerAccount: null,
+        depositReceipt: null,
+        user: user.publicKey,
+        userTokenAccount: userTokenAccount.address,
+        tokenMint: tokenMint,
+        programTokenVault: vaultPDA,
+        programTokenVaultAuthority: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .preInstructions([treeInsertBudget()])
+      .signers([user])
+      .rpc();
+    deposits.push({ amount, commitment: commitment.toString("hex"), signature });
+  }
+  console.log(`Made ${deposits.length} deposits`);
+
+  // A fresh pool (the localnet validator resets its ledger) starts a fresh deposit list
+  const previous = state !== null && fs.existsSync(outputPath) ? JSON.parse(fs.readFileSync(outputPath, "utf8")) : { deposits: [] };
+  const summary = {
+    rpcUrl: connection.rpcEndpoint,
+    programId: PROGRAM_ID.toBase58(),
+    programState: programStatePDA.toBase58(),
+    merkleTree: merkleTreePDA.toBase58(),
+    vault: vaultPDA.toBase58(),
+    mint: tokenMint.toBase58(),
+    decimals: DECIMALS,
+    circuitVersion: CIRCUIT_VERSION,
+    admin: admin.publicKey.toBase58(),
+    relayer: { address: relayer.publicKey.toBase58(), url: relayerUrl, ...feeSchedule, minFee: feeSchedule.minFee.toString() },
+    user: { address: user.publicKey.toBase58(), keypair: path.join(keysDir, "user.json"), tokenAccount: userTokenAccount.address.toBase58() },
+    // Random commitments with no notes behind them: they fill the tree and exercise the
+    // indexer, but can't be withdrawn.
+    deposits: [...previous.deposits, ...deposits],
+  };
+  fs.writeFileSync(outputPath, JSON.stringify(summary, null, 2) + "\n");
+  console.log(`Wrote ${outputPath}`);
+}
+
+main().catch((err) => {
+  console.error(err);
+  process.exit(1);
+});
@@ -0,0 +1,151 @@
+// Fixed inputs and the outputs every Privax implementation must produce from them, so the
+// program, the SDKs and the circuits can't drift apart without a test failing. The vectors
+// live in vectors.json next to this crate; Rust tests read them through `vectors()` and the
+// circuits' tests read the file directly.
+//
+// Field elements and hashes are 32 big-endian bytes in hex (decimal strings where the
+// circuit's witness takes them), and u64s are decimal strings so JavaScript reads them
+// without losing precision. The expected values don't change unless the protocol does: a
+// change to one is a breaking change for notes, proofs or deployed pools.
+use std::sync::OnceLock;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+pub const VECTORS_JSON: &str = include_str!("../vectors.json");
+
+pub fn vectors() -> &'static Vectors {
+    static VECTORS: OnceLock<Vectors> = OnceLock::new();
+    VECTORS.get_or_init(|| serde_json::from_str(VECTORS_JSON).expect("vectors.json matches Vectors"))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Vectors {
+    pub version: u32,
+    pub program_id: String, // external nullifiers are bound to it
+    pub poseidon: Vec<PoseidonVector>,
+    pub notes: Vec<NoteVector>,
+    pub poseidon_tree: PoseidonTreeVector,
+    pub external_nullifiers: Vec<ExternalNullifierVector>,
+    pub withdrawals: Vec<WithdrawalVector>,
+}
+
+// circomlib Poseidon over BN254, inputs and output as decimal field elements.
+#[derive(Deserialize, Debug)]
+pub struct PoseidonVector {
+    pub inputs: Vec<String>,
+    pub output: String,
+}
+
+// commitment = Poseidon(amount, secret, nullifierSecret), nullifierHash = Poseidon(nullifierSecret, 1).
+#[derive(Deserialize, Debug)]
+pub struct NoteVector {
+    #[serde(deserialize_with = "decimal_u64")]
+    pub amount: u64,
+    #[serde(deserialize_with = "hex_bytes")]
+    pub secret: Vec<u8>,
+    #[serde(deserialize_with = "hex_bytes")]
+    pub nullifier_secret: Vec<u8>,
+    pub backup: String,
+    #[serde(deserialize_with = "hex_32")]
+    pub commitment: [u8; 32],
+    #[serde(deserialize_with = "hex_32")]
+    pub nullifier_hash: [u8; 32],
+}
+
+// The commitment tree, as the circuits and the program (privax_protocol::hash_pair) build it:
+// Poseidon(left, right) over zero leaves. Holds the commitments of the first three notes.
+#[derive(Deserialize, Debug)]
+pub struct PoseidonTreeVector {
+    pub depth: usize,
+    #[serde(deserialize_with = "hex_32s")]
+    pub zeros: Vec<[u8; 32]>, // zeros[i]: root of an empty subtree of height i, up to `depth`
+    #[serde(deserialize_with = "hex_32s")]
+    pub leaves: Vec<[u8; 32]>,
+    #[serde(deserialize_with = "hex_32")]
+    pub root: [u8; 32],
+    #[serde(deserialize_with = "hex_32s")]
+    pub roots: Vec<[u8; 32]>, // roots[i]: root after the first i inserts
+    pub paths: Vec<PathVector>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PathVector {
+    pub leaf_index: u64,
+    #[serde(deserialize_with = "hex_32s")]
+    pub elements: Vec<[u8; 32]>, // Sibling at each level, leaf level first
+    pub indices: Vec<bool>,      // Whether the path node is the right child at each level
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ExternalNullifierVector {
+    pub mint: String,
+    #[serde(deserialize_with = "decimal_u64")]
+    pub denomination: u64,
+    pub circuit_version: u16,
+    #[serde(deserialize_with = "decimal_u64")]
+    pub value: u64,
+}
+
+// A withdrawal of `input_notes` (indices into `notes`, spent against the Poseidon tree's
+// root) and the public inputs withdraw takes for it.
+#[derive(Deserialize, Debug)]
+pub struct WithdrawalVector {
+    pub input_notes: Vec<usize>,
+    pub change_note: Option<usize>,
+    pub recipient: String,
+    pub mint: String,
+    pub circuit_version: u16,
+    #[serde(deserialize_with = "decimal_u64")]
+    pub proof_slot: u64,
+    #[serde(deserialize_with = "decimal_u64")]
+    pub amount: u64,
+    #[serde(deserialize_with = "decimal_u64s")]
+    pub public_inputs: Vec<u64>,
+    #[serde(deserialize_with = "hex_bytes")]
+    pub serialized_public_inputs: Vec<u8>, // Borsh, as in withdraw's instruction data
+    #[serde(deserialize_with = "hex_2s")]
+    pub nullifier_prefixes: Vec<[u8; 2]>, // Nullifier shard of each input note, in order
+    pub witness_input: Option<serde_json::Value>, // The circuit's input JSON, for single-note withdrawals
+}
+
+pub fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return None;
+    }
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok()).collect()
+}
+
+fn hex_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_hex(&s).ok_or_else(|| D::Error::custom(format!("invalid hex {s:?}")))
+}
+
+fn hex_array<const N: usize, E: Error>(s: &str) -> Result<[u8; N], E> {
+    let bytes = parse_hex(s).ok_or_else(|| E::custom(format!("invalid hex {s:?}")))?;
+    bytes.try_into().map_err(|_| E::custom(format!("expected {N} bytes of hex, got {s:?}")))
+}
+
+fn hex_32<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+    hex_array(&String::deserialize(deserializer)?)
+}
+
+fn hex_32s<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<[u8; 32]>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?.iter().map(|s| hex_array(s)).collect()
+}
+
+fn hex_2s<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<[u8; 2]>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?.iter().map(|s| hex_array(s)).collect()
+}
+
+fn parse_u64<E: Error>(s: &str) -> Result<u64, E> {
+    s.parse().map_err(|_| E::custom(format!("invalid u64 {s:?}")))
+}
+
+fn decimal_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    parse_u64(&String::deserialize(deserializer)?)
+}
+
+fn decimal_u64s<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u64>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?.iter().map(|s| parse_u64(s)).collect()
+}
@@ -0,0 +1,24 @@
+import { assert } from "chai";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { protocolStaticAccounts } from "../app/src/lookupTables";
+
+describe("lookup_tables", () => {
+  const programId = new PublicKey("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+  it("lists each static withdraw account once, with a verifying key per circuit version", () => {
+    const single = protocolStaticAccounts(programId, [1]);
+    const both = protocolStaticAccounts(programId, [1, 2]);
+    assert.equal(both.length, single.length + 1);
+    assert.equal(new Set(both.map((address) => address.toBase58())).size, both.length);
+
+    const [programState] = PublicKey.findProgramAddressSync([Buffer.from("program_state")], programId);
+    const [vault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("program_token_vault"), programState.toBuffer()],
+      programId
+    );
+    for (const expected of [programState, vault, TOKEN_PROGRAM_ID, SystemProgram.programId]) {
+      assert.isTrue(both.some((address) => address.equals(expected)));
+    }
+  });
+});
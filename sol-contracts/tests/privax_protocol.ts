@@ -1,6 +1,6 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
-import { Keypair, PublicKey, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { ComputeBudgetProgram, Keypair, PublicKey, SystemProgram, LAMPORTS_PER_SOL, Transaction, TransactionInstruction } from "@solana/web3.js";
 import { 
   TOKEN_PROGRAM_ID, 
   createMint, 
@@ -8,7 +8,39 @@ import {
   mintTo,
   getAssociatedTokenAddress
 } from "@solana/spl-token";
+import {
+  GovernanceConfig,
+  MintMaxVoteWeightSource,
+  Vote,
+  VoteThreshold,
+  VoteThresholdType,
+  VoteTipping,
+  VoteType,
+  YesNoVote,
+  createInstructionData,
+  getGovernanceProgramVersion,
+  getProposalTransactionAddress,
+  withCastVote,
+  withCreateGovernance,
+  withCreateNativeTreasury,
+  withCreateProposal,
+  withCreateRealm,
+  withDepositGoverningTokens,
+  withExecuteTransaction,
+  withInsertTransaction,
+  withSignOffProposal,
+} from "@solana/spl-governance";
 import { assert } from "chai";
+import { createHash } from "crypto";
+import {
+  SPL_GOVERNANCE_PROGRAM_ID,
+  addRelayerProposalIx,
+  registerVerifyingKeyProposalIx,
+  setFeeSwapProgramProposalIx,
+  updateFeeConfigProposalIx,
+  writeVerifyingKeyChunkProposalIx,
+} from "../app/src/governance";
+import { G1_SIZE, VK_FIXED_SIZE } from "../app/src/proofFormat";
 
 describe("privax_protocol", () => {
   // Configure the client to use the local cluster
@@ -21,25 +53,202 @@ describe("privax_protocol", () => {
   const admin = Keypair.generate();
   const user = Keypair.generate();
   const recipient = Keypair.generate();
+  const relayer = Keypair.generate();
   
   // Mock data for testing
   let tokenMint: PublicKey;
   let userTokenAccount: PublicKey;
   let recipientTokenAccount: PublicKey;
   let programStatePDA: PublicKey;
+  let feeConfigPDA: PublicKey;
+  let nullifierFilterPDA: PublicKey;
+  let merkleTreePDA: PublicKey;
+  let auditLogPDA: PublicKey;
   let vaultPDA: PublicKey;
   let vaultAuthority: PublicKey;
   
+  const relayerInfoPDA = (relayerAddress: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("relayer"), relayerAddress.toBuffer()],
+      program.programId
+    )[0];
+  // Nullifiers are stored in shards keyed by their first two bytes
+  const nullifierPrefix = (nullifier: anchor.BN) => nullifier.toArrayLike(Buffer, "le", 8).subarray(0, 2);
+  const nullifierShardPDA = (nullifier: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("nullifier_shard"), programStatePDA.toBuffer(), nullifierPrefix(nullifier)],
+      program.programId
+    )[0];
+  const ensureNullifierShard = async (nullifier: anchor.BN) => {
+    if ((await program.account.nullifierShard.fetchNullable(nullifierShardPDA(nullifier))) !== null) return;
+    await program.methods
+      .initNullifierShard(Array.from(nullifierPrefix(nullifier)))
+      .accounts({
+        programState: programStatePDA,
+        nullifierShard: nullifierShardPDA(nullifier),
+        payer: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+  };
+  const commitmentShardPDA = (commitment: Uint8Array) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("commitment_shard"), programStatePDA.toBuffer(), commitment.subarray(0, 2)],
+      program.programId
+    )[0];
+  const CIRCUIT_VERSION = 1;
+  const verifyingKeyPDA = (circuitVersion: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("verifying_key"), programStatePDA.toBuffer(), new anchor.BN(circuitVersion).toArrayLike(Buffer, "le", 2)],
+      program.programId
+    )[0];
+  // On-chain Groth16 layout: alpha, beta, gamma, delta, then one ic point per public input plus one
+  const mockVerifyingKey = (fill: number) => Buffer.alloc(VK_FIXED_SIZE + 9 * G1_SIZE, fill);
+  const ENDPOINT_URL_HASH = Array.from(new Uint8Array(32).fill(7));
+  const FEE_SCHEDULE = { feeBps: 30, minFee: new anchor.BN(1_000) };
+
   // Constants
   const AMOUNT = 1_000_000_000; // 1 token with 9 decimals
   const MOCK_COMMITMENT = new Uint8Array(32).fill(1); // Dummy commitment
   
   // Mock proof data for testing
   const mockProof = {
-    aProof: Buffer.from([1, 2, 3]),
-    bProof: Buffer.from([4, 5, 6]),
-    cProof: Buffer.from([7, 8, 9]),
-    publicInputs: [1, 2, 3, AMOUNT, 5] // Matching the required format in contract
+    // The all-zero proof, which only a `devnet` build accepts: run with
+    // `anchor test -- --features devnet`
+    aProof: Buffer.alloc(64),
+    bProof: Buffer.alloc(128),
+    cProof: Buffer.alloc(64),
+    // Matching the required format in contract; the recipient input is the first 8 bytes of its key (LE)
+    publicInputs: [
+      new anchor.BN(0), // Merkle root; the tree's current root, set by freshPublicInputs
+      new anchor.BN(2),
+      new anchor.BN(recipient.publicKey.toBuffer().subarray(0, 8), "le"),
+      new anchor.BN(AMOUNT),
+      new anchor.BN(0), // External nullifier; derived for this deployment by freshPublicInputs
+      new anchor.BN(0), // Proof slot; set to a recent slot right before each withdrawal
+      new anchor.BN(0), // Relayer fee; these withdrawals pay none
+      new anchor.BN(0), // Relayer; 0 lets anyone submit the proof
+    ],
+  };
+  // Mirrors external_nullifier in the program (variable denomination = 0)
+  const externalNullifier = (circuitVersion: number) => {
+    const digest = createHash("sha256")
+      .update(
+        Buffer.concat([
+          Buffer.from("privax-external-nullifier"),
+          program.programId.toBuffer(),
+          tokenMint.toBuffer(),
+          new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+          new anchor.BN(circuitVersion).toArrayLike(Buffer, "le", 2),
+        ])
+      )
+      .digest();
+    return new anchor.BN(digest.subarray(0, 8), "le");
+  };
+  // withdraw takes the root and nullifier hashes in full alongside their u64 inputs
+  const currentRoot = async () => {
+    const tree = await program.account.merkleTree.fetch(merkleTreePDA);
+    return (tree.roots as number[][])[tree.currentRootIndex.toNumber()];
+  };
+  // The mock nullifiers' full hashes: the input's bytes (LE), zero-padded to 32
+  const nullifierHash = (input: anchor.BN) => Array.from(input.toArrayLike(Buffer, "le", 32));
+  const freshPublicInputs = async (publicInputs: anchor.BN[]) => {
+    const inputs = [...publicInputs];
+    inputs[0] = new anchor.BN(Buffer.from(await currentRoot()).subarray(0, 8), "le");
+    inputs[4] = externalNullifier(CIRCUIT_VERSION);
+    inputs[5] = new anchor.BN(await provider.connection.getSlot());
+    return inputs;
+  };
+  // withdraw takes a fixed array of MAX_PUBLIC_INPUTS; unused trailing slots are zero
+  const MAX_PUBLIC_INPUTS = 12;
+  const withdrawInputs = (inputs: anchor.BN[]) =>
+    [...inputs, ...Array.from({ length: MAX_PUBLIC_INPUTS - inputs.length }, () => new anchor.BN(0))];
+
+  // Tree inserts hash with Poseidon at every level, past the default 200k compute units
+  const treeInsertBudget = () => ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 });
+
+  // Shared instruction helpers for tests that need a deposit or withdrawal as a setup step.
+  // A commitment can only be deposited once, so each deposit takes a fresh one.
+  let nextCommitment = 2;
+  const depositTokens = () => {
+    const commitment = new Uint8Array(32).fill(nextCommitment++);
+    return program.methods
+      .deposit(new anchor.BN(AMOUNT), Array.from(commitment))
+      .accounts({
+        programState: programStatePDA,
+        feeConfig: feeConfigPDA,
+        merkleTree: merkleTreePDA,
+        commitmentShard: commitmentShardPDA(commitment),
+        referrerAccount: null,
+        depositReceipt: null,
+        user: user.publicKey,
+        userTokenAccount: userTokenAccount,
+        tokenMint: tokenMint,
+        programTokenVault: vaultPDA,
+        programTokenVaultAuthority: vaultAuthority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .preInstructions([treeInsertBudget()])
+      .signers([user])
+      .rpc();
+  };
+
+  // Each helper withdrawal spends a fresh nullifier; "Withdraws tokens" uses mockProof's own
+  let nextNullifier = 100;
+  const withdrawTokens = async (
+    opts: { relayer?: Keypair; relayerFee?: number; relayerFeeTokenAccount?: PublicKey } = {}
+  ) => {
+    const signer = opts.relayer ?? user;
+    const nullifier = new anchor.BN(nextNullifier++);
+    const publicInputs = await freshPublicInputs(mockProof.publicInputs);
+    publicInputs[1] = nullifier;
+    if (opts.relayer) {
+      // The proof binds the relayer's fee and key
+      publicInputs[6] = new anchor.BN(opts.relayerFee ?? 0);
+      publicInputs[7] = new anchor.BN(opts.relayer.publicKey.toBuffer().subarray(0, 8), "le");
+    }
+    await ensureNullifierShard(nullifier);
+    return program.methods
+      .withdraw(
+        Array.from(mockProof.aProof),
+        Array.from(mockProof.bProof),
+        Array.from(mockProof.cProof),
+        withdrawInputs(publicInputs),
+        await currentRoot(),
+        nullifierHash(nullifier),
+        [],
+        recipient.publicKey,
+        new anchor.BN(AMOUNT),
+        new anchor.BN(opts.relayerFee ?? 0),
+        Buffer.alloc(0),
+        new anchor.BN(0),
+        CIRCUIT_VERSION,
+        Buffer.alloc(0),
+        null
+      )
+      .accounts({
+        programState: programStatePDA,
+        verifyingKey: verifyingKeyPDA(CIRCUIT_VERSION),
+        feeConfig: feeConfigPDA,
+        nullifierShard: nullifierShardPDA(nullifier),
+        nullifierFilter: nullifierFilterPDA,
+        merkleTree: merkleTreePDA,
+        rootArchive: null,
+        user: signer.publicKey,
+        programTokenVault: vaultPDA,
+        programTokenVaultAuthority: vaultAuthority,
+        recipientTokenAccount: recipientTokenAccount,
+        relayerInfo: opts.relayer ? relayerInfoPDA(opts.relayer.publicKey) : null,
+        relayerFeeTokenAccount: opts.relayerFeeTokenAccount ?? null,
+        feeSwapProgram: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc();
   };
 
   before(async () => {
@@ -47,6 +256,7 @@ describe("privax_protocol", () => {
     await provider.connection.requestAirdrop(admin.publicKey, 10 * LAMPORTS_PER_SOL);
     await provider.connection.requestAirdrop(user.publicKey, 10 * LAMPORTS_PER_SOL);
     await provider.connection.requestAirdrop(recipient.publicKey, 10 * LAMPORTS_PER_SOL);
+    await provider.connection.requestAirdrop(relayer.publicKey, 10 * LAMPORTS_PER_SOL);
     
     // Wait for confirmations
     await new Promise(resolve => setTimeout(resolve, 3000));
@@ -91,6 +301,23 @@ describe("privax_protocol", () => {
       program.programId
     );
     
+    [feeConfigPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_config"), programStatePDA.toBuffer()],
+      program.programId
+    );
+    [nullifierFilterPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("nullifier_filter"), programStatePDA.toBuffer()],
+      program.programId
+    );
+    [merkleTreePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("merkle_tree"), programStatePDA.toBuffer()],
+      program.programId
+    );
+    [auditLogPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("audit_log"), programStatePDA.toBuffer()],
+      program.programId
+    );
+
     [vaultPDA] = PublicKey.findProgramAddressSync(
       [Buffer.from("program_token_vault"), programStatePDA.toBuffer()],
       program.programId
@@ -109,9 +336,14 @@ describe("privax_protocol", () => {
       .initialize(tokenMint, mockVerifierProgramId)
       .accounts({
         programState: programStatePDA,
+        feeConfig: feeConfigPDA,
+        nullifierFilter: nullifierFilterPDA,
+        merkleTree: merkleTreePDA,
+        auditLog: auditLogPDA,
         admin: admin.publicKey,
         systemProgram: SystemProgram.programId,
       })
+      .preInstructions([treeInsertBudget()])
       .signers([admin])
       .rpc();
     
@@ -121,6 +353,62 @@ describe("privax_protocol", () => {
     assert.isTrue(programState.tokenMint.equals(tokenMint));
     assert.isTrue(programState.verifierProgramId.equals(mockVerifierProgramId));
     assert.equal(programState.whitelistedRelayers.length, 0);
+
+    const feeConfig = await program.account.feeConfig.fetch(feeConfigPDA);
+    assert.isTrue(feeConfig.feeAuthority.equals(admin.publicKey));
+    assert.equal(feeConfig.protocolFeeBps, 0);
+  });
+
+  it("Registers and retires verifying keys per circuit version", async () => {
+    // The second key is uploaded in two chunks and only becomes usable once complete
+    for (const circuitVersion of [CIRCUIT_VERSION, CIRCUIT_VERSION + 1]) {
+      const vkData = mockVerifyingKey(circuitVersion);
+      const firstChunk = circuitVersion === CIRCUIT_VERSION ? vkData : vkData.subarray(0, VK_FIXED_SIZE);
+      await program.methods
+        .registerVerifyingKey(circuitVersion, { groth16: {} }, 64, vkData.length, firstChunk)
+        .accounts({
+          programState: programStatePDA,
+          auditLog: auditLogPDA,
+          verifyingKey: verifyingKeyPDA(circuitVersion),
+          admin: admin.publicKey,
+          payer: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+    }
+
+    const partial = await program.account.verifyingKey.fetch(verifyingKeyPDA(CIRCUIT_VERSION + 1));
+    assert.equal(partial.written, VK_FIXED_SIZE);
+    assert.equal(partial.enabled, 0);
+    await program.methods
+      .writeVerifyingKeyChunk(mockVerifyingKey(CIRCUIT_VERSION + 1).subarray(VK_FIXED_SIZE))
+      .accounts({
+        programState: programStatePDA,
+        auditLog: auditLogPDA,
+        verifyingKey: verifyingKeyPDA(CIRCUIT_VERSION + 1),
+        admin: admin.publicKey,
+      })
+      .signers([admin])
+      .rpc();
+    const uploaded = await program.account.verifyingKey.fetch(verifyingKeyPDA(CIRCUIT_VERSION + 1));
+    assert.equal(uploaded.enabled, 1);
+
+    await program.methods
+      .setVerifyingKeyEnabled(false)
+      .accounts({
+        programState: programStatePDA,
+        auditLog: auditLogPDA,
+        verifyingKey: verifyingKeyPDA(CIRCUIT_VERSION + 1),
+        admin: admin.publicKey,
+      })
+      .signers([admin])
+      .rpc();
+
+    const current = await program.account.verifyingKey.fetch(verifyingKeyPDA(CIRCUIT_VERSION));
+    assert.equal(current.enabled, 1);
+    const retired = await program.account.verifyingKey.fetch(verifyingKeyPDA(CIRCUIT_VERSION + 1));
+    assert.equal(retired.enabled, 0);
   });
 
   it("Adds and removes a relayer", async () => {
@@ -128,10 +416,14 @@ describe("privax_protocol", () => {
     
     // Add relayer
     await program.methods
-      .addRelayer(relayer)
+      .addRelayer(relayer, ENDPOINT_URL_HASH, FEE_SCHEDULE, null)
       .accounts({
         programState: programStatePDA,
+        auditLog: auditLogPDA,
+        relayerInfo: relayerInfoPDA(relayer),
         admin: admin.publicKey,
+        payer: admin.publicKey,
+        systemProgram: SystemProgram.programId,
       })
       .signers([admin])
       .rpc();
@@ -139,60 +431,131 @@ describe("privax_protocol", () => {
     let programState = await program.account.programState.fetch(programStatePDA);
     assert.equal(programState.whitelistedRelayers.length, 1);
     assert.isTrue(programState.whitelistedRelayers[0].equals(relayer));
+
+    const relayerInfo = await program.account.relayerInfo.fetch(relayerInfoPDA(relayer));
+    assert.deepEqual(relayerInfo.endpointUrlHash, ENDPOINT_URL_HASH);
+    assert.equal(relayerInfo.feeSchedule.feeBps, FEE_SCHEDULE.feeBps);
+    assert.isNull(relayerInfo.expirySlot);
+
+    // Renew with an expiry slot, then clear it again
+    const expirySlot = new anchor.BN((await provider.connection.getSlot()) + 1_000);
+    await program.methods
+      .renewRelayer(relayer, expirySlot)
+      .accounts({
+        programState: programStatePDA,
+        auditLog: auditLogPDA,
+        relayerInfo: relayerInfoPDA(relayer),
+        admin: admin.publicKey,
+      })
+      .signers([admin])
+      .rpc();
+    const renewed = await program.account.relayerInfo.fetch(relayerInfoPDA(relayer));
+    assert.isTrue(renewed.expirySlot.eq(expirySlot));
     
     // Remove relayer
     await program.methods
       .removeRelayer(relayer)
       .accounts({
         programState: programStatePDA,
+        auditLog: auditLogPDA,
+        relayerInfo: relayerInfoPDA(relayer),
         admin: admin.publicKey,
+        rentReceiver: admin.publicKey,
       })
       .signers([admin])
       .rpc();
     
     programState = await program.account.programState.fetch(programStatePDA);
     assert.equal(programState.whitelistedRelayers.length, 0);
+    assert.isNull(await program.account.relayerInfo.fetchNullable(relayerInfoPDA(relayer)));
   });
 
   it("Deposits tokens", async () => {
+    // First deposit takes leaf index 0; ask for a receipt
+    const [receiptPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("receipt"), programStatePDA.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
     await program.methods
       .deposit(new anchor.BN(AMOUNT), Array.from(MOCK_COMMITMENT))
       .accounts({
         programState: programStatePDA,
+        feeConfig: feeConfigPDA,
+        merkleTree: merkleTreePDA,
+        commitmentShard: commitmentShardPDA(MOCK_COMMITMENT),
+        referrerAccount: null,
+        depositReceipt: receiptPDA,
         user: user.publicKey,
         userTokenAccount: userTokenAccount,
+        tokenMint: tokenMint,
         programTokenVault: vaultPDA,
         programTokenVaultAuthority: vaultAuthority,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
       })
+      .preInstructions([treeInsertBudget()])
       .signers([user])
       .rpc();
 
     // Verify tokens were transferred to vault
     const vaultBalance = await provider.connection.getTokenAccountBalance(vaultPDA);
     assert.equal(vaultBalance.value.uiAmount, AMOUNT / 1_000_000_000); // Convert to UI amount
+
+    const receipt = await program.account.depositReceipt.fetch(receiptPDA);
+    assert.isTrue(receipt.depositor.equals(user.publicKey));
+    assert.equal(receipt.leafIndex.toNumber(), 0);
+    assert.equal(receipt.amount.toNumber(), AMOUNT);
+    const tree = await program.account.merkleTree.fetch(merkleTreePDA);
+    assert.equal(tree.nextIndex.toNumber(), 1);
+
+    await program.methods
+      .closeDepositReceipt()
+      .accounts({ depositReceipt: receiptPDA, depositor: user.publicKey })
+      .signers([user])
+      .rpc();
+    assert.isNull(await program.account.depositReceipt.fetchNullable(receiptPDA));
   });
 
   // Note: In a real test, you would need to generate a valid ZK proof
   it("Withdraws tokens", async () => {
+    await ensureNullifierShard(mockProof.publicInputs[1]);
     await program.methods
       .withdraw(
         Array.from(mockProof.aProof),
         Array.from(mockProof.bProof),
         Array.from(mockProof.cProof),
-        mockProof.publicInputs,
+        withdrawInputs(await freshPublicInputs(mockProof.publicInputs)),
+        await currentRoot(),
+        nullifierHash(mockProof.publicInputs[1]),
+        [],
         recipient.publicKey,
-        new anchor.BN(AMOUNT)
+        new anchor.BN(AMOUNT),
+        new anchor.BN(0),
+        Buffer.alloc(0),
+        new anchor.BN(0),
+        CIRCUIT_VERSION,
+        Buffer.alloc(0),
+        null
       )
       .accounts({
         programState: programStatePDA,
+        verifyingKey: verifyingKeyPDA(CIRCUIT_VERSION),
+        feeConfig: feeConfigPDA,
+        nullifierShard: nullifierShardPDA(mockProof.publicInputs[1]),
+        nullifierFilter: nullifierFilterPDA,
+        merkleTree: merkleTreePDA,
+        rootArchive: null,
         user: user.publicKey,
         programTokenVault: vaultPDA,
         programTokenVaultAuthority: vaultAuthority,
         recipientTokenAccount: recipientTokenAccount,
+        relayerInfo: null,
+        relayerFeeTokenAccount: null,
+        feeSwapProgram: null,
         tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
       })
       .signers([user])
       .rpc();
@@ -200,6 +563,390 @@ describe("privax_protocol", () => {
     // Verify tokens were transferred to recipient
     const recipientBalance = await provider.connection.getTokenAccountBalance(recipientTokenAccount);
     assert.equal(recipientBalance.value.uiAmount, AMOUNT / 1_000_000_000); // Convert to UI amount
+
+    // The nullifier is recorded both authoritatively in its shard and in the bloom filter
+    const shard = await program.account.nullifierShard.fetch(nullifierShardPDA(mockProof.publicInputs[1]));
+    assert.equal(shard.count, 1);
+    const filter = await program.account.nullifierFilter.fetch(nullifierFilterPDA);
+    assert.equal(filter.inserted.toNumber(), 1);
+  });
+
+  it("Rejects a proof bound to a slot in the future", async () => {
+    const publicInputs = await freshPublicInputs(mockProof.publicInputs);
+    publicInputs[1] = new anchor.BN(3);
+    publicInputs[5] = publicInputs[5].addn(1_000_000);
+    await ensureNullifierShard(publicInputs[1]);
+    try {
+      await program.methods
+        .withdraw(
+          Array.from(mockProof.aProof),
+          Array.from(mockProof.bProof),
+          Array.from(mockProof.cProof),
+          withdrawInputs(publicInputs),
+          await currentRoot(),
+          nullifierHash(publicInputs[1]),
+          [],
+          recipient.publicKey,
+          new anchor.BN(AMOUNT),
+          new anchor.BN(0),
+          Buffer.alloc(0),
+          new anchor.BN(0),
+          CIRCUIT_VERSION,
+          Buffer.alloc(0),
+          null
+        )
+        .accounts({
+          programState: programStatePDA,
+          verifyingKey: verifyingKeyPDA(CIRCUIT_VERSION),
+          feeConfig: feeConfigPDA,
+          nullifierShard: nullifierShardPDA(publicInputs[1]),
+          nullifierFilter: nullifierFilterPDA,
+          merkleTree: merkleTreePDA,
+          rootArchive: null,
+          user: user.publicKey,
+          programTokenVault: vaultPDA,
+          programTokenVaultAuthority: vaultAuthority,
+          recipientTokenAccount: recipientTokenAccount,
+          relayerInfo: null,
+          relayerFeeTokenAccount: null,
+          feeSwapProgram: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      assert.fail("withdraw should have failed");
+    } catch (err) {
+      assert.include(err.toString(), "ProofExpired");
+    }
+  });
+
+  it("Spends two notes in one proof and commits the change", async () => {
+    await depositTokens();
+    const changeCommitment = Buffer.alloc(32, 9);
+    const first = new anchor.BN(nextNullifier++);
+    const second = new anchor.BN(nextNullifier++);
+    const publicInputs = await freshPublicInputs(mockProof.publicInputs);
+    publicInputs[1] = first;
+    publicInputs.push(second, new anchor.BN(changeCommitment.subarray(0, 8), "le"));
+    await ensureNullifierShard(first);
+    await ensureNullifierShard(second);
+    const treeBefore = await program.account.merkleTree.fetch(merkleTreePDA);
+    const filterBefore = await program.account.nullifierFilter.fetch(nullifierFilterPDA);
+
+    await program.methods
+      .withdraw(
+        Array.from(mockProof.aProof),
+        Array.from(mockProof.bProof),
+        Array.from(mockProof.cProof),
+        withdrawInputs(publicInputs),
+        await currentRoot(),
+        nullifierHash(first),
+        [nullifierHash(second)],
+        recipient.publicKey,
+        new anchor.BN(AMOUNT),
+        new anchor.BN(0),
+        Buffer.alloc(0),
+        new anchor.BN(0),
+        CIRCUIT_VERSION,
+        Buffer.alloc(0),
+        Array.from(changeCommitment)
+      )
+      .accounts({
+        programState: programStatePDA,
+        verifyingKey: verifyingKeyPDA(CIRCUIT_VERSION),
+        feeConfig: feeConfigPDA,
+        nullifierShard: nullifierShardPDA(first),
+        nullifierFilter: nullifierFilterPDA,
+        merkleTree: merkleTreePDA,
+        rootArchive: null,
+        user: user.publicKey,
+        programTokenVault: vaultPDA,
+        programTokenVaultAuthority: vaultAuthority,
+        recipientTokenAccount: recipientTokenAccount,
+        relayerInfo: null,
+        relayerFeeTokenAccount: null,
+        feeSwapProgram: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts([{ pubkey: nullifierShardPDA(second), isWritable: true, isSigner: false }])
+      .signers([user])
+      .rpc();
+
+    const tree = await program.account.merkleTree.fetch(merkleTreePDA);
+    assert.equal(tree.nextIndex.toNumber(), treeBefore.nextIndex.toNumber() + 1);
+    const filter = await program.account.nullifierFilter.fetch(nullifierFilterPDA);
+    assert.equal(filter.inserted.toNumber(), filterBefore.inserted.toNumber() + 2);
+  });
+
+  it("Pays the relayer fee in the pool mint", async () => {
+    const RELAYER_FEE = AMOUNT / 100;
+    const relayerTokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      relayer,
+      tokenMint,
+      relayer.publicKey
+    );
+
+    await program.methods
+      .addRelayer(relayer.publicKey, ENDPOINT_URL_HASH, FEE_SCHEDULE, null)
+      .accounts({
+        programState: programStatePDA,
+        auditLog: auditLogPDA,
+        relayerInfo: relayerInfoPDA(relayer.publicKey),
+        admin: admin.publicKey,
+        payer: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    await depositTokens();
+    const recipientBefore = await provider.connection.getTokenAccountBalance(recipientTokenAccount);
+    await withdrawTokens({ relayer, relayerFee: RELAYER_FEE, relayerFeeTokenAccount: relayerTokenAccount });
+
+    const recipientAfter = await provider.connection.getTokenAccountBalance(recipientTokenAccount);
+    const relayerBalance = await provider.connection.getTokenAccountBalance(relayerTokenAccount);
+    assert.equal(
+      Number(recipientAfter.value.amount) - Number(recipientBefore.value.amount),
+      AMOUNT - RELAYER_FEE
+    );
+    assert.equal(Number(relayerBalance.value.amount), RELAYER_FEE);
+  });
+
+  it("Distributes accrued protocol fees by weight", async () => {
+    const daoTokenAccount = await createAssociatedTokenAccount(provider.connection, admin, tokenMint, admin.publicKey);
+    const devFund = Keypair.generate();
+    const devTokenAccount = await createAssociatedTokenAccount(provider.connection, admin, tokenMint, devFund.publicKey);
+
+    await program.methods
+      .updateFeeConfig(100, new anchor.BN(AMOUNT)) // 1%
+      .accounts({ programState: programStatePDA, feeConfig: feeConfigPDA, auditLog: auditLogPDA, feeAuthority: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .setFeeBeneficiaries([
+        { tokenAccount: daoTokenAccount, weight: 3 },
+        { tokenAccount: devTokenAccount, weight: 1 },
+      ])
+      .accounts({ programState: programStatePDA, feeConfig: feeConfigPDA, auditLog: auditLogPDA, feeAuthority: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+    await depositTokens();
+    await withdrawTokens();
+    const protocolFee = AMOUNT / 100;
+    let feeConfig = await program.account.feeConfig.fetch(feeConfigPDA);
+    assert.equal(feeConfig.accruedProtocolFees.toNumber(), protocolFee);
+
+    await program.methods
+      .distributeFees()
+      .accounts({
+        programState: programStatePDA,
+        feeConfig: feeConfigPDA,
+        programTokenVault: vaultPDA,
+        programTokenVaultAuthority: vaultAuthority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: daoTokenAccount, isSigner: false, isWritable: true },
+        { pubkey: devTokenAccount, isSigner: false, isWritable: true },
+      ])
+      .rpc();
+
+    const daoBalance = await provider.connection.getTokenAccountBalance(daoTokenAccount);
+    const devBalance = await provider.connection.getTokenAccountBalance(devTokenAccount);
+    assert.equal(Number(daoBalance.value.amount), (protocolFee * 3) / 4);
+    assert.equal(Number(devBalance.value.amount), protocolFee / 4);
+    feeConfig = await program.account.feeConfig.fetch(feeConfigPDA);
+    assert.equal(feeConfig.accruedProtocolFees.toNumber(), 0);
+
+    // Later tests assume a fee-free pool
+    await program.methods
+      .updateFeeConfig(0, new anchor.BN(0))
+      .accounts({ programState: programStatePDA, feeConfig: feeConfigPDA, auditLog: auditLogPDA, feeAuthority: admin.publicKey })
+      .signers([admin])
+      .rpc();
+  });
+
+  it("Lets the guardian pause and only the admin unpause", async () => {
+    const guardian = Keypair.generate();
+
+    await program.methods
+      .setGuardian(guardian.publicKey)
+      .accounts({ programState: programStatePDA, auditLog: auditLogPDA, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+    await program.methods
+      .pause()
+      .accounts({ programState: programStatePDA, auditLog: auditLogPDA, authority: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+    let programState = await program.account.programState.fetch(programStatePDA);
+    assert.isTrue(programState.paused);
+
+    try {
+      await depositTokens();
+      assert.fail("deposit should fail while paused");
+    } catch (err) {
+      assert.include(err.toString(), "PoolPaused");
+    }
+
+    await program.methods
+      .unpause()
+      .accounts({ programState: programStatePDA, auditLog: auditLogPDA, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    programState = await program.account.programState.fetch(programStatePDA);
+    assert.isFalse(programState.paused);
+  });
+
+  it("Runs relayer, fee and verifying key changes through Realms proposals", async () => {
+    // A realm whose single member holds the whole community supply, so a proposal passes on its
+    // one vote (early tipping) and executes right away (no hold-up time)
+    const connection = provider.connection;
+    const governanceProgram = SPL_GOVERNANCE_PROGRAM_ID;
+    const programVersion = await getGovernanceProgramVersion(connection, governanceProgram);
+    const communityMint = await createMint(connection, admin, admin.publicKey, null, 0);
+    const memberTokenAccount = await createAssociatedTokenAccount(connection, admin, communityMint, admin.publicKey);
+    await mintTo(connection, admin, communityMint, memberTokenAccount, admin.publicKey, 1);
+    const send = (instructions: TransactionInstruction[]) =>
+      provider.sendAndConfirm(new Transaction().add(...instructions), [admin]);
+
+    let instructions: TransactionInstruction[] = [];
+    const realm = await withCreateRealm(
+      instructions, governanceProgram, programVersion, "privax", admin.publicKey, communityMint, admin.publicKey,
+      undefined, MintMaxVoteWeightSource.FULL_SUPPLY_FRACTION, new anchor.BN(1)
+    );
+    const tokenOwnerRecord = await withDepositGoverningTokens(
+      instructions, governanceProgram, programVersion, realm, memberTokenAccount, communityMint, admin.publicKey,
+      admin.publicKey, admin.publicKey, new anchor.BN(1)
+    );
+    await send(instructions);
+
+    const disabled = new VoteThreshold({ type: VoteThresholdType.Disabled });
+    const config = new GovernanceConfig({
+      communityVoteThreshold: new VoteThreshold({ type: VoteThresholdType.YesVotePercentage, value: 60 }),
+      minCommunityTokensToCreateProposal: new anchor.BN(1),
+      minInstructionHoldUpTime: 0,
+      baseVotingTime: 3600,
+      communityVoteTipping: VoteTipping.Early,
+      minCouncilTokensToCreateProposal: new anchor.BN(1),
+      councilVoteThreshold: disabled,
+      councilVetoVoteThreshold: disabled,
+      communityVetoVoteThreshold: disabled,
+      councilVoteTipping: VoteTipping.Disabled,
+      votingCoolOffTime: 0,
+      depositExemptProposalCount: 10,
+    });
+    instructions = [];
+    const governance = await withCreateGovernance(
+      instructions, governanceProgram, programVersion, realm, undefined, config, tokenOwnerRecord, admin.publicKey,
+      admin.publicKey
+    );
+    const treasury = await withCreateNativeTreasury(instructions, governanceProgram, programVersion, governance, admin.publicKey);
+    await send(instructions);
+    // The treasury signs as admin and fee authority, and pays the rent of accounts proposals create
+    await send([SystemProgram.transfer({ fromPubkey: admin.publicKey, toPubkey: treasury, lamports: LAMPORTS_PER_SOL })]);
+
+    // Each instruction goes in a proposal transaction of its own, executed in order
+    let proposalIndex = 0;
+    const executeProposal = async (proposalInstructions: TransactionInstruction[]) => {
+      let instructions: TransactionInstruction[] = [];
+      const proposal = await withCreateProposal(
+        instructions, governanceProgram, programVersion, realm, governance, tokenOwnerRecord, `Proposal ${proposalIndex}`,
+        "", communityMint, admin.publicKey, proposalIndex++, VoteType.SINGLE_CHOICE, ["Approve"], true, admin.publicKey
+      );
+      await send(instructions);
+      const transactions = proposalInstructions.map((instruction) => [createInstructionData(instruction)]);
+      for (const [index, instructionData] of transactions.entries()) {
+        instructions = [];
+        await withInsertTransaction(
+          instructions, governanceProgram, programVersion, governance, proposal, tokenOwnerRecord, admin.publicKey,
+          index, 0, 0, instructionData, admin.publicKey
+        );
+        await send(instructions);
+      }
+      instructions = [];
+      withSignOffProposal(
+        instructions, governanceProgram, programVersion, realm, governance, proposal, admin.publicKey, undefined,
+        tokenOwnerRecord
+      );
+      await withCastVote(
+        instructions, governanceProgram, programVersion, realm, governance, proposal, tokenOwnerRecord, tokenOwnerRecord,
+        admin.publicKey, communityMint, Vote.fromYesNoVote(YesNoVote.Yes), admin.publicKey
+      );
+      await send(instructions);
+      for (const [index, instructionData] of transactions.entries()) {
+        const transaction = await getProposalTransactionAddress(governanceProgram, programVersion, proposal, 0, index);
+        instructions = [];
+        await withExecuteTransaction(
+          instructions, governanceProgram, programVersion, governance, proposal, transaction, instructionData
+        );
+        await send(instructions);
+      }
+    };
+
+    // Hand both roles to the treasury
+    await program.methods
+      .transferOwnership(treasury)
+      .accounts({ programState: programStatePDA, auditLog: auditLogPDA, admin: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .setFeeAuthority(treasury)
+      .accounts({ programState: programStatePDA, feeConfig: feeConfigPDA, auditLog: auditLogPDA, feeAuthority: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+    const daoRelayer = Keypair.generate().publicKey;
+    await executeProposal([
+      await addRelayerProposalIx(
+        program, governance, daoRelayer, Buffer.from(ENDPOINT_URL_HASH), { feeBps: 30, minFee: 1_000 }, null,
+        governanceProgram, treasury
+      ),
+    ]);
+    const programState = await program.account.programState.fetch(programStatePDA);
+    assert.isTrue(programState.whitelistedRelayers.some((relayer: PublicKey) => relayer.equals(daoRelayer)));
+
+    const feeSwapProgram = Keypair.generate().publicKey;
+    await executeProposal([
+      await updateFeeConfigProposalIx(program, governance, 25, AMOUNT, treasury),
+      await setFeeSwapProgramProposalIx(program, governance, feeSwapProgram, treasury),
+    ]);
+    const feeConfig = await program.account.feeConfig.fetch(feeConfigPDA);
+    assert.equal(feeConfig.protocolFeeBps, 25);
+    assert.isTrue(feeConfig.feeSwapProgram.equals(feeSwapProgram));
+
+    // A verifying key upgrade, too large for one proposal transaction
+    const circuitVersion = CIRCUIT_VERSION + 2;
+    const vkData = mockVerifyingKey(circuitVersion);
+    await executeProposal([
+      await registerVerifyingKeyProposalIx(
+        program, governance, circuitVersion, "groth16", 64, vkData.length, vkData.subarray(0, VK_FIXED_SIZE),
+        governanceProgram, treasury
+      ),
+      await writeVerifyingKeyChunkProposalIx(program, governance, circuitVersion, vkData.subarray(VK_FIXED_SIZE), treasury),
+    ]);
+    const upgraded = await program.account.verifyingKey.fetch(verifyingKeyPDA(circuitVersion));
+    assert.equal(upgraded.written, vkData.length);
+    assert.equal(upgraded.enabled, 1);
+
+    // And back to the admin key, with the fee-free pool later tests assume
+    await executeProposal([
+      await updateFeeConfigProposalIx(program, governance, 0, 0, treasury),
+      await program.methods
+        .setFeeAuthority(admin.publicKey)
+        .accounts({ programState: programStatePDA, feeConfig: feeConfigPDA, auditLog: auditLogPDA, feeAuthority: treasury })
+        .instruction(),
+      await program.methods
+        .transferOwnership(admin.publicKey)
+        .accounts({ programState: programStatePDA, auditLog: auditLogPDA, admin: treasury })
+        .instruction(),
+    ]);
+    assert.isTrue((await program.account.programState.fetch(programStatePDA)).admin.equals(admin.publicKey));
   });
 
   it("Transfers ownership", async () => {
@@ -209,6 +956,7 @@ describe("privax_protocol", () => {
       .transferOwnership(newAdmin)
       .accounts({
         programState: programStatePDA,
+        auditLog: auditLogPDA,
         admin: admin.publicKey,
       })
       .signers([admin])
@@ -216,5 +964,11 @@ describe("privax_protocol", () => {
     
     const programState = await program.account.programState.fetch(programStatePDA);
     assert.isTrue(programState.admin.equals(newAdmin));
+
+    // Recorded in the audit log, which keeps the latest entry at (count - 1) % its length
+    const auditLog = await program.account.auditLog.fetch(auditLogPDA);
+    const latest = auditLog.entries[(auditLog.count.toNumber() - 1) % auditLog.entries.length];
+    assert.isTrue(latest.actor.equals(admin.publicKey));
+    assert.deepEqual(latest.newValueHash, Array.from(createHash("sha256").update(newAdmin.toBuffer()).digest()));
   });
 }); 
\ No newline at end of file
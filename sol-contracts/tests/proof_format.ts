@@ -0,0 +1,100 @@
+import { assert } from "chai";
+import BN from "bn.js";
+import {
+  G1_SIZE,
+  G2_SIZE,
+  VK_FIXED_SIZE,
+  proofFromArkworks,
+  proofToArkworks,
+  proofFromSnarkjs,
+  vkFromArkworks,
+  vkToArkworks,
+  vkFromSnarkjs,
+  PLONK_PROOF_SIZE,
+  PLONK_VK_SIZE,
+  plonkProofFromSnarkjs,
+  plonkVkFromSnarkjs,
+} from "../app/src/proofFormat";
+
+describe("proof_format", () => {
+  // Deterministic bytes that stay below the BN254 modulus and clear of arkworks flag bits
+  const fieldBytes = (seed: number, count: number) =>
+    Buffer.from(Array.from({ length: count }, (_, i) => (seed + i * 7) % 0x30));
+
+  it("round-trips an arkworks proof through the on-chain layout", () => {
+    const arkworks = fieldBytes(1, 2 * G1_SIZE + G2_SIZE);
+    const onChain = proofFromArkworks(arkworks);
+    assert.equal(onChain.aProof.length, G1_SIZE);
+    assert.equal(onChain.bProof.length, G2_SIZE);
+    assert.equal(onChain.cProof.length, G1_SIZE);
+    assert.isTrue(proofToArkworks(onChain).equals(arkworks));
+  });
+
+  it("round-trips an arkworks verifying key through the on-chain layout", () => {
+    const icCount = 7;
+    const count = Buffer.alloc(8);
+    count.writeBigUInt64LE(BigInt(icCount));
+    const arkworks = Buffer.concat([
+      fieldBytes(3, VK_FIXED_SIZE),
+      count,
+      fieldBytes(5, icCount * G1_SIZE),
+    ]);
+    const onChain = vkFromArkworks(arkworks);
+    assert.equal(onChain.length, VK_FIXED_SIZE + icCount * G1_SIZE);
+    assert.isTrue(vkToArkworks(onChain).equals(arkworks));
+  });
+
+  it("encodes snarkjs points big-endian with G2 as c1 before c0", () => {
+    const proof = proofFromSnarkjs({
+      pi_a: ["1", "2", "1"],
+      pi_b: [["3", "4"], ["5", "6"], ["1", "0"]],
+      pi_c: ["7", "8", "1"],
+    });
+    const word = (n: number) => new BN(n).toArrayLike(Buffer, "be", 32);
+    assert.isTrue(proof.aProof.equals(Buffer.concat([word(1), word(2)])));
+    assert.isTrue(proof.bProof.equals(Buffer.concat([word(4), word(3), word(6), word(5)])));
+    assert.isTrue(proof.cProof.equals(Buffer.concat([word(7), word(8)])));
+  });
+
+  it("agrees between snarkjs and arkworks encodings of the same key", () => {
+    const g1 = ["9", "10", "1"];
+    const g2 = [["11", "12"], ["13", "14"], ["1", "0"]];
+    const fromSnarkjs = vkFromSnarkjs({
+      vk_alpha_1: g1,
+      vk_beta_2: g2,
+      vk_gamma_2: g2,
+      vk_delta_2: g2,
+      IC: [g1, g1],
+    });
+    assert.isTrue(vkFromArkworks(vkToArkworks(fromSnarkjs)).equals(fromSnarkjs));
+  });
+
+  it("lays out snarkjs PLONK keys and proofs as the program reads them", () => {
+    const word = (n: number) => new BN(n).toArrayLike(Buffer, "be", 32);
+    const g1 = ["1", "2", "1"];
+    const infinity = ["0", "1", "0"];
+    const vk = plonkVkFromSnarkjs({
+      power: 4,
+      nPublic: 9,
+      k1: "2",
+      k2: "3",
+      w: "5",
+      Qm: g1, Ql: g1, Qr: infinity, Qo: g1, Qc: infinity, S1: g1, S2: g1, S3: g1,
+      X_2: [["11", "12"], ["13", "14"], ["1", "0"]],
+    });
+    assert.equal(vk.length, PLONK_VK_SIZE);
+    assert.deepEqual([vk[0], vk[1]], [4, 9]);
+    assert.isTrue(vk.subarray(2, 98).equals(Buffer.concat([word(2), word(3), word(5)])));
+    assert.isTrue(vk.subarray(98 + 2 * G1_SIZE, 98 + 3 * G1_SIZE).equals(Buffer.alloc(G1_SIZE)));
+    assert.isTrue(vk.subarray(PLONK_VK_SIZE - G2_SIZE).equals(Buffer.concat([word(12), word(11), word(14), word(13)])));
+
+    const proof = plonkProofFromSnarkjs({
+      A: g1, B: g1, C: g1, Z: g1, T1: g1, T2: g1, T3: infinity, Wxi: g1, Wxiw: g1,
+      eval_a: "21", eval_b: "22", eval_c: "23", eval_s1: "24", eval_s2: "25", eval_zw: "26",
+    });
+    assert.equal(proof.length, PLONK_PROOF_SIZE);
+    assert.isTrue(proof.subarray(0, G1_SIZE).equals(Buffer.concat([word(1), word(2)])));
+    assert.isTrue(proof.subarray(6 * G1_SIZE, 7 * G1_SIZE).equals(Buffer.alloc(G1_SIZE)));
+    assert.isTrue(proof.subarray(PLONK_PROOF_SIZE - 32).equals(word(26)));
+  });
+});